@@ -1,12 +1,6 @@
 use derive_builder::Builder;
 
-/// Protocol role
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[allow(missing_docs)]
-pub enum Role {
-    Leader,
-    Follower,
-}
+use crate::Role;
 
 /// Configuration for AES-GCM.
 #[derive(Debug, Clone, Builder)]
@@ -1,8 +1,8 @@
 //! Mock implementation of AES-GCM for testing purposes.
 
-use block_cipher::{BlockCipherConfig, MpcBlockCipher};
+use block_cipher::{BlockCipherCircuit, BlockCipherConfig, MpcBlockCipher};
 use mpz_garble::{Decode, DecodePrivate, Execute, Memory, Prove, Verify, Vm};
-use tlsn_stream_cipher::{MpcStreamCipher, StreamCipherConfig};
+use tlsn_stream_cipher::{CtrCircuit, MpcStreamCipher, StreamCipherConfig};
 use tlsn_universal_hash::ghash::{mock_ghash_pair, GhashConfig};
 use utils_aio::duplex::MemoryDuplex;
 
@@ -17,26 +17,28 @@ use super::*;
 /// * `follower_vm` - The VM of the follower.
 /// * `leader_config` - The configuration of the leader.
 /// * `follower_config` - The configuration of the follower.
-pub async fn create_mock_aes_gcm_pair<T>(
+pub async fn create_mock_aes_gcm_pair<C, B, T>(
     id: &str,
     leader_vm: &mut T,
     follower_vm: &mut T,
     leader_config: AesGcmConfig,
     follower_config: AesGcmConfig,
-) -> (MpcAesGcm, MpcAesGcm)
+) -> (MpcAesGcm<C, B>, MpcAesGcm<C, B>)
 where
+    C: CtrCircuit,
+    B: BlockCipherCircuit,
     T: Vm + Send,
     <T as Vm>::Thread: Memory + Execute + Decode + DecodePrivate + Prove + Verify + Send + Sync,
 {
     let block_cipher_id = format!("{}/block_cipher", id);
-    let leader_block_cipher = MpcBlockCipher::new(
+    let leader_block_cipher = MpcBlockCipher::<B, _>::new(
         BlockCipherConfig::builder()
             .id(block_cipher_id.clone())
             .build()
             .unwrap(),
         leader_vm.new_thread(&block_cipher_id).await.unwrap(),
     );
-    let follower_block_cipher = MpcBlockCipher::new(
+    let follower_block_cipher = MpcBlockCipher::<B, _>::new(
         BlockCipherConfig::builder()
             .id(block_cipher_id.clone())
             .build()
@@ -45,7 +47,7 @@ where
     );
 
     let stream_cipher_id = format!("{}/stream_cipher", id);
-    let leader_stream_cipher = MpcStreamCipher::new(
+    let leader_stream_cipher = MpcStreamCipher::<C, _>::new(
         StreamCipherConfig::builder()
             .id(stream_cipher_id.clone())
             .build()
@@ -55,7 +57,7 @@ where
             .await
             .unwrap(),
     );
-    let follower_stream_cipher = MpcStreamCipher::new(
+    let follower_stream_cipher = MpcStreamCipher::<C, _>::new(
         StreamCipherConfig::builder()
             .id(stream_cipher_id.clone())
             .build()
@@ -5,7 +5,8 @@ mod config;
 pub mod mock;
 mod tag;
 
-pub use config::{AesGcmConfig, AesGcmConfigBuilder, AesGcmConfigBuilderError, Role};
+pub use crate::Role;
+pub use config::{AesGcmConfig, AesGcmConfigBuilder, AesGcmConfigBuilderError};
 
 use crate::{
     msg::{AeadMessage, TagShare},
@@ -15,10 +16,10 @@ use crate::{
 use async_trait::async_trait;
 use futures::{SinkExt, StreamExt, TryFutureExt};
 
-use block_cipher::{Aes128, BlockCipher};
+use block_cipher::{Aes128, BlockCipher, BlockCipherCircuit};
 use mpz_core::commit::HashCommit;
 use mpz_garble::value::ValueRef;
-use tlsn_stream_cipher::{Aes128Ctr, StreamCipher};
+use tlsn_stream_cipher::{Aes128Ctr, CtrCircuit, StreamCipher};
 use tlsn_universal_hash::UniversalHash;
 use utils_aio::expect_msg_or_err;
 
@@ -26,15 +27,21 @@ pub(crate) use tag::AesGcmTagShare;
 use tag::{build_ghash_data, AES_GCM_TAG_LEN};
 
 /// An implementation of 2PC AES-GCM.
-pub struct MpcAesGcm {
+///
+/// The counter-mode circuit `C` determines how the IV and the explicit nonce
+/// are combined into the counter block, see [`Aes128Ctr`] (TLS 1.2) and
+/// [`Aes128CtrTls13`](tlsn_stream_cipher::Aes128CtrTls13) (TLS 1.3). The block cipher
+/// circuit `B`, which is used to compute the GHASH key, must use the same key length, e.g.
+/// [`Aes256`](block_cipher::Aes256) with [`Aes256Ctr`](tlsn_stream_cipher::Aes256Ctr).
+pub struct MpcAesGcm<C: CtrCircuit = Aes128Ctr, B: BlockCipherCircuit = Aes128> {
     config: AesGcmConfig,
     channel: AeadChannel,
-    aes_block: Box<dyn BlockCipher<Aes128>>,
-    aes_ctr: Box<dyn StreamCipher<Aes128Ctr>>,
+    aes_block: Box<dyn BlockCipher<B>>,
+    aes_ctr: Box<dyn StreamCipher<C>>,
     ghash: Box<dyn UniversalHash>,
 }
 
-impl std::fmt::Debug for MpcAesGcm {
+impl<C: CtrCircuit, B: BlockCipherCircuit> std::fmt::Debug for MpcAesGcm<C, B> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("MpcAesGcm")
             .field("config", &self.config)
@@ -46,7 +53,7 @@ impl std::fmt::Debug for MpcAesGcm {
     }
 }
 
-impl MpcAesGcm {
+impl<C: CtrCircuit, B: BlockCipherCircuit> MpcAesGcm<C, B> {
     /// Creates a new instance of [`MpcAesGcm`].
     #[cfg_attr(
         feature = "tracing",
@@ -55,8 +62,8 @@ impl MpcAesGcm {
     pub fn new(
         config: AesGcmConfig,
         channel: AeadChannel,
-        aes_block: Box<dyn BlockCipher<Aes128>>,
-        aes_ctr: Box<dyn StreamCipher<Aes128Ctr>>,
+        aes_block: Box<dyn BlockCipher<B>>,
+        aes_ctr: Box<dyn StreamCipher<C>>,
         ghash: Box<dyn UniversalHash>,
     ) -> Self {
         Self {
@@ -187,7 +194,7 @@ impl MpcAesGcm {
 }
 
 #[async_trait]
-impl Aead for MpcAesGcm {
+impl<C: CtrCircuit, B: BlockCipherCircuit> Aead for MpcAesGcm<C, B> {
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", err))]
     async fn set_key(&mut self, key: ValueRef, iv: ValueRef) -> Result<(), AeadError> {
         self.aes_block.set_key(key.clone());
@@ -221,6 +228,10 @@ impl Aead for MpcAesGcm {
         self.aes_ctr.set_transcript_id(id)
     }
 
+    fn set_transcript_trailer(&mut self, id: &str, len: usize) {
+        self.aes_ctr.set_transcript_trailer(id, len)
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "trace", skip(plaintext), err)
@@ -431,6 +442,8 @@ impl Aead for MpcAesGcm {
 mod tests {
     use super::{mock::create_mock_aes_gcm_pair, *};
     use crate::Aead;
+    use block_cipher::Aes256;
+    use tlsn_stream_cipher::{Aes128CtrTls13, Aes256Ctr};
 
     use mpz_garble::{
         protocol::deap::mock::{create_mock_deap_vm, MockFollower, MockLeader},
@@ -439,7 +452,7 @@ mod tests {
 
     use ::aes_gcm::{
         aead::{AeadInPlace, KeyInit},
-        Aes128Gcm, Nonce,
+        Aes128Gcm, Aes256Gcm, Nonce,
     };
 
     fn reference_impl(
@@ -461,10 +474,32 @@ mod tests {
         ciphertext
     }
 
-    async fn setup_pair(
+    fn reference_impl_aes256(
+        key: &[u8],
+        iv: &[u8],
+        explicit_nonce: &[u8],
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Vec<u8> {
+        let cipher = Aes256Gcm::new_from_slice(key).unwrap();
+        let nonce = [iv, explicit_nonce].concat();
+        let nonce = Nonce::from_slice(nonce.as_slice());
+
+        let mut ciphertext = plaintext.to_vec();
+        cipher
+            .encrypt_in_place(nonce, aad, &mut ciphertext)
+            .unwrap();
+
+        ciphertext
+    }
+
+    async fn setup_pair<C: CtrCircuit, B: BlockCipherCircuit>(
         key: Vec<u8>,
         iv: Vec<u8>,
-    ) -> ((MpcAesGcm, MpcAesGcm), (MockLeader, MockFollower)) {
+    ) -> (
+        (MpcAesGcm<C, B>, MpcAesGcm<C, B>),
+        (MockLeader, MockFollower),
+    ) {
         let (mut leader_vm, mut follower_vm) = create_mock_deap_vm("test_vm").await;
 
         let leader_thread = leader_vm.new_thread("test_thread").await.unwrap();
@@ -527,7 +562,7 @@ mod tests {
         let aad = vec![2u8; 12];
 
         let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
-            setup_pair(key.clone(), iv.clone()).await;
+            setup_pair::<Aes128Ctr, Aes128>(key.clone(), iv.clone()).await;
 
         let (leader_ciphertext, follower_ciphertext) = tokio::try_join!(
             leader.encrypt_private(explicit_nonce.clone(), plaintext.clone(), aad.clone(),),
@@ -542,6 +577,30 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_aes_256_gcm_encrypt_private() {
+        let key = vec![3u8; 32];
+        let iv = vec![0u8; 4];
+        let explicit_nonce = vec![0u8; 8];
+        let plaintext = vec![1u8; 32];
+        let aad = vec![2u8; 12];
+
+        let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
+            setup_pair::<Aes256Ctr, Aes256>(key.clone(), iv.clone()).await;
+
+        let (leader_ciphertext, follower_ciphertext) = tokio::try_join!(
+            leader.encrypt_private(explicit_nonce.clone(), plaintext.clone(), aad.clone()),
+            follower.encrypt_blind(explicit_nonce.clone(), plaintext.len(), aad.clone())
+        )
+        .unwrap();
+
+        assert_eq!(leader_ciphertext, follower_ciphertext);
+        assert_eq!(
+            leader_ciphertext,
+            reference_impl_aes256(&key, &iv, &explicit_nonce, &plaintext, &aad)
+        );
+    }
+
     #[tokio::test]
     async fn test_aes_gcm_encrypt_public() {
         let key = vec![0u8; 16];
@@ -551,7 +610,7 @@ mod tests {
         let aad = vec![2u8; 12];
 
         let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
-            setup_pair(key.clone(), iv.clone()).await;
+            setup_pair::<Aes128Ctr, Aes128>(key.clone(), iv.clone()).await;
 
         let (leader_ciphertext, follower_ciphertext) = tokio::try_join!(
             leader.encrypt_public(explicit_nonce.clone(), plaintext.clone(), aad.clone(),),
@@ -576,7 +635,7 @@ mod tests {
         let ciphertext = reference_impl(&key, &iv, &explicit_nonce, &plaintext, &aad);
 
         let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
-            setup_pair(key.clone(), iv.clone()).await;
+            setup_pair::<Aes128Ctr, Aes128>(key.clone(), iv.clone()).await;
 
         let (leader_plaintext, _) = tokio::try_join!(
             leader.decrypt_private(explicit_nonce.clone(), ciphertext.clone(), aad.clone(),),
@@ -603,7 +662,7 @@ mod tests {
         corrupted[len - 1] -= 1;
 
         let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
-            setup_pair(key.clone(), iv.clone()).await;
+            setup_pair::<Aes128Ctr, Aes128>(key.clone(), iv.clone()).await;
 
         // leader receives corrupted tag
         let err = tokio::try_join!(
@@ -614,7 +673,7 @@ mod tests {
         assert!(matches!(err, AeadError::CorruptedTag));
 
         let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
-            setup_pair(key.clone(), iv.clone()).await;
+            setup_pair::<Aes128Ctr, Aes128>(key.clone(), iv.clone()).await;
 
         // follower receives corrupted tag
         let err = tokio::try_join!(
@@ -635,7 +694,7 @@ mod tests {
         let ciphertext = reference_impl(&key, &iv, &explicit_nonce, &plaintext, &aad);
 
         let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
-            setup_pair(key.clone(), iv.clone()).await;
+            setup_pair::<Aes128Ctr, Aes128>(key.clone(), iv.clone()).await;
 
         let (leader_plaintext, follower_plaintext) = tokio::try_join!(
             leader.decrypt_public(explicit_nonce.clone(), ciphertext.clone(), aad.clone(),),
@@ -663,7 +722,7 @@ mod tests {
         corrupted[len - 1] -= 1;
 
         let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
-            setup_pair(key.clone(), iv.clone()).await;
+            setup_pair::<Aes128Ctr, Aes128>(key.clone(), iv.clone()).await;
 
         // leader receives corrupted tag
         let err = tokio::try_join!(
@@ -674,7 +733,7 @@ mod tests {
         assert!(matches!(err, AeadError::CorruptedTag));
 
         let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
-            setup_pair(key.clone(), iv.clone()).await;
+            setup_pair::<Aes128Ctr, Aes128>(key.clone(), iv.clone()).await;
 
         // follower receives corrupted tag
         let err = tokio::try_join!(
@@ -697,7 +756,7 @@ mod tests {
         let len = ciphertext.len();
 
         let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
-            setup_pair(key.clone(), iv.clone()).await;
+            setup_pair::<Aes128Ctr, Aes128>(key.clone(), iv.clone()).await;
 
         tokio::try_join!(
             leader.verify_tag(explicit_nonce.clone(), ciphertext.clone(), aad.clone()),
@@ -717,4 +776,91 @@ mod tests {
         assert!(matches!(leader_res.unwrap_err(), AeadError::CorruptedTag));
         assert!(matches!(follower_res.unwrap_err(), AeadError::CorruptedTag));
     }
+
+    fn reference_impl_tls13(
+        key: &[u8],
+        iv: &[u8],
+        seq: u64,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Vec<u8> {
+        let mut nonce = iv.to_vec();
+        nonce[4..]
+            .iter_mut()
+            .zip(seq.to_be_bytes())
+            .for_each(|(nonce, seq)| *nonce ^= seq);
+
+        reference_impl(key, &nonce[..4], &nonce[4..], plaintext, aad)
+    }
+
+    #[tokio::test]
+    async fn test_aes_gcm_tls13_rekey() {
+        let key = vec![0u8; 16];
+        let iv = vec![1u8; 12];
+        let plaintext = vec![1u8; 32];
+        let aad = vec![2u8; 5];
+
+        let ((mut leader, mut follower), (mut leader_vm, mut follower_vm)) =
+            setup_pair::<Aes128CtrTls13, Aes128>(key.clone(), iv.clone()).await;
+
+        let seq = 1u64;
+        let (leader_ciphertext, follower_ciphertext) = tokio::try_join!(
+            leader.encrypt_private(seq.to_be_bytes().to_vec(), plaintext.clone(), aad.clone()),
+            follower.encrypt_blind(seq.to_be_bytes().to_vec(), plaintext.len(), aad.clone())
+        )
+        .unwrap();
+
+        assert_eq!(leader_ciphertext, follower_ciphertext);
+        assert_eq!(
+            leader_ciphertext,
+            reference_impl_tls13(&key, &iv, seq, &plaintext, &aad)
+        );
+
+        // Switch to new traffic keys, as is done after the TLS 1.3 handshake.
+        let new_key = vec![3u8; 16];
+        let new_iv = vec![4u8; 12];
+
+        let leader_thread = leader_vm.new_thread("rekey").await.unwrap();
+        let leader_key = leader_thread
+            .new_public_array_input::<u8>("key", new_key.len())
+            .unwrap();
+        let leader_iv = leader_thread
+            .new_public_array_input::<u8>("iv", new_iv.len())
+            .unwrap();
+        leader_thread.assign(&leader_key, new_key.clone()).unwrap();
+        leader_thread.assign(&leader_iv, new_iv.clone()).unwrap();
+
+        let follower_thread = follower_vm.new_thread("rekey").await.unwrap();
+        let follower_key = follower_thread
+            .new_public_array_input::<u8>("key", new_key.len())
+            .unwrap();
+        let follower_iv = follower_thread
+            .new_public_array_input::<u8>("iv", new_iv.len())
+            .unwrap();
+        follower_thread
+            .assign(&follower_key, new_key.clone())
+            .unwrap();
+        follower_thread
+            .assign(&follower_iv, new_iv.clone())
+            .unwrap();
+
+        futures::try_join!(
+            leader.set_key(leader_key, leader_iv),
+            follower.set_key(follower_key, follower_iv)
+        )
+        .unwrap();
+
+        let seq = 0u64;
+        let (leader_ciphertext, follower_ciphertext) = tokio::try_join!(
+            leader.encrypt_private(seq.to_be_bytes().to_vec(), plaintext.clone(), aad.clone()),
+            follower.encrypt_blind(seq.to_be_bytes().to_vec(), plaintext.len(), aad.clone())
+        )
+        .unwrap();
+
+        assert_eq!(leader_ciphertext, follower_ciphertext);
+        assert_eq!(
+            leader_ciphertext,
+            reference_impl_tls13(&new_key, &new_iv, seq, &plaintext, &aad)
+        );
+    }
 }
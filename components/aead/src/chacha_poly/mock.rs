@@ -0,0 +1,83 @@
+//! Mock implementation of ChaCha20-Poly1305 for testing purposes.
+
+use mpz_garble::{Decode, DecodePrivate, Execute, Memory, Prove, Verify, Vm};
+use tlsn_stream_cipher::{CtrCircuit, MpcStreamCipher, StreamCipherConfig};
+use tlsn_universal_hash::poly1305::{mock_poly1305_pair, Poly1305Config};
+use utils_aio::duplex::MemoryDuplex;
+
+use super::*;
+
+/// Creates a mock ChaCha20-Poly1305 pair.
+///
+/// # Arguments
+///
+/// * `id` - The id of the ChaCha20-Poly1305 instances.
+/// * `leader_vm` - The VM of the leader.
+/// * `follower_vm` - The VM of the follower.
+/// * `leader_config` - The configuration of the leader.
+/// * `follower_config` - The configuration of the follower.
+pub async fn create_mock_chacha_poly_pair<C, T>(
+    id: &str,
+    leader_vm: &mut T,
+    follower_vm: &mut T,
+    leader_config: ChaChaPolyConfig,
+    follower_config: ChaChaPolyConfig,
+) -> (MpcChaChaPoly<C>, MpcChaChaPoly<C>)
+where
+    C: CtrCircuit,
+    T: Vm + Send,
+    <T as Vm>::Thread: Memory + Execute + Decode + DecodePrivate + Prove + Verify + Send + Sync,
+{
+    let stream_cipher_id = format!("{}/stream_cipher", id);
+    let leader_stream_cipher = MpcStreamCipher::<C, _>::new(
+        StreamCipherConfig::builder()
+            .id(stream_cipher_id.clone())
+            .start_ctr(1)
+            .build()
+            .unwrap(),
+        leader_vm
+            .new_thread_pool(&stream_cipher_id, 4)
+            .await
+            .unwrap(),
+    );
+    let follower_stream_cipher = MpcStreamCipher::<C, _>::new(
+        StreamCipherConfig::builder()
+            .id(stream_cipher_id.clone())
+            .start_ctr(1)
+            .build()
+            .unwrap(),
+        follower_vm
+            .new_thread_pool(&stream_cipher_id, 4)
+            .await
+            .unwrap(),
+    );
+
+    let (leader_poly1305, follower_poly1305) = mock_poly1305_pair(
+        Poly1305Config::builder()
+            .id(format!("{}/poly1305", id))
+            .build()
+            .unwrap(),
+        Poly1305Config::builder()
+            .id(format!("{}/poly1305", id))
+            .build()
+            .unwrap(),
+    );
+
+    let (leader_channel, follower_channel) = MemoryDuplex::new();
+
+    let leader = MpcChaChaPoly::new(
+        leader_config,
+        Box::new(leader_channel),
+        Box::new(leader_stream_cipher),
+        Box::new(leader_poly1305),
+    );
+
+    let follower = MpcChaChaPoly::new(
+        follower_config,
+        Box::new(follower_channel),
+        Box::new(follower_stream_cipher),
+        Box::new(follower_poly1305),
+    );
+
+    (leader, follower)
+}
@@ -0,0 +1,634 @@
+//! This module provides an implementation of 2PC ChaCha20-Poly1305, as specified in
+//! [RFC 8439](https://www.rfc-editor.org/rfc/rfc8439).
+//!
+//! The one-time Poly1305 key is derived from the first keystream block of each message, of which
+//! both parties receive XOR shares. The Poly1305 accumulator is computed in 2PC, see
+//! [`Poly1305`](tlsn_universal_hash::poly1305::Poly1305), after which the shares are combined
+//! into the tag depending on the operation:
+//!
+//! - **Encryption**: The leader sends its tag share to the follower, who computes the tag and
+//!   returns it to the leader. The leader never learns the Poly1305 key, so it can not create
+//!   a valid tag for a different ciphertext.
+//! - **Decryption**: The ciphertext and the tag are already fixed, so both parties exchange their
+//!   tag shares using a commit-and-reveal scheme and verify the tag. The key is never used again.
+
+mod config;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod tag;
+
+pub use crate::Role;
+pub use config::{ChaChaPolyConfig, ChaChaPolyConfigBuilder, ChaChaPolyConfigBuilderError};
+
+use crate::{
+    msg::{AeadMessage, TagShare},
+    Aead, AeadChannel, AeadError,
+};
+
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt, TryFutureExt};
+
+use mpz_core::commit::HashCommit;
+use mpz_garble::value::ValueRef;
+use tlsn_stream_cipher::{ChaCha20Ctr, CtrCircuit, StreamCipher};
+use tlsn_universal_hash::UniversalHash;
+use utils_aio::expect_msg_or_err;
+
+pub(crate) use tag::ChaChaPolyTagShare;
+use tag::{build_poly1305_data, build_tag_share, CHACHA_POLY_TAG_LEN};
+
+/// An implementation of 2PC ChaCha20-Poly1305.
+///
+/// The stream cipher must be configured to start at block counter 1, as the first
+/// keystream block is used to derive the Poly1305 key.
+pub struct MpcChaChaPoly<C: CtrCircuit = ChaCha20Ctr> {
+    config: ChaChaPolyConfig,
+    channel: AeadChannel,
+    chacha: Box<dyn StreamCipher<C>>,
+    poly1305: Box<dyn UniversalHash>,
+}
+
+impl<C: CtrCircuit> std::fmt::Debug for MpcChaChaPoly<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MpcChaChaPoly")
+            .field("config", &self.config)
+            .field("channel", &"AeadChannel {{ ... }}")
+            .field("chacha", &"StreamCipher {{ ... }}")
+            .field("poly1305", &"UniversalHash {{ ... }}")
+            .finish()
+    }
+}
+
+impl<C: CtrCircuit> MpcChaChaPoly<C> {
+    /// Creates a new instance of [`MpcChaChaPoly`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", skip(channel, chacha, poly1305), ret)
+    )]
+    pub fn new(
+        config: ChaChaPolyConfig,
+        channel: AeadChannel,
+        chacha: Box<dyn StreamCipher<C>>,
+        poly1305: Box<dyn UniversalHash>,
+    ) -> Self {
+        Self {
+            config,
+            channel,
+            chacha,
+            poly1305,
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", err, ret))]
+    async fn compute_tag_share(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        aad: Vec<u8>,
+        ciphertext: Vec<u8>,
+    ) -> Result<ChaChaPolyTagShare, AeadError> {
+        // The Poly1305 key (r, s) is the first 32 bytes of keystream block 0.
+        let key_block_share = self.chacha.share_keystream_block(explicit_nonce, 0).await?;
+
+        self.poly1305
+            .set_key(key_block_share[..16].to_vec())
+            .await?;
+
+        let acc_share = self
+            .poly1305
+            .finalize(build_poly1305_data(aad, ciphertext))
+            .await?;
+
+        Ok(build_tag_share(&acc_share, &key_block_share))
+    }
+
+    /// Computes the tag of a ciphertext which is being encrypted.
+    ///
+    /// Only the follower learns the Poly1305 key.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", err, ret))]
+    async fn compute_tag(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        let tag_share = self
+            .compute_tag_share(explicit_nonce, aad, ciphertext)
+            .await?;
+
+        let tag = match self.config.role() {
+            Role::Leader => {
+                // Send tag share to follower
+                self.channel
+                    .send(AeadMessage::TagShare(tag_share.into()))
+                    .await?;
+
+                // Expect tag from follower
+                let tag = expect_msg_or_err!(self.channel, AeadMessage::Tag)?;
+
+                if tag.len() != CHACHA_POLY_TAG_LEN {
+                    return Err(AeadError::ValidationError(format!(
+                        "Received tag is not {CHACHA_POLY_TAG_LEN} bytes long"
+                    )));
+                }
+
+                tag
+            }
+            Role::Follower => {
+                // Expect tag share from leader
+                let msg = expect_msg_or_err!(self.channel, AeadMessage::TagShare)?;
+
+                let other_tag_share = ChaChaPolyTagShare::from_unchecked(&msg.share)?;
+
+                let tag = tag_share + other_tag_share;
+
+                // Send tag to leader
+                self.channel.send(AeadMessage::Tag(tag.clone())).await?;
+
+                tag
+            }
+        };
+
+        Ok(tag)
+    }
+
+    /// Splits off the tag from the end of the payload and verifies it.
+    ///
+    /// The payload is fixed at this point, so the Poly1305 key is revealed to both parties.
+    async fn _verify_tag(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        payload: &mut Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<(), AeadError> {
+        if payload.len() < CHACHA_POLY_TAG_LEN {
+            return Err(AeadError::ValidationError(
+                "Payload is shorter than the tag".to_string(),
+            ));
+        }
+
+        let purported_tag = payload.split_off(payload.len() - CHACHA_POLY_TAG_LEN);
+
+        let tag_share = self
+            .compute_tag_share(explicit_nonce, aad, payload.clone())
+            .await?;
+
+        let tag = match self.config.role() {
+            Role::Leader => {
+                // Send commitment of tag share to follower
+                let (tag_share_decommitment, tag_share_commitment) =
+                    TagShare::from(tag_share).hash_commit();
+
+                self.channel
+                    .send(AeadMessage::TagShareCommitment(tag_share_commitment))
+                    .await?;
+
+                // Expect tag share from follower
+                let msg = expect_msg_or_err!(self.channel, AeadMessage::TagShare)?;
+
+                let other_tag_share = ChaChaPolyTagShare::from_unchecked(&msg.share)?;
+
+                // Send decommitment (tag share) to follower
+                self.channel
+                    .send(AeadMessage::TagShareDecommitment(tag_share_decommitment))
+                    .await?;
+
+                tag_share + other_tag_share
+            }
+            Role::Follower => {
+                // Wait for commitment from leader
+                let commitment = expect_msg_or_err!(self.channel, AeadMessage::TagShareCommitment)?;
+
+                // Send tag share to leader
+                self.channel
+                    .send(AeadMessage::TagShare(tag_share.into()))
+                    .await?;
+
+                // Expect decommitment (tag share) from leader
+                let decommitment =
+                    expect_msg_or_err!(self.channel, AeadMessage::TagShareDecommitment)?;
+
+                // Verify decommitment
+                decommitment.verify(&commitment).map_err(|_| {
+                    AeadError::ValidationError(
+                        "Leader tag share commitment verification failed".to_string(),
+                    )
+                })?;
+
+                let other_tag_share =
+                    ChaChaPolyTagShare::from_unchecked(&decommitment.into_inner().share)?;
+
+                tag_share + other_tag_share
+            }
+        };
+
+        // Reject if tag is incorrect.
+        if tag != purported_tag {
+            return Err(AeadError::CorruptedTag);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<C: CtrCircuit> Aead for MpcChaChaPoly<C> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", err))]
+    async fn set_key(&mut self, key: ValueRef, iv: ValueRef) -> Result<(), AeadError> {
+        // The Poly1305 key is derived for each message, see `compute_tag_share`.
+        self.chacha.set_key(key, iv);
+
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", err))]
+    async fn decode_key_private(&mut self) -> Result<(), AeadError> {
+        self.chacha
+            .decode_key_private()
+            .await
+            .map_err(AeadError::from)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", err))]
+    async fn decode_key_blind(&mut self) -> Result<(), AeadError> {
+        self.chacha
+            .decode_key_blind()
+            .await
+            .map_err(AeadError::from)
+    }
+
+    fn set_transcript_id(&mut self, id: &str) {
+        self.chacha.set_transcript_id(id)
+    }
+
+    fn set_transcript_trailer(&mut self, id: &str, len: usize) {
+        self.chacha.set_transcript_trailer(id, len)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(plaintext), err)
+    )]
+    async fn encrypt_public(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        plaintext: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        let ciphertext = self
+            .chacha
+            .encrypt_public(explicit_nonce.clone(), plaintext)
+            .await?;
+
+        let tag = self
+            .compute_tag(explicit_nonce, ciphertext.clone(), aad)
+            .await?;
+
+        let mut payload = ciphertext;
+        payload.extend(tag);
+
+        Ok(payload)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(plaintext), err)
+    )]
+    async fn encrypt_private(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        plaintext: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        let ciphertext = self
+            .chacha
+            .encrypt_private(explicit_nonce.clone(), plaintext)
+            .await?;
+
+        let tag = self
+            .compute_tag(explicit_nonce, ciphertext.clone(), aad)
+            .await?;
+
+        let mut payload = ciphertext;
+        payload.extend(tag);
+
+        Ok(payload)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", err))]
+    async fn encrypt_blind(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        plaintext_len: usize,
+        aad: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        let ciphertext = self
+            .chacha
+            .encrypt_blind(explicit_nonce.clone(), plaintext_len)
+            .await?;
+
+        let tag = self
+            .compute_tag(explicit_nonce, ciphertext.clone(), aad)
+            .await?;
+
+        let mut payload = ciphertext;
+        payload.extend(tag);
+
+        Ok(payload)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(payload), err)
+    )]
+    async fn decrypt_public(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        mut payload: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        self._verify_tag(explicit_nonce.clone(), &mut payload, aad)
+            .await?;
+
+        self.chacha
+            .decrypt_public(explicit_nonce, payload)
+            .map_err(AeadError::from)
+            .await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(payload), err)
+    )]
+    async fn decrypt_private(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        mut payload: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        self._verify_tag(explicit_nonce.clone(), &mut payload, aad)
+            .await?;
+
+        self.chacha
+            .decrypt_private(explicit_nonce, payload)
+            .map_err(AeadError::from)
+            .await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(payload), err)
+    )]
+    async fn decrypt_blind(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        mut payload: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<(), AeadError> {
+        self._verify_tag(explicit_nonce.clone(), &mut payload, aad)
+            .await?;
+
+        self.chacha
+            .decrypt_blind(explicit_nonce, payload)
+            .map_err(AeadError::from)
+            .await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(payload), err)
+    )]
+    async fn verify_tag(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        mut payload: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<(), AeadError> {
+        self._verify_tag(explicit_nonce.clone(), &mut payload, aad)
+            .await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(payload), err)
+    )]
+    async fn prove_plaintext(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        mut payload: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        self._verify_tag(explicit_nonce.clone(), &mut payload, aad)
+            .await?;
+
+        self.prove_plaintext_no_tag(explicit_nonce, payload).await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(ciphertext), err)
+    )]
+    async fn prove_plaintext_no_tag(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        self.chacha
+            .prove_plaintext(explicit_nonce, ciphertext)
+            .map_err(AeadError::from)
+            .await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(payload), err)
+    )]
+    async fn verify_plaintext(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        mut payload: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<(), AeadError> {
+        self._verify_tag(explicit_nonce.clone(), &mut payload, aad)
+            .await?;
+
+        self.verify_plaintext_no_tag(explicit_nonce, payload).await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(ciphertext), err)
+    )]
+    async fn verify_plaintext_no_tag(
+        &mut self,
+        explicit_nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+    ) -> Result<(), AeadError> {
+        self.chacha
+            .verify_plaintext(explicit_nonce, ciphertext)
+            .map_err(AeadError::from)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mock::create_mock_chacha_poly_pair, *};
+    use crate::Aead;
+
+    use mpz_garble::{
+        protocol::deap::mock::{create_mock_deap_vm, MockFollower, MockLeader},
+        Memory, Vm,
+    };
+
+    use ::chacha20poly1305::{
+        aead::{AeadInPlace, KeyInit},
+        ChaCha20Poly1305, Nonce,
+    };
+
+    fn reference_impl(key: &[u8], iv: &[u8], seq: u64, plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+        let cipher = ChaCha20Poly1305::new_from_slice(key).unwrap();
+        let mut nonce = iv.to_vec();
+        nonce[4..]
+            .iter_mut()
+            .zip(seq.to_be_bytes())
+            .for_each(|(nonce, seq)| *nonce ^= seq);
+        let nonce = Nonce::from_slice(nonce.as_slice());
+
+        let mut ciphertext = plaintext.to_vec();
+        cipher
+            .encrypt_in_place(nonce, aad, &mut ciphertext)
+            .unwrap();
+
+        ciphertext
+    }
+
+    async fn setup_pair(
+        key: Vec<u8>,
+        iv: Vec<u8>,
+    ) -> ((MpcChaChaPoly, MpcChaChaPoly), (MockLeader, MockFollower)) {
+        let (mut leader_vm, mut follower_vm) = create_mock_deap_vm("test_vm").await;
+
+        let leader_thread = leader_vm.new_thread("test_thread").await.unwrap();
+        let leader_key = leader_thread
+            .new_public_array_input::<u8>("key", key.len())
+            .unwrap();
+        let leader_iv = leader_thread
+            .new_public_array_input::<u8>("iv", iv.len())
+            .unwrap();
+
+        leader_thread.assign(&leader_key, key.clone()).unwrap();
+        leader_thread.assign(&leader_iv, iv.clone()).unwrap();
+
+        let follower_thread = follower_vm.new_thread("test_thread").await.unwrap();
+        let follower_key = follower_thread
+            .new_public_array_input::<u8>("key", key.len())
+            .unwrap();
+        let follower_iv = follower_thread
+            .new_public_array_input::<u8>("iv", iv.len())
+            .unwrap();
+
+        follower_thread.assign(&follower_key, key.clone()).unwrap();
+        follower_thread.assign(&follower_iv, iv.clone()).unwrap();
+
+        let leader_config = ChaChaPolyConfig::builder()
+            .id("test")
+            .role(Role::Leader)
+            .build()
+            .unwrap();
+        let follower_config = ChaChaPolyConfig::builder()
+            .id("test")
+            .role(Role::Follower)
+            .build()
+            .unwrap();
+
+        let (mut leader, mut follower) = create_mock_chacha_poly_pair(
+            "test",
+            &mut leader_vm,
+            &mut follower_vm,
+            leader_config,
+            follower_config,
+        )
+        .await;
+
+        futures::try_join!(
+            leader.set_key(leader_key, leader_iv),
+            follower.set_key(follower_key, follower_iv)
+        )
+        .unwrap();
+
+        ((leader, follower), (leader_vm, follower_vm))
+    }
+
+    #[tokio::test]
+    async fn test_chacha_poly_encrypt_private() {
+        let key = vec![0u8; 32];
+        let iv = vec![1u8; 12];
+        let seq = 1u64;
+        let plaintext = vec![1u8; 32];
+        let aad = vec![2u8; 13];
+
+        let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
+            setup_pair(key.clone(), iv.clone()).await;
+
+        let (leader_ciphertext, follower_ciphertext) = tokio::try_join!(
+            leader.encrypt_private(seq.to_be_bytes().to_vec(), plaintext.clone(), aad.clone()),
+            follower.encrypt_blind(seq.to_be_bytes().to_vec(), plaintext.len(), aad.clone())
+        )
+        .unwrap();
+
+        assert_eq!(leader_ciphertext, follower_ciphertext);
+        assert_eq!(
+            leader_ciphertext,
+            reference_impl(&key, &iv, seq, &plaintext, &aad)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chacha_poly_decrypt_public() {
+        let key = vec![0u8; 32];
+        let iv = vec![1u8; 12];
+        let seq = 1u64;
+        let plaintext = vec![1u8; 32];
+        let aad = vec![2u8; 13];
+        let ciphertext = reference_impl(&key, &iv, seq, &plaintext, &aad);
+
+        let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
+            setup_pair(key.clone(), iv.clone()).await;
+
+        let (leader_plaintext, follower_plaintext) = tokio::try_join!(
+            leader.decrypt_public(seq.to_be_bytes().to_vec(), ciphertext.clone(), aad.clone()),
+            follower.decrypt_public(seq.to_be_bytes().to_vec(), ciphertext, aad.clone())
+        )
+        .unwrap();
+
+        assert_eq!(leader_plaintext, plaintext);
+        assert_eq!(leader_plaintext, follower_plaintext);
+    }
+
+    #[tokio::test]
+    async fn test_chacha_poly_decrypt_private_bad_tag() {
+        let key = vec![0u8; 32];
+        let iv = vec![1u8; 12];
+        let seq = 1u64;
+        let plaintext = vec![1u8; 32];
+        let aad = vec![2u8; 13];
+        let ciphertext = reference_impl(&key, &iv, seq, &plaintext, &aad);
+
+        let len = ciphertext.len();
+
+        // corrupt tag
+        let mut corrupted = ciphertext.clone();
+        corrupted[len - 1] ^= 1;
+
+        let ((mut leader, mut follower), (_leader_vm, _follower_vm)) =
+            setup_pair(key.clone(), iv.clone()).await;
+
+        let (leader_res, follower_res) = tokio::join!(
+            leader.decrypt_private(seq.to_be_bytes().to_vec(), corrupted.clone(), aad.clone()),
+            follower.decrypt_blind(seq.to_be_bytes().to_vec(), corrupted, aad.clone())
+        );
+
+        assert!(matches!(leader_res.unwrap_err(), AeadError::CorruptedTag));
+        assert!(matches!(follower_res.unwrap_err(), AeadError::CorruptedTag));
+    }
+}
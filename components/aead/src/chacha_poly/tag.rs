@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use std::ops::Add;
+
+use tlsn_universal_hash::poly1305::P1305;
+
+use crate::AeadError;
+
+pub(crate) const CHACHA_POLY_TAG_LEN: usize = 16;
+
+/// The length of an additive share of the Poly1305 accumulator.
+const ACC_SHARE_LEN: usize = 17;
+
+/// A share of a Poly1305 tag.
+///
+/// Consists of an additive share of the accumulator over the prime field and
+/// an XOR share of `s`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct ChaChaPolyTagShare {
+    acc: [u8; ACC_SHARE_LEN],
+    s: [u8; 16],
+}
+
+impl ChaChaPolyTagShare {
+    pub(crate) fn from_unchecked(share: &[u8]) -> Result<Self, AeadError> {
+        if share.len() != ACC_SHARE_LEN + 16 {
+            return Err(AeadError::ValidationError(format!(
+                "Received tag share is not {} bytes long",
+                ACC_SHARE_LEN + 16
+            )));
+        }
+
+        let (acc, s) = share.split_at(ACC_SHARE_LEN);
+
+        Ok(Self {
+            acc: acc.try_into().unwrap(),
+            s: s.try_into().unwrap(),
+        })
+    }
+
+    pub(crate) fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = self.acc.to_vec();
+        bytes.extend_from_slice(&self.s);
+        bytes
+    }
+}
+
+impl Add for ChaChaPolyTagShare {
+    type Output = Vec<u8>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let acc = P1305::from_le_bytes(&self.acc) + P1305::from_le_bytes(&rhs.acc);
+        let s = u128::from_le_bytes(self.s) ^ u128::from_le_bytes(rhs.s);
+
+        // tag = (acc + s) mod 2^128
+        acc.low_u128().wrapping_add(s).to_le_bytes().to_vec()
+    }
+}
+
+/// Computes the tag share from the Poly1305 accumulator share and the share of the
+/// first keystream block.
+pub(crate) fn build_tag_share(acc_share: &[u8], key_block_share: &[u8]) -> ChaChaPolyTagShare {
+    ChaChaPolyTagShare {
+        acc: acc_share.try_into().expect("accumulator share is 17 bytes"),
+        s: key_block_share[16..32]
+            .try_into()
+            .expect("key block is at least 32 bytes"),
+    }
+}
+
+/// Builds padded data for Poly1305, as specified in RFC 8439, Section 2.8.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", ret))]
+pub(crate) fn build_poly1305_data(mut aad: Vec<u8>, mut ciphertext: Vec<u8>) -> Vec<u8> {
+    let aad_len = aad.len() as u64;
+    let ciphertext_len = ciphertext.len() as u64;
+
+    // pad data to be a multiple of 16 bytes
+    let aad_padded_block_count = (aad.len() / 16) + (aad.len() % 16 != 0) as usize;
+    aad.resize(aad_padded_block_count * 16, 0);
+
+    let ciphertext_padded_block_count =
+        (ciphertext.len() / 16) + (ciphertext.len() % 16 != 0) as usize;
+    ciphertext.resize(ciphertext_padded_block_count * 16, 0);
+
+    let mut data: Vec<u8> = Vec::with_capacity(aad.len() + ciphertext.len() + 16);
+    data.extend(aad);
+    data.extend(ciphertext);
+    data.extend_from_slice(&aad_len.to_le_bytes());
+    data.extend_from_slice(&ciphertext_len.to_le_bytes());
+
+    data
+}
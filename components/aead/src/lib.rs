@@ -13,6 +13,7 @@
 #![forbid(unsafe_code)]
 
 pub mod aes_gcm;
+pub mod chacha_poly;
 pub mod msg;
 
 pub use msg::AeadMessage;
@@ -25,6 +26,14 @@ use utils_aio::duplex::Duplex;
 /// A channel for sending and receiving AEAD messages.
 pub type AeadChannel = Box<dyn Duplex<AeadMessage>>;
 
+/// Protocol role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Role {
+    Leader,
+    Follower,
+}
+
 /// An error that can occur during AEAD operations.
 #[derive(Debug, thiserror::Error)]
 #[allow(missing_docs)]
@@ -70,6 +79,12 @@ pub trait Aead: Send {
     /// The state of a transcript counter is preserved between calls to `set_transcript_id`.
     fn set_transcript_id(&mut self, id: &str);
 
+    /// Sets the transcript id of the trailing bytes of each message.
+    ///
+    /// If set, the last `len` bytes of each plaintext are assigned identifiers from the
+    /// transcript `id` instead of the current transcript. Setting `len` to 0 disables the trailer.
+    fn set_transcript_trailer(&mut self, id: &str, len: usize);
+
     /// Encrypts a plaintext message, returning the ciphertext and tag.
     ///
     /// The plaintext is provided by both parties.
@@ -11,6 +11,7 @@ pub enum AeadMessage {
     TagShareCommitment(Hash),
     TagShareDecommitment(Decommitment<TagShare>),
     TagShare(TagShare),
+    Tag(Vec<u8>),
 }
 
 /// A tag share.
@@ -27,3 +28,11 @@ impl From<crate::aes_gcm::AesGcmTagShare> for TagShare {
         }
     }
 }
+
+impl From<crate::chacha_poly::ChaChaPolyTagShare> for TagShare {
+    fn from(tag_share: crate::chacha_poly::ChaChaPolyTagShare) -> Self {
+        Self {
+            share: tag_share.to_bytes(),
+        }
+    }
+}
@@ -1,9 +1,10 @@
-use std::sync::Arc;
+use std::{cell::RefCell, sync::Arc};
 
 use mpz_circuits::{
     circuits::AES128,
-    types::{StaticValueType, Value},
-    Circuit,
+    once_cell::sync::Lazy,
+    types::{StaticValueType, Value, U8},
+    BuilderState, Circuit, CircuitBuilder, Tracer,
 };
 
 /// A block cipher circuit.
@@ -37,3 +38,259 @@ impl BlockCipherCircuit for Aes128 {
         AES128.clone()
     }
 }
+
+/// Aes256 block cipher circuit.
+#[derive(Default, Debug, Clone)]
+pub struct Aes256;
+
+impl BlockCipherCircuit for Aes256 {
+    type KEY = [u8; 32];
+    type BLOCK = [u8; 16];
+
+    const KEY_LEN: usize = 32;
+    const BLOCK_LEN: usize = 16;
+
+    fn circuit() -> Arc<Circuit> {
+        AES256.clone()
+    }
+}
+
+/// The AES round constants used by the AES-256 key schedule.
+const RCON: [u8; 7] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40];
+
+/// AES-256 encryption of a single block.
+///
+/// # Inputs
+///
+///   0. KEY: 32-byte encryption key
+///   1. MSG: 16-byte message
+///
+/// # Outputs
+///
+///   0. CIPHERTEXT: 16-byte ciphertext
+pub(crate) static AES256: Lazy<Arc<Circuit>> = Lazy::new(|| {
+    let builder = CircuitBuilder::new();
+    let key = builder.add_array_input::<u8, 32>();
+    let msg = builder.add_array_input::<u8, 16>();
+    let ciphertext = aes256_trace(builder.state(), key, msg);
+    builder.add_output(ciphertext);
+
+    Arc::new(builder.build().unwrap())
+});
+
+/// Traces AES-256 encryption of a single block, as specified in FIPS 197.
+///
+/// The S-box is computed as the inversion `x^254` in GF(2^8) followed by the affine
+/// transformation. Squaring is linear over GF(2), so only the 4 multiplications of the
+/// addition chain require AND gates.
+pub fn aes256_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    key: [Tracer<'a, U8>; 32],
+    msg: [Tracer<'a, U8>; 16],
+) -> [Tracer<'a, U8>; 16] {
+    let round_keys = expand_key(builder_state, key);
+
+    let mut state = msg;
+    add_round_key(&mut state, &round_keys[0]);
+    for round_key in &round_keys[1..14] {
+        sub_bytes(builder_state, &mut state);
+        shift_rows(&mut state);
+        mix_columns(builder_state, &mut state);
+        add_round_key(&mut state, round_key);
+    }
+    sub_bytes(builder_state, &mut state);
+    shift_rows(&mut state);
+    add_round_key(&mut state, &round_keys[14]);
+
+    state
+}
+
+/// Expands the 32-byte key into the 15 round keys.
+fn expand_key<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    key: [Tracer<'a, U8>; 32],
+) -> Vec<[Tracer<'a, U8>; 16]> {
+    let mut words: Vec<[Tracer<'a, U8>; 4]> = key
+        .chunks(4)
+        .map(|word| word.try_into().expect("word is 4 bytes"))
+        .collect();
+
+    for i in 8..60 {
+        let mut temp = words[i - 1];
+        if i % 8 == 0 {
+            temp.rotate_left(1);
+            temp = temp.map(|byte| sbox(builder_state, byte));
+            temp[0] = temp[0] ^ constant(builder_state, RCON[i / 8 - 1]);
+        } else if i % 8 == 4 {
+            temp = temp.map(|byte| sbox(builder_state, byte));
+        }
+
+        let prev = words[i - 8];
+        words.push(std::array::from_fn(|j| prev[j] ^ temp[j]));
+    }
+
+    words
+        .chunks(4)
+        .map(|round_key| {
+            round_key
+                .concat()
+                .try_into()
+                .expect("round key is 16 bytes")
+        })
+        .collect()
+}
+
+fn add_round_key<'a>(state: &mut [Tracer<'a, U8>; 16], round_key: &[Tracer<'a, U8>; 16]) {
+    state
+        .iter_mut()
+        .zip(round_key)
+        .for_each(|(byte, key)| *byte = *byte ^ *key);
+}
+
+fn sub_bytes<'a>(builder_state: &'a RefCell<BuilderState>, state: &mut [Tracer<'a, U8>; 16]) {
+    state
+        .iter_mut()
+        .for_each(|byte| *byte = sbox(builder_state, *byte));
+}
+
+/// Shifts row `r` of the column-major state to the left by `r` positions.
+fn shift_rows(state: &mut [Tracer<'_, U8>; 16]) {
+    let prev = *state;
+    for (i, byte) in state.iter_mut().enumerate() {
+        let (row, col) = (i % 4, i / 4);
+        *byte = prev[row + 4 * ((col + row) % 4)];
+    }
+}
+
+fn mix_columns<'a>(builder_state: &'a RefCell<BuilderState>, state: &mut [Tracer<'a, U8>; 16]) {
+    for column in state.chunks_mut(4) {
+        let [s0, s1, s2, s3]: [Tracer<'a, U8>; 4] =
+            (&*column).try_into().expect("column is 4 bytes");
+        let [d0, d1, d2, d3] = [s0, s1, s2, s3].map(|s| linear_map(builder_state, s, xtime));
+
+        // 3s = 2s ⊕ s
+        column[0] = d0 ^ d1 ^ s1 ^ s2 ^ s3;
+        column[1] = s0 ^ d1 ^ d2 ^ s2 ^ s3;
+        column[2] = s0 ^ s1 ^ d2 ^ d3 ^ s3;
+        column[3] = d0 ^ s0 ^ s1 ^ s2 ^ d3;
+    }
+}
+
+/// The AES S-box.
+fn sbox<'a>(builder_state: &'a RefCell<BuilderState>, x: Tracer<'a, U8>) -> Tracer<'a, U8> {
+    // Inversion using the addition chain 2, 3, 12, 15, 240, 252, 254.
+    let x2 = linear_map(builder_state, x, |v| gf_pow(v, 2));
+    let x3 = gf_mul_trace(builder_state, x2, x);
+    let x12 = linear_map(builder_state, x3, |v| gf_pow(v, 4));
+    let x15 = gf_mul_trace(builder_state, x12, x3);
+    let x240 = linear_map(builder_state, x15, |v| gf_pow(v, 16));
+    let x252 = gf_mul_trace(builder_state, x240, x12);
+    let inv = gf_mul_trace(builder_state, x252, x2);
+
+    let affine = linear_map(builder_state, inv, |v| {
+        v ^ v.rotate_left(1) ^ v.rotate_left(2) ^ v.rotate_left(3) ^ v.rotate_left(4)
+    });
+
+    affine ^ constant(builder_state, 0x63)
+}
+
+/// Multiplies two bytes in GF(2^8), which requires 64 AND gates.
+fn gf_mul_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    a: Tracer<'a, U8>,
+    b: Tracer<'a, U8>,
+) -> Tracer<'a, U8> {
+    let b_bits = b.to_inner().nodes();
+
+    (0..8)
+        .map(|i| {
+            let a_shifted = linear_map(builder_state, a, |v| gf_mul(v, 1 << i));
+            let b_bit = Tracer::new(builder_state, U8::new([b_bits[i]; 8]));
+            a_shifted & b_bit
+        })
+        .reduce(|acc, term| acc ^ term)
+        .expect("byte is 8 bits")
+}
+
+/// Applies a GF(2)-linear map to a byte, which only requires XOR gates.
+///
+/// The wiring is derived by evaluating the map on the basis vectors.
+fn linear_map<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    x: Tracer<'a, U8>,
+    f: impl Fn(u8) -> u8,
+) -> Tracer<'a, U8> {
+    let bits = x.to_inner().nodes();
+    let zero = builder_state.borrow_mut().get_constant(0u8).nodes()[0];
+
+    (0..8)
+        .map(|i| {
+            // Bits are in LSB0 order.
+            let image = f(1 << i);
+            let nodes: [_; 8] =
+                std::array::from_fn(|j| if (image >> j) & 1 == 1 { bits[i] } else { zero });
+            Tracer::new(builder_state, U8::new(nodes))
+        })
+        .reduce(|acc, term| acc ^ term)
+        .expect("byte is 8 bits")
+}
+
+fn constant<'a>(builder_state: &'a RefCell<BuilderState>, value: u8) -> Tracer<'a, U8> {
+    Tracer::new(
+        builder_state,
+        builder_state.borrow_mut().get_constant(value),
+    )
+}
+
+/// Multiplies a byte by `x` in GF(2^8).
+fn xtime(v: u8) -> u8 {
+    (v << 1) ^ if v & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// Multiplies two bytes in GF(2^8).
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    product
+}
+
+fn gf_pow(v: u8, exp: u32) -> u8 {
+    (0..exp).fold(1, |acc, _| gf_mul(acc, v))
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::evaluate;
+
+    use super::*;
+
+    #[test]
+    fn test_aes256_circuit() {
+        use ::aes::Aes256 as TestAes256;
+        use ::cipher::{BlockEncrypt, KeyInit};
+
+        let key: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let msg: [u8; 16] = std::array::from_fn(|i| (i as u8) << 4 | i as u8);
+
+        let mut expected = msg.into();
+        TestAes256::new(&key.into()).encrypt_block(&mut expected);
+        let expected: [u8; 16] = expected.into();
+
+        let ciphertext = evaluate!(AES256.clone(), fn(key, msg) -> [u8; 16]).unwrap();
+
+        assert_eq!(ciphertext, expected);
+    }
+
+    #[test]
+    fn test_gf_pow_inverse() {
+        for v in 1..=255u8 {
+            assert_eq!(gf_mul(gf_pow(v, 254), v), 1);
+        }
+    }
+}
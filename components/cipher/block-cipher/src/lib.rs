@@ -16,7 +16,7 @@ use mpz_garble::value::ValueRef;
 
 pub use crate::{
     cipher::MpcBlockCipher,
-    circuit::{Aes128, BlockCipherCircuit},
+    circuit::{aes256_trace, Aes128, Aes256, BlockCipherCircuit},
 };
 pub use config::{BlockCipherConfig, BlockCipherConfigBuilder, BlockCipherConfigBuilderError};
 
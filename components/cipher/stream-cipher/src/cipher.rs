@@ -5,7 +5,10 @@ use mpz_circuits::{
     Circuit,
 };
 
-use crate::{circuit::AES_CTR, StreamCipherError};
+use crate::{
+    circuit::{AES256_CTR, AES_CTR, AES_CTR_TLS13, CHACHA20_CTR},
+    StreamCipherError,
+};
 
 /// A counter-mode block cipher circuit.
 pub trait CtrCircuit: Default + Clone + Send + Sync + 'static {
@@ -16,7 +19,6 @@ pub trait CtrCircuit: Default + Clone + Send + Sync + 'static {
         + TryFrom<Vec<u8>>
         + TryFrom<Value>
         + Into<Vec<u8>>
-        + Default
         + Send
         + Sync
         + 'static;
@@ -125,3 +127,300 @@ impl CtrCircuit for Aes128Ctr {
         Ok(buf)
     }
 }
+
+/// A circuit for AES-128 in counter mode, using the TLS 1.3 per-record nonce.
+///
+/// The nonce is the 8-byte record sequence number, which is XORed with
+/// the last 8 bytes of the 12-byte IV.
+#[derive(Default, Debug, Clone)]
+pub struct Aes128CtrTls13;
+
+impl CtrCircuit for Aes128CtrTls13 {
+    type KEY = [u8; 16];
+    type BLOCK = [u8; 16];
+    type IV = [u8; 12];
+    type NONCE = [u8; 8];
+
+    const KEY_LEN: usize = 16;
+    const BLOCK_LEN: usize = 16;
+    const IV_LEN: usize = 12;
+    const NONCE_LEN: usize = 8;
+
+    fn circuit() -> Arc<Circuit> {
+        AES_CTR_TLS13.clone()
+    }
+
+    fn apply_keystream(
+        key: &[u8],
+        iv: &[u8],
+        start_ctr: usize,
+        explicit_nonce: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, StreamCipherError> {
+        use ::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+        use aes::Aes128;
+        use ctr::Ctr32BE;
+
+        let key: &[u8; 16] = key
+            .try_into()
+            .map_err(|_| StreamCipherError::InvalidKeyLength {
+                expected: 16,
+                actual: key.len(),
+            })?;
+        let iv: &[u8; 12] = iv
+            .try_into()
+            .map_err(|_| StreamCipherError::InvalidIvLength {
+                expected: 12,
+                actual: iv.len(),
+            })?;
+        let explicit_nonce: &[u8; 8] = explicit_nonce.try_into().map_err(|_| {
+            StreamCipherError::InvalidExplicitNonceLength {
+                expected: 8,
+                actual: explicit_nonce.len(),
+            }
+        })?;
+
+        let mut full_iv = [0u8; 16];
+        full_iv[0..12].copy_from_slice(iv);
+        full_iv[4..12]
+            .iter_mut()
+            .zip(explicit_nonce)
+            .for_each(|(iv, nonce)| *iv ^= nonce);
+        let mut cipher = Ctr32BE::<Aes128>::new(key.into(), &full_iv.into());
+        let mut buf = msg.to_vec();
+
+        cipher
+            .try_seek(start_ctr * Self::BLOCK_LEN)
+            .expect("start counter is less than keystream length");
+        cipher.apply_keystream(&mut buf);
+
+        Ok(buf)
+    }
+}
+
+/// A circuit for AES-256 in counter mode.
+#[derive(Default, Debug, Clone)]
+pub struct Aes256Ctr;
+
+impl CtrCircuit for Aes256Ctr {
+    type KEY = [u8; 32];
+    type BLOCK = [u8; 16];
+    type IV = [u8; 4];
+    type NONCE = [u8; 8];
+
+    const KEY_LEN: usize = 32;
+    const BLOCK_LEN: usize = 16;
+    const IV_LEN: usize = 4;
+    const NONCE_LEN: usize = 8;
+
+    fn circuit() -> Arc<Circuit> {
+        AES256_CTR.clone()
+    }
+
+    fn apply_keystream(
+        key: &[u8],
+        iv: &[u8],
+        start_ctr: usize,
+        explicit_nonce: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, StreamCipherError> {
+        use ::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+        use aes::Aes256;
+        use ctr::Ctr32BE;
+
+        let key: &[u8; 32] = key
+            .try_into()
+            .map_err(|_| StreamCipherError::InvalidKeyLength {
+                expected: 32,
+                actual: key.len(),
+            })?;
+        let iv: &[u8; 4] = iv
+            .try_into()
+            .map_err(|_| StreamCipherError::InvalidIvLength {
+                expected: 4,
+                actual: iv.len(),
+            })?;
+        let explicit_nonce: &[u8; 8] = explicit_nonce.try_into().map_err(|_| {
+            StreamCipherError::InvalidExplicitNonceLength {
+                expected: 8,
+                actual: explicit_nonce.len(),
+            }
+        })?;
+
+        let mut full_iv = [0u8; 16];
+        full_iv[0..4].copy_from_slice(iv);
+        full_iv[4..12].copy_from_slice(explicit_nonce);
+        let mut cipher = Ctr32BE::<Aes256>::new(key.into(), &full_iv.into());
+        let mut buf = msg.to_vec();
+
+        cipher
+            .try_seek(start_ctr * Self::BLOCK_LEN)
+            .expect("start counter is less than keystream length");
+        cipher.apply_keystream(&mut buf);
+
+        Ok(buf)
+    }
+}
+
+/// A circuit for ChaCha20, using the TLS per-record nonce.
+///
+/// The nonce is the 8-byte record sequence number, which is XORed with
+/// the last 8 bytes of the 12-byte IV. The block counter is 32 bits, as specified in RFC 8439.
+#[derive(Default, Debug, Clone)]
+pub struct ChaCha20Ctr;
+
+impl CtrCircuit for ChaCha20Ctr {
+    type KEY = [u8; 32];
+    type BLOCK = [u8; 64];
+    type IV = [u8; 12];
+    type NONCE = [u8; 8];
+
+    const KEY_LEN: usize = 32;
+    const BLOCK_LEN: usize = 64;
+    const IV_LEN: usize = 12;
+    const NONCE_LEN: usize = 8;
+
+    fn circuit() -> Arc<Circuit> {
+        CHACHA20_CTR.clone()
+    }
+
+    fn apply_keystream(
+        key: &[u8],
+        iv: &[u8],
+        start_ctr: usize,
+        explicit_nonce: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, StreamCipherError> {
+        use ::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+        use chacha20::ChaCha20;
+
+        let key: &[u8; 32] = key
+            .try_into()
+            .map_err(|_| StreamCipherError::InvalidKeyLength {
+                expected: 32,
+                actual: key.len(),
+            })?;
+        let iv: &[u8; 12] = iv
+            .try_into()
+            .map_err(|_| StreamCipherError::InvalidIvLength {
+                expected: 12,
+                actual: iv.len(),
+            })?;
+        let explicit_nonce: &[u8; 8] = explicit_nonce.try_into().map_err(|_| {
+            StreamCipherError::InvalidExplicitNonceLength {
+                expected: 8,
+                actual: explicit_nonce.len(),
+            }
+        })?;
+
+        let mut nonce = *iv;
+        nonce[4..]
+            .iter_mut()
+            .zip(explicit_nonce)
+            .for_each(|(iv, nonce)| *iv ^= nonce);
+        let mut cipher = ChaCha20::new(key.into(), &nonce.into());
+        let mut buf = msg.to_vec();
+
+        cipher
+            .try_seek(start_ctr * Self::BLOCK_LEN)
+            .expect("start counter is less than keystream length");
+        cipher.apply_keystream(&mut buf);
+
+        Ok(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::evaluate;
+
+    use super::*;
+
+    #[test]
+    fn test_aes_128_ctr_tls13_reference() {
+        let key = [1u8; 16];
+        let iv = [2u8; 12];
+        let seq = 3u64.to_be_bytes();
+        let msg = b"This is a test message which will be encrypted using AES-CTR.".to_vec();
+
+        let mut explicit_nonce = [0u8; 8];
+        explicit_nonce
+            .iter_mut()
+            .zip(iv[4..].iter().zip(seq))
+            .for_each(|(nonce, (iv, seq))| *nonce = iv ^ seq);
+
+        let expected =
+            Aes128Ctr::apply_keystream(&key, &iv[..4], 2, &explicit_nonce, &msg).unwrap();
+
+        assert_eq!(
+            Aes128CtrTls13::apply_keystream(&key, &iv, 2, &seq, &msg).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_aes_128_ctr_tls13_circuit() {
+        let circ = Aes128CtrTls13::circuit();
+
+        let key = [1u8; 16];
+        let iv = [2u8; 12];
+        let nonce = 3u64.to_be_bytes();
+        let ctr = 1u32.to_be_bytes();
+
+        let expected = Aes128CtrTls13::apply_keystream(&key, &iv, 1, &nonce, &[0u8; 16]).unwrap();
+
+        let block = evaluate!(circ, fn(key, iv, nonce, ctr) -> [u8; 16]).unwrap();
+
+        assert_eq!(block.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_aes_256_ctr_circuit() {
+        let circ = Aes256Ctr::circuit();
+
+        let key = [1u8; 32];
+        let iv = [2u8; 4];
+        let nonce = 3u64.to_be_bytes();
+        let ctr = 1u32.to_be_bytes();
+
+        let expected = Aes256Ctr::apply_keystream(&key, &iv, 1, &nonce, &[0u8; 16]).unwrap();
+
+        let block = evaluate!(circ, fn(key, iv, nonce, ctr) -> [u8; 16]).unwrap();
+
+        assert_eq!(block.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_chacha20_reference() {
+        // RFC 8439, Section 2.4.2
+        let key: Vec<u8> = (0u8..32).collect();
+        let nonce = [0, 0, 0, 0, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+        let msg = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+        let ciphertext = ChaCha20Ctr::apply_keystream(&key, &nonce, 1, &[0u8; 8], msg).unwrap();
+
+        assert_eq!(
+            &ciphertext[..16],
+            &[
+                0x6e, 0x2e, 0x35, 0x9a, 0x25, 0x68, 0xf9, 0x80, 0x41, 0xba, 0x07, 0x28, 0xdd, 0x0d,
+                0x69, 0x81
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chacha20_circuit() {
+        let circ = ChaCha20Ctr::circuit();
+
+        let key = [1u8; 32];
+        let iv = [2u8; 12];
+        let nonce = 3u64.to_be_bytes();
+        let ctr = 1u32.to_be_bytes();
+
+        let expected = ChaCha20Ctr::apply_keystream(&key, &iv, 1, &nonce, &[0u8; 64]).unwrap();
+
+        let block = evaluate!(circ, fn(key, iv, nonce, ctr) -> [u8; 64]).unwrap();
+
+        assert_eq!(block.to_vec(), expected);
+    }
+}
@@ -1,5 +1,16 @@
-use mpz_circuits::{circuits::aes128_trace, once_cell::sync::Lazy, trace, Circuit, CircuitBuilder};
-use std::sync::Arc;
+use block_cipher::aes256_trace;
+use mpz_circuits::{
+    circuits::aes128_trace,
+    once_cell::sync::Lazy,
+    ops::WrappingAdd,
+    trace,
+    types::{U32, U8},
+    BuilderState, Circuit, CircuitBuilder, Tracer,
+};
+use std::{cell::RefCell, sync::Arc};
+
+/// The ChaCha20 constant "expand 32-byte k".
+const CHACHA20_CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
 
 /// AES encrypt counter block.
 ///
@@ -25,6 +36,215 @@ pub(crate) static AES_CTR: Lazy<Arc<Circuit>> = Lazy::new(|| {
     Arc::new(builder.build().unwrap())
 });
 
+/// AES encrypt counter block, using the TLS 1.3 per-record nonce.
+///
+/// The counter block is `IV[0..4] || (IV[4..12] ⊕ NONCE) || CTR`.
+///
+/// # Inputs
+///
+///   0. KEY: 16-byte encryption key
+///   1. IV: 12-byte IV
+///   2. NONCE: 8-byte sequence number
+///   3. CTR: 4-byte counter
+///
+/// # Outputs
+///
+///   0. ECB: 16-byte output
+pub(crate) static AES_CTR_TLS13: Lazy<Arc<Circuit>> = Lazy::new(|| {
+    let builder = CircuitBuilder::new();
+    let key = builder.add_array_input::<u8, 16>();
+    let iv = builder.add_array_input::<u8, 12>();
+    let nonce = builder.add_array_input::<u8, 8>();
+    let ctr = builder.add_array_input::<u8, 4>();
+
+    let block: Vec<_> = iv[..4]
+        .iter()
+        .copied()
+        .chain(iv[4..].iter().zip(nonce).map(|(iv, nonce)| *iv ^ nonce))
+        .chain(ctr)
+        .collect();
+
+    let ecb = aes128_trace(builder.state(), key, block.try_into().unwrap());
+    builder.add_output(ecb);
+
+    Arc::new(builder.build().unwrap())
+});
+
+/// AES-256 encrypt counter block.
+///
+/// # Inputs
+///
+///   0. KEY: 32-byte encryption key
+///   1. IV: 4-byte IV
+///   2. EXPLICIT_NONCE: 8-byte explicit nonce
+///   3. CTR: 4-byte counter
+///
+/// # Outputs
+///
+///   0. ECB: 16-byte output
+pub(crate) static AES256_CTR: Lazy<Arc<Circuit>> = Lazy::new(|| {
+    let builder = CircuitBuilder::new();
+    let key = builder.add_array_input::<u8, 32>();
+    let iv = builder.add_array_input::<u8, 4>();
+    let nonce = builder.add_array_input::<u8, 8>();
+    let ctr = builder.add_array_input::<u8, 4>();
+
+    let block: Vec<_> = iv.into_iter().chain(nonce).chain(ctr).collect();
+
+    let ecb = aes256_trace(builder.state(), key, block.try_into().unwrap());
+    builder.add_output(ecb);
+
+    Arc::new(builder.build().unwrap())
+});
+
+/// ChaCha20 keystream block, using the TLS per-record nonce.
+///
+/// The nonce is `IV ⊕ (0^32 || NONCE)`, as specified in RFC 7905 and RFC 8446.
+///
+/// # Inputs
+///
+///   0. KEY: 32-byte encryption key
+///   1. IV: 12-byte IV
+///   2. NONCE: 8-byte sequence number
+///   3. CTR: 4-byte big-endian block counter
+///
+/// # Outputs
+///
+///   0. BLOCK: 64-byte keystream block
+pub(crate) static CHACHA20_CTR: Lazy<Arc<Circuit>> = Lazy::new(|| {
+    let builder = CircuitBuilder::new();
+    let key = builder.add_array_input::<u8, 32>();
+    let iv = builder.add_array_input::<u8, 12>();
+    let nonce = builder.add_array_input::<u8, 8>();
+    let ctr = builder.add_array_input::<u8, 4>();
+
+    let nonce: Vec<_> = iv[..4]
+        .iter()
+        .copied()
+        .chain(iv[4..].iter().zip(nonce).map(|(iv, nonce)| *iv ^ nonce))
+        .collect();
+
+    let block = chacha20_block_trace(builder.state(), key, ctr, nonce.try_into().unwrap());
+    builder.add_output(block);
+
+    Arc::new(builder.build().unwrap())
+});
+
+/// Computes a ChaCha20 block as specified in RFC 8439, Section 2.3.
+///
+/// The counter is provided as big-endian bytes, as assigned by the stream cipher.
+fn chacha20_block_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    key: [Tracer<'a, U8>; 32],
+    ctr: [Tracer<'a, U8>; 4],
+    nonce: [Tracer<'a, U8>; 12],
+) -> [Tracer<'a, U8>; 64] {
+    let mut ctr_le = ctr;
+    ctr_le.reverse();
+
+    let mut state = Vec::with_capacity(16);
+    state.extend(
+        CHACHA20_CONSTANTS
+            .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v))),
+    );
+    state.extend(
+        key.chunks(4)
+            .map(|word| u32_from_le_bytes(builder_state, word)),
+    );
+    state.push(u32_from_le_bytes(builder_state, &ctr_le));
+    state.extend(
+        nonce
+            .chunks(4)
+            .map(|word| u32_from_le_bytes(builder_state, word)),
+    );
+
+    let initial_state = state.clone();
+    for _ in 0..10 {
+        // Column rounds
+        quarter_round(builder_state, &mut state, [0, 4, 8, 12]);
+        quarter_round(builder_state, &mut state, [1, 5, 9, 13]);
+        quarter_round(builder_state, &mut state, [2, 6, 10, 14]);
+        quarter_round(builder_state, &mut state, [3, 7, 11, 15]);
+        // Diagonal rounds
+        quarter_round(builder_state, &mut state, [0, 5, 10, 15]);
+        quarter_round(builder_state, &mut state, [1, 6, 11, 12]);
+        quarter_round(builder_state, &mut state, [2, 7, 8, 13]);
+        quarter_round(builder_state, &mut state, [3, 4, 9, 14]);
+    }
+
+    state
+        .into_iter()
+        .zip(initial_state)
+        .flat_map(|(word, initial)| u32_to_le_bytes(builder_state, word.wrapping_add(initial)))
+        .collect::<Vec<_>>()
+        .try_into()
+        .expect("block is 64 bytes")
+}
+
+fn quarter_round<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    state: &mut [Tracer<'a, U32>],
+    [a, b, c, d]: [usize; 4],
+) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = rotate_left(builder_state, state[d] ^ state[a], 16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = rotate_left(builder_state, state[b] ^ state[c], 12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = rotate_left(builder_state, state[d] ^ state[a], 8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = rotate_left(builder_state, state[b] ^ state[c], 7);
+}
+
+/// Rotates a word to the left, which only rewires the bits.
+fn rotate_left<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    word: Tracer<'a, U32>,
+    n: usize,
+) -> Tracer<'a, U32> {
+    let mut nodes = word.to_inner().nodes();
+    // Bits are in LSB0 order.
+    nodes.rotate_right(n);
+
+    Tracer::new(
+        builder_state,
+        U32::new(nodes.try_into().expect("word is 32 bits")),
+    )
+}
+
+fn u32_from_le_bytes<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    bytes: &[Tracer<'a, U8>],
+) -> Tracer<'a, U32> {
+    let nodes: Vec<_> = bytes
+        .iter()
+        .flat_map(|byte| byte.to_inner().nodes())
+        .collect();
+
+    Tracer::new(
+        builder_state,
+        U32::new(nodes.try_into().expect("word is 4 bytes")),
+    )
+}
+
+fn u32_to_le_bytes<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    word: Tracer<'a, U32>,
+) -> [Tracer<'a, U8>; 4] {
+    let nodes = word.to_inner().nodes();
+
+    std::array::from_fn(|i| {
+        Tracer::new(
+            builder_state,
+            U8::new(
+                nodes[i * 8..(i + 1) * 8]
+                    .try_into()
+                    .expect("byte is 8 bits"),
+            ),
+        )
+    })
+}
+
 #[trace]
 #[dep(aes_128, aes128_trace)]
 #[allow(dead_code)]
@@ -1,4 +1,5 @@
-//! This crate provides a 2PC stream cipher implementation using a block cipher in counter mode.
+//! This crate provides a 2PC stream cipher implementation using a block cipher in counter mode,
+//! or ChaCha20.
 //!
 //! Each party plays a specific role, either the `StreamCipherLeader` or the `StreamCipherFollower`. Both parties
 //! work together to encrypt and decrypt messages using a shared key.
@@ -19,7 +20,7 @@ mod circuit;
 mod config;
 mod stream_cipher;
 
-pub use self::cipher::{Aes128Ctr, CtrCircuit};
+pub use self::cipher::{Aes128Ctr, Aes128CtrTls13, Aes256Ctr, ChaCha20Ctr, CtrCircuit};
 pub use config::{StreamCipherConfig, StreamCipherConfigBuilder, StreamCipherConfigBuilderError};
 pub use stream_cipher::MpcStreamCipher;
 
@@ -82,6 +83,15 @@ where
     /// The state of a transcript counter is preserved between calls to `set_transcript_id`.
     fn set_transcript_id(&mut self, id: &str);
 
+    /// Sets the transcript id of the trailing bytes of each message.
+    ///
+    /// If set, the last `len` bytes of each plaintext are assigned identifiers from the
+    /// transcript `id` instead of the current transcript. This is used for TLS 1.3, where
+    /// the content type is appended to the plaintext of each record.
+    ///
+    /// Setting `len` to 0 disables the trailer.
+    fn set_transcript_trailer(&mut self, id: &str, len: usize);
+
     /// Applies the keystream to the given plaintext, where all parties
     /// provide the plaintext as an input.
     ///
@@ -40,6 +40,8 @@ struct State {
     ciphertext_counter: NestedId,
     /// Persists the transcript counter for each transcript id.
     transcript_state: HashMap<String, NestedId>,
+    /// Transcript id and length of the trailer of each message, if set.
+    transcript_trailer: Option<(String, usize)>,
 }
 
 #[derive(Clone)]
@@ -74,6 +76,7 @@ where
                 transcript_counter,
                 ciphertext_counter,
                 transcript_state: HashMap::new(),
+                transcript_trailer: None,
             },
             thread_pool,
             _cipher: PhantomData,
@@ -81,15 +84,43 @@ where
     }
 
     /// Returns unique identifiers for the next bytes in the transcript.
+    ///
+    /// If a trailer is set, the last bytes are assigned identifiers from the trailer transcript.
     fn plaintext_ids(&mut self, len: usize) -> Vec<String> {
-        (0..len)
+        let (trailer_id, trailer_len) = match &self.state.transcript_trailer {
+            Some((id, trailer_len)) => (Some(id.clone()), (*trailer_len).min(len)),
+            None => (None, 0),
+        };
+
+        let mut ids: Vec<_> = (0..len - trailer_len)
             .map(|_| {
                 self.state
                     .transcript_counter
                     .increment_in_place()
                     .to_string()
             })
-            .collect()
+            .collect();
+
+        if let Some(trailer_id) = trailer_id {
+            let current_id = self
+                .state
+                .transcript_counter
+                .root()
+                .expect("root id is set");
+
+            let counter = if current_id == trailer_id {
+                &mut self.state.transcript_counter
+            } else {
+                self.state
+                    .transcript_state
+                    .entry(trailer_id)
+                    .or_insert_with_key(|id| NestedId::new(id).append_counter())
+            };
+
+            ids.extend((0..trailer_len).map(|_| counter.increment_in_place().to_string()));
+        }
+
+        ids
     }
 
     /// Returns unique identifiers for the next bytes in the ciphertext.
@@ -282,6 +313,10 @@ where
         }
     }
 
+    fn set_transcript_trailer(&mut self, id: &str, len: usize) {
+        self.state.transcript_trailer = (len > 0).then(|| (id.to_string(), len));
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "debug", skip(self, plaintext), err)
@@ -840,3 +875,54 @@ async fn compute_key_block<
 
     Ok(key_block)
 }
+
+#[cfg(test)]
+mod tests {
+    use mpz_garble::{protocol::deap::mock::create_mock_deap_vm, Vm};
+
+    use crate::Aes128Ctr;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_transcript_trailer_ids() {
+        let (mut leader_vm, _follower_vm) = create_mock_deap_vm("test").await;
+        let thread_pool = leader_vm.new_thread_pool("mock", 1).await.unwrap();
+
+        let config = StreamCipherConfig::builder()
+            .id("test")
+            .transcript_id("tx")
+            .build()
+            .unwrap();
+
+        let mut cipher = MpcStreamCipher::<Aes128Ctr, _>::new(config, thread_pool);
+
+        let mut ids = Vec::new();
+
+        cipher.set_transcript_trailer("opaque_tx", 1);
+        ids.extend(cipher.plaintext_ids(3));
+
+        cipher.set_transcript_id("opaque_tx");
+        cipher.set_transcript_trailer("opaque_tx", 0);
+        ids.extend(cipher.plaintext_ids(2));
+
+        cipher.set_transcript_trailer("opaque_tx", 1);
+        ids.extend(cipher.plaintext_ids(2));
+
+        cipher.set_transcript_id("tx");
+        ids.extend(cipher.plaintext_ids(2));
+
+        let is_tx = |id: &String| id.starts_with("tx/");
+        let is_opaque = |id: &String| id.starts_with("opaque_tx/");
+
+        assert!(ids[0..2].iter().all(is_tx));
+        assert!(is_opaque(&ids[2]));
+        assert!(ids[3..7].iter().all(is_opaque));
+        assert!(is_tx(&ids[7]));
+        assert!(is_opaque(&ids[8]));
+
+        // Identifiers are never reused across transcripts and trailers.
+        let unique: std::collections::HashSet<_> = ids.iter().collect();
+        assert_eq!(unique.len(), ids.len());
+    }
+}
@@ -6,7 +6,9 @@ use block_cipher::{Aes128, BlockCipherConfigBuilder, MpcBlockCipher};
 use ff::Gf2_128;
 use futures::StreamExt;
 use hmac_sha256::{MpcPrf, Prf, PrfConfig, SessionKeys};
-use key_exchange::{KeyExchange, KeyExchangeConfig, Role as KeyExchangeRole};
+use key_exchange::{
+    KeyExchange, KeyExchangeConfig, NamedGroup, Role as KeyExchangeRole, SecretKey,
+};
 use mpz_garble::{config::Role as GarbleRole, protocol::deap::DEAPVm, Vm};
 use mpz_ot::{
     actor::kos::{ReceiverActor, SenderActor},
@@ -18,7 +20,6 @@ use mpz_ot::{
 };
 use mpz_share_conversion as ff;
 use mpz_share_conversion::{ShareConversionReveal, ShareConversionVerify};
-use p256::{NonZeroScalar, PublicKey, SecretKey};
 use point_addition::{MpcPointAddition, Role as PointAdditionRole, P256};
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
@@ -213,27 +214,25 @@ async fn test_components() {
 
     let mut leader_ke = key_exchange::KeyExchangeCore::new(
         leader_mux.get_channel("ke").await.unwrap(),
-        leader_pa_sender,
-        leader_pa_receiver,
         leader_vm.new_thread("ke").await.unwrap(),
         KeyExchangeConfig::builder()
             .id("ke")
             .role(KeyExchangeRole::Leader)
             .build()
             .unwrap(),
-    );
+    )
+    .with_p256(leader_pa_sender, leader_pa_receiver);
 
     let mut follower_ke = key_exchange::KeyExchangeCore::new(
         follower_mux.get_channel("ke").await.unwrap(),
-        follower_pa_sender,
-        follower_pa_receiver,
         follower_vm.new_thread("ke").await.unwrap(),
         KeyExchangeConfig::builder()
             .id("ke")
             .role(KeyExchangeRole::Follower)
             .build()
             .unwrap(),
-    );
+    )
+    .with_p256(follower_pa_sender, follower_pa_receiver);
 
     let (leader_pms, follower_pms) =
         futures::try_join!(leader_ke.setup(), follower_ke.setup()).unwrap();
@@ -341,9 +340,9 @@ async fn test_components() {
         Box::new(follower_ghash),
     );
 
-    let leader_private_key = SecretKey::random(&mut rng);
-    let follower_private_key = SecretKey::random(&mut rng);
-    let server_public_key = PublicKey::from_secret_scalar(&NonZeroScalar::random(&mut rng));
+    let leader_private_key = SecretKey::random(NamedGroup::P256, &mut rng);
+    let follower_private_key = SecretKey::random(NamedGroup::P256, &mut rng);
+    let server_public_key = SecretKey::random(NamedGroup::P256, &mut rng).public_key();
 
     // Setup complete
 
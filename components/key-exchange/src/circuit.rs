@@ -2,42 +2,85 @@
 
 use std::sync::Arc;
 
-use mpz_circuits::{circuits::big_num::nbyte_add_mod_trace, Circuit, CircuitBuilder};
+use mpz_circuits::{
+    circuits::big_num::nbyte_add_mod_trace, types::U8, Circuit, CircuitBuilder, Tracer,
+};
+
+use crate::{NamedGroup, PMS_LEN};
 
 /// NIST P-256 prime big-endian
-static P: [u8; 32] = [
+static P256_P: [u8; 32] = [
     0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
 ];
 
+/// NIST P-384 prime big-endian
+static P384_P: [u8; 48] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE,
+    0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+];
+
+/// Curve25519 prime 2^255 - 19 big-endian
+static CURVE25519_P: [u8; 32] = [
+    0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xED,
+];
+
 /// Circuit for combining additive shares of the PMS, twice
 ///
+/// The shares are big-endian field elements of the group's curve. The PMS is output in the
+/// encoding used by TLS, which is little-endian for X25519, and zero-padded to [`PMS_LEN`] bytes.
+///
 /// # Inputs
 ///
-/// 0. PMS_SHARE_A: 32 bytes PMS Additive Share
-/// 1. PMS_SHARE_B: 32 bytes PMS Additive Share
-/// 2. PMS_SHARE_C: 32 bytes PMS Additive Share
-/// 3. PMS_SHARE_D: 32 bytes PMS Additive Share
+/// 0. PMS_SHARE_A: PMS Additive Share
+/// 1. PMS_SHARE_B: PMS Additive Share
+/// 2. PMS_SHARE_C: PMS Additive Share
+/// 3. PMS_SHARE_D: PMS Additive Share
 ///
 /// # Outputs
 /// 0. PMS1: Pre-master Secret = PMS_SHARE_A + PMS_SHARE_B
 /// 1. PMS2: Pre-master Secret = PMS_SHARE_C + PMS_SHARE_D
 /// 2. EQ: Equality check of PMS1 and PMS2
-pub(crate) fn build_pms_circuit() -> Arc<Circuit> {
+pub(crate) fn build_pms_circuit(group: NamedGroup) -> Arc<Circuit> {
+    match group {
+        NamedGroup::P256 => build_pms_circuit_with(P256_P, false),
+        NamedGroup::P384 => build_pms_circuit_with(P384_P, false),
+        NamedGroup::X25519 => build_pms_circuit_with(CURVE25519_P, true),
+    }
+}
+
+fn build_pms_circuit_with<const N: usize>(modulus: [u8; N], little_endian: bool) -> Arc<Circuit> {
     let builder = CircuitBuilder::new();
-    let share_a = builder.add_array_input::<u8, 32>();
-    let share_b = builder.add_array_input::<u8, 32>();
-    let share_c = builder.add_array_input::<u8, 32>();
-    let share_d = builder.add_array_input::<u8, 32>();
+    let share_a = builder.add_array_input::<u8, N>();
+    let share_b = builder.add_array_input::<u8, N>();
+    let share_c = builder.add_array_input::<u8, N>();
+    let share_d = builder.add_array_input::<u8, N>();
+
+    let mut a = nbyte_add_mod_trace(builder.state(), share_a, share_b, modulus);
+    let mut b = nbyte_add_mod_trace(builder.state(), share_c, share_d, modulus);
 
-    let a = nbyte_add_mod_trace(builder.state(), share_a, share_b, P);
-    let b = nbyte_add_mod_trace(builder.state(), share_c, share_d, P);
+    if little_endian {
+        a.reverse();
+        b.reverse();
+    }
 
-    let eq: [_; 32] = std::array::from_fn(|i| a[i] ^ b[i]);
+    let eq: [_; N] = std::array::from_fn(|i| a[i] ^ b[i]);
 
-    builder.add_output(a);
-    builder.add_output(b);
-    builder.add_output(eq);
+    let zero = Tracer::new(builder.state(), builder.get_constant(0u8).to_inner());
+
+    builder.add_output(pad(a, zero));
+    builder.add_output(pad(b, zero));
+    builder.add_output(pad(eq, zero));
 
     Arc::new(builder.build().expect("pms circuit is valid"))
 }
+
+/// Pads a value with trailing zeros to [`PMS_LEN`] bytes
+fn pad<'a, const N: usize>(
+    value: [Tracer<'a, U8>; N],
+    zero: Tracer<'a, U8>,
+) -> [Tracer<'a, U8>; PMS_LEN] {
+    std::array::from_fn(|i| if i < N { value[i] } else { zero })
+}
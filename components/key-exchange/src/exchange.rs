@@ -2,11 +2,10 @@
 
 use async_trait::async_trait;
 use futures::{SinkExt, StreamExt};
-use mpz_garble::{value::ValueRef, Decode, Execute, Load, Memory};
+use mpz_garble::{value::ValueRef, Decode, Execute, Memory};
 
 use mpz_share_conversion_core::fields::{p256::P256, Field};
-use p256::{EncodedPoint, PublicKey, SecretKey};
-use point_addition::PointAddition;
+use point_addition::{Curve25519, PointAddition, P384};
 use std::fmt::Debug;
 
 use utils_aio::expect_msg_or_err;
@@ -14,25 +13,26 @@ use utils_aio::expect_msg_or_err;
 use crate::{
     circuit::build_pms_circuit,
     config::{KeyExchangeConfig, Role},
-    KeyExchange, KeyExchangeChannel, KeyExchangeError, KeyExchangeMessage, Pms,
+    key::Point,
+    msg, KeyExchange, KeyExchangeChannel, KeyExchangeError, KeyExchangeMessage, NamedGroup, Pms,
+    PublicKey, SecretKey, PMS_LEN,
 };
 
+/// A boxed point addition instance
+type BoxedPointAddition<P, F> = Box<dyn PointAddition<Point = P, XCoordinate = F> + Send>;
+
+/// A pair of point addition instances, acting as share conversion sender and receiver
+type PointAdditionPair<P, F> = (BoxedPointAddition<P, F>, BoxedPointAddition<P, F>);
+
 enum State {
     Initialized,
     Setup {
-        share_a: ValueRef,
-        share_b: ValueRef,
-        share_c: ValueRef,
-        share_d: ValueRef,
         pms_1: ValueRef,
         pms_2: ValueRef,
         eq: ValueRef,
     },
     KeyExchange {
-        share_a: ValueRef,
-        share_b: ValueRef,
-        share_c: ValueRef,
-        share_d: ValueRef,
+        group: NamedGroup,
         pms_1: ValueRef,
         pms_2: ValueRef,
         eq: ValueRef,
@@ -44,13 +44,19 @@ enum State {
 /// The instance for performing the key exchange protocol
 ///
 /// Can be either a leader or a follower depending on the `role` field in [KeyExchangeConfig]
-pub struct KeyExchangeCore<PS, PR, E> {
+///
+/// A group is only supported if point addition instances have been provided for it, see
+/// [`with_p256`](Self::with_p256), [`with_p384`](Self::with_p384) and
+/// [`with_x25519`](Self::with_x25519).
+pub struct KeyExchangeCore<E> {
     /// A channel for exchanging messages between leader and follower
     channel: KeyExchangeChannel,
-    /// The sender instance for performing point addition
-    point_addition_sender: PS,
-    /// The receiver instance for performing point addition
-    point_addition_receiver: PR,
+    /// The point addition instances for P-256
+    p256: Option<PointAdditionPair<p256::EncodedPoint, P256>>,
+    /// The point addition instances for P-384
+    p384: Option<PointAdditionPair<p384::EncodedPoint, P384>>,
+    /// The point addition instances for X25519
+    x25519: Option<PointAdditionPair<ark_curve25519::EdwardsAffine, Curve25519>>,
     /// MPC executor
     executor: E,
     /// The private key of the party behind this instance, either follower or leader
@@ -63,17 +69,14 @@ pub struct KeyExchangeCore<PS, PR, E> {
     state: State,
 }
 
-impl<PS, PR, E> Debug for KeyExchangeCore<PS, PR, E>
+impl<E> Debug for KeyExchangeCore<E>
 where
-    PS: PointAddition<Point = EncodedPoint, XCoordinate = P256> + Send + Debug,
-    PR: PointAddition<Point = EncodedPoint, XCoordinate = P256> + Send + Debug,
     E: Memory + Execute + Decode + Send,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("KeyExchangeCore")
             .field("channel", &"{{ ... }}")
-            .field("point_addition_sender", &"{{ ... }}")
-            .field("point_addition_receiver", &"{{ ... }}")
+            .field("groups", &self.groups())
             .field("executor", &"{{ ... }}")
             .field("private_key", &"{{ ... }}")
             .field("server_key", &self.server_key)
@@ -82,38 +85,25 @@ where
     }
 }
 
-impl<PS, PR, E> KeyExchangeCore<PS, PR, E>
+impl<E> KeyExchangeCore<E>
 where
-    PS: PointAddition<Point = EncodedPoint, XCoordinate = P256> + Send + Debug,
-    PR: PointAddition<Point = EncodedPoint, XCoordinate = P256> + Send + Debug,
     E: Memory + Execute + Decode + Send,
 {
     /// Creates a new [KeyExchangeCore]
     ///
     /// * `channel`                 - The channel for sending messages between leader and follower
-    /// * `point_addition_sender`   - The point addition sender instance used during key exchange
-    /// * `point_addition_receiver` - The point addition receiver instance used during key exchange
     /// * `executor`                - The MPC executor
     /// * `config`                  - The config used for the key exchange protocol
     #[cfg_attr(
         feature = "tracing",
-        tracing::instrument(
-            level = "info",
-            skip(channel, executor, point_addition_sender, point_addition_receiver),
-            ret
-        )
+        tracing::instrument(level = "info", skip(channel, executor), ret)
     )]
-    pub fn new(
-        channel: KeyExchangeChannel,
-        point_addition_sender: PS,
-        point_addition_receiver: PR,
-        executor: E,
-        config: KeyExchangeConfig,
-    ) -> Self {
+    pub fn new(channel: KeyExchangeChannel, executor: E, config: KeyExchangeConfig) -> Self {
         Self {
             channel,
-            point_addition_sender,
-            point_addition_receiver,
+            p256: None,
+            p384: None,
+            x25519: None,
             executor,
             private_key: None,
             server_key: None,
@@ -122,31 +112,138 @@ where
         }
     }
 
-    async fn compute_pms_shares(&mut self) -> Result<(P256, P256), KeyExchangeError> {
+    /// Adds support for P-256 with the provided point addition sender and receiver instances
+    pub fn with_p256<PS, PR>(
+        mut self,
+        point_addition_sender: PS,
+        point_addition_receiver: PR,
+    ) -> Self
+    where
+        PS: PointAddition<Point = p256::EncodedPoint, XCoordinate = P256> + Send + 'static,
+        PR: PointAddition<Point = p256::EncodedPoint, XCoordinate = P256> + Send + 'static,
+    {
+        self.p256 = Some((
+            Box::new(point_addition_sender),
+            Box::new(point_addition_receiver),
+        ));
+        self
+    }
+
+    /// Adds support for P-384 with the provided point addition sender and receiver instances
+    pub fn with_p384<PS, PR>(
+        mut self,
+        point_addition_sender: PS,
+        point_addition_receiver: PR,
+    ) -> Self
+    where
+        PS: PointAddition<Point = p384::EncodedPoint, XCoordinate = P384> + Send + 'static,
+        PR: PointAddition<Point = p384::EncodedPoint, XCoordinate = P384> + Send + 'static,
+    {
+        self.p384 = Some((
+            Box::new(point_addition_sender),
+            Box::new(point_addition_receiver),
+        ));
+        self
+    }
+
+    /// Adds support for X25519 with the provided point addition sender and receiver instances
+    pub fn with_x25519<PS, PR>(
+        mut self,
+        point_addition_sender: PS,
+        point_addition_receiver: PR,
+    ) -> Self
+    where
+        PS: PointAddition<Point = ark_curve25519::EdwardsAffine, XCoordinate = Curve25519>
+            + Send
+            + 'static,
+        PR: PointAddition<Point = ark_curve25519::EdwardsAffine, XCoordinate = Curve25519>
+            + Send
+            + 'static,
+    {
+        self.x25519 = Some((
+            Box::new(point_addition_sender),
+            Box::new(point_addition_receiver),
+        ));
+        self
+    }
+
+    /// Returns the supported groups
+    pub fn groups(&self) -> Vec<NamedGroup> {
+        let mut groups = Vec::new();
+        if self.p256.is_some() {
+            groups.push(NamedGroup::P256);
+        }
+        if self.p384.is_some() {
+            groups.push(NamedGroup::P384);
+        }
+        if self.x25519.is_some() {
+            groups.push(NamedGroup::X25519);
+        }
+        groups
+    }
+
+    /// Computes the additive shares of the shared secret's x-coordinate, as big-endian bytes
+    async fn compute_x_coordinate_shares(
+        &mut self,
+        shared_secret: Point,
+    ) -> Result<(Vec<u8>, Vec<u8>), KeyExchangeError> {
+        let group = shared_secret.group();
+        let unsupported = || KeyExchangeError::UnsupportedGroup(group);
+
+        let shares = match shared_secret {
+            Point::P256(point) => {
+                let (sender, receiver) = self.p256.as_mut().ok_or_else(unsupported)?;
+                let point = p256::EncodedPoint::from(point);
+                let (a, b) = futures::try_join!(
+                    sender.compute_x_coordinate_share(point),
+                    receiver.compute_x_coordinate_share(point)
+                )?;
+                (a.to_be_bytes(), b.to_be_bytes())
+            }
+            Point::P384(point) => {
+                let (sender, receiver) = self.p384.as_mut().ok_or_else(unsupported)?;
+                let point = p384::EncodedPoint::from(point);
+                let (a, b) = futures::try_join!(
+                    sender.compute_x_coordinate_share(point),
+                    receiver.compute_x_coordinate_share(point)
+                )?;
+                (a.to_be_bytes(), b.to_be_bytes())
+            }
+            Point::X25519(point) => {
+                let (sender, receiver) = self.x25519.as_mut().ok_or_else(unsupported)?;
+                let (a, b) = futures::try_join!(
+                    sender.compute_x_coordinate_share(point),
+                    receiver.compute_x_coordinate_share(point)
+                )?;
+                (a.to_be_bytes(), b.to_be_bytes())
+            }
+        };
+
+        Ok(shares)
+    }
+
+    async fn compute_pms_shares(&mut self) -> Result<(Vec<u8>, Vec<u8>), KeyExchangeError> {
         let state = std::mem::replace(&mut self.state, State::Error);
 
-        let State::Setup {
-            share_a,
-            share_b,
-            share_c,
-            share_d,
-            pms_1,
-            pms_2,
-            eq,
-        } = state
-        else {
-            todo!()
+        let State::Setup { pms_1, pms_2, eq } = state else {
+            return Err(KeyExchangeError::InvalidState(
+                "expected to be in Setup state".to_string(),
+            ));
         };
 
         let server_key = match self.config.role() {
             Role::Leader => {
                 // Send server public key to follower
                 if let Some(server_key) = &self.server_key {
+                    let server_key = Point::from_public_key(server_key)?;
+
                     self.channel
-                        .send(KeyExchangeMessage::ServerPublicKey((*server_key).into()))
+                        .send(KeyExchangeMessage::ServerPublicKey(
+                            msg::PublicKey::from_point(&server_key),
+                        ))
                         .await?;
 
-                    *server_key
+                    server_key
                 } else {
                     return Err(KeyExchangeError::NoServerKey);
                 }
@@ -155,9 +252,9 @@ where
                 // Receive server's public key from leader
                 let message =
                     expect_msg_or_err!(self.channel, KeyExchangeMessage::ServerPublicKey)?;
-                let server_key = message.try_into()?;
+                let server_key = message.to_point()?;
 
-                self.server_key = Some(server_key);
+                self.server_key = Some(server_key.to_public_key());
 
                 server_key
             }
@@ -169,28 +266,12 @@ where
             .ok_or(KeyExchangeError::NoPrivateKey)?;
 
         // Compute the leader's/follower's share of the pre-master secret
-        //
-        // We need to mimic the [diffie-hellman](p256::ecdh::diffie_hellman) function without the
-        // [SharedSecret](p256::ecdh::SharedSecret) wrapper, because this makes it harder to get
-        // the result as an EC curve point.
-        let shared_secret = {
-            let public_projective = server_key.to_projective();
-            (public_projective * private_key.to_nonzero_scalar().as_ref()).to_affine()
-        };
-
-        let encoded_point = EncodedPoint::from(PublicKey::from_affine(shared_secret)?);
-        let (sender_share, receiver_share) = futures::try_join!(
-            self.point_addition_sender
-                .compute_x_coordinate_share(encoded_point),
-            self.point_addition_receiver
-                .compute_x_coordinate_share(encoded_point)
-        )?;
+        let shared_secret = server_key.mul(&private_key)?;
+        let (sender_share, receiver_share) =
+            self.compute_x_coordinate_shares(shared_secret).await?;
 
         self.state = State::KeyExchange {
-            share_a,
-            share_b,
-            share_c,
-            share_d,
+            group: shared_secret.group(),
             pms_1,
             pms_2,
             eq,
@@ -204,47 +285,53 @@ where
 
     async fn compute_pms_for(
         &mut self,
-        pms_share1: P256,
-        pms_share2: P256,
+        pms_share1: Vec<u8>,
+        pms_share2: Vec<u8>,
     ) -> Result<Pms, KeyExchangeError> {
         let state = std::mem::replace(&mut self.state, State::Error);
 
         let State::KeyExchange {
-            share_a,
-            share_b,
-            share_c,
-            share_d,
+            group,
             pms_1,
             pms_2,
             eq,
         } = state
         else {
-            todo!()
+            return Err(KeyExchangeError::InvalidState(
+                "expected to be in KeyExchange state".to_string(),
+            ));
         };
 
-        let pms_share1: [u8; 32] = pms_share1
-            .to_be_bytes()
-            .try_into()
-            .expect("pms share is 32 bytes");
-        let pms_share2: [u8; 32] = pms_share2
-            .to_be_bytes()
-            .try_into()
-            .expect("pms share is 32 bytes");
+        // The shares are allocated once the group is known, as their length depends on it
+        let (share_a, share_b, share_c, share_d) = match self.config.role() {
+            Role::Leader => (
+                new_share(&mut self.executor, "pms/share_a", group, true)?,
+                new_share(&mut self.executor, "pms/share_b", group, false)?,
+                new_share(&mut self.executor, "pms/share_c", group, true)?,
+                new_share(&mut self.executor, "pms/share_d", group, false)?,
+            ),
+            Role::Follower => (
+                new_share(&mut self.executor, "pms/share_a", group, false)?,
+                new_share(&mut self.executor, "pms/share_b", group, true)?,
+                new_share(&mut self.executor, "pms/share_c", group, false)?,
+                new_share(&mut self.executor, "pms/share_d", group, true)?,
+            ),
+        };
 
         match self.config.role() {
             Role::Leader => {
-                self.executor.assign(&share_a, pms_share1)?;
-                self.executor.assign(&share_c, pms_share2)?;
+                assign_share(&mut self.executor, &share_a, pms_share1)?;
+                assign_share(&mut self.executor, &share_c, pms_share2)?;
             }
             Role::Follower => {
-                self.executor.assign(&share_b, pms_share1)?;
-                self.executor.assign(&share_d, pms_share2)?;
+                assign_share(&mut self.executor, &share_b, pms_share1)?;
+                assign_share(&mut self.executor, &share_d, pms_share2)?;
             }
         }
 
         self.executor
             .execute(
-                build_pms_circuit(),
+                build_pms_circuit(group),
                 &[share_a, share_b, share_c, share_d],
                 &[pms_1.clone(), pms_2, eq.clone()],
             )
@@ -255,10 +342,10 @@ where
 
         let mut outputs = self.executor.decode(&[eq]).await?;
 
-        let eq: [u8; 32] = outputs.remove(0).try_into().expect("eq is 32 bytes");
+        let eq: [u8; PMS_LEN] = outputs.remove(0).try_into().expect("eq is PMS_LEN bytes");
 
         // Eq should be all zeros if pms_1 == pms_2
-        if eq != [0u8; 32] {
+        if eq != [0u8; PMS_LEN] {
             return Err(KeyExchangeError::CheckFailed);
         }
 
@@ -269,19 +356,50 @@ where
     }
 }
 
+/// Allocates an input for a PMS share of the given group
+fn new_share<E: Memory>(
+    executor: &mut E,
+    id: &str,
+    group: NamedGroup,
+    private: bool,
+) -> Result<ValueRef, KeyExchangeError> {
+    let value = match (group.secret_len(), private) {
+        (32, true) => executor.new_private_input::<[u8; 32]>(id)?,
+        (32, false) => executor.new_blind_input::<[u8; 32]>(id)?,
+        (48, true) => executor.new_private_input::<[u8; 48]>(id)?,
+        (48, false) => executor.new_blind_input::<[u8; 48]>(id)?,
+        _ => unreachable!("shared secrets are 32 or 48 bytes"),
+    };
+
+    Ok(value)
+}
+
+/// Assigns a big-endian PMS share to an input
+fn assign_share<E: Memory>(
+    executor: &mut E,
+    value: &ValueRef,
+    share: Vec<u8>,
+) -> Result<(), KeyExchangeError> {
+    match share.len() {
+        32 => executor.assign(value, <[u8; 32]>::try_from(share).unwrap())?,
+        48 => executor.assign(value, <[u8; 48]>::try_from(share).unwrap())?,
+        _ => unreachable!("shared secrets are 32 or 48 bytes"),
+    }
+
+    Ok(())
+}
+
 #[async_trait]
-impl<PS, PR, E> KeyExchange for KeyExchangeCore<PS, PR, E>
+impl<E> KeyExchange for KeyExchangeCore<E>
 where
-    PS: PointAddition<Point = EncodedPoint, XCoordinate = P256> + Send + Debug,
-    PR: PointAddition<Point = EncodedPoint, XCoordinate = P256> + Send + Debug,
-    E: Memory + Load + Execute + Decode + Send,
+    E: Memory + Execute + Decode + Send,
 {
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "info", skip(self), ret)
     )]
     fn server_key(&self) -> Option<PublicKey> {
-        self.server_key
+        self.server_key.clone()
     }
 
     /// Set the server's public key
@@ -299,71 +417,13 @@ where
             ));
         };
 
-        let (share_a, share_b, share_c, share_d) = match self.config.role() {
-            Role::Leader => {
-                let share_a = self
-                    .executor
-                    .new_private_input::<[u8; 32]>("pms/share_a")
-                    .unwrap();
-                let share_b = self
-                    .executor
-                    .new_blind_input::<[u8; 32]>("pms/share_b")
-                    .unwrap();
-                let share_c = self
-                    .executor
-                    .new_private_input::<[u8; 32]>("pms/share_c")
-                    .unwrap();
-                let share_d = self
-                    .executor
-                    .new_blind_input::<[u8; 32]>("pms/share_d")
-                    .unwrap();
-
-                (share_a, share_b, share_c, share_d)
-            }
-            Role::Follower => {
-                let share_a = self
-                    .executor
-                    .new_blind_input::<[u8; 32]>("pms/share_a")
-                    .unwrap();
-                let share_b = self
-                    .executor
-                    .new_private_input::<[u8; 32]>("pms/share_b")
-                    .unwrap();
-                let share_c = self
-                    .executor
-                    .new_blind_input::<[u8; 32]>("pms/share_c")
-                    .unwrap();
-                let share_d = self
-                    .executor
-                    .new_private_input::<[u8; 32]>("pms/share_d")
-                    .unwrap();
-
-                (share_a, share_b, share_c, share_d)
-            }
-        };
-
-        let pms_1 = self.executor.new_output::<[u8; 32]>("pms/1")?;
-        let pms_2 = self.executor.new_output::<[u8; 32]>("pms/2")?;
-        let eq = self.executor.new_output::<[u8; 32]>("pms/eq")?;
-
-        self.executor
-            .load(
-                build_pms_circuit(),
-                &[
-                    share_a.clone(),
-                    share_b.clone(),
-                    share_c.clone(),
-                    share_d.clone(),
-                ],
-                &[pms_1.clone(), pms_2.clone(), eq.clone()],
-            )
-            .await?;
+        // The PMS circuit depends on the group selected by the server, so it can not be loaded
+        // in advance. It is small in comparison to the key derivation circuits.
+        let pms_1 = self.executor.new_output::<[u8; PMS_LEN]>("pms/1")?;
+        let pms_2 = self.executor.new_output::<[u8; PMS_LEN]>("pms/2")?;
+        let eq = self.executor.new_output::<[u8; PMS_LEN]>("pms/eq")?;
 
         self.state = State::Setup {
-            share_a,
-            share_b,
-            share_c,
-            share_d,
             pms_1: pms_1.clone(),
             pms_2,
             eq,
@@ -384,7 +444,12 @@ where
         &mut self,
         private_key: SecretKey,
     ) -> Result<Option<PublicKey>, KeyExchangeError> {
-        let public_key = private_key.public_key();
+        let group = private_key.group();
+        if !self.groups().contains(&group) {
+            return Err(KeyExchangeError::UnsupportedGroup(group));
+        }
+
+        let public_key = Point::from_secret(&private_key);
         self.private_key = Some(private_key);
 
         match self.config.role() {
@@ -392,19 +457,19 @@ where
                 // Receive public key from follower
                 let message =
                     expect_msg_or_err!(self.channel, KeyExchangeMessage::FollowerPublicKey)?;
-                let follower_public_key: PublicKey = message.try_into()?;
+                let follower_public_key = message.to_point()?;
 
                 // Combine public keys
-                let client_public_key = PublicKey::from_affine(
-                    (public_key.to_projective() + follower_public_key.to_projective()).to_affine(),
-                )?;
+                let client_public_key = public_key.add(&follower_public_key)?;
 
-                Ok(Some(client_public_key))
+                Ok(Some(client_public_key.to_public_key()))
             }
             Role::Follower => {
                 // Send public key to leader
                 self.channel
-                    .send(KeyExchangeMessage::FollowerPublicKey(public_key.into()))
+                    .send(KeyExchangeMessage::FollowerPublicKey(
+                        msg::PublicKey::from_point(&public_key),
+                    ))
                     .await?;
 
                 Ok(None)
@@ -434,8 +499,6 @@ mod tests {
         },
         Vm,
     };
-    use mpz_share_conversion_core::fields::{p256::P256, Field};
-    use p256::{NonZeroScalar, PublicKey, SecretKey};
     use rand_chacha::ChaCha20Rng;
     use rand_core::SeedableRng;
 
@@ -444,6 +507,8 @@ mod tests {
         KeyExchangeError,
     };
 
+    const GROUPS: [NamedGroup; 3] = [NamedGroup::P256, NamedGroup::P384, NamedGroup::X25519];
+
     async fn create_pair() -> (
         (
             MockKeyExchange<MockLeaderThread>,
@@ -462,141 +527,212 @@ mod tests {
         )
     }
 
+    /// Adds big-endian shares of a field element, returning the big-endian sum
+    fn add_shares(group: NamedGroup, a: Vec<u8>, b: Vec<u8>) -> Vec<u8> {
+        fn le<const N: usize>(mut share: Vec<u8>) -> [u8; N] {
+            share.reverse();
+            share.try_into().unwrap()
+        }
+
+        match group {
+            NamedGroup::P256 => (P256::try_from(le::<32>(a)).unwrap()
+                + P256::try_from(le::<32>(b)).unwrap())
+            .to_be_bytes(),
+            NamedGroup::P384 => (P384::try_from(le::<48>(a)).unwrap()
+                + P384::try_from(le::<48>(b)).unwrap())
+            .to_be_bytes(),
+            NamedGroup::X25519 => (Curve25519::try_from(le::<32>(a)).unwrap()
+                + Curve25519::try_from(le::<32>(b)).unwrap())
+            .to_be_bytes(),
+        }
+    }
+
+    /// Returns the shared secret in the encoding used by TLS
+    fn shared_secret(server_private_key: &SecretKey, client_public_key: &PublicKey) -> Vec<u8> {
+        let shared = Point::from_public_key(client_public_key)
+            .unwrap()
+            .mul(server_private_key)
+            .unwrap()
+            .to_public_key();
+
+        match shared.group() {
+            NamedGroup::X25519 => shared.key().to_vec(),
+            group => shared.key()[1..1 + group.secret_len()].to_vec(),
+        }
+    }
+
     #[tokio::test]
     async fn test_key_exchange() {
         let mut rng = ChaCha20Rng::from_seed([0_u8; 32]);
 
-        let leader_private_key = SecretKey::random(&mut rng);
-        let follower_private_key = SecretKey::random(&mut rng);
-        let server_public_key = PublicKey::from_secret_scalar(&NonZeroScalar::random(&mut rng));
+        for group in GROUPS {
+            let leader_private_key = SecretKey::random(group, &mut rng);
+            let follower_private_key = SecretKey::random(group, &mut rng);
+            let server_public_key = SecretKey::random(group, &mut rng).public_key();
 
-        let ((mut leader, mut follower), (_leader_vm, _follower_vm)) = create_pair().await;
+            let ((mut leader, mut follower), (_leader_vm, _follower_vm)) = create_pair().await;
 
-        let client_public_key = perform_key_exchange(
-            &mut leader,
-            &mut follower,
-            leader_private_key.clone(),
-            follower_private_key.clone(),
-            server_public_key,
-        )
-        .await;
+            let client_public_key = perform_key_exchange(
+                &mut leader,
+                &mut follower,
+                leader_private_key.clone(),
+                follower_private_key.clone(),
+                server_public_key,
+            )
+            .await;
 
-        let expected_client_public_key = PublicKey::from_affine(
-            (leader_private_key.public_key().to_projective()
-                + follower_private_key.public_key().to_projective())
-            .to_affine(),
-        )
-        .unwrap();
+            let expected_client_public_key = Point::from_secret(&leader_private_key)
+                .add(&Point::from_secret(&follower_private_key))
+                .unwrap()
+                .to_public_key();
 
-        assert_eq!(client_public_key, expected_client_public_key);
+            assert_eq!(client_public_key, expected_client_public_key);
+            assert_eq!(client_public_key.group(), group);
+        }
     }
 
     #[tokio::test]
     async fn test_compute_pms_share() {
         let mut rng = ChaCha20Rng::from_seed([0_u8; 32]);
 
-        let leader_private_key = SecretKey::random(&mut rng);
-        let follower_private_key = SecretKey::random(&mut rng);
-        let server_private_key = NonZeroScalar::random(&mut rng);
-        let server_public_key = PublicKey::from_secret_scalar(&server_private_key);
+        for group in GROUPS {
+            let leader_private_key = SecretKey::random(group, &mut rng);
+            let follower_private_key = SecretKey::random(group, &mut rng);
+            let server_private_key = SecretKey::random(group, &mut rng);
+            let server_public_key = server_private_key.public_key();
 
-        let ((mut leader, mut follower), (_leader_vm, _follower_vm)) = create_pair().await;
+            let ((mut leader, mut follower), (_leader_vm, _follower_vm)) = create_pair().await;
 
-        let client_public_key = perform_key_exchange(
-            &mut leader,
-            &mut follower,
-            leader_private_key.clone(),
-            follower_private_key.clone(),
-            server_public_key,
-        )
-        .await;
+            let client_public_key = perform_key_exchange(
+                &mut leader,
+                &mut follower,
+                leader_private_key.clone(),
+                follower_private_key.clone(),
+                server_public_key.clone(),
+            )
+            .await;
 
-        leader.set_server_key(server_public_key);
+            leader.set_server_key(server_public_key);
 
-        let ((l_pms1, l_pms2), (f_pms1, f_pms2)) =
-            tokio::try_join!(leader.compute_pms_shares(), follower.compute_pms_shares()).unwrap();
-
-        let expected_ecdh_x =
-            p256::ecdh::diffie_hellman(server_private_key, client_public_key.as_affine());
-
-        assert_eq!(
-            expected_ecdh_x.raw_secret_bytes().to_vec(),
-            (l_pms1 + f_pms1).to_be_bytes()
-        );
-        assert_eq!(
-            expected_ecdh_x.raw_secret_bytes().to_vec(),
-            (l_pms2 + f_pms2).to_be_bytes()
-        );
-        assert_eq!(l_pms1 + f_pms1, l_pms2 + f_pms2);
-        assert_ne!(l_pms1, f_pms1);
-        assert_ne!(l_pms2, f_pms2);
-        assert_ne!(l_pms1, l_pms2);
-        assert_ne!(f_pms1, f_pms2);
+            let ((l_pms1, l_pms2), (f_pms1, f_pms2)) =
+                tokio::try_join!(leader.compute_pms_shares(), follower.compute_pms_shares())
+                    .unwrap();
+
+            let mut expected = shared_secret(&server_private_key, &client_public_key);
+            if group == NamedGroup::X25519 {
+                // The shares are big-endian, X25519 secrets are little-endian
+                expected.reverse();
+            }
+
+            assert_eq!(expected, add_shares(group, l_pms1.clone(), f_pms1.clone()));
+            assert_eq!(expected, add_shares(group, l_pms2.clone(), f_pms2.clone()));
+            assert_ne!(l_pms1, f_pms1);
+            assert_ne!(l_pms2, f_pms2);
+            assert_ne!(l_pms1, l_pms2);
+            assert_ne!(f_pms1, f_pms2);
+        }
     }
 
     #[tokio::test]
     async fn test_compute_pms() {
         let mut rng = ChaCha20Rng::from_seed([0_u8; 32]);
 
-        let leader_private_key = SecretKey::random(&mut rng);
-        let follower_private_key = SecretKey::random(&mut rng);
-        let server_private_key = NonZeroScalar::random(&mut rng);
-        let server_public_key = PublicKey::from_secret_scalar(&server_private_key);
+        for group in GROUPS {
+            let leader_private_key = SecretKey::random(group, &mut rng);
+            let follower_private_key = SecretKey::random(group, &mut rng);
+            let server_private_key = SecretKey::random(group, &mut rng);
+            let server_public_key = server_private_key.public_key();
 
-        let ((mut leader, mut follower), (_leader_vm, _follower_vm)) = create_pair().await;
+            let ((mut leader, mut follower), (_leader_vm, _follower_vm)) = create_pair().await;
 
-        _ = perform_key_exchange(
-            &mut leader,
-            &mut follower,
-            leader_private_key.clone(),
-            follower_private_key.clone(),
-            server_public_key,
-        )
-        .await;
+            let client_public_key = perform_key_exchange(
+                &mut leader,
+                &mut follower,
+                leader_private_key.clone(),
+                follower_private_key.clone(),
+                server_public_key.clone(),
+            )
+            .await;
 
-        leader.set_server_key(server_public_key);
+            leader.set_server_key(server_public_key.clone());
+
+            let (leader_pms, follower_pms) =
+                tokio::try_join!(leader.compute_pms(), follower.compute_pms()).unwrap();
+
+            assert_eq!(leader.server_key.clone().unwrap(), server_public_key);
+            assert_eq!(follower.server_key.clone().unwrap(), server_public_key);
 
-        let (_leader_pms, _follower_pms) =
-            tokio::try_join!(leader.compute_pms(), follower.compute_pms()).unwrap();
+            let (mut leader_pms, mut follower_pms) = tokio::try_join!(
+                leader.executor.decode(&[leader_pms.into_value()]),
+                follower.executor.decode(&[follower_pms.into_value()])
+            )
+            .unwrap();
+            let leader_pms: [u8; PMS_LEN] = leader_pms.remove(0).try_into().unwrap();
+            let follower_pms: [u8; PMS_LEN] = follower_pms.remove(0).try_into().unwrap();
+
+            // The PMS is the shared secret, zero-padded to PMS_LEN bytes
+            let mut expected = [0u8; PMS_LEN];
+            let secret = shared_secret(&server_private_key, &client_public_key);
+            expected[..secret.len()].copy_from_slice(&secret);
 
-        assert_eq!(leader.server_key.unwrap(), server_public_key);
-        assert_eq!(follower.server_key.unwrap(), server_public_key);
+            assert_eq!(leader_pms, expected);
+            assert_eq!(follower_pms, expected);
+        }
     }
 
     #[tokio::test]
     async fn test_compute_pms_fail() {
         let mut rng = ChaCha20Rng::from_seed([0_u8; 32]);
 
-        let leader_private_key = SecretKey::random(&mut rng);
-        let follower_private_key = SecretKey::random(&mut rng);
-        let server_private_key = NonZeroScalar::random(&mut rng);
-        let server_public_key = PublicKey::from_secret_scalar(&server_private_key);
+        for group in GROUPS {
+            let leader_private_key = SecretKey::random(group, &mut rng);
+            let follower_private_key = SecretKey::random(group, &mut rng);
+            let server_public_key = SecretKey::random(group, &mut rng).public_key();
 
-        let ((mut leader, mut follower), (_leader_vm, _follower_vm)) = create_pair().await;
+            let ((mut leader, mut follower), (_leader_vm, _follower_vm)) = create_pair().await;
 
-        _ = perform_key_exchange(
-            &mut leader,
-            &mut follower,
-            leader_private_key.clone(),
-            follower_private_key.clone(),
-            server_public_key,
-        )
-        .await;
+            _ = perform_key_exchange(
+                &mut leader,
+                &mut follower,
+                leader_private_key.clone(),
+                follower_private_key.clone(),
+                server_public_key.clone(),
+            )
+            .await;
 
-        leader.set_server_key(server_public_key);
+            leader.set_server_key(server_public_key);
+
+            let ((mut l_pms1, l_pms2), (f_pms1, f_pms2)) =
+                tokio::try_join!(leader.compute_pms_shares(), follower.compute_pms_shares())
+                    .unwrap();
+
+            *l_pms1.last_mut().unwrap() ^= 1;
+
+            let err = tokio::try_join!(
+                leader.compute_pms_for(l_pms1, l_pms2),
+                follower.compute_pms_for(f_pms1, f_pms2)
+            )
+            .unwrap_err();
 
-        let ((mut l_pms1, l_pms2), (f_pms1, f_pms2)) =
-            tokio::try_join!(leader.compute_pms_shares(), follower.compute_pms_shares()).unwrap();
+            assert!(matches!(err, KeyExchangeError::CheckFailed));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_group_mismatch() {
+        let mut rng = ChaCha20Rng::from_seed([0_u8; 32]);
+
+        let ((mut leader, mut follower), (_leader_vm, _follower_vm)) = create_pair().await;
 
-        l_pms1 = l_pms1 + P256::one();
+        tokio::try_join!(leader.setup(), follower.setup()).unwrap();
 
         let err = tokio::try_join!(
-            leader.compute_pms_for(l_pms1, l_pms2),
-            follower.compute_pms_for(f_pms1, f_pms2)
+            leader.compute_client_key(SecretKey::random(NamedGroup::P256, &mut rng)),
+            follower.compute_client_key(SecretKey::random(NamedGroup::X25519, &mut rng))
         )
         .unwrap_err();
 
-        assert!(matches!(err, KeyExchangeError::CheckFailed));
+        assert!(matches!(err, KeyExchangeError::GroupMismatch(..)));
     }
 
     async fn perform_key_exchange(
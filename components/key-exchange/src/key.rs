@@ -0,0 +1,347 @@
+//! This module provides the keys and curve points of the supported key exchange groups
+
+use ark_curve25519::{EdwardsAffine, EdwardsConfig, EdwardsProjective, Fq, Fr};
+use ark_ec::{twisted_edwards::TECurveConfig, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, One, PrimeField, UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{msg::KeyParseError, KeyExchangeError};
+
+/// A named group for the ephemeral (EC)DH key exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NamedGroup {
+    /// NIST P-256, also known as secp256r1
+    P256,
+    /// NIST P-384, also known as secp384r1
+    P384,
+    /// X25519, ECDH on Curve25519
+    X25519,
+}
+
+impl NamedGroup {
+    /// Returns the length of the shared secret in bytes
+    pub fn secret_len(&self) -> usize {
+        match self {
+            NamedGroup::P256 | NamedGroup::X25519 => 32,
+            NamedGroup::P384 => 48,
+        }
+    }
+}
+
+/// A public key in the encoding used by TLS
+///
+/// Keys of the NIST curves are SEC1 encoded uncompressed points, X25519 keys are the 32-byte
+/// little-endian u-coordinate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    group: NamedGroup,
+    key: Vec<u8>,
+}
+
+impl PublicKey {
+    /// Creates a new public key
+    pub fn new(group: NamedGroup, key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            group,
+            key: key.into(),
+        }
+    }
+
+    /// Returns the group of the key
+    pub fn group(&self) -> NamedGroup {
+        self.group
+    }
+
+    /// Returns the encoded key
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+}
+
+/// An ephemeral private key of one of the supported groups
+#[derive(Clone)]
+pub enum SecretKey {
+    /// A P-256 private key
+    P256(p256::SecretKey),
+    /// A P-384 private key
+    P384(p384::SecretKey),
+    /// An X25519 private key, as a scalar of the prime order subgroup
+    X25519(Fr),
+}
+
+impl std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SecretKey").field(&self.group()).finish()
+    }
+}
+
+impl SecretKey {
+    /// Generates a random private key for the given group
+    pub fn random(group: NamedGroup, rng: &mut (impl CryptoRng + RngCore)) -> Self {
+        match group {
+            NamedGroup::P256 => SecretKey::P256(p256::SecretKey::random(rng)),
+            NamedGroup::P384 => SecretKey::P384(p384::SecretKey::random(rng)),
+            NamedGroup::X25519 => SecretKey::X25519(loop {
+                let scalar = Fr::rand(rng);
+                if !scalar.is_zero() {
+                    break scalar;
+                }
+            }),
+        }
+    }
+
+    /// Returns the group of the key
+    pub fn group(&self) -> NamedGroup {
+        match self {
+            SecretKey::P256(_) => NamedGroup::P256,
+            SecretKey::P384(_) => NamedGroup::P384,
+            SecretKey::X25519(_) => NamedGroup::X25519,
+        }
+    }
+
+    /// Returns the public key
+    pub fn public_key(&self) -> PublicKey {
+        Point::from_secret(self).to_public_key()
+    }
+}
+
+/// A curve point of one of the supported groups
+///
+/// Curve25519 points are kept in twisted Edwards form, as the Montgomery u-coordinate used by
+/// X25519 only determines a point up to its sign.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Point {
+    P256(p256::PublicKey),
+    P384(p384::PublicKey),
+    X25519(EdwardsAffine),
+}
+
+impl Point {
+    /// Returns the group of the point
+    pub(crate) fn group(&self) -> NamedGroup {
+        match self {
+            Point::P256(_) => NamedGroup::P256,
+            Point::P384(_) => NamedGroup::P384,
+            Point::X25519(_) => NamedGroup::X25519,
+        }
+    }
+
+    /// Returns the public point of a private key
+    pub(crate) fn from_secret(secret: &SecretKey) -> Self {
+        match secret {
+            SecretKey::P256(secret) => Point::P256(secret.public_key()),
+            SecretKey::P384(secret) => Point::P384(secret.public_key()),
+            SecretKey::X25519(secret) => {
+                Point::X25519((EdwardsAffine::generator() * secret).into_affine())
+            }
+        }
+    }
+
+    /// Adds two points of the same group
+    pub(crate) fn add(&self, other: &Point) -> Result<Point, KeyExchangeError> {
+        match (self, other) {
+            (Point::P256(a), Point::P256(b)) => Ok(Point::P256(p256::PublicKey::from_affine(
+                (a.to_projective() + b.to_projective()).to_affine(),
+            )?)),
+            (Point::P384(a), Point::P384(b)) => Ok(Point::P384(p384::PublicKey::from_affine(
+                (a.to_projective() + b.to_projective()).to_affine(),
+            )?)),
+            (Point::X25519(a), Point::X25519(b)) => Ok(Point::X25519(
+                (EdwardsProjective::from(*a) + b).into_affine(),
+            )),
+            _ => Err(KeyExchangeError::GroupMismatch(self.group(), other.group())),
+        }
+    }
+
+    /// Multiplies the point by a private key of the same group
+    ///
+    /// This mimics the diffie-hellman function without hashing or truncating the result, because
+    /// we need the shared secret as a curve point.
+    pub(crate) fn mul(&self, secret: &SecretKey) -> Result<Point, KeyExchangeError> {
+        match (self, secret) {
+            (Point::P256(point), SecretKey::P256(secret)) => {
+                Ok(Point::P256(p256::PublicKey::from_affine(
+                    (point.to_projective() * secret.to_nonzero_scalar().as_ref()).to_affine(),
+                )?))
+            }
+            (Point::P384(point), SecretKey::P384(secret)) => {
+                Ok(Point::P384(p384::PublicKey::from_affine(
+                    (point.to_projective() * secret.to_nonzero_scalar().as_ref()).to_affine(),
+                )?))
+            }
+            (Point::X25519(point), SecretKey::X25519(secret)) => {
+                Ok(Point::X25519((*point * secret).into_affine()))
+            }
+            _ => Err(KeyExchangeError::GroupMismatch(
+                self.group(),
+                secret.group(),
+            )),
+        }
+    }
+
+    /// Decodes a public key in the encoding used by TLS
+    ///
+    /// An X25519 key is lifted to the twisted Edwards point with an even x-coordinate.
+    pub(crate) fn from_public_key(key: &PublicKey) -> Result<Self, KeyParseError> {
+        match key.group {
+            NamedGroup::P256 => Ok(Point::P256(p256::PublicKey::from_sec1_bytes(&key.key)?)),
+            NamedGroup::P384 => Ok(Point::P384(p384::PublicKey::from_sec1_bytes(&key.key)?)),
+            NamedGroup::X25519 => {
+                let mut u: [u8; 32] = key
+                    .key
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| KeyParseError::from(p256::elliptic_curve::Error))?;
+
+                // RFC 7748 requires masking the most significant bit
+                u[31] &= 0x7f;
+
+                lift_x25519(Fq::from_le_bytes_mod_order(&u))
+                    .map(Point::X25519)
+                    .ok_or(KeyParseError::from(p256::elliptic_curve::Error))
+            }
+        }
+    }
+
+    /// Encodes the point as a public key in the encoding used by TLS
+    pub(crate) fn to_public_key(&self) -> PublicKey {
+        match self {
+            Point::P256(point) => {
+                PublicKey::new(NamedGroup::P256, point.to_encoded_point(false).as_bytes())
+            }
+            Point::P384(point) => {
+                PublicKey::new(NamedGroup::P384, point.to_encoded_point(false).as_bytes())
+            }
+            Point::X25519(point) => {
+                // u = (1 + y) / (1 - y), the identity is encoded as 0
+                let u = (Fq::one() - point.y)
+                    .inverse()
+                    .map(|inv| (Fq::one() + point.y) * inv)
+                    .unwrap_or_else(Fq::zero);
+
+                PublicKey::new(NamedGroup::X25519, u.into_bigint().to_bytes_le())
+            }
+        }
+    }
+
+    /// Serializes the point for the messages exchanged between leader and follower
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Point::P256(point) => point.to_encoded_point(false).as_bytes().to_vec(),
+            Point::P384(point) => point.to_encoded_point(false).as_bytes().to_vec(),
+            Point::X25519(point) => {
+                let mut bytes = Vec::new();
+                point
+                    .serialize_compressed(&mut bytes)
+                    .expect("point should serialize");
+                bytes
+            }
+        }
+    }
+
+    /// Deserializes a point from the messages exchanged between leader and follower
+    pub(crate) fn from_bytes(group: NamedGroup, bytes: &[u8]) -> Result<Self, KeyParseError> {
+        match group {
+            NamedGroup::P256 => Ok(Point::P256(p256::PublicKey::from_sec1_bytes(bytes)?)),
+            NamedGroup::P384 => Ok(Point::P384(p384::PublicKey::from_sec1_bytes(bytes)?)),
+            NamedGroup::X25519 => EdwardsAffine::deserialize_compressed(bytes)
+                .map(Point::X25519)
+                .map_err(|_| KeyParseError::from(p256::elliptic_curve::Error)),
+        }
+    }
+}
+
+/// Lifts a Montgomery u-coordinate to a point of the prime order subgroup in twisted Edwards
+/// form, returning `None` if there is no such point.
+fn lift_x25519(u: Fq) -> Option<EdwardsAffine> {
+    // y = (u - 1) / (u + 1)
+    let y = (u - Fq::one()) * (u + Fq::one()).inverse()?;
+
+    // -x^2 + y^2 = 1 + d * x^2 * y^2  =>  x^2 = (y^2 - 1) / (d * y^2 + 1)
+    let y2 = y.square();
+    let x2 = (y2 - Fq::one()) * (EdwardsConfig::COEFF_D * y2 + Fq::one()).inverse()?;
+    let mut x = x2.sqrt()?;
+
+    if x.into_bigint().is_odd() {
+        x = -x;
+    }
+
+    let point = EdwardsAffine::new_unchecked(x, y);
+
+    (point.is_on_curve()
+        && point.is_in_correct_subgroup_assuming_on_curve()
+        && !AffineRepr::is_zero(&point))
+    .then_some(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn test_x25519_public_key() {
+        let mut rng = ChaCha20Rng::from_seed([0_u8; 32]);
+
+        // The base point of edwards25519 is encoded as u = 9
+        let mut expected = [0u8; 32];
+        expected[0] = 9;
+        assert_eq!(
+            Point::X25519(EdwardsAffine::generator())
+                .to_public_key()
+                .key(),
+            expected
+        );
+
+        let secret = SecretKey::random(NamedGroup::X25519, &mut rng);
+        let point = Point::from_secret(&secret);
+        let public_key = point.to_public_key();
+
+        // Lifting the u-coordinate recovers the point up to its sign
+        let lifted = Point::from_public_key(&public_key).unwrap();
+        assert_eq!(lifted.to_public_key(), public_key);
+
+        let Point::X25519(lifted) = lifted else {
+            unreachable!()
+        };
+        let Point::X25519(point) = point else {
+            unreachable!()
+        };
+        assert!(lifted == point || lifted == -point);
+    }
+
+    #[test]
+    fn test_point_bytes() {
+        let mut rng = ChaCha20Rng::from_seed([0_u8; 32]);
+
+        for group in [NamedGroup::P256, NamedGroup::P384, NamedGroup::X25519] {
+            let point = Point::from_secret(&SecretKey::random(group, &mut rng));
+
+            assert_eq!(Point::from_bytes(group, &point.to_bytes()).unwrap(), point);
+            assert_eq!(
+                Point::from_public_key(&point.to_public_key())
+                    .unwrap()
+                    .to_public_key(),
+                point.to_public_key()
+            );
+        }
+    }
+
+    #[test]
+    fn test_group_mismatch() {
+        let mut rng = ChaCha20Rng::from_seed([0_u8; 32]);
+
+        let p256 = Point::from_secret(&SecretKey::random(NamedGroup::P256, &mut rng));
+        let x25519 = SecretKey::random(NamedGroup::X25519, &mut rng);
+
+        assert!(matches!(
+            p256.mul(&x25519).unwrap_err(),
+            KeyExchangeError::GroupMismatch(NamedGroup::P256, NamedGroup::X25519)
+        ));
+    }
+}
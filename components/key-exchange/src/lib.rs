@@ -16,6 +16,7 @@
 mod circuit;
 mod config;
 mod exchange;
+mod key;
 #[cfg(feature = "mock")]
 pub mod mock;
 pub mod msg;
@@ -24,6 +25,7 @@ pub use config::{
     KeyExchangeConfig, KeyExchangeConfigBuilder, KeyExchangeConfigBuilderError, Role,
 };
 pub use exchange::KeyExchangeCore;
+pub use key::{NamedGroup, PublicKey, SecretKey};
 pub use msg::KeyExchangeMessage;
 
 /// A channel for exchanging key exchange messages
@@ -31,10 +33,16 @@ pub type KeyExchangeChannel = Box<dyn Duplex<KeyExchangeMessage>>;
 
 use async_trait::async_trait;
 use mpz_garble::value::ValueRef;
-use p256::{PublicKey, SecretKey};
 use utils_aio::duplex::Duplex;
 
+/// Length of the PMS in bytes.
+///
+/// This is the length of a P-384 shared secret, shorter secrets are padded with trailing zeros.
+pub const PMS_LEN: usize = 48;
+
 /// Pre-master secret.
+///
+/// The shared secret in the encoding used by TLS, zero-padded to [`PMS_LEN`] bytes.
 #[derive(Debug, Clone)]
 pub struct Pms(ValueRef);
 
@@ -70,6 +78,10 @@ pub enum KeyExchangeError {
     PublicKey(#[from] p256::elliptic_curve::Error),
     #[error(transparent)]
     KeyParseError(#[from] msg::KeyParseError),
+    #[error("Unsupported key exchange group: {0:?}")]
+    UnsupportedGroup(NamedGroup),
+    #[error("Key exchange group mismatch: {0:?} and {1:?}")]
+    GroupMismatch(NamedGroup, NamedGroup),
     #[error("Server Key not set")]
     NoServerKey,
     #[error("Private key not set")]
@@ -97,7 +109,11 @@ pub trait KeyExchange {
     /// Compute the client's public key
     ///
     /// The client's public key in this context is the combined public key (EC point addition) of
-    /// the leader's public key and the follower's public key.
+    /// the leader's public key and the follower's public key. Both parties must provide a private
+    /// key of the same group.
+    ///
+    /// This may be called again with a key of another group, e.g. if the server selects a
+    /// different group than the one offered first.
     async fn compute_client_key(
         &mut self,
         private_key: SecretKey,
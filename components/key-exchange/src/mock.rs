@@ -4,24 +4,18 @@
 use crate::{KeyExchangeConfig, KeyExchangeCore, KeyExchangeMessage, Role};
 
 use mpz_garble::{Decode, Execute, Memory};
-use point_addition::mock::{
-    mock_point_converter_pair, MockPointAdditionReceiver, MockPointAdditionSender,
-};
+use point_addition::{mock::mock_point_converter_pair, Curve25519, P256, P384};
 use utils_aio::duplex::MemoryDuplex;
 
 /// A mock key exchange instance
-pub type MockKeyExchange<E> =
-    KeyExchangeCore<MockPointAdditionSender, MockPointAdditionReceiver, E>;
+pub type MockKeyExchange<E> = KeyExchangeCore<E>;
 
-/// Create a mock pair of key exchange leader and follower
+/// Create a mock pair of key exchange leader and follower, supporting all groups
 pub fn create_mock_key_exchange_pair<E: Memory + Execute + Decode + Send>(
     id: &str,
     leader_executor: E,
     follower_executor: E,
 ) -> (MockKeyExchange<E>, MockKeyExchange<E>) {
-    let (leader_pa_sender, follower_pa_recvr) = mock_point_converter_pair(&format!("{}/pa/0", id));
-    let (follower_pa_sender, leader_pa_recvr) = mock_point_converter_pair(&format!("{}/pa/1", id));
-
     let (leader_channel, follower_channel) = MemoryDuplex::<KeyExchangeMessage>::new();
 
     let key_exchange_config_leader = KeyExchangeConfig::builder()
@@ -36,21 +30,38 @@ pub fn create_mock_key_exchange_pair<E: Memory + Execute + Decode + Send>(
         .build()
         .unwrap();
 
-    let leader = KeyExchangeCore::new(
+    let mut leader = KeyExchangeCore::new(
         Box::new(leader_channel),
-        leader_pa_sender,
-        leader_pa_recvr,
         leader_executor,
         key_exchange_config_leader,
     );
 
-    let follower = KeyExchangeCore::new(
+    let mut follower = KeyExchangeCore::new(
         Box::new(follower_channel),
-        follower_pa_sender,
-        follower_pa_recvr,
         follower_executor,
         key_exchange_config_follower,
     );
 
+    let (leader_pa_sender, follower_pa_recvr) =
+        mock_point_converter_pair::<P256>(&format!("{}/p256/pa/0", id));
+    let (follower_pa_sender, leader_pa_recvr) =
+        mock_point_converter_pair::<P256>(&format!("{}/p256/pa/1", id));
+    leader = leader.with_p256(leader_pa_sender, leader_pa_recvr);
+    follower = follower.with_p256(follower_pa_sender, follower_pa_recvr);
+
+    let (leader_pa_sender, follower_pa_recvr) =
+        mock_point_converter_pair::<P384>(&format!("{}/p384/pa/0", id));
+    let (follower_pa_sender, leader_pa_recvr) =
+        mock_point_converter_pair::<P384>(&format!("{}/p384/pa/1", id));
+    leader = leader.with_p384(leader_pa_sender, leader_pa_recvr);
+    follower = follower.with_p384(follower_pa_sender, follower_pa_recvr);
+
+    let (leader_pa_sender, follower_pa_recvr) =
+        mock_point_converter_pair::<Curve25519>(&format!("{}/x25519/pa/0", id));
+    let (follower_pa_sender, leader_pa_recvr) =
+        mock_point_converter_pair::<Curve25519>(&format!("{}/x25519/pa/1", id));
+    leader = leader.with_x25519(leader_pa_sender, leader_pa_recvr);
+    follower = follower.with_x25519(follower_pa_sender, follower_pa_recvr);
+
     (leader, follower)
 }
@@ -2,9 +2,10 @@
 
 use std::fmt::{self, Display, Formatter};
 
-use p256::{elliptic_curve::sec1::ToEncodedPoint, PublicKey as P256PublicKey};
 use serde::{Deserialize, Serialize};
 
+use crate::{key::Point, NamedGroup};
+
 /// A type for messages exchanged between user and notary during the key exchange protocol
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(missing_docs)]
@@ -16,10 +17,28 @@ pub enum KeyExchangeMessage {
 /// A wrapper for a serialized public key
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublicKey {
-    /// The sec1 serialized public key
+    /// The group of the key
+    pub group: NamedGroup,
+    /// The serialized public key
+    ///
+    /// Keys of the NIST curves are sec1 serialized, Curve25519 keys are compressed twisted
+    /// Edwards points.
     pub key: Vec<u8>,
 }
 
+impl PublicKey {
+    pub(crate) fn from_point(point: &Point) -> Self {
+        Self {
+            group: point.group(),
+            key: point.to_bytes(),
+        }
+    }
+
+    pub(crate) fn to_point(&self) -> Result<Point, KeyParseError> {
+        Point::from_bytes(self.group, &self.key)
+    }
+}
+
 /// An error that can occur during parsing of a public key
 #[derive(Debug, thiserror::Error)]
 pub struct KeyParseError(#[from] p256::elliptic_curve::Error);
@@ -29,18 +48,3 @@ impl Display for KeyParseError {
         write!(f, "Unable to parse public key: {}", self.0)
     }
 }
-
-impl From<P256PublicKey> for PublicKey {
-    fn from(value: P256PublicKey) -> Self {
-        let key = value.to_encoded_point(false).as_bytes().to_vec();
-        PublicKey { key }
-    }
-}
-
-impl TryFrom<PublicKey> for P256PublicKey {
-    type Error = KeyParseError;
-
-    fn try_from(value: PublicKey) -> Result<Self, Self::Error> {
-        P256PublicKey::from_sec1_bytes(&value.key).map_err(Into::into)
-    }
-}
@@ -7,12 +7,20 @@
 
 use std::marker::PhantomData;
 
-use super::{PointAddition, PointAdditionError};
+use super::{
+    fields::{Curve25519, P384},
+    PointAddition, PointAdditionError,
+};
+use ark_curve25519::{EdwardsAffine, Fq as Fq25519};
+use ark_ff::{Field as _, One, Zero};
 use async_trait::async_trait;
 use mpz_share_conversion::ShareConversion;
 use mpz_share_conversion_core::fields::{p256::P256, Field};
 use p256::EncodedPoint;
 
+/// The coefficient `A` of the Montgomery form of Curve25519, `v^2 = u^3 + A * u^2 + u`.
+const MONTGOMERY_A: u64 = 486662;
+
 /// The instance used for adding the curve points
 #[derive(Debug)]
 pub struct MpcPointAddition<F, C>
@@ -111,6 +119,51 @@ where
     }
 }
 
+#[async_trait]
+impl<C> PointAddition for MpcPointAddition<P384, C>
+where
+    C: ShareConversion<P384> + Send + Sync + std::fmt::Debug,
+{
+    type Point = p384::EncodedPoint;
+    type XCoordinate = P384;
+
+    async fn compute_x_coordinate_share(
+        &mut self,
+        point: Self::Point,
+    ) -> Result<Self::XCoordinate, PointAdditionError> {
+        let [x, y] = point_to_p384(point)?;
+        self.convert([x, y]).await
+    }
+}
+
+/// Point addition on Curve25519, as used by X25519.
+///
+/// The points are provided in twisted Edwards form and are added on the birationally equivalent
+/// Montgomery curve, so that the shares are of the Montgomery u-coordinate.
+#[async_trait]
+impl<C> PointAddition for MpcPointAddition<Curve25519, C>
+where
+    C: ShareConversion<Curve25519> + Send + Sync + std::fmt::Debug,
+{
+    type Point = EdwardsAffine;
+    type XCoordinate = Curve25519;
+
+    async fn compute_x_coordinate_share(
+        &mut self,
+        point: Self::Point,
+    ) -> Result<Self::XCoordinate, PointAdditionError> {
+        let [u, v] = edwards_to_montgomery(point)?;
+        let u_r = self.convert([u, v]).await?;
+
+        // On the Montgomery curve the u-coordinate of the sum is `λ^2 - A - u_1 - u_2`, so one
+        // party also has to subtract `A`.
+        match self.role {
+            Role::Leader => Ok(u_r + -Curve25519(Fq25519::from(MONTGOMERY_A))),
+            Role::Follower => Ok(u_r),
+        }
+    }
+}
+
 /// Convert the external library's point type to our library's field type
 #[cfg_attr(
     feature = "tracing",
@@ -129,3 +182,50 @@ pub(crate) fn point_to_p256(point: EncodedPoint) -> Result<[P256; 2], PointAddit
 
     Ok([x, y])
 }
+
+/// Convert a P-384 point to our library's field type
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(point), err)
+)]
+pub(crate) fn point_to_p384(point: p384::EncodedPoint) -> Result<[P384; 2], PointAdditionError> {
+    let mut x: [u8; 48] = (*point.x().ok_or(PointAdditionError::Coordinates)?).into();
+    let mut y: [u8; 48] = (*point.y().ok_or(PointAdditionError::Coordinates)?).into();
+
+    // reverse to little endian
+    x.reverse();
+    y.reverse();
+
+    let x = P384::try_from(x).unwrap();
+    let y = P384::try_from(y).unwrap();
+
+    Ok([x, y])
+}
+
+/// Convert a twisted Edwards point of Curve25519 to the affine coordinates `[u, v]` of the
+/// Montgomery curve `v^2 = u^3 + A * u^2 + u`
+///
+/// Uses the map `u = (1 + y) / (1 - y)`, `v = sqrt(-(A + 2)) * u / x` from RFC 7748.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(point), err)
+)]
+pub(crate) fn edwards_to_montgomery(
+    point: EdwardsAffine,
+) -> Result<[Curve25519; 2], PointAdditionError> {
+    let one = Fq25519::one();
+
+    // The identity and the point of order 2 have no affine Montgomery representation
+    if point.x.is_zero() || point.y == one {
+        return Err(PointAdditionError::Coordinates);
+    }
+
+    let scale = (-Fq25519::from(MONTGOMERY_A + 2))
+        .sqrt()
+        .expect("-(A + 2) is a square");
+
+    let u = (one + point.y) * (one - point.y).inverse().expect("y is not 1");
+    let v = scale * u * point.x.inverse().expect("x is not 0");
+
+    Ok([Curve25519(u), Curve25519(v)])
+}
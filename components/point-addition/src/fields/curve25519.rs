@@ -0,0 +1,20 @@
+//! The base field of Curve25519, GF(2^255 - 19).
+
+use std::ops::{Add, Mul, Neg};
+
+use ark_curve25519::Fq;
+use ark_ff::{BigInteger, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use itybity::{BitLength, FromBitIterator, GetBit, Lsb0, Msb0};
+use mpz_share_conversion_core::fields::Field;
+use rand::distributions::{Distribution, Standard};
+use serde::{Deserialize, Serialize};
+
+use super::impl_field;
+
+/// An element of the base field of Curve25519
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "Vec<u8>", try_from = "Vec<u8>")]
+pub struct Curve25519(pub(crate) Fq);
+
+impl_field!(Curve25519, 32);
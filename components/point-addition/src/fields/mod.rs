@@ -0,0 +1,195 @@
+//! Finite fields underlying the supported elliptic curves, in addition to the
+//! [P256](mpz_share_conversion_core::fields::p256::P256) field provided by `mpz`.
+
+mod curve25519;
+mod p384;
+
+pub use curve25519::Curve25519;
+pub use p384::P384;
+
+/// An error that can occur when decoding a field element
+#[derive(Debug, thiserror::Error)]
+#[error("invalid field element")]
+pub struct FieldError;
+
+/// Implements [`Field`](mpz_share_conversion_core::fields::Field) for a newtype around an
+/// `ark-ff` prime field element which is serialized as `$len` little-endian bytes.
+macro_rules! impl_field {
+    ($name:ident, $len:expr) => {
+        impl From<$name> for Vec<u8> {
+            fn from(value: $name) -> Self {
+                let mut bytes = vec![0_u8; $len];
+                value
+                    .0
+                    .serialize_uncompressed(&mut bytes[..])
+                    .expect("field element should serialize");
+                bytes
+            }
+        }
+
+        impl TryFrom<Vec<u8>> for $name {
+            type Error = $crate::fields::FieldError;
+
+            fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+                if value.len() != $len {
+                    return Err($crate::fields::FieldError);
+                }
+
+                Fq::deserialize_uncompressed(&value[..])
+                    .map($name)
+                    .map_err(|_| $crate::fields::FieldError)
+            }
+        }
+
+        impl TryFrom<[u8; $len]> for $name {
+            type Error = $crate::fields::FieldError;
+
+            fn try_from(value: [u8; $len]) -> Result<Self, Self::Error> {
+                value.to_vec().try_into()
+            }
+        }
+
+        impl Distribution<$name> for Standard {
+            fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> $name {
+                $name(<Fq as ark_ff::UniformRand>::rand(rng))
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self::Output {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl Mul for $name {
+            type Output = Self;
+
+            fn mul(self, rhs: Self) -> Self::Output {
+                Self(self.0 * rhs.0)
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+
+            fn neg(self) -> Self::Output {
+                Self(-self.0)
+            }
+        }
+
+        impl Field for $name {
+            const BIT_SIZE: u32 = $len * 8;
+
+            fn zero() -> Self {
+                Self(<Fq as ark_ff::Zero>::zero())
+            }
+
+            fn one() -> Self {
+                Self(<Fq as ark_ff::One>::one())
+            }
+
+            fn two_pow(rhs: u32) -> Self {
+                let mut out = <Fq as ark_ff::One>::one();
+                for _ in 0..rhs {
+                    ark_ff::Field::double_in_place(&mut out);
+                }
+                Self(out)
+            }
+
+            fn inverse(self) -> Self {
+                Self(ark_ff::Field::inverse(&self.0).expect("Unable to invert field element"))
+            }
+
+            fn to_le_bytes(&self) -> Vec<u8> {
+                (*self).into()
+            }
+
+            fn to_be_bytes(&self) -> Vec<u8> {
+                let mut bytes = self.to_le_bytes();
+                bytes.reverse();
+                bytes
+            }
+        }
+
+        impl BitLength for $name {
+            const BITS: usize = $len * 8;
+        }
+
+        impl GetBit<Lsb0> for $name {
+            fn get_bit(&self, index: usize) -> bool {
+                self.0.into_bigint().get_bit(index)
+            }
+        }
+
+        impl GetBit<Msb0> for $name {
+            fn get_bit(&self, index: usize) -> bool {
+                self.0.into_bigint().get_bit($len * 8 - 1 - index)
+            }
+        }
+
+        impl FromBitIterator for $name {
+            fn from_lsb0_iter(iter: impl IntoIterator<Item = bool>) -> Self {
+                let bits: Vec<bool> = iter.into_iter().collect();
+                let int = <<Fq as PrimeField>::BigInt as BigInteger>::from_bits_le(&bits);
+                Self(Fq::from_bigint(int).expect("bits are a valid field element"))
+            }
+
+            fn from_msb0_iter(iter: impl IntoIterator<Item = bool>) -> Self {
+                let bits: Vec<bool> = iter.into_iter().collect();
+                let int = <<Fq as PrimeField>::BigInt as BigInteger>::from_bits_be(&bits);
+                Self(Fq::from_bigint(int).expect("bits are a valid field element"))
+            }
+        }
+    };
+}
+
+pub(crate) use impl_field;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use itybity::{BitLength, FromBitIterator, GetBit, Lsb0, Msb0};
+    use mpz_share_conversion_core::fields::Field;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha12Rng;
+
+    fn test_field<F>()
+    where
+        F: Field + BitLength + GetBit<Lsb0> + GetBit<Msb0> + FromBitIterator,
+        rand::distributions::Standard: rand::distributions::Distribution<F>,
+    {
+        let mut rng = ChaCha12Rng::from_seed([0_u8; 32]);
+
+        let a: F = rng.gen();
+        let b: F = rng.gen();
+
+        assert_eq!(a + -a, F::zero());
+        assert_eq!(a * a.inverse(), F::one());
+        assert_eq!(a * (b + F::one()), a * b + a);
+        let two = F::one() + F::one();
+        assert_eq!(F::two_pow(3), two * two * two);
+
+        let mut be = a.to_be_bytes();
+        be.reverse();
+        assert_eq!(be, a.to_le_bytes());
+
+        let lsb0 = (0..F::BITS).map(|i| GetBit::<Lsb0>::get_bit(&a, i));
+        assert_eq!(F::from_lsb0_iter(lsb0), a);
+
+        let msb0 = (0..F::BITS).map(|i| GetBit::<Msb0>::get_bit(&a, i));
+        assert_eq!(F::from_msb0_iter(msb0), a);
+    }
+
+    #[test]
+    fn test_p384() {
+        test_field::<P384>();
+    }
+
+    #[test]
+    fn test_curve25519() {
+        test_field::<Curve25519>();
+    }
+}
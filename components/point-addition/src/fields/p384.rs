@@ -0,0 +1,20 @@
+//! The base field of the NIST P-384 curve.
+
+use std::ops::{Add, Mul, Neg};
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_secp384r1::Fq;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use itybity::{BitLength, FromBitIterator, GetBit, Lsb0, Msb0};
+use mpz_share_conversion_core::fields::Field;
+use rand::distributions::{Distribution, Standard};
+use serde::{Deserialize, Serialize};
+
+use super::impl_field;
+
+/// An element of the base field of P-384
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "Vec<u8>", try_from = "Vec<u8>")]
+pub struct P384(pub(crate) Fq);
+
+impl_field!(P384, 48);
@@ -11,12 +11,14 @@ use mpz_share_conversion::ShareConversionError;
 use mpz_share_conversion_core::fields::Field;
 
 mod conversion;
+pub mod fields;
 
 /// A mock implementation of the [PointAddition] trait
 #[cfg(feature = "mock")]
 pub mod mock;
 
 pub use conversion::{MpcPointAddition, Role};
+pub use fields::{Curve25519, P384};
 pub use mpz_share_conversion_core::fields::p256::P256;
 
 /// The error type for [PointAddition]
@@ -50,7 +52,15 @@ pub trait PointAddition {
 
 #[cfg(test)]
 mod tests {
-    use crate::{conversion::point_to_p256, mock::mock_point_converter_pair, PointAddition};
+    use crate::{
+        conversion::{edwards_to_montgomery, point_to_p256, point_to_p384},
+        fields::{Curve25519, P384},
+        mock::mock_point_converter_pair,
+        PointAddition,
+    };
+    use ark_curve25519::{EdwardsAffine, EdwardsProjective, Fr};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::UniformRand;
     use mpz_core::Block;
     use mpz_share_conversion_core::{fields::p256::P256, Field};
     use p256::{
@@ -72,7 +82,7 @@ mod tests {
 
         let p = add_curve_points(&p1, &p2);
 
-        let (mut c1, mut c2) = mock_point_converter_pair("test");
+        let (mut c1, mut c2) = mock_point_converter_pair::<P256>("test");
 
         let c1_fut = c1.compute_x_coordinate_share(p1);
         let c2_fut = c2.compute_x_coordinate_share(p2);
@@ -100,6 +110,71 @@ mod tests {
         assert_eq!(p_expected, p);
     }
 
+    #[tokio::test]
+    async fn test_point_conversion_p384() {
+        let mut rng = ChaCha12Rng::from_seed([0_u8; 32]);
+
+        let p1 = p384::PublicKey::from_secret_scalar(&p384::NonZeroScalar::random(&mut rng));
+        let p2 = p384::PublicKey::from_secret_scalar(&p384::NonZeroScalar::random(&mut rng));
+
+        let p = p384::PublicKey::from_affine((p1.to_projective() + p2.to_projective()).to_affine())
+            .unwrap();
+
+        let (mut c1, mut c2) = mock_point_converter_pair::<P384>("test");
+
+        let (c1_output, c2_output) = tokio::join!(
+            c1.compute_x_coordinate_share(p1.to_encoded_point(false)),
+            c2.compute_x_coordinate_share(p2.to_encoded_point(false))
+        );
+        let (c1_output, c2_output) = (c1_output.unwrap(), c2_output.unwrap());
+
+        assert_eq!(
+            point_to_p384(p.to_encoded_point(false)).unwrap()[0],
+            c1_output + c2_output
+        );
+    }
+
+    #[tokio::test]
+    async fn test_point_conversion_curve25519() {
+        let mut rng = ChaCha12Rng::from_seed([0_u8; 32]);
+
+        let generator = EdwardsAffine::generator();
+        let p1 = (generator * Fr::rand(&mut rng)).into_affine();
+        let p2 = (generator * Fr::rand(&mut rng)).into_affine();
+
+        let p = (EdwardsProjective::from(p1) + p2).into_affine();
+
+        let (mut c1, mut c2) = mock_point_converter_pair::<Curve25519>("test");
+
+        let (c1_output, c2_output) = tokio::join!(
+            c1.compute_x_coordinate_share(p1),
+            c2.compute_x_coordinate_share(p2)
+        );
+        let (c1_output, c2_output) = (c1_output.unwrap(), c2_output.unwrap());
+
+        assert_eq!(edwards_to_montgomery(p).unwrap()[0], c1_output + c2_output);
+    }
+
+    #[test]
+    fn test_edwards_to_montgomery() {
+        // The base point of edwards25519 maps to u = 9
+        let [u, v] = edwards_to_montgomery(EdwardsAffine::generator()).unwrap();
+
+        let mut expected_u = [0u8; 32];
+        expected_u[0] = 9;
+
+        assert_eq!(u.to_le_bytes(), expected_u.to_vec());
+
+        // The point is on the Montgomery curve v^2 = u^3 + A * u^2 + u
+        let a = Curve25519::try_from({
+            let mut a = [0u8; 32];
+            a[..3].copy_from_slice(&486662u32.to_le_bytes()[..3]);
+            a
+        })
+        .unwrap();
+        assert_eq!(v * v, u * u * u + a * u * u + u);
+    }
+
     fn curve_point_from_be_bytes(bytes: [u8; 32]) -> EncodedPoint {
         let scalar = NonZeroScalar::from_repr(bytes.into()).unwrap();
         let pk = PublicKey::from_secret_scalar(&scalar);
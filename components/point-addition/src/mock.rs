@@ -3,16 +3,18 @@ use mpz_share_conversion::{
     mock::{mock_converter_pair, MockConverterReceiver, MockConverterSender},
     ReceiverConfig, SenderConfig,
 };
-use mpz_share_conversion_core::fields::p256::P256;
+use mpz_share_conversion_core::fields::{p256::P256, Field};
 
-/// A mock point addition sender implementing [MpcPointAddition] for [P256]
-pub type MockPointAdditionSender = MpcPointAddition<P256, MockConverterSender<P256>>;
+/// A mock point addition sender implementing [MpcPointAddition] for a field, [P256] by default
+pub type MockPointAdditionSender<F = P256> = MpcPointAddition<F, MockConverterSender<F>>;
 
-/// A mock point addition receiver implementing [MpcPointAddition] for [P256]
-pub type MockPointAdditionReceiver = MpcPointAddition<P256, MockConverterReceiver<P256>>;
+/// A mock point addition receiver implementing [MpcPointAddition] for a field, [P256] by default
+pub type MockPointAdditionReceiver<F = P256> = MpcPointAddition<F, MockConverterReceiver<F>>;
 
-/// Create a pair of [MpcPointAddition] instances
-pub fn mock_point_converter_pair(id: &str) -> (MockPointAdditionSender, MockPointAdditionReceiver) {
+/// Create a pair of [MpcPointAddition] instances for the field `F`
+pub fn mock_point_converter_pair<F: Field>(
+    id: &str,
+) -> (MockPointAdditionSender<F>, MockPointAdditionReceiver<F>) {
     let (sender, receiver) = mock_converter_pair(
         SenderConfig::builder()
             .id(format!("{}/converter", id))
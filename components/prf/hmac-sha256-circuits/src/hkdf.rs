@@ -0,0 +1,177 @@
+//! This module provides an implementation of `HKDF-Expand-Label` defined in [RFC 8446](https://www.rfc-editor.org/rfc/rfc8446#section-7.1).
+
+use std::cell::RefCell;
+
+use mpz_circuits::{
+    types::{U32, U8},
+    BuilderState, Tracer,
+};
+
+use crate::hmac_sha256::{hmac_sha256_finalize, hmac_sha256_finalize_trace};
+
+/// Label prefix prepended to every label used in the TLS 1.3 key schedule.
+const LABEL_PREFIX: &[u8] = b"tls13 ";
+
+/// Returns the `HkdfLabel` structure, excluding the context, up to the context length byte.
+fn hkdf_label_prefix(label: &[u8], context_len: usize, len: usize) -> Vec<u8> {
+    assert!(len <= 32, "only a single HKDF block is supported");
+    assert!(context_len <= 255);
+
+    let mut info = Vec::with_capacity(4 + LABEL_PREFIX.len() + label.len());
+    info.extend_from_slice(&(len as u16).to_be_bytes());
+    info.push((LABEL_PREFIX.len() + label.len()) as u8);
+    info.extend_from_slice(LABEL_PREFIX);
+    info.extend_from_slice(label);
+    info.push(context_len as u8);
+    info
+}
+
+/// Computes `HKDF-Expand-Label(Secret, Label, Context, Length)` as specified in RFC 8446, Section 7.1.
+///
+/// The secret is provided as the outer and inner HMAC states.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `outer_state`     - The outer HMAC state of the secret
+/// * `inner_state`     - The inner HMAC state of the secret
+/// * `label`           - The label, without the "tls13 " prefix
+/// * `context`         - The context
+/// * `len`             - The output length (must be <= 32 bytes)
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip(builder_state, outer_state, inner_state, context)
+    )
+)]
+pub fn hkdf_expand_label_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    outer_state: [Tracer<'a, U32>; 8],
+    inner_state: [Tracer<'a, U32>; 8],
+    label: &[u8],
+    context: &[Tracer<'a, U8>],
+    len: usize,
+) -> Vec<Tracer<'a, U8>> {
+    let prefix = hkdf_label_prefix(label, context.len(), len);
+
+    let mut info: Vec<_> = prefix
+        .into_iter()
+        .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v)))
+        .collect();
+    info.extend_from_slice(context);
+    // T(1) = HMAC-Hash(PRK, info | 0x01)
+    info.push(Tracer::new(
+        builder_state,
+        builder_state.borrow_mut().get_constant(1u8),
+    ));
+
+    let okm = hmac_sha256_finalize_trace(builder_state, outer_state, inner_state, &info);
+
+    okm[..len].to_vec()
+}
+
+/// Reference implementation of `HKDF-Expand-Label(Secret, Label, Context, Length)` as specified in RFC 8446, Section 7.1.
+///
+/// # Arguments
+///
+/// * `outer_state` - The outer HMAC state of the secret
+/// * `inner_state` - The inner HMAC state of the secret
+/// * `label`       - The label, without the "tls13 " prefix
+/// * `context`     - The context
+/// * `len`         - The output length (must be <= 32 bytes)
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(outer_state, inner_state, context))
+)]
+pub fn hkdf_expand_label(
+    outer_state: [u32; 8],
+    inner_state: [u32; 8],
+    label: &[u8],
+    context: &[u8],
+    len: usize,
+) -> Vec<u8> {
+    let mut info = hkdf_label_prefix(label, context.len(), len);
+    info.extend_from_slice(context);
+    info.push(1);
+
+    let okm = hmac_sha256_finalize(outer_state, inner_state, &info);
+
+    okm[..len].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::{evaluate, CircuitBuilder};
+
+    use crate::hmac_sha256::hmac_sha256_partial;
+
+    use super::*;
+
+    struct Len(usize);
+
+    impl ring::hkdf::KeyType for Len {
+        fn len(&self) -> usize {
+            self.0
+        }
+    }
+
+    fn ring_hkdf_expand_label(secret: &[u8], label: &[u8], context: &[u8], len: usize) -> Vec<u8> {
+        let prk = ring::hkdf::Prk::new_less_safe(ring::hkdf::HKDF_SHA256, secret);
+
+        let mut info = hkdf_label_prefix(label, context.len(), len);
+        info.extend_from_slice(context);
+
+        let mut out = vec![0u8; len];
+        prk.expand(&[&info], Len(len))
+            .unwrap()
+            .fill(&mut out)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_hkdf_expand_label_reference() {
+        let secret = [42u8; 32];
+        let (outer_state, inner_state) = hmac_sha256_partial(&secret);
+
+        for (label, context, len) in [
+            (b"key".as_slice(), [].as_slice(), 16),
+            (b"iv".as_slice(), [].as_slice(), 12),
+            (b"c hs traffic".as_slice(), [69u8; 32].as_slice(), 32),
+        ] {
+            assert_eq!(
+                hkdf_expand_label(outer_state, inner_state, label, context, len),
+                ring_hkdf_expand_label(&secret, label, context, len)
+            );
+        }
+    }
+
+    #[test]
+    fn test_hkdf_expand_label() {
+        let builder = CircuitBuilder::new();
+        let outer_state = builder.add_array_input::<u32, 8>();
+        let inner_state = builder.add_array_input::<u32, 8>();
+        let context = builder.add_array_input::<u8, 32>();
+        let okm = hkdf_expand_label_trace(
+            builder.state(),
+            outer_state,
+            inner_state,
+            b"s hs traffic",
+            &context,
+            32,
+        );
+        let okm: [_; 32] = okm.try_into().unwrap();
+        builder.add_output(okm);
+        let circ = builder.build().unwrap();
+
+        let (outer_state, inner_state) = hmac_sha256_partial(&[1u8; 32]);
+        let context = [7u8; 32];
+
+        let expected = hkdf_expand_label(outer_state, inner_state, b"s hs traffic", &context, 32);
+
+        let actual = evaluate!(circ, fn(outer_state, inner_state, context) -> [u8; 32]).unwrap();
+
+        assert_eq!(actual.to_vec(), expected);
+    }
+}
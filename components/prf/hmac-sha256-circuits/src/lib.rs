@@ -1,61 +1,207 @@
-//! HMAC-SHA256 circuits.
+//! HMAC-SHA256 and HMAC-SHA384 circuits.
 
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
+mod hkdf;
 mod hmac_sha256;
+mod hmac_sha384;
 mod prf;
 mod session_keys;
+mod sha384;
+mod tls13;
 mod verify_data;
 
+pub use hkdf::{hkdf_expand_label, hkdf_expand_label_trace};
 pub use hmac_sha256::{
     hmac_sha256_finalize, hmac_sha256_finalize_trace, hmac_sha256_partial,
     hmac_sha256_partial_trace,
 };
+pub use hmac_sha384::{
+    hmac_sha384_finalize, hmac_sha384_finalize_trace, hmac_sha384_partial,
+    hmac_sha384_partial_trace,
+};
 
-pub use prf::{prf, prf_trace};
-pub use session_keys::{session_keys, session_keys_trace};
-pub use verify_data::{verify_data, verify_data_trace};
+pub use prf::{prf, prf_sha384, prf_sha384_trace, prf_trace};
+pub use session_keys::{
+    session_keys, session_keys_sha256, session_keys_sha256_trace, session_keys_sha384,
+    session_keys_sha384_trace, session_keys_trace,
+};
+pub use tls13::{
+    application_keys, application_keys_trace, handshake_keys, handshake_keys_trace,
+    tls13_verify_data, tls13_verify_data_trace,
+};
+pub use verify_data::{
+    verify_data, verify_data_sha384, verify_data_sha384_trace, verify_data_trace,
+};
 
 use mpz_circuits::{Circuit, CircuitBuilder, Tracer};
 use std::sync::Arc;
 
+/// Length of the pre-master secret input of the key derivation circuits.
+///
+/// This is the length of a P-384 shared secret. Shorter secrets are padded with trailing zeros,
+/// which does not change the HMAC output as HMAC keys are zero-padded to the hash block size.
+/// The TLS 1.3 key schedule only uses the first 32 bytes.
+pub const PMS_LEN: usize = 48;
+
+/// The hash function of the TLS 1.2 PRF, which is determined by the cipher suite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrfHash {
+    /// HMAC-SHA256
+    Sha256,
+    /// HMAC-SHA384
+    Sha384,
+}
+
+impl PrfHash {
+    /// Returns the length of the handshake hash.
+    pub fn hash_len(&self) -> usize {
+        match self {
+            PrfHash::Sha256 => 32,
+            PrfHash::Sha384 => 48,
+        }
+    }
+}
+
 /// Builds session key derivation circuit.
+///
+/// The master secret HMAC states are output as `[u32; 8]` for SHA-256 and
+/// `[u64; 8]` for SHA-384.
+///
+/// # Arguments
+///
+/// * `hash`    - The hash function of the PRF
+/// * `key_len` - Length of the write keys
+/// * `iv_len`  - Length of the fixed IVs
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
-pub fn build_session_keys() -> Arc<Circuit> {
+pub fn build_session_keys(hash: PrfHash, key_len: usize, iv_len: usize) -> Arc<Circuit> {
     let builder = CircuitBuilder::new();
-    let pms = builder.add_array_input::<u8, 32>();
+    let pms = builder.add_array_input::<u8, PMS_LEN>();
     let client_random = builder.add_array_input::<u8, 32>();
     let server_random = builder.add_array_input::<u8, 32>();
-    let (cwk, swk, civ, siv, outer_state, inner_state) =
-        session_keys_trace(builder.state(), pms, client_random, server_random);
+    match hash {
+        PrfHash::Sha256 => {
+            let (cwk, swk, civ, siv, outer_state, inner_state) = session_keys_sha256_trace(
+                builder.state(),
+                &pms,
+                client_random,
+                server_random,
+                key_len,
+                iv_len,
+            );
+            builder.add_output(cwk);
+            builder.add_output(swk);
+            builder.add_output(civ);
+            builder.add_output(siv);
+            builder.add_output(outer_state);
+            builder.add_output(inner_state);
+        }
+        PrfHash::Sha384 => {
+            let (cwk, swk, civ, siv, outer_state, inner_state) = session_keys_sha384_trace(
+                builder.state(),
+                &pms,
+                client_random,
+                server_random,
+                key_len,
+                iv_len,
+            );
+            builder.add_output(cwk);
+            builder.add_output(swk);
+            builder.add_output(civ);
+            builder.add_output(siv);
+            builder.add_output(outer_state);
+            builder.add_output(inner_state);
+        }
+    }
+    Arc::new(builder.build().expect("session keys should build"))
+}
+
+/// Builds a verify data circuit.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip(label)))]
+pub fn build_verify_data(hash: PrfHash, label: &[u8]) -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let label = label
+        .iter()
+        .map(|v| Tracer::new(builder.state(), builder.get_constant(*v).to_inner()))
+        .collect::<Vec<_>>();
+    match hash {
+        PrfHash::Sha256 => {
+            let outer_state = builder.add_array_input::<u32, 8>();
+            let inner_state = builder.add_array_input::<u32, 8>();
+            let handshake_hash = builder.add_array_input::<u8, 32>();
+            let vd = verify_data_trace(
+                builder.state(),
+                outer_state,
+                inner_state,
+                &label,
+                handshake_hash,
+            );
+            builder.add_output(vd);
+        }
+        PrfHash::Sha384 => {
+            let outer_state = builder.add_array_input::<u64, 8>();
+            let inner_state = builder.add_array_input::<u64, 8>();
+            let handshake_hash = builder.add_array_input::<u8, 48>();
+            let vd = verify_data_sha384_trace(
+                builder.state(),
+                outer_state,
+                inner_state,
+                &label,
+                handshake_hash,
+            );
+            builder.add_output(vd);
+        }
+    }
+    Arc::new(builder.build().expect("verify data should build"))
+}
+
+/// Builds the TLS 1.3 handshake key derivation circuit.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
+pub fn build_handshake_keys() -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let pms = builder.add_array_input::<u8, PMS_LEN>();
+    let hello_hash = builder.add_array_input::<u8, 32>();
+    let (cwk, swk, civ, siv, cf_outer, cf_inner, sf_outer, sf_inner, ms_outer, ms_inner) =
+        handshake_keys_trace(builder.state(), pms[..32].try_into().unwrap(), hello_hash);
     builder.add_output(cwk);
     builder.add_output(swk);
     builder.add_output(civ);
     builder.add_output(siv);
-    builder.add_output(outer_state);
-    builder.add_output(inner_state);
-    Arc::new(builder.build().expect("session keys should build"))
+    builder.add_output(cf_outer);
+    builder.add_output(cf_inner);
+    builder.add_output(sf_outer);
+    builder.add_output(sf_inner);
+    builder.add_output(ms_outer);
+    builder.add_output(ms_inner);
+    Arc::new(builder.build().expect("handshake keys should build"))
 }
 
-/// Builds a verify data circuit.
-#[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip(label)))]
-pub fn build_verify_data(label: &[u8]) -> Arc<Circuit> {
+/// Builds the TLS 1.3 application key derivation circuit.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
+pub fn build_application_keys() -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let outer_state = builder.add_array_input::<u32, 8>();
+    let inner_state = builder.add_array_input::<u32, 8>();
+    let hs_hash = builder.add_array_input::<u8, 32>();
+    let (cwk, swk, civ, siv) =
+        application_keys_trace(builder.state(), outer_state, inner_state, hs_hash);
+    builder.add_output(cwk);
+    builder.add_output(swk);
+    builder.add_output(civ);
+    builder.add_output(siv);
+    Arc::new(builder.build().expect("application keys should build"))
+}
+
+/// Builds a TLS 1.3 verify data circuit.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
+pub fn build_tls13_verify_data() -> Arc<Circuit> {
     let builder = CircuitBuilder::new();
     let outer_state = builder.add_array_input::<u32, 8>();
     let inner_state = builder.add_array_input::<u32, 8>();
-    let handshake_hash = builder.add_array_input::<u8, 32>();
-    let vd = verify_data_trace(
-        builder.state(),
-        outer_state,
-        inner_state,
-        &label
-            .iter()
-            .map(|v| Tracer::new(builder.state(), builder.get_constant(*v).to_inner()))
-            .collect::<Vec<_>>(),
-        handshake_hash,
-    );
+    let hs_hash = builder.add_array_input::<u8, 32>();
+    let vd = tls13_verify_data_trace(builder.state(), outer_state, inner_state, hs_hash);
     builder.add_output(vd);
     Arc::new(builder.build().expect("verify data should build"))
 }
@@ -1,22 +1,20 @@
-//! This module provides an implementation of the HMAC-SHA256 PRF defined in [RFC 5246](https://www.rfc-editor.org/rfc/rfc5246#section-5).
+//! This module provides an implementation of the HMAC-SHA256 and HMAC-SHA384 PRFs defined in [RFC 5246](https://www.rfc-editor.org/rfc/rfc5246#section-5).
 
 use std::cell::RefCell;
 
 use mpz_circuits::{
-    types::{U32, U8},
+    types::{U32, U64, U8},
     BuilderState, Tracer,
 };
 
-use crate::hmac_sha256::{hmac_sha256_finalize, hmac_sha256_finalize_trace};
+use crate::{
+    hmac_sha256::{hmac_sha256_finalize, hmac_sha256_finalize_trace},
+    hmac_sha384::{hmac_sha384_finalize, hmac_sha384_finalize_trace},
+};
 
-#[cfg_attr(
-    feature = "tracing",
-    tracing::instrument(level = "trace", skip(builder_state, outer_state, inner_state, seed))
-)]
+/// Computes P_hash(secret, seed) using the provided HMAC function, which is keyed with the secret.
 fn p_hash_trace<'a>(
-    builder_state: &'a RefCell<BuilderState>,
-    outer_state: [Tracer<'a, U32>; 8],
-    inner_state: [Tracer<'a, U32>; 8],
+    hmac: impl Fn(&[Tracer<'a, U8>]) -> Vec<Tracer<'a, U8>>,
     seed: &[Tracer<'a, U8>],
     iterations: usize,
 ) -> Vec<Tracer<'a, U8>> {
@@ -28,28 +26,26 @@ fn p_hash_trace<'a>(
     a_cache.push(seed.to_vec());
 
     for i in 0..iterations {
-        let a_i = hmac_sha256_finalize_trace(builder_state, outer_state, inner_state, &a_cache[i]);
-        a_cache.push(a_i.to_vec());
+        let a_i = hmac(&a_cache[i]);
+        a_cache.push(a_i);
     }
 
     // HMAC_hash(secret, A(i) + seed)
-    let mut output: Vec<_> = Vec::with_capacity(iterations * 32);
+    let mut output: Vec<_> = Vec::new();
     for i in 0..iterations {
         let mut a_i_seed = a_cache[i + 1].clone();
         a_i_seed.extend_from_slice(seed);
 
-        let hash = hmac_sha256_finalize_trace(builder_state, outer_state, inner_state, &a_i_seed);
+        let hash = hmac(&a_i_seed);
         output.extend_from_slice(&hash);
     }
 
     output
 }
 
-#[cfg_attr(
-    feature = "tracing",
-    tracing::instrument(level = "trace", skip(outer_state, inner_state, seed))
-)]
-fn p_hash(outer_state: [u32; 8], inner_state: [u32; 8], seed: &[u8], iterations: usize) -> Vec<u8> {
+/// Reference implementation of P_hash(secret, seed) using the provided HMAC function, which is
+/// keyed with the secret.
+fn p_hash(hmac: impl Fn(&[u8]) -> Vec<u8>, seed: &[u8], iterations: usize) -> Vec<u8> {
     // A() is defined as:
     //
     // A(0) = seed
@@ -58,17 +54,17 @@ fn p_hash(outer_state: [u32; 8], inner_state: [u32; 8], seed: &[u8], iterations:
     a_cache.push(seed.to_vec());
 
     for i in 0..iterations {
-        let a_i = hmac_sha256_finalize(outer_state, inner_state, &a_cache[i]);
-        a_cache.push(a_i.to_vec());
+        let a_i = hmac(&a_cache[i]);
+        a_cache.push(a_i);
     }
 
     // HMAC_hash(secret, A(i) + seed)
-    let mut output: Vec<_> = Vec::with_capacity(iterations * 32);
+    let mut output: Vec<_> = Vec::new();
     for i in 0..iterations {
         let mut a_i_seed = a_cache[i + 1].clone();
         a_i_seed.extend_from_slice(seed);
 
-        let hash = hmac_sha256_finalize(outer_state, inner_state, &a_i_seed);
+        let hash = hmac(&a_i_seed);
         output.extend_from_slice(&hash);
     }
 
@@ -105,9 +101,7 @@ pub fn prf_trace<'a>(
     label_seed.extend_from_slice(seed);
 
     let mut output = p_hash_trace(
-        builder_state,
-        outer_state,
-        inner_state,
+        |msg| hmac_sha256_finalize_trace(builder_state, outer_state, inner_state, msg).to_vec(),
         &label_seed,
         iterations,
     );
@@ -140,7 +134,84 @@ pub fn prf(
     let mut label_seed = label.to_vec();
     label_seed.extend_from_slice(seed);
 
-    let mut output = p_hash(outer_state, inner_state, &label_seed, iterations);
+    let mut output = p_hash(
+        |msg| hmac_sha256_finalize(outer_state, inner_state, msg).to_vec(),
+        &label_seed,
+        iterations,
+    );
+    output.truncate(bytes);
+
+    output
+}
+
+/// Computes PRF(secret, label, seed) with HMAC-SHA384, as used by the `_SHA384` cipher suites.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state.
+/// * `outer_state`     - The outer state of HMAC-SHA384
+/// * `inner_state`     - The inner state of HMAC-SHA384
+/// * `seed`            - The seed to use
+/// * `label`           - The label to use
+/// * `bytes`           - The number of bytes to output
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip(builder_state, outer_state, inner_state, seed, label)
+    )
+)]
+pub fn prf_sha384_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    outer_state: [Tracer<'a, U64>; 8],
+    inner_state: [Tracer<'a, U64>; 8],
+    seed: &[Tracer<'a, U8>],
+    label: &[Tracer<'a, U8>],
+    bytes: usize,
+) -> Vec<Tracer<'a, U8>> {
+    let iterations = bytes / 48 + (bytes % 48 != 0) as usize;
+    let mut label_seed = label.to_vec();
+    label_seed.extend_from_slice(seed);
+
+    let mut output = p_hash_trace(
+        |msg| hmac_sha384_finalize_trace(builder_state, outer_state, inner_state, msg).to_vec(),
+        &label_seed,
+        iterations,
+    );
+    output.truncate(bytes);
+
+    output
+}
+
+/// Reference implementation of PRF(secret, label, seed) with HMAC-SHA384.
+///
+/// # Arguments
+///
+/// * `outer_state` - The outer state of HMAC-SHA384
+/// * `inner_state` - The inner state of HMAC-SHA384
+/// * `seed`        - The seed to use
+/// * `label`       - The label to use
+/// * `bytes`       - The number of bytes to output
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(outer_state, inner_state, seed, label))
+)]
+pub fn prf_sha384(
+    outer_state: [u64; 8],
+    inner_state: [u64; 8],
+    seed: &[u8],
+    label: &[u8],
+    bytes: usize,
+) -> Vec<u8> {
+    let iterations = bytes / 48 + (bytes % 48 != 0) as usize;
+    let mut label_seed = label.to_vec();
+    label_seed.extend_from_slice(seed);
+
+    let mut output = p_hash(
+        |msg| hmac_sha384_finalize(outer_state, inner_state, msg).to_vec(),
+        &label_seed,
+        iterations,
+    );
     output.truncate(bytes);
 
     output
@@ -150,7 +221,9 @@ pub fn prf(
 mod tests {
     use mpz_circuits::{evaluate, CircuitBuilder};
 
-    use crate::hmac_sha256::hmac_sha256_partial;
+    use crate::{hmac_sha256::hmac_sha256_partial, hmac_sha384::hmac_sha384_partial};
+
+    use ring::hmac::HMAC_SHA384;
 
     use super::*;
 
@@ -160,7 +233,13 @@ mod tests {
         let outer_state = builder.add_array_input::<u32, 8>();
         let inner_state = builder.add_array_input::<u32, 8>();
         let seed = builder.add_array_input::<u8, 64>();
-        let output = p_hash_trace(builder.state(), outer_state, inner_state, &seed, 2);
+        let output = p_hash_trace(
+            |msg| {
+                hmac_sha256_finalize_trace(builder.state(), outer_state, inner_state, msg).to_vec()
+            },
+            &seed,
+            2,
+        );
         builder.add_output(output);
         let circ = builder.build().unwrap();
 
@@ -168,7 +247,11 @@ mod tests {
         let inner_state = [1u32; 8];
         let seed = [42u8; 64];
 
-        let expected = p_hash(outer_state, inner_state, &seed, 2);
+        let expected = p_hash(
+            |msg| hmac_sha256_finalize(outer_state, inner_state, msg).to_vec(),
+            &seed,
+            2,
+        );
         let actual = evaluate!(circ, fn(outer_state, inner_state, &seed) -> Vec<u8>).unwrap();
 
         assert_eq!(actual, expected);
@@ -203,10 +286,48 @@ mod tests {
         assert_eq!(actual, expected_ring);
     }
 
+    #[test]
+    fn test_prf_sha384() {
+        let builder = CircuitBuilder::new();
+        let outer_state = builder.add_array_input::<u64, 8>();
+        let inner_state = builder.add_array_input::<u64, 8>();
+        let seed = builder.add_array_input::<u8, 64>();
+        let label = builder.add_array_input::<u8, 13>();
+        let output = prf_sha384_trace(builder.state(), outer_state, inner_state, &seed, &label, 72);
+        builder.add_output(output);
+        let circ = builder.build().unwrap();
+
+        let master_secret = [0u8; 48];
+        let seed = [43u8; 64];
+        let label = b"key expansion";
+
+        let (outer_state, inner_state) = hmac_sha384_partial(&master_secret);
+
+        let expected = prf_sha384(outer_state, inner_state, &seed, label, 72);
+        let actual =
+            evaluate!(circ, fn(outer_state, inner_state, &seed, label) -> Vec<u8>).unwrap();
+
+        assert_eq!(actual, expected);
+
+        let mut expected_ring = [0u8; 72];
+        ring_prf::prf_with(
+            HMAC_SHA384,
+            &mut expected_ring,
+            &master_secret,
+            label,
+            &seed,
+        );
+
+        assert_eq!(actual, expected_ring);
+    }
+
     // Borrowed from Rustls for testing
     // https://github.com/rustls/rustls/blob/main/rustls/src/tls12/prf.rs
     mod ring_prf {
-        use ring::{hmac, hmac::HMAC_SHA256};
+        use ring::{
+            hmac,
+            hmac::{Algorithm, HMAC_SHA256},
+        };
 
         fn concat_sign(key: &hmac::Key, a: &[u8], b: &[u8]) -> hmac::Tag {
             let mut ctx = hmac::Context::with_key(key);
@@ -215,12 +336,12 @@ mod tests {
             ctx.sign()
         }
 
-        fn p(out: &mut [u8], secret: &[u8], seed: &[u8]) {
-            let hmac_key = hmac::Key::new(HMAC_SHA256, secret);
+        fn p(alg: Algorithm, out: &mut [u8], secret: &[u8], seed: &[u8]) {
+            let hmac_key = hmac::Key::new(alg, secret);
 
             // A(1)
             let mut current_a = hmac::sign(&hmac_key, seed);
-            let chunk_size = HMAC_SHA256.digest_algorithm().output_len();
+            let chunk_size = alg.digest_algorithm().output_len();
             for chunk in out.chunks_mut(chunk_size) {
                 // P_hash[i] = HMAC_hash(secret, A(i) + seed)
                 let p_term = concat_sign(&hmac_key, current_a.as_ref(), seed);
@@ -239,8 +360,18 @@ mod tests {
         }
 
         pub(crate) fn prf(out: &mut [u8], secret: &[u8], label: &[u8], seed: &[u8]) {
+            prf_with(HMAC_SHA256, out, secret, label, seed);
+        }
+
+        pub(crate) fn prf_with(
+            alg: Algorithm,
+            out: &mut [u8],
+            secret: &[u8],
+            label: &[u8],
+            seed: &[u8],
+        ) {
             let joined_seed = concat(label, seed);
-            p(out, secret, &joined_seed);
+            p(alg, out, secret, &joined_seed);
         }
     }
 }
@@ -1,13 +1,14 @@
 use std::cell::RefCell;
 
 use mpz_circuits::{
-    types::{U32, U8},
+    types::{U32, U64, U8},
     BuilderState, Tracer,
 };
 
 use crate::{
     hmac_sha256::{hmac_sha256_partial, hmac_sha256_partial_trace},
-    prf::{prf, prf_trace},
+    hmac_sha384::{hmac_sha384_partial, hmac_sha384_partial_trace},
+    prf::{prf, prf_sha384, prf_sha384_trace, prf_trace},
 };
 
 /// Session Keys
@@ -48,7 +49,52 @@ pub fn session_keys_trace<'a>(
     [Tracer<'a, U32>; 8],
     [Tracer<'a, U32>; 8],
 ) {
-    let (pms_outer_state, pms_inner_state) = hmac_sha256_partial_trace(builder_state, &pms);
+    let (cwk, swk, civ, siv, outer_state, inner_state) =
+        session_keys_sha256_trace(builder_state, &pms, client_random, server_random, 16, 4);
+
+    (
+        cwk.try_into().unwrap(),
+        swk.try_into().unwrap(),
+        civ.try_into().unwrap(),
+        siv.try_into().unwrap(),
+        outer_state,
+        inner_state,
+    )
+}
+
+/// Session keys for a cipher suite using the SHA-256 PRF.
+///
+/// Same as [`session_keys_trace`], with the key and IV lengths of the cipher suite.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `pms`             - Premaster secret, at most 64 bytes
+/// * `client_random`   - 32-byte client random
+/// * `server_random`   - 32-byte server random
+/// * `key_len`         - Length of the write keys
+/// * `iv_len`          - Length of the fixed IVs
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, pms))
+)]
+#[allow(clippy::type_complexity)]
+pub fn session_keys_sha256_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    pms: &[Tracer<'a, U8>],
+    client_random: [Tracer<'a, U8>; 32],
+    server_random: [Tracer<'a, U8>; 32],
+    key_len: usize,
+    iv_len: usize,
+) -> (
+    Vec<Tracer<'a, U8>>,
+    Vec<Tracer<'a, U8>>,
+    Vec<Tracer<'a, U8>>,
+    Vec<Tracer<'a, U8>>,
+    [Tracer<'a, U32>; 8],
+    [Tracer<'a, U32>; 8],
+) {
+    let (pms_outer_state, pms_inner_state) = hmac_sha256_partial_trace(builder_state, pms);
 
     let master_secret = {
         let seed = client_random
@@ -89,14 +135,110 @@ pub fn session_keys_trace<'a>(
             master_secret_inner_state,
             &seed,
             &label,
-            40,
+            2 * (key_len + iv_len),
+        )
+    };
+
+    let (cwk, swk, civ, siv) = split_key_material(key_material, key_len, iv_len);
+
+    (
+        cwk,
+        swk,
+        civ,
+        siv,
+        master_secret_outer_state,
+        master_secret_inner_state,
+    )
+}
+
+/// Session keys for a cipher suite using the SHA-384 PRF.
+///
+/// Compute expanded p1 which consists of client_write_key + server_write_key
+/// Compute expanded p2 which consists of client_IV + server_IV
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `pms`             - Premaster secret, at most 128 bytes
+/// * `client_random`   - 32-byte client random
+/// * `server_random`   - 32-byte server random
+/// * `key_len`         - Length of the write keys
+/// * `iv_len`          - Length of the fixed IVs
+///
+/// # Returns
+///
+/// * `client_write_key`    - Client write key
+/// * `server_write_key`    - Server write key
+/// * `client_IV`           - Client IV
+/// * `server_IV`           - Server IV
+/// * `outer_hash_state`    - 512-bit master-secret outer HMAC state
+/// * `inner_hash_state`    - 512-bit master-secret inner HMAC state
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, pms))
+)]
+#[allow(clippy::type_complexity)]
+pub fn session_keys_sha384_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    pms: &[Tracer<'a, U8>],
+    client_random: [Tracer<'a, U8>; 32],
+    server_random: [Tracer<'a, U8>; 32],
+    key_len: usize,
+    iv_len: usize,
+) -> (
+    Vec<Tracer<'a, U8>>,
+    Vec<Tracer<'a, U8>>,
+    Vec<Tracer<'a, U8>>,
+    Vec<Tracer<'a, U8>>,
+    [Tracer<'a, U64>; 8],
+    [Tracer<'a, U64>; 8],
+) {
+    let (pms_outer_state, pms_inner_state) = hmac_sha384_partial_trace(builder_state, pms);
+
+    let master_secret = {
+        let seed = client_random
+            .iter()
+            .chain(&server_random)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let label = b"master secret"
+            .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v)));
+
+        prf_sha384_trace(
+            builder_state,
+            pms_outer_state,
+            pms_inner_state,
+            &seed,
+            &label,
+            48,
+        )
+    };
+
+    let (master_secret_outer_state, master_secret_inner_state) =
+        hmac_sha384_partial_trace(builder_state, &master_secret);
+
+    let key_material = {
+        let seed = server_random
+            .iter()
+            .chain(&client_random)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let label = b"key expansion"
+            .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v)));
+
+        prf_sha384_trace(
+            builder_state,
+            master_secret_outer_state,
+            master_secret_inner_state,
+            &seed,
+            &label,
+            2 * (key_len + iv_len),
         )
     };
 
-    let cwk = key_material[0..16].try_into().unwrap();
-    let swk = key_material[16..32].try_into().unwrap();
-    let civ = key_material[32..36].try_into().unwrap();
-    let siv = key_material[36..40].try_into().unwrap();
+    let (cwk, swk, civ, siv) = split_key_material(key_material, key_len, iv_len);
 
     (
         cwk,
@@ -108,6 +250,20 @@ pub fn session_keys_trace<'a>(
     )
 }
 
+/// Splits the key block into the client and server write keys and IVs.
+#[allow(clippy::type_complexity)]
+fn split_key_material<T: Clone>(
+    key_material: Vec<T>,
+    key_len: usize,
+    iv_len: usize,
+) -> (Vec<T>, Vec<T>, Vec<T>, Vec<T>) {
+    let (keys, ivs) = key_material.split_at(2 * key_len);
+    let (cwk, swk) = keys.split_at(key_len);
+    let (civ, siv) = ivs.split_at(iv_len);
+
+    (cwk.to_vec(), swk.to_vec(), civ.to_vec(), siv.to_vec())
+}
+
 /// Reference implementation of session keys derivation.
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(pms)))]
 pub fn session_keys(
@@ -115,6 +271,25 @@ pub fn session_keys(
     client_random: [u8; 32],
     server_random: [u8; 32],
 ) -> ([u8; 16], [u8; 16], [u8; 4], [u8; 4]) {
+    let (cwk, swk, civ, siv) = session_keys_sha256(pms, client_random, server_random, 16, 4);
+
+    (
+        cwk.try_into().unwrap(),
+        swk.try_into().unwrap(),
+        civ.try_into().unwrap(),
+        siv.try_into().unwrap(),
+    )
+}
+
+/// Reference implementation of session keys derivation with the SHA-256 PRF.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(pms)))]
+pub fn session_keys_sha256(
+    pms: [u8; 32],
+    client_random: [u8; 32],
+    server_random: [u8; 32],
+    key_len: usize,
+    iv_len: usize,
+) -> (Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>) {
     let (pms_outer_state, pms_inner_state) = hmac_sha256_partial(&pms);
 
     let master_secret = {
@@ -146,16 +321,58 @@ pub fn session_keys(
             master_secret_inner_state,
             &seed,
             label,
-            40,
+            2 * (key_len + iv_len),
         )
     };
 
-    let cwk = key_material[0..16].try_into().unwrap();
-    let swk = key_material[16..32].try_into().unwrap();
-    let civ = key_material[32..36].try_into().unwrap();
-    let siv = key_material[36..40].try_into().unwrap();
+    split_key_material(key_material, key_len, iv_len)
+}
+
+/// Reference implementation of session keys derivation with the SHA-384 PRF.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(pms)))]
+pub fn session_keys_sha384(
+    pms: [u8; 32],
+    client_random: [u8; 32],
+    server_random: [u8; 32],
+    key_len: usize,
+    iv_len: usize,
+) -> (Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>) {
+    let (pms_outer_state, pms_inner_state) = hmac_sha384_partial(&pms);
+
+    let master_secret = {
+        let seed = client_random
+            .iter()
+            .chain(&server_random)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let label = b"master secret";
+
+        prf_sha384(pms_outer_state, pms_inner_state, &seed, label, 48)
+    };
+
+    let (master_secret_outer_state, master_secret_inner_state) =
+        hmac_sha384_partial(&master_secret);
+
+    let key_material = {
+        let seed = server_random
+            .iter()
+            .chain(&client_random)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let label = b"key expansion";
+
+        prf_sha384(
+            master_secret_outer_state,
+            master_secret_inner_state,
+            &seed,
+            label,
+            2 * (key_len + iv_len),
+        )
+    };
 
-    (cwk, swk, civ, siv)
+    split_key_material(key_material, key_len, iv_len)
 }
 
 #[cfg(test)]
@@ -202,4 +419,43 @@ mod tests {
         assert_eq!(civ, expected_civ);
         assert_eq!(siv, expected_siv);
     }
+
+    #[test]
+    fn test_session_keys_sha384() {
+        let builder = CircuitBuilder::new();
+        let pms = builder.add_array_input::<u8, 32>();
+        let client_random = builder.add_array_input::<u8, 32>();
+        let server_random = builder.add_array_input::<u8, 32>();
+        let (cwk, swk, civ, siv, outer_state, inner_state) =
+            session_keys_sha384_trace(builder.state(), &pms, client_random, server_random, 32, 4);
+        builder.add_output(cwk);
+        builder.add_output(swk);
+        builder.add_output(civ);
+        builder.add_output(siv);
+        builder.add_output(outer_state);
+        builder.add_output(inner_state);
+        let circ = builder.build().unwrap();
+
+        let pms = [0u8; 32];
+        let client_random = [42u8; 32];
+        let server_random = [69u8; 32];
+
+        let (expected_cwk, expected_swk, expected_civ, expected_siv) =
+            session_keys_sha384(pms, client_random, server_random, 32, 4);
+
+        let (cwk, swk, civ, siv, _, _) = evaluate!(
+            circ,
+            fn(
+                pms,
+                client_random,
+                server_random,
+            ) -> ([u8; 32], [u8; 32], [u8; 4], [u8; 4], [u64; 8], [u64; 8])
+        )
+        .unwrap();
+
+        assert_eq!(cwk.to_vec(), expected_cwk);
+        assert_eq!(swk.to_vec(), expected_swk);
+        assert_eq!(civ.to_vec(), expected_civ);
+        assert_eq!(siv.to_vec(), expected_siv);
+    }
 }
@@ -0,0 +1,406 @@
+//! This module provides an implementation of SHA-384 as specified in [FIPS 180-4](https://csrc.nist.gov/pubs/fips/180-4/upd1/final).
+//!
+//! SHA-384 is SHA-512 with a different initial state, truncated to 48 bytes.
+
+use std::cell::RefCell;
+
+use mpz_circuits::{
+    ops::WrappingAdd,
+    types::{U64, U8},
+    BuilderState, Tracer,
+};
+
+/// The initial state of SHA-384.
+pub(crate) static SHA384_INITIAL_STATE: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+/// The SHA-512 round constants.
+static K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// Computes the SHA-512 compression function.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `state`           - 512-bit hash state
+/// * `block`           - 128-byte message block
+pub(crate) fn sha512_compress_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    state: [Tracer<'a, U64>; 8],
+    block: [Tracer<'a, U8>; 128],
+) -> [Tracer<'a, U64>; 8] {
+    let mut w: Vec<_> = block
+        .chunks(8)
+        .map(|word| u64_from_be_bytes(builder_state, word))
+        .collect();
+
+    for i in 16..80 {
+        let s0 = rotate_right(builder_state, w[i - 15], 1)
+            ^ rotate_right(builder_state, w[i - 15], 8)
+            ^ shift_right(builder_state, w[i - 15], 7);
+        let s1 = rotate_right(builder_state, w[i - 2], 19)
+            ^ rotate_right(builder_state, w[i - 2], 61)
+            ^ shift_right(builder_state, w[i - 2], 6);
+        w.push(
+            w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1),
+        );
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for (i, w_i) in w.into_iter().enumerate() {
+        let s1 = rotate_right(builder_state, e, 14)
+            ^ rotate_right(builder_state, e, 18)
+            ^ rotate_right(builder_state, e, 41);
+        // ch = (e & f) ^ (!e & g)
+        let ch = g ^ (e & (f ^ g));
+        let k = Tracer::new(builder_state, builder_state.borrow_mut().get_constant(K[i]));
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(k)
+            .wrapping_add(w_i);
+
+        let s0 = rotate_right(builder_state, a, 28)
+            ^ rotate_right(builder_state, a, 34)
+            ^ rotate_right(builder_state, a, 39);
+        // maj = (a & b) ^ (a & c) ^ (b & c)
+        let maj = ((a ^ b) & (b ^ c)) ^ b;
+        let t2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    let mut output = state;
+    output
+        .iter_mut()
+        .zip([a, b, c, d, e, f, g, h])
+        .for_each(|(output, v)| *output = output.wrapping_add(v));
+
+    output
+}
+
+/// Reference implementation of the SHA-512 compression function.
+pub(crate) fn sha512_compress(state: [u64; 8], block: [u8; 128]) -> [u64; 8] {
+    let mut w: Vec<u64> = block
+        .chunks(8)
+        .map(|word| u64::from_be_bytes(word.try_into().unwrap()))
+        .collect();
+
+    for i in 16..80 {
+        let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+        let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+        w.push(
+            w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1),
+        );
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for (i, w_i) in w.into_iter().enumerate() {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(K[i])
+            .wrapping_add(w_i);
+
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    let mut output = state;
+    output
+        .iter_mut()
+        .zip([a, b, c, d, e, f, g, h])
+        .for_each(|(output, v)| *output = output.wrapping_add(v));
+
+    output
+}
+
+/// Computes SHA-384 of a message, starting from an intermediate hash state.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `state`           - 512-bit hash state
+/// * `pos`             - The number of bytes which were already processed into the state
+/// * `msg`             - N-byte message
+pub(crate) fn sha384_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    state: [Tracer<'a, U64>; 8],
+    pos: usize,
+    msg: &[Tracer<'a, U8>],
+) -> [Tracer<'a, U8>; 48] {
+    let padding = padding(pos, msg.len())
+        .into_iter()
+        .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v)));
+
+    let msg: Vec<_> = msg.iter().copied().chain(padding).collect();
+
+    let state = msg.chunks(128).fold(state, |state, block| {
+        sha512_compress_trace(
+            builder_state,
+            state,
+            block.try_into().expect("block is 128 bytes"),
+        )
+    });
+
+    state[..6]
+        .iter()
+        .flat_map(|word| u64_to_be_bytes(builder_state, *word))
+        .collect::<Vec<_>>()
+        .try_into()
+        .expect("digest is 48 bytes")
+}
+
+/// Reference implementation of SHA-384, starting from an intermediate hash state.
+///
+/// # Arguments
+///
+/// * `state`   - 512-bit hash state
+/// * `pos`     - The number of bytes which were already processed into the state
+/// * `msg`     - N-byte message
+pub(crate) fn sha384(state: [u64; 8], pos: usize, msg: &[u8]) -> [u8; 48] {
+    let msg: Vec<_> = msg.iter().copied().chain(padding(pos, msg.len())).collect();
+
+    let state = msg.chunks(128).fold(state, |state, block| {
+        sha512_compress(state, block.try_into().expect("block is 128 bytes"))
+    });
+
+    state[..6]
+        .iter()
+        .flat_map(|word| word.to_be_bytes())
+        .collect::<Vec<_>>()
+        .try_into()
+        .expect("digest is 48 bytes")
+}
+
+/// Returns the padding for a message of `len` bytes, of which `pos` bytes were already processed.
+fn padding(pos: usize, len: usize) -> Vec<u8> {
+    let bit_len = ((pos + len) as u128) * 8;
+    let zeros = (128 + 112 - (len + 1) % 128) % 128;
+
+    let mut padding = vec![0x80];
+    padding.extend(std::iter::repeat(0).take(zeros));
+    padding.extend(bit_len.to_be_bytes());
+
+    padding
+}
+
+/// Rotates a word to the right, which only rewires the bits.
+fn rotate_right<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    word: Tracer<'a, U64>,
+    n: usize,
+) -> Tracer<'a, U64> {
+    let mut nodes = word.to_inner().nodes();
+    // Bits are in LSB0 order.
+    nodes.rotate_left(n);
+
+    Tracer::new(
+        builder_state,
+        U64::new(nodes.try_into().expect("word is 64 bits")),
+    )
+}
+
+/// Shifts a word to the right, which only rewires the bits.
+fn shift_right<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    word: Tracer<'a, U64>,
+    n: usize,
+) -> Tracer<'a, U64> {
+    let nodes = word.to_inner().nodes();
+    let zero = builder_state.borrow_mut().get_constant(0u64).nodes();
+
+    let nodes: Vec<_> = nodes[n..].iter().chain(&zero[..n]).copied().collect();
+
+    Tracer::new(
+        builder_state,
+        U64::new(nodes.try_into().expect("word is 64 bits")),
+    )
+}
+
+fn u64_from_be_bytes<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    bytes: &[Tracer<'a, U8>],
+) -> Tracer<'a, U64> {
+    let nodes: Vec<_> = bytes
+        .iter()
+        .rev()
+        .flat_map(|byte| byte.to_inner().nodes())
+        .collect();
+
+    Tracer::new(
+        builder_state,
+        U64::new(nodes.try_into().expect("word is 8 bytes")),
+    )
+}
+
+fn u64_to_be_bytes<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    word: Tracer<'a, U64>,
+) -> [Tracer<'a, U8>; 8] {
+    let nodes = word.to_inner().nodes();
+
+    std::array::from_fn(|i| {
+        let i = 7 - i;
+        Tracer::new(
+            builder_state,
+            U8::new(
+                nodes[i * 8..(i + 1) * 8]
+                    .try_into()
+                    .expect("byte is 8 bits"),
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::{evaluate, CircuitBuilder};
+    use ring::digest::{digest, SHA384};
+
+    use super::*;
+
+    #[test]
+    fn test_sha384_reference() {
+        for len in [0, 3, 111, 112, 128, 300] {
+            let msg = vec![42u8; len];
+
+            assert_eq!(
+                sha384(SHA384_INITIAL_STATE, 0, &msg).as_slice(),
+                digest(&SHA384, &msg).as_ref()
+            );
+        }
+    }
+
+    #[test]
+    fn test_sha384() {
+        let builder = CircuitBuilder::new();
+        let state = builder.add_array_input::<u64, 8>();
+        let msg = builder.add_array_input::<u8, 150>();
+        let hash = sha384_trace(builder.state(), state, 0, &msg);
+        builder.add_output(hash);
+        let circ = builder.build().unwrap();
+
+        let state = SHA384_INITIAL_STATE;
+        let msg = [69u8; 150];
+
+        let expected = sha384(state, 0, &msg);
+        let actual = evaluate!(circ, fn(state, &msg) -> [u8; 48]).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+}
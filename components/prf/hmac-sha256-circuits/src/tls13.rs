@@ -0,0 +1,514 @@
+//! This module provides an implementation of the TLS 1.3 key schedule defined in [RFC 8446](https://www.rfc-editor.org/rfc/rfc8446#section-7.1)
+//! for the SHA-256 based cipher suites.
+//!
+//! PSKs are not supported, so the early secret is a public constant.
+
+use std::cell::RefCell;
+
+use mpz_circuits::{
+    types::{U32, U8},
+    BuilderState, Tracer,
+};
+
+use crate::{
+    hkdf::{hkdf_expand_label, hkdf_expand_label_trace},
+    hmac_sha256::{
+        hmac_sha256_finalize, hmac_sha256_finalize_trace, hmac_sha256_partial,
+        hmac_sha256_partial_trace,
+    },
+};
+
+/// SHA-256 digest of the empty string.
+const EMPTY_HASH: [u8; 32] = [
+    0xe3, 0xb0, 0xc4, 0x42, 0x98, 0xfc, 0x1c, 0x14, 0x9a, 0xfb, 0xf4, 0xc8, 0x99, 0x6f, 0xb9, 0x24,
+    0x27, 0xae, 0x41, 0xe4, 0x64, 0x9b, 0x93, 0x4c, 0xa4, 0x95, 0x99, 0x1b, 0x78, 0x52, 0xb8, 0x55,
+];
+
+/// Returns the HMAC states of the salt used to extract the handshake secret.
+///
+/// early_secret = HKDF-Extract(0, 0)
+///
+/// salt = Derive-Secret(early_secret, "derived", "")
+fn handshake_secret_salt() -> ([u32; 8], [u32; 8]) {
+    let (outer_state, inner_state) = hmac_sha256_partial(&[0u8; 32]);
+    let early_secret = hmac_sha256_finalize(outer_state, inner_state, &[0u8; 32]);
+
+    let (outer_state, inner_state) = hmac_sha256_partial(&early_secret);
+    let salt = hkdf_expand_label(outer_state, inner_state, b"derived", &EMPTY_HASH, 32);
+
+    hmac_sha256_partial(&salt)
+}
+
+#[allow(clippy::type_complexity)]
+fn traffic_keys_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    secret: &[Tracer<'a, U8>],
+) -> ([Tracer<'a, U8>; 16], [Tracer<'a, U8>; 12]) {
+    let (outer_state, inner_state) = hmac_sha256_partial_trace(builder_state, secret);
+
+    let key = hkdf_expand_label_trace(builder_state, outer_state, inner_state, b"key", &[], 16);
+    let iv = hkdf_expand_label_trace(builder_state, outer_state, inner_state, b"iv", &[], 12);
+
+    (key.try_into().unwrap(), iv.try_into().unwrap())
+}
+
+fn traffic_keys(secret: &[u8]) -> ([u8; 16], [u8; 12]) {
+    let (outer_state, inner_state) = hmac_sha256_partial(secret);
+
+    let key = hkdf_expand_label(outer_state, inner_state, b"key", &[], 16);
+    let iv = hkdf_expand_label(outer_state, inner_state, b"iv", &[], 12);
+
+    (key.try_into().unwrap(), iv.try_into().unwrap())
+}
+
+/// TLS 1.3 handshake traffic keys.
+///
+/// Computes the handshake secret from the (EC)DHE shared secret, and derives the handshake
+/// traffic keys, the finished keys and the master secret from it.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `pms`             - 32-byte (EC)DHE shared secret
+/// * `hello_hash`      - Transcript hash of ClientHello..ServerHello
+///
+/// # Returns
+///
+/// * `client_write_key`            - 16-byte client handshake write key
+/// * `server_write_key`            - 16-byte server handshake write key
+/// * `client_IV`                   - 12-byte client handshake IV
+/// * `server_IV`                   - 12-byte server handshake IV
+/// * `client_finished_outer_state` - 256-bit client finished key outer HMAC state
+/// * `client_finished_inner_state` - 256-bit client finished key inner HMAC state
+/// * `server_finished_outer_state` - 256-bit server finished key outer HMAC state
+/// * `server_finished_inner_state` - 256-bit server finished key inner HMAC state
+/// * `master_secret_outer_state`   - 256-bit master secret outer HMAC state
+/// * `master_secret_inner_state`   - 256-bit master secret inner HMAC state
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, pms))
+)]
+#[allow(clippy::type_complexity)]
+pub fn handshake_keys_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    pms: [Tracer<'a, U8>; 32],
+    hello_hash: [Tracer<'a, U8>; 32],
+) -> (
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 12],
+    [Tracer<'a, U8>; 12],
+    [Tracer<'a, U32>; 8],
+    [Tracer<'a, U32>; 8],
+    [Tracer<'a, U32>; 8],
+    [Tracer<'a, U32>; 8],
+    [Tracer<'a, U32>; 8],
+    [Tracer<'a, U32>; 8],
+) {
+    let (salt_outer_state, salt_inner_state) = handshake_secret_salt();
+
+    // handshake_secret = HKDF-Extract(salt, pms)
+    let handshake_secret = hmac_sha256_finalize_trace(
+        builder_state,
+        salt_outer_state
+            .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v))),
+        salt_inner_state
+            .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v))),
+        &pms,
+    );
+
+    let (hs_outer_state, hs_inner_state) =
+        hmac_sha256_partial_trace(builder_state, &handshake_secret);
+
+    let client_secret = hkdf_expand_label_trace(
+        builder_state,
+        hs_outer_state,
+        hs_inner_state,
+        b"c hs traffic",
+        &hello_hash,
+        32,
+    );
+    let server_secret = hkdf_expand_label_trace(
+        builder_state,
+        hs_outer_state,
+        hs_inner_state,
+        b"s hs traffic",
+        &hello_hash,
+        32,
+    );
+
+    let (cwk, civ) = traffic_keys_trace(builder_state, &client_secret);
+    let (swk, siv) = traffic_keys_trace(builder_state, &server_secret);
+
+    let finished_key_states = |secret: &[Tracer<'a, U8>]| {
+        let (outer_state, inner_state) = hmac_sha256_partial_trace(builder_state, secret);
+        let finished_key = hkdf_expand_label_trace(
+            builder_state,
+            outer_state,
+            inner_state,
+            b"finished",
+            &[],
+            32,
+        );
+        hmac_sha256_partial_trace(builder_state, &finished_key)
+    };
+
+    let (cf_outer_state, cf_inner_state) = finished_key_states(&client_secret);
+    let (sf_outer_state, sf_inner_state) = finished_key_states(&server_secret);
+
+    // master_secret = HKDF-Extract(Derive-Secret(handshake_secret, "derived", ""), 0)
+    let master_secret = {
+        let empty_hash = EMPTY_HASH
+            .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v)));
+        let salt = hkdf_expand_label_trace(
+            builder_state,
+            hs_outer_state,
+            hs_inner_state,
+            b"derived",
+            &empty_hash,
+            32,
+        );
+        let (outer_state, inner_state) = hmac_sha256_partial_trace(builder_state, &salt);
+        hmac_sha256_finalize_trace(
+            builder_state,
+            outer_state,
+            inner_state,
+            &[0u8; 32]
+                .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v))),
+        )
+    };
+
+    let (ms_outer_state, ms_inner_state) = hmac_sha256_partial_trace(builder_state, &master_secret);
+
+    (
+        cwk,
+        swk,
+        civ,
+        siv,
+        cf_outer_state,
+        cf_inner_state,
+        sf_outer_state,
+        sf_inner_state,
+        ms_outer_state,
+        ms_inner_state,
+    )
+}
+
+/// Reference implementation of TLS 1.3 handshake traffic key derivation.
+///
+/// Returns the handshake traffic keys and IVs, the client and server finished keys, and
+/// the master secret.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(pms)))]
+#[allow(clippy::type_complexity)]
+pub fn handshake_keys(
+    pms: [u8; 32],
+    hello_hash: [u8; 32],
+) -> (
+    [u8; 16],
+    [u8; 16],
+    [u8; 12],
+    [u8; 12],
+    [u8; 32],
+    [u8; 32],
+    [u8; 32],
+) {
+    let (salt_outer_state, salt_inner_state) = handshake_secret_salt();
+    let handshake_secret = hmac_sha256_finalize(salt_outer_state, salt_inner_state, &pms);
+
+    let (hs_outer_state, hs_inner_state) = hmac_sha256_partial(&handshake_secret);
+
+    let client_secret = hkdf_expand_label(
+        hs_outer_state,
+        hs_inner_state,
+        b"c hs traffic",
+        &hello_hash,
+        32,
+    );
+    let server_secret = hkdf_expand_label(
+        hs_outer_state,
+        hs_inner_state,
+        b"s hs traffic",
+        &hello_hash,
+        32,
+    );
+
+    let (cwk, civ) = traffic_keys(&client_secret);
+    let (swk, siv) = traffic_keys(&server_secret);
+
+    let finished_key = |secret: &[u8]| -> [u8; 32] {
+        let (outer_state, inner_state) = hmac_sha256_partial(secret);
+        hkdf_expand_label(outer_state, inner_state, b"finished", &[], 32)
+            .try_into()
+            .unwrap()
+    };
+
+    let client_finished_key = finished_key(&client_secret);
+    let server_finished_key = finished_key(&server_secret);
+
+    let master_secret = {
+        let salt = hkdf_expand_label(hs_outer_state, hs_inner_state, b"derived", &EMPTY_HASH, 32);
+        let (outer_state, inner_state) = hmac_sha256_partial(&salt);
+        hmac_sha256_finalize(outer_state, inner_state, &[0u8; 32])
+    };
+
+    (
+        cwk,
+        swk,
+        civ,
+        siv,
+        client_finished_key,
+        server_finished_key,
+        master_secret,
+    )
+}
+
+/// TLS 1.3 application traffic keys.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `outer_state`     - The outer HMAC state of the master secret
+/// * `inner_state`     - The inner HMAC state of the master secret
+/// * `hs_hash`         - Transcript hash of ClientHello..server Finished
+///
+/// # Returns
+///
+/// * `client_write_key`    - 16-byte client application write key
+/// * `server_write_key`    - 16-byte server application write key
+/// * `client_IV`           - 12-byte client application IV
+/// * `server_IV`           - 12-byte server application IV
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, outer_state, inner_state))
+)]
+#[allow(clippy::type_complexity)]
+pub fn application_keys_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    outer_state: [Tracer<'a, U32>; 8],
+    inner_state: [Tracer<'a, U32>; 8],
+    hs_hash: [Tracer<'a, U8>; 32],
+) -> (
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 12],
+    [Tracer<'a, U8>; 12],
+) {
+    let client_secret = hkdf_expand_label_trace(
+        builder_state,
+        outer_state,
+        inner_state,
+        b"c ap traffic",
+        &hs_hash,
+        32,
+    );
+    let server_secret = hkdf_expand_label_trace(
+        builder_state,
+        outer_state,
+        inner_state,
+        b"s ap traffic",
+        &hs_hash,
+        32,
+    );
+
+    let (cwk, civ) = traffic_keys_trace(builder_state, &client_secret);
+    let (swk, siv) = traffic_keys_trace(builder_state, &server_secret);
+
+    (cwk, swk, civ, siv)
+}
+
+/// Reference implementation of TLS 1.3 application traffic key derivation.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(outer_state, inner_state))
+)]
+pub fn application_keys(
+    outer_state: [u32; 8],
+    inner_state: [u32; 8],
+    hs_hash: [u8; 32],
+) -> ([u8; 16], [u8; 16], [u8; 12], [u8; 12]) {
+    let client_secret = hkdf_expand_label(outer_state, inner_state, b"c ap traffic", &hs_hash, 32);
+    let server_secret = hkdf_expand_label(outer_state, inner_state, b"s ap traffic", &hs_hash, 32);
+
+    let (cwk, civ) = traffic_keys(&client_secret);
+    let (swk, siv) = traffic_keys(&server_secret);
+
+    (cwk, swk, civ, siv)
+}
+
+/// Computes TLS 1.3 verify_data as specified in RFC 8446, Section 4.4.4.
+///
+/// verify_data = HMAC(finished_key, Transcript-Hash(Handshake Context, Certificate*, CertificateVerify*))
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `outer_state`     - The outer HMAC state of the finished key
+/// * `inner_state`     - The inner HMAC state of the finished key
+/// * `hs_hash`         - The handshake hash
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, outer_state, inner_state))
+)]
+pub fn tls13_verify_data_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    outer_state: [Tracer<'a, U32>; 8],
+    inner_state: [Tracer<'a, U32>; 8],
+    hs_hash: [Tracer<'a, U8>; 32],
+) -> [Tracer<'a, U8>; 32] {
+    hmac_sha256_finalize_trace(builder_state, outer_state, inner_state, &hs_hash)
+}
+
+/// Reference implementation of TLS 1.3 verify_data as specified in RFC 8446, Section 4.4.4.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(outer_state, inner_state))
+)]
+pub fn tls13_verify_data(
+    outer_state: [u32; 8],
+    inner_state: [u32; 8],
+    hs_hash: [u8; 32],
+) -> [u8; 32] {
+    hmac_sha256_finalize(outer_state, inner_state, &hs_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::{evaluate, CircuitBuilder};
+
+    use super::*;
+
+    fn hkdf_extract(salt: &[u8], ikm: &[u8]) -> [u8; 32] {
+        let tag = ring::hmac::sign(&ring::hmac::Key::new(ring::hmac::HMAC_SHA256, salt), ikm);
+        tag.as_ref().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_empty_hash() {
+        assert_eq!(
+            ring::digest::digest(&ring::digest::SHA256, &[]).as_ref(),
+            EMPTY_HASH.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_handshake_secret_salt() {
+        let early_secret = hkdf_extract(&[0u8; 32], &[0u8; 32]);
+        let (outer_state, inner_state) = hmac_sha256_partial(&early_secret);
+        let salt = hkdf_expand_label(outer_state, inner_state, b"derived", &EMPTY_HASH, 32);
+
+        assert_eq!(handshake_secret_salt(), hmac_sha256_partial(&salt));
+    }
+
+    #[test]
+    fn test_handshake_keys() {
+        let builder = CircuitBuilder::new();
+        let pms = builder.add_array_input::<u8, 32>();
+        let hello_hash = builder.add_array_input::<u8, 32>();
+        let (cwk, swk, civ, siv, cf_outer, cf_inner, sf_outer, sf_inner, ms_outer, ms_inner) =
+            handshake_keys_trace(builder.state(), pms, hello_hash);
+        builder.add_output(cwk);
+        builder.add_output(swk);
+        builder.add_output(civ);
+        builder.add_output(siv);
+        builder.add_output(cf_outer);
+        builder.add_output(cf_inner);
+        builder.add_output(sf_outer);
+        builder.add_output(sf_inner);
+        builder.add_output(ms_outer);
+        builder.add_output(ms_inner);
+        let circ = builder.build().unwrap();
+
+        let pms = [42u8; 32];
+        let hello_hash = [69u8; 32];
+
+        let (
+            expected_cwk,
+            expected_swk,
+            expected_civ,
+            expected_siv,
+            client_finished_key,
+            server_finished_key,
+            master_secret,
+        ) = handshake_keys(pms, hello_hash);
+
+        let (cwk, swk, civ, siv, cf_outer, cf_inner, sf_outer, sf_inner, ms_outer, ms_inner) =
+            evaluate!(
+                circ,
+                fn(
+                    pms,
+                    hello_hash,
+                ) -> (
+                    [u8; 16],
+                    [u8; 16],
+                    [u8; 12],
+                    [u8; 12],
+                    [u32; 8],
+                    [u32; 8],
+                    [u32; 8],
+                    [u32; 8],
+                    [u32; 8],
+                    [u32; 8],
+                )
+            )
+            .unwrap();
+
+        assert_eq!(cwk, expected_cwk);
+        assert_eq!(swk, expected_swk);
+        assert_eq!(civ, expected_civ);
+        assert_eq!(siv, expected_siv);
+        assert_eq!(
+            (cf_outer, cf_inner),
+            hmac_sha256_partial(&client_finished_key)
+        );
+        assert_eq!(
+            (sf_outer, sf_inner),
+            hmac_sha256_partial(&server_finished_key)
+        );
+        assert_eq!((ms_outer, ms_inner), hmac_sha256_partial(&master_secret));
+    }
+
+    #[test]
+    fn test_application_keys() {
+        let builder = CircuitBuilder::new();
+        let outer_state = builder.add_array_input::<u32, 8>();
+        let inner_state = builder.add_array_input::<u32, 8>();
+        let hs_hash = builder.add_array_input::<u8, 32>();
+        let (cwk, swk, civ, siv) =
+            application_keys_trace(builder.state(), outer_state, inner_state, hs_hash);
+        builder.add_output(cwk);
+        builder.add_output(swk);
+        builder.add_output(civ);
+        builder.add_output(siv);
+        let circ = builder.build().unwrap();
+
+        let (outer_state, inner_state) = hmac_sha256_partial(&[1u8; 32]);
+        let hs_hash = [2u8; 32];
+
+        let expected = application_keys(outer_state, inner_state, hs_hash);
+
+        let actual = evaluate!(
+            circ,
+            fn(outer_state, inner_state, hs_hash) -> ([u8; 16], [u8; 16], [u8; 12], [u8; 12])
+        )
+        .unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_tls13_verify_data() {
+        let finished_key = [3u8; 32];
+        let hs_hash = [4u8; 32];
+        let (outer_state, inner_state) = hmac_sha256_partial(&finished_key);
+
+        let expected = ring::hmac::sign(
+            &ring::hmac::Key::new(ring::hmac::HMAC_SHA256, &finished_key),
+            &hs_hash,
+        );
+
+        assert_eq!(
+            tls13_verify_data(outer_state, inner_state, hs_hash).as_slice(),
+            expected.as_ref()
+        );
+    }
+}
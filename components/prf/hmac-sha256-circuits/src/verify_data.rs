@@ -1,11 +1,11 @@
 use std::cell::RefCell;
 
 use mpz_circuits::{
-    types::{U32, U8},
+    types::{U32, U64, U8},
     BuilderState, Tracer,
 };
 
-use crate::prf::{prf, prf_trace};
+use crate::prf::{prf, prf_sha384, prf_sha384_trace, prf_trace};
 
 /// Computes verify_data as specified in RFC 5246, Section 7.4.9.
 ///
@@ -58,6 +58,54 @@ pub fn verify_data(
     vd.try_into().expect("vd is 12 bytes")
 }
 
+/// Computes verify_data with the SHA-384 PRF, as specified in RFC 5246, Section 7.4.9.
+///
+/// # Arguments
+///
+/// * `builder_state`   - The builder state
+/// * `outer_state`     - The outer HMAC-SHA384 state of the master secret
+/// * `inner_state`     - The inner HMAC-SHA384 state of the master secret
+/// * `label`           - The label to use
+/// * `hs_hash`         - The SHA-384 handshake hash
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, outer_state, inner_state, label))
+)]
+pub fn verify_data_sha384_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    outer_state: [Tracer<'a, U64>; 8],
+    inner_state: [Tracer<'a, U64>; 8],
+    label: &[Tracer<'a, U8>],
+    hs_hash: [Tracer<'a, U8>; 48],
+) -> [Tracer<'a, U8>; 12] {
+    let vd = prf_sha384_trace(builder_state, outer_state, inner_state, &hs_hash, label, 12);
+
+    vd.try_into().expect("vd is 12 bytes")
+}
+
+/// Reference implementation of verify_data with the SHA-384 PRF.
+///
+/// # Arguments
+///
+/// * `outer_state` - The outer HMAC-SHA384 state of the master secret
+/// * `inner_state` - The inner HMAC-SHA384 state of the master secret
+/// * `label`       - The label to use
+/// * `hs_hash`     - The SHA-384 handshake hash
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(outer_state, inner_state, label))
+)]
+pub fn verify_data_sha384(
+    outer_state: [u64; 8],
+    inner_state: [u64; 8],
+    label: &[u8],
+    hs_hash: [u8; 48],
+) -> [u8; 12] {
+    let vd = prf_sha384(outer_state, inner_state, &hs_hash, label, 12);
+
+    vd.try_into().expect("vd is 12 bytes")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,4 +139,31 @@ mod tests {
 
         assert_eq!(actual.to_vec(), expected);
     }
+
+    #[test]
+    fn test_verify_data_sha384() {
+        let builder = CircuitBuilder::new();
+        let outer_state = builder.add_array_input::<u64, 8>();
+        let inner_state = builder.add_array_input::<u64, 8>();
+        let label = builder.add_array_input::<u8, 15>();
+        let hs_hash = builder.add_array_input::<u8, 48>();
+        let vd =
+            verify_data_sha384_trace(builder.state(), outer_state, inner_state, &label, hs_hash);
+        builder.add_output(vd);
+        let circ = builder.build().unwrap();
+
+        let outer_state = [0u64; 8];
+        let inner_state = [1u64; 8];
+        let hs_hash = [42u8; 48];
+
+        let expected = verify_data_sha384(outer_state, inner_state, CF_LABEL, hs_hash);
+
+        let actual = evaluate!(
+            circ,
+            fn(outer_state, inner_state, CF_LABEL, hs_hash) -> [u8; 12]
+        )
+        .unwrap();
+
+        assert_eq!(actual, expected);
+    }
 }
@@ -1,6 +1,6 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 
-use hmac_sha256::{MpcPrf, Prf, PrfConfig, Role};
+use hmac_sha256::{MpcPrf, Prf, PrfConfig, Role, PMS_LEN};
 use mpz_garble::{protocol::deap::mock::create_mock_deap_vm, Memory, Vm};
 
 #[allow(clippy::unit_arg)]
@@ -33,8 +33,12 @@ async fn setup() {
     let leader_thread = leader_vm.new_thread("setup").await.unwrap();
     let follower_thread = follower_vm.new_thread("setup").await.unwrap();
 
-    let leader_pms = leader_thread.new_public_input::<[u8; 32]>("pms").unwrap();
-    let follower_pms = follower_thread.new_public_input::<[u8; 32]>("pms").unwrap();
+    let leader_pms = leader_thread
+        .new_public_input::<[u8; PMS_LEN]>("pms")
+        .unwrap();
+    let follower_pms = follower_thread
+        .new_public_input::<[u8; PMS_LEN]>("pms")
+        .unwrap();
 
     futures::try_join!(leader.setup(leader_pms), follower.setup(follower_pms)).unwrap();
 }
@@ -53,7 +57,7 @@ async fn prf() {
         follower_vm.new_thread("prf/1").await.unwrap(),
     );
 
-    let pms = [42u8; 32];
+    let pms = [42u8; PMS_LEN];
 
     let client_random = [0u8; 32];
     let server_random = [1u8; 32];
@@ -63,8 +67,12 @@ async fn prf() {
     let leader_thread = leader_vm.new_thread("setup").await.unwrap();
     let follower_thread = follower_vm.new_thread("setup").await.unwrap();
 
-    let leader_pms = leader_thread.new_public_input::<[u8; 32]>("pms").unwrap();
-    let follower_pms = follower_thread.new_public_input::<[u8; 32]>("pms").unwrap();
+    let leader_pms = leader_thread
+        .new_public_input::<[u8; PMS_LEN]>("pms")
+        .unwrap();
+    let follower_pms = follower_thread
+        .new_public_input::<[u8; PMS_LEN]>("pms")
+        .unwrap();
 
     leader_thread.assign(&leader_pms, pms).unwrap();
     follower_thread.assign(&follower_pms, pms).unwrap();
@@ -78,13 +86,13 @@ async fn prf() {
     .unwrap();
 
     let _ = futures::try_join!(
-        leader.compute_client_finished_vd_private(cf_hs_hash),
+        leader.compute_client_finished_vd_private(&cf_hs_hash),
         follower.compute_client_finished_vd_blind()
     )
     .unwrap();
 
     let _ = futures::try_join!(
-        leader.compute_server_finished_vd_private(sf_hs_hash),
+        leader.compute_server_finished_vd_private(&sf_hs_hash),
         follower.compute_server_finished_vd_blind()
     )
     .unwrap();
@@ -1,5 +1,7 @@
 use derive_builder::Builder;
 
+use crate::PrfParams;
+
 /// Role of this party in the PRF.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Role {
@@ -14,6 +16,11 @@ pub enum Role {
 pub struct PrfConfig {
     /// The role of this party in the PRF.
     pub(crate) role: Role,
+    /// The key derivation parameters which the circuits are preprocessed for during setup.
+    ///
+    /// Defaults to the parameters of the AES-128-GCM cipher suites.
+    #[builder(default)]
+    pub(crate) params: PrfParams,
 }
 
 impl PrfConfig {
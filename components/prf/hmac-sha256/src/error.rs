@@ -12,6 +12,8 @@ pub enum PrfError {
     RoleError(String),
     #[error("Invalid state: {0}")]
     InvalidState(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
 }
 
 impl From<StateError> for PrfError {
@@ -20,6 +22,12 @@ impl From<StateError> for PrfError {
     }
 }
 
+impl From<crate::key_schedule::state::StateError> for PrfError {
+    fn from(err: crate::key_schedule::state::StateError) -> Self {
+        PrfError::InvalidState(err.to_string())
+    }
+}
+
 impl From<mpz_garble::MemoryError> for PrfError {
     fn from(err: mpz_garble::MemoryError) -> Self {
         PrfError::Mpc(Box::new(err))
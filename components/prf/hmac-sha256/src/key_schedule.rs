@@ -0,0 +1,475 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, OnceLock},
+};
+
+use async_trait::async_trait;
+
+use hmac_sha256_circuits::{build_application_keys, build_handshake_keys, build_tls13_verify_data};
+use mpz_circuits::Circuit;
+use mpz_garble::{
+    config::Visibility, value::ValueRef, Decode, DecodePrivate, Execute, Load, Memory,
+};
+use utils_aio::non_blocking_backend::{Backend, NonBlockingBackend};
+
+use crate::{KeySchedule, PrfConfig, PrfError, Role, SessionKeys};
+
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+/// Circuit for computing TLS 1.3 handshake keys.
+static HANDSHAKE_KEYS_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuit for computing TLS 1.3 application keys.
+static APPLICATION_KEYS_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuit for computing TLS 1.3 verify data.
+static VD_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+pub(crate) struct HashState {
+    pub(crate) outer_hash_state: ValueRef,
+    pub(crate) inner_hash_state: ValueRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct VerifyData {
+    pub(crate) handshake_hash: ValueRef,
+    pub(crate) vd: ValueRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct AppKeys {
+    pub(crate) handshake_hash: ValueRef,
+    pub(crate) keys: SessionKeys,
+}
+
+/// MPC TLS 1.3 key schedule.
+///
+/// Only the SHA-256 based cipher suites are supported.
+///
+/// Contrary to [`MpcPrf`](crate::MpcPrf), the circuits are not preprocessed during setup,
+/// as it is not known in advance which protocol version will be negotiated.
+pub struct MpcKeySchedule<E> {
+    config: PrfConfig,
+    state: state::State,
+    thread: E,
+}
+
+impl<E> Debug for MpcKeySchedule<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MpcKeySchedule")
+            .field("config", &self.config)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<E> MpcKeySchedule<E>
+where
+    E: Load + Memory + Execute + DecodePrivate + Send,
+{
+    /// Creates a new instance of the key schedule.
+    pub fn new(config: PrfConfig, thread: E) -> MpcKeySchedule<E> {
+        MpcKeySchedule {
+            config,
+            state: state::State::Initialized,
+            thread,
+        }
+    }
+
+    fn check_role(&self, private: bool) -> Result<(), PrfError> {
+        match (private, self.config.role) {
+            (true, Role::Follower) => Err(PrfError::RoleError(
+                "only leader can provide inputs".to_string(),
+            )),
+            (false, Role::Leader) => Err(PrfError::RoleError(
+                "leader must provide inputs".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Executes a circuit which computes the TLS 1.3 handshake keys.
+    async fn execute_handshake_keys(
+        &mut self,
+        hello_hash: Option<[u8; 32]>,
+    ) -> Result<SessionKeys, PrfError> {
+        let state::HandshakeKeys {
+            pms,
+            hello_hash: hello_hash_ref,
+            keys,
+            cf_hash_state,
+            sf_hash_state,
+            ms_hash_state,
+            cf_vd,
+            sf_vd,
+            app_keys,
+        } = std::mem::replace(&mut self.state, state::State::Error).try_into_handshake_keys()?;
+
+        let circ = get_or_build(&HANDSHAKE_KEYS_CIRC, build_handshake_keys).await;
+
+        if let Some(hello_hash) = hello_hash {
+            self.thread.assign(&hello_hash_ref, hello_hash)?;
+        }
+
+        self.thread
+            .execute(
+                circ,
+                &[pms, hello_hash_ref],
+                &[
+                    keys.client_write_key.clone(),
+                    keys.server_write_key.clone(),
+                    keys.client_iv.clone(),
+                    keys.server_iv.clone(),
+                    cf_hash_state.outer_hash_state.clone(),
+                    cf_hash_state.inner_hash_state.clone(),
+                    sf_hash_state.outer_hash_state.clone(),
+                    sf_hash_state.inner_hash_state.clone(),
+                    ms_hash_state.outer_hash_state.clone(),
+                    ms_hash_state.inner_hash_state.clone(),
+                ],
+            )
+            .await?;
+
+        self.state = state::State::ServerFinished(state::ServerFinished {
+            cf_hash_state,
+            sf_hash_state,
+            ms_hash_state,
+            cf_vd,
+            sf_vd,
+            app_keys,
+        });
+
+        Ok(keys)
+    }
+
+    async fn execute_vd(
+        &mut self,
+        hash_state: HashState,
+        vd: VerifyData,
+        handshake_hash: Option<[u8; 32]>,
+    ) -> Result<Option<[u8; 32]>, PrfError> {
+        let circ = get_or_build(&VD_CIRC, build_tls13_verify_data).await;
+
+        if let Some(handshake_hash) = handshake_hash {
+            self.thread.assign(&vd.handshake_hash, handshake_hash)?;
+        }
+
+        self.thread
+            .execute(
+                circ,
+                &[
+                    hash_state.outer_hash_state,
+                    hash_state.inner_hash_state,
+                    vd.handshake_hash,
+                ],
+                &[vd.vd.clone()],
+            )
+            .await?;
+
+        if handshake_hash.is_some() {
+            let mut outputs = self.thread.decode_private(&[vd.vd]).await?;
+            let vd: [u8; 32] = outputs.remove(0).try_into().expect("vd is 32 bytes");
+
+            Ok(Some(vd))
+        } else {
+            self.thread.decode_blind(&[vd.vd]).await?;
+
+            Ok(None)
+        }
+    }
+
+    async fn execute_sf_vd(
+        &mut self,
+        handshake_hash: Option<[u8; 32]>,
+    ) -> Result<Option<[u8; 32]>, PrfError> {
+        let state::ServerFinished {
+            cf_hash_state,
+            sf_hash_state,
+            ms_hash_state,
+            cf_vd,
+            sf_vd,
+            app_keys,
+        } = std::mem::replace(&mut self.state, state::State::Error).try_into_server_finished()?;
+
+        let vd = self
+            .execute_vd(sf_hash_state, sf_vd, handshake_hash)
+            .await?;
+
+        self.state = state::State::ClientFinished(state::ClientFinished {
+            cf_hash_state,
+            ms_hash_state,
+            cf_vd,
+            app_keys,
+        });
+
+        Ok(vd)
+    }
+
+    async fn execute_cf_vd(
+        &mut self,
+        handshake_hash: Option<[u8; 32]>,
+    ) -> Result<Option<[u8; 32]>, PrfError> {
+        let state::ClientFinished {
+            cf_hash_state,
+            ms_hash_state,
+            cf_vd,
+            app_keys,
+        } = std::mem::replace(&mut self.state, state::State::Error).try_into_client_finished()?;
+
+        let vd = self
+            .execute_vd(cf_hash_state, cf_vd, handshake_hash)
+            .await?;
+
+        self.state = state::State::ApplicationKeys(state::ApplicationKeys {
+            ms_hash_state,
+            app_keys,
+        });
+
+        Ok(vd)
+    }
+
+    /// Executes a circuit which computes the TLS 1.3 application keys.
+    async fn execute_application_keys(
+        &mut self,
+        handshake_hash: Option<[u8; 32]>,
+    ) -> Result<SessionKeys, PrfError> {
+        let state::ApplicationKeys {
+            ms_hash_state,
+            app_keys,
+        } = std::mem::replace(&mut self.state, state::State::Error).try_into_application_keys()?;
+
+        let circ = get_or_build(&APPLICATION_KEYS_CIRC, build_application_keys).await;
+
+        if let Some(handshake_hash) = handshake_hash {
+            self.thread
+                .assign(&app_keys.handshake_hash, handshake_hash)?;
+        }
+
+        let keys = app_keys.keys;
+
+        self.thread
+            .execute(
+                circ,
+                &[
+                    ms_hash_state.outer_hash_state,
+                    ms_hash_state.inner_hash_state,
+                    app_keys.handshake_hash,
+                ],
+                &[
+                    keys.client_write_key.clone(),
+                    keys.server_write_key.clone(),
+                    keys.client_iv.clone(),
+                    keys.server_iv.clone(),
+                ],
+            )
+            .await?;
+
+        self.state = state::State::Complete;
+
+        Ok(keys)
+    }
+}
+
+#[async_trait]
+impl<E> KeySchedule for MpcKeySchedule<E>
+where
+    E: Memory + Load + Execute + Decode + DecodePrivate + Send,
+{
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn setup(&mut self, pms: ValueRef) -> Result<(), PrfError> {
+        std::mem::replace(&mut self.state, state::State::Error).try_into_initialized()?;
+
+        let visibility = match self.config.role {
+            Role::Leader => Visibility::Private,
+            Role::Follower => Visibility::Blind,
+        };
+
+        let thread = &mut self.thread;
+
+        let hello_hash = thread.new_input::<[u8; 32]>("tls13/hello_hash", visibility)?;
+        let keys = SessionKeys {
+            client_write_key: thread.new_output::<[u8; 16]>("tls13/hs/client_write_key")?,
+            server_write_key: thread.new_output::<[u8; 16]>("tls13/hs/server_write_key")?,
+            client_iv: thread.new_output::<[u8; 12]>("tls13/hs/client_write_iv")?,
+            server_iv: thread.new_output::<[u8; 12]>("tls13/hs/server_write_iv")?,
+        };
+
+        let mut new_hash_state = |name: &str| -> Result<HashState, PrfError> {
+            Ok(HashState {
+                outer_hash_state: thread
+                    .new_output::<[u32; 8]>(&format!("tls13/{name}_outer_hash_state"))?,
+                inner_hash_state: thread
+                    .new_output::<[u32; 8]>(&format!("tls13/{name}_inner_hash_state"))?,
+            })
+        };
+
+        let cf_hash_state = new_hash_state("cf")?;
+        let sf_hash_state = new_hash_state("sf")?;
+        let ms_hash_state = new_hash_state("ms")?;
+
+        let mut new_vd = |name: &str| -> Result<VerifyData, PrfError> {
+            Ok(VerifyData {
+                handshake_hash: thread
+                    .new_input::<[u8; 32]>(&format!("tls13/{name}/handshake_hash"), visibility)?,
+                vd: thread.new_output::<[u8; 32]>(&format!("tls13/{name}/vd"))?,
+            })
+        };
+
+        let cf_vd = new_vd("client_finished")?;
+        let sf_vd = new_vd("server_finished")?;
+
+        let app_keys = AppKeys {
+            handshake_hash: thread.new_input::<[u8; 32]>("tls13/ap/handshake_hash", visibility)?,
+            keys: SessionKeys {
+                client_write_key: thread.new_output::<[u8; 16]>("tls13/ap/client_write_key")?,
+                server_write_key: thread.new_output::<[u8; 16]>("tls13/ap/server_write_key")?,
+                client_iv: thread.new_output::<[u8; 12]>("tls13/ap/client_write_iv")?,
+                server_iv: thread.new_output::<[u8; 12]>("tls13/ap/server_write_iv")?,
+            },
+        };
+
+        self.state = state::State::HandshakeKeys(state::HandshakeKeys {
+            pms,
+            hello_hash,
+            keys,
+            cf_hash_state,
+            sf_hash_state,
+            ms_hash_state,
+            cf_vd,
+            sf_vd,
+            app_keys,
+        });
+
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_handshake_keys_private(
+        &mut self,
+        hello_hash: [u8; 32],
+    ) -> Result<SessionKeys, PrfError> {
+        self.check_role(true)?;
+        self.execute_handshake_keys(Some(hello_hash)).await
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_server_finished_vd_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<[u8; 32], PrfError> {
+        self.check_role(true)?;
+        self.execute_sf_vd(Some(handshake_hash))
+            .await
+            .map(|vd| vd.expect("vd is decoded"))
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_client_finished_vd_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<[u8; 32], PrfError> {
+        self.check_role(true)?;
+        self.execute_cf_vd(Some(handshake_hash))
+            .await
+            .map(|vd| vd.expect("vd is decoded"))
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_application_keys_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<SessionKeys, PrfError> {
+        self.check_role(true)?;
+        self.execute_application_keys(Some(handshake_hash)).await
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_handshake_keys_blind(&mut self) -> Result<SessionKeys, PrfError> {
+        self.check_role(false)?;
+        self.execute_handshake_keys(None).await
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_server_finished_vd_blind(&mut self) -> Result<(), PrfError> {
+        self.check_role(false)?;
+        self.execute_sf_vd(None).await.map(|_| ())
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_client_finished_vd_blind(&mut self) -> Result<(), PrfError> {
+        self.check_role(false)?;
+        self.execute_cf_vd(None).await.map(|_| ())
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_application_keys_blind(&mut self) -> Result<SessionKeys, PrfError> {
+        self.check_role(false)?;
+        self.execute_application_keys(None).await
+    }
+}
+
+/// Returns the circuit, building it first if necessary.
+async fn get_or_build(
+    circ: &'static OnceLock<Arc<Circuit>>,
+    build: fn() -> Arc<Circuit>,
+) -> Arc<Circuit> {
+    if circ.get().is_none() {
+        _ = circ.set(Backend::spawn(build).await);
+    }
+
+    circ.get().expect("circuit is set").clone()
+}
+
+pub(crate) mod state {
+    use super::*;
+    use enum_try_as_inner::EnumTryAsInner;
+
+    #[derive(Debug, EnumTryAsInner)]
+    #[derive_err(Debug)]
+    pub(crate) enum State {
+        Initialized,
+        HandshakeKeys(HandshakeKeys),
+        ServerFinished(ServerFinished),
+        ClientFinished(ClientFinished),
+        ApplicationKeys(ApplicationKeys),
+        Complete,
+        Error,
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct HandshakeKeys {
+        pub(crate) pms: ValueRef,
+        pub(crate) hello_hash: ValueRef,
+        pub(crate) keys: SessionKeys,
+        pub(crate) cf_hash_state: HashState,
+        pub(crate) sf_hash_state: HashState,
+        pub(crate) ms_hash_state: HashState,
+        pub(crate) cf_vd: VerifyData,
+        pub(crate) sf_vd: VerifyData,
+        pub(crate) app_keys: AppKeys,
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct ServerFinished {
+        pub(crate) cf_hash_state: HashState,
+        pub(crate) sf_hash_state: HashState,
+        pub(crate) ms_hash_state: HashState,
+        pub(crate) cf_vd: VerifyData,
+        pub(crate) sf_vd: VerifyData,
+        pub(crate) app_keys: AppKeys,
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct ClientFinished {
+        pub(crate) cf_hash_state: HashState,
+        pub(crate) ms_hash_state: HashState,
+        pub(crate) cf_vd: VerifyData,
+        pub(crate) app_keys: AppKeys,
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct ApplicationKeys {
+        pub(crate) ms_hash_state: HashState,
+        pub(crate) app_keys: AppKeys,
+    }
+}
@@ -1,4 +1,5 @@
-//! This module contains the protocol for computing TLS SHA-256 HMAC PRF.
+//! This module contains the protocol for computing the TLS 1.2 PRF, with HMAC-SHA256 or HMAC-SHA384,
+//! and the TLS 1.3 key schedule.
 
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
 #![deny(clippy::all)]
@@ -6,12 +7,16 @@
 
 mod config;
 mod error;
+mod key_schedule;
 mod prf;
 
 pub use config::{PrfConfig, PrfConfigBuilder, PrfConfigBuilderError, Role};
 pub use error::PrfError;
+pub use key_schedule::MpcKeySchedule;
 pub use prf::MpcPrf;
 
+pub use hmac_sha256_circuits::{PrfHash, PMS_LEN};
+
 use async_trait::async_trait;
 
 use mpz_garble::value::ValueRef;
@@ -19,7 +24,7 @@ use mpz_garble::value::ValueRef;
 pub(crate) static CF_LABEL: &[u8] = b"client finished";
 pub(crate) static SF_LABEL: &[u8] = b"server finished";
 
-/// Session keys computed by the PRF.
+/// Session keys computed by the PRF or the TLS 1.3 key schedule.
 #[derive(Debug)]
 pub struct SessionKeys {
     /// Client write key.
@@ -27,11 +32,54 @@ pub struct SessionKeys {
     /// Server write key.
     pub server_write_key: ValueRef,
     /// Client IV.
+    ///
+    /// 4 bytes for TLS 1.2, 12 bytes for TLS 1.3.
     pub client_iv: ValueRef,
     /// Server IV.
+    ///
+    /// 4 bytes for TLS 1.2, 12 bytes for TLS 1.3.
     pub server_iv: ValueRef,
 }
 
+/// Parameters of the TLS 1.2 key derivation, which are determined by the cipher suite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PrfParams {
+    /// Hash function of the PRF.
+    pub hash: PrfHash,
+    /// Length of the write keys.
+    pub key_len: usize,
+    /// Length of the fixed IVs.
+    pub iv_len: usize,
+}
+
+impl PrfParams {
+    /// Creates new PRF parameters.
+    pub fn new(hash: PrfHash, key_len: usize, iv_len: usize) -> Self {
+        Self {
+            hash,
+            key_len,
+            iv_len,
+        }
+    }
+
+    /// Returns an identifier for these parameters.
+    pub(crate) fn id(&self) -> String {
+        let hash = match self.hash {
+            PrfHash::Sha256 => "sha256",
+            PrfHash::Sha384 => "sha384",
+        };
+
+        format!("{hash}/{}/{}", self.key_len, self.iv_len)
+    }
+}
+
+impl Default for PrfParams {
+    /// Parameters of the AES-128-GCM cipher suites, with the SHA-256 PRF.
+    fn default() -> Self {
+        Self::new(PrfHash::Sha256, 16, 4)
+    }
+}
+
 /// PRF trait for computing TLS PRF.
 #[async_trait]
 pub trait Prf {
@@ -39,9 +87,15 @@ pub trait Prf {
     ///
     /// # Arguments
     ///
-    /// * `pms` - The pre-master secret.
+    /// * `pms` - The pre-master secret, zero-padded to [`PMS_LEN`] bytes.
     async fn setup(&mut self, pms: ValueRef) -> Result<(), PrfError>;
 
+    /// Sets the key derivation parameters of the negotiated cipher suite.
+    ///
+    /// Must be called by both parties after setup and before the session keys are computed,
+    /// if the parameters differ from the configured ones.
+    async fn set_params(&mut self, params: PrfParams) -> Result<(), PrfError>;
+
     /// Computes the session keys using the provided client random, server random and PMS.
     async fn compute_session_keys_private(
         &mut self,
@@ -50,15 +104,19 @@ pub trait Prf {
     ) -> Result<SessionKeys, PrfError>;
 
     /// Computes the client finished verify data using the provided handshake hash.
+    ///
+    /// The length of the handshake hash is that of the PRF hash function.
     async fn compute_client_finished_vd_private(
         &mut self,
-        handshake_hash: [u8; 32],
+        handshake_hash: &[u8],
     ) -> Result<[u8; 12], PrfError>;
 
     /// Computes the server finished verify data using the provided handshake hash.
+    ///
+    /// The length of the handshake hash is that of the PRF hash function.
     async fn compute_server_finished_vd_private(
         &mut self,
-        handshake_hash: [u8; 32],
+        handshake_hash: &[u8],
     ) -> Result<[u8; 12], PrfError>;
 
     /// Computes the session keys using randoms provided by the other party.
@@ -71,11 +129,61 @@ pub trait Prf {
     async fn compute_server_finished_vd_blind(&mut self) -> Result<(), PrfError>;
 }
 
+/// Key schedule trait for computing the TLS 1.3 traffic keys and verify data.
+#[async_trait]
+pub trait KeySchedule {
+    /// Performs any necessary one-time setup.
+    ///
+    /// # Arguments
+    ///
+    /// * `pms` - The (EC)DHE shared secret, zero-padded to [`PMS_LEN`] bytes.
+    async fn setup(&mut self, pms: ValueRef) -> Result<(), PrfError>;
+
+    /// Computes the handshake traffic keys using the provided hash of ClientHello..ServerHello.
+    async fn compute_handshake_keys_private(
+        &mut self,
+        hello_hash: [u8; 32],
+    ) -> Result<SessionKeys, PrfError>;
+
+    /// Computes the server finished verify data using the provided handshake hash.
+    async fn compute_server_finished_vd_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<[u8; 32], PrfError>;
+
+    /// Computes the client finished verify data using the provided handshake hash.
+    async fn compute_client_finished_vd_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<[u8; 32], PrfError>;
+
+    /// Computes the application traffic keys using the provided hash of ClientHello..server Finished.
+    async fn compute_application_keys_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<SessionKeys, PrfError>;
+
+    /// Computes the handshake traffic keys using the hash provided by the other party.
+    async fn compute_handshake_keys_blind(&mut self) -> Result<SessionKeys, PrfError>;
+
+    /// Computes the server finished verify data using the handshake hash provided by the other party.
+    async fn compute_server_finished_vd_blind(&mut self) -> Result<(), PrfError>;
+
+    /// Computes the client finished verify data using the handshake hash provided by the other party.
+    async fn compute_client_finished_vd_blind(&mut self) -> Result<(), PrfError>;
+
+    /// Computes the application traffic keys using the hash provided by the other party.
+    async fn compute_application_keys_blind(&mut self) -> Result<SessionKeys, PrfError>;
+}
+
 #[cfg(test)]
 mod tests {
     use mpz_garble::{protocol::deap::mock::create_mock_deap_vm, Decode, Memory, Vm};
 
-    use hmac_sha256_circuits::{hmac_sha256_partial, prf, session_keys};
+    use hmac_sha256_circuits::{
+        application_keys, handshake_keys, hmac_sha256_partial, hmac_sha384_partial, prf,
+        prf_sha384, session_keys, session_keys_sha384, tls13_verify_data, verify_data_sha384,
+    };
 
     use super::*;
 
@@ -90,6 +198,13 @@ mod tests {
         ms.try_into().unwrap()
     }
 
+    /// Pads a 32-byte PMS to the length expected by the circuits.
+    fn padded_pms(pms: [u8; 32]) -> [u8; PMS_LEN] {
+        let mut padded = [0u8; PMS_LEN];
+        padded[..32].copy_from_slice(&pms);
+        padded
+    }
+
     fn compute_vd(ms: [u8; 48], label: &[u8], hs_hash: [u8; 32]) -> [u8; 12] {
         let (outer_state, inner_state) = hmac_sha256_partial(&ms);
         let vd = prf(outer_state, inner_state, &hs_hash, label, 12);
@@ -111,14 +226,18 @@ mod tests {
 
         // Setup public PMS for testing
         let leader_pms = leader_test_thread
-            .new_public_input::<[u8; 32]>("pms")
+            .new_public_input::<[u8; PMS_LEN]>("pms")
             .unwrap();
         let follower_pms = follower_test_thread
-            .new_public_input::<[u8; 32]>("pms")
+            .new_public_input::<[u8; PMS_LEN]>("pms")
             .unwrap();
 
-        leader_test_thread.assign(&leader_pms, pms).unwrap();
-        follower_test_thread.assign(&follower_pms, pms).unwrap();
+        leader_test_thread
+            .assign(&leader_pms, padded_pms(pms))
+            .unwrap();
+        follower_test_thread
+            .assign(&follower_pms, padded_pms(pms))
+            .unwrap();
 
         let mut leader = MpcPrf::new(
             PrfConfig::builder().role(Role::Leader).build().unwrap(),
@@ -195,7 +314,7 @@ mod tests {
         let sf_hs_hash = [2u8; 32];
 
         let (cf_vd, _) = futures::try_join!(
-            leader.compute_client_finished_vd_private(cf_hs_hash),
+            leader.compute_client_finished_vd_private(&cf_hs_hash),
             follower.compute_client_finished_vd_blind()
         )
         .unwrap();
@@ -205,7 +324,7 @@ mod tests {
         assert_eq!(cf_vd, expected_cf_vd);
 
         let (sf_vd, _) = futures::try_join!(
-            leader.compute_server_finished_vd_private(sf_hs_hash),
+            leader.compute_server_finished_vd_private(&sf_hs_hash),
             follower.compute_server_finished_vd_blind()
         )
         .unwrap();
@@ -214,4 +333,261 @@ mod tests {
 
         assert_eq!(sf_vd, expected_sf_vd);
     }
+
+    #[ignore = "expensive"]
+    #[tokio::test]
+    async fn test_prf_sha384() {
+        let pms = [42u8; 32];
+        let client_random = [69u8; 32];
+        let server_random: [u8; 32] = [96u8; 32];
+        let params = PrfParams::new(PrfHash::Sha384, 32, 4);
+
+        let (mut leader_vm, mut follower_vm) = create_mock_deap_vm("test").await;
+
+        let mut leader_test_thread = leader_vm.new_thread("test").await.unwrap();
+        let mut follower_test_thread = follower_vm.new_thread("test").await.unwrap();
+
+        // Setup public PMS for testing
+        let leader_pms = leader_test_thread
+            .new_public_input::<[u8; PMS_LEN]>("pms")
+            .unwrap();
+        let follower_pms = follower_test_thread
+            .new_public_input::<[u8; PMS_LEN]>("pms")
+            .unwrap();
+
+        leader_test_thread
+            .assign(&leader_pms, padded_pms(pms))
+            .unwrap();
+        follower_test_thread
+            .assign(&follower_pms, padded_pms(pms))
+            .unwrap();
+
+        let mut leader = MpcPrf::new(
+            PrfConfig::builder().role(Role::Leader).build().unwrap(),
+            leader_vm.new_thread("prf/0").await.unwrap(),
+            leader_vm.new_thread("prf/1").await.unwrap(),
+        );
+        let mut follower = MpcPrf::new(
+            PrfConfig::builder().role(Role::Follower).build().unwrap(),
+            follower_vm.new_thread("prf/0").await.unwrap(),
+            follower_vm.new_thread("prf/1").await.unwrap(),
+        );
+
+        futures::try_join!(leader.setup(leader_pms), follower.setup(follower_pms)).unwrap();
+        futures::try_join!(leader.set_params(params), follower.set_params(params)).unwrap();
+
+        let (leader_session_keys, follower_session_keys) = futures::try_join!(
+            leader.compute_session_keys_private(client_random, server_random),
+            follower.compute_session_keys_blind()
+        )
+        .unwrap();
+
+        let leader_refs = [
+            leader_session_keys.client_write_key,
+            leader_session_keys.server_write_key,
+            leader_session_keys.client_iv,
+            leader_session_keys.server_iv,
+        ];
+        let follower_refs = [
+            follower_session_keys.client_write_key,
+            follower_session_keys.server_write_key,
+            follower_session_keys.client_iv,
+            follower_session_keys.server_iv,
+        ];
+
+        // Decode session keys
+        let (leader_session_keys, _) = futures::try_join!(
+            async move { leader_test_thread.decode(&leader_refs).await },
+            async move { follower_test_thread.decode(&follower_refs).await }
+        )
+        .unwrap();
+
+        let (expected_cwk, expected_swk, expected_civ, expected_siv) =
+            session_keys_sha384(pms, client_random, server_random, 32, 4);
+
+        let keys: Vec<Vec<u8>> = leader_session_keys
+            .into_iter()
+            .map(|key| key.try_into().unwrap())
+            .collect();
+
+        assert_eq!(
+            keys,
+            vec![expected_cwk, expected_swk, expected_civ, expected_siv]
+        );
+
+        let (outer_state, inner_state) = hmac_sha384_partial(&pms);
+        let seed = client_random
+            .iter()
+            .chain(&server_random)
+            .copied()
+            .collect::<Vec<_>>();
+        let ms = prf_sha384(outer_state, inner_state, &seed, b"master secret", 48);
+        let (ms_outer_state, ms_inner_state) = hmac_sha384_partial(&ms);
+
+        let cf_hs_hash = [1u8; 48];
+
+        let (cf_vd, _) = futures::try_join!(
+            leader.compute_client_finished_vd_private(&cf_hs_hash),
+            follower.compute_client_finished_vd_blind()
+        )
+        .unwrap();
+
+        assert_eq!(
+            cf_vd,
+            verify_data_sha384(ms_outer_state, ms_inner_state, CF_LABEL, cf_hs_hash)
+        );
+
+        // The handshake hash must match the hash function of the PRF.
+        let err = leader
+            .compute_server_finished_vd_private(&[2u8; 32])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, PrfError::InvalidInput(_)));
+    }
+
+    #[ignore = "expensive"]
+    #[tokio::test]
+    async fn test_key_schedule() {
+        let pms = [42u8; 32];
+        let hello_hash = [1u8; 32];
+        let sf_hs_hash = [2u8; 32];
+        let cf_hs_hash = [3u8; 32];
+
+        let (mut leader_vm, mut follower_vm) = create_mock_deap_vm("test").await;
+
+        let mut leader_test_thread = leader_vm.new_thread("test").await.unwrap();
+        let mut follower_test_thread = follower_vm.new_thread("test").await.unwrap();
+
+        let leader_pms = leader_test_thread
+            .new_public_input::<[u8; PMS_LEN]>("pms")
+            .unwrap();
+        let follower_pms = follower_test_thread
+            .new_public_input::<[u8; PMS_LEN]>("pms")
+            .unwrap();
+
+        leader_test_thread
+            .assign(&leader_pms, padded_pms(pms))
+            .unwrap();
+        follower_test_thread
+            .assign(&follower_pms, padded_pms(pms))
+            .unwrap();
+
+        let mut leader = MpcKeySchedule::new(
+            PrfConfig::builder().role(Role::Leader).build().unwrap(),
+            leader_vm.new_thread("key_schedule").await.unwrap(),
+        );
+        let mut follower = MpcKeySchedule::new(
+            PrfConfig::builder().role(Role::Follower).build().unwrap(),
+            follower_vm.new_thread("key_schedule").await.unwrap(),
+        );
+
+        futures::try_join!(leader.setup(leader_pms), follower.setup(follower_pms)).unwrap();
+
+        let (leader_keys, follower_keys) = futures::try_join!(
+            leader.compute_handshake_keys_private(hello_hash),
+            follower.compute_handshake_keys_blind()
+        )
+        .unwrap();
+
+        let (
+            expected_cwk,
+            expected_swk,
+            expected_civ,
+            expected_siv,
+            client_finished_key,
+            server_finished_key,
+            master_secret,
+        ) = handshake_keys(pms, hello_hash);
+
+        let (leader_keys, follower_keys) = futures::try_join!(
+            leader_test_thread.decode(&[
+                leader_keys.client_write_key,
+                leader_keys.server_write_key,
+                leader_keys.client_iv,
+                leader_keys.server_iv
+            ]),
+            follower_test_thread.decode(&[
+                follower_keys.client_write_key,
+                follower_keys.server_write_key,
+                follower_keys.client_iv,
+                follower_keys.server_iv
+            ])
+        )
+        .unwrap();
+
+        for keys in [leader_keys, follower_keys] {
+            let cwk: [u8; 16] = keys[0].clone().try_into().unwrap();
+            let swk: [u8; 16] = keys[1].clone().try_into().unwrap();
+            let civ: [u8; 12] = keys[2].clone().try_into().unwrap();
+            let siv: [u8; 12] = keys[3].clone().try_into().unwrap();
+
+            assert_eq!(cwk, expected_cwk);
+            assert_eq!(swk, expected_swk);
+            assert_eq!(civ, expected_civ);
+            assert_eq!(siv, expected_siv);
+        }
+
+        let (sf_vd, _) = futures::try_join!(
+            leader.compute_server_finished_vd_private(&sf_hs_hash),
+            follower.compute_server_finished_vd_blind()
+        )
+        .unwrap();
+
+        let (outer_state, inner_state) = hmac_sha256_partial(&server_finished_key);
+        assert_eq!(
+            sf_vd,
+            tls13_verify_data(outer_state, inner_state, sf_hs_hash)
+        );
+
+        let (cf_vd, _) = futures::try_join!(
+            leader.compute_client_finished_vd_private(&cf_hs_hash),
+            follower.compute_client_finished_vd_blind()
+        )
+        .unwrap();
+
+        let (outer_state, inner_state) = hmac_sha256_partial(&client_finished_key);
+        assert_eq!(
+            cf_vd,
+            tls13_verify_data(outer_state, inner_state, cf_hs_hash)
+        );
+
+        let (leader_keys, follower_keys) = futures::try_join!(
+            leader.compute_application_keys_private(cf_hs_hash),
+            follower.compute_application_keys_blind()
+        )
+        .unwrap();
+
+        let (outer_state, inner_state) = hmac_sha256_partial(&master_secret);
+        let (expected_cwk, expected_swk, expected_civ, expected_siv) =
+            application_keys(outer_state, inner_state, cf_hs_hash);
+
+        let (leader_keys, follower_keys) = futures::try_join!(
+            leader_test_thread.decode(&[
+                leader_keys.client_write_key,
+                leader_keys.server_write_key,
+                leader_keys.client_iv,
+                leader_keys.server_iv
+            ]),
+            follower_test_thread.decode(&[
+                follower_keys.client_write_key,
+                follower_keys.server_write_key,
+                follower_keys.client_iv,
+                follower_keys.server_iv
+            ])
+        )
+        .unwrap();
+
+        for keys in [leader_keys, follower_keys] {
+            let cwk: [u8; 16] = keys[0].clone().try_into().unwrap();
+            let swk: [u8; 16] = keys[1].clone().try_into().unwrap();
+            let civ: [u8; 12] = keys[2].clone().try_into().unwrap();
+            let siv: [u8; 12] = keys[3].clone().try_into().unwrap();
+
+            assert_eq!(cwk, expected_cwk);
+            assert_eq!(swk, expected_swk);
+            assert_eq!(civ, expected_civ);
+            assert_eq!(siv, expected_siv);
+        }
+    }
 }
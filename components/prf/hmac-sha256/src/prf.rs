@@ -1,29 +1,31 @@
 use std::{
+    collections::HashMap,
     fmt::Debug,
-    sync::{Arc, OnceLock},
+    sync::{Arc, Mutex, OnceLock},
 };
 
 use async_trait::async_trait;
 
-use hmac_sha256_circuits::{build_session_keys, build_verify_data};
+use hmac_sha256_circuits::{build_session_keys, build_verify_data, PrfHash};
 use mpz_circuits::Circuit;
 use mpz_garble::{
     config::Visibility, value::ValueRef, Decode, DecodePrivate, Execute, Load, Memory,
 };
 use utils_aio::non_blocking_backend::{Backend, NonBlockingBackend};
 
-use crate::{Prf, PrfConfig, PrfError, Role, SessionKeys, CF_LABEL, SF_LABEL};
+use crate::{Prf, PrfConfig, PrfError, PrfParams, Role, SessionKeys, CF_LABEL, SF_LABEL};
 
 #[cfg(feature = "tracing")]
 use tracing::instrument;
 
-/// Circuit for computing TLS session keys.
-static SESSION_KEYS_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
-/// Circuit for computing TLS client verify data.
-static CLIENT_VD_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
-/// Circuit for computing TLS server verify data.
-static SERVER_VD_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuits for computing TLS session keys.
+static SESSION_KEYS_CIRCS: OnceLock<Mutex<HashMap<PrfParams, Arc<Circuit>>>> = OnceLock::new();
+/// Circuits for computing TLS client verify data.
+static CLIENT_VD_CIRCS: OnceLock<Mutex<HashMap<PrfHash, Arc<Circuit>>>> = OnceLock::new();
+/// Circuits for computing TLS server verify data.
+static SERVER_VD_CIRCS: OnceLock<Mutex<HashMap<PrfHash, Arc<Circuit>>>> = OnceLock::new();
 
+#[derive(Clone, Copy)]
 enum Msg {
     Cf,
     Sf,
@@ -43,11 +45,16 @@ pub(crate) struct HashState {
 
 #[derive(Debug)]
 pub(crate) struct VerifyData {
+    pub(crate) hash: PrfHash,
     pub(crate) handshake_hash: ValueRef,
     pub(crate) vd: ValueRef,
 }
 
-/// MPC PRF for computing TLS HMAC-SHA256 PRF.
+/// MPC PRF for computing the TLS 1.2 PRF, with either HMAC-SHA256 or HMAC-SHA384.
+///
+/// The circuits are preprocessed during setup for the parameters in the [`PrfConfig`]. If the
+/// negotiated cipher suite requires other parameters, the circuits are instead processed
+/// during the handshake, see [`Prf::set_params`].
 pub struct MpcPrf<E> {
     config: PrfConfig,
     state: state::State,
@@ -78,6 +85,52 @@ where
         }
     }
 
+    fn visibility(&self) -> Visibility {
+        match self.config.role {
+            Role::Leader => Visibility::Private,
+            Role::Follower => Visibility::Blind,
+        }
+    }
+
+    /// Allocates the values of the session keys and verify data circuits and loads them.
+    async fn load(
+        &mut self,
+        pms: ValueRef,
+        params: PrfParams,
+    ) -> Result<state::SessionKeys, PrfError> {
+        let visibility = self.visibility();
+
+        let (randoms, hash_state, keys) =
+            setup_session_keys(&mut self.thread_0, pms.clone(), params, visibility).await?;
+
+        let (cf_vd, sf_vd) = futures::try_join!(
+            setup_finished_msg(
+                &mut self.thread_0,
+                Msg::Cf,
+                params,
+                hash_state.clone(),
+                visibility
+            ),
+            setup_finished_msg(
+                &mut self.thread_1,
+                Msg::Sf,
+                params,
+                hash_state.clone(),
+                visibility
+            ),
+        )?;
+
+        Ok(state::SessionKeys {
+            pms,
+            params,
+            randoms,
+            hash_state,
+            keys,
+            cf_vd,
+            sf_vd,
+        })
+    }
+
     /// Executes a circuit which computes TLS session keys.
     async fn execute_session_keys(
         &mut self,
@@ -85,6 +138,7 @@ where
     ) -> Result<SessionKeys, PrfError> {
         let state::SessionKeys {
             pms,
+            params,
             randoms: randoms_refs,
             hash_state,
             keys,
@@ -92,9 +146,7 @@ where
             sf_vd,
         } = std::mem::replace(&mut self.state, state::State::Error).try_into_session_keys()?;
 
-        let circ = SESSION_KEYS_CIRC
-            .get()
-            .expect("session keys circuit is set");
+        let circ = session_keys_circ(params).await;
 
         if let Some((client_random, server_random)) = randoms {
             self.thread_0
@@ -129,7 +181,7 @@ where
 
     async fn execute_cf_vd(
         &mut self,
-        handshake_hash: Option<[u8; 32]>,
+        handshake_hash: Option<&[u8]>,
     ) -> Result<Option<[u8; 12]>, PrfError> {
         let state::ClientFinished {
             hash_state,
@@ -137,11 +189,12 @@ where
             sf_vd,
         } = std::mem::replace(&mut self.state, state::State::Error).try_into_client_finished()?;
 
-        let circ = CLIENT_VD_CIRC.get().expect("client vd circuit is set");
+        let circ = vd_circ(Msg::Cf, cf_vd.hash).await;
 
         if let Some(handshake_hash) = handshake_hash {
+            check_hash_len(cf_vd.hash, handshake_hash)?;
             self.thread_0
-                .assign(&cf_vd.handshake_hash, handshake_hash)?;
+                .assign(&cf_vd.handshake_hash, handshake_hash.to_vec())?;
         }
 
         self.thread_0
@@ -174,16 +227,17 @@ where
 
     async fn execute_sf_vd(
         &mut self,
-        handshake_hash: Option<[u8; 32]>,
+        handshake_hash: Option<&[u8]>,
     ) -> Result<Option<[u8; 12]>, PrfError> {
         let state::ServerFinished { hash_state, sf_vd } =
             std::mem::replace(&mut self.state, state::State::Error).try_into_server_finished()?;
 
-        let circ = SERVER_VD_CIRC.get().expect("server vd circuit is set");
+        let circ = vd_circ(Msg::Sf, sf_vd.hash).await;
 
         if let Some(handshake_hash) = handshake_hash {
+            check_hash_len(sf_vd.hash, handshake_hash)?;
             self.thread_1
-                .assign(&sf_vd.handshake_hash, handshake_hash)?;
+                .assign(&sf_vd.handshake_hash, handshake_hash.to_vec())?;
         }
 
         self.thread_1
@@ -224,28 +278,27 @@ where
     async fn setup(&mut self, pms: ValueRef) -> Result<(), PrfError> {
         std::mem::replace(&mut self.state, state::State::Error).try_into_initialized()?;
 
-        let visibility = match self.config.role {
-            Role::Leader => Visibility::Private,
-            Role::Follower => Visibility::Blind,
-        };
-
         // Perform pre-computation for all circuits.
-        let (randoms, hash_state, keys) =
-            setup_session_keys(&mut self.thread_0, pms.clone(), visibility).await?;
+        let state = self.load(pms, self.config.params).await?;
 
-        let (cf_vd, sf_vd) = futures::try_join!(
-            setup_finished_msg(&mut self.thread_0, Msg::Cf, hash_state.clone(), visibility),
-            setup_finished_msg(&mut self.thread_1, Msg::Sf, hash_state.clone(), visibility),
-        )?;
+        self.state = state::State::SessionKeys(state);
 
-        self.state = state::State::SessionKeys(state::SessionKeys {
-            pms,
-            randoms,
-            hash_state,
-            keys,
-            cf_vd,
-            sf_vd,
-        });
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip(self), err))]
+    async fn set_params(&mut self, params: PrfParams) -> Result<(), PrfError> {
+        let state =
+            std::mem::replace(&mut self.state, state::State::Error).try_into_session_keys()?;
+
+        let state = if state.params == params {
+            state
+        } else {
+            // The preprocessed circuits are discarded.
+            self.load(state.pms, params).await?
+        };
+
+        self.state = state::State::SessionKeys(state);
 
         Ok(())
     }
@@ -269,7 +322,7 @@ where
     #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
     async fn compute_client_finished_vd_private(
         &mut self,
-        handshake_hash: [u8; 32],
+        handshake_hash: &[u8],
     ) -> Result<[u8; 12], PrfError> {
         if self.config.role != Role::Leader {
             return Err(PrfError::RoleError(
@@ -285,7 +338,7 @@ where
     #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
     async fn compute_server_finished_vd_private(
         &mut self,
-        handshake_hash: [u8; 32],
+        handshake_hash: &[u8],
     ) -> Result<[u8; 12], PrfError> {
         if self.config.role != Role::Leader {
             return Err(PrfError::RoleError(
@@ -350,6 +403,7 @@ pub(crate) mod state {
     #[derive(Debug)]
     pub(crate) struct SessionKeys {
         pub(crate) pms: ValueRef,
+        pub(crate) params: PrfParams,
         pub(crate) randoms: Randoms,
         pub(crate) hash_state: HashState,
         pub(crate) keys: crate::SessionKeys,
@@ -374,30 +428,39 @@ pub(crate) mod state {
 async fn setup_session_keys<T: Memory + Load + Send>(
     thread: &mut T,
     pms: ValueRef,
+    params: PrfParams,
     visibility: Visibility,
 ) -> Result<(Randoms, HashState, SessionKeys), PrfError> {
-    let client_random = thread.new_input::<[u8; 32]>("client_finished", visibility)?;
-    let server_random = thread.new_input::<[u8; 32]>("server_finished", visibility)?;
-
-    let client_write_key = thread.new_output::<[u8; 16]>("client_write_key")?;
-    let server_write_key = thread.new_output::<[u8; 16]>("server_write_key")?;
-    let client_iv = thread.new_output::<[u8; 4]>("client_write_iv")?;
-    let server_iv = thread.new_output::<[u8; 4]>("server_write_iv")?;
-
-    let ms_outer_hash_state = thread.new_output::<[u32; 8]>("ms_outer_hash_state")?;
-    let ms_inner_hash_state = thread.new_output::<[u32; 8]>("ms_inner_hash_state")?;
-
-    if SESSION_KEYS_CIRC.get().is_none() {
-        _ = SESSION_KEYS_CIRC.set(Backend::spawn(build_session_keys).await);
-    }
+    let id = params.id();
+
+    let client_random = thread.new_input::<[u8; 32]>(&format!("{id}/client_random"), visibility)?;
+    let server_random = thread.new_input::<[u8; 32]>(&format!("{id}/server_random"), visibility)?;
+
+    let client_write_key =
+        thread.new_array_output::<u8>(&format!("{id}/client_write_key"), params.key_len)?;
+    let server_write_key =
+        thread.new_array_output::<u8>(&format!("{id}/server_write_key"), params.key_len)?;
+    let client_iv =
+        thread.new_array_output::<u8>(&format!("{id}/client_write_iv"), params.iv_len)?;
+    let server_iv =
+        thread.new_array_output::<u8>(&format!("{id}/server_write_iv"), params.iv_len)?;
+
+    let (ms_outer_hash_state, ms_inner_hash_state) = match params.hash {
+        PrfHash::Sha256 => (
+            thread.new_output::<[u32; 8]>(&format!("{id}/ms_outer_hash_state"))?,
+            thread.new_output::<[u32; 8]>(&format!("{id}/ms_inner_hash_state"))?,
+        ),
+        PrfHash::Sha384 => (
+            thread.new_output::<[u64; 8]>(&format!("{id}/ms_outer_hash_state"))?,
+            thread.new_output::<[u64; 8]>(&format!("{id}/ms_inner_hash_state"))?,
+        ),
+    };
 
-    let circ = SESSION_KEYS_CIRC
-        .get()
-        .expect("session keys circuit is set");
+    let circ = session_keys_circ(params).await;
 
     thread
         .load(
-            circ.clone(),
+            circ,
             &[pms, client_random.clone(), server_random.clone()],
             &[
                 client_write_key.clone(),
@@ -431,37 +494,31 @@ async fn setup_session_keys<T: Memory + Load + Send>(
 async fn setup_finished_msg<T: Memory + Load + Send>(
     thread: &mut T,
     msg: Msg,
+    params: PrfParams,
     hash_state: HashState,
     visibility: Visibility,
 ) -> Result<VerifyData, PrfError> {
+    let id = params.id();
     let name = match msg {
         Msg::Cf => String::from("client_finished"),
         Msg::Sf => String::from("server_finished"),
     };
 
-    let handshake_hash =
-        thread.new_input::<[u8; 32]>(&format!("{name}/handshake_hash"), visibility)?;
-    let vd = thread.new_output::<[u8; 12]>(&format!("{name}/vd"))?;
-
-    let circ = match msg {
-        Msg::Cf => &CLIENT_VD_CIRC,
-        Msg::Sf => &SERVER_VD_CIRC,
-    };
-
-    let label = match msg {
-        Msg::Cf => CF_LABEL,
-        Msg::Sf => SF_LABEL,
+    let handshake_hash = match params.hash {
+        PrfHash::Sha256 => {
+            thread.new_input::<[u8; 32]>(&format!("{id}/{name}/handshake_hash"), visibility)?
+        }
+        PrfHash::Sha384 => {
+            thread.new_input::<[u8; 48]>(&format!("{id}/{name}/handshake_hash"), visibility)?
+        }
     };
+    let vd = thread.new_output::<[u8; 12]>(&format!("{id}/{name}/vd"))?;
 
-    if circ.get().is_none() {
-        _ = circ.set(Backend::spawn(move || build_verify_data(label)).await);
-    }
-
-    let circ = circ.get().expect("session keys circuit is set");
+    let circ = vd_circ(msg, params.hash).await;
 
     thread
         .load(
-            circ.clone(),
+            circ,
             &[
                 hash_state.ms_outer_hash_state,
                 hash_state.ms_inner_hash_state,
@@ -471,5 +528,53 @@ async fn setup_finished_msg<T: Memory + Load + Send>(
         )
         .await?;
 
-    Ok(VerifyData { handshake_hash, vd })
+    Ok(VerifyData {
+        hash: params.hash,
+        handshake_hash,
+        vd,
+    })
+}
+
+/// Returns the session keys circuit for the provided parameters, building it if necessary.
+async fn session_keys_circ(params: PrfParams) -> Arc<Circuit> {
+    let circs = SESSION_KEYS_CIRCS.get_or_init(Default::default);
+
+    if let Some(circ) = circs.lock().unwrap().get(&params) {
+        return circ.clone();
+    }
+
+    let circ =
+        Backend::spawn(move || build_session_keys(params.hash, params.key_len, params.iv_len))
+            .await;
+
+    circs.lock().unwrap().entry(params).or_insert(circ).clone()
+}
+
+/// Returns the verify data circuit for the provided message and hash, building it if necessary.
+async fn vd_circ(msg: Msg, hash: PrfHash) -> Arc<Circuit> {
+    let (circs, label) = match msg {
+        Msg::Cf => (&CLIENT_VD_CIRCS, CF_LABEL),
+        Msg::Sf => (&SERVER_VD_CIRCS, SF_LABEL),
+    };
+    let circs = circs.get_or_init(Default::default);
+
+    if let Some(circ) = circs.lock().unwrap().get(&hash) {
+        return circ.clone();
+    }
+
+    let circ = Backend::spawn(move || build_verify_data(hash, label)).await;
+
+    circs.lock().unwrap().entry(hash).or_insert(circ).clone()
+}
+
+fn check_hash_len(hash: PrfHash, handshake_hash: &[u8]) -> Result<(), PrfError> {
+    if handshake_hash.len() != hash.hash_len() {
+        return Err(PrfError::InvalidInput(format!(
+            "expected handshake hash of length {}, got {}",
+            hash.hash_len(),
+            handshake_hash.len()
+        )));
+    }
+
+    Ok(())
 }
@@ -84,6 +84,12 @@ pub trait Backend: Send {
     async fn set_decrypt(&mut self, mode: DecryptMode) -> Result<(), BackendError>;
     /// Returns client_random value.
     async fn get_client_random(&mut self) -> Result<Random, BackendError>;
+    /// Sets the group selected by the server for the key exchange.
+    ///
+    /// The client keyshare returned afterwards must belong to this group.
+    async fn set_key_exchange_group(&mut self, _group: NamedGroup) -> Result<(), BackendError> {
+        Ok(())
+    }
     /// Returns public client keyshare.
     async fn get_client_key_share(&mut self) -> Result<PublicKey, BackendError>;
     /// Sets server random.
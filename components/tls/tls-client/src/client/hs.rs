@@ -17,7 +17,7 @@ use tls_core::{
         codec::{Codec, Reader},
         enums::{
             AlertDescription, CipherSuite, Compression, ContentType, ECPointFormat, ExtensionType,
-            HandshakeType, PSKKeyExchangeMode, ProtocolVersion,
+            HandshakeType, ProtocolVersion,
         },
         handshake::{
             CertificateStatusRequest, ClientExtension, ClientHelloPayload, ClientSessionTicket,
@@ -86,6 +86,10 @@ pub(super) async fn start_handshake(
 
     let support_tls13 = config.supports_version(ProtocolVersion::TLSv1_3);
     let key_share = if support_tls13 {
+        // The initial key share is for our most preferred group.
+        if let Some(skxg) = config.kx_groups.first() {
+            cx.common.backend.set_key_exchange_group(skxg.name).await?;
+        }
         Some(cx.common.backend.get_client_key_share().await?)
     } else {
         None
@@ -236,12 +240,9 @@ async fn emit_client_hello_for_retry(
         exts.push(ClientExtension::Cookie(cookie.clone()));
     }
 
-    if support_tls13 && config.enable_tickets {
-        // We could support PSK_KE here too. Such connections don't
-        // have forward secrecy, and are similar to TLS1.2 resumption.
-        let psk_modes = vec![PSKKeyExchangeMode::PSK_DHE_KE];
-        exts.push(ClientExtension::PresharedKeyModes(psk_modes));
-    }
+    // TLS1.3 session resumption is not supported, so we do not offer any PSK
+    // key exchange modes. Servers will not send us NewSessionTicket messages
+    // without them, which would otherwise be mixed into the received transcript.
 
     if !config.alpn_protocols.is_empty() {
         exts.push(ClientExtension::Protocols(ProtocolNameList::from_slices(
@@ -711,11 +712,21 @@ impl ExpectServerHelloOrHelloRetryRequest {
 
         let key_share = match req_group {
             Some(group) if group != offered_key_share.group => {
-                // For now we do not support changing group after starting hs
-                return Err(cx
-                    .common
-                    .illegal_param("server requested hrr with bad group")
-                    .await?);
+                if !self
+                    .next
+                    .config
+                    .kx_groups
+                    .iter()
+                    .any(|skxg| skxg.name == group)
+                {
+                    return Err(cx
+                        .common
+                        .illegal_param("server requested hrr with bad group")
+                        .await?);
+                }
+
+                cx.common.backend.set_key_exchange_group(group).await?;
+                cx.common.backend.get_client_key_share().await?
             }
             _ => offered_key_share,
         };
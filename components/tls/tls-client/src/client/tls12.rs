@@ -831,6 +831,10 @@ impl State<ClientConnectionData> for ExpectServerDone {
                 }
             };
 
+        cx.common
+            .backend
+            .set_key_exchange_group(ecdh_params.curve_params.named_group)
+            .await?;
         let key_share = cx.common.backend.get_client_key_share().await?;
         if key_share.group != ecdh_params.curve_params.named_group {
             return Err(Error::PeerMisbehavedError(
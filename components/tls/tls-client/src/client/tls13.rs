@@ -15,6 +15,7 @@ use crate::{
     sign, verify, KeyLog,
 };
 use tls_core::{
+    ke::ServerKxDetails,
     key::PublicKey,
     msgs::{
         base::{Payload, PayloadU8},
@@ -130,6 +131,7 @@ pub(super) async fn handle_server_hello(
         .backend
         .set_decrypt(DecryptMode::Handshake)
         .await?;
+    cx.common.record_layer.set_message_decrypter();
 
     if !cx.data.early_data.is_enabled() {
         // Set the client encryption key for handshakes if early data is not used
@@ -137,6 +139,7 @@ pub(super) async fn handle_server_hello(
             .backend
             .set_encrypt(EncryptMode::Handshake)
             .await?;
+        cx.common.record_layer.set_message_encrypter();
     }
 
     emit_fake_ccs(&mut sent_tls13_fake_ccs, cx.common).await?;
@@ -636,6 +639,19 @@ impl State<ClientConnectionData> for ExpectCertificateVerify {
             Err(e) => return Err(hs::send_cert_error_alert(cx.common, Error::CoreError(e)).await?),
         };
 
+        // The backend needs the signed transcript to attest to the server identity.
+        cx.common
+            .backend
+            .set_server_cert_details(self.server_cert.clone())
+            .await?;
+        cx.common
+            .backend
+            .set_server_kx_details(ServerKxDetails::new(
+                self.transcript.transcript().to_vec(),
+                cert_verify.clone(),
+            ))
+            .await?;
+
         cx.common.peer_certificates = Some(self.server_cert.cert_chain().to_vec());
         self.transcript.add_message(&m);
 
@@ -796,6 +812,7 @@ impl State<ClientConnectionData> for ExpectFinished {
                 .backend
                 .set_encrypt(EncryptMode::Handshake)
                 .await?;
+            cx.common.record_layer.set_message_encrypter();
         }
 
         /* Send our authentication/finished messages.  These are still encrypted
@@ -840,10 +857,12 @@ impl State<ClientConnectionData> for ExpectFinished {
             .backend
             .set_encrypt(EncryptMode::Application)
             .await?;
+        cx.common.record_layer.set_message_encrypter();
         cx.common
             .backend
             .set_decrypt(DecryptMode::Application)
             .await?;
+        cx.common.record_layer.set_message_decrypter();
 
         cx.common.start_traffic().await?;
 
@@ -74,6 +74,7 @@ impl HandshakeHashBuffer {
         ctx.update(&self.buffer);
         HandshakeHash {
             ctx,
+            transcript: self.buffer.clone(),
             client_auth: match self.client_auth_enabled {
                 true => Some(self.buffer),
                 false => None,
@@ -93,6 +94,12 @@ pub(crate) struct HandshakeHash {
     /// None before we know what hash function we're using
     ctx: digest::Context,
 
+    /// All handshake messages hashed so far.
+    ///
+    /// This is retained so the backend can prove the TLS 1.3 handshake
+    /// transcript signed by the server to a third party.
+    transcript: Vec<u8>,
+
     /// buffer for client-auth.
     client_auth: Option<Vec<u8>>,
 }
@@ -116,6 +123,7 @@ impl HandshakeHash {
     /// Hash or buffer a byte slice.
     fn update_raw(&mut self, buf: &[u8]) -> &mut Self {
         self.ctx.update(buf);
+        self.transcript.extend_from_slice(buf);
 
         if let Some(buffer) = &mut self.client_auth {
             buffer.extend_from_slice(buf);
@@ -154,6 +162,7 @@ impl HandshakeHash {
         let old_handshake_hash_msg =
             HandshakeMessagePayload::build_handshake_hash(old_hash.as_ref());
 
+        self.transcript.clear();
         self.update_raw(&old_handshake_hash_msg.get_encoding());
     }
 
@@ -162,6 +171,11 @@ impl HandshakeHash {
         self.ctx.clone().finish()
     }
 
+    /// Returns all handshake messages hashed so far.
+    pub(crate) fn transcript(&self) -> &[u8] {
+        &self.transcript
+    }
+
     /// Takes this object's buffer containing all handshake messages
     /// so far.  This method only works once; it resets the buffer
     /// to empty.
@@ -189,6 +203,7 @@ mod test {
         let mut hh = hhb.start_hash(&HashAlgorithm::SHA256);
         assert!(hh.client_auth.is_none());
         hh.update_raw(b"world");
+        assert_eq!(hh.transcript(), b"helloworld");
         let h = hh.get_current_hash();
         let h = h.as_ref();
         assert_eq!(h[0], 0x93);
@@ -8,3 +8,11 @@ pub fn make_tls12_aad(seq: u64, typ: ContentType, vers: ProtocolVersion, len: us
     aad[11..13].copy_from_slice(&(len as u16).to_be_bytes());
     aad
 }
+
+pub fn make_tls13_aad(len: usize) -> [u8; 5] {
+    let mut aad = [0u8; 5];
+    aad[0] = ContentType::ApplicationData.get_u8();
+    aad[1..3].copy_from_slice(&ProtocolVersion::TLSv1_2.get_u16().to_be_bytes());
+    aad[3..5].copy_from_slice(&(len as u16).to_be_bytes());
+    aad
+}
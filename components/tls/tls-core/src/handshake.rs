@@ -1,57 +1,118 @@
+use ring::digest;
 use web_time::SystemTime;
 
 use crate::{
-    cert::ServerCertDetails, dns::ServerName, ke::ServerKxDetails, msgs::handshake::Random,
-    verify::ServerCertVerifier, Error,
+    cert::ServerCertDetails,
+    dns::ServerName,
+    ke::ServerKxDetails,
+    key::PublicKey,
+    msgs::{
+        codec::Reader,
+        enums::{CipherSuite, ProtocolVersion},
+        handshake::{
+            DigitallySignedStruct, HandshakeMessagePayload, HandshakePayload, Random,
+            ServerECDHParams, HELLO_RETRY_REQUEST_RANDOM,
+        },
+    },
+    suites::tls12::decode_ecdh_params,
+    verify::{construct_tls13_server_verify_message, ServerCertVerifier},
+    Error,
 };
 
+/// Data which authenticates the server in a TLS handshake.
+///
+/// The Prover commits to the serialization of this type during notarization. Its serialization
+/// changed when TLS 1.3 support was added, so handshake decommitments of earlier releases do not
+/// open against it.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct HandshakeData {
-    /// Server certificate chain and related details.
-    server_cert_details: ServerCertDetails,
-    /// Key exchange details.
-    server_kx_details: ServerKxDetails,
-    /// Client random
-    client_random: Random,
-    /// Server random
-    server_random: Random,
+pub enum HandshakeData {
+    /// TLS 1.2 handshake data.
+    V1_2(HandshakeDataV1_2),
+    /// TLS 1.3 handshake data.
+    V1_3(HandshakeDataV1_3),
 }
 
 impl HandshakeData {
-    /// Creates a new `HandshakeData` instance.
+    /// Creates a new TLS 1.2 `HandshakeData` instance.
     pub fn new(
         server_cert_details: ServerCertDetails,
         server_kx_details: ServerKxDetails,
         client_random: Random,
         server_random: Random,
     ) -> Self {
-        Self {
+        Self::V1_2(HandshakeDataV1_2 {
             server_cert_details,
             server_kx_details,
             client_random,
             server_random,
+        })
+    }
+
+    /// Creates a new TLS 1.3 `HandshakeData` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `server_cert_details` - Server certificate chain and related details.
+    /// * `handshake` - The encoded handshake messages from the ClientHello up to and including
+    ///                 the server Certificate message.
+    /// * `cert_verify` - The signature from the server CertificateVerify message.
+    /// * `client_random` - Client random.
+    /// * `server_random` - Server random.
+    pub fn new_v1_3(
+        server_cert_details: ServerCertDetails,
+        handshake: Vec<u8>,
+        cert_verify: DigitallySignedStruct,
+        client_random: Random,
+        server_random: Random,
+    ) -> Self {
+        Self::V1_3(HandshakeDataV1_3 {
+            server_cert_details,
+            handshake,
+            cert_verify,
+            client_random,
+            server_random,
+        })
+    }
+
+    /// Returns the protocol version of the handshake.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        match self {
+            Self::V1_2(_) => ProtocolVersion::TLSv1_2,
+            Self::V1_3(_) => ProtocolVersion::TLSv1_3,
         }
     }
 
     /// Returns the server certificate chain.
     pub fn server_cert_details(&self) -> &ServerCertDetails {
-        &self.server_cert_details
-    }
-
-    /// Returns the key exchange details.
-    pub fn server_kx_details(&self) -> &ServerKxDetails {
-        &self.server_kx_details
+        match self {
+            Self::V1_2(data) => &data.server_cert_details,
+            Self::V1_3(data) => &data.server_cert_details,
+        }
     }
 
     /// Returns the client random value.
     pub fn client_random(&self) -> &Random {
-        &self.client_random
+        match self {
+            Self::V1_2(data) => &data.client_random,
+            Self::V1_3(data) => &data.client_random,
+        }
     }
 
     /// Returns the server random value.
     pub fn server_random(&self) -> &Random {
-        &self.server_random
+        match self {
+            Self::V1_2(data) => &data.server_random,
+            Self::V1_3(data) => &data.server_random,
+        }
+    }
+
+    /// Returns the server ephemeral public key, if it can be decoded.
+    pub fn server_public_key(&self) -> Option<PublicKey> {
+        match self {
+            Self::V1_2(data) => data.server_public_key(),
+            Self::V1_3(data) => data.server_public_key(),
+        }
     }
 
     /// Verifies the handshake data.
@@ -61,42 +122,252 @@ impl HandshakeData {
         time: SystemTime,
         server_name: &ServerName,
     ) -> Result<(), Error> {
-        let (end_entity, intermediates) = self
-            .server_cert_details
-            .cert_chain()
-            .split_first()
-            .ok_or(Error::NoCertificatesPresented)?;
-
-        // Verify the end entity cert is valid for the provided server name
-        // and that it chains to at least one of the roots we trust.
-        _ = verifier.verify_server_cert(
-            end_entity,
-            intermediates,
-            server_name,
-            &mut self
-                .server_cert_details
-                .scts()
-                .map(|sct| sct.as_slice())
-                .unwrap_or(&[])
-                .iter()
-                .map(|sct| sct.0.as_slice()),
-            self.server_cert_details.ocsp_response(),
-            time,
-        )?;
+        verify_server_cert(self.server_cert_details(), verifier, time, server_name)?;
+
+        match self {
+            Self::V1_2(data) => data.verify_signature(verifier),
+            Self::V1_3(data) => data.verify_signature(verifier),
+        }
+    }
+}
 
+/// TLS 1.2 handshake data.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandshakeDataV1_2 {
+    /// Server certificate chain and related details.
+    server_cert_details: ServerCertDetails,
+    /// Key exchange details.
+    server_kx_details: ServerKxDetails,
+    /// Client random
+    client_random: Random,
+    /// Server random
+    server_random: Random,
+}
+
+impl HandshakeDataV1_2 {
+    /// Returns the key exchange details.
+    pub fn server_kx_details(&self) -> &ServerKxDetails {
+        &self.server_kx_details
+    }
+
+    fn server_public_key(&self) -> Option<PublicKey> {
+        let params =
+            decode_ecdh_params::<ServerECDHParams>(self.server_kx_details.kx_params())?;
+
+        Some(PublicKey::new(
+            params.curve_params.named_group,
+            &params.public.0,
+        ))
+    }
+
+    fn verify_signature(&self, verifier: &impl ServerCertVerifier) -> Result<(), Error> {
         // Verify the signature matches the certificate and key exchange parameters.
         let mut message = Vec::new();
         message.extend_from_slice(&self.client_random.0);
         message.extend_from_slice(&self.server_random.0);
-        message.extend_from_slice(self.server_kx_details().kx_params());
+        message.extend_from_slice(self.server_kx_details.kx_params());
 
-        let sig = self.server_kx_details().kx_sig();
         _ = verifier.verify_tls12_signature(
             &message,
-            &self.server_cert_details().cert_chain()[0],
-            sig,
+            &self.server_cert_details.cert_chain()[0],
+            self.server_kx_details.kx_sig(),
         )?;
 
         Ok(())
     }
 }
+
+/// TLS 1.3 handshake data.
+///
+/// In TLS 1.3 the server signs a hash of the handshake transcript, so the handshake messages
+/// up to the server Certificate are retained to verify the CertificateVerify signature.
+///
+/// Handshakes which include a HelloRetryRequest are not supported.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandshakeDataV1_3 {
+    /// Server certificate chain and related details.
+    server_cert_details: ServerCertDetails,
+    /// Encoded handshake messages from the ClientHello up to and including the server Certificate.
+    handshake: Vec<u8>,
+    /// Signature from the server CertificateVerify message.
+    cert_verify: DigitallySignedStruct,
+    /// Client random
+    client_random: Random,
+    /// Server random
+    server_random: Random,
+}
+
+impl HandshakeDataV1_3 {
+    /// Returns the encoded handshake messages from the ClientHello up to and including the
+    /// server Certificate.
+    pub fn handshake(&self) -> &[u8] {
+        &self.handshake
+    }
+
+    /// Returns the signature from the server CertificateVerify message.
+    pub fn cert_verify(&self) -> &DigitallySignedStruct {
+        &self.cert_verify
+    }
+
+    fn messages(&self) -> Result<Vec<HandshakeMessagePayload>, Error> {
+        let mut reader = Reader::init(&self.handshake);
+        let mut messages = Vec::new();
+        while reader.any_left() {
+            let msg = HandshakeMessagePayload::read_version(&mut reader, ProtocolVersion::TLSv1_3)
+                .ok_or(Error::CorruptMessage)?;
+            messages.push(msg);
+        }
+
+        Ok(messages)
+    }
+
+    fn server_public_key(&self) -> Option<PublicKey> {
+        self.messages()
+            .ok()?
+            .into_iter()
+            .find_map(|msg| match msg.payload {
+                HandshakePayload::ServerHello(server_hello) => {
+                    server_hello.get_key_share().cloned().map(PublicKey::from)
+                }
+                _ => None,
+            })
+    }
+
+    fn verify_signature(&self, verifier: &impl ServerCertVerifier) -> Result<(), Error> {
+        if self.server_random == HELLO_RETRY_REQUEST_RANDOM {
+            return Err(Error::General(
+                "HelloRetryRequest is not supported".to_string(),
+            ));
+        }
+
+        let messages = self.messages()?;
+
+        if messages
+            .iter()
+            .any(|msg| matches!(msg.payload, HandshakePayload::HelloRetryRequest(_)))
+        {
+            return Err(Error::General(
+                "HelloRetryRequest is not supported".to_string(),
+            ));
+        }
+
+        let Some(HandshakePayload::ClientHello(client_hello)) =
+            messages.first().map(|msg| &msg.payload)
+        else {
+            return Err(Error::General(
+                "handshake does not start with a ClientHello".to_string(),
+            ));
+        };
+
+        if client_hello.random != self.client_random {
+            return Err(Error::General("client random does not match".to_string()));
+        }
+
+        let Some(HandshakePayload::ServerHello(server_hello)) =
+            messages.get(1).map(|msg| &msg.payload)
+        else {
+            return Err(Error::General(
+                "ClientHello is not followed by a ServerHello".to_string(),
+            ));
+        };
+
+        if server_hello.random != self.server_random {
+            return Err(Error::General("server random does not match".to_string()));
+        }
+
+        let Some(HandshakePayload::CertificateTLS13(certificate)) =
+            messages.last().map(|msg| &msg.payload)
+        else {
+            return Err(Error::General(
+                "handshake does not end with a Certificate".to_string(),
+            ));
+        };
+
+        if certificate.convert().as_slice() != self.server_cert_details.cert_chain() {
+            return Err(Error::General(
+                "certificate chain does not match the handshake".to_string(),
+            ));
+        }
+
+        let algorithm = match server_hello.cipher_suite {
+            CipherSuite::TLS13_AES_128_GCM_SHA256 | CipherSuite::TLS13_CHACHA20_POLY1305_SHA256 => {
+                &digest::SHA256
+            }
+            CipherSuite::TLS13_AES_256_GCM_SHA384 => &digest::SHA384,
+            suite => {
+                return Err(Error::General(format!(
+                    "unsupported TLS 1.3 cipher suite: {:?}",
+                    suite
+                )))
+            }
+        };
+
+        let handshake_hash = digest::digest(algorithm, &self.handshake);
+
+        _ = verifier.verify_tls13_signature(
+            &construct_tls13_server_verify_message(&handshake_hash),
+            &self.server_cert_details.cert_chain()[0],
+            &self.cert_verify,
+        )?;
+
+        Ok(())
+    }
+}
+
+fn verify_server_cert(
+    server_cert_details: &ServerCertDetails,
+    verifier: &impl ServerCertVerifier,
+    time: SystemTime,
+    server_name: &ServerName,
+) -> Result<(), Error> {
+    let (end_entity, intermediates) = server_cert_details
+        .cert_chain()
+        .split_first()
+        .ok_or(Error::NoCertificatesPresented)?;
+
+    // Verify the end entity cert is valid for the provided server name
+    // and that it chains to at least one of the roots we trust.
+    _ = verifier.verify_server_cert(
+        end_entity,
+        intermediates,
+        server_name,
+        &mut server_cert_details
+            .scts()
+            .map(|sct| sct.as_slice())
+            .unwrap_or(&[])
+            .iter()
+            .map(|sct| sct.0.as_slice()),
+        server_cert_details.ocsp_response(),
+        time,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{anchors::RootCertStore, msgs::enums::SignatureScheme, verify::WebPkiVerifier};
+
+    #[test]
+    fn test_tls13_rejects_hello_retry_request() {
+        let data = HandshakeDataV1_3 {
+            server_cert_details: ServerCertDetails::new(Vec::new(), Vec::new(), None),
+            handshake: Vec::new(),
+            cert_verify: DigitallySignedStruct::new(
+                SignatureScheme::ECDSA_NISTP256_SHA256,
+                Vec::new(),
+            ),
+            client_random: Random([0u8; 32]),
+            server_random: HELLO_RETRY_REQUEST_RANDOM,
+        };
+
+        let err = data
+            .verify_signature(&WebPkiVerifier::new(RootCertStore::empty(), None))
+            .unwrap_err();
+
+        assert!(matches!(err, Error::General(msg) if msg.contains("HelloRetryRequest")));
+    }
+}
@@ -1,5 +1,9 @@
 use crate::msgs::handshake::DigitallySignedStruct;
 
+/// Server key exchange details.
+///
+/// In TLS 1.2 these are the ServerKeyExchange parameters and signature. In TLS 1.3 the
+/// parameters are the handshake messages covered by the server CertificateVerify signature.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerKxDetails {
@@ -1,4 +1,7 @@
 use derive_builder::Builder;
+use tls_core::msgs::enums::CipherSuite;
+
+use crate::MPC_CIPHER_SUITES;
 
 static DEFAULT_OPAQUE_TX_TRANSCRIPT_ID: &str = "opaque_tx";
 static DEFAULT_OPAQUE_RX_TRANSCRIPT_ID: &str = "opaque_rx";
@@ -33,6 +36,14 @@ pub struct MpcTlsCommonConfig {
     /// Whether the leader commits to the handshake data.
     #[builder(default = "true")]
     handshake_commit: bool,
+    /// The cipher suites this party is willing to run.
+    ///
+    /// Defaults to all the cipher suites supported by the MPC backend.
+    #[builder(
+        setter(into),
+        default = "MPC_CIPHER_SUITES.iter().map(|suite| suite.suite()).collect()"
+    )]
+    cipher_suites: Vec<CipherSuite>,
 }
 
 impl MpcTlsCommonConfig {
@@ -80,6 +91,11 @@ impl MpcTlsCommonConfig {
     pub fn handshake_commit(&self) -> bool {
         self.handshake_commit
     }
+
+    /// Returns the cipher suites this party is willing to run.
+    pub fn cipher_suites(&self) -> &[CipherSuite] {
+        &self.cipher_suites
+    }
 }
 
 /// Configuration for the leader
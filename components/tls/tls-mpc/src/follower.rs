@@ -2,7 +2,7 @@ use std::{collections::VecDeque, future::Future, mem};
 
 use futures::{
     stream::{SplitSink, SplitStream},
-    FutureExt, StreamExt,
+    FutureExt, SinkExt, StreamExt,
 };
 
 use hmac_sha256 as prf;
@@ -10,11 +10,10 @@ use key_exchange as ke;
 use ludi::{Address, FuturesAddress};
 use mpz_core::hash::Hash;
 
-use p256::elliptic_curve::sec1::ToEncodedPoint;
 use prf::SessionKeys;
+use rand::rngs::OsRng;
 
-use aead::Aead;
-use hmac_sha256::Prf;
+use hmac_sha256::{KeySchedule, Prf};
 use ke::KeyExchange;
 use tls_core::{
     key::PublicKey,
@@ -22,16 +21,17 @@ use tls_core::{
         alert::AlertMessagePayload,
         base::Payload,
         codec::Codec,
-        enums::{AlertDescription, ContentType, NamedGroup, ProtocolVersion},
+        enums::{AlertDescription, CipherSuite, ContentType, NamedGroup, ProtocolVersion},
         message::{OpaqueMessage, PlainMessage},
     },
 };
 
 use crate::{
     error::Kind,
-    msg::{CloseConnection, Commit, MpcTlsFollowerMsg, MpcTlsMessage},
+    msg::{CipherSuites, CloseConnection, Commit, MpcTlsFollowerMsg, MpcTlsMessage},
     record_layer::{Decrypter, Encrypter},
-    MpcTlsChannel, MpcTlsError, MpcTlsFollowerConfig,
+    suites::{ke_group, mpc_cipher_suites, tls12_suite, tls_group, Cipher},
+    MpcTlsChannel, MpcTlsComponents, MpcTlsError, MpcTlsFollowerConfig,
 };
 
 /// Controller for MPC-TLS follower.
@@ -43,11 +43,12 @@ pub struct MpcTlsFollower {
     state: State,
     config: MpcTlsFollowerConfig,
 
-    _sink: SplitSink<MpcTlsChannel, MpcTlsMessage>,
+    sink: SplitSink<MpcTlsChannel, MpcTlsMessage>,
     stream: Option<SplitStream<MpcTlsChannel>>,
 
     ke: Box<dyn KeyExchange + Send>,
     prf: Box<dyn Prf + Send>,
+    key_schedule: Box<dyn KeySchedule + Send>,
     encrypter: Encrypter,
     decrypter: Decrypter,
 
@@ -100,11 +101,16 @@ impl MpcTlsFollower {
     pub fn new(
         config: MpcTlsFollowerConfig,
         channel: MpcTlsChannel,
-        ke: Box<dyn KeyExchange + Send>,
-        prf: Box<dyn Prf + Send>,
-        encrypter: Box<dyn Aead + Send>,
-        decrypter: Box<dyn Aead + Send>,
+        components: MpcTlsComponents,
     ) -> Self {
+        let MpcTlsComponents {
+            ke,
+            prf,
+            key_schedule,
+            encrypter,
+            decrypter,
+        } = components;
+
         let encrypter = Encrypter::new(
             encrypter,
             config.common().tx_transcript_id().to_string(),
@@ -116,15 +122,16 @@ impl MpcTlsFollower {
             config.common().opaque_rx_transcript_id().to_string(),
         );
 
-        let (_sink, stream) = channel.split();
+        let (sink, stream) = channel.split();
 
         Self {
             state: State::Init,
             config,
-            _sink,
+            sink,
             stream: Some(stream),
             ke,
             prf,
+            key_schedule,
             encrypter,
             decrypter,
             close_notify: false,
@@ -138,8 +145,15 @@ impl MpcTlsFollower {
         tracing::instrument(level = "trace", skip_all, err)
     )]
     pub async fn setup(&mut self) -> Result<(), MpcTlsError> {
-        let pms = self.ke.setup().await?;
-        self.prf.setup(pms.into_value()).await?;
+        self.sink
+            .send(MpcTlsMessage::CipherSuites(CipherSuites(
+                self.config.common().cipher_suites().to_vec(),
+            )))
+            .await?;
+
+        let pms = self.ke.setup().await?.into_value();
+        self.prf.setup(pms.clone()).await?;
+        self.key_schedule.setup(pms).await?;
 
         Ok(())
     }
@@ -215,6 +229,23 @@ impl MpcTlsFollower {
         Ok(())
     }
 
+    /// Returns an error if the leader selected a cipher suite the follower did not agree to.
+    fn check_cipher_suite(&self, cipher_suite: CipherSuite) -> Result<(), MpcTlsError> {
+        if !self.config.common().cipher_suites().contains(&cipher_suite)
+            || mpc_cipher_suites(&[cipher_suite]).is_empty()
+        {
+            return Err(MpcTlsError::new(
+                Kind::PeerMisbehaved,
+                format!(
+                    "leader selected unsupported cipher suite: {:?}",
+                    cipher_suite
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Returns an error if the follower is not accepting new messages.
     ///
     /// This can happen if the follower has received a CloseNotify alert or if the leader has
@@ -241,12 +272,25 @@ impl MpcTlsFollower {
         feature = "tracing",
         tracing::instrument(level = "trace", skip_all, err)
     )]
-    async fn compute_client_key(&mut self) -> Result<(), MpcTlsError> {
-        self.state.take().try_into_init()?;
+    async fn compute_client_key(&mut self, group: NamedGroup) -> Result<(), MpcTlsError> {
+        // The client key is computed again if the server selects a different group.
+        if !matches!(self.state.take(), State::Init | State::ClientKey) {
+            return Err(MpcTlsError::new(
+                Kind::State,
+                "client key can only be computed before the key exchange",
+            ));
+        }
+
+        let kx_group = ke_group(group).ok_or_else(|| {
+            MpcTlsError::new(
+                Kind::PeerMisbehaved,
+                format!("unsupported key exchange group: {:?}", group),
+            )
+        })?;
 
         _ = self
             .ke
-            .compute_client_key(p256::SecretKey::random(&mut rand::rngs::OsRng))
+            .compute_client_key(ke::SecretKey::random(kx_group, &mut OsRng))
             .await?;
 
         self.state = State::ClientKey;
@@ -261,6 +305,7 @@ impl MpcTlsFollower {
     async fn compute_key_exchange(
         &mut self,
         handshake_commitment: Option<Hash>,
+        cipher_suite: CipherSuite,
     ) -> Result<(), MpcTlsError> {
         self.state.take().try_into_client_key()?;
 
@@ -271,6 +316,15 @@ impl MpcTlsFollower {
             ));
         }
 
+        self.check_cipher_suite(cipher_suite)?;
+
+        let suite = tls12_suite(cipher_suite).ok_or_else(|| {
+            MpcTlsError::new(
+                Kind::PeerMisbehaved,
+                format!("{:?} is not a TLS 1.2 cipher suite", cipher_suite),
+            )
+        })?;
+
         // Key exchange
         self.ke.compute_pms().await?;
 
@@ -280,6 +334,12 @@ impl MpcTlsFollower {
             .expect("server key should be set after computing pms");
 
         // PRF
+        self.prf.set_params(suite.prf).await?;
+        self.encrypter
+            .set_cipher(ProtocolVersion::TLSv1_2, suite.cipher)?;
+        self.decrypter
+            .set_cipher(ProtocolVersion::TLSv1_2, suite.cipher)?;
+
         let SessionKeys {
             client_write_key,
             server_write_key,
@@ -292,10 +352,95 @@ impl MpcTlsFollower {
 
         self.state = State::Ke(Ke {
             handshake_commitment,
-            server_key: PublicKey::new(
-                NamedGroup::secp256r1,
-                server_key.to_encoded_point(false).as_bytes(),
-            ),
+            server_key: PublicKey::new(tls_group(server_key.group()), server_key.key()),
+        });
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, err)
+    )]
+    async fn compute_handshake_keys(
+        &mut self,
+        cipher_suite: CipherSuite,
+    ) -> Result<(), MpcTlsError> {
+        self.state.take().try_into_client_key()?;
+
+        self.check_cipher_suite(cipher_suite)?;
+
+        if cipher_suite != CipherSuite::TLS13_AES_128_GCM_SHA256 {
+            return Err(MpcTlsError::new(
+                Kind::PeerMisbehaved,
+                format!("{:?} is not a supported TLS 1.3 cipher suite", cipher_suite),
+            ));
+        }
+
+        // Key exchange
+        self.ke.compute_pms().await?;
+
+        let server_key = self
+            .ke
+            .server_key()
+            .expect("server key should be set after computing pms");
+
+        // Key schedule
+        let SessionKeys {
+            client_write_key,
+            server_write_key,
+            client_iv,
+            server_iv,
+        } = self.key_schedule.compute_handshake_keys_blind().await?;
+
+        // TLS 1.3 is only supported with TLS_AES_128_GCM_SHA256.
+        self.encrypter
+            .set_cipher(ProtocolVersion::TLSv1_3, Cipher::Aes128Gcm)?;
+        self.decrypter
+            .set_cipher(ProtocolVersion::TLSv1_3, Cipher::Aes128Gcm)?;
+        self.encrypter.set_key(client_write_key, client_iv).await?;
+        self.decrypter
+            .set_handshake_key(server_write_key, server_iv)
+            .await?;
+
+        self.state = State::Hs(Hs {
+            server_key: PublicKey::new(tls_group(server_key.group()), server_key.key()),
+        });
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, err)
+    )]
+    async fn compute_application_keys(
+        &mut self,
+        handshake_commitment: Option<Hash>,
+    ) -> Result<(), MpcTlsError> {
+        let Hs { server_key } = self.state.take().try_into_hs()?;
+
+        if self.config.common().handshake_commit() && handshake_commitment.is_none() {
+            return Err(MpcTlsError::new(
+                Kind::PeerMisbehaved,
+                "handshake commitment missing",
+            ));
+        }
+
+        let SessionKeys {
+            client_write_key,
+            server_write_key,
+            client_iv,
+            server_iv,
+        } = self.key_schedule.compute_application_keys_blind().await?;
+
+        self.encrypter.set_key(client_write_key, client_iv).await?;
+        self.decrypter.set_key(server_write_key, server_iv).await?;
+
+        self.state = State::Active(Active {
+            handshake_commitment,
+            server_key,
+            buffer: Default::default(),
         });
 
         Ok(())
@@ -306,6 +451,12 @@ impl MpcTlsFollower {
         tracing::instrument(level = "trace", skip_all, err)
     )]
     async fn client_finished_vd(&mut self) -> Result<(), MpcTlsError> {
+        if self.state.is_hs() {
+            self.key_schedule.compute_client_finished_vd_blind().await?;
+
+            return Ok(());
+        }
+
         let Ke {
             handshake_commitment,
             server_key,
@@ -326,6 +477,12 @@ impl MpcTlsFollower {
         tracing::instrument(level = "trace", skip_all, err)
     )]
     async fn server_finished_vd(&mut self) -> Result<(), MpcTlsError> {
+        if self.state.is_hs() {
+            self.key_schedule.compute_server_finished_vd_blind().await?;
+
+            return Ok(());
+        }
+
         let Sf {
             handshake_commitment,
             server_key,
@@ -347,6 +504,15 @@ impl MpcTlsFollower {
         tracing::instrument(level = "trace", skip_all, err)
     )]
     async fn encrypt_client_finished(&mut self) -> Result<(), MpcTlsError> {
+        if self.state.is_hs() {
+            // Handshake header and 32 byte verify data.
+            self.encrypter
+                .encrypt_blind(ContentType::Handshake, ProtocolVersion::TLSv1_3, 36)
+                .await?;
+
+            return Ok(());
+        }
+
         let Cf {
             handshake_commitment,
             server_key,
@@ -448,6 +614,25 @@ impl MpcTlsFollower {
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, err)
+    )]
+    async fn decrypt_handshake(&mut self, msg: Vec<u8>) -> Result<(), MpcTlsError> {
+        self.state.try_as_hs()?;
+
+        // In TLS 1.3 encrypted handshake records have an outer content type of ApplicationData.
+        self.decrypter
+            .decrypt_blind(OpaqueMessage {
+                typ: ContentType::ApplicationData,
+                version: ProtocolVersion::TLSv1_2,
+                payload: Payload::new(msg),
+            })
+            .await?;
+
+        Ok(())
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "trace", skip_all, err)
@@ -554,12 +739,26 @@ impl MpcTlsFollower {
 #[msg(name = "{name}")]
 #[msg(attrs(derive(Debug, serde::Serialize, serde::Deserialize)))]
 impl MpcTlsFollower {
-    pub async fn compute_client_key(&mut self) {
-        ctx.try_or_stop(|_| self.compute_client_key()).await;
+    pub async fn compute_client_key(&mut self, group: NamedGroup) {
+        ctx.try_or_stop(|_| self.compute_client_key(group)).await;
     }
 
-    pub async fn compute_key_exchange(&mut self, handshake_commitment: Option<Hash>) {
-        ctx.try_or_stop(|_| self.compute_key_exchange(handshake_commitment))
+    pub async fn compute_key_exchange(
+        &mut self,
+        handshake_commitment: Option<Hash>,
+        cipher_suite: CipherSuite,
+    ) {
+        ctx.try_or_stop(|_| self.compute_key_exchange(handshake_commitment, cipher_suite))
+            .await;
+    }
+
+    pub async fn compute_handshake_keys(&mut self, cipher_suite: CipherSuite) {
+        ctx.try_or_stop(|_| self.compute_handshake_keys(cipher_suite))
+            .await;
+    }
+
+    pub async fn compute_application_keys(&mut self, handshake_commitment: Option<Hash>) {
+        ctx.try_or_stop(|_| self.compute_application_keys(handshake_commitment))
             .await;
     }
 
@@ -588,6 +787,11 @@ impl MpcTlsFollower {
             .await;
     }
 
+    pub async fn decrypt_handshake(&mut self, ciphertext: Vec<u8>) {
+        ctx.try_or_stop(|_| self.decrypt_handshake(ciphertext))
+            .await;
+    }
+
     pub async fn decrypt_alert(&mut self, ciphertext: Vec<u8>) {
         ctx.try_or_stop(|_| self.decrypt_alert(ciphertext)).await;
     }
@@ -627,6 +831,7 @@ mod state {
     pub(super) enum State {
         Init,
         ClientKey,
+        Hs(Hs),
         Ke(Ke),
         Cf(Cf),
         Sf(Sf),
@@ -647,6 +852,12 @@ mod state {
         }
     }
 
+    /// TLS 1.3 handshake, after the handshake traffic keys have been computed.
+    #[derive(Debug)]
+    pub(super) struct Hs {
+        pub(super) server_key: PublicKey,
+    }
+
     #[derive(Debug)]
     pub(super) struct Ke {
         pub(super) handshake_commitment: Option<Hash>,
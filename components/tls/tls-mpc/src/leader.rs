@@ -1,18 +1,17 @@
 use std::{collections::VecDeque, future::Future};
 
 use async_trait::async_trait;
-use futures::SinkExt;
+use futures::{SinkExt, StreamExt};
 
 use hmac_sha256 as prf;
 use key_exchange as ke;
 use mpz_core::commit::{Decommitment, HashCommit};
 use prf::SessionKeys;
 
-use aead::Aead;
-use hmac_sha256::Prf;
+use hmac_sha256::{KeySchedule, Prf};
 use ke::KeyExchange;
 
-use p256::SecretKey;
+use rand::rngs::OsRng;
 use tls_backend::{
     Backend, BackendError, BackendNotifier, BackendNotify, DecryptMode, EncryptMode,
 };
@@ -34,13 +33,15 @@ use tls_core::{
 use crate::{
     error::Kind,
     follower::{
-        ClientFinishedVd, CommitMessage, ComputeClientKey, ComputeKeyExchange, DecryptAlert,
-        DecryptMessage, DecryptServerFinished, EncryptAlert, EncryptClientFinished, EncryptMessage,
+        ClientFinishedVd, CommitMessage, ComputeApplicationKeys, ComputeClientKey,
+        ComputeHandshakeKeys, ComputeKeyExchange, DecryptAlert, DecryptHandshake, DecryptMessage,
+        DecryptServerFinished, EncryptAlert, EncryptClientFinished, EncryptMessage,
         ServerFinishedVd,
     },
-    msg::{CloseConnection, Commit, MpcTlsLeaderMsg, MpcTlsMessage},
+    msg::{CipherSuites, CloseConnection, Commit, MpcTlsLeaderMsg, MpcTlsMessage},
     record_layer::{Decrypter, Encrypter},
-    MpcTlsChannel, MpcTlsError, MpcTlsLeaderConfig,
+    suites::{ke_group, mpc_cipher_suites, tls12_suite, tls13_kx_group, Cipher, MPC_KX_GROUPS},
+    MpcTlsChannel, MpcTlsComponents, MpcTlsError, MpcTlsLeaderConfig,
 };
 
 /// Controller for MPC-TLS leader.
@@ -53,9 +54,12 @@ pub struct MpcTlsLeader {
     channel: MpcTlsChannel,
 
     state: State,
+    /// Cipher suites supported by both parties, in order of preference.
+    cipher_suites: Vec<SupportedCipherSuite>,
 
     ke: Box<dyn KeyExchange + Send>,
     prf: Box<dyn Prf + Send>,
+    key_schedule: Box<dyn KeySchedule + Send>,
     encrypter: Encrypter,
     decrypter: Decrypter,
 
@@ -89,11 +93,16 @@ impl MpcTlsLeader {
     pub fn new(
         config: MpcTlsLeaderConfig,
         channel: MpcTlsChannel,
-        ke: Box<dyn KeyExchange + Send>,
-        prf: Box<dyn Prf + Send>,
-        encrypter: Box<dyn Aead + Send>,
-        decrypter: Box<dyn Aead + Send>,
+        components: MpcTlsComponents,
     ) -> Self {
+        let MpcTlsComponents {
+            ke,
+            prf,
+            key_schedule,
+            encrypter,
+            decrypter,
+        } = components;
+
         let encrypter = Encrypter::new(
             encrypter,
             config.common().tx_transcript_id().to_string(),
@@ -105,12 +114,16 @@ impl MpcTlsLeader {
             config.common().opaque_rx_transcript_id().to_string(),
         );
 
+        let cipher_suites = mpc_cipher_suites(config.common().cipher_suites());
+
         Self {
             config,
             channel,
             state: State::default(),
+            cipher_suites,
             ke,
             prf,
+            key_schedule,
             encrypter,
             decrypter,
             notifier: BackendNotifier::new(),
@@ -126,12 +139,46 @@ impl MpcTlsLeader {
         tracing::instrument(level = "trace", skip_all, err)
     )]
     pub async fn setup(&mut self) -> Result<(), MpcTlsError> {
-        let pms = self.ke.setup().await?;
-        self.prf.setup(pms.into_value()).await?;
+        let CipherSuites(follower_suites) = match self.channel.next().await {
+            Some(Ok(MpcTlsMessage::CipherSuites(suites))) => suites,
+            Some(Ok(msg)) => {
+                return Err(MpcTlsError::new(
+                    Kind::PeerMisbehaved,
+                    format!("expected cipher suites from follower, got: {:?}", msg),
+                ))
+            }
+            Some(Err(e)) => return Err(e.into()),
+            None => {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+        };
+
+        self.cipher_suites
+            .retain(|suite| follower_suites.contains(&suite.suite()));
+
+        if self.cipher_suites.is_empty() {
+            return Err(MpcTlsError::new(
+                Kind::Config,
+                "leader and follower do not support any cipher suites in common",
+            ));
+        }
+
+        let pms = self.ke.setup().await?.into_value();
+        self.prf.setup(pms.clone()).await?;
+        self.key_schedule.setup(pms).await?;
 
         Ok(())
     }
 
+    /// Returns the cipher suites supported by both the leader and the follower, in order of
+    /// preference.
+    ///
+    /// The list is only agreed upon with the follower during [`setup`](Self::setup), and the
+    /// client should not offer any other suites to the server.
+    pub fn cipher_suites(&self) -> &[SupportedCipherSuite] {
+        &self.cipher_suites
+    }
+
     /// Runs the leader actor.
     ///
     /// Returns a control handle and a future that resolves when the actor is stopped.
@@ -200,6 +247,201 @@ impl MpcTlsLeader {
         Ok(msg)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, err)
+    )]
+    async fn encrypt_client_finished_tls13(
+        &mut self,
+        msg: PlainMessage,
+    ) -> Result<OpaqueMessage, MpcTlsError> {
+        self.state.try_as_hs()?;
+
+        self.channel
+            .send(MpcTlsMessage::EncryptClientFinished(EncryptClientFinished))
+            .await?;
+
+        let msg = self.encrypter.encrypt_private(msg).await?;
+
+        Ok(msg)
+    }
+
+    /// Sets the server's public key in the key exchange.
+    fn set_server_key(&mut self, key: &PublicKey) -> Result<(), MpcTlsError> {
+        let group = ke_group(key.group).ok_or_else(|| {
+            MpcTlsError::new(
+                Kind::KeyExchange,
+                format!("unsupported key group: {:?}", key.group),
+            )
+        })?;
+
+        self.ke
+            .set_server_key(ke::PublicKey::new(group, key.key.clone()));
+
+        Ok(())
+    }
+
+    /// Computes the TLS 1.3 handshake traffic keys.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, err)
+    )]
+    async fn compute_handshake_keys(&mut self) -> Result<(), MpcTlsError> {
+        let Ke {
+            protocol_version,
+            cipher_suite,
+            client_random,
+            server_random,
+            server_public_key,
+            server_hello_hash,
+            ..
+        } = self.state.take().try_into_ke()?;
+
+        if protocol_version != Some(ProtocolVersion::TLSv1_3) {
+            return Err(MpcTlsError::new(
+                Kind::State,
+                "handshake keys are only used in TLS 1.3",
+            ));
+        }
+
+        let cipher_suite = cipher_suite.ok_or(MpcTlsError::other("cipher suite not set"))?;
+        let server_random = server_random.ok_or(MpcTlsError::other("server random not set"))?;
+        let server_public_key =
+            server_public_key.ok_or(MpcTlsError::other("server public key not set"))?;
+        let hello_hash: [u8; 32] = server_hello_hash
+            .ok_or(MpcTlsError::other("server hello hash not set"))?
+            .try_into()
+            .map_err(|_| MpcTlsError::other("server hello hash is not 32 bytes"))?;
+
+        if !tls13_kx_group(server_public_key.group) {
+            return Err(MpcTlsError::new(
+                Kind::KeyExchange,
+                format!("{:?} is only supported in TLS 1.2", server_public_key.group),
+            ));
+        }
+
+        self.channel
+            .send(MpcTlsMessage::ComputeHandshakeKeys(ComputeHandshakeKeys {
+                cipher_suite,
+            }))
+            .await?;
+
+        self.set_server_key(&server_public_key)?;
+
+        self.ke.compute_pms().await?;
+
+        let SessionKeys {
+            client_write_key,
+            server_write_key,
+            client_iv,
+            server_iv,
+        } = self
+            .key_schedule
+            .compute_handshake_keys_private(hello_hash)
+            .await?;
+
+        // TLS 1.3 is only supported with TLS_AES_128_GCM_SHA256.
+        self.encrypter
+            .set_cipher(ProtocolVersion::TLSv1_3, Cipher::Aes128Gcm)?;
+        self.decrypter
+            .set_cipher(ProtocolVersion::TLSv1_3, Cipher::Aes128Gcm)?;
+        self.encrypter.set_key(client_write_key, client_iv).await?;
+        self.decrypter
+            .set_handshake_key(server_write_key, server_iv)
+            .await?;
+
+        self.state = State::Hs(Hs {
+            cipher_suite,
+            client_random,
+            server_random,
+            server_public_key,
+            server_cert_details: None,
+            server_kx_details: None,
+            handshake_hash: None,
+        });
+
+        Ok(())
+    }
+
+    /// Computes the TLS 1.3 application traffic keys.
+    ///
+    /// The leader commits to the handshake data before the keys are computed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, err)
+    )]
+    async fn compute_application_keys(&mut self) -> Result<(), MpcTlsError> {
+        let Hs {
+            cipher_suite,
+            client_random,
+            server_random,
+            server_public_key,
+            server_cert_details,
+            server_kx_details,
+            handshake_hash,
+        } = self.state.take().try_into_hs()?;
+
+        let server_cert_details =
+            server_cert_details.ok_or(MpcTlsError::other("server cert not set"))?;
+        let server_kx_details =
+            server_kx_details.ok_or(MpcTlsError::other("server kx details not set"))?;
+        let handshake_hash = handshake_hash.ok_or(MpcTlsError::other("handshake hash not set"))?;
+
+        let handshake_data = HandshakeData::new_v1_3(
+            server_cert_details.clone(),
+            server_kx_details.kx_params().to_vec(),
+            server_kx_details.kx_sig().clone(),
+            client_random,
+            server_random,
+        );
+
+        let (handshake_decommitment, handshake_commitment) =
+            if self.config.common().handshake_commit() {
+                let (decommitment, commitment) = handshake_data.clone().hash_commit();
+
+                (Some(decommitment), Some(commitment))
+            } else {
+                (None, None)
+            };
+
+        self.channel
+            .send(MpcTlsMessage::ComputeApplicationKeys(
+                ComputeApplicationKeys {
+                    handshake_commitment,
+                },
+            ))
+            .await?;
+
+        let SessionKeys {
+            client_write_key,
+            server_write_key,
+            client_iv,
+            server_iv,
+        } = self
+            .key_schedule
+            .compute_application_keys_private(handshake_hash)
+            .await?;
+
+        self.encrypter.set_key(client_write_key, client_iv).await?;
+        self.decrypter.set_key(server_write_key, server_iv).await?;
+
+        self.state = State::Active(Active {
+            data: MpcTlsData {
+                protocol_version: ProtocolVersion::TLSv1_3,
+                cipher_suite,
+                client_random,
+                server_random,
+                server_cert_details,
+                server_public_key,
+                server_kx_details,
+                handshake_data,
+                handshake_decommitment,
+            },
+        });
+
+        Ok(())
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "trace", skip_all, err)
@@ -276,6 +518,24 @@ impl MpcTlsLeader {
         Ok(msg)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, err)
+    )]
+    async fn decrypt_handshake(&mut self, msg: OpaqueMessage) -> Result<PlainMessage, MpcTlsError> {
+        self.state.try_as_hs()?;
+
+        self.channel
+            .send(MpcTlsMessage::DecryptHandshake(DecryptHandshake {
+                ciphertext: msg.payload.0.clone(),
+            }))
+            .await?;
+
+        let msg = self.decrypter.decrypt_private(msg).await?;
+
+        Ok(msg)
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "trace", skip_all, err)
@@ -317,15 +577,45 @@ impl MpcTlsLeader {
             self.decrypter.decrypt_private(msg).await?
         };
 
+        // In TLS 1.3 the content type is only known after decryption.
+        if msg.version == ProtocolVersion::TLSv1_3 && msg.typ == ContentType::Handshake {
+            return Err(MpcTlsError::new(
+                Kind::PeerMisbehaved,
+                "server sent a post-handshake message, which is not supported",
+            ));
+        }
+
         Ok(msg)
     }
 
+    /// Commits a received record to the follower before it is decrypted.
+    async fn commit_message(&mut self, msg: &OpaqueMessage) -> Result<(), MpcTlsError> {
+        self.channel
+            .send(MpcTlsMessage::CommitMessage(CommitMessage {
+                msg: msg.payload.0.clone(),
+            }))
+            .await?;
+
+        Ok(())
+    }
+
     async fn commit(&mut self) -> Result<(), MpcTlsError> {
         self.state.try_as_active()?;
 
         #[cfg(feature = "tracing")]
         tracing::debug!("committing to transcript");
 
+        // Commit to any records which have not been decrypted yet, the follower must
+        // receive them before the key is revealed.
+        let buffer = std::mem::take(&mut self.buffer);
+        for msg in buffer
+            .iter()
+            .filter(|msg| msg.typ == ContentType::ApplicationData)
+        {
+            self.commit_message(msg).await?;
+        }
+        self.buffer = buffer;
+
         self.channel.send(MpcTlsMessage::Commit(Commit)).await?;
 
         self.committed = true;
@@ -407,6 +697,10 @@ impl Backend for MpcTlsLeader {
     }
 
     async fn set_cipher_suite(&mut self, suite: SupportedCipherSuite) -> Result<(), BackendError> {
+        if !self.cipher_suites.contains(&suite) {
+            return Err(BackendError::UnsupportedCiphersuite(suite.suite()));
+        }
+
         let Ke { cipher_suite, .. } = self.state.try_as_ke_mut().map_err(MpcTlsError::from)?;
 
         *cipher_suite = Some(suite.suite());
@@ -415,15 +709,51 @@ impl Backend for MpcTlsLeader {
     }
 
     async fn get_suite(&mut self) -> Result<SupportedCipherSuite, BackendError> {
-        unimplemented!()
+        let cipher_suite = match &self.state {
+            State::Ke(Ke { cipher_suite, .. }) => *cipher_suite,
+            State::Hs(Hs { cipher_suite, .. }) => Some(*cipher_suite),
+            State::Cf(Cf { data })
+            | State::Sf(Sf { data })
+            | State::Active(Active { data })
+            | State::Closed(Closed { data }) => Some(data.cipher_suite),
+            State::Error => None,
+        };
+
+        cipher_suite
+            .and_then(|cipher_suite| {
+                self.cipher_suites
+                    .iter()
+                    .find(|suite| suite.suite() == cipher_suite)
+                    .copied()
+            })
+            .ok_or_else(|| MpcTlsError::new(Kind::State, "cipher suite not set").into())
     }
 
     async fn set_encrypt(&mut self, mode: EncryptMode) -> Result<(), BackendError> {
-        unimplemented!()
+        match mode {
+            // The client handshake key is set together with the server handshake key.
+            EncryptMode::Handshake => {
+                self.state.try_as_hs().map_err(MpcTlsError::from)?;
+            }
+            EncryptMode::Application => self.compute_application_keys().await?,
+            EncryptMode::EarlyData => {
+                return Err(MpcTlsError::new(Kind::Config, "early data is not supported").into())
+            }
+        }
+
+        Ok(())
     }
 
     async fn set_decrypt(&mut self, mode: DecryptMode) -> Result<(), BackendError> {
-        unimplemented!()
+        match mode {
+            DecryptMode::Handshake => self.compute_handshake_keys().await?,
+            // The application keys are set together with the client application key.
+            DecryptMode::Application => {
+                self.state.try_as_active().map_err(MpcTlsError::from)?;
+            }
+        }
+
+        Ok(())
     }
 
     async fn get_client_random(&mut self) -> Result<Random, BackendError> {
@@ -432,23 +762,73 @@ impl Backend for MpcTlsLeader {
         Ok(*client_random)
     }
 
+    async fn set_key_exchange_group(&mut self, group: NamedGroup) -> Result<(), BackendError> {
+        if !MPC_KX_GROUPS.contains(&group) {
+            return Err(BackendError::UnsupportedCurveGroup(group));
+        }
+
+        let Ke {
+            protocol_version,
+            key_exchange_group,
+            ..
+        } = self.state.try_as_ke_mut().map_err(MpcTlsError::from)?;
+
+        // The protocol version is not known yet when the key share of the client hello is
+        // computed, so groups which can not be used in TLS 1.3 are only accepted once TLS 1.2
+        // has been negotiated.
+        if !tls13_kx_group(group) && *protocol_version != Some(ProtocolVersion::TLSv1_2) {
+            return Err(MpcTlsError::new(
+                Kind::KeyExchange,
+                format!("{group:?} is only supported in TLS 1.2"),
+            )
+            .into());
+        }
+
+        *key_exchange_group = Some(group);
+
+        Ok(())
+    }
+
     async fn get_client_key_share(&mut self) -> Result<PublicKey, BackendError> {
+        let Ke {
+            key_exchange_group,
+            client_key_share,
+            ..
+        } = self.state.try_as_ke().map_err(MpcTlsError::from)?;
+
+        let group = key_exchange_group.unwrap_or(MPC_KX_GROUPS[0]);
+
+        // The key share may be requested more than once during the handshake, it only needs
+        // to be recomputed if the server selected a different group.
+        if let Some(key_share) = client_key_share {
+            if key_share.group == group {
+                return Ok(key_share.clone());
+            }
+        }
+
+        let kx_group = ke_group(group).expect("group is supported");
+
         self.channel
-            .send(MpcTlsMessage::ComputeClientKey(ComputeClientKey))
+            .send(MpcTlsMessage::ComputeClientKey(ComputeClientKey { group }))
             .await
             .map_err(MpcTlsError::from)?;
 
         let pk = self
             .ke
-            .compute_client_key(SecretKey::random(&mut rand::rngs::OsRng))
+            .compute_client_key(ke::SecretKey::random(kx_group, &mut OsRng))
             .await
             .map_err(MpcTlsError::from)?
             .expect("client key is returned as leader");
 
-        Ok(PublicKey::new(
-            NamedGroup::secp256r1,
-            &p256::EncodedPoint::from(pk).to_bytes(),
-        ))
+        let key_share = PublicKey::new(group, pk.key());
+
+        let Ke {
+            client_key_share, ..
+        } = self.state.try_as_ke_mut().map_err(MpcTlsError::from)?;
+
+        *client_key_share = Some(key_share.clone());
+
+        Ok(key_share)
     }
 
     async fn set_server_random(&mut self, random: Random) -> Result<(), BackendError> {
@@ -461,15 +841,24 @@ impl Backend for MpcTlsLeader {
 
     async fn set_server_key_share(&mut self, key: PublicKey) -> Result<(), BackendError> {
         let Ke {
-            server_public_key, ..
+            protocol_version,
+            server_public_key,
+            ..
         } = self.state.try_as_ke_mut().map_err(MpcTlsError::from)?;
 
-        if key.group != NamedGroup::secp256r1 {
+        if !MPC_KX_GROUPS.contains(&key.group) {
             Err(MpcTlsError::new(
                 Kind::KeyExchange,
                 format!("unsupported key group: {:?}", key.group),
             )
             .into())
+        } else if *protocol_version == Some(ProtocolVersion::TLSv1_3) && !tls13_kx_group(key.group)
+        {
+            Err(MpcTlsError::new(
+                Kind::KeyExchange,
+                format!("{:?} is only supported in TLS 1.2", key.group),
+            )
+            .into())
         } else {
             *server_public_key = Some(key);
             Ok(())
@@ -480,10 +869,23 @@ impl Backend for MpcTlsLeader {
         &mut self,
         cert_details: ServerCertDetails,
     ) -> Result<(), BackendError> {
-        let Ke {
-            server_cert_details,
-            ..
-        } = self.state.try_as_ke_mut().map_err(MpcTlsError::from)?;
+        let server_cert_details = match &mut self.state {
+            State::Ke(Ke {
+                server_cert_details,
+                ..
+            }) => server_cert_details,
+            State::Hs(Hs {
+                server_cert_details,
+                ..
+            }) => server_cert_details,
+            _ => {
+                return Err(MpcTlsError::new(
+                    Kind::State,
+                    "server cert details can only be set during the handshake",
+                )
+                .into())
+            }
+        };
 
         *server_cert_details = Some(cert_details);
 
@@ -494,9 +896,21 @@ impl Backend for MpcTlsLeader {
         &mut self,
         kx_details: ServerKxDetails,
     ) -> Result<(), BackendError> {
-        let Ke {
-            server_kx_details, ..
-        } = self.state.try_as_ke_mut().map_err(MpcTlsError::from)?;
+        let server_kx_details = match &mut self.state {
+            State::Ke(Ke {
+                server_kx_details, ..
+            }) => server_kx_details,
+            State::Hs(Hs {
+                server_kx_details, ..
+            }) => server_kx_details,
+            _ => {
+                return Err(MpcTlsError::new(
+                    Kind::State,
+                    "server kx details can only be set during the handshake",
+                )
+                .into())
+            }
+        };
 
         *server_kx_details = Some(kx_details);
 
@@ -508,45 +922,72 @@ impl Backend for MpcTlsLeader {
     }
 
     async fn set_hs_hash_server_hello(&mut self, hash: Vec<u8>) -> Result<(), BackendError> {
+        let Ke {
+            server_hello_hash, ..
+        } = self.state.try_as_ke_mut().map_err(MpcTlsError::from)?;
+
+        *server_hello_hash = Some(hash);
+
         Ok(())
     }
 
     async fn get_server_finished_vd(&mut self, hash: Vec<u8>) -> Result<Vec<u8>, BackendError> {
-        let hash: [u8; 32] = hash
-            .try_into()
-            .map_err(|_| MpcTlsError::other("server finished handshake hash is not 32 bytes"))?;
-
         self.channel
             .send(MpcTlsMessage::ServerFinishedVd(ServerFinishedVd))
             .await
             .map_err(|e| BackendError::InternalError(e.to_string()))?;
 
-        let vd = self
-            .prf
-            .compute_server_finished_vd_private(hash)
-            .await
-            .map_err(MpcTlsError::from)?;
+        let vd = if self.state.is_hs() {
+            let hash: [u8; 32] = hash.try_into().map_err(|_| {
+                MpcTlsError::other("server finished handshake hash is not 32 bytes")
+            })?;
+
+            self.key_schedule
+                .compute_server_finished_vd_private(hash)
+                .await
+                .map_err(MpcTlsError::from)?
+                .to_vec()
+        } else {
+            self.prf
+                .compute_server_finished_vd_private(&hash)
+                .await
+                .map_err(MpcTlsError::from)?
+                .to_vec()
+        };
 
-        Ok(vd.to_vec())
+        Ok(vd)
     }
 
     async fn get_client_finished_vd(&mut self, hash: Vec<u8>) -> Result<Vec<u8>, BackendError> {
-        let hash: [u8; 32] = hash
-            .try_into()
-            .map_err(|_| MpcTlsError::other("client finished handshake hash is not 32 bytes"))?;
-
         self.channel
             .send(MpcTlsMessage::ClientFinishedVd(ClientFinishedVd))
             .await
             .map_err(|e| BackendError::InternalError(e.to_string()))?;
 
-        let vd = self
-            .prf
-            .compute_client_finished_vd_private(hash)
-            .await
-            .map_err(MpcTlsError::from)?;
+        let vd = if let State::Hs(Hs { handshake_hash, .. }) = &mut self.state {
+            let hash: [u8; 32] = hash.try_into().map_err(|_| {
+                MpcTlsError::other("client finished handshake hash is not 32 bytes")
+            })?;
 
-        Ok(vd.to_vec())
+            // Without client authentication this is the hash of the handshake up to and
+            // including the server Finished, which is also used to derive the
+            // application traffic secrets.
+            *handshake_hash = Some(hash);
+
+            self.key_schedule
+                .compute_client_finished_vd_private(hash)
+                .await
+                .map_err(MpcTlsError::from)?
+                .to_vec()
+        } else {
+            self.prf
+                .compute_client_finished_vd_private(&hash)
+                .await
+                .map_err(MpcTlsError::from)?
+                .to_vec()
+        };
+
+        Ok(vd)
     }
 
     async fn prepare_encryption(&mut self) -> Result<(), BackendError> {
@@ -558,6 +999,7 @@ impl Backend for MpcTlsLeader {
             server_cert_details,
             server_public_key,
             server_kx_details,
+            ..
         } = self.state.take().try_into_ke().map_err(MpcTlsError::from)?;
 
         let protocol_version =
@@ -571,6 +1013,13 @@ impl Backend for MpcTlsLeader {
             server_public_key.ok_or(MpcTlsError::other("server public key not set"))?;
         let server_random = server_random.ok_or(MpcTlsError::other("server random not set"))?;
 
+        let suite = tls12_suite(cipher_suite).ok_or_else(|| {
+            MpcTlsError::new(
+                Kind::Config,
+                format!("unsupported cipher suite: {:?}", cipher_suite),
+            )
+        })?;
+
         let handshake_data = HandshakeData::new(
             server_cert_details.clone(),
             server_kx_details.clone(),
@@ -590,17 +1039,24 @@ impl Backend for MpcTlsLeader {
         self.channel
             .send(MpcTlsMessage::ComputeKeyExchange(ComputeKeyExchange {
                 handshake_commitment,
+                cipher_suite,
             }))
             .await
             .map_err(|e| BackendError::InternalError(e.to_string()))?;
 
-        let server_key = p256::PublicKey::from_sec1_bytes(&server_public_key.key)
-            .map_err(|_| MpcTlsError::other("server key is not valid sec1p256"))?;
-
-        self.ke.set_server_key(server_key);
+        self.set_server_key(&server_public_key)?;
 
         self.ke.compute_pms().await.map_err(MpcTlsError::from)?;
 
+        self.prf
+            .set_params(suite.prf)
+            .await
+            .map_err(MpcTlsError::from)?;
+        self.encrypter
+            .set_cipher(ProtocolVersion::TLSv1_2, suite.cipher)?;
+        self.decrypter
+            .set_cipher(ProtocolVersion::TLSv1_2, suite.cipher)?;
+
         let SessionKeys {
             client_write_key,
             server_write_key,
@@ -638,6 +1094,9 @@ impl Backend for MpcTlsLeader {
         seq: u64,
     ) -> Result<OpaqueMessage, BackendError> {
         let msg = match msg.typ {
+            ContentType::Handshake if self.state.is_hs() => {
+                self.encrypt_client_finished_tls13(msg).await
+            }
             ContentType::Handshake => self.encrypt_client_finished(msg).await,
             ContentType::ApplicationData => self.encrypt_application_data(msg).await,
             ContentType::Alert => self.encrypt_alert(msg).await,
@@ -658,6 +1117,9 @@ impl Backend for MpcTlsLeader {
         seq: u64,
     ) -> Result<PlainMessage, BackendError> {
         let msg = match msg.typ {
+            // In TLS 1.3 all encrypted handshake records have an outer content type of
+            // ApplicationData.
+            ContentType::ApplicationData if self.state.is_hs() => self.decrypt_handshake(msg).await,
             ContentType::Handshake => self.decrypt_server_finished(msg).await,
             ContentType::ApplicationData => self.decrypt_application_data(msg).await,
             ContentType::Alert => self.decrypt_alert(msg).await,
@@ -679,15 +1141,6 @@ impl Backend for MpcTlsLeader {
             ));
         }
 
-        if msg.typ == ContentType::ApplicationData {
-            self.channel
-                .send(MpcTlsMessage::CommitMessage(CommitMessage {
-                    msg: msg.payload.0.clone(),
-                }))
-                .await
-                .map_err(|e| BackendError::InternalError(e.to_string()))?;
-        }
-
         self.buffer.push_back(msg);
 
         if self.is_decrypting {
@@ -702,11 +1155,21 @@ impl Backend for MpcTlsLeader {
             return Ok(None);
         }
 
+        let msg = self.buffer.pop_front();
+
         if self.buffer.is_empty() {
             self.notifier.clear();
         }
 
-        Ok(self.buffer.pop_front())
+        // Application data is committed once it is taken out of the buffer, as in TLS 1.3
+        // encrypted handshake records can not be distinguished from it before then.
+        if let Some(msg) = &msg {
+            if msg.typ == ContentType::ApplicationData && self.state.is_active() {
+                self.commit_message(msg).await?;
+            }
+        }
+
+        Ok(msg)
     }
 
     async fn get_notify(&mut self) -> Result<BackendNotify, BackendError> {
@@ -753,6 +1216,7 @@ mod state {
     #[derive_err(Debug)]
     pub(super) enum State {
         Ke(Ke),
+        Hs(Hs),
         Cf(Cf),
         Sf(Sf),
         Active(Active),
@@ -772,10 +1236,13 @@ mod state {
                 protocol_version: None,
                 cipher_suite: None,
                 client_random: Random::new().expect("rng is available"),
+                key_exchange_group: None,
+                client_key_share: None,
                 server_random: None,
                 server_cert_details: None,
                 server_public_key: None,
                 server_kx_details: None,
+                server_hello_hash: None,
             })
         }
     }
@@ -791,10 +1258,25 @@ mod state {
         pub(super) protocol_version: Option<ProtocolVersion>,
         pub(super) cipher_suite: Option<CipherSuite>,
         pub(super) client_random: Random,
+        pub(super) key_exchange_group: Option<NamedGroup>,
+        pub(super) client_key_share: Option<PublicKey>,
         pub(super) server_random: Option<Random>,
         pub(super) server_cert_details: Option<ServerCertDetails>,
         pub(super) server_public_key: Option<PublicKey>,
         pub(super) server_kx_details: Option<ServerKxDetails>,
+        pub(super) server_hello_hash: Option<Vec<u8>>,
+    }
+
+    /// TLS 1.3 handshake, after the handshake traffic keys have been computed.
+    #[derive(Debug)]
+    pub(super) struct Hs {
+        pub(super) cipher_suite: CipherSuite,
+        pub(super) client_random: Random,
+        pub(super) server_random: Random,
+        pub(super) server_public_key: PublicKey,
+        pub(super) server_cert_details: Option<ServerCertDetails>,
+        pub(super) server_kx_details: Option<ServerKxDetails>,
+        pub(super) handshake_hash: Option<[u8; 32]>,
     }
 
     #[derive(Debug)]
@@ -14,6 +14,7 @@ pub(crate) mod leader;
 pub mod msg;
 pub(crate) mod record_layer;
 pub(crate) mod setup;
+mod suites;
 
 pub use config::{
     MpcTlsCommonConfig, MpcTlsCommonConfigBuilder, MpcTlsCommonConfigBuilderError,
@@ -23,7 +24,8 @@ pub use config::{
 pub use error::MpcTlsError;
 pub use follower::{FollowerCtrl, MpcTlsFollower, MpcTlsFollowerData};
 pub use leader::{LeaderCtrl, MpcTlsData, MpcTlsLeader};
-pub use setup::setup_components;
+pub use setup::{setup_components, MpcTlsComponents, ShareConversions};
+pub use suites::{MPC_CIPHER_SUITES, MPC_KX_GROUPS};
 use utils_aio::duplex::Duplex;
 
 /// A channel for sending and receiving messages between leader and follower
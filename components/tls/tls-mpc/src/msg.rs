@@ -1,12 +1,14 @@
 //! Contains message types for communication between leader and follower
 
 use serde::{Deserialize, Serialize};
+use tls_core::msgs::enums::CipherSuite;
 
 use crate::{
     error::Kind,
     follower::{
-        ClientFinishedVd, CommitMessage, ComputeClientKey, ComputeKeyExchange, DecryptAlert,
-        DecryptMessage, DecryptServerFinished, EncryptAlert, EncryptClientFinished, EncryptMessage,
+        ClientFinishedVd, CommitMessage, ComputeApplicationKeys, ComputeClientKey,
+        ComputeHandshakeKeys, ComputeKeyExchange, DecryptAlert, DecryptHandshake, DecryptMessage,
+        DecryptServerFinished, EncryptAlert, EncryptClientFinished, EncryptMessage,
         ServerFinishedVd,
     },
     leader::{
@@ -16,8 +18,9 @@ use crate::{
         BackendMsgNextIncoming, BackendMsgPrepareEncryption, BackendMsgServerClosed,
         BackendMsgSetCipherSuite, BackendMsgSetDecrypt, BackendMsgSetEncrypt,
         BackendMsgSetHsHashClientKeyExchange, BackendMsgSetHsHashServerHello,
-        BackendMsgSetProtocolVersion, BackendMsgSetServerCertDetails, BackendMsgSetServerKeyShare,
-        BackendMsgSetServerKxDetails, BackendMsgSetServerRandom, DeferDecryption,
+        BackendMsgSetKeyExchangeGroup, BackendMsgSetProtocolVersion,
+        BackendMsgSetServerCertDetails, BackendMsgSetServerKeyShare, BackendMsgSetServerKxDetails,
+        BackendMsgSetServerRandom, DeferDecryption,
     },
     MpcTlsError,
 };
@@ -26,13 +29,18 @@ use crate::{
 #[allow(missing_docs)]
 #[derive(Debug, Serialize, Deserialize)]
 pub enum MpcTlsMessage {
+    /// The cipher suites supported by the follower, sent during setup.
+    CipherSuites(CipherSuites),
     ComputeClientKey(ComputeClientKey),
     ComputeKeyExchange(ComputeKeyExchange),
+    ComputeHandshakeKeys(ComputeHandshakeKeys),
+    ComputeApplicationKeys(ComputeApplicationKeys),
     ClientFinishedVd(ClientFinishedVd),
     EncryptClientFinished(EncryptClientFinished),
     EncryptAlert(EncryptAlert),
     ServerFinishedVd(ServerFinishedVd),
     DecryptServerFinished(DecryptServerFinished),
+    DecryptHandshake(DecryptHandshake),
     DecryptAlert(DecryptAlert),
     /// A leader commitment to a TLS message received from the server.
     CommitMessage(CommitMessage),
@@ -50,11 +58,14 @@ impl TryFrom<MpcTlsMessage> for MpcTlsFollowerMsg {
         match msg {
             MpcTlsMessage::ComputeClientKey(msg) => Ok(Self::ComputeClientKey(msg)),
             MpcTlsMessage::ComputeKeyExchange(msg) => Ok(Self::ComputeKeyExchange(msg)),
+            MpcTlsMessage::ComputeHandshakeKeys(msg) => Ok(Self::ComputeHandshakeKeys(msg)),
+            MpcTlsMessage::ComputeApplicationKeys(msg) => Ok(Self::ComputeApplicationKeys(msg)),
             MpcTlsMessage::ClientFinishedVd(msg) => Ok(Self::ClientFinishedVd(msg)),
             MpcTlsMessage::EncryptClientFinished(msg) => Ok(Self::EncryptClientFinished(msg)),
             MpcTlsMessage::EncryptAlert(msg) => Ok(Self::EncryptAlert(msg)),
             MpcTlsMessage::ServerFinishedVd(msg) => Ok(Self::ServerFinishedVd(msg)),
             MpcTlsMessage::DecryptServerFinished(msg) => Ok(Self::DecryptServerFinished(msg)),
+            MpcTlsMessage::DecryptHandshake(msg) => Ok(Self::DecryptHandshake(msg)),
             MpcTlsMessage::DecryptAlert(msg) => Ok(Self::DecryptAlert(msg)),
             MpcTlsMessage::CommitMessage(msg) => Ok(Self::CommitMessage(msg)),
             MpcTlsMessage::EncryptMessage(msg) => Ok(Self::EncryptMessage(msg)),
@@ -79,6 +90,7 @@ pub enum MpcTlsLeaderMsg {
     BackendMsgSetEncrypt(BackendMsgSetEncrypt),
     BackendMsgSetDecrypt(BackendMsgSetDecrypt),
     BackendMsgGetClientRandom(BackendMsgGetClientRandom),
+    BackendMsgSetKeyExchangeGroup(BackendMsgSetKeyExchangeGroup),
     BackendMsgGetClientKeyShare(BackendMsgGetClientKeyShare),
     BackendMsgSetServerRandom(BackendMsgSetServerRandom),
     BackendMsgSetServerKeyShare(BackendMsgSetServerKeyShare),
@@ -107,11 +119,14 @@ pub enum MpcTlsLeaderMsg {
 pub enum MpcTlsFollowerMsg {
     ComputeClientKey(ComputeClientKey),
     ComputeKeyExchange(ComputeKeyExchange),
+    ComputeHandshakeKeys(ComputeHandshakeKeys),
+    ComputeApplicationKeys(ComputeApplicationKeys),
     ClientFinishedVd(ClientFinishedVd),
     EncryptClientFinished(EncryptClientFinished),
     EncryptAlert(EncryptAlert),
     ServerFinishedVd(ServerFinishedVd),
     DecryptServerFinished(DecryptServerFinished),
+    DecryptHandshake(DecryptHandshake),
     DecryptAlert(DecryptAlert),
     CommitMessage(CommitMessage),
     EncryptMessage(EncryptMessage),
@@ -120,6 +135,10 @@ pub enum MpcTlsFollowerMsg {
     Finalize(Commit),
 }
 
+/// Cipher suites supported by a party.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CipherSuites(pub Vec<CipherSuite>);
+
 /// Message to close the connection
 #[derive(Debug, ludi::Message, Serialize, Deserialize)]
 #[ludi(return_ty = "Result<(), MpcTlsError>")]
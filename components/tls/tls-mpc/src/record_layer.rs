@@ -1,7 +1,7 @@
 use mpz_garble::value::ValueRef;
 
 use tls_core::{
-    cipher::make_tls12_aad,
+    cipher::{make_tls12_aad, make_tls13_aad},
     msgs::{
         base::Payload,
         enums::{ContentType, ProtocolVersion},
@@ -9,10 +9,19 @@ use tls_core::{
     },
 };
 
-use crate::{error::Kind, MpcTlsError};
+use crate::{error::Kind, setup::RecordAeadSetup, suites::Cipher, MpcTlsError};
+
+/// The length of the explicit nonce in TLS 1.2 records.
+const EXPLICIT_NONCE_LEN: usize = 8;
+/// The length of the AEAD authentication tag.
+const TAG_LEN: usize = 16;
 
 pub(crate) struct Encrypter {
-    aead: Box<dyn aead::Aead>,
+    /// The setup of the AEAD, consumed once the cipher suite is negotiated.
+    setup: Option<RecordAeadSetup>,
+    aead: Option<Box<dyn aead::Aead + Send>>,
+    version: ProtocolVersion,
+    cipher: Cipher,
     seq: u64,
     sent_bytes: usize,
     transcript_id: String,
@@ -21,12 +30,15 @@ pub(crate) struct Encrypter {
 
 impl Encrypter {
     pub(crate) fn new(
-        aead: Box<dyn aead::Aead>,
+        setup: RecordAeadSetup,
         transcript_id: String,
         opaque_transcript_id: String,
     ) -> Self {
         Self {
-            aead,
+            setup: Some(setup),
+            aead: None,
+            version: ProtocolVersion::TLSv1_2,
+            cipher: Cipher::Aes128Gcm,
             seq: 0,
             sent_bytes: 0,
             transcript_id,
@@ -39,11 +51,31 @@ impl Encrypter {
         self.sent_bytes
     }
 
+    /// Sets the protocol version and cipher of the record layer, setting up its AEAD.
+    pub(crate) fn set_cipher(
+        &mut self,
+        version: ProtocolVersion,
+        cipher: Cipher,
+    ) -> Result<(), MpcTlsError> {
+        let setup = self
+            .setup
+            .take()
+            .ok_or_else(|| MpcTlsError::new(Kind::State, "cipher is already set"))?;
+
+        self.aead = Some(setup.build(version, cipher));
+        self.version = version;
+        self.cipher = cipher;
+
+        Ok(())
+    }
+
     pub(crate) async fn set_key(&mut self, key: ValueRef, iv: ValueRef) -> Result<(), MpcTlsError> {
-        self.aead.set_key(key, iv).await.map_err(|e| {
+        self.aead_mut()?.set_key(key, iv).await.map_err(|e| {
             MpcTlsError::new_with_source(Kind::Encrypt, "error setting encryption key", e)
         })?;
 
+        self.seq = 0;
+
         Ok(())
     }
 
@@ -57,29 +89,23 @@ impl Encrypter {
             payload,
         } = msg;
 
-        self.prepare_encrypt(typ);
+        self.prepare_encrypt(typ)?;
 
-        let seq = self.seq;
         let len = payload.0.len();
-        let explicit_nonce = seq.to_be_bytes().to_vec();
-        let aad = make_tls12_aad(seq, typ, version, len);
+        let (explicit_nonce, aad) = self.nonce_and_aad(typ, version, len);
+        let plaintext = self.inner_plaintext(typ, payload.0);
 
         let ciphertext = self
-            .aead
-            .encrypt_private(explicit_nonce.clone(), payload.0, aad.to_vec())
+            .aead_mut()?
+            .encrypt_private(explicit_nonce.clone(), plaintext, aad)
             .await
             .map_err(|e| MpcTlsError::new_with_source(Kind::Encrypt, "encrypt_private error", e))?;
 
-        self.record_message(typ, len);
+        let msg = self.opaque_message(typ, version, explicit_nonce, ciphertext);
 
-        let mut payload = explicit_nonce;
-        payload.extend(ciphertext);
+        self.record_message(typ, len);
 
-        Ok(OpaqueMessage {
-            typ,
-            version,
-            payload: Payload::new(payload),
-        })
+        Ok(msg)
     }
 
     pub(crate) async fn encrypt_blind(
@@ -88,14 +114,13 @@ impl Encrypter {
         version: ProtocolVersion,
         len: usize,
     ) -> Result<(), MpcTlsError> {
-        self.prepare_encrypt(typ);
+        self.prepare_encrypt(typ)?;
 
-        let seq = self.seq;
-        let explicit_nonce = seq.to_be_bytes().to_vec();
-        let aad = make_tls12_aad(seq, typ, version, len);
+        let (explicit_nonce, aad) = self.nonce_and_aad(typ, version, len);
+        let inner_len = self.inner_len(len);
 
-        self.aead
-            .encrypt_blind(explicit_nonce, len, aad.to_vec())
+        self.aead_mut()?
+            .encrypt_blind(explicit_nonce, inner_len, aad)
             .await
             .map_err(|e| MpcTlsError::new_with_source(Kind::Encrypt, "encrypt_blind error", e))?;
 
@@ -114,39 +139,134 @@ impl Encrypter {
             payload,
         } = msg;
 
-        self.prepare_encrypt(typ);
+        self.prepare_encrypt(typ)?;
 
-        let seq = self.seq;
         let len = payload.0.len();
-        let explicit_nonce = seq.to_be_bytes().to_vec();
-        let aad = make_tls12_aad(seq, typ, version, len);
+        let (explicit_nonce, aad) = self.nonce_and_aad(typ, version, len);
+        let plaintext = self.inner_plaintext(typ, payload.0);
 
         let ciphertext = self
-            .aead
-            .encrypt_public(explicit_nonce.clone(), payload.0, aad.to_vec())
+            .aead_mut()?
+            .encrypt_public(explicit_nonce.clone(), plaintext, aad)
             .await
             .map_err(|e| MpcTlsError::new_with_source(Kind::Encrypt, "encrypt_public error", e))?;
 
+        let msg = self.opaque_message(typ, version, explicit_nonce, ciphertext);
+
         self.record_message(typ, len);
 
-        let mut payload = explicit_nonce;
-        payload.extend(ciphertext);
+        Ok(msg)
+    }
 
-        Ok(OpaqueMessage {
-            typ,
-            version,
-            payload: Payload::new(payload),
-        })
+    fn is_tls13(&self) -> bool {
+        self.version == ProtocolVersion::TLSv1_3
+    }
+
+    /// Returns whether records carry an explicit nonce.
+    fn has_explicit_nonce(&self) -> bool {
+        !self.is_tls13() && self.cipher.has_explicit_nonce()
+    }
+
+    fn aead_mut(&mut self) -> Result<&mut (dyn aead::Aead + Send), MpcTlsError> {
+        match self.aead.as_mut() {
+            Some(aead) => Ok(aead.as_mut()),
+            None => Err(MpcTlsError::new(Kind::State, "cipher is not set")),
+        }
     }
 
-    fn prepare_encrypt(&mut self, typ: ContentType) {
+    /// Returns the explicit nonce and additional data for the next record.
+    ///
+    /// If records do not carry an explicit nonce, the sequence number is used as the nonce
+    /// which is combined with the IV.
+    fn nonce_and_aad(
+        &self,
+        typ: ContentType,
+        version: ProtocolVersion,
+        len: usize,
+    ) -> (Vec<u8>, Vec<u8>) {
+        let explicit_nonce = self.seq.to_be_bytes().to_vec();
+        let aad = if self.is_tls13() {
+            make_tls13_aad(self.inner_len(len) + TAG_LEN).to_vec()
+        } else {
+            make_tls12_aad(self.seq, typ, version, len).to_vec()
+        };
+
+        (explicit_nonce, aad)
+    }
+
+    /// Returns the length of the plaintext which is encrypted.
+    ///
+    /// In TLS 1.3 the content type is encrypted along with the content.
+    fn inner_len(&self, len: usize) -> usize {
+        if self.is_tls13() {
+            len + 1
+        } else {
+            len
+        }
+    }
+
+    fn inner_plaintext(&self, typ: ContentType, mut payload: Vec<u8>) -> Vec<u8> {
+        if self.is_tls13() {
+            payload.push(typ.get_u8());
+        }
+
+        payload
+    }
+
+    fn opaque_message(
+        &self,
+        typ: ContentType,
+        version: ProtocolVersion,
+        explicit_nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+    ) -> OpaqueMessage {
+        if self.is_tls13() {
+            OpaqueMessage {
+                typ: ContentType::ApplicationData,
+                version: ProtocolVersion::TLSv1_2,
+                payload: Payload::new(ciphertext),
+            }
+        } else if self.has_explicit_nonce() {
+            let mut payload = explicit_nonce;
+            payload.extend(ciphertext);
+
+            OpaqueMessage {
+                typ,
+                version,
+                payload: Payload::new(payload),
+            }
+        } else {
+            OpaqueMessage {
+                typ,
+                version,
+                payload: Payload::new(ciphertext),
+            }
+        }
+    }
+
+    fn prepare_encrypt(&mut self, typ: ContentType) -> Result<(), MpcTlsError> {
+        let is_tls13 = self.is_tls13();
+        let transcript_id = self.transcript_id.clone();
+        let opaque_transcript_id = self.opaque_transcript_id.clone();
+        let aead = self.aead_mut()?;
+
         // Set the transcript id depending on the type of message
         match typ {
+            ContentType::ApplicationData if is_tls13 => {
+                // The content type trails the content, it is not part of the transcript.
+                aead.set_transcript_id(&transcript_id);
+                aead.set_transcript_trailer(&opaque_transcript_id, 1);
+            }
             ContentType::ApplicationData => {
-                self.aead.set_transcript_id(&self.transcript_id);
+                aead.set_transcript_id(&transcript_id);
+            }
+            _ => {
+                aead.set_transcript_id(&opaque_transcript_id);
+                aead.set_transcript_trailer(&opaque_transcript_id, 0);
             }
-            _ => self.aead.set_transcript_id(&self.opaque_transcript_id),
         }
+
+        Ok(())
     }
 
     fn record_message(&mut self, typ: ContentType, len: usize) {
@@ -158,7 +278,13 @@ impl Encrypter {
 }
 
 pub(crate) struct Decrypter {
-    aead: Box<dyn aead::Aead>,
+    /// The setup of the AEAD, consumed once the cipher suite is negotiated.
+    setup: Option<RecordAeadSetup>,
+    aead: Option<Box<dyn aead::Aead + Send>>,
+    version: ProtocolVersion,
+    cipher: Cipher,
+    /// Whether the TLS 1.3 handshake traffic keys are in use.
+    handshake: bool,
     seq: u64,
     recv_bytes: usize,
     transcript_id: String,
@@ -167,12 +293,16 @@ pub(crate) struct Decrypter {
 
 impl Decrypter {
     pub(crate) fn new(
-        aead: Box<dyn aead::Aead>,
+        setup: RecordAeadSetup,
         transcript_id: String,
         opaque_transcript_id: String,
     ) -> Self {
         Self {
-            aead,
+            setup: Some(setup),
+            aead: None,
+            version: ProtocolVersion::TLSv1_2,
+            cipher: Cipher::Aes128Gcm,
+            handshake: false,
             seq: 0,
             recv_bytes: 0,
             transcript_id,
@@ -181,15 +311,53 @@ impl Decrypter {
     }
 
     /// Returns the number of application data bytes decrypted
+    ///
+    /// # Note
+    ///
+    /// In TLS 1.3 the content type of a record is encrypted, so this includes the content of
+    /// every record received after the handshake.
     pub(crate) fn recv_bytes(&self) -> usize {
         self.recv_bytes
     }
 
+    /// Sets the protocol version and cipher of the record layer, setting up its AEAD.
+    pub(crate) fn set_cipher(
+        &mut self,
+        version: ProtocolVersion,
+        cipher: Cipher,
+    ) -> Result<(), MpcTlsError> {
+        let setup = self
+            .setup
+            .take()
+            .ok_or_else(|| MpcTlsError::new(Kind::State, "cipher is already set"))?;
+
+        self.aead = Some(setup.build(version, cipher));
+        self.version = version;
+        self.cipher = cipher;
+
+        Ok(())
+    }
+
     pub(crate) async fn set_key(&mut self, key: ValueRef, iv: ValueRef) -> Result<(), MpcTlsError> {
-        self.aead.set_key(key, iv).await.map_err(|e| {
+        self.aead_mut()?.set_key(key, iv).await.map_err(|e| {
             MpcTlsError::new_with_source(Kind::Decrypt, "error setting decryption key", e)
         })?;
 
+        self.seq = 0;
+        self.handshake = false;
+
+        Ok(())
+    }
+
+    /// Sets the TLS 1.3 handshake traffic key.
+    pub(crate) async fn set_handshake_key(
+        &mut self,
+        key: ValueRef,
+        iv: ValueRef,
+    ) -> Result<(), MpcTlsError> {
+        self.set_key(key, iv).await?;
+        self.handshake = true;
+
         Ok(())
     }
 
@@ -197,50 +365,42 @@ impl Decrypter {
         &mut self,
         msg: OpaqueMessage,
     ) -> Result<PlainMessage, MpcTlsError> {
-        let OpaqueMessage {
+        let Record {
             typ,
             version,
-            mut payload,
-        } = msg;
-
-        let explicit_nonce: Vec<u8> = payload.0.drain(..8).collect();
-        let len = payload.0.len() - 16;
-        let seq = self.seq;
+            explicit_nonce,
+            ciphertext,
+            aad,
+            len,
+        } = self.open_record(msg)?;
 
-        self.prepare_decrypt(typ);
+        self.prepare_decrypt(typ)?;
 
-        let aad = make_tls12_aad(seq, typ, version, len);
         let plaintext = self
-            .aead
-            .decrypt_private(explicit_nonce, payload.0, aad.to_vec())
+            .aead_mut()?
+            .decrypt_private(explicit_nonce, ciphertext, aad)
             .await
             .map_err(|e| MpcTlsError::new_with_source(Kind::Decrypt, "decrypt_private error", e))?;
 
         self.record_message(typ, len);
 
-        Ok(PlainMessage {
-            typ,
-            version,
-            payload: Payload::new(plaintext),
-        })
+        self.plain_message(typ, version, plaintext)
     }
 
     pub(crate) async fn decrypt_blind(&mut self, msg: OpaqueMessage) -> Result<(), MpcTlsError> {
-        let OpaqueMessage {
+        let Record {
             typ,
-            version,
-            mut payload,
-        } = msg;
-
-        let explicit_nonce: Vec<u8> = payload.0.drain(..8).collect();
-        let len = payload.0.len() - 16;
-        let seq = self.seq;
+            explicit_nonce,
+            ciphertext,
+            aad,
+            len,
+            ..
+        } = self.open_record(msg)?;
 
-        self.prepare_decrypt(typ);
+        self.prepare_decrypt(typ)?;
 
-        let aad = make_tls12_aad(seq, typ, version, len);
-        self.aead
-            .decrypt_blind(explicit_nonce, payload.0, aad.to_vec())
+        self.aead_mut()?
+            .decrypt_blind(explicit_nonce, ciphertext, aad)
             .await
             .map_err(|e| MpcTlsError::new_with_source(Kind::Decrypt, "decrypt_blind error", e))?;
 
@@ -253,43 +413,37 @@ impl Decrypter {
         &mut self,
         msg: OpaqueMessage,
     ) -> Result<PlainMessage, MpcTlsError> {
-        let OpaqueMessage {
+        let Record {
             typ,
             version,
-            mut payload,
-        } = msg;
-
-        let explicit_nonce: Vec<u8> = payload.0.drain(..8).collect();
-        let len = payload.0.len() - 16;
-        let seq = self.seq;
+            explicit_nonce,
+            ciphertext,
+            aad,
+            len,
+        } = self.open_record(msg)?;
 
-        self.prepare_decrypt(typ);
+        self.prepare_decrypt(typ)?;
 
-        let aad = make_tls12_aad(seq, typ, version, len);
         let plaintext = self
-            .aead
-            .decrypt_public(explicit_nonce, payload.0, aad.to_vec())
+            .aead_mut()?
+            .decrypt_public(explicit_nonce, ciphertext, aad)
             .await
             .map_err(|e| MpcTlsError::new_with_source(Kind::Decrypt, "decrypt_public error", e))?;
 
         self.record_message(typ, len);
 
-        Ok(PlainMessage {
-            typ,
-            version,
-            payload: Payload::new(plaintext),
-        })
+        self.plain_message(typ, version, plaintext)
     }
 
     pub(crate) async fn decode_key_private(&mut self) -> Result<(), MpcTlsError> {
-        self.aead
+        self.aead_mut()?
             .decode_key_private()
             .await
             .map_err(|e| MpcTlsError::new_with_source(Kind::Decrypt, "error decoding key", e))
     }
 
     pub(crate) async fn decode_key_blind(&mut self) -> Result<(), MpcTlsError> {
-        self.aead
+        self.aead_mut()?
             .decode_key_blind()
             .await
             .map_err(|e| MpcTlsError::new_with_source(Kind::Decrypt, "error decoding key", e))
@@ -303,32 +457,26 @@ impl Decrypter {
         &mut self,
         msg: OpaqueMessage,
     ) -> Result<PlainMessage, MpcTlsError> {
-        let OpaqueMessage {
+        let Record {
             typ,
             version,
-            mut payload,
-        } = msg;
-
-        let explicit_nonce: Vec<u8> = payload.0.drain(..8).collect();
-        let len = payload.0.len() - 16;
-        let seq = self.seq;
+            explicit_nonce,
+            ciphertext,
+            aad,
+            len,
+        } = self.open_record(msg)?;
 
-        self.prepare_decrypt(typ);
+        self.prepare_decrypt(typ)?;
 
-        let aad = make_tls12_aad(seq, typ, version, len);
         let plaintext = self
-            .aead
-            .prove_plaintext(explicit_nonce, payload.0, aad.to_vec())
+            .aead_mut()?
+            .prove_plaintext(explicit_nonce, ciphertext, aad)
             .await
             .map_err(|e| MpcTlsError::new_with_source(Kind::Decrypt, "prove_plaintext error", e))?;
 
         self.record_message(typ, len);
 
-        Ok(PlainMessage {
-            typ,
-            version,
-            payload: Payload::new(plaintext),
-        })
+        self.plain_message(typ, version, plaintext)
     }
 
     /// Verifies the plaintext of the message
@@ -336,21 +484,19 @@ impl Decrypter {
     /// This verifies the tag of the message then has the other party decrypt it. Then,
     /// the other party commits to the plaintext and proves it encrypts back to the ciphertext.
     pub(crate) async fn verify_plaintext(&mut self, msg: OpaqueMessage) -> Result<(), MpcTlsError> {
-        let OpaqueMessage {
+        let Record {
             typ,
-            version,
-            mut payload,
-        } = msg;
-
-        let explicit_nonce: Vec<u8> = payload.0.drain(..8).collect();
-        let len = payload.0.len() - 16;
-        let seq = self.seq;
+            explicit_nonce,
+            ciphertext,
+            aad,
+            len,
+            ..
+        } = self.open_record(msg)?;
 
-        self.prepare_decrypt(typ);
+        self.prepare_decrypt(typ)?;
 
-        let aad = make_tls12_aad(seq, typ, version, len);
-        self.aead
-            .verify_plaintext(explicit_nonce, payload.0, aad.to_vec())
+        self.aead_mut()?
+            .verify_plaintext(explicit_nonce, ciphertext, aad)
             .await
             .map_err(|e| {
                 MpcTlsError::new_with_source(Kind::Decrypt, "verify_plaintext error", e)
@@ -361,20 +507,153 @@ impl Decrypter {
         Ok(())
     }
 
-    fn prepare_decrypt(&mut self, typ: ContentType) {
+    fn is_tls13(&self) -> bool {
+        self.version == ProtocolVersion::TLSv1_3
+    }
+
+    /// Returns whether records carry an explicit nonce.
+    fn has_explicit_nonce(&self) -> bool {
+        !self.is_tls13() && self.cipher.has_explicit_nonce()
+    }
+
+    fn aead_mut(&mut self) -> Result<&mut (dyn aead::Aead + Send), MpcTlsError> {
+        match self.aead.as_mut() {
+            Some(aead) => Ok(aead.as_mut()),
+            None => Err(MpcTlsError::new(Kind::State, "cipher is not set")),
+        }
+    }
+
+    /// Splits a record into the inputs of the AEAD.
+    fn open_record(&self, msg: OpaqueMessage) -> Result<Record, MpcTlsError> {
+        let OpaqueMessage {
+            typ,
+            version,
+            mut payload,
+        } = msg;
+
+        if self.is_tls13() {
+            // The inner plaintext contains at least the content type.
+            if payload.0.len() < TAG_LEN + 1 {
+                return Err(MpcTlsError::new(Kind::Decrypt, "record is too short"));
+            }
+
+            let len = payload.0.len() - TAG_LEN;
+
+            Ok(Record {
+                typ,
+                version,
+                explicit_nonce: self.seq.to_be_bytes().to_vec(),
+                aad: make_tls13_aad(payload.0.len()).to_vec(),
+                ciphertext: payload.0,
+                len,
+            })
+        } else {
+            let explicit_nonce_len = if self.has_explicit_nonce() {
+                EXPLICIT_NONCE_LEN
+            } else {
+                0
+            };
+
+            if payload.0.len() < explicit_nonce_len + TAG_LEN {
+                return Err(MpcTlsError::new(Kind::Decrypt, "record is too short"));
+            }
+
+            let explicit_nonce: Vec<u8> = if self.has_explicit_nonce() {
+                payload.0.drain(..EXPLICIT_NONCE_LEN).collect()
+            } else {
+                self.seq.to_be_bytes().to_vec()
+            };
+            let len = payload.0.len() - TAG_LEN;
+
+            Ok(Record {
+                typ,
+                version,
+                explicit_nonce,
+                aad: make_tls12_aad(self.seq, typ, version, len).to_vec(),
+                ciphertext: payload.0,
+                len,
+            })
+        }
+    }
+
+    /// Builds the plaintext message, removing the trailing content type in TLS 1.3.
+    fn plain_message(
+        &self,
+        typ: ContentType,
+        version: ProtocolVersion,
+        mut plaintext: Vec<u8>,
+    ) -> Result<PlainMessage, MpcTlsError> {
+        if !self.is_tls13() {
+            return Ok(PlainMessage {
+                typ,
+                version,
+                payload: Payload::new(plaintext),
+            });
+        }
+
+        // Padding would be assigned to the transcript, so it is not supported.
+        let typ = match plaintext.pop() {
+            Some(0) | None => {
+                return Err(MpcTlsError::new(
+                    Kind::Decrypt,
+                    "padded records are not supported",
+                ))
+            }
+            Some(typ) => ContentType::from(typ),
+        };
+
+        Ok(PlainMessage {
+            typ,
+            version: ProtocolVersion::TLSv1_3,
+            payload: Payload::new(plaintext),
+        })
+    }
+
+    fn prepare_decrypt(&mut self, typ: ContentType) -> Result<(), MpcTlsError> {
+        let is_tls13 = self.is_tls13();
+        let handshake = self.handshake;
+        let transcript_id = self.transcript_id.clone();
+        let opaque_transcript_id = self.opaque_transcript_id.clone();
+        let aead = self.aead_mut()?;
+
         // Set the transcript id depending on the type of message
         match typ {
-            ContentType::ApplicationData => {
-                self.aead.set_transcript_id(&self.transcript_id);
+            ContentType::ApplicationData if is_tls13 && !handshake => {
+                // The content type trails the content, it is not part of the transcript.
+                aead.set_transcript_id(&transcript_id);
+                aead.set_transcript_trailer(&opaque_transcript_id, 1);
+            }
+            ContentType::ApplicationData if !is_tls13 => {
+                aead.set_transcript_id(&transcript_id);
+            }
+            _ => {
+                aead.set_transcript_id(&opaque_transcript_id);
+                aead.set_transcript_trailer(&opaque_transcript_id, 0);
             }
-            _ => self.aead.set_transcript_id(&self.opaque_transcript_id),
         }
+
+        Ok(())
     }
 
     fn record_message(&mut self, typ: ContentType, len: usize) {
         self.seq += 1;
-        if let ContentType::ApplicationData = typ {
+        if self.is_tls13() {
+            if !self.handshake {
+                self.recv_bytes += len - 1;
+            }
+        } else if let ContentType::ApplicationData = typ {
             self.recv_bytes += len;
         }
     }
 }
+
+/// The inputs of the AEAD for a received record.
+struct Record {
+    typ: ContentType,
+    version: ProtocolVersion,
+    explicit_nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+    aad: Vec<u8>,
+    /// The length of the plaintext.
+    len: usize,
+}
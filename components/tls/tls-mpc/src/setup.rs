@@ -1,21 +1,80 @@
 use hmac_sha256 as prf;
 use key_exchange as ke;
-use mpz_garble::{Decode, DecodePrivate, Execute, Load, Prove, Verify, Vm};
+use mpz_garble::{Decode, DecodePrivate, Execute, Load, Prove, Thread, ThreadPool, Verify, Vm};
 use mpz_share_conversion as ff;
 use point_addition as pa;
 use tlsn_stream_cipher as stream_cipher;
 use tlsn_universal_hash as universal_hash;
+use universal_hash::poly1305::P1305;
 
-use aead::Aead;
-use hmac_sha256::Prf;
+use aead::{Aead, AeadChannel};
+use block_cipher::BlockCipherCircuit;
+use hmac_sha256::{KeySchedule, Prf};
 use ke::KeyExchange;
+use stream_cipher::CtrCircuit;
+use tls_core::msgs::enums::ProtocolVersion;
 
 use utils_aio::mux::MuxChannel;
 
-use crate::{config::MpcTlsCommonConfig, MpcTlsError, TlsRole};
+use crate::{config::MpcTlsCommonConfig, suites::Cipher, MpcTlsError, TlsRole};
+
+/// The AEAD of one direction of the record layer.
+///
+/// Only the AEAD of the negotiated cipher suite is set up, which happens once the suite is known
+/// to both parties. The threads it runs on are reserved in advance, as the VM is not available
+/// at that point.
+pub(crate) struct RecordAeadSetup(Box<dyn BuildAead>);
+
+impl RecordAeadSetup {
+    /// Sets up the AEAD for `cipher`, with the counter-mode of `version`.
+    pub(crate) fn build(self, version: ProtocolVersion, cipher: Cipher) -> Box<dyn Aead + Send> {
+        self.0.build(version, cipher)
+    }
+}
+
+impl std::fmt::Debug for RecordAeadSetup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordAeadSetup").finish_non_exhaustive()
+    }
+}
+
+trait BuildAead: Send {
+    fn build(self: Box<Self>, version: ProtocolVersion, cipher: Cipher) -> Box<dyn Aead + Send>;
+}
+
+/// The share conversions used by the MPC-TLS components.
+pub struct ShareConversions<PS, PR, PS384, PR384, PSX, PRX, GF, P1305C> {
+    /// P-256 share conversion for the sending direction of the point addition.
+    pub p256_send: PS,
+    /// P-256 share conversion for the receiving direction of the point addition.
+    pub p256_recv: PR,
+    /// P-384 share conversion for the sending direction of the point addition.
+    pub p384_send: PS384,
+    /// P-384 share conversion for the receiving direction of the point addition.
+    pub p384_recv: PR384,
+    /// X25519 share conversion for the sending direction of the point addition.
+    pub x25519_send: PSX,
+    /// X25519 share conversion for the receiving direction of the point addition.
+    pub x25519_recv: PRX,
+    /// GF(2^128) share conversion for GHASH.
+    pub gf2: GF,
+    /// Share conversion for Poly1305.
+    pub p1305: P1305C,
+}
+
+/// The MPC components of a leader or follower, see [`setup_components`].
+pub struct MpcTlsComponents {
+    pub(crate) ke: Box<dyn KeyExchange + Send>,
+    pub(crate) prf: Box<dyn Prf + Send>,
+    pub(crate) key_schedule: Box<dyn KeySchedule + Send>,
+    pub(crate) encrypter: RecordAeadSetup,
+    pub(crate) decrypter: RecordAeadSetup,
+}
 
 /// Helper function for setting up components
-#[allow(clippy::type_complexity)]
+///
+/// Sets up the key exchange, the TLS 1.2 PRF, the TLS 1.3 key schedule, and the encrypter and
+/// decrypter of the record layer.
 #[cfg_attr(
     feature = "tracing",
     tracing::instrument(level = "info", skip_all, err)
@@ -25,27 +84,34 @@ pub async fn setup_components<
     VM: Vm + Send,
     PS: ff::ShareConversion<ff::P256> + Send + Sync + 'static + std::fmt::Debug,
     PR: ff::ShareConversion<ff::P256> + Send + Sync + 'static + std::fmt::Debug,
+    PS384: ff::ShareConversion<pa::P384> + Send + Sync + 'static + std::fmt::Debug,
+    PR384: ff::ShareConversion<pa::P384> + Send + Sync + 'static + std::fmt::Debug,
+    PSX: ff::ShareConversion<pa::Curve25519> + Send + Sync + 'static + std::fmt::Debug,
+    PRX: ff::ShareConversion<pa::Curve25519> + Send + Sync + 'static + std::fmt::Debug,
     GF: ff::ShareConversion<ff::Gf2_128> + Send + Sync + Clone + 'static + std::fmt::Debug,
+    P1305C: ff::ShareConversion<P1305> + Send + Sync + Clone + 'static + std::fmt::Debug,
 >(
     config: &MpcTlsCommonConfig,
     role: TlsRole,
     mux: &mut M,
     vm: &mut VM,
-    p256_send: PS,
-    p256_recv: PR,
-    gf: GF,
-) -> Result<
-    (
-        Box<dyn KeyExchange + Send>,
-        Box<dyn Prf + Send>,
-        Box<dyn Aead + Send>,
-        Box<dyn Aead + Send>,
-    ),
-    MpcTlsError,
->
+    conversions: ShareConversions<PS, PR, PS384, PR384, PSX, PRX, GF, P1305C>,
+) -> Result<MpcTlsComponents, MpcTlsError>
 where
-    <VM as Vm>::Thread: Execute + Load + Decode + DecodePrivate + Prove + Verify + Send + Sync,
+    <VM as Vm>::Thread:
+        Execute + Load + Decode + DecodePrivate + Prove + Verify + Send + Sync + 'static,
 {
+    let ShareConversions {
+        p256_send,
+        p256_recv,
+        p384_send,
+        p384_recv,
+        x25519_send,
+        x25519_recv,
+        gf2,
+        p1305,
+    } = conversions;
+
     // Set up channels
     let (mut mux_0, mut mux_1) = (mux.clone(), mux.clone());
     let (ke_channel, encrypter_channel, decrypter_channel) = futures::try_join!(
@@ -56,29 +122,31 @@ where
     .map_err(|_| std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "mux error"))?;
 
     let (ke_role, pa_role, aead_role) = match role {
-        TlsRole::Leader => (
-            ke::Role::Leader,
-            pa::Role::Leader,
-            aead::aes_gcm::Role::Leader,
-        ),
-        TlsRole::Follower => (
-            ke::Role::Follower,
-            pa::Role::Follower,
-            aead::aes_gcm::Role::Follower,
-        ),
+        TlsRole::Leader => (ke::Role::Leader, pa::Role::Leader, aead::Role::Leader),
+        TlsRole::Follower => (ke::Role::Follower, pa::Role::Follower, aead::Role::Follower),
     };
 
     // Key exchange
     let ke = ke::KeyExchangeCore::new(
         ke_channel,
-        pa::MpcPointAddition::new(pa_role, p256_send),
-        pa::MpcPointAddition::new(pa_role, p256_recv),
         vm.new_thread("ke").await?,
         ke::KeyExchangeConfig::builder()
             .id("ke")
             .role(ke_role)
             .build()
             .unwrap(),
+    )
+    .with_p256(
+        pa::MpcPointAddition::new(pa_role, p256_send),
+        pa::MpcPointAddition::new(pa_role, p256_recv),
+    )
+    .with_p384(
+        pa::MpcPointAddition::new(pa_role, p384_send),
+        pa::MpcPointAddition::new(pa_role, p384_recv),
+    )
+    .with_x25519(
+        pa::MpcPointAddition::new(pa_role, x25519_send),
+        pa::MpcPointAddition::new(pa_role, x25519_recv),
     );
 
     // PRF
@@ -92,94 +160,174 @@ where
         vm.new_thread("prf/1").await?,
     );
 
-    // Encrypter
-    let block_cipher = block_cipher::MpcBlockCipher::<block_cipher::Aes128, _>::new(
-        block_cipher::BlockCipherConfig::builder()
-            .id("encrypter/block_cipher")
-            .build()
-            .unwrap(),
-        vm.new_thread("encrypter/block_cipher").await?,
+    // TLS 1.3 key schedule
+    let key_schedule = prf::MpcKeySchedule::new(
+        prf::PrfConfig::builder().role(prf_role).build().unwrap(),
+        vm.new_thread("key_schedule").await?,
     );
 
-    let stream_cipher = stream_cipher::MpcStreamCipher::<stream_cipher::Aes128Ctr, _>::new(
-        stream_cipher::StreamCipherConfig::builder()
-            .id("encrypter/stream_cipher")
-            .transcript_id("tx")
-            .build()
-            .unwrap(),
-        vm.new_thread_pool("encrypter/stream_cipher", config.num_threads())
+    // Encrypter
+    let encrypter = MpcAeadSetup {
+        id: "encrypter".to_string(),
+        transcript_id: "tx".to_string(),
+        opaque_transcript_id: config.opaque_tx_transcript_id().to_string(),
+        role: aead_role,
+        channel: encrypter_channel,
+        block_cipher_thread: vm.new_thread("encrypter/block_cipher").await?,
+        stream_cipher_threads: vm
+            .new_thread_pool("encrypter/stream_cipher", config.num_threads())
             .await?,
-    );
+        gf: gf2.clone(),
+        p1305: p1305.clone(),
+    };
 
-    let ghash = universal_hash::ghash::Ghash::new(
-        universal_hash::ghash::GhashConfig::builder()
-            .id("encrypter/ghash")
-            .initial_block_count(64)
-            .build()
-            .unwrap(),
-        gf.clone(),
-    );
+    // Decrypter
+    let decrypter = MpcAeadSetup {
+        id: "decrypter".to_string(),
+        transcript_id: "rx".to_string(),
+        opaque_transcript_id: config.opaque_rx_transcript_id().to_string(),
+        role: aead_role,
+        channel: decrypter_channel,
+        block_cipher_thread: vm.new_thread("decrypter/block_cipher").await?,
+        stream_cipher_threads: vm
+            .new_thread_pool("decrypter/stream_cipher", config.num_threads())
+            .await?,
+        gf: gf2,
+        p1305,
+    };
 
-    let mut encrypter = aead::aes_gcm::MpcAesGcm::new(
-        aead::aes_gcm::AesGcmConfig::builder()
-            .id("encrypter/aes_gcm")
-            .role(aead_role)
-            .build()
-            .unwrap(),
-        encrypter_channel,
-        Box::new(block_cipher),
-        Box::new(stream_cipher),
-        Box::new(ghash),
-    );
+    Ok(MpcTlsComponents {
+        ke: Box::new(ke),
+        prf: Box::new(prf),
+        key_schedule: Box::new(key_schedule),
+        encrypter: RecordAeadSetup(Box::new(encrypter)),
+        decrypter: RecordAeadSetup(Box::new(decrypter)),
+    })
+}
 
-    encrypter.set_transcript_id(config.opaque_tx_transcript_id());
+/// The resources of the AEAD of one direction, which are shared by all the cipher suites.
+struct MpcAeadSetup<T, GF, P1305C> {
+    id: String,
+    transcript_id: String,
+    opaque_transcript_id: String,
+    role: aead::Role,
+    channel: AeadChannel,
+    block_cipher_thread: T,
+    stream_cipher_threads: ThreadPool<T>,
+    gf: GF,
+    p1305: P1305C,
+}
 
-    // Decrypter
-    let block_cipher = block_cipher::MpcBlockCipher::<block_cipher::Aes128, _>::new(
-        block_cipher::BlockCipherConfig::builder()
-            .id("decrypter/block_cipher")
-            .build()
-            .unwrap(),
-        vm.new_thread("decrypter/block_cipher").await?,
-    );
+impl<T, GF, P1305C> MpcAeadSetup<T, GF, P1305C>
+where
+    T: Thread + Execute + Load + Decode + DecodePrivate + Prove + Verify + Send + Sync + 'static,
+    GF: ff::ShareConversion<ff::Gf2_128> + Send + Sync + Clone + 'static + std::fmt::Debug,
+    P1305C: ff::ShareConversion<P1305> + Send + Sync + Clone + 'static + std::fmt::Debug,
+{
+    /// Sets up an AES-GCM instance with the provided counter-mode and block cipher circuits.
+    fn aes_gcm<C: CtrCircuit, B: BlockCipherCircuit>(self) -> aead::aes_gcm::MpcAesGcm<C, B> {
+        let id = self.id;
 
-    let stream_cipher = stream_cipher::MpcStreamCipher::<stream_cipher::Aes128Ctr, _>::new(
-        stream_cipher::StreamCipherConfig::builder()
-            .id("decrypter/stream_cipher")
-            .transcript_id("rx")
-            .build()
-            .unwrap(),
-        vm.new_thread_pool("decrypter/stream_cipher", config.num_threads())
-            .await?,
-    );
+        let block_cipher = block_cipher::MpcBlockCipher::<B, _>::new(
+            block_cipher::BlockCipherConfig::builder()
+                .id(format!("{id}/block_cipher"))
+                .build()
+                .unwrap(),
+            self.block_cipher_thread,
+        );
 
-    let ghash = universal_hash::ghash::Ghash::new(
-        universal_hash::ghash::GhashConfig::builder()
-            .id("decrypter/ghash")
-            .initial_block_count(64)
-            .build()
-            .unwrap(),
-        gf,
-    );
+        let stream_cipher = stream_cipher::MpcStreamCipher::<C, _>::new(
+            stream_cipher::StreamCipherConfig::builder()
+                .id(format!("{id}/stream_cipher"))
+                .transcript_id(self.transcript_id)
+                .build()
+                .unwrap(),
+            self.stream_cipher_threads,
+        );
 
-    let mut decrypter = aead::aes_gcm::MpcAesGcm::new(
-        aead::aes_gcm::AesGcmConfig::builder()
-            .id("decrypter/aes_gcm")
-            .role(aead_role)
-            .build()
-            .unwrap(),
-        decrypter_channel,
-        Box::new(block_cipher),
-        Box::new(stream_cipher),
-        Box::new(ghash),
-    );
+        let ghash = universal_hash::ghash::Ghash::new(
+            universal_hash::ghash::GhashConfig::builder()
+                .id(format!("{id}/ghash"))
+                .initial_block_count(64)
+                .build()
+                .unwrap(),
+            self.gf,
+        );
+
+        aead::aes_gcm::MpcAesGcm::new(
+            aead::aes_gcm::AesGcmConfig::builder()
+                .id(format!("{id}/aes_gcm"))
+                .role(self.role)
+                .build()
+                .unwrap(),
+            self.channel,
+            Box::new(block_cipher),
+            Box::new(stream_cipher),
+            Box::new(ghash),
+        )
+    }
+
+    /// Sets up a ChaCha20-Poly1305 instance.
+    fn chacha_poly(self) -> aead::chacha_poly::MpcChaChaPoly {
+        let id = self.id;
+
+        // The first keystream block of each record is used for the Poly1305 key.
+        let stream_cipher = stream_cipher::MpcStreamCipher::<stream_cipher::ChaCha20Ctr, _>::new(
+            stream_cipher::StreamCipherConfig::builder()
+                .id(format!("{id}/stream_cipher"))
+                .transcript_id(self.transcript_id)
+                .start_ctr(1)
+                .build()
+                .unwrap(),
+            self.stream_cipher_threads,
+        );
+
+        let poly1305 = universal_hash::poly1305::Poly1305::new(
+            universal_hash::poly1305::Poly1305Config::builder()
+                .id(format!("{id}/poly1305"))
+                .build()
+                .unwrap(),
+            self.p1305,
+        );
+
+        aead::chacha_poly::MpcChaChaPoly::new(
+            aead::chacha_poly::ChaChaPolyConfig::builder()
+                .id(format!("{id}/chacha_poly"))
+                .role(self.role)
+                .build()
+                .unwrap(),
+            self.channel,
+            Box::new(stream_cipher),
+            Box::new(poly1305),
+        )
+    }
+}
+
+impl<T, GF, P1305C> BuildAead for MpcAeadSetup<T, GF, P1305C>
+where
+    T: Thread + Execute + Load + Decode + DecodePrivate + Prove + Verify + Send + Sync + 'static,
+    GF: ff::ShareConversion<ff::Gf2_128> + Send + Sync + Clone + 'static + std::fmt::Debug,
+    P1305C: ff::ShareConversion<P1305> + Send + Sync + Clone + 'static + std::fmt::Debug,
+{
+    fn build(self: Box<Self>, version: ProtocolVersion, cipher: Cipher) -> Box<dyn Aead + Send> {
+        let setup = *self;
+        let opaque_transcript_id = setup.opaque_transcript_id.clone();
+
+        let mut aead: Box<dyn Aead + Send> = match (version, cipher) {
+            (ProtocolVersion::TLSv1_3, _) => {
+                Box::new(setup.aes_gcm::<stream_cipher::Aes128CtrTls13, block_cipher::Aes128>())
+            }
+            (_, Cipher::Aes128Gcm) => {
+                Box::new(setup.aes_gcm::<stream_cipher::Aes128Ctr, block_cipher::Aes128>())
+            }
+            (_, Cipher::Aes256Gcm) => {
+                Box::new(setup.aes_gcm::<stream_cipher::Aes256Ctr, block_cipher::Aes256>())
+            }
+            (_, Cipher::ChaCha20Poly1305) => Box::new(setup.chacha_poly()),
+        };
 
-    decrypter.set_transcript_id(config.opaque_rx_transcript_id());
+        aead.set_transcript_id(&opaque_transcript_id);
 
-    Ok((
-        Box::new(ke),
-        Box::new(prf),
-        Box::new(encrypter),
-        Box::new(decrypter),
-    ))
+        aead
+    }
 }
@@ -0,0 +1,201 @@
+//! Parameters of the cipher suites and key exchange groups supported by the MPC backend.
+
+use hmac_sha256::{PrfHash, PrfParams};
+use key_exchange as ke;
+use tls_core::{
+    msgs::enums::{CipherSuite, NamedGroup},
+    suites::{
+        SupportedCipherSuite, TLS13_AES_128_GCM_SHA256, TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+        TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384, TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+        TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256, TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+        TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+    },
+};
+
+/// The cipher suites which can be run by the MPC backend, in order of preference.
+///
+/// AES-128 is preferred as it is considerably cheaper to compute in MPC than AES-256.
+/// ChaCha20-Poly1305 comes last, as every record needs a new Poly1305 key whose powers are
+/// computed with share conversion.
+pub static MPC_CIPHER_SUITES: &[SupportedCipherSuite] = &[
+    TLS13_AES_128_GCM_SHA256,
+    TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+    TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+    TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+    TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+    TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+    TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+];
+
+/// Returns the cipher suites in `suites` which can be run by the MPC backend, in the order of
+/// [`MPC_CIPHER_SUITES`].
+pub(crate) fn mpc_cipher_suites(suites: &[CipherSuite]) -> Vec<SupportedCipherSuite> {
+    MPC_CIPHER_SUITES
+        .iter()
+        .filter(|suite| suites.contains(&suite.suite()))
+        .copied()
+        .collect()
+}
+
+/// The key exchange groups which can be run by the MPC backend, in order of preference.
+///
+/// The first group is used for the key share sent in the client hello.
+pub static MPC_KX_GROUPS: &[NamedGroup] = &[
+    NamedGroup::X25519,
+    NamedGroup::secp256r1,
+    NamedGroup::secp384r1,
+];
+
+/// Returns whether a key exchange group can be used in TLS 1.3.
+///
+/// The TLS 1.3 key schedule only supports the 32-byte shared secrets of X25519 and P-256.
+pub(crate) fn tls13_kx_group(group: NamedGroup) -> bool {
+    matches!(group, NamedGroup::X25519 | NamedGroup::secp256r1)
+}
+
+/// Returns the key exchange group corresponding to a TLS named group, or `None` if the group is
+/// not supported.
+pub(crate) fn ke_group(group: NamedGroup) -> Option<ke::NamedGroup> {
+    match group {
+        NamedGroup::secp256r1 => Some(ke::NamedGroup::P256),
+        NamedGroup::secp384r1 => Some(ke::NamedGroup::P384),
+        NamedGroup::X25519 => Some(ke::NamedGroup::X25519),
+        _ => None,
+    }
+}
+
+/// Returns the TLS named group corresponding to a key exchange group.
+pub(crate) fn tls_group(group: ke::NamedGroup) -> NamedGroup {
+    match group {
+        ke::NamedGroup::P256 => NamedGroup::secp256r1,
+        ke::NamedGroup::P384 => NamedGroup::secp384r1,
+        ke::NamedGroup::X25519 => NamedGroup::X25519,
+    }
+}
+
+/// The AEAD used to protect TLS 1.2 records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Cipher {
+    /// AES-128-GCM.
+    Aes128Gcm,
+    /// AES-256-GCM.
+    Aes256Gcm,
+    /// ChaCha20-Poly1305, as specified for TLS 1.2 in RFC 7905.
+    ChaCha20Poly1305,
+}
+
+impl Cipher {
+    /// Returns whether records carry an explicit nonce.
+    ///
+    /// The nonce of ChaCha20-Poly1305 is derived from the sequence number instead.
+    pub(crate) fn has_explicit_nonce(&self) -> bool {
+        !matches!(self, Cipher::ChaCha20Poly1305)
+    }
+}
+
+/// Parameters of a TLS 1.2 cipher suite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Tls12Suite {
+    /// Key derivation parameters of the PRF.
+    pub(crate) prf: PrfParams,
+    /// The record protection AEAD.
+    pub(crate) cipher: Cipher,
+}
+
+/// Returns the parameters of a TLS 1.2 cipher suite, or `None` if the suite is not supported.
+pub(crate) fn tls12_suite(suite: CipherSuite) -> Option<Tls12Suite> {
+    match suite {
+        CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256
+        | CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256 => Some(Tls12Suite {
+            prf: PrfParams::new(PrfHash::Sha256, 16, 4),
+            cipher: Cipher::Aes128Gcm,
+        }),
+        CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384
+        | CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384 => Some(Tls12Suite {
+            prf: PrfParams::new(PrfHash::Sha384, 32, 4),
+            cipher: Cipher::Aes256Gcm,
+        }),
+        CipherSuite::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256
+        | CipherSuite::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256 => Some(Tls12Suite {
+            prf: PrfParams::new(PrfHash::Sha256, 32, 12),
+            cipher: Cipher::ChaCha20Poly1305,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mpc_cipher_suites() {
+        let suites = mpc_cipher_suites(&[
+            CipherSuite::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+            CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+            CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+            CipherSuite::TLS13_CHACHA20_POLY1305_SHA256,
+        ]);
+
+        assert_eq!(
+            suites,
+            vec![
+                TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+                TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+                TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mpc_cipher_suites_are_implemented() {
+        for suite in MPC_CIPHER_SUITES {
+            match suite {
+                SupportedCipherSuite::Tls12(_) => assert!(tls12_suite(suite.suite()).is_some()),
+                SupportedCipherSuite::Tls13(_) => {
+                    assert_eq!(suite.suite(), CipherSuite::TLS13_AES_128_GCM_SHA256)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_kx_groups() {
+        for group in MPC_KX_GROUPS {
+            assert_eq!(tls_group(ke_group(*group).unwrap()), *group);
+        }
+        assert!(ke_group(NamedGroup::X448).is_none());
+
+        assert!(tls13_kx_group(NamedGroup::X25519));
+        assert!(tls13_kx_group(NamedGroup::secp256r1));
+        assert!(!tls13_kx_group(NamedGroup::secp384r1));
+    }
+
+    #[test]
+    fn test_tls12_suite() {
+        let suite = tls12_suite(CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384).unwrap();
+
+        assert_eq!(suite.prf.hash, PrfHash::Sha384);
+        assert_eq!(suite.prf.key_len, 32);
+        assert_eq!(suite.cipher, Cipher::Aes256Gcm);
+
+        assert_eq!(
+            tls12_suite(CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256),
+            Some(Tls12Suite {
+                prf: PrfParams::default(),
+                cipher: Cipher::Aes128Gcm,
+            })
+        );
+
+        let suite =
+            tls12_suite(CipherSuite::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256).unwrap();
+
+        assert_eq!(suite.prf.hash, PrfHash::Sha256);
+        assert_eq!(suite.prf.key_len, 32);
+        assert_eq!(suite.prf.iv_len, 12);
+        assert_eq!(suite.cipher, Cipher::ChaCha20Poly1305);
+        assert!(!suite.cipher.has_explicit_nonce());
+
+        assert!(tls12_suite(CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA).is_none());
+    }
+}
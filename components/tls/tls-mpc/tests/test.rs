@@ -12,21 +12,111 @@ use mpz_ot::{
 };
 use mpz_share_conversion as ff;
 use mpz_share_conversion::{ShareConversionReveal, ShareConversionVerify};
-use tls_client::Certificate;
+use point_addition as pa;
+use tls_client::{
+    kx_group::{SECP256R1, SECP384R1, X25519},
+    Certificate, ClientConfig, RootCertStore,
+};
 use tls_client_async::bind_client;
 use tls_mpc::{
     setup_components, MpcTlsCommonConfig, MpcTlsFollower, MpcTlsFollowerConfig, MpcTlsLeader,
-    MpcTlsLeaderConfig, TlsRole,
+    MpcTlsLeaderConfig, ShareConversions, TlsRole,
 };
 use tls_server_fixture::{bind_test_server_hyper, CA_CERT_DER, SERVER_DOMAIN};
+use tlsn_universal_hash::poly1305::P1305;
 use tokio_util::compat::TokioAsyncReadCompatExt;
 use uid_mux::{yamux, UidYamux};
 use utils_aio::{codec::BincodeMux, mux::MuxChannel};
 
+fn root_store() -> RootCertStore {
+    let mut root_store = RootCertStore::empty();
+    root_store.add(&Certificate(CA_CERT_DER.to_vec())).unwrap();
+    root_store
+}
+
 #[tokio::test]
 #[ignore]
 async fn test() {
-    tracing_subscriber::fmt::init();
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store())
+        .with_no_client_auth();
+
+    run(config).await;
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_tls13() {
+    let config = ClientConfig::builder()
+        .with_cipher_suites(&[tls_core::suites::TLS13_AES_128_GCM_SHA256])
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(&[&tls_client::version::TLS13])
+        .unwrap()
+        .with_root_certificates(root_store())
+        .with_no_client_auth();
+
+    run(config).await;
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_aes_256_gcm_sha384() {
+    let config = ClientConfig::builder()
+        .with_cipher_suites(&[tls_core::suites::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384])
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(&[&tls_client::version::TLS12])
+        .unwrap()
+        .with_root_certificates(root_store())
+        .with_no_client_auth();
+
+    run(config).await;
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_chacha20_poly1305() {
+    let config = ClientConfig::builder()
+        .with_cipher_suites(&[tls_core::suites::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256])
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(&[&tls_client::version::TLS12])
+        .unwrap()
+        .with_root_certificates(root_store())
+        .with_no_client_auth();
+
+    run(config).await;
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_x25519() {
+    let config = ClientConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_kx_groups(&[&X25519, &SECP256R1])
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(root_store())
+        .with_no_client_auth();
+
+    run(config).await;
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_p384() {
+    let config = ClientConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_kx_groups(&[&SECP384R1])
+        .with_protocol_versions(&[&tls_client::version::TLS12])
+        .unwrap()
+        .with_root_certificates(root_store())
+        .with_no_client_auth();
+
+    run(config).await;
+}
+
+async fn run(config: ClientConfig) {
+    _ = tracing_subscriber::fmt::try_init();
 
     let (leader_socket, follower_socket) = tokio::io::duplex(1 << 25);
 
@@ -185,6 +275,60 @@ async fn test() {
         follower_mux.get_channel("p256/0").await.unwrap(),
     );
 
+    let leader_p384_send = ff::ConverterSender::<pa::P384, _>::new(
+        ff::SenderConfig::builder().id("p384/0").build().unwrap(),
+        leader_ot_send.clone(),
+        leader_mux.get_channel("p384/0").await.unwrap(),
+    );
+
+    let leader_p384_recv = ff::ConverterReceiver::<pa::P384, _>::new(
+        ff::ReceiverConfig::builder().id("p384/1").build().unwrap(),
+        leader_ot_recv.clone(),
+        leader_mux.get_channel("p384/1").await.unwrap(),
+    );
+
+    let follower_p384_send = ff::ConverterSender::<pa::P384, _>::new(
+        ff::SenderConfig::builder().id("p384/1").build().unwrap(),
+        follower_ot_send.clone(),
+        follower_mux.get_channel("p384/1").await.unwrap(),
+    );
+
+    let follower_p384_recv = ff::ConverterReceiver::<pa::P384, _>::new(
+        ff::ReceiverConfig::builder().id("p384/0").build().unwrap(),
+        follower_ot_recv.clone(),
+        follower_mux.get_channel("p384/0").await.unwrap(),
+    );
+
+    let leader_x25519_send = ff::ConverterSender::<pa::Curve25519, _>::new(
+        ff::SenderConfig::builder().id("x25519/0").build().unwrap(),
+        leader_ot_send.clone(),
+        leader_mux.get_channel("x25519/0").await.unwrap(),
+    );
+
+    let leader_x25519_recv = ff::ConverterReceiver::<pa::Curve25519, _>::new(
+        ff::ReceiverConfig::builder()
+            .id("x25519/1")
+            .build()
+            .unwrap(),
+        leader_ot_recv.clone(),
+        leader_mux.get_channel("x25519/1").await.unwrap(),
+    );
+
+    let follower_x25519_send = ff::ConverterSender::<pa::Curve25519, _>::new(
+        ff::SenderConfig::builder().id("x25519/1").build().unwrap(),
+        follower_ot_send.clone(),
+        follower_mux.get_channel("x25519/1").await.unwrap(),
+    );
+
+    let follower_x25519_recv = ff::ConverterReceiver::<pa::Curve25519, _>::new(
+        ff::ReceiverConfig::builder()
+            .id("x25519/0")
+            .build()
+            .unwrap(),
+        follower_ot_recv.clone(),
+        follower_mux.get_channel("x25519/0").await.unwrap(),
+    );
+
     let mut leader_gf2 = ff::ConverterSender::<ff::Gf2_128, _>::new(
         ff::SenderConfig::builder()
             .id("gf2")
@@ -205,16 +349,43 @@ async fn test() {
         follower_mux.get_channel("gf2").await.unwrap(),
     );
 
+    let mut leader_p1305 = ff::ConverterSender::<P1305, _>::new(
+        ff::SenderConfig::builder()
+            .id("p1305")
+            .record()
+            .build()
+            .unwrap(),
+        leader_ot_send.clone(),
+        leader_mux.get_channel("p1305").await.unwrap(),
+    );
+
+    let mut follower_p1305 = ff::ConverterReceiver::<P1305, _>::new(
+        ff::ReceiverConfig::builder()
+            .id("p1305")
+            .record()
+            .build()
+            .unwrap(),
+        follower_ot_recv.clone(),
+        follower_mux.get_channel("p1305").await.unwrap(),
+    );
+
     let common_config = MpcTlsCommonConfig::builder().id("test").build().unwrap();
 
-    let (leader_ke, leader_prf, leader_encrypter, leader_decrypter) = setup_components(
+    let leader_components = setup_components(
         &common_config,
         TlsRole::Leader,
         &mut leader_mux,
         &mut leader_vm,
-        leader_p256_send,
-        leader_p256_recv,
-        leader_gf2.handle().unwrap(),
+        ShareConversions {
+            p256_send: leader_p256_send,
+            p256_recv: leader_p256_recv,
+            p384_send: leader_p384_send,
+            p384_recv: leader_p384_recv,
+            x25519_send: leader_x25519_send,
+            x25519_recv: leader_x25519_recv,
+            gf2: leader_gf2.handle().unwrap(),
+            p1305: leader_p1305.handle().unwrap(),
+        },
     )
     .await
     .unwrap();
@@ -225,20 +396,24 @@ async fn test() {
             .build()
             .unwrap(),
         leader_mux.get_channel("test").await.unwrap(),
-        leader_ke,
-        leader_prf,
-        leader_encrypter,
-        leader_decrypter,
+        leader_components,
     );
 
-    let (follower_ke, follower_prf, follower_encrypter, follower_decrypter) = setup_components(
+    let follower_components = setup_components(
         &common_config,
         TlsRole::Follower,
         &mut follower_mux,
         &mut follower_vm,
-        follower_p256_send,
-        follower_p256_recv,
-        follower_gf2.handle().unwrap(),
+        ShareConversions {
+            p256_send: follower_p256_send,
+            p256_recv: follower_p256_recv,
+            p384_send: follower_p384_send,
+            p384_recv: follower_p384_recv,
+            x25519_send: follower_x25519_send,
+            x25519_recv: follower_x25519_recv,
+            gf2: follower_gf2.handle().unwrap(),
+            p1305: follower_p1305.handle().unwrap(),
+        },
     )
     .await
     .unwrap();
@@ -249,10 +424,7 @@ async fn test() {
             .build()
             .unwrap(),
         follower_mux.get_channel("test").await.unwrap(),
-        follower_ke,
-        follower_prf,
-        follower_encrypter,
-        follower_decrypter,
+        follower_components,
     );
 
     let follower_task = tokio::spawn(async move {
@@ -267,13 +439,6 @@ async fn test() {
     let (leader_ctrl, leader_fut) = leader.run();
     let leader_task = tokio::spawn(leader_fut);
 
-    let mut root_store = tls_client::RootCertStore::empty();
-    root_store.add(&Certificate(CA_CERT_DER.to_vec())).unwrap();
-    let config = tls_client::ClientConfig::builder()
-        .with_safe_defaults()
-        .with_root_certificates(root_store)
-        .with_no_client_auth();
-
     let server_name = SERVER_DOMAIN.try_into().unwrap();
 
     let client = tls_client::ClientConnection::new(
@@ -339,6 +504,7 @@ async fn test() {
 
     tokio::try_join!(leader_vm.finalize(), follower_vm.finalize()).unwrap();
     tokio::try_join!(leader_gf2.reveal(), follower_gf2.verify()).unwrap();
+    tokio::try_join!(leader_p1305.reveal(), follower_p1305.verify()).unwrap();
 
     conn_task.await.unwrap().unwrap();
     _ = leader_task.await.unwrap();
@@ -82,9 +82,11 @@ where
             return Err(UniversalHashError::KeyLengthError(16, key.len()));
         }
 
-        if !matches!(&self.state, State::Init) {
+        // Setting a new key replaces the previous one, which is required for protocols
+        // which change keys during a session (e.g. TLS 1.3).
+        if matches!(&self.state, State::Error) {
             return Err(UniversalHashError::InvalidState(
-                "Key already set".to_string(),
+                "Ghash is in an error state".to_string(),
             ));
         }
 
@@ -309,6 +311,41 @@ mod tests {
         assert_eq!(tag, ghash_reference_impl(h, &short_message));
     }
 
+    #[tokio::test]
+    async fn test_ghash_rekey() {
+        let mut rng = ChaCha12Rng::from_seed([0; 32]);
+        let message: Vec<u8> = (0..128).map(|_| rng.gen()).collect();
+
+        let (mut sender, mut receiver) = create_pair("test", 1);
+
+        for _ in 0..2 {
+            let h: u128 = rng.gen();
+            let sender_key: u128 = rng.gen();
+            let receiver_key: u128 = h ^ sender_key;
+
+            let (sender_result, receiver_result) = tokio::join!(
+                sender.set_key(sender_key.to_be_bytes().to_vec()),
+                receiver.set_key(receiver_key.to_be_bytes().to_vec())
+            );
+            sender_result.unwrap();
+            receiver_result.unwrap();
+
+            let (sender_result, receiver_result) = tokio::join!(
+                sender.finalize(message.clone()),
+                receiver.finalize(message.clone())
+            );
+            let (sender_share, receiver_share) = (sender_result.unwrap(), receiver_result.unwrap());
+
+            let tag = sender_share
+                .iter()
+                .zip(receiver_share.iter())
+                .map(|(a, b)| a ^ b)
+                .collect::<Vec<u8>>();
+
+            assert_eq!(tag, ghash_reference_impl(h, &message));
+        }
+    }
+
     fn ghash_reference_impl(h: u128, message: &[u8]) -> Vec<u8> {
         let mut ghash = GhashReference::new(&h.to_be_bytes().into());
         ghash.update_padded(message);
@@ -8,6 +8,10 @@
 #[cfg(feature = "ghash")]
 pub mod ghash;
 
+/// This module implements [UniversalHash] for Poly1305
+#[cfg(feature = "poly1305")]
+pub mod poly1305;
+
 use async_trait::async_trait;
 
 /// Errors for [UniversalHash]
@@ -29,6 +33,8 @@ pub enum UniversalHashError {
 pub trait UniversalHash: Send {
     /// Set the key for the hash function
     ///
+    /// Calling this again replaces the previous key.
+    ///
     /// * `key` - Key to use for the hash function
     async fn set_key(&mut self, key: Vec<u8>) -> Result<(), UniversalHashError>;
 
@@ -0,0 +1,290 @@
+//! The prime field used by Poly1305.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use mpz_share_conversion_core::fields::{Field, UniformRand};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+const MASK_44: u128 = (1 << 44) - 1;
+const MASK_42: u128 = (1 << 42) - 1;
+
+/// A field element of the prime field with modulus `2^130 - 5`, as used by Poly1305.
+///
+/// The element is always kept in its canonical form, i.e. in the range `[0, 2^130 - 5)`.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct P1305 {
+    /// The upper 2 bits.
+    hi: u8,
+    /// The lower 128 bits.
+    lo: u128,
+}
+
+impl P1305 {
+    /// The field modulus `2^130 - 5`.
+    const MODULUS: Self = Self {
+        hi: 3,
+        lo: u128::MAX - 4,
+    };
+
+    /// Creates a new field element from the lower 128 bits and the upper 2 bits.
+    ///
+    /// The value is reduced modulo `2^130 - 5`.
+    pub fn new(lo: u128, hi: u8) -> Self {
+        let (h0, h1, h2) = (
+            lo & MASK_44,
+            (lo >> 44) & MASK_44,
+            (lo >> 88) | ((hi as u128) << 40),
+        );
+
+        Self::from_limbs([h0, h1, h2])
+    }
+
+    /// Creates a new field element from up to 17 little-endian bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than 17 bytes are provided.
+    pub fn from_le_bytes(bytes: &[u8]) -> Self {
+        assert!(bytes.len() <= 17, "field element is at most 17 bytes");
+
+        let mut lo = [0u8; 16];
+        let len = bytes.len().min(16);
+        lo[..len].copy_from_slice(&bytes[..len]);
+
+        Self::new(u128::from_le_bytes(lo), bytes.get(16).copied().unwrap_or(0))
+    }
+
+    /// Returns the lower 128 bits of the element.
+    pub fn low_u128(&self) -> u128 {
+        self.lo
+    }
+
+    /// Splits the element into 3 limbs of 44, 44 and 42 bits.
+    fn limbs(&self) -> [u128; 3] {
+        [
+            self.lo & MASK_44,
+            (self.lo >> 44) & MASK_44,
+            (self.lo >> 88) | ((self.hi as u128) << 40),
+        ]
+    }
+
+    /// Reduces unnormalized limbs into a canonical field element.
+    fn from_limbs(mut h: [u128; 3]) -> Self {
+        // Each round propagates the carries, folding everything above 2^130 back
+        // into the lowest limb, as 2^130 = 5 (mod p).
+        for _ in 0..3 {
+            let c = h[0] >> 44;
+            h[0] &= MASK_44;
+            h[1] += c;
+            let c = h[1] >> 44;
+            h[1] &= MASK_44;
+            h[2] += c;
+            let c = h[2] >> 42;
+            h[2] &= MASK_42;
+            h[0] += c * 5;
+        }
+
+        let lo = h[0] | (h[1] << 44) | ((h[2] & ((1 << 40) - 1)) << 88);
+        let hi = (h[2] >> 40) as u8;
+
+        // The value is now below 2^130, so at most a single subtraction of the modulus is needed.
+        if hi == Self::MODULUS.hi && lo >= Self::MODULUS.lo {
+            Self {
+                hi: 0,
+                lo: lo.wrapping_add(5),
+            }
+        } else {
+            Self { hi, lo }
+        }
+    }
+
+    /// Raises the element to the power of `exp`, where `exp` is given as 130-bit
+    /// little-endian `(lo, hi)`.
+    fn pow(self, exp_lo: u128, exp_hi: u8) -> Self {
+        let mut result = Self::one();
+        for i in (0..130).rev() {
+            result = result * result;
+            let bit = if i < 128 {
+                (exp_lo >> i) & 1 == 1
+            } else {
+                (exp_hi >> (i - 128)) & 1 == 1
+            };
+            if bit {
+                result = result * self;
+            }
+        }
+        result
+    }
+}
+
+impl Add for P1305 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let (a, b) = (self.limbs(), rhs.limbs());
+
+        Self::from_limbs([a[0] + b[0], a[1] + b[1], a[2] + b[2]])
+    }
+}
+
+impl Sub for P1305 {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Neg for P1305 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        if self == Self::zero() {
+            return self;
+        }
+
+        let (lo, borrow) = Self::MODULUS.lo.overflowing_sub(self.lo);
+        let hi = Self::MODULUS.hi - self.hi - borrow as u8;
+
+        Self { hi, lo }
+    }
+}
+
+impl Mul for P1305 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let ([a0, a1, a2], [b0, b1, b2]) = (self.limbs(), rhs.limbs());
+
+        // Terms at 2^132 and above are folded using 2^132 = 20 (mod p).
+        let d0 = a0 * b0 + 20 * (a1 * b2 + a2 * b1);
+        let d1 = a0 * b1 + a1 * b0 + 20 * (a2 * b2);
+        let d2 = a0 * b2 + a1 * b1 + a2 * b0;
+
+        Self::from_limbs([d0, d1, d2])
+    }
+}
+
+impl UniformRand for P1305 {
+    fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        loop {
+            let lo: u128 = rng.gen();
+            let hi: u8 = rng.gen::<u8>() & 3;
+
+            let candidate = Self { hi, lo };
+            if candidate < Self::MODULUS {
+                return candidate;
+            }
+        }
+    }
+}
+
+impl Field for P1305 {
+    const BIT_SIZE: u32 = 130;
+
+    fn zero() -> Self {
+        Self { hi: 0, lo: 0 }
+    }
+
+    fn one() -> Self {
+        Self { hi: 0, lo: 1 }
+    }
+
+    fn two_pow(rhs: u32) -> Self {
+        match rhs {
+            0..=127 => Self {
+                hi: 0,
+                lo: 1 << rhs,
+            },
+            128..=129 => Self {
+                hi: 1 << (rhs - 128),
+                lo: 0,
+            },
+            _ => Self::two_pow(129) * Self::two_pow(rhs - 129),
+        }
+    }
+
+    fn get_bit(&self, n: usize) -> bool {
+        match n {
+            0..=127 => (self.lo >> n) & 1 == 1,
+            128..=129 => (self.hi >> (n - 128)) & 1 == 1,
+            _ => false,
+        }
+    }
+
+    fn inverse(self) -> Self {
+        assert!(self != Self::zero(), "zero has no inverse");
+
+        // Fermat's little theorem: a^(p-2) = a^-1
+        self.pow(Self::MODULUS.lo - 2, Self::MODULUS.hi)
+    }
+
+    fn to_le_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.lo.to_le_bytes().to_vec();
+        bytes.push(self.hi);
+        bytes
+    }
+
+    fn to_be_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.to_le_bytes();
+        bytes.reverse();
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn test_p1305_reduction() {
+        // 2^130 = 5 (mod p)
+        assert_eq!(P1305::two_pow(130), P1305::new(5, 0));
+        assert_eq!(P1305::new(u128::MAX - 4, 3), P1305::zero());
+        assert_eq!(P1305::new(u128::MAX - 3, 3), P1305::one());
+    }
+
+    #[test]
+    fn test_p1305_arithmetic() {
+        let mut rng = ChaCha12Rng::from_seed([0; 32]);
+
+        for _ in 0..32 {
+            let a = P1305::rand(&mut rng);
+            let b = P1305::rand(&mut rng);
+            let c = P1305::rand(&mut rng);
+
+            assert_eq!(a + -a, P1305::zero());
+            assert_eq!((a + b) - b, a);
+            assert_eq!(a * (b + c), a * b + a * c);
+            assert_eq!(a * a.inverse(), P1305::one());
+        }
+    }
+
+    #[test]
+    fn test_p1305_mul_small() {
+        let a = P1305::new(u64::MAX as u128, 0);
+        let b = P1305::new(3, 0);
+
+        assert_eq!(a * b, P1305::new(u64::MAX as u128 * 3, 0));
+        // (p - 1)^2 = 1
+        assert_eq!(-P1305::one() * -P1305::one(), P1305::one());
+    }
+
+    #[test]
+    fn test_p1305_bytes() {
+        let a = P1305::new(0x0102030405060708090a0b0c0d0e0f10, 2);
+        let bytes = a.to_le_bytes();
+
+        assert_eq!(bytes.len(), 17);
+        assert_eq!(P1305::from_le_bytes(&bytes), a);
+        assert!(a.get_bit(129));
+        assert!(!a.get_bit(128));
+    }
+}
@@ -0,0 +1,10 @@
+mod field;
+mod poly1305_core;
+mod poly1305_inner;
+
+pub use field::P1305;
+#[cfg(feature = "mock")]
+pub use poly1305_inner::mock::*;
+pub use poly1305_inner::{
+    Poly1305, Poly1305Config, Poly1305ConfigBuilder, Poly1305ConfigBuilderError,
+};
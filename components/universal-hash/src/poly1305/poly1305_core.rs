@@ -0,0 +1,234 @@
+//! The core logic of the 2PC Poly1305 implementation.
+//!
+//! Both parties start with XOR shares of the Poly1305 key `r`. After clamping, which is
+//! compatible with XOR sharing, each bit of `r` is converted into an additive share over the
+//! prime field `2^130 - 5` using `a ⊕ b = a + b - 2ab`, where the product `ab` is computed with a
+//! multiplicative-to-additive (M2A) conversion. The additive share of `r` is then converted into a
+//! multiplicative share (A2M), which allows computing the multiplicative shares of all powers of
+//! `r` locally. Finally, these are converted back into additive shares (M2A), from which each party
+//! can locally compute an additive share of the polynomial evaluation for any message.
+//!
+//! Contrary to GHASH, squaring is not free in a prime field, so every power of `r` requires
+//! its own M2A conversion.
+
+use mpz_share_conversion_core::fields::Field;
+use thiserror::Error;
+
+use super::field::P1305;
+
+/// The clamping mask applied to `r`, see RFC 8439, Section 2.5.
+const R_CLAMP: u128 = 0x0ffffffc_0ffffffc_0ffffffc_0fffffff;
+
+#[derive(Debug, Error)]
+pub(crate) enum Poly1305Error {
+    #[error("Message too long")]
+    InvalidMessageLength,
+}
+
+/// The core logic for our 2PC Poly1305 implementation.
+#[derive(Debug)]
+pub(crate) struct Poly1305Core {
+    /// Multiplicative share of `r`
+    mul_share: P1305,
+    /// Additive shares of the powers of `r`, where the n-th index holds the share of `r^(n+1)`.
+    add_shares: Vec<P1305>,
+    /// Maximum number of message blocks we want to authenticate
+    max_block_count: usize,
+}
+
+impl Poly1305Core {
+    /// Creates a new `Poly1305Core` from a multiplicative share of `r`.
+    ///
+    /// * `mul_share`       - Multiplicative share of `r`.
+    /// * `max_block_count` - The maximum number of 128-bit message blocks we want to authenticate.
+    pub(crate) fn new(mul_share: P1305, max_block_count: usize) -> Self {
+        Self {
+            mul_share,
+            add_shares: Vec::new(),
+            max_block_count,
+        }
+    }
+
+    /// Returns the number of blocks for which additive shares are available.
+    pub(crate) fn block_count(&self) -> usize {
+        self.add_shares.len()
+    }
+
+    /// Returns the multiplicative shares of the powers of `r` which are missing to
+    /// authenticate `block_count` blocks.
+    pub(crate) fn missing_mul_shares(&self, block_count: usize) -> Vec<P1305> {
+        let present = self.add_shares.len();
+        if block_count <= present {
+            return Vec::new();
+        }
+
+        let mut power = pow(self.mul_share, present);
+        (present..block_count)
+            .map(|_| {
+                power = power * self.mul_share;
+                power
+            })
+            .collect()
+    }
+
+    /// Adds additive shares of the next powers of `r`.
+    pub(crate) fn add_new_add_shares(&mut self, new_add_shares: &[P1305]) {
+        self.add_shares.extend_from_slice(new_add_shares);
+    }
+
+    /// Computes an additive share of the Poly1305 accumulator, before the addition of `s`.
+    ///
+    /// * `message` - The encoded message blocks, see [`encode_blocks`].
+    pub(crate) fn finalize(&self, message: &[P1305]) -> Result<P1305, Poly1305Error> {
+        if message.len() > self.max_block_count || message.len() > self.add_shares.len() {
+            return Err(Poly1305Error::InvalidMessageLength);
+        }
+
+        // acc = m_1 * r^q + m_2 * r^(q-1) + ... + m_q * r
+        let output = message
+            .iter()
+            .zip(self.add_shares[..message.len()].iter().rev())
+            .fold(P1305::zero(), |acc, (block, share)| acc + *block * *share);
+
+        Ok(output)
+    }
+}
+
+/// Returns the bits of the clamped XOR share of `r` which are not fixed to zero by clamping.
+///
+/// The bits are returned as field elements in little-endian bit order.
+pub(crate) fn clamped_key_bits(key_share: [u8; 16]) -> Vec<P1305> {
+    let r = u128::from_le_bytes(key_share) & R_CLAMP;
+
+    (0..128)
+        .filter(|i| (R_CLAMP >> i) & 1 == 1)
+        .map(|i| P1305::new((r >> i) & 1, 0))
+        .collect()
+}
+
+/// Converts the XOR share of `r` into an additive share.
+///
+/// * `bits`     - The bits of the share, as returned by [`clamped_key_bits`].
+/// * `products` - Additive shares of the products of both parties' bits.
+pub(crate) fn xor_to_additive(bits: &[P1305], products: &[P1305]) -> P1305 {
+    let two = P1305::new(2, 0);
+
+    (0..128)
+        .filter(|i| (R_CLAMP >> i) & 1 == 1)
+        .zip(bits.iter().zip(products))
+        .fold(P1305::zero(), |acc, (i, (bit, product))| {
+            acc + P1305::two_pow(i) * (*bit - two * *product)
+        })
+}
+
+/// Encodes a message into Poly1305 blocks, as specified in RFC 8439, Section 2.5.1.
+///
+/// Each 16-byte chunk is read as a little-endian number with an additional `0x01` byte
+/// appended, which also applies to the last, possibly shorter, chunk.
+pub(crate) fn encode_blocks(message: &[u8]) -> Vec<P1305> {
+    message
+        .chunks(16)
+        .map(|chunk| {
+            let mut block = [0u8; 17];
+            block[..chunk.len()].copy_from_slice(chunk);
+            block[chunk.len()] = 1;
+            P1305::from_le_bytes(&block)
+        })
+        .collect()
+}
+
+fn pow(base: P1305, exp: usize) -> P1305 {
+    (0..exp).fold(P1305::one(), |acc, _| acc * base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mpz_share_conversion_core::fields::UniformRand;
+    use poly1305_rc::{universal_hash::KeyInit, Poly1305};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha12Rng;
+
+    fn reference_accumulator(r: [u8; 16], message: &[u8]) -> [u8; 16] {
+        let mut key = [0u8; 32];
+        key[..16].copy_from_slice(&r);
+
+        // With `s = 0` the tag is the accumulator reduced modulo 2^128.
+        let tag = Poly1305::new(&key.into()).compute_unpadded(message);
+
+        let mut acc = [0u8; 16];
+        acc.copy_from_slice(&tag);
+        acc
+    }
+
+    #[test]
+    fn test_xor_to_additive() {
+        let mut rng = ChaCha12Rng::from_seed([0; 32]);
+        let a: [u8; 16] = rng.gen();
+        let b: [u8; 16] = rng.gen();
+
+        let (a_bits, b_bits) = (clamped_key_bits(a), clamped_key_bits(b));
+
+        // Split the products of the bits into additive shares.
+        let (a_products, b_products): (Vec<_>, Vec<_>) = a_bits
+            .iter()
+            .zip(&b_bits)
+            .map(|(a, b)| {
+                let mask = P1305::rand(&mut rng);
+                (mask, *a * *b - mask)
+            })
+            .unzip();
+
+        let r = xor_to_additive(&a_bits, &a_products) + xor_to_additive(&b_bits, &b_products);
+
+        let expected = (u128::from_le_bytes(a) ^ u128::from_le_bytes(b)) & R_CLAMP;
+
+        assert_eq!(r, P1305::new(expected, 0));
+    }
+
+    #[test]
+    fn test_poly1305_core_finalize() {
+        let mut rng = ChaCha12Rng::from_seed([0; 32]);
+        let r_bytes: [u8; 16] = rng.gen();
+        let r = P1305::new(u128::from_le_bytes(r_bytes) & R_CLAMP, 0);
+        let message: Vec<u8> = (0..87).map(|_| rng.gen()).collect();
+        let blocks = encode_blocks(&message);
+
+        // Split r into multiplicative shares.
+        let mask = P1305::rand(&mut rng);
+        let mut sender = Poly1305Core::new(mask, 8);
+        let mut receiver = Poly1305Core::new(r * mask.inverse(), 8);
+
+        // Split the powers of r into additive shares.
+        let (sender_add, receiver_add): (Vec<_>, Vec<_>) = sender
+            .missing_mul_shares(blocks.len())
+            .into_iter()
+            .zip(receiver.missing_mul_shares(blocks.len()))
+            .map(|(a, b)| {
+                let mask = P1305::rand(&mut rng);
+                (mask, a * b - mask)
+            })
+            .unzip();
+
+        sender.add_new_add_shares(&sender_add);
+        receiver.add_new_add_shares(&receiver_add);
+
+        let acc = sender.finalize(&blocks).unwrap() + receiver.finalize(&blocks).unwrap();
+
+        assert_eq!(
+            acc.low_u128().to_le_bytes(),
+            reference_accumulator(r_bytes, &message)
+        );
+    }
+
+    #[test]
+    fn test_poly1305_core_message_too_long() {
+        let core = Poly1305Core::new(P1305::one(), 1);
+
+        assert!(matches!(
+            core.finalize(&encode_blocks(&[0u8; 32])),
+            Err(Poly1305Error::InvalidMessageLength)
+        ));
+    }
+}
@@ -0,0 +1,19 @@
+use derive_builder::Builder;
+
+#[derive(Debug, Clone, Builder)]
+/// Configuration struct for [Poly1305](crate::poly1305::Poly1305)
+pub struct Poly1305Config {
+    /// The instance ID
+    #[builder(setter(into))]
+    pub id: String,
+    /// Maximum number of blocks supported
+    #[builder(default = "1026")]
+    pub max_block_count: usize,
+}
+
+impl Poly1305Config {
+    /// Creates a new builder for the [Poly1305Config]
+    pub fn builder() -> Poly1305ConfigBuilder {
+        Poly1305ConfigBuilder::default()
+    }
+}
@@ -0,0 +1,36 @@
+use mpz_share_conversion::{
+    mock::{mock_converter_pair, MockConverterReceiver, MockConverterSender},
+    ReceiverConfig, SenderConfig,
+};
+
+use super::{Poly1305, Poly1305Config};
+use crate::poly1305::P1305;
+
+/// Create a Poly1305 sender/receiver pair for testing purpose
+pub fn mock_poly1305_pair(
+    sender_config: Poly1305Config,
+    receiver_config: Poly1305Config,
+) -> (
+    Poly1305<MockConverterSender<P1305>>,
+    Poly1305<MockConverterReceiver<P1305>>,
+) {
+    let (sender, receiver) = mock_converter_pair::<P1305>(
+        SenderConfig::builder()
+            .id(format!("{}/converter", sender_config.id))
+            .record()
+            .build()
+            .unwrap(),
+        ReceiverConfig::builder()
+            .id(format!("{}/converter", receiver_config.id))
+            .record()
+            .build()
+            .unwrap(),
+    );
+
+    let (sender, receiver) = (
+        Poly1305::new(sender_config, sender),
+        Poly1305::new(receiver_config, receiver),
+    );
+
+    (sender, receiver)
+}
@@ -0,0 +1,232 @@
+use crate::{
+    poly1305::{
+        field::P1305,
+        poly1305_core::{clamped_key_bits, encode_blocks, xor_to_additive, Poly1305Core},
+    },
+    UniversalHash, UniversalHashError,
+};
+use async_trait::async_trait;
+use mpz_share_conversion::ShareConversion;
+use std::fmt::Debug;
+
+mod config;
+#[cfg(feature = "mock")]
+pub(crate) mod mock;
+
+pub use config::{Poly1305Config, Poly1305ConfigBuilder, Poly1305ConfigBuilderError};
+
+#[derive(Debug)]
+enum State {
+    Init,
+    Ready { core: Poly1305Core },
+    Error,
+}
+
+/// This is the common instance used by both sender and receiver
+///
+/// It is an aio wrapper which mostly uses [Poly1305Core] for computation.
+///
+/// The key is the XOR share of `r` and the output is an additive share of the Poly1305
+/// accumulator over the prime field `2^130 - 5`, encoded as 17 little-endian bytes. Adding `s`
+/// to obtain the tag is left to the caller, as it is a reduction modulo `2^128`.
+///
+/// Poly1305 keys are single-use, so a new key is expected to be set for every message.
+#[derive(Debug)]
+pub struct Poly1305<C> {
+    state: State,
+    config: Poly1305Config,
+    converter: C,
+}
+
+impl<C> Poly1305<C>
+where
+    C: ShareConversion<P1305> + Send + Sync + Debug,
+{
+    /// Creates a new instance
+    ///
+    /// * `config`      - The configuration for this Poly1305 instance
+    /// * `converter`   - An instance which allows to convert multiplicative into additive shares
+    ///                   and vice versa
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", ret))]
+    pub fn new(config: Poly1305Config, converter: C) -> Self {
+        Self {
+            state: State::Init,
+            config,
+            converter,
+        }
+    }
+
+    /// Computes the additive shares of the powers of `r` which are missing to authenticate
+    /// `block_count` blocks.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+    async fn compute_add_shares(
+        &mut self,
+        mut core: Poly1305Core,
+        block_count: usize,
+    ) -> Result<Poly1305Core, UniversalHashError> {
+        let mul_shares = core.missing_mul_shares(block_count);
+
+        if !mul_shares.is_empty() {
+            let add_shares = self.converter.to_additive(mul_shares).await?;
+            core.add_new_add_shares(&add_shares);
+        }
+
+        Ok(core)
+    }
+}
+
+#[async_trait]
+impl<C> UniversalHash for Poly1305<C>
+where
+    C: ShareConversion<P1305> + Send + Sync + Debug,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", skip(key), err)
+    )]
+    async fn set_key(&mut self, key: Vec<u8>) -> Result<(), UniversalHashError> {
+        let key: [u8; 16] = key
+            .try_into()
+            .map_err(|key: Vec<u8>| UniversalHashError::KeyLengthError(16, key.len()))?;
+
+        if matches!(&self.state, State::Error) {
+            return Err(UniversalHashError::InvalidState(
+                "Poly1305 is in an error state".to_string(),
+            ));
+        }
+
+        // Convert the XOR share of r into an additive share, bit by bit.
+        let bits = clamped_key_bits(key);
+        let products = self.converter.to_additive(bits.clone()).await?;
+        let r_additive = xor_to_additive(&bits, &products);
+
+        let r_multiplicative = self.converter.to_multiplicative(vec![r_additive]).await?;
+
+        self.state = State::Ready {
+            core: Poly1305Core::new(r_multiplicative[0], self.config.max_block_count),
+        };
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", skip_all, err)
+    )]
+    async fn finalize(&mut self, input: Vec<u8>) -> Result<Vec<u8>, UniversalHashError> {
+        let blocks = encode_blocks(&input);
+
+        if blocks.len() > self.config.max_block_count {
+            return Err(UniversalHashError::InputLengthError(input.len()));
+        }
+
+        let state = std::mem::replace(&mut self.state, State::Error);
+
+        // Calling finalize when not setup is a fatal error
+        let State::Ready { core } = state else {
+            return Err(UniversalHashError::InvalidState("Key not set".to_string()));
+        };
+
+        // Compute new shares if the block count increased
+        let core = if blocks.len() > core.block_count() {
+            self.compute_add_shares(core, blocks.len()).await?
+        } else {
+            core
+        };
+
+        let acc = core
+            .finalize(&blocks)
+            .expect("Input length should be valid");
+
+        // Reinsert state
+        self.state = State::Ready { core };
+
+        Ok(acc.to_le_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mock::mock_poly1305_pair, Poly1305Config, UniversalHash};
+    use crate::poly1305::P1305;
+    use poly1305_rc::{universal_hash::KeyInit, Poly1305 as Poly1305Reference};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha12Rng;
+
+    fn create_pair(id: &str) -> (impl UniversalHash, impl UniversalHash) {
+        let config = Poly1305Config::builder().id(id).build().unwrap();
+
+        mock_poly1305_pair(config.clone(), config)
+    }
+
+    async fn compute_accumulator(
+        sender: &mut impl UniversalHash,
+        receiver: &mut impl UniversalHash,
+        r: [u8; 16],
+        message: &[u8],
+        rng: &mut ChaCha12Rng,
+    ) -> [u8; 16] {
+        let sender_key: [u8; 16] = rng.gen();
+        let receiver_key: Vec<u8> = r.iter().zip(sender_key).map(|(r, k)| r ^ k).collect();
+
+        let (sender_result, receiver_result) = tokio::join!(
+            sender.set_key(sender_key.to_vec()),
+            receiver.set_key(receiver_key)
+        );
+        sender_result.unwrap();
+        receiver_result.unwrap();
+
+        let (sender_share, receiver_share) = tokio::join!(
+            sender.finalize(message.to_vec()),
+            receiver.finalize(message.to_vec())
+        );
+
+        let acc = P1305::from_le_bytes(&sender_share.unwrap())
+            + P1305::from_le_bytes(&receiver_share.unwrap());
+
+        acc.low_u128().to_le_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_poly1305_output() {
+        let mut rng = ChaCha12Rng::from_seed([0; 32]);
+        let r: [u8; 16] = rng.gen();
+
+        // Message length is not a multiple of the block length
+        let message: Vec<u8> = (0..126).map(|_| rng.gen()).collect();
+
+        let (mut sender, mut receiver) = create_pair("test");
+
+        let acc = compute_accumulator(&mut sender, &mut receiver, r, &message, &mut rng).await;
+
+        assert_eq!(acc, poly1305_reference_impl(r, &message));
+    }
+
+    #[tokio::test]
+    async fn test_poly1305_rekey() {
+        let mut rng = ChaCha12Rng::from_seed([0; 32]);
+        let message: Vec<u8> = (0..128).map(|_| rng.gen()).collect();
+
+        let (mut sender, mut receiver) = create_pair("test");
+
+        for _ in 0..2 {
+            let r: [u8; 16] = rng.gen();
+
+            let acc = compute_accumulator(&mut sender, &mut receiver, r, &message, &mut rng).await;
+
+            assert_eq!(acc, poly1305_reference_impl(r, &message));
+        }
+    }
+
+    fn poly1305_reference_impl(r: [u8; 16], message: &[u8]) -> [u8; 16] {
+        let mut key = [0u8; 32];
+        key[..16].copy_from_slice(&r);
+
+        // With `s = 0` the tag is the accumulator reduced modulo 2^128.
+        let tag = Poly1305Reference::new(&key.into()).compute_unpadded(message);
+
+        let mut acc = [0u8; 16];
+        acc.copy_from_slice(&tag);
+        acc
+    }
+}
@@ -0,0 +1,519 @@
+use eyre::{ensure, eyre, Result};
+use hyper::{client::HttpConnector, Client};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use instant_acme::{
+    Account, AccountCredentials, AuthorizationStatus, ChallengeType, Identifier, NewAccount,
+    NewOrder, Order, OrderStatus,
+};
+use rcgen::{CertificateParams, CustomExtension, DistinguishedName};
+use rustls::{
+    sign::{any_supported_type, CertifiedKey},
+    Certificate, ClientConfig, PrivateKey, RootCertStore,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+use crate::{
+    config::AcmeProperties,
+    tls::{parse_certified_key, CertificateResolver},
+};
+
+/// Time to wait before retrying after obtaining a certificate failed, which doubles with each
+/// consecutive failure
+const MIN_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+/// Maximum time to wait before retrying after obtaining a certificate failed
+const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Interval at which a pending order is polled
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Number of times a pending order is polled before giving up
+const MAX_POLL_ATTEMPTS: u32 = 30;
+
+/// Spawn a background task which obtains the TLS certificate from the ACME CA with the
+/// TLS-ALPN-01 challenge, and renews it [`AcmeProperties::renew_before_days`] before it expires
+///
+/// The certificate is cached in [`AcmeProperties::cache_dir`], so that it is reused across
+/// restarts.
+pub fn spawn_acme_task(
+    config: AcmeProperties,
+    resolver: Arc<CertificateResolver>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let cache = AcmeCache::new(&config.cache_dir);
+        let mut retry_interval = MIN_RETRY_INTERVAL;
+        loop {
+            let next_renewal = match renew_certificate(&config, &cache, &resolver).await {
+                Ok(next_renewal) => {
+                    retry_interval = MIN_RETRY_INTERVAL;
+                    next_renewal
+                }
+                Err(err) => {
+                    error!(
+                        ?retry_interval,
+                        "Failed to obtain tls certificate via ACME: {err}"
+                    );
+                    let next_attempt = retry_interval;
+                    retry_interval = (retry_interval * 2).min(MAX_RETRY_INTERVAL);
+                    next_attempt
+                }
+            };
+            debug!(?next_renewal, "Scheduled the next tls certificate renewal");
+            tokio::time::sleep(next_renewal).await;
+        }
+    })
+}
+
+/// Obtain a new certificate unless the cached one is not due for renewal yet, returns the time
+/// until the next renewal
+async fn renew_certificate(
+    config: &AcmeProperties,
+    cache: &AcmeCache,
+    resolver: &CertificateResolver,
+) -> Result<Duration> {
+    let renew_before = Duration::from_secs(config.renew_before_days * 24 * 60 * 60);
+    if let Some(cached) = cache.load_certificate(&config.domains)? {
+        if !resolver.has_certificate() {
+            resolver.set_certificate(parse_certified_key(
+                &cached.private_key_pem,
+                &cached.certificate_pem,
+            )?);
+            info!("Loaded cached tls certificate");
+        }
+        if let Some(until_renewal) = time_until_renewal(cached.not_after, renew_before) {
+            return Ok(until_renewal);
+        }
+    }
+
+    let account = load_account(config, cache).await?;
+    let (private_key_pem, certificate_pem) =
+        order_certificate(&account, &config.domains, resolver).await?;
+    let not_after = certificate_not_after(certificate_pem.as_bytes())?;
+
+    resolver.set_certificate(parse_certified_key(
+        private_key_pem.as_bytes(),
+        certificate_pem.as_bytes(),
+    )?);
+    cache.store_certificate(&config.domains, &private_key_pem, &certificate_pem)?;
+    info!(domains = ?config.domains, "Obtained tls certificate via ACME");
+
+    // A certificate which is valid for less than `renew_before` is renewed at the slowest rate of
+    // retries, instead of right away
+    Ok(time_until_renewal(not_after, renew_before).unwrap_or(MAX_RETRY_INTERVAL))
+}
+
+/// Time until a certificate expiring at `not_after` is due for renewal, or `None` if it is due
+/// already
+fn time_until_renewal(not_after: SystemTime, renew_before: Duration) -> Option<Duration> {
+    not_after
+        .checked_sub(renew_before)?
+        .duration_since(SystemTime::now())
+        .ok()
+}
+
+/// Expiry of the leaf certificate of a PEM-formatted certificate chain
+fn certificate_not_after(certificate_pem: &[u8]) -> Result<SystemTime> {
+    let (_, pem) = x509_parser::pem::parse_x509_pem(certificate_pem)
+        .map_err(|err| eyre!("Failed to parse tls certificate pem: {err}"))?;
+    let certificate = pem
+        .parse_x509()
+        .map_err(|err| eyre!("Failed to parse tls certificate: {err}"))?;
+    let not_after = u64::try_from(certificate.validity().not_after.timestamp())
+        .map_err(|_| eyre!("Tls certificate expired before 1970"))?;
+    Ok(UNIX_EPOCH + Duration::from_secs(not_after))
+}
+
+/// Load the ACME account from the cache, or register one if there is none yet
+async fn load_account(config: &AcmeProperties, cache: &AcmeCache) -> Result<Account> {
+    let http = Box::new(http_client(config)?);
+    if let Some(credentials) = cache.account_credentials()? {
+        return Ok(Account::from_credentials_and_http(credentials, http).await?);
+    }
+
+    let contact: Vec<&str> = config.contact.iter().map(String::as_str).collect();
+    let (account, credentials) = Account::create_with_http(
+        &NewAccount {
+            contact: &contact,
+            terms_of_service_agreed: true,
+            only_return_existing: false,
+        },
+        &config.directory_url,
+        None,
+        http,
+    )
+    .await?;
+    cache.store_account_credentials(&credentials)?;
+    info!(account = account.id(), "Registered ACME account");
+    Ok(account)
+}
+
+/// Client of the ACME CA, which trusts [`AcmeProperties::directory_ca_pem_path`] if set, or the
+/// system roots otherwise
+fn http_client(config: &AcmeProperties) -> Result<Client<HttpsConnector<HttpConnector>>> {
+    let connector = match &config.directory_ca_pem_path {
+        Some(path) => {
+            let mut root_store = RootCertStore::empty();
+            for certificate in rustls_pemfile::certs(&mut &*std::fs::read(path)?)? {
+                root_store
+                    .add(&Certificate(certificate))
+                    .map_err(|err| eyre!("Failed to add ACME directory CA: {err}"))?;
+            }
+            HttpsConnectorBuilder::new().with_tls_config(
+                ClientConfig::builder()
+                    .with_safe_defaults()
+                    .with_root_certificates(root_store)
+                    .with_no_client_auth(),
+            )
+        }
+        None => HttpsConnectorBuilder::new().with_native_roots(),
+    }
+    .https_only()
+    .enable_http1()
+    .build();
+    Ok(Client::builder().build(connector))
+}
+
+/// Order a certificate for `domains`, presenting the challenge certificates via `resolver`,
+/// returns its PEM-formatted private key and certificate chain
+async fn order_certificate(
+    account: &Account,
+    domains: &[String],
+    resolver: &CertificateResolver,
+) -> Result<(String, String)> {
+    let identifiers: Vec<_> = domains.iter().cloned().map(Identifier::Dns).collect();
+    let mut order = account
+        .new_order(&NewOrder {
+            identifiers: &identifiers,
+        })
+        .await?;
+
+    let validated = validate(&mut order, resolver).await;
+    // The challenge certificates are not needed anymore, whether the validation succeeded
+    resolver.clear_challenge_certificates();
+    validated?;
+
+    let (csr, private_key_pem) = certificate_request(domains)?;
+    order.finalize(&csr).await?;
+    for _ in 0..MAX_POLL_ATTEMPTS {
+        if let Some(certificate_pem) = order.certificate().await? {
+            return Ok((private_key_pem, certificate_pem));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Err(eyre!("ACME CA did not issue the certificate in time"))
+}
+
+/// Respond to the TLS-ALPN-01 challenges of the order, and wait until it is ready to be
+/// finalized
+async fn validate(order: &mut Order, resolver: &CertificateResolver) -> Result<()> {
+    for authorization in order.authorizations().await? {
+        if authorization.status == AuthorizationStatus::Valid {
+            continue;
+        }
+
+        let Identifier::Dns(domain) = &authorization.identifier;
+        let challenge = authorization
+            .challenges
+            .iter()
+            .find(|challenge| challenge.r#type == ChallengeType::TlsAlpn01)
+            .ok_or_else(|| eyre!("ACME CA did not offer a tls-alpn-01 challenge for {domain}"))?;
+        let key_authorization = order.key_authorization(challenge);
+        resolver.set_challenge_certificate(
+            domain,
+            challenge_certificate(domain, key_authorization.digest().as_ref())?,
+        );
+
+        debug!(domain, "Responding to ACME tls-alpn-01 challenge");
+        order.set_challenge_ready(&challenge.url).await?;
+    }
+
+    for _ in 0..MAX_POLL_ATTEMPTS {
+        let state = order.refresh().await?;
+        if state.status == OrderStatus::Ready {
+            return Ok(());
+        }
+        ensure!(
+            state.status != OrderStatus::Invalid,
+            "ACME order is invalid: {:?}",
+            state.error
+        );
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Err(eyre!("ACME order did not become ready in time"))
+}
+
+/// Self-signed certificate of the TLS-ALPN-01 challenge of `domain`, which carries the SHA-256
+/// digest of the key authorization in the acmeIdentifier extension (RFC 8737 section 3)
+fn challenge_certificate(domain: &str, key_authorization_digest: &[u8]) -> Result<CertifiedKey> {
+    let mut params = CertificateParams::new(vec![domain.to_string()]);
+    params.custom_extensions = vec![CustomExtension::new_acme_identifier(
+        key_authorization_digest,
+    )];
+    let certificate = rcgen::Certificate::from_params(params)?;
+
+    let private_key = any_supported_type(&PrivateKey(certificate.serialize_private_key_der()))
+        .map_err(|err| eyre!("Unsupported challenge private key: {err}"))?;
+    Ok(CertifiedKey::new(
+        vec![Certificate(certificate.serialize_der()?)],
+        private_key,
+    ))
+}
+
+/// Generate the private key of the certificate of `domains`, returns the DER-encoded certificate
+/// signing request and the PEM-formatted private key
+fn certificate_request(domains: &[String]) -> Result<(Vec<u8>, String)> {
+    let mut params = CertificateParams::new(domains.to_vec());
+    params.distinguished_name = DistinguishedName::new();
+    let certificate = rcgen::Certificate::from_params(params)?;
+    Ok((
+        certificate.serialize_request_der()?,
+        certificate.serialize_private_key_pem(),
+    ))
+}
+
+/// Directory where the ACME account credentials and the certificate are persisted
+#[derive(Debug)]
+struct AcmeCache {
+    dir: PathBuf,
+}
+
+/// Certificate read from the [`AcmeCache`]
+#[derive(Debug)]
+struct CachedCertificate {
+    private_key_pem: Vec<u8>,
+    certificate_pem: Vec<u8>,
+    not_after: SystemTime,
+}
+
+impl AcmeCache {
+    fn new(dir: &str) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+        }
+    }
+
+    /// Load the credentials of the ACME account, if it is registered already
+    fn account_credentials(&self) -> Result<Option<AccountCredentials>> {
+        let path = self.dir.join("account.json");
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let credentials = serde_json::from_slice(&std::fs::read(path)?)
+            .map_err(|err| eyre!("Failed to parse ACME account credentials: {err}"))?;
+        Ok(Some(credentials))
+    }
+
+    fn store_account_credentials(&self, credentials: &AccountCredentials) -> Result<()> {
+        self.write_private(
+            &self.dir.join("account.json"),
+            &serde_json::to_vec(credentials)?,
+        )
+    }
+
+    /// Load the cached certificate, unless it was issued for other domains
+    fn load_certificate(&self, domains: &[String]) -> Result<Option<CachedCertificate>> {
+        let certificate_path = self.dir.join("certificate.pem");
+        if !certificate_path.is_file() {
+            return Ok(None);
+        }
+        let cached_domains = std::fs::read_to_string(self.dir.join("domains")).unwrap_or_default();
+        if cached_domains != domains.join("\n") {
+            return Ok(None);
+        }
+
+        let certificate_pem = std::fs::read(certificate_path)?;
+        Ok(Some(CachedCertificate {
+            private_key_pem: std::fs::read(self.dir.join("certificate.key"))?,
+            not_after: certificate_not_after(&certificate_pem)?,
+            certificate_pem,
+        }))
+    }
+
+    fn store_certificate(
+        &self,
+        domains: &[String],
+        private_key_pem: &str,
+        certificate_pem: &str,
+    ) -> Result<()> {
+        self.write_private(
+            &self.dir.join("certificate.key"),
+            private_key_pem.as_bytes(),
+        )?;
+        std::fs::write(self.dir.join("domains"), domains.join("\n"))?;
+        std::fs::write(self.dir.join("certificate.pem"), certificate_pem)?;
+        Ok(())
+    }
+
+    /// Write a file which is only readable by the owner, as it contains a private key
+    fn write_private(&self, path: &Path, content: &[u8]) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(path, content)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hyper::body::to_bytes;
+    use rustls::{ServerConfig, ServerName};
+    use std::env;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+    use crate::tls::ACME_TLS_ALPN_PROTOCOL;
+
+    /// Self-signed certificate of `domain` which expires at `not_after`, returns its
+    /// PEM-formatted private key and certificate
+    fn self_signed_certificate(domain: &str, not_after: (i32, u8, u8)) -> (String, String) {
+        let mut params = CertificateParams::new(vec![domain.to_string()]);
+        params.not_after = rcgen::date_time_ymd(not_after.0, not_after.1, not_after.2);
+        let certificate = rcgen::Certificate::from_params(params).unwrap();
+        (
+            certificate.serialize_private_key_pem(),
+            certificate.serialize_pem().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_cache_certificate() {
+        let dir = env::temp_dir().join(format!("notary-acme-{}", uuid::Uuid::new_v4()));
+        let cache = AcmeCache::new(dir.to_str().unwrap());
+        let domains = vec!["notary.test".to_string()];
+        let (private_key_pem, certificate_pem) =
+            self_signed_certificate("notary.test", (2100, 1, 1));
+
+        assert!(cache.account_credentials().unwrap().is_none());
+        assert!(cache.load_certificate(&domains).unwrap().is_none());
+        cache
+            .store_certificate(&domains, &private_key_pem, &certificate_pem)
+            .unwrap();
+        let cached = cache.load_certificate(&domains).unwrap().unwrap();
+        assert_eq!(cached.private_key_pem, private_key_pem.as_bytes());
+        assert_eq!(cached.certificate_pem, certificate_pem.as_bytes());
+        // The expiry is read from the certificate, not from the file
+        assert_eq!(
+            cached.not_after,
+            UNIX_EPOCH + Duration::from_secs(4_102_444_800)
+        );
+        // A certificate of other domains is not reused
+        assert!(cache
+            .load_certificate(&["other.test".to_string()])
+            .unwrap()
+            .is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_time_until_renewal() {
+        let renew_before = Duration::from_secs(30 * 24 * 60 * 60);
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        let until_renewal =
+            time_until_renewal(SystemTime::now() + renew_before + day, renew_before).unwrap();
+        assert!(until_renewal <= day && until_renewal > day - Duration::from_secs(60));
+        // A certificate which is about to expire, or has expired, is due right away
+        assert!(time_until_renewal(SystemTime::now() + day, renew_before).is_none());
+        assert!(time_until_renewal(SystemTime::now() - day, renew_before).is_none());
+
+        let (_, certificate_pem) = self_signed_certificate("notary.test", (2000, 1, 1));
+        let not_after = certificate_not_after(certificate_pem.as_bytes()).unwrap();
+        assert!(time_until_renewal(not_after, renew_before).is_none());
+    }
+
+    /// Obtain a certificate from a local Pebble instance (https://github.com/letsencrypt/pebble),
+    /// e.g. started with `PEBBLE_VA_NOSLEEP=1 pebble -config test/config/pebble-config.json`, by
+    /// running `PEBBLE_CA_PEM_PATH=<pebble>/test/certs/pebble.minica.pem cargo test -- --ignored`
+    ///
+    /// `PEBBLE_DIRECTORY_URL` and `PEBBLE_TLS_PORT` (the port on which Pebble validates the
+    /// TLS-ALPN-01 challenges) can be set if Pebble is not run with its default config.
+    #[tokio::test]
+    #[ignore = "requires a running Pebble instance"]
+    async fn test_pebble() {
+        let directory_url = env::var("PEBBLE_DIRECTORY_URL")
+            .unwrap_or_else(|_| "https://localhost:14000/dir".to_string());
+        let tls_port: u16 = env::var("PEBBLE_TLS_PORT")
+            .map(|port| port.parse().unwrap())
+            .unwrap_or(5001);
+        let cache_dir = env::temp_dir().join(format!("notary-acme-{}", uuid::Uuid::new_v4()));
+        let config = AcmeProperties {
+            domains: vec!["localhost".to_string()],
+            contact: vec!["mailto:admin@localhost".to_string()],
+            directory_url,
+            directory_ca_pem_path: Some(env::var("PEBBLE_CA_PEM_PATH").unwrap()),
+            cache_dir: cache_dir.to_str().unwrap().to_string(),
+            renew_before_days: 30,
+        };
+
+        // Serve the challenge certificates to Pebble
+        let resolver = Arc::new(CertificateResolver::new());
+        let mut server_config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_cert_resolver(resolver.clone());
+        server_config.alpn_protocols = vec![b"http/1.1".to_vec(), ACME_TLS_ALPN_PROTOCOL.to_vec()];
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+        let listener = TcpListener::bind(("0.0.0.0", tls_port)).await.unwrap();
+        let server = tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let _ = acceptor.accept(stream).await;
+                });
+            }
+        });
+
+        let cache = AcmeCache::new(&config.cache_dir);
+        let next_renewal = renew_certificate(&config, &cache, &resolver).await.unwrap();
+        assert!(next_renewal > Duration::ZERO);
+        assert!(cache.account_credentials().unwrap().is_some());
+
+        // The obtained certificate is presented to provers, and is trusted by the Pebble CA
+        let mut root_store = RootCertStore::empty();
+        root_store.add(&pebble_root(&config).await).unwrap();
+        let mut client_config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(root_store)
+            .with_no_client_auth();
+        client_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        let stream = TcpStream::connect(("localhost", tls_port)).await.unwrap();
+        TlsConnector::from(Arc::new(client_config))
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+
+        // The cached certificate is reused after a restart
+        let resolver = CertificateResolver::new();
+        let next_renewal_after_restart =
+            renew_certificate(&config, &cache, &resolver).await.unwrap();
+        assert!(resolver.has_certificate());
+        assert!(next_renewal_after_restart <= next_renewal);
+
+        server.abort();
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    /// Root certificate which Pebble generates on startup to issue certificates, served by its
+    /// management interface
+    async fn pebble_root(config: &AcmeProperties) -> Certificate {
+        let management_url = env::var("PEBBLE_MANAGEMENT_URL")
+            .unwrap_or_else(|_| "https://localhost:15000".to_string());
+        let response = http_client(config)
+            .unwrap()
+            .get(format!("{management_url}/roots/0").parse().unwrap())
+            .await
+            .unwrap();
+        let pem = to_bytes(response.into_body()).await.unwrap();
+        Certificate(rustls_pemfile::certs(&mut &*pem).unwrap().remove(0))
+    }
+}
@@ -0,0 +1,311 @@
+use chrono::{DateTime, Utc};
+use eyre::{eyre, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+use tlsn_verifier::tls::DEFAULT_MAX_TRANSCRIPT_SIZE;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+use crate::{
+    domain::auth::{authorization_whitelist_vec_into_hashmap, AuthorizationWhitelistRecord},
+    util::parse_csv_file,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum QuotaError {
+    #[error("API key has reached its quota of {0} sessions per day")]
+    SessionsPerDayExceeded(u32),
+    #[error("Max transcript size requested exceeds the quota of {0} bytes of the API key")]
+    TranscriptSizeExceeded(usize),
+    #[error("Failed to access API key usage: {0}")]
+    Usage(String),
+}
+
+impl From<rusqlite::Error> for QuotaError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Usage(error.to_string())
+    }
+}
+
+/// Whitelist of API keys, which is reloaded when its csv file changes
+///
+/// The whitelist is swapped atomically on reload, so requests see either the old or the new
+/// whitelist. If the file can not be parsed, the previous whitelist is kept.
+#[derive(Debug)]
+pub struct AuthorizationWhitelist {
+    path: String,
+    records: RwLock<Arc<HashMap<String, AuthorizationWhitelistRecord>>>,
+    /// Content of the file when it was last loaded
+    content: Mutex<Option<Vec<u8>>>,
+    usage: UsageStore,
+}
+
+impl AuthorizationWhitelist {
+    /// Load the whitelist csv at `path`, the usage of each API key is persisted in the SQLite
+    /// database at `usage_db_path` if set, or kept in memory otherwise
+    pub fn load(path: &str, usage_db_path: Option<&str>) -> Result<Self> {
+        let whitelist = Self {
+            path: path.to_string(),
+            records: Default::default(),
+            content: Default::default(),
+            usage: UsageStore::open(usage_db_path.unwrap_or(":memory:"))
+                .map_err(|err| eyre!("Failed to open API key usage database: {err}"))?,
+        };
+        whitelist.reload()?;
+        Ok(whitelist)
+    }
+
+    /// Reload the whitelist if its file has changed since it was last loaded, returns whether
+    /// it was reloaded
+    ///
+    /// The content of the file is compared rather than its modification time, as the latter is
+    /// not reliable when the file is replaced, e.g. when mounted from a Kubernetes ConfigMap.
+    pub fn reload(&self) -> Result<bool> {
+        let content = std::fs::read(&self.path)
+            .map_err(|err| eyre!("Failed to read authorization whitelist csv: {err}"))?;
+        let mut last_content = self.content.lock().unwrap();
+        if last_content.as_ref() == Some(&content) {
+            return Ok(false);
+        }
+
+        let records = parse_csv_file::<AuthorizationWhitelistRecord>(&self.path)
+            .map_err(|err| eyre!("Failed to parse authorization whitelist csv: {:?}", err))?;
+        // Convert the whitelist record into hashmap for faster lookup
+        *self.records.write().unwrap() =
+            Arc::new(authorization_whitelist_vec_into_hashmap(records));
+        *last_content = Some(content);
+
+        Ok(true)
+    }
+
+    /// Snapshot of the current whitelist
+    pub fn records(&self) -> Arc<HashMap<String, AuthorizationWhitelistRecord>> {
+        self.records.read().unwrap().clone()
+    }
+
+    /// Check the quotas of an API key for a new session requesting `max_transcript_size`, and
+    /// count the session towards the daily quota of the key
+    ///
+    /// A session which does not request a size runs with the default size of the verifier
+    pub async fn consume_session(
+        &self,
+        api_key: &str,
+        max_transcript_size: Option<usize>,
+    ) -> Result<(), QuotaError> {
+        // Unknown keys are rejected by the authorization middleware
+        let Some(record) = self.records().get(api_key).cloned() else {
+            return Ok(());
+        };
+
+        if let Some(limit) = record.max_transcript_size {
+            if max_transcript_size.unwrap_or(DEFAULT_MAX_TRANSCRIPT_SIZE) > limit {
+                return Err(QuotaError::TranscriptSizeExceeded(limit));
+            }
+        }
+
+        self.usage
+            .increment_sessions(api_key, Utc::now(), record.max_sessions_per_day)
+            .await
+    }
+}
+
+/// Spawn a background task which periodically reloads the whitelist if its file changed
+pub fn spawn_whitelist_reload_task(
+    whitelist: Arc<AuthorizationWhitelist>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            match whitelist.reload() {
+                Ok(true) => info!("Reloaded authorization whitelist"),
+                Ok(false) => {}
+                Err(err) => error!("Failed to reload authorization whitelist: {err}"),
+            }
+        }
+    })
+}
+
+/// Usage of each API key, persisted in a SQLite database
+#[derive(Debug)]
+struct UsageStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl UsageStore {
+    fn open(path: &str) -> Result<Self, QuotaError> {
+        debug!(path, "Opening API key usage database");
+
+        let connection = Connection::open(path)?;
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS api_key_usage (
+                api_key TEXT NOT NULL,
+                day TEXT NOT NULL,
+                sessions INTEGER NOT NULL,
+                PRIMARY KEY (api_key, day)
+            )",
+            [],
+        )?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Count a session of `api_key` on the day of `now`, unless it would exceed `limit`
+    async fn increment_sessions(
+        &self,
+        api_key: &str,
+        now: DateTime<Utc>,
+        limit: Option<u32>,
+    ) -> Result<(), QuotaError> {
+        if limit == Some(0) {
+            return Err(QuotaError::SessionsPerDayExceeded(0));
+        }
+
+        let connection = self.connection.clone();
+        let api_key = api_key.to_string();
+        let day = now.date_naive().to_string();
+        // The check and the increment are done in one statement so that concurrent sessions can
+        // not exceed the quota
+        let sessions = tokio::task::spawn_blocking(move || {
+            let connection = connection
+                .lock()
+                .map_err(|err| QuotaError::Usage(err.to_string()))?;
+            connection
+                .query_row(
+                    "INSERT INTO api_key_usage (api_key, day, sessions) VALUES (?1, ?2, 1)
+                        ON CONFLICT (api_key, day) DO UPDATE SET sessions = sessions + 1
+                        WHERE sessions < ?3
+                        RETURNING sessions",
+                    params![api_key, day, limit.unwrap_or(u32::MAX)],
+                    |row| row.get::<_, u32>(0),
+                )
+                .optional()
+                .map_err(QuotaError::from)
+        })
+        .await
+        .map_err(|err| QuotaError::Usage(err.to_string()))??;
+
+        match (sessions, limit) {
+            (Some(_), _) => Ok(()),
+            (None, Some(limit)) => Err(QuotaError::SessionsPerDayExceeded(limit)),
+            (None, None) => unreachable!("sessions are always counted when there is no limit"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    const WHITELIST_HEADER: &str =
+        "\"Name\",\"ApiKey\",\"CreatedAt\",\"ExpiresAt\",\"MaxSessionsPerDay\",\"MaxTranscriptSize\"\n";
+
+    fn temp_path(extension: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("notary-auth-{}.{extension}", uuid::Uuid::new_v4()))
+    }
+
+    fn write_whitelist(path: &std::path::Path, rows: &[&str]) {
+        let mut content = WHITELIST_HEADER.to_string();
+        for row in rows {
+            content.push_str(row);
+            content.push('\n');
+        }
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_reload_whitelist() {
+        let path = temp_path("csv");
+        write_whitelist(&path, &[r#""a","key-0","2023-09-18T07:38:53Z",,,"#]);
+        let whitelist = AuthorizationWhitelist::load(path.to_str().unwrap(), None).unwrap();
+        assert!(whitelist.records().contains_key("key-0"));
+        assert!(!whitelist.reload().unwrap());
+
+        write_whitelist(&path, &[r#""b","key-1","2023-09-18T07:38:53Z",,,"#]);
+        assert!(whitelist.reload().unwrap());
+        assert!(!whitelist.records().contains_key("key-0"));
+        assert!(whitelist.records().contains_key("key-1"));
+
+        // An invalid whitelist does not replace the current one
+        std::fs::write(&path, "\"Name\"\n\"a\"").unwrap();
+        assert!(whitelist.reload().is_err());
+        assert!(whitelist.records().contains_key("key-1"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_quotas() {
+        let path = temp_path("csv");
+        write_whitelist(
+            &path,
+            &[
+                r#""a","key-0","2023-09-18T07:38:53Z",,"2","4096""#,
+                r#""b","key-1","2023-09-18T07:38:53Z",,"2","16384""#,
+            ],
+        );
+        let whitelist = AuthorizationWhitelist::load(path.to_str().unwrap(), None).unwrap();
+
+        assert!(matches!(
+            whitelist.consume_session("key-0", Some(8192)).await,
+            Err(QuotaError::TranscriptSizeExceeded(4096))
+        ));
+        // Not requesting a size does not bypass the limit, the default size of the verifier is used
+        assert!(matches!(
+            whitelist.consume_session("key-0", None).await,
+            Err(QuotaError::TranscriptSizeExceeded(4096))
+        ));
+        whitelist
+            .consume_session("key-0", Some(4096))
+            .await
+            .unwrap();
+        whitelist
+            .consume_session("key-0", Some(1024))
+            .await
+            .unwrap();
+        assert!(matches!(
+            whitelist.consume_session("key-0", Some(1024)).await,
+            Err(QuotaError::SessionsPerDayExceeded(2))
+        ));
+
+        whitelist.consume_session("key-1", None).await.unwrap();
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_usage_is_persisted() {
+        let path = temp_path("db");
+        let path_str = path.to_str().unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+
+        let usage = UsageStore::open(path_str).unwrap();
+        usage
+            .increment_sessions("key-0", now, Some(1))
+            .await
+            .unwrap();
+        drop(usage);
+
+        let usage = UsageStore::open(path_str).unwrap();
+        assert!(usage
+            .increment_sessions("key-0", now, Some(1))
+            .await
+            .is_err());
+        // The quota resets every day
+        usage
+            .increment_sessions("key-0", now + chrono::Duration::days(1), Some(1))
+            .await
+            .unwrap();
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
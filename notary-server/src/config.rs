@@ -1,4 +1,12 @@
+use chrono::{DateTime, Utc};
+use eyre::{ensure, eyre, Result};
 use serde::Deserialize;
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -9,12 +17,220 @@ pub struct NotaryServerProperties {
     pub notarization: NotarizationProperties,
     /// Setting for TLS connection between prover and notary
     pub tls: TLSProperties,
-    /// File path of private key (in PEM format) used to sign the notarization
+    /// Keys used to sign the notarization
     pub notary_key: NotarySigningKeyProperties,
     /// Setting for logging/tracing
     pub tracing: TracingProperties,
     /// Setting for authorization
     pub authorization: AuthorizationProperties,
+    /// Setting for the storage of notarization sessions
+    #[serde(default)]
+    pub session_store: SessionStoreProperties,
+    /// Setting for the number of notarization sessions that can run concurrently
+    #[serde(default)]
+    pub concurrency: ConcurrencyProperties,
+    /// Setting for the admin API
+    #[serde(default)]
+    pub admin: AdminProperties,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AdminProperties {
+    /// Switch to turn on or off the admin API
+    pub enabled: bool,
+    /// File path of the bearer token required to access the admin API
+    pub token_path: String,
+}
+
+impl NotaryServerProperties {
+    /// Check the config for invalid values and missing files, so that they are reported at
+    /// startup rather than when the first prover connects
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+        let mut check = |valid: bool, error: String| {
+            if !valid {
+                errors.push(error);
+            }
+        };
+        let file_exists = |path: &str| Path::new(path).is_file();
+
+        check(
+            self.server.port != 0,
+            "server.port must not be 0".to_string(),
+        );
+        if let Err(err) = self.server.listener_addresses() {
+            check(false, err.to_string());
+        }
+        check(
+            self.notarization.max_transcript_size > 0,
+            "notarization.max-transcript-size must be greater than 0".to_string(),
+        );
+        let timeouts = &self.notarization.timeouts;
+        check(
+            timeouts.setup_seconds > 0
+                && timeouts.mpc_tls_seconds > 0
+                && timeouts.finalize_seconds > 0,
+            "notarization.timeouts must be greater than 0".to_string(),
+        );
+        if self.tls.enabled {
+            if let Some(path) = &self.tls.client_ca_pem_path {
+                check(file_exists(path), format!("TLS file {path} does not exist"));
+            }
+            check(
+                self.tls.reload_interval_seconds != Some(0),
+                "tls.reload-interval-seconds must be greater than 0".to_string(),
+            );
+            match &self.tls.acme {
+                Some(acme) => {
+                    check(
+                        !acme.domains.is_empty(),
+                        "tls.acme.domains must contain at least one domain".to_string(),
+                    );
+                    check(
+                        acme.renew_before_days > 0,
+                        "tls.acme.renew-before-days must be greater than 0".to_string(),
+                    );
+                    if let Some(path) = &acme.directory_ca_pem_path {
+                        check(file_exists(path), format!("TLS file {path} does not exist"));
+                    }
+                }
+                None => {
+                    for path in [
+                        &self.tls.private_key_pem_path,
+                        &self.tls.certificate_pem_path,
+                    ] {
+                        check(file_exists(path), format!("TLS file {path} does not exist"));
+                    }
+                }
+            }
+        } else {
+            check(
+                self.tls.client_ca_pem_path.is_none(),
+                "tls.client-ca-pem-path requires tls to be enabled".to_string(),
+            );
+        }
+        check(
+            !self.notary_key.keys.is_empty(),
+            "notary-key.keys must contain at least one key".to_string(),
+        );
+        for key in &self.notary_key.keys {
+            check(
+                file_exists(&key.private_key_pem_path),
+                format!(
+                    "Notary key file {} does not exist",
+                    key.private_key_pem_path
+                ),
+            );
+        }
+        for key in &self.notary_key.retired_keys {
+            check(
+                file_exists(&key.public_key_pem_path),
+                format!(
+                    "Retired notary key file {} does not exist",
+                    key.public_key_pem_path
+                ),
+            );
+        }
+        check(
+            self.tracing.default_level.parse::<tracing::Level>().is_ok(),
+            format!(
+                "tracing.default-level {} is not a valid level",
+                self.tracing.default_level
+            ),
+        );
+        if self.authorization.enabled {
+            match (&self.authorization.mode, &self.authorization.jwt) {
+                (AuthorizationMode::Whitelist, _) => check(
+                    file_exists(&self.authorization.whitelist_csv_path),
+                    format!(
+                        "Authorization whitelist file {} does not exist",
+                        self.authorization.whitelist_csv_path
+                    ),
+                ),
+                (AuthorizationMode::Jwt, Some(jwt)) => check(
+                    file_exists(&jwt.key_path),
+                    format!("JWT issuer key file {} does not exist", jwt.key_path),
+                ),
+                (AuthorizationMode::Jwt, None) => check(
+                    false,
+                    "authorization.jwt must be set when the authorization mode is jwt".to_string(),
+                ),
+            }
+        }
+        check(
+            self.concurrency.max_sessions > 0,
+            "concurrency.max-sessions must be greater than 0".to_string(),
+        );
+        check(
+            self.session_store.ttl_seconds > 0 && self.session_store.eviction_interval_seconds > 0,
+            "session-store.ttl-seconds and eviction-interval-seconds must be greater than 0"
+                .to_string(),
+        );
+        if self.admin.enabled {
+            check(
+                file_exists(&self.admin.token_path),
+                format!("Admin token file {} does not exist", self.admin.token_path),
+            );
+        }
+
+        ensure!(errors.is_empty(), "Invalid config: {}", errors.join("; "));
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConcurrencyProperties {
+    /// Maximum number of notarization sessions that can run concurrently
+    pub max_sessions: usize,
+    /// Maximum number of notarization sessions that can run concurrently per API key, only
+    /// applies when authorization is turned on
+    #[serde(default)]
+    pub max_sessions_per_api_key: Option<usize>,
+    /// Time in seconds a prover waits for a session when the server is at capacity
+    pub queue_timeout_seconds: u64,
+}
+
+impl Default for ConcurrencyProperties {
+    fn default() -> Self {
+        Self {
+            max_sessions: 16,
+            max_sessions_per_api_key: None,
+            queue_timeout_seconds: 30,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SessionStoreProperties {
+    /// Backend used to store the sessions
+    pub backend: SessionStoreBackend,
+    /// Time in seconds after which a session that has not been used for notarization expires
+    pub ttl_seconds: u64,
+    /// Interval in seconds at which expired sessions are evicted from the store
+    pub eviction_interval_seconds: u64,
+}
+
+impl Default for SessionStoreProperties {
+    fn default() -> Self {
+        Self {
+            backend: SessionStoreBackend::Memory,
+            ttl_seconds: 1800,
+            eviction_interval_seconds: 60,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SessionStoreBackend {
+    /// Sessions are kept in memory, and are lost on restart
+    Memory,
+    /// Sessions are persisted in a SQLite database file, which can be shared by multiple replicas
+    /// on the same host, but not over a network filesystem
+    Sqlite { path: String },
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -22,8 +238,56 @@ pub struct NotaryServerProperties {
 pub struct AuthorizationProperties {
     /// Switch to turn on or off auth middleware
     pub enabled: bool,
+    /// Whether requests are authorized with an API key in the whitelist, or with a bearer JWT
+    #[serde(default)]
+    pub mode: AuthorizationMode,
+    /// Setting for the JWT authorization mode
+    #[serde(default)]
+    pub jwt: Option<JwtProperties>,
     /// File path of the whitelist API key csv
     pub whitelist_csv_path: String,
+    /// Interval in seconds at which the whitelist csv is checked for changes and reloaded
+    #[serde(default = "default_whitelist_reload_interval_seconds")]
+    pub whitelist_reload_interval_seconds: u64,
+    /// File path of the SQLite database used to persist the usage of each API key, the usage is
+    /// only kept in memory if not set
+    #[serde(default)]
+    pub usage_db_path: Option<String>,
+}
+
+fn default_whitelist_reload_interval_seconds() -> u64 {
+    10
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthorizationMode {
+    /// API key in the authorization header, checked against the whitelist csv
+    #[default]
+    Whitelist,
+    /// Bearer JWT in the authorization header, signed by the configured issuer key
+    Jwt,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct JwtProperties {
+    /// Algorithm used by the issuer to sign the tokens
+    pub algorithm: JwtAlgorithm,
+    /// File path of the shared secret (HS256) or of the public key in PEM format (ES256) of the issuer
+    pub key_path: String,
+    /// Expected `iss` claim of the tokens, not checked if not set
+    #[serde(default)]
+    pub issuer: Option<String>,
+    /// Expected `aud` claim of the tokens, not checked if not set
+    #[serde(default)]
+    pub audience: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    HS256,
+    ES256,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -31,6 +295,31 @@ pub struct AuthorizationProperties {
 pub struct NotarizationProperties {
     /// Global limit for maximum transcript size in bytes
     pub max_transcript_size: usize,
+    /// Time limits of each phase of the notarization
+    #[serde(default)]
+    pub timeouts: NotarizationTimeoutProperties,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NotarizationTimeoutProperties {
+    /// Time in seconds for the prover to complete the setup of the MPC protocols
+    pub setup_seconds: u64,
+    /// Time in seconds the MPC-TLS connection between the prover and the server can last
+    pub mpc_tls_seconds: u64,
+    /// Time in seconds for the prover to complete the finalization, i.e. to have its commitments
+    /// signed
+    pub finalize_seconds: u64,
+}
+
+impl Default for NotarizationTimeoutProperties {
+    fn default() -> Self {
+        Self {
+            setup_seconds: 120,
+            mpc_tls_seconds: 600,
+            finalize_seconds: 120,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -38,8 +327,72 @@ pub struct NotarizationProperties {
 pub struct ServerProperties {
     /// Used for testing purpose
     pub name: String,
+    /// IPv4 or IPv6 address to listen on
     pub host: String,
     pub port: u16,
+    /// Addresses to listen on in addition to the host and port, see [`ListenerAddress`]
+    #[serde(default)]
+    pub additional_listeners: Vec<ListenerAddress>,
+    /// Time in seconds to wait for running notarizations to finish when shutting down
+    #[serde(default = "default_shutdown_timeout_seconds")]
+    pub shutdown_timeout_seconds: u64,
+}
+
+fn default_shutdown_timeout_seconds() -> u64 {
+    60
+}
+
+impl ServerProperties {
+    /// All the addresses to listen on, starting with the host and port
+    pub fn listener_addresses(&self) -> Result<Vec<ListenerAddress>> {
+        let host: IpAddr = self.host.parse().map_err(|err| {
+            eyre!("Failed to parse notary host address from server config: {err}")
+        })?;
+        let mut addresses = vec![ListenerAddress::Tcp(SocketAddr::new(host, self.port))];
+        addresses.extend(self.additional_listeners.iter().cloned());
+        Ok(addresses)
+    }
+}
+
+/// Address to listen on, either `<ip>:<port>` (with the IPv6 address in brackets, e.g.
+/// `[::1]:7047`) or `unix:<path>` for a Unix domain socket
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ListenerAddress {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenerAddress {
+    type Err = eyre::Report;
+
+    fn from_str(address: &str) -> Result<Self> {
+        match address.strip_prefix("unix:") {
+            Some("") => Err(eyre!("Unix socket path must not be empty")),
+            Some(path) => Ok(Self::Unix(PathBuf::from(path))),
+            None => address
+                .parse()
+                .map(Self::Tcp)
+                .map_err(|err| eyre!("Invalid listener address {address}: {err}")),
+        }
+    }
+}
+
+impl TryFrom<String> for ListenerAddress {
+    type Error = eyre::Report;
+
+    fn try_from(address: String) -> Result<Self> {
+        address.parse()
+    }
+}
+
+impl fmt::Display for ListenerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(address) => write!(f, "{address}"),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -49,12 +402,123 @@ pub struct TLSProperties {
     pub enabled: bool,
     pub private_key_pem_path: String,
     pub certificate_pem_path: String,
+    /// Interval in seconds at which the private key and certificate files are checked for changes and reloaded without restarting the server, they are only loaded on startup if not set
+    #[serde(default)]
+    pub reload_interval_seconds: Option<u64>,
+    /// Setting to obtain and renew the certificate from an ACME CA instead of the files above
+    #[serde(default)]
+    pub acme: Option<AcmeProperties>,
+    /// CA bundle to turn on mutual TLS, only provers presenting a certificate issued by one of its CAs are admitted
+    #[serde(default)]
+    pub client_ca_pem_path: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
+pub struct AcmeProperties {
+    /// Domains of the certificate, the ACME CA validates them with the TLS-ALPN-01 challenge, i.e. by connecting to port 443 of each domain, which must reach this server
+    pub domains: Vec<String>,
+    /// Contact urls of the ACME account, e.g. "mailto:admin@example.com"
+    #[serde(default)]
+    pub contact: Vec<String>,
+    /// Url of the directory of the ACME CA
+    #[serde(default = "default_acme_directory_url")]
+    pub directory_url: String,
+    /// CA certificate to trust for the connection to the ACME CA instead of the system roots, e.g. of a local Pebble instance
+    #[serde(default)]
+    pub directory_ca_pem_path: Option<String>,
+    /// Folder where the account key and the certificate are stored to be reused across restarts
+    pub cache_dir: String,
+    /// Number of days before the expiry (notAfter) of the certificate at which it is renewed
+    #[serde(default = "default_acme_renew_before_days")]
+    pub renew_before_days: u64,
+}
+
+fn default_acme_directory_url() -> String {
+    "https://acme-v02.api.letsencrypt.org/directory".to_string()
+}
+
+fn default_acme_renew_before_days() -> u64 {
+    30
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "NotarySigningKeyLayout")]
 pub struct NotarySigningKeyProperties {
+    /// Keys used for signing, the active key with the latest activation time signs new notarizations
+    pub keys: Vec<NotaryKeyProperties>,
+    /// Keys no longer used for signing, which are still published to verify earlier notarizations
+    pub retired_keys: Vec<RetiredNotaryKeyProperties>,
+}
+
+/// Id of the key configured with the single key layout, which predates key rotation
+pub const SINGLE_NOTARY_KEY_ID: &str = "notary-key-0";
+
+/// The `notary-key` config as written, which is either a list of keys or the single key layout
+/// that predates key rotation, i.e. `private-key-pem-path` and `public-key-pem-path`. The public
+/// key is derived from the private key, so `public-key-pem-path` is ignored
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct NotarySigningKeyLayout {
+    keys: Option<Vec<NotaryKeyProperties>>,
+    #[serde(default)]
+    retired_keys: Vec<RetiredNotaryKeyProperties>,
+    private_key_pem_path: Option<String>,
+}
+
+impl TryFrom<NotarySigningKeyLayout> for NotarySigningKeyProperties {
+    type Error = String;
+
+    fn try_from(layout: NotarySigningKeyLayout) -> Result<Self, Self::Error> {
+        let keys = match (layout.keys, layout.private_key_pem_path) {
+            (Some(keys), None) => keys,
+            // The single key is active immediately, as it was before key rotation
+            (None, Some(private_key_pem_path)) => vec![NotaryKeyProperties {
+                id: SINGLE_NOTARY_KEY_ID.to_string(),
+                private_key_pem_path,
+                active_from: None,
+                retire_at: None,
+            }],
+            (Some(_), Some(_)) => {
+                return Err("notary-key.private-key-pem-path can not be combined with \
+                    notary-key.keys, move the key into notary-key.keys"
+                    .to_string())
+            }
+            (None, None) => {
+                return Err("notary-key.keys is missing, each key needs an id and a \
+                    private-key-pem-path"
+                    .to_string())
+            }
+        };
+
+        Ok(Self {
+            keys,
+            retired_keys: layout.retired_keys,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NotaryKeyProperties {
+    /// Unique id of the key, which is embedded in the signed session header
+    pub id: String,
+    /// File path of private key (in PEM format) used to sign the notarization
     pub private_key_pem_path: String,
+    /// Time (RFC 3339) from which the key is active, the key is active immediately if not set
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    /// Time (RFC 3339) from which the key is retired, i.e. no longer used for signing
+    #[serde(default)]
+    pub retire_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RetiredNotaryKeyProperties {
+    /// Id of the key when it was used for signing
+    pub id: String,
+    /// File path of public key (in PEM format) of the retired key
     pub public_key_pem_path: String,
 }
 
@@ -63,4 +527,39 @@ pub struct NotarySigningKeyProperties {
 pub struct TracingProperties {
     /// The minimum logging level, must be either of <https://docs.rs/tracing/latest/tracing/struct.Level.html#implementations>
     pub default_level: String,
+    /// Format of the logs
+    #[serde(default)]
+    pub format: LogFormat,
+    /// Setting to export each notarization as a trace to an OpenTelemetry collector, traces are not exported if not set
+    #[serde(default)]
+    pub otlp: Option<OtlpProperties>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogFormat {
+    /// Human readable text
+    #[default]
+    Compact,
+    /// One JSON object per line, including the fields of the enclosing spans, e.g. the session id
+    Json,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct OtlpProperties {
+    /// Url of the OTLP gRPC endpoint of the collector
+    #[serde(default = "default_otlp_endpoint")]
+    pub endpoint: String,
+    /// Name of the service which the traces are attributed to
+    #[serde(default = "default_otlp_service_name")]
+    pub service_name: String,
+}
+
+fn default_otlp_endpoint() -> String {
+    "http://localhost:4317".to_string()
+}
+
+fn default_otlp_service_name() -> String {
+    "notary-server".to_string()
 }
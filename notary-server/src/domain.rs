@@ -1,3 +1,4 @@
+pub mod admin;
 pub mod auth;
 pub mod cli;
 pub mod notary;
@@ -10,10 +11,26 @@ use serde::{Deserialize, Serialize};
 pub struct InfoResponse {
     /// Current version of notary-server
     pub version: String,
-    /// Public key of the notary signing key
+    /// Public key (in PEM format) of the key currently used for signing
     pub public_key: String,
+    /// Id of the key currently used for signing
+    pub key_id: String,
+    /// Public keys which are currently in use, including the one used for signing
+    pub active_keys: Vec<PublicKeyInfo>,
+    /// Public keys which are no longer used for signing, to verify earlier notarizations
+    pub retired_keys: Vec<PublicKeyInfo>,
     /// Current git commit hash of notary-server
     pub git_commit_hash: String,
     /// Current git commit timestamp of notary-server
     pub git_commit_timestamp: String,
 }
+
+/// Public key of the notary, as published by the /info API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyInfo {
+    /// Id of the key, which is embedded in the session headers it signed
+    pub key_id: String,
+    /// Public key in PEM format
+    pub public_key: String,
+}
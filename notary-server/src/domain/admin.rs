@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::notary::ClientType;
+
+/// Response object of the /admin/sessions API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionsResponse {
+    /// Sessions created via /session which have not been used for notarization yet
+    pub pending: Vec<PendingSessionInfo>,
+    /// Notarizations which are currently running on this server
+    pub running: Vec<RunningSessionInfo>,
+}
+
+/// Session created via /session which has not been used for notarization yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingSessionInfo {
+    pub session_id: String,
+    /// Maximum transcript size in bytes requested by the prover
+    pub max_transcript_size: Option<usize>,
+    pub created_at: DateTime<Utc>,
+    /// Subject of the token which created the session, when JWT authorization is turned on
+    pub subject: Option<String>,
+    /// Subject of the certificate of the prover which created the session, when mutual TLS is
+    /// turned on
+    pub client_certificate_subject: Option<String>,
+}
+
+/// Notarization which is currently running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunningSessionInfo {
+    pub session_id: String,
+    pub client_type: ClientType,
+    /// Address of the prover's connection
+    pub remote_address: String,
+    pub started_at: DateTime<Utc>,
+    /// Number of bytes sent to the prover so far
+    pub bytes_sent: u64,
+    /// Number of bytes received from the prover so far
+    pub bytes_received: u64,
+}
+
+/// Request query of the /admin/sessions/:session_id API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelSessionQuery {
+    /// Reason of the cancellation, which is logged
+    pub reason: Option<String>,
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -8,6 +9,24 @@ pub struct AuthorizationWhitelistRecord {
     pub name: String,
     pub api_key: String,
     pub created_at: String,
+    /// Time (RFC 3339) after which the API key is no longer valid, the key never expires if not set
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Maximum number of sessions per day (UTC), unlimited if not set
+    #[serde(default)]
+    pub max_sessions_per_day: Option<u32>,
+    /// Maximum transcript size in bytes that can be requested, only limited by the global
+    /// maximum if not set
+    #[serde(default)]
+    pub max_transcript_size: Option<usize>,
+}
+
+impl AuthorizationWhitelistRecord {
+    /// Whether the API key has expired at `time`
+    pub fn is_expired(&self, time: DateTime<Utc>) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| expires_at <= time)
+    }
 }
 
 /// Convert whitelist data structure from vector to hashmap using api_key as the key to speed up lookup
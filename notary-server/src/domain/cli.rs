@@ -1,5 +1,7 @@
 use structopt::StructOpt;
 
+use crate::util::parse_config_override;
+
 /// Fields loaded from the command line when launching this server.
 #[derive(Clone, Debug, StructOpt)]
 #[structopt(name = "Notary Server")]
@@ -7,4 +9,7 @@ pub struct CliFields {
     /// Configuration file location
     #[structopt(long, default_value = "./config/config.yaml")]
     pub config_file: String,
+    /// Override a property of the configuration file, e.g. --set server.port=7047 (can be repeated)
+    #[structopt(long = "set", number_of_values = 1, parse(try_from_str = parse_config_override))]
+    pub overrides: Vec<(String, String)>,
 }
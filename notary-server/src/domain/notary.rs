@@ -1,11 +1,13 @@
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
-use p256::ecdsa::SigningKey;
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
 
-use crate::{config::NotarizationProperties, domain::auth::AuthorizationWhitelistRecord};
+use crate::{
+    auth::AuthorizationWhitelist, config::NotarizationProperties, jwt::JwtAuthorizer,
+    limiter::SessionLimiter, metrics::NotaryMetrics, registry::SessionRegistry,
+    signer::NotarySigner, store::SessionStore,
+};
 
 /// Response object of the /session API
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,30 +48,53 @@ pub enum ClientType {
 pub struct SessionData {
     pub max_transcript_size: Option<usize>,
     pub created_at: DateTime<Utc>,
+    /// Subject of the token which created the session, when JWT authorization is turned on
+    pub subject: Option<String>,
+    /// Subject of the certificate of the prover which created the session, when mutual TLS is
+    /// turned on
+    pub client_certificate_subject: Option<String>,
 }
 
 /// Global data that needs to be shared with the axum handlers
 #[derive(Clone, Debug)]
 pub struct NotaryGlobals {
-    pub notary_signing_key: SigningKey,
+    pub notary_signer: Arc<dyn NotarySigner>,
     pub notarization_config: NotarizationProperties,
     /// A temporary storage to store configuration data, mainly used for WebSocket client
-    pub store: Arc<Mutex<HashMap<String, SessionData>>>,
+    pub store: Arc<dyn SessionStore>,
+    /// Limiter of the number of concurrent notarization sessions
+    pub session_limiter: Arc<SessionLimiter>,
+    /// Metrics of the notarization sessions
+    pub metrics: Arc<NotaryMetrics>,
     /// Whitelist of API keys for authorization purpose
-    pub authorization_whitelist: Option<Arc<HashMap<String, AuthorizationWhitelistRecord>>>,
+    pub authorization_whitelist: Option<Arc<AuthorizationWhitelist>>,
+    /// Validator of bearer tokens for authorization purpose
+    pub jwt_authorizer: Option<Arc<JwtAuthorizer>>,
+    /// Registry of the running notarizations, for the admin API
+    pub session_registry: Arc<SessionRegistry>,
 }
 
 impl NotaryGlobals {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        notary_signing_key: SigningKey,
+        notary_signer: Arc<dyn NotarySigner>,
         notarization_config: NotarizationProperties,
-        authorization_whitelist: Option<Arc<HashMap<String, AuthorizationWhitelistRecord>>>,
+        store: Arc<dyn SessionStore>,
+        session_limiter: Arc<SessionLimiter>,
+        metrics: Arc<NotaryMetrics>,
+        authorization_whitelist: Option<Arc<AuthorizationWhitelist>>,
+        jwt_authorizer: Option<Arc<JwtAuthorizer>>,
+        session_registry: Arc<SessionRegistry>,
     ) -> Self {
         Self {
-            notary_signing_key,
+            notary_signer,
             notarization_config,
-            store: Default::default(),
+            store,
+            session_limiter,
+            metrics,
             authorization_whitelist,
+            jwt_authorizer,
+            session_registry,
         }
     }
 }
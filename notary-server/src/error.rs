@@ -1,12 +1,17 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
 };
 use eyre::Report;
-use std::error::Error;
+use std::{error::Error, time::Duration};
 
 use tlsn_verifier::tls::{VerifierConfigBuilderError, VerifierError};
 
+use crate::{
+    auth::QuotaError, limiter::SessionLimitError, signer::NotarySignerError,
+    store::SessionStoreError,
+};
+
 #[derive(Debug, thiserror::Error)]
 pub enum NotaryServerError {
     #[error(transparent)]
@@ -19,11 +24,40 @@ pub enum NotaryServerError {
     BadProverRequest(String),
     #[error("Unauthorized request from prover: {0}")]
     UnauthorizedProverRequest(String),
+    #[error("Failed to access notarization session store: {0}")]
+    SessionStore(#[from] SessionStoreError),
+    #[error("Notarization session is not available: {0}")]
+    SessionLimit(#[from] SessionLimitError),
+    #[error("API key quota exceeded: {0}")]
+    Quota(#[from] QuotaError),
+    #[error("Notarization session was cancelled")]
+    Cancelled,
+    #[error("Prover did not complete the MPC setup within {0:?}")]
+    SetupTimeout(Duration),
+    #[error("MPC-TLS connection did not finish within {0:?}")]
+    MpcTlsTimeout(Duration),
+    #[error("Prover did not complete the finalization within {0:?}")]
+    FinalizeTimeout(Duration),
+}
+
+impl NotaryServerError {
+    /// Whether a phase of the notarization exceeded its time limit
+    pub fn is_timeout(&self) -> bool {
+        matches!(
+            self,
+            Self::SetupTimeout(_) | Self::MpcTlsTimeout(_) | Self::FinalizeTimeout(_)
+        )
+    }
 }
 
 impl From<VerifierError> for NotaryServerError {
     fn from(error: VerifierError) -> Self {
-        Self::Notarization(Box::new(error))
+        match error {
+            VerifierError::SetupTimeout(timeout) => Self::SetupTimeout(timeout),
+            VerifierError::MpcTlsTimeout(timeout) => Self::MpcTlsTimeout(timeout),
+            VerifierError::FinalizeTimeout(timeout) => Self::FinalizeTimeout(timeout),
+            error => Self::Notarization(Box::new(error)),
+        }
     }
 }
 
@@ -33,6 +67,12 @@ impl From<VerifierConfigBuilderError> for NotaryServerError {
     }
 }
 
+impl From<NotarySignerError> for NotaryServerError {
+    fn from(error: NotarySignerError) -> Self {
+        Self::Notarization(Box::new(error))
+    }
+}
+
 /// Trait implementation to convert this error into an axum http response
 impl IntoResponse for NotaryServerError {
     fn into_response(self) -> Response {
@@ -45,6 +85,25 @@ impl IntoResponse for NotaryServerError {
                 unauthorized_request_error.to_string(),
             )
                 .into_response(),
+            NotaryServerError::SessionLimit(err @ SessionLimitError::AtCapacity(_)) => {
+                (StatusCode::SERVICE_UNAVAILABLE, err.to_string()).into_response()
+            }
+            NotaryServerError::SessionLimit(err @ SessionLimitError::ShuttingDown) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                // Provers can retry right away, as another replica would take the session
+                [(header::RETRY_AFTER, "1")],
+                err.to_string(),
+            )
+                .into_response(),
+            NotaryServerError::SessionLimit(err @ SessionLimitError::ApiKeyLimitReached(_)) => {
+                (StatusCode::TOO_MANY_REQUESTS, err.to_string()).into_response()
+            }
+            NotaryServerError::Quota(err @ QuotaError::SessionsPerDayExceeded(_)) => {
+                (StatusCode::TOO_MANY_REQUESTS, err.to_string()).into_response()
+            }
+            NotaryServerError::Quota(err @ QuotaError::TranscriptSizeExceeded(_)) => {
+                (StatusCode::BAD_REQUEST, err.to_string()).into_response()
+            }
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Something wrong happened.",
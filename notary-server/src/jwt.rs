@@ -0,0 +1,159 @@
+use eyre::{eyre, Result};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::config::{JwtAlgorithm, JwtProperties};
+
+/// Claims of the bearer tokens accepted by the notary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwtClaims {
+    /// Subject of the token, the sessions created with the token are bound to it
+    pub sub: String,
+    /// Expiry time of the token (seconds since the Unix epoch)
+    pub exp: u64,
+    /// Maximum transcript size in bytes that can be requested with the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_transcript_size: Option<usize>,
+}
+
+/// Validates bearer tokens signed by the configured issuer key
+pub struct JwtAuthorizer {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl JwtAuthorizer {
+    /// Load the issuer key from the file in the config, i.e. the shared secret for HS256 or the
+    /// public key (in PEM format) for ES256
+    pub fn load(config: &JwtProperties) -> Result<Self> {
+        debug!("Loading JWT issuer key");
+
+        let key_bytes = std::fs::read(&config.key_path)
+            .map_err(|err| eyre!("Failed to read JWT issuer key: {err}"))?;
+        let (key, algorithm) = match config.algorithm {
+            JwtAlgorithm::HS256 => (
+                // Ignore the trailing newline added by most editors
+                DecodingKey::from_secret(key_bytes.strip_suffix(b"\n").unwrap_or(&key_bytes)),
+                Algorithm::HS256,
+            ),
+            JwtAlgorithm::ES256 => (
+                DecodingKey::from_ec_pem(&key_bytes)
+                    .map_err(|err| eyre!("Failed to parse JWT issuer key: {err}"))?,
+                Algorithm::ES256,
+            ),
+        };
+
+        let mut validation = Validation::new(algorithm);
+        validation.set_required_spec_claims(&["exp", "sub"]);
+        if let Some(issuer) = &config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(audience) = &config.audience {
+            validation.set_audience(&[audience]);
+        }
+
+        Ok(Self { key, validation })
+    }
+
+    /// Validate the signature and claims of a token
+    pub fn authorize(&self, token: &str) -> Result<JwtClaims, jsonwebtoken::errors::Error> {
+        decode::<JwtClaims>(token, &self.key, &self.validation).map(|data| data.claims)
+    }
+}
+
+impl std::fmt::Debug for JwtAuthorizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtAuthorizer")
+            .field("algorithms", &self.validation.algorithms)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    fn now() -> u64 {
+        jsonwebtoken::get_current_timestamp()
+    }
+
+    fn claims(exp: u64) -> JwtClaims {
+        JwtClaims {
+            sub: "prover-0".to_string(),
+            exp,
+            max_transcript_size: Some(1 << 12),
+        }
+    }
+
+    fn config(algorithm: JwtAlgorithm, key_path: &str) -> JwtProperties {
+        JwtProperties {
+            algorithm,
+            key_path: key_path.to_string(),
+            issuer: None,
+            audience: None,
+        }
+    }
+
+    #[test]
+    fn test_hs256() {
+        let path = std::env::temp_dir().join(format!("notary-jwt-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "secret\n").unwrap();
+        let authorizer =
+            JwtAuthorizer::load(&config(JwtAlgorithm::HS256, path.to_str().unwrap())).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let sign = |claims: &JwtClaims, secret: &[u8]| {
+            encode(
+                &Header::new(Algorithm::HS256),
+                claims,
+                &EncodingKey::from_secret(secret),
+            )
+            .unwrap()
+        };
+
+        let token = sign(&claims(now() + 60), b"secret");
+        assert_eq!(authorizer.authorize(&token).unwrap(), claims(now() + 60));
+
+        // Expired tokens, tokens signed with another key, and tokens without a subject are rejected
+        assert!(authorizer
+            .authorize(&sign(&claims(now() - 120), b"secret"))
+            .is_err());
+        assert!(authorizer
+            .authorize(&sign(&claims(now() + 60), b"other"))
+            .is_err());
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &serde_json::json!({ "exp": now() + 60 }),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+        assert!(authorizer.authorize(&token).is_err());
+    }
+
+    #[test]
+    fn test_es256() {
+        let authorizer =
+            JwtAuthorizer::load(&config(JwtAlgorithm::ES256, "./fixture/notary/notary.pub"))
+                .unwrap();
+
+        let key = std::fs::read("./fixture/notary/notary.key").unwrap();
+        let token = encode(
+            &Header::new(Algorithm::ES256),
+            &claims(now() + 60),
+            &EncodingKey::from_ec_pem(&key).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(authorizer.authorize(&token).unwrap().sub, "prover-0");
+
+        // A token signed with HS256 is rejected even if it is otherwise valid
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims(now() + 60),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+        assert!(authorizer.authorize(&token).is_err());
+    }
+}
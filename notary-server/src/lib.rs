@@ -1,21 +1,49 @@
+mod acme;
+mod auth;
 mod config;
 mod domain;
 mod error;
+mod jwt;
+mod limiter;
+mod listener;
+mod metrics;
 mod middleware;
+mod registry;
 mod server;
 mod server_tracing;
 mod service;
+mod signer;
+mod store;
+mod tls;
 mod util;
 
+pub use auth::{AuthorizationWhitelist, QuotaError};
 pub use config::{
-    AuthorizationProperties, NotarizationProperties, NotaryServerProperties,
-    NotarySigningKeyProperties, ServerProperties, TLSProperties, TracingProperties,
+    AcmeProperties, AdminProperties, AuthorizationMode, AuthorizationProperties,
+    ConcurrencyProperties, JwtAlgorithm, JwtProperties, ListenerAddress, LogFormat,
+    NotarizationProperties, NotarizationTimeoutProperties, NotaryKeyProperties,
+    NotaryServerProperties, NotarySigningKeyProperties, OtlpProperties, RetiredNotaryKeyProperties,
+    ServerProperties, SessionStoreBackend, SessionStoreProperties, TLSProperties,
+    TracingProperties,
 };
 pub use domain::{
+    admin::{CancelSessionQuery, PendingSessionInfo, RunningSessionInfo, SessionsResponse},
     cli::CliFields,
     notary::{ClientType, NotarizationSessionRequest, NotarizationSessionResponse},
+    InfoResponse, PublicKeyInfo,
 };
 pub use error::NotaryServerError;
+pub use jwt::{JwtAuthorizer, JwtClaims};
+pub use limiter::{SessionLimitError, SessionLimiter, SessionPermit};
+pub use listener::{ConnectionAddress, Listener};
+pub use metrics::{NotarizationPhase, NotaryMetrics};
+pub use registry::{RegisteredSession, SessionRegistry};
 pub use server::{read_pem_file, run_server};
-pub use server_tracing::init_tracing;
-pub use util::parse_config_file;
+pub use server_tracing::{init_tracing, shutdown_tracing};
+pub use signer::{FileNotarySigner, NotarySigner, NotarySignerError, NotarySigningKey};
+pub use store::{InMemorySessionStore, SessionStore, SessionStoreError, SqliteSessionStore};
+pub use tls::{CertificateFiles, CertificateResolver, ClientCertificate, ACME_TLS_ALPN_PROTOCOL};
+pub use util::{
+    env_config_overrides, parse_config_file, parse_config_file_with_overrides,
+    parse_config_override, CONFIG_ENV_PREFIX,
+};
@@ -0,0 +1,277 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
+
+use crate::config::ConcurrencyProperties;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionLimitError {
+    #[error("Notary server is at capacity, no session became available within {0:?}")]
+    AtCapacity(Duration),
+    #[error("API key has reached its limit of {0} concurrent sessions")]
+    ApiKeyLimitReached(usize),
+    #[error("Notary server is shutting down")]
+    ShuttingDown,
+}
+
+/// Permit to run a notarization session, the capacity is released when the permit is dropped
+#[derive(Debug)]
+pub struct SessionPermit {
+    _global: OwnedSemaphorePermit,
+    _api_key: Option<ApiKeyPermit>,
+}
+
+/// Semaphores limiting the concurrent sessions of each API key, an API key only has an entry
+/// while it holds any session
+type ApiKeySemaphores = Arc<Mutex<HashMap<String, Arc<Semaphore>>>>;
+
+/// Permit of an API key, which removes the semaphore of the API key once all of its permits are
+/// released, so that the semaphores do not pile up for API keys which are no longer used
+#[derive(Debug)]
+struct ApiKeyPermit {
+    permit: Option<OwnedSemaphorePermit>,
+    api_key: String,
+    limit: usize,
+    api_keys: ApiKeySemaphores,
+}
+
+impl Drop for ApiKeyPermit {
+    fn drop(&mut self) {
+        let mut api_keys = self.api_keys.lock().unwrap();
+        // Release the permit while holding the lock, so that no permit is acquired from a
+        // semaphore which is about to be removed
+        drop(self.permit.take());
+        let unused = api_keys
+            .get(&self.api_key)
+            .is_some_and(|semaphore| semaphore.available_permits() == self.limit);
+        if unused {
+            api_keys.remove(&self.api_key);
+        }
+    }
+}
+
+/// Limits the number of notarization sessions which run concurrently, as each session allocates
+/// memory proportional to its max transcript size
+///
+/// Capacity is acquired when a session is created via /session and released once the
+/// notarization finishes, or when the session expires without being used.
+#[derive(Debug)]
+pub struct SessionLimiter {
+    global: Arc<Semaphore>,
+    max_sessions_per_api_key: Option<usize>,
+    api_keys: ApiKeySemaphores,
+    queue_timeout: Duration,
+    session_ttl: Duration,
+    reservations: Arc<Mutex<HashMap<String, SessionPermit>>>,
+}
+
+impl SessionLimiter {
+    /// Create a limiter, where sessions which are not used within `session_ttl` release their
+    /// capacity
+    pub fn new(config: &ConcurrencyProperties, session_ttl: Duration) -> Self {
+        Self {
+            global: Arc::new(Semaphore::new(config.max_sessions)),
+            max_sessions_per_api_key: config.max_sessions_per_api_key,
+            api_keys: Default::default(),
+            queue_timeout: Duration::from_secs(config.queue_timeout_seconds),
+            session_ttl,
+            reservations: Default::default(),
+        }
+    }
+
+    /// Acquire the capacity to run a session
+    ///
+    /// Fails immediately if the API key has reached its limit, otherwise waits in the queue for
+    /// up to the queue timeout if the server is at capacity.
+    pub async fn acquire(&self, api_key: Option<&str>) -> Result<SessionPermit, SessionLimitError> {
+        let api_key_permit = match (api_key, self.max_sessions_per_api_key) {
+            (Some(api_key), Some(limit)) => {
+                let mut api_keys = self.api_keys.lock().unwrap();
+                let permit = api_keys
+                    .entry(api_key.to_string())
+                    .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+                    .clone()
+                    .try_acquire_owned()
+                    .map_err(|_| SessionLimitError::ApiKeyLimitReached(limit))?;
+                Some(ApiKeyPermit {
+                    permit: Some(permit),
+                    api_key: api_key.to_string(),
+                    limit,
+                    api_keys: self.api_keys.clone(),
+                })
+            }
+            _ => None,
+        };
+
+        let global_permit =
+            tokio::time::timeout(self.queue_timeout, self.global.clone().acquire_owned())
+                .await
+                .map_err(|_| SessionLimitError::AtCapacity(self.queue_timeout))?
+                .map_err(|_| SessionLimitError::ShuttingDown)?;
+
+        Ok(SessionPermit {
+            _global: global_permit,
+            _api_key: api_key_permit,
+        })
+    }
+
+    /// Hold the permit of a session until it is taken for notarization, or until the session
+    /// expires
+    pub fn reserve(&self, session_id: String, permit: SessionPermit) {
+        self.reservations
+            .lock()
+            .unwrap()
+            .insert(session_id.clone(), permit);
+
+        let reservations = self.reservations.clone();
+        let ttl = self.session_ttl;
+        tokio::spawn(async move {
+            tokio::time::sleep(ttl).await;
+            if reservations.lock().unwrap().remove(&session_id).is_some() {
+                debug!(?session_id, "Released capacity of expired session");
+            }
+        });
+    }
+
+    /// Take the permit reserved for a session, if the session was created by this server
+    pub fn take_reservation(&self, session_id: &str) -> Option<SessionPermit> {
+        self.reservations.lock().unwrap().remove(session_id)
+    }
+
+    /// Stop admitting new sessions, sessions which already acquired their capacity are not affected
+    pub fn close(&self) {
+        self.global.close();
+    }
+
+    /// Number of sessions which can still be acquired without waiting
+    pub fn available_sessions(&self) -> usize {
+        self.global.available_permits()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn limiter(max_sessions_per_api_key: Option<usize>) -> SessionLimiter {
+        SessionLimiter::new(
+            &ConcurrencyProperties {
+                max_sessions: 2,
+                max_sessions_per_api_key,
+                queue_timeout_seconds: 0,
+            },
+            Duration::from_millis(10),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_global_limit() {
+        let limiter = limiter(None);
+
+        let permit = limiter.acquire(None).await.unwrap();
+        let _other_permit = limiter.acquire(None).await.unwrap();
+        assert!(matches!(
+            limiter.acquire(None).await,
+            Err(SessionLimitError::AtCapacity(_))
+        ));
+
+        drop(permit);
+        assert!(limiter.acquire(None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_queued_session_is_admitted() {
+        let limiter = Arc::new(SessionLimiter::new(
+            &ConcurrencyProperties {
+                max_sessions: 1,
+                max_sessions_per_api_key: None,
+                queue_timeout_seconds: 10,
+            },
+            Duration::from_secs(60),
+        ));
+
+        let permit = limiter.acquire(None).await.unwrap();
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire(None).await.map(|_| ()) }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(permit);
+
+        assert!(queued.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_api_key_limit() {
+        let limiter = limiter(Some(1));
+
+        let _permit = limiter.acquire(Some("key-0")).await.unwrap();
+        assert!(matches!(
+            limiter.acquire(Some("key-0")).await,
+            Err(SessionLimitError::ApiKeyLimitReached(1))
+        ));
+        assert!(limiter.acquire(Some("key-1")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_api_key_semaphore_is_removed() {
+        let limiter = limiter(Some(2));
+
+        let permit = limiter.acquire(Some("key-0")).await.unwrap();
+        let other_permit = limiter.acquire(Some("key-0")).await.unwrap();
+        drop(limiter.acquire(Some("key-1")).await.unwrap());
+        assert_eq!(limiter.api_keys.lock().unwrap().len(), 1);
+
+        // The API key still holds a session
+        drop(permit);
+        assert_eq!(limiter.api_keys.lock().unwrap().len(), 1);
+
+        drop(other_permit);
+        assert!(limiter.api_keys.lock().unwrap().is_empty());
+
+        // The limit still applies after the semaphore is created again
+        let _permit = limiter.acquire(Some("key-0")).await.unwrap();
+        let _other_permit = limiter.acquire(Some("key-0")).await.unwrap();
+        assert!(matches!(
+            limiter.acquire(Some("key-0")).await,
+            Err(SessionLimitError::ApiKeyLimitReached(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_close() {
+        let limiter = limiter(None);
+
+        let permit = limiter.acquire(None).await.unwrap();
+        limiter.close();
+        assert!(matches!(
+            limiter.acquire(None).await,
+            Err(SessionLimitError::ShuttingDown)
+        ));
+        drop(permit);
+    }
+
+    #[tokio::test]
+    async fn test_reservation() {
+        let limiter = limiter(None);
+
+        let permit = limiter.acquire(None).await.unwrap();
+        limiter.reserve("0".to_string(), permit);
+        assert_eq!(limiter.available_sessions(), 1);
+
+        let permit = limiter.take_reservation("0").unwrap();
+        assert!(limiter.take_reservation("0").is_none());
+        drop(permit);
+        assert_eq!(limiter.available_sessions(), 2);
+
+        // The capacity of a session which is never used is released once it expires
+        let permit = limiter.acquire(None).await.unwrap();
+        limiter.reserve("1".to_string(), permit);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(limiter.available_sessions(), 2);
+    }
+}
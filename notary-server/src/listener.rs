@@ -0,0 +1,242 @@
+use axum::extract::connect_info::Connected;
+use eyre::{eyre, Result};
+use std::{fmt, net::SocketAddr};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::info;
+
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::config::ListenerAddress;
+
+/// Address of a prover's connection, made available to the handlers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionAddress {
+    /// Remote address of a TCP connection
+    Tcp(SocketAddr),
+    /// Connection over a Unix domain socket, whose peers are usually unnamed, e.g. a reverse proxy
+    Unix,
+}
+
+impl fmt::Display for ConnectionAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(address) => write!(f, "{address}"),
+            Self::Unix => write!(f, "unix"),
+        }
+    }
+}
+
+impl Connected<ConnectionAddress> for ConnectionAddress {
+    fn connect_info(target: ConnectionAddress) -> Self {
+        target
+    }
+}
+
+/// Listener bound to one of the addresses of the server
+#[derive(Debug)]
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+/// Connection accepted by a [`Listener`]
+#[derive(Debug)]
+pub enum Connection {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Listener {
+    /// Bind a listener to `address`
+    ///
+    /// A stale socket file left by a previous run is removed before binding a Unix domain socket.
+    /// Binding fails if a server is still listening on the socket, or if any other file is at the
+    /// path.
+    pub async fn bind(address: &ListenerAddress) -> Result<Self> {
+        let listener = match address {
+            ListenerAddress::Tcp(socket_address) => Self::Tcp(
+                TcpListener::bind(socket_address)
+                    .await
+                    .map_err(|err| eyre!("Failed to bind tcp listener to {address}: {err}"))?,
+            ),
+            #[cfg(unix)]
+            ListenerAddress::Unix(path) => {
+                match std::fs::symlink_metadata(path) {
+                    Ok(metadata) if metadata.file_type().is_socket() => {
+                        // The socket is only stale if nothing accepts connections on it
+                        match UnixStream::connect(path).await {
+                            Ok(_) => {
+                                return Err(eyre!(
+                                    "Failed to bind unix listener to {address}: address in use"
+                                ))
+                            }
+                            Err(err)
+                                if matches!(
+                                    err.kind(),
+                                    std::io::ErrorKind::ConnectionRefused
+                                        | std::io::ErrorKind::NotFound
+                                ) =>
+                            {
+                                match std::fs::remove_file(path) {
+                                    Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                                        return Err(eyre!(
+                                            "Failed to remove stale unix socket {address}: {err}"
+                                        ))
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            Err(err) => {
+                                return Err(eyre!(
+                                    "Failed to check whether unix socket {address} is in use: {err}"
+                                ))
+                            }
+                        }
+                    }
+                    Ok(_) => {
+                        return Err(eyre!(
+                            "Failed to bind unix listener to {address}: the path exists and is not a socket"
+                        ))
+                    }
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(err) => {
+                        return Err(eyre!("Failed to inspect unix socket path {address}: {err}"))
+                    }
+                }
+                Self::Unix(
+                    UnixListener::bind(path)
+                        .map_err(|err| eyre!("Failed to bind unix listener to {address}: {err}"))?,
+                )
+            }
+            #[cfg(not(unix))]
+            ListenerAddress::Unix(_) => {
+                return Err(eyre!(
+                    "Unix domain sockets are not supported on this platform: {address}"
+                ))
+            }
+        };
+        info!("Listening for traffic at {address}");
+        Ok(listener)
+    }
+
+    /// Accept the next connection
+    pub async fn accept(&self) -> std::io::Result<(Connection, ConnectionAddress)> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, address) = listener.accept().await?;
+                Ok((Connection::Tcp(stream), ConnectionAddress::Tcp(address)))
+            }
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let (stream, _) = listener.accept().await?;
+                Ok((Connection::Unix(stream), ConnectionAddress::Unix))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_parse_listener_address() {
+        assert_eq!(
+            "[::1]:7047".parse::<ListenerAddress>().unwrap(),
+            ListenerAddress::Tcp("[::1]:7047".parse().unwrap())
+        );
+        assert_eq!(
+            "unix:/run/notary.sock".parse::<ListenerAddress>().unwrap(),
+            ListenerAddress::Unix("/run/notary.sock".into())
+        );
+        assert!("::1".parse::<ListenerAddress>().is_err());
+        assert!("unix:".parse::<ListenerAddress>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ipv6_listener() {
+        let Ok(listener) = Listener::bind(&"[::1]:0".parse().unwrap()).await else {
+            // IPv6 is not available in this environment
+            return;
+        };
+        let Listener::Tcp(tcp_listener) = &listener else {
+            panic!("expected a tcp listener");
+        };
+        let local_address = tcp_listener.local_addr().unwrap();
+
+        let client = tokio::spawn(async move { TcpStream::connect(local_address).await.unwrap() });
+        let (_, address) = listener.accept().await.unwrap();
+        assert!(matches!(address, ConnectionAddress::Tcp(address) if address.is_ipv6()));
+        client.await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_listener() {
+        let path = std::env::temp_dir().join(format!("notary-{}.sock", uuid::Uuid::new_v4()));
+        let address = ListenerAddress::Unix(path.clone());
+        // Binding again replaces the socket of the previous listener
+        drop(Listener::bind(&address).await.unwrap());
+        let listener = Listener::bind(&address).await.unwrap();
+
+        let client = tokio::spawn({
+            let path = path.clone();
+            async move {
+                let mut stream = UnixStream::connect(path).await.unwrap();
+                stream.write_all(b"hello").await.unwrap();
+            }
+        });
+        let (connection, address) = listener.accept().await.unwrap();
+        assert_eq!(address, ConnectionAddress::Unix);
+        let Connection::Unix(mut stream) = connection else {
+            panic!("expected a unix connection");
+        };
+        let mut buf = [0u8; 5];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+        client.await.unwrap();
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_listener_does_not_replace_live_socket() {
+        let path = std::env::temp_dir().join(format!("notary-{}.sock", uuid::Uuid::new_v4()));
+        let address = ListenerAddress::Unix(path.clone());
+        let listener = Listener::bind(&address).await.unwrap();
+
+        let err = Listener::bind(&address).await.unwrap_err();
+        assert!(err.to_string().contains("address in use"));
+
+        // The first listener still owns the socket
+        let client = tokio::spawn({
+            let path = path.clone();
+            async move { UnixStream::connect(path).await.unwrap() }
+        });
+        listener.accept().await.unwrap();
+        client.await.unwrap();
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_listener_does_not_replace_files() {
+        let path = std::env::temp_dir().join(format!("notary-{}.sock", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"not a socket").unwrap();
+
+        assert!(Listener::bind(&ListenerAddress::Unix(path.clone()))
+            .await
+            .is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"not a socket");
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
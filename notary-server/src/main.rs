@@ -3,15 +3,21 @@ use structopt::StructOpt;
 use tracing::debug;
 
 use notary_server::{
-    init_tracing, parse_config_file, run_server, CliFields, NotaryServerError,
-    NotaryServerProperties,
+    env_config_overrides, init_tracing, parse_config_file_with_overrides, run_server,
+    shutdown_tracing, CliFields, NotaryServerError, NotaryServerProperties,
 };
 
 #[tokio::main]
 async fn main() -> Result<(), NotaryServerError> {
     // Load command line arguments which contains the config file location
     let cli_fields: CliFields = CliFields::from_args();
-    let config: NotaryServerProperties = parse_config_file(&cli_fields.config_file)?;
+    // The config file is overridden by the environment variables, which are overridden by the
+    // command line
+    let mut overrides = env_config_overrides(std::env::vars());
+    overrides.extend(cli_fields.overrides);
+    let config: NotaryServerProperties =
+        parse_config_file_with_overrides(&cli_fields.config_file, &overrides)?;
+    config.validate()?;
 
     // Set up tracing for logging
     init_tracing(&config).map_err(|err| eyre!("Failed to set up tracing: {err}"))?;
@@ -19,7 +25,9 @@ async fn main() -> Result<(), NotaryServerError> {
     debug!(?config, "Server config loaded");
 
     // Run the server
-    run_server(&config).await?;
+    let result = run_server(&config).await;
+    // Export the remaining spans, e.g. of the notarizations drained on shutdown
+    shutdown_tracing();
 
-    Ok(())
+    result
 }
@@ -0,0 +1,342 @@
+use prometheus::{
+    exponential_buckets, Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Instant,
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::domain::notary::ClientType;
+
+/// Phases of a notarization, used to label the duration metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotarizationPhase {
+    /// Setup of the MPC protocols with the prover
+    Setup,
+    /// MPC-TLS connection to the server
+    MpcTls,
+    /// Signing of the prover's commitments
+    Finalize,
+}
+
+impl NotarizationPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Setup => "setup",
+            Self::MpcTls => "mpc_tls",
+            Self::Finalize => "finalize",
+        }
+    }
+}
+
+impl ClientType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Websocket => "websocket",
+        }
+    }
+}
+
+/// Prometheus metrics of the notarization sessions, exposed via the /metrics endpoint
+#[derive(Debug)]
+pub struct NotaryMetrics {
+    registry: Registry,
+    sessions_started: IntCounterVec,
+    sessions_completed: IntCounterVec,
+    sessions_failed: IntCounterVec,
+    phase_timeouts: IntCounterVec,
+    active_sessions: IntGauge,
+    phase_duration: HistogramVec,
+    bytes_sent: HistogramVec,
+    bytes_received: HistogramVec,
+}
+
+impl NotaryMetrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("notary".to_string()), None)
+            .expect("metrics namespace is valid");
+
+        let counter = |name: &str, help: &str| {
+            let counter = IntCounterVec::new(Opts::new(name, help), &["client_type"])
+                .expect("metric options are valid");
+            registry
+                .register(Box::new(counter.clone()))
+                .expect("metric is only registered once");
+            counter
+        };
+        let sessions_started = counter(
+            "sessions_started_total",
+            "Number of notarization sessions started",
+        );
+        let sessions_completed = counter(
+            "sessions_completed_total",
+            "Number of notarization sessions completed successfully",
+        );
+        let sessions_failed = counter(
+            "sessions_failed_total",
+            "Number of notarization sessions which failed",
+        );
+        let phase_timeouts = IntCounterVec::new(
+            Opts::new(
+                "notarization_phase_timeouts_total",
+                "Number of notarizations which timed out, per phase",
+            ),
+            &["phase"],
+        )
+        .expect("metric options are valid");
+        registry
+            .register(Box::new(phase_timeouts.clone()))
+            .expect("metric is only registered once");
+
+        let active_sessions = IntGauge::new(
+            "active_sessions",
+            "Number of notarization sessions currently running",
+        )
+        .expect("metric options are valid");
+        registry
+            .register(Box::new(active_sessions.clone()))
+            .expect("metric is only registered once");
+
+        let histogram = |name: &str, help: &str, label: &str, buckets: Vec<f64>| {
+            let histogram =
+                HistogramVec::new(HistogramOpts::new(name, help).buckets(buckets), &[label])
+                    .expect("metric options are valid");
+            registry
+                .register(Box::new(histogram.clone()))
+                .expect("metric is only registered once");
+            histogram
+        };
+        // From 100ms to ~7 min
+        let duration_buckets = exponential_buckets(0.1, 2.0, 13).unwrap();
+        // From 1 KiB to 1 GiB
+        let bytes_buckets = exponential_buckets(1024.0, 4.0, 11).unwrap();
+        let phase_duration = histogram(
+            "notarization_phase_duration_seconds",
+            "Duration of each phase of the notarization",
+            "phase",
+            duration_buckets,
+        );
+        let bytes_sent = histogram(
+            "session_bytes_sent",
+            "Number of bytes sent to the prover per notarization session",
+            "client_type",
+            bytes_buckets.clone(),
+        );
+        let bytes_received = histogram(
+            "session_bytes_received",
+            "Number of bytes received from the prover per notarization session",
+            "client_type",
+            bytes_buckets,
+        );
+
+        Self {
+            registry,
+            sessions_started,
+            sessions_completed,
+            sessions_failed,
+            phase_timeouts,
+            active_sessions,
+            phase_duration,
+            bytes_sent,
+            bytes_received,
+        }
+    }
+
+    /// Record the start of a session, the session is counted as active until the returned guard
+    /// is dropped
+    pub fn session_started(&self, client_type: &ClientType) -> ActiveSession {
+        self.sessions_started
+            .with_label_values(&[client_type.as_str()])
+            .inc();
+        self.active_sessions.inc();
+        ActiveSession {
+            active_sessions: self.active_sessions.clone(),
+        }
+    }
+
+    /// Record the outcome of a session
+    pub fn session_finished(&self, client_type: &ClientType, success: bool) {
+        let counter = if success {
+            &self.sessions_completed
+        } else {
+            &self.sessions_failed
+        };
+        counter.with_label_values(&[client_type.as_str()]).inc();
+    }
+
+    /// Record the duration of a notarization phase which started at `start`
+    pub fn phase_finished(&self, phase: NotarizationPhase, start: Instant) {
+        self.phase_duration
+            .with_label_values(&[phase.as_str()])
+            .observe(start.elapsed().as_secs_f64());
+    }
+
+    /// Record a notarization which did not complete a phase in time
+    pub fn phase_timed_out(&self, phase: NotarizationPhase) {
+        self.phase_timeouts
+            .with_label_values(&[phase.as_str()])
+            .inc();
+    }
+
+    /// Record the traffic of a session
+    pub fn session_traffic(&self, client_type: &ClientType, counter: &ByteCounter) {
+        self.bytes_sent
+            .with_label_values(&[client_type.as_str()])
+            .observe(counter.sent() as f64);
+        self.bytes_received
+            .with_label_values(&[client_type.as_str()])
+            .observe(counter.received() as f64);
+    }
+
+    /// Encode the metrics in the Prometheus text format
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("metrics can be encoded");
+        String::from_utf8(buffer).expect("metrics are valid utf-8")
+    }
+}
+
+impl Default for NotaryMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Guard of an active session, which decrements the active-session gauge when dropped
+#[derive(Debug)]
+pub struct ActiveSession {
+    active_sessions: IntGauge,
+}
+
+impl Drop for ActiveSession {
+    fn drop(&mut self) {
+        self.active_sessions.dec();
+    }
+}
+
+/// Number of bytes sent and received over a stream
+#[derive(Debug, Default)]
+pub struct ByteCounter {
+    sent: AtomicU64,
+    received: AtomicU64,
+}
+
+impl ByteCounter {
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    pub fn received(&self) -> u64 {
+        self.received.load(Ordering::Relaxed)
+    }
+}
+
+/// Stream wrapper which counts the bytes sent and received
+#[derive(Debug)]
+pub struct CountingStream<T> {
+    inner: T,
+    counter: Arc<ByteCounter>,
+}
+
+impl<T> CountingStream<T> {
+    pub fn new(inner: T, counter: Arc<ByteCounter>) -> Self {
+        Self { inner, counter }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for CountingStream<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            this.counter
+                .received
+                .fetch_add((buf.filled().len() - filled) as u64, Ordering::Relaxed);
+        }
+        poll
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for CountingStream<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = poll {
+            this.counter
+                .sent
+                .fetch_add(written as u64, Ordering::Relaxed);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_session_metrics() {
+        let metrics = NotaryMetrics::new();
+
+        let active_session = metrics.session_started(&ClientType::Tcp);
+        assert_eq!(metrics.active_sessions.get(), 1);
+        metrics.phase_finished(NotarizationPhase::Setup, Instant::now());
+        metrics.phase_timed_out(NotarizationPhase::MpcTls);
+        metrics.session_finished(&ClientType::Tcp, false);
+        drop(active_session);
+        assert_eq!(metrics.active_sessions.get(), 0);
+
+        let encoded = metrics.encode();
+        assert!(encoded.contains(r#"notary_sessions_started_total{client_type="tcp"} 1"#));
+        assert!(encoded.contains(r#"notary_sessions_failed_total{client_type="tcp"} 1"#));
+        assert!(encoded
+            .contains(r#"notary_notarization_phase_duration_seconds_count{phase="setup"} 1"#));
+        assert!(encoded.contains(r#"notary_notarization_phase_timeouts_total{phase="mpc_tls"} 1"#));
+        assert!(encoded.contains("notary_active_sessions 0"));
+    }
+
+    #[tokio::test]
+    async fn test_counting_stream() {
+        let (client, server) = tokio::io::duplex(1024);
+        let counter = Arc::new(ByteCounter::default());
+        let mut server = CountingStream::new(server, counter.clone());
+        let mut client = client;
+
+        server.write_all(b"hello").await.unwrap();
+        client.write_all(b"hi").await.unwrap();
+        let mut buf = [0u8; 2];
+        server.read_exact(&mut buf).await.unwrap();
+
+        assert_eq!(counter.sent(), 5);
+        assert_eq!(counter.received(), 2);
+    }
+}
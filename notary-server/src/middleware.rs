@@ -1,11 +1,13 @@
 use async_trait::async_trait;
 use axum::http::{header, request::Parts};
 use axum_core::extract::{FromRef, FromRequestParts};
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use std::{collections::HashMap, sync::Arc};
 use tracing::{error, trace};
 
 use crate::{
     domain::{auth::AuthorizationWhitelistRecord, notary::NotaryGlobals},
+    tls::ClientCertificate,
     NotaryServerError,
 };
 
@@ -22,28 +24,98 @@ where
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let notary_globals = NotaryGlobals::from_ref(state);
-        let Some(whitelist) = notary_globals.authorization_whitelist else {
-            trace!("Skipping authorization as whitelist is not set.");
-            return Ok(Self);
-        };
+        // Subject of the prover's certificate, which has been verified during the handshake when
+        // mutual TLS is turned on
+        let client_certificate_subject = parts
+            .extensions
+            .get::<ClientCertificate>()
+            .map(|certificate| certificate.subject.clone());
         let auth_header = parts
             .headers
             .get(header::AUTHORIZATION)
             .and_then(|value| std::str::from_utf8(value.as_bytes()).ok());
 
+        if let Some(jwt_authorizer) = notary_globals.jwt_authorizer {
+            let Some(token) = auth_header.and_then(bearer_token) else {
+                let err_msg = "Missing bearer token.".to_string();
+                error!(?client_certificate_subject, err_msg);
+                return Err(NotaryServerError::UnauthorizedProverRequest(err_msg));
+            };
+            return match jwt_authorizer.authorize(token) {
+                Ok(claims) => {
+                    trace!(
+                        subject = claims.sub,
+                        ?client_certificate_subject,
+                        "Request authorized."
+                    );
+                    // Make the claims available to the handlers
+                    parts.extensions.insert(claims);
+                    Ok(Self)
+                }
+                Err(err) => {
+                    let err_msg = format!("Invalid bearer token: {err}.");
+                    error!(?client_certificate_subject, err_msg);
+                    Err(NotaryServerError::UnauthorizedProverRequest(err_msg))
+                }
+            };
+        }
+
+        let Some(whitelist) = notary_globals.authorization_whitelist else {
+            trace!(
+                ?client_certificate_subject,
+                "Skipping authorization as whitelist is not set."
+            );
+            return Ok(Self);
+        };
+
         match auth_header {
             Some(auth_header) => {
-                if api_key_is_valid(auth_header, &whitelist) {
-                    trace!("Request authorized.");
+                if api_key_is_valid(auth_header, &whitelist.records(), Utc::now()) {
+                    trace!(?client_certificate_subject, "Request authorized.");
                     Ok(Self)
                 } else {
                     let err_msg = "Invalid API key.".to_string();
-                    error!(err_msg);
+                    error!(?client_certificate_subject, err_msg);
                     Err(NotaryServerError::UnauthorizedProverRequest(err_msg))
                 }
             }
             None => {
                 let err_msg = "Missing API key.".to_string();
+                error!(?client_certificate_subject, err_msg);
+                Err(NotaryServerError::UnauthorizedProverRequest(err_msg))
+            }
+        }
+    }
+}
+
+/// Bearer token required to access the admin API
+#[derive(Clone)]
+pub struct AdminToken(pub Arc<String>);
+
+/// Auth middleware of the admin API
+pub struct AdminAuthorizationMiddleware;
+
+#[async_trait]
+impl FromRequestParts<AdminToken> for AdminAuthorizationMiddleware {
+    type Rejection = NotaryServerError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        admin_token: &AdminToken,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| std::str::from_utf8(value.as_bytes()).ok())
+            .and_then(bearer_token);
+
+        match token {
+            Some(token) if tokens_eq(token.as_bytes(), admin_token.0.as_bytes()) => {
+                trace!("Admin request authorized.");
+                Ok(Self)
+            }
+            _ => {
+                let err_msg = "Invalid admin token.".to_string();
                 error!(err_msg);
                 Err(NotaryServerError::UnauthorizedProverRequest(err_msg))
             }
@@ -51,20 +123,42 @@ where
     }
 }
 
-/// Helper function to check if an API key is in whitelist
+/// Helper function to compare tokens in constant time
+fn tokens_eq(token: &[u8], expected: &[u8]) -> bool {
+    token.len() == expected.len()
+        && token
+            .iter()
+            .zip(expected)
+            .fold(0, |acc, (left, right)| acc | (left ^ right))
+            == 0
+}
+
+/// Helper function to extract the token from a bearer authorization header
+pub fn bearer_token(auth_header: &str) -> Option<&str> {
+    auth_header
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Helper function to check if an API key is in whitelist and has not expired
 fn api_key_is_valid(
     api_key: &str,
     whitelist: &HashMap<String, AuthorizationWhitelistRecord>,
+    now: DateTime<Utc>,
 ) -> bool {
-    whitelist.get(api_key).is_some()
+    whitelist
+        .get(api_key)
+        .map_or(false, |record| !record.is_expired(now))
 }
 
 #[cfg(test)]
 mod test {
-    use super::{api_key_is_valid, HashMap};
+    use super::{api_key_is_valid, bearer_token, tokens_eq, HashMap};
     use crate::domain::auth::{
         authorization_whitelist_vec_into_hashmap, AuthorizationWhitelistRecord,
     };
+    use chrono::{TimeZone, Utc};
     use std::sync::Arc;
 
     fn get_whitelist_fixture() -> HashMap<String, AuthorizationWhitelistRecord> {
@@ -73,16 +167,25 @@ mod test {
                 name: "test-name-0".to_string(),
                 api_key: "test-api-key-0".to_string(),
                 created_at: "2023-10-18T07:38:53Z".to_string(),
+                expires_at: None,
+                max_sessions_per_day: None,
+                max_transcript_size: None,
             },
             AuthorizationWhitelistRecord {
                 name: "test-name-1".to_string(),
                 api_key: "test-api-key-1".to_string(),
                 created_at: "2023-10-11T07:38:53Z".to_string(),
+                expires_at: None,
+                max_sessions_per_day: None,
+                max_transcript_size: None,
             },
             AuthorizationWhitelistRecord {
                 name: "test-name-2".to_string(),
                 api_key: "test-api-key-2".to_string(),
                 created_at: "2022-10-11T07:38:53Z".to_string(),
+                expires_at: Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()),
+                max_sessions_per_day: None,
+                max_transcript_size: None,
             },
         ])
     }
@@ -90,15 +193,44 @@ mod test {
     #[test]
     fn test_api_key_is_present() {
         let whitelist = get_whitelist_fixture();
-        assert!(api_key_is_valid("test-api-key-0", &Arc::new(whitelist)));
+        assert!(api_key_is_valid(
+            "test-api-key-0",
+            &Arc::new(whitelist),
+            Utc::now()
+        ));
     }
 
     #[test]
     fn test_api_key_is_absent() {
         let whitelist = get_whitelist_fixture();
         assert_eq!(
-            api_key_is_valid("test-api-keY-0", &Arc::new(whitelist)),
+            api_key_is_valid("test-api-keY-0", &Arc::new(whitelist), Utc::now()),
             false
         );
     }
+
+    #[test]
+    fn test_bearer_token() {
+        assert_eq!(bearer_token("Bearer abc.def.ghi"), Some("abc.def.ghi"));
+        assert_eq!(bearer_token("Bearer "), None);
+        assert_eq!(bearer_token("abc.def.ghi"), None);
+    }
+
+    #[test]
+    fn test_tokens_eq() {
+        assert!(tokens_eq(b"admin-token", b"admin-token"));
+        assert!(!tokens_eq(b"admin-tokem", b"admin-token"));
+        assert!(!tokens_eq(b"admin", b"admin-token"));
+    }
+
+    #[test]
+    fn test_api_key_is_expired() {
+        let whitelist = get_whitelist_fixture();
+        assert!(api_key_is_valid(
+            "test-api-key-2",
+            &whitelist,
+            Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()
+        ));
+        assert!(!api_key_is_valid("test-api-key-2", &whitelist, Utc::now()));
+    }
 }
@@ -0,0 +1,323 @@
+use chrono::{DateTime, Utc};
+use futures_util::future::{AbortHandle, AbortRegistration};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::watch;
+use tracing::warn;
+
+use crate::{
+    domain::{admin::RunningSessionInfo, notary::ClientType},
+    listener::ConnectionAddress,
+    metrics::ByteCounter,
+};
+
+/// A notarization which is currently running
+#[derive(Debug)]
+struct RunningSession {
+    client_type: ClientType,
+    remote_address: ConnectionAddress,
+    started_at: DateTime<Utc>,
+    byte_counter: Arc<ByteCounter>,
+    abort_handle: AbortHandle,
+}
+
+/// State of a session known to the registry
+#[derive(Debug)]
+enum SessionState {
+    /// Created via /session and not used yet, until it expires
+    Pending { expires_at: Instant },
+    /// Taken by /notarize, waiting for the protocol upgrade
+    Connecting,
+    /// Notarization running
+    Running(RunningSession),
+}
+
+/// Registry of the sessions of this server, from their creation until their notarization
+/// finishes, used by the admin API to inspect and cancel notarizations and to drain the server
+/// on shutdown
+#[derive(Debug)]
+pub struct SessionRegistry {
+    sessions: Arc<Mutex<HashMap<String, SessionState>>>,
+    session_ttl: Duration,
+    /// Signalled whenever a session is removed
+    finished: Arc<watch::Sender<()>>,
+}
+
+impl SessionRegistry {
+    /// Create a registry, where sessions which are not used within `session_ttl` are no longer
+    /// waited for
+    pub fn new(session_ttl: Duration) -> Self {
+        Self {
+            sessions: Default::default(),
+            session_ttl,
+            finished: Arc::new(watch::channel(()).0),
+        }
+    }
+
+    /// Register a session created via /session, which has not been used yet
+    pub fn reserve(&self, session_id: &str) {
+        self.sessions.lock().unwrap().insert(
+            session_id.to_string(),
+            SessionState::Pending {
+                expires_at: Instant::now() + self.session_ttl,
+            },
+        );
+    }
+
+    /// Remove a session which has not been used, e.g. when it is revoked
+    pub fn release(&self, session_id: &str) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if matches!(sessions.get(session_id), Some(SessionState::Pending { .. })) {
+            sessions.remove(session_id);
+            self.finished.send_replace(());
+        }
+    }
+
+    /// Claim a session for notarization, which stays registered until the returned guard is
+    /// dropped
+    ///
+    /// Sessions created by another replica sharing the session store are registered here.
+    pub fn claim(&self, session_id: &str) -> RegisteredSession {
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(session_id.to_string(), SessionState::Connecting);
+
+        RegisteredSession {
+            session_id: session_id.to_string(),
+            sessions: self.sessions.clone(),
+            finished: self.finished.clone(),
+        }
+    }
+
+    /// Snapshot of the running notarizations, sorted by start time
+    pub fn running_sessions(&self) -> Vec<RunningSessionInfo> {
+        let mut sessions: Vec<_> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(session_id, state)| match state {
+                SessionState::Running(session) => Some(RunningSessionInfo {
+                    session_id: session_id.clone(),
+                    client_type: session.client_type.clone(),
+                    remote_address: session.remote_address.to_string(),
+                    started_at: session.started_at,
+                    bytes_sent: session.byte_counter.sent(),
+                    bytes_received: session.byte_counter.received(),
+                }),
+                _ => None,
+            })
+            .collect();
+        sessions.sort_by_key(|session| session.started_at);
+        sessions
+    }
+
+    /// Cancel a running notarization, returns whether the session was running
+    pub fn cancel(&self, session_id: &str, reason: &str) -> bool {
+        match self.sessions.lock().unwrap().get(session_id) {
+            Some(SessionState::Running(session)) => {
+                warn!(?session_id, reason, "Cancelling notarization session");
+                session.abort_handle.abort();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Cancel all the running notarizations, returns the number of cancelled sessions
+    pub fn cancel_all(&self, reason: &str) -> usize {
+        let sessions = self.sessions.lock().unwrap();
+        let mut cancelled = 0;
+        for (session_id, state) in sessions.iter() {
+            if let SessionState::Running(session) = state {
+                warn!(?session_id, reason, "Cancelling notarization session");
+                session.abort_handle.abort();
+                cancelled += 1;
+            }
+        }
+        cancelled
+    }
+
+    /// Wait until all the notarizations have finished, including those of the sessions which
+    /// have been created but not used yet, unless they expire
+    pub async fn wait_until_finished(&self) {
+        // Subscribe before checking, so that a session finishing in between is not missed
+        let mut finished = self.finished.subscribe();
+        loop {
+            match self.outstanding() {
+                Outstanding::Nothing => return,
+                Outstanding::Active => {
+                    // The sender is owned by the registry, so the channel can not be closed here
+                    let _ = finished.changed().await;
+                }
+                Outstanding::Pending(expires_at) => {
+                    tokio::select! {
+                        _ = finished.changed() => {}
+                        _ = tokio::time::sleep_until(expires_at.into()) => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remove the expired pending sessions, and return what is left to wait for
+    fn outstanding(&self) -> Outstanding {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(
+            |_, state| !matches!(state, SessionState::Pending { expires_at } if *expires_at <= now),
+        );
+
+        let mut outstanding = Outstanding::Nothing;
+        for state in sessions.values() {
+            outstanding = match (state, outstanding) {
+                (SessionState::Connecting | SessionState::Running(_), _) => {
+                    return Outstanding::Active
+                }
+                (SessionState::Pending { expires_at }, Outstanding::Pending(next)) => {
+                    Outstanding::Pending(next.min(*expires_at))
+                }
+                (SessionState::Pending { expires_at }, _) => Outstanding::Pending(*expires_at),
+            };
+        }
+        outstanding
+    }
+}
+
+/// Sessions left to wait for when draining
+#[derive(Debug, Clone, Copy)]
+enum Outstanding {
+    Nothing,
+    /// Only pending sessions are left, the earliest of which expires at the given instant
+    Pending(Instant),
+    /// A session is connecting or running
+    Active,
+}
+
+/// Guard of a claimed session, which removes it from the registry when dropped
+#[derive(Debug)]
+pub struct RegisteredSession {
+    session_id: String,
+    sessions: Arc<Mutex<HashMap<String, SessionState>>>,
+    finished: Arc<watch::Sender<()>>,
+}
+
+impl RegisteredSession {
+    /// Mark the notarization of the session as running
+    ///
+    /// The notarization should be wrapped in [`futures_util::future::Abortable`] with the returned
+    /// registration, so that it can be cancelled.
+    pub fn start(
+        &self,
+        client_type: ClientType,
+        remote_address: ConnectionAddress,
+        byte_counter: Arc<ByteCounter>,
+    ) -> AbortRegistration {
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        self.sessions.lock().unwrap().insert(
+            self.session_id.clone(),
+            SessionState::Running(RunningSession {
+                client_type,
+                remote_address,
+                started_at: Utc::now(),
+                byte_counter,
+                abort_handle,
+            }),
+        );
+        abort_registration
+    }
+}
+
+impl Drop for RegisteredSession {
+    fn drop(&mut self) {
+        self.sessions.lock().unwrap().remove(&self.session_id);
+        self.finished.send_replace(());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures_util::future::Abortable;
+
+    fn address() -> ConnectionAddress {
+        ConnectionAddress::Tcp("127.0.0.1:7047".parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_session() {
+        let registry = SessionRegistry::new(Duration::from_secs(60));
+
+        registry.reserve("0");
+        assert!(registry.running_sessions().is_empty());
+        assert!(!registry.cancel("0", "test"));
+
+        let guard = registry.claim("0");
+        let registration = guard.start(ClientType::Tcp, address(), Default::default());
+        assert_eq!(registry.running_sessions().len(), 1);
+        assert_eq!(
+            registry.running_sessions()[0].remote_address,
+            "127.0.0.1:7047"
+        );
+
+        assert!(registry.cancel("0", "test"));
+        assert!(!registry.cancel("1", "test"));
+        let result = Abortable::new(std::future::pending::<()>(), registration).await;
+        assert!(result.is_err());
+
+        drop(guard);
+        assert!(registry.running_sessions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_finished() {
+        let registry = SessionRegistry::new(Duration::from_secs(60));
+        registry.wait_until_finished().await;
+
+        // A session which has been created but not used yet is waited for
+        registry.reserve("0");
+        registry.reserve("1");
+        let guard_0 = registry.claim("0");
+        let _ = guard_0.start(ClientType::Tcp, address(), Default::default());
+        let guard_1 = registry.claim("1");
+        let mut wait = Box::pin(registry.wait_until_finished());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), &mut wait)
+                .await
+                .is_err()
+        );
+
+        tokio::spawn(async move {
+            drop(guard_0);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(guard_1);
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), wait)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pending_sessions_expire() {
+        let registry = SessionRegistry::new(Duration::from_millis(10));
+
+        registry.reserve("0");
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            registry.wait_until_finished(),
+        )
+        .await
+        .unwrap();
+
+        // Released sessions are not waited for
+        let registry = SessionRegistry::new(Duration::from_secs(60));
+        registry.reserve("0");
+        registry.release("0");
+        registry.wait_until_finished().await;
+    }
+}
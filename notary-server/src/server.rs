@@ -1,112 +1,163 @@
 use axum::{
-    http::{Request, StatusCode},
+    extract::State,
+    http::{header, Request, StatusCode},
     middleware::from_extractor_with_state,
     response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
+use chrono::Utc;
 use eyre::{ensure, eyre, Result};
-use futures_util::future::poll_fn;
-use hyper::server::{
-    accept::Accept,
-    conn::{AddrIncoming, Http},
-};
-use p256::{ecdsa::SigningKey, pkcs8::DecodePrivateKey};
-use rustls::{Certificate, PrivateKey, ServerConfig};
-use std::{
-    fs::File as StdFile,
-    io::BufReader,
-    net::{IpAddr, SocketAddr},
-    pin::Pin,
-    sync::Arc,
-};
+use hyper::server::conn::Http;
+use rustls::ServerConfig;
+use std::{fs::File as StdFile, io::BufReader, sync::Arc, time::Duration};
 use tower_http::cors::CorsLayer;
 
-use tokio::{fs::File, net::TcpListener};
+use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncWrite},
+};
 use tokio_rustls::TlsAcceptor;
-use tower::MakeService;
-use tracing::{debug, error, info};
+use tower::{MakeService, ServiceExt};
+use tracing::{debug, error, info, warn};
 
 use crate::{
-    config::{NotaryServerProperties, NotarySigningKeyProperties},
-    domain::{
-        auth::{authorization_whitelist_vec_into_hashmap, AuthorizationWhitelistRecord},
-        notary::NotaryGlobals,
-        InfoResponse,
-    },
+    acme::spawn_acme_task,
+    auth::{spawn_whitelist_reload_task, AuthorizationWhitelist},
+    config::{AuthorizationMode, NotaryServerProperties},
+    domain::{notary::NotaryGlobals, InfoResponse},
     error::NotaryServerError,
-    middleware::AuthorizationMiddleware,
-    service::{initialize, upgrade_protocol},
-    util::parse_csv_file,
+    jwt::JwtAuthorizer,
+    limiter::SessionLimiter,
+    listener::{Connection, ConnectionAddress, Listener},
+    metrics::NotaryMetrics,
+    middleware::{AdminAuthorizationMiddleware, AdminToken, AuthorizationMiddleware},
+    registry::SessionRegistry,
+    service::{
+        admin::{cancel_session, list_sessions},
+        initialize, upgrade_protocol,
+    },
+    signer::FileNotarySigner,
+    store::{build_session_store, spawn_eviction_task},
+    tls::{
+        load_client_cert_verifier, spawn_certificate_reload_task, CertificateFiles,
+        CertificateResolver, ClientCertificate, ACME_TLS_ALPN_PROTOCOL,
+    },
 };
 
-/// Start a TCP server (with or without TLS) to accept notarization request for both TCP and WebSocket clients
+/// Start a server listening on TCP and/or Unix domain sockets (with or without TLS) to accept notarization request for both TCP and WebSocket clients
+///
+/// The server runs until the process receives SIGTERM or SIGINT, after which it drains the running
+/// notarizations (see [`crate::config::ServerProperties::shutdown_timeout_seconds`])
 #[tracing::instrument(skip(config))]
 pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotaryServerError> {
-    // Load the private key for notarized transcript signing
-    let notary_signing_key = load_notary_signing_key(&config.notary_key).await?;
+    // Load the keys for notarized transcript signing
+    let notary_signer = FileNotarySigner::load(&config.notary_key)?;
     // Build TLS acceptor if it is turned on
     let tls_acceptor = if !config.tls.enabled {
         debug!("Skipping TLS setup as it is turned off.");
         None
     } else {
-        let (tls_private_key, tls_certificates) = load_tls_key_and_cert(
-            &config.tls.private_key_pem_path,
-            &config.tls.certificate_pem_path,
-        )
-        .await?;
-
-        let mut server_config = ServerConfig::builder()
-            .with_safe_defaults()
-            .with_no_client_auth()
-            .with_single_cert(tls_certificates, tls_private_key)
-            .map_err(|err| eyre!("Failed to instantiate notary server tls config: {err}"))?;
-
+        // The certificate is looked up for every handshake, so that it can be swapped when it is
+        // renewed
+        let resolver = Arc::new(CertificateResolver::new());
         // Set the http protocols we support
-        server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        let mut alpn_protocols = vec![b"http/1.1".to_vec()];
+        if let Some(acme_config) = &config.tls.acme {
+            // Obtain the certificate in the background, the CA validates the domains via the
+            // listeners of the server
+            spawn_acme_task(acme_config.clone(), resolver.clone());
+            alpn_protocols.push(ACME_TLS_ALPN_PROTOCOL.to_vec());
+        } else {
+            let files = CertificateFiles::load(
+                &config.tls.private_key_pem_path,
+                &config.tls.certificate_pem_path,
+                resolver.clone(),
+            )?;
+            if let Some(interval) = config.tls.reload_interval_seconds {
+                spawn_certificate_reload_task(files, Duration::from_secs(interval));
+            }
+        }
+
+        let server_config = ServerConfig::builder().with_safe_defaults();
+        let server_config = match &config.tls.client_ca_pem_path {
+            // The ACME CA does not present a client certificate when validating a challenge, so
+            // anonymous clients are rejected after the handshake instead
+            Some(client_ca_pem_path) => server_config.with_client_cert_verifier(
+                load_client_cert_verifier(client_ca_pem_path, config.tls.acme.is_some())?,
+            ),
+            None => server_config.with_no_client_auth(),
+        };
+        let mut server_config = server_config.with_cert_resolver(resolver);
+        server_config.alpn_protocols = alpn_protocols;
         let tls_config = Arc::new(server_config);
         Some(TlsAcceptor::from(tls_config))
     };
 
+    // Load the JWT issuer key if the JWT authorization mode is turned on
+    let jwt_authorizer =
+        if !config.authorization.enabled || config.authorization.mode != AuthorizationMode::Jwt {
+            None
+        } else {
+            let jwt_config = config.authorization.jwt.as_ref().ok_or_else(|| {
+                eyre!("JWT setting is required when the authorization mode is jwt")
+            })?;
+            Some(Arc::new(JwtAuthorizer::load(jwt_config)?))
+        };
+
     // Load the authorization whitelist csv if it is turned on
     let authorization_whitelist = if !config.authorization.enabled {
         debug!("Skipping authorization as it is turned off.");
         None
+    } else if jwt_authorizer.is_some() {
+        debug!("Skipping authorization whitelist as the authorization mode is jwt.");
+        None
     } else {
-        // Load the csv
-        let whitelist_csv = parse_csv_file::<AuthorizationWhitelistRecord>(
+        // Load the csv, and reload it in the background whenever it changes
+        let whitelist = Arc::new(AuthorizationWhitelist::load(
             &config.authorization.whitelist_csv_path,
-        )
-        .map_err(|err| eyre!("Failed to parse authorization whitelist csv: {:?}", err))?;
-        // Convert the whitelist record into hashmap for faster lookup
-        Some(authorization_whitelist_vec_into_hashmap(whitelist_csv))
+            config.authorization.usage_db_path.as_deref(),
+        )?);
+        spawn_whitelist_reload_task(
+            whitelist.clone(),
+            Duration::from_secs(config.authorization.whitelist_reload_interval_seconds),
+        );
+        Some(whitelist)
     };
 
-    let notary_address = SocketAddr::new(
-        IpAddr::V4(config.server.host.parse().map_err(|err| {
-            eyre!("Failed to parse notary host address from server config: {err}")
-        })?),
-        config.server.port,
-    );
-    let listener = TcpListener::bind(notary_address)
-        .await
-        .map_err(|err| eyre!("Failed to bind server address to tcp listener: {err}"))?;
-    let mut listener = AddrIncoming::from_listener(listener)
-        .map_err(|err| eyre!("Failed to build hyper tcp listener: {err}"))?;
+    // Bind all the listeners before serving, so that an invalid address fails the startup
+    let mut listeners = Vec::new();
+    for address in config.server.listener_addresses()? {
+        listeners.push(Listener::bind(&address).await?);
+    }
 
-    info!("Listening for TCP traffic at {}", notary_address);
+    // Set up the session store, and evict sessions which are never used in the background
+    let session_store = build_session_store(&config.session_store)?;
+    spawn_eviction_task(
+        session_store.clone(),
+        Duration::from_secs(config.session_store.eviction_interval_seconds),
+    );
 
     let protocol = Arc::new(Http::new());
     let notary_globals = NotaryGlobals::new(
-        notary_signing_key,
+        Arc::new(notary_signer),
         config.notarization.clone(),
+        session_store,
+        Arc::new(SessionLimiter::new(
+            &config.concurrency,
+            Duration::from_secs(config.session_store.ttl_seconds),
+        )),
+        Arc::new(NotaryMetrics::new()),
         // Use Arc to prevent cloning the whitelist for every request
-        authorization_whitelist.map(Arc::new),
+        authorization_whitelist,
+        jwt_authorizer,
+        Arc::new(SessionRegistry::new(Duration::from_secs(
+            config.session_store.ttl_seconds,
+        ))),
     );
 
     // Parameters needed for the info endpoint
-    let public_key = std::fs::read_to_string(&config.notary_key.public_key_pem_path)
-        .map_err(|err| eyre!("Failed to load notary public signing key for notarization: {err}"))?;
     let version = env!("CARGO_PKG_VERSION").to_string();
     let git_commit_hash = env!("GIT_COMMIT_HASH").to_string();
     let git_commit_timestamp = env!("GIT_COMMIT_TIMESTAMP").to_string();
@@ -118,12 +169,29 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
         )
         .route(
             "/info",
-            get(|| async move {
+            get(|State(notary_globals): State<NotaryGlobals>| async move {
+                // Keys are looked up on every request as they may have been rotated
+                let now = Utc::now();
+                let signer = &notary_globals.notary_signer;
+                let signing_key = match signer.signing_key(now) {
+                    Ok(signing_key) => signing_key,
+                    Err(err) => return NotaryServerError::from(err).into_response(),
+                };
+                let active_keys = signer.active_keys(now);
+                let public_key = active_keys
+                    .iter()
+                    .find(|key| key.key_id == signing_key.id())
+                    .map(|key| key.public_key.clone())
+                    .unwrap_or_default();
+
                 (
                     StatusCode::OK,
                     Json(InfoResponse {
                         version,
                         public_key,
+                        key_id: signing_key.id().to_string(),
+                        active_keys,
+                        retired_keys: signer.retired_keys(now),
                         git_commit_hash,
                         git_commit_timestamp,
                     }),
@@ -133,7 +201,8 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
         )
         .route("/session", post(initialize))
         // Not applying auth middleware to /notarize endpoint for now as we can rely on our
-        // short-lived session id generated from /session endpoint, as it is not possible
+        // short-lived session id generated from /session endpoint (which is bound to the token
+        // subject in the jwt authorization mode), as it is not possible
         // to use header for API key for websocket /notarize endpoint due to browser restriction
         // ref: https://stackoverflow.com/a/4361358; And putting it in url query param
         // seems to be more insecured: https://stackoverflow.com/questions/5517281/place-api-key-in-headers-or-url
@@ -142,132 +211,303 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
             NotaryGlobals,
         >(notary_globals.clone()))
         .route("/notarize", get(upgrade_protocol))
+        // Not applying auth middleware to /metrics endpoint either, as metrics scrapers do not
+        // hold an API key or token; the metrics do not identify any prover
+        .route(
+            "/metrics",
+            get(|State(notary_globals): State<NotaryGlobals>| async move {
+                (
+                    StatusCode::OK,
+                    [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+                    notary_globals.metrics.encode(),
+                )
+                    .into_response()
+            }),
+        );
+
+    // Mount the admin API if it is turned on
+    let router = if !config.admin.enabled {
+        debug!("Skipping admin API as it is turned off.");
+        router
+    } else {
+        let admin_token = load_admin_token(&config.admin.token_path).await?;
+        router.merge(
+            Router::new()
+                .route("/admin/sessions", get(list_sessions))
+                .route("/admin/sessions/:session_id", delete(cancel_session))
+                .route_layer(from_extractor_with_state::<
+                    AdminAuthorizationMiddleware,
+                    AdminToken,
+                >(admin_token)),
+        )
+    };
+
+    let router = router
         .layer(CorsLayer::permissive())
-        .with_state(notary_globals);
-    let mut app = router.into_make_service();
+        .with_state(notary_globals.clone());
+    // Make the address of the prover available to the handlers
+    let app = router.into_make_service_with_connect_info::<ConnectionAddress>();
+
+    // Accept connections on each listener, with the same TLS setting and router
+    let require_client_certificate = config.tls.enabled && config.tls.client_ca_pem_path.is_some();
+    let accept_tasks: Vec<_> = listeners
+        .into_iter()
+        .map(|listener| {
+            tokio::spawn(accept_connections(
+                listener,
+                tls_acceptor.clone(),
+                require_client_certificate,
+                protocol.clone(),
+                app.clone(),
+            ))
+        })
+        .collect();
+
+    shutdown_signal().await;
+    // Keep accepting connections while draining, so that provers which already created a
+    // session can still connect to /notarize
+    drain(
+        &notary_globals,
+        Duration::from_secs(config.server.shutdown_timeout_seconds),
+    )
+    .await;
+    // Stop accepting connections, aborting the tasks drops the listeners
+    for task in accept_tasks {
+        task.abort();
+    }
+    info!("Notary server shut down");
+
+    Ok(())
+}
+
+type NotaryApp = IntoMakeServiceWithConnectInfo<Router, ConnectionAddress>;
 
+/// Accept the connections of a listener, ensure that all operations inside are infallible to prevent bringing down the server
+async fn accept_connections(
+    listener: Listener,
+    tls_acceptor: Option<TlsAcceptor>,
+    require_client_certificate: bool,
+    protocol: Arc<Http>,
+    app: NotaryApp,
+) {
     loop {
-        // Poll and await for any incoming connection, ensure that all operations inside are infallible to prevent bringing down the server
-        let (prover_address, stream) =
-            match poll_fn(|cx| Pin::new(&mut listener).poll_accept(cx)).await {
-                Some(Ok(connection)) => (connection.remote_addr(), connection),
-                Some(Err(err)) => {
-                    error!("{}", NotaryServerError::Connection(err.to_string()));
-                    continue;
-                }
-                None => unreachable!("The poll_accept method should never return None"),
-            };
-        debug!(?prover_address, "Received a prover's TCP connection");
+        let (connection, prover_address) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                error!("{}", NotaryServerError::Connection(err.to_string()));
+                // Back off as the error may persist, e.g. when running out of file descriptors
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        debug!(%prover_address, "Received a prover's connection");
 
         let tls_acceptor = tls_acceptor.clone();
         let protocol = protocol.clone();
-        let service = MakeService::<_, Request<hyper::Body>>::make_service(&mut app, &stream);
+        let app = app.clone();
 
         // Spawn a new async task to handle the new connection
         tokio::spawn(async move {
-            // When TLS is enabled
-            if let Some(acceptor) = tls_acceptor {
-                match acceptor.accept(stream).await {
-                    Ok(stream) => {
-                        info!(
-                            ?prover_address,
-                            "Accepted prover's TLS-secured TCP connection",
-                        );
-                        // Serve different requests using the same hyper protocol and axum router
-                        let _ = protocol
-                            // Can unwrap because it's infallible
-                            .serve_connection(stream, service.await.unwrap())
-                            // use with_upgrades to upgrade connection to websocket for websocket clients
-                            // and to extract tcp connection for tcp clients
-                            .with_upgrades()
-                            .await;
-                    }
-                    Err(err) => {
+            match connection {
+                Connection::Tcp(stream) => {
+                    serve_connection(
+                        stream,
+                        prover_address,
+                        tls_acceptor,
+                        require_client_certificate,
+                        protocol,
+                        app,
+                    )
+                    .await
+                }
+                #[cfg(unix)]
+                Connection::Unix(stream) => {
+                    serve_connection(
+                        stream,
+                        prover_address,
+                        tls_acceptor,
+                        require_client_certificate,
+                        protocol,
+                        app,
+                    )
+                    .await
+                }
+            }
+        });
+    }
+}
+
+/// Serve a prover's connection (with or without TLS), for both HTTP requests and the protocol
+/// upgrades of TCP and WebSocket clients
+///
+/// With mutual TLS, the subject of the prover's certificate is made available to the middlewares
+/// and handlers as a [`ClientCertificate`] extension.
+async fn serve_connection<S>(
+    stream: S,
+    prover_address: ConnectionAddress,
+    tls_acceptor: Option<TlsAcceptor>,
+    require_client_certificate: bool,
+    protocol: Arc<Http>,
+    mut app: NotaryApp,
+) where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let service =
+        MakeService::<_, Request<hyper::Body>>::make_service(&mut app, prover_address.clone());
+
+    // When TLS is enabled
+    if let Some(acceptor) = tls_acceptor {
+        match acceptor.accept(stream).await {
+            // The handshake of an ACME TLS-ALPN-01 challenge is all the CA needs
+            Ok(stream) if stream.get_ref().1.alpn_protocol() == Some(ACME_TLS_ALPN_PROTOCOL) => {
+                debug!(%prover_address, "Completed ACME challenge handshake");
+            }
+            Ok(stream) => {
+                let peer_certificate = stream
+                    .get_ref()
+                    .1
+                    .peer_certificates()
+                    .and_then(|certificates| certificates.first());
+                let client_certificate = match peer_certificate {
+                    Some(certificate) => match ClientCertificate::from_der(certificate) {
+                        Ok(client_certificate) => Some(client_certificate),
+                        Err(err) => {
+                            error!(
+                                %prover_address,
+                                "{}",
+                                NotaryServerError::Connection(err.to_string())
+                            );
+                            return;
+                        }
+                    },
+                    None if require_client_certificate => {
                         error!(
-                            ?prover_address,
+                            %prover_address,
                             "{}",
-                            NotaryServerError::Connection(err.to_string())
+                            NotaryServerError::Connection(
+                                "Prover did not present a client certificate".to_string()
+                            )
                         );
+                        return;
                     }
-                }
-            } else {
-                // When TLS is disabled
-                info!(?prover_address, "Accepted prover's TCP connection",);
+                    None => None,
+                };
+                let client_certificate_subject = client_certificate
+                    .as_ref()
+                    .map(|certificate| certificate.subject.as_str());
+                info!(
+                    %prover_address,
+                    ?client_certificate_subject,
+                    "Accepted prover's TLS-secured connection"
+                );
+
+                let service = service
+                    .await
+                    // Can unwrap because it's infallible
+                    .unwrap()
+                    .map_request(move |mut request: Request<hyper::Body>| {
+                        if let Some(client_certificate) = &client_certificate {
+                            request.extensions_mut().insert(client_certificate.clone());
+                        }
+                        request
+                    });
                 // Serve different requests using the same hyper protocol and axum router
                 let _ = protocol
-                    // Can unwrap because it's infallible
-                    .serve_connection(stream, service.await.unwrap())
+                    .serve_connection(stream, service)
                     // use with_upgrades to upgrade connection to websocket for websocket clients
                     // and to extract tcp connection for tcp clients
                     .with_upgrades()
                     .await;
             }
-        });
+            Err(err) => {
+                error!(
+                    %prover_address,
+                    "{}",
+                    NotaryServerError::Connection(err.to_string())
+                );
+            }
+        }
+    } else {
+        // When TLS is disabled
+        info!(%prover_address, "Accepted prover's connection");
+        // Serve different requests using the same hyper protocol and axum router
+        let _ = protocol
+            // Can unwrap because it's infallible
+            .serve_connection(stream, service.await.unwrap())
+            // use with_upgrades to upgrade connection to websocket for websocket clients
+            // and to extract tcp connection for tcp clients
+            .with_upgrades()
+            .await;
     }
 }
 
-/// Temporary function to load notary signing key from static file
-async fn load_notary_signing_key(config: &NotarySigningKeyProperties) -> Result<SigningKey> {
-    debug!("Loading notary server's signing key");
-
-    let notary_signing_key = SigningKey::read_pkcs8_pem_file(&config.private_key_pem_path)
-        .map_err(|err| eyre!("Failed to load notary signing key for notarization: {err}"))?;
-
-    debug!("Successfully loaded notary server's signing key!");
-    Ok(notary_signing_key)
-}
+/// Stop admitting new sessions, and wait for the notarizations of the sessions which have already
+/// been created to finish for up to the shutdown timeout before cancelling them
+///
+/// Sessions created via /session before the shutdown can still be used via /notarize until they
+/// expire.
+async fn drain(notary_globals: &NotaryGlobals, shutdown_timeout: Duration) {
+    notary_globals.session_limiter.close();
+    info!(
+        ?shutdown_timeout,
+        "Stopped admitting new sessions, waiting for notarizations to finish"
+    );
 
-/// Read a PEM-formatted file and return its buffer reader
-pub async fn read_pem_file(file_path: &str) -> Result<BufReader<StdFile>> {
-    let key_file = File::open(file_path).await?.into_std().await;
-    Ok(BufReader::new(key_file))
+    let registry = &notary_globals.session_registry;
+    if tokio::time::timeout(shutdown_timeout, registry.wait_until_finished())
+        .await
+        .is_err()
+    {
+        let cancelled = registry.cancel_all("Notary server is shutting down");
+        warn!(
+            cancelled,
+            "Cancelled notarizations still running after the shutdown timeout"
+        );
+    }
 }
 
-/// Load notary tls private key and cert from static files
-async fn load_tls_key_and_cert(
-    private_key_pem_path: &str,
-    certificate_pem_path: &str,
-) -> Result<(PrivateKey, Vec<Certificate>)> {
-    debug!("Loading notary server's tls private key and certificate");
-
-    let mut private_key_file_reader = read_pem_file(private_key_pem_path).await?;
-    let mut private_keys = rustls_pemfile::pkcs8_private_keys(&mut private_key_file_reader)?;
-    ensure!(
-        private_keys.len() == 1,
-        "More than 1 key found in the tls private key pem file"
-    );
-    let private_key = PrivateKey(private_keys.remove(0));
+/// Resolve when the process receives SIGTERM or SIGINT (Ctrl+C)
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl+C: {err}");
+            std::future::pending::<()>().await;
+        }
+    };
 
-    let mut certificate_file_reader = read_pem_file(certificate_pem_path).await?;
-    let certificates = rustls_pemfile::certs(&mut certificate_file_reader)?
-        .into_iter()
-        .map(Certificate)
-        .collect();
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                error!("Failed to listen for SIGTERM: {err}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-    debug!("Successfully loaded notary server's tls private key and certificate!");
-    Ok((private_key, certificates))
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl+C, shutting down"),
+        _ = terminate => info!("Received SIGTERM, shutting down"),
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    #[tokio::test]
-    async fn test_load_notary_key_and_cert() {
-        let private_key_pem_path = "./fixture/tls/notary.key";
-        let certificate_pem_path = "./fixture/tls/notary.crt";
-        let result: Result<(PrivateKey, Vec<Certificate>)> =
-            load_tls_key_and_cert(private_key_pem_path, certificate_pem_path).await;
-        assert!(result.is_ok(), "Could not load tls private key and cert");
-    }
+/// Load the bearer token of the admin API
+async fn load_admin_token(token_path: &str) -> Result<AdminToken> {
+    let token = tokio::fs::read_to_string(token_path)
+        .await
+        .map_err(|err| eyre!("Failed to read admin token: {err}"))?;
+    let token = token.trim();
+    ensure!(!token.is_empty(), "Admin token must not be empty");
+    Ok(AdminToken(Arc::new(token.to_string())))
+}
 
-    #[tokio::test]
-    async fn test_load_notary_signing_key() {
-        let config = NotarySigningKeyProperties {
-            private_key_pem_path: "./fixture/notary/notary.key".to_string(),
-            public_key_pem_path: "./fixture/notary/notary.pub".to_string(),
-        };
-        let result: Result<SigningKey> = load_notary_signing_key(&config).await;
-        assert!(result.is_ok(), "Could not load notary private key");
-    }
+/// Read a PEM-formatted file and return its buffer reader
+pub async fn read_pem_file(file_path: &str) -> Result<BufReader<StdFile>> {
+    let key_file = File::open(file_path).await?.into_std().await;
+    Ok(BufReader::new(key_file))
 }
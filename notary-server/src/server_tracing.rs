@@ -1,28 +1,57 @@
 use eyre::Result;
 use opentelemetry::{
     global,
-    sdk::{export::trace::stdout, propagation::TraceContextPropagator},
+    sdk::{propagation::TraceContextPropagator, trace, Resource},
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use tracing_subscriber::{
+    layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry,
 };
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
 
-use crate::config::NotaryServerProperties;
+use crate::config::{LogFormat, NotaryServerProperties};
 
 pub fn init_tracing(config: &NotaryServerProperties) -> Result<()> {
-    // Create a new OpenTelemetry pipeline
-    let tracer = stdout::new_pipeline().install_simple();
-
-    // Create a tracing layer with the configured tracer
-    let tracing_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+    // Export the spans to an OpenTelemetry collector if it is configured, so that each
+    // notarization becomes one trace
+    let tracing_layer = match &config.tracing.otlp {
+        Some(otlp_config) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(&otlp_config.endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", otlp_config.service_name.clone()),
+                ])))
+                .install_batch(opentelemetry::runtime::Tokio)?;
+            // Create a tracing layer with the configured tracer
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
 
     // Set the log level
     let env_filter_layer = EnvFilter::new(&config.tracing.default_level);
 
     // Format the log
-    let format_layer = tracing_subscriber::fmt::layer()
-        // Use a more compact, abbreviated log format
-        .compact()
-        .with_thread_ids(true)
-        .with_thread_names(true);
+    let format_layer = match config.tracing.format {
+        LogFormat::Compact => tracing_subscriber::fmt::layer()
+            // Use a more compact, abbreviated log format
+            .compact()
+            .with_thread_ids(true)
+            .with_thread_names(true)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            // Include the fields of the enclosing spans, e.g. the session id of the notarization
+            .with_span_list(true)
+            .with_thread_ids(true)
+            .with_thread_names(true)
+            .boxed(),
+    };
 
     // Set up context propagation
     global::set_text_map_propagator(TraceContextPropagator::default());
@@ -35,3 +64,8 @@ pub fn init_tracing(config: &NotaryServerProperties) -> Result<()> {
 
     Ok(())
 }
+
+/// Flush the spans which have not been exported yet, before the process exits
+pub fn shutdown_tracing() {
+    global::shutdown_tracer_provider();
+}
@@ -1,33 +1,45 @@
+pub mod admin;
 pub mod axum_websocket;
 pub mod tcp;
 pub mod websocket;
 
 use async_trait::async_trait;
 use axum::{
-    extract::{rejection::JsonRejection, FromRequestParts, Query, State},
-    http::{header, request::Parts, StatusCode},
+    extract::{rejection::JsonRejection, ConnectInfo, Extension, FromRequestParts, Query, State},
+    http::{header, request::Parts, HeaderMap, StatusCode},
     response::{IntoResponse, Json, Response},
 };
 use axum_macros::debug_handler;
 use chrono::Utc;
-use p256::ecdsa::{Signature, SigningKey};
-use tlsn_verifier::tls::{Verifier, VerifierConfig};
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+use tlsn_core::Signature;
+use tlsn_verifier::tls::{Verifier, VerifierConfig, DEFAULT_MAX_TRANSCRIPT_SIZE};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_util::compat::TokioAsyncReadCompatExt;
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, info_span, trace, Instrument};
 use uuid::Uuid;
 
 use crate::{
+    config::NotarizationTimeoutProperties,
     domain::notary::{
         NotarizationRequestQuery, NotarizationSessionRequest, NotarizationSessionResponse,
         NotaryGlobals, SessionData,
     },
     error::NotaryServerError,
+    jwt::JwtClaims,
+    listener::ConnectionAddress,
+    metrics::{NotarizationPhase, NotaryMetrics},
+    middleware::bearer_token,
     service::{
         axum_websocket::{header_eq, WebSocketUpgrade},
         tcp::{tcp_notarize, TcpUpgrade},
         websocket::websocket_notarize,
     },
+    signer::NotarySigner,
+    tls::ClientCertificate,
 };
 
 /// A wrapper enum to facilitate extracting TCP connection for either WebSocket or TCP clients,
@@ -72,38 +84,141 @@ pub async fn upgrade_protocol(
     protocol_upgrade: ProtocolUpgrade,
     State(notary_globals): State<NotaryGlobals>,
     Query(params): Query<NotarizationRequestQuery>,
+    ConnectInfo(remote_address): ConnectInfo<ConnectionAddress>,
+    client_certificate: Option<Extension<ClientCertificate>>,
+    headers: HeaderMap,
 ) -> Response {
     info!("Received upgrade protocol request");
     let session_id = params.session_id;
-    // Fetch the configuration data from the store using the session_id
-    // This also removes the configuration data from the store as each session_id can only be used once
-    let max_transcript_size = match notary_globals.store.lock().await.remove(&session_id) {
-        Some(data) => data.max_transcript_size,
-        None => {
-            let err_msg = format!("Session id {} does not exist", session_id);
-            error!(err_msg);
-            return NotaryServerError::BadProverRequest(err_msg).into_response();
+    // Fetch the configuration data from the store using the session_id, it is only removed from
+    // the store once the prover is authorized, so that a request with a wrong token or client
+    // certificate can not use up the session of another prover
+    let session = match notary_globals.store.get(&session_id).await {
+        Ok(Some(data)) => data,
+        Ok(None) => return missing_session(&session_id),
+        Err(err) => {
+            error!("Failed to fetch session {session_id}: {err}");
+            return NotaryServerError::from(err).into_response();
+        }
+    };
+    // Sessions created with a bearer token are bound to the token subject, so only a prover
+    // holding a token of that subject can notarize with the session id
+    if let Some(subject) = &session.subject {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(bearer_token);
+        let authorized = match (token, &notary_globals.jwt_authorizer) {
+            (Some(token), Some(jwt_authorizer)) => {
+                matches!(jwt_authorizer.authorize(token), Ok(claims) if &claims.sub == subject)
+            }
+            _ => false,
+        };
+        if !authorized {
+            let err_msg = "Bearer token does not match the subject of the session".to_string();
+            error!(?session_id, err_msg);
+            return NotaryServerError::UnauthorizedProverRequest(err_msg).into_response();
+        }
+        info!(
+            ?session_id,
+            subject, "Notarizing session of authorized subject"
+        );
+    }
+    // Sessions created over mutual TLS can only be used by a prover with a certificate of the same
+    // subject
+    if let Some(subject) = &session.client_certificate_subject {
+        let connection_subject =
+            client_certificate.map(|Extension(certificate)| certificate.subject);
+        if connection_subject.as_ref() != Some(subject) {
+            let err_msg =
+                "Client certificate does not match the subject of the session".to_string();
+            error!(?session_id, ?connection_subject, err_msg);
+            return NotaryServerError::UnauthorizedProverRequest(err_msg).into_response();
+        }
+        info!(
+            ?session_id,
+            client_certificate_subject = subject,
+            "Notarizing session of authenticated client"
+        );
+    }
+    // Remove the configuration data from the store as each session_id can only be used once
+    let session = match notary_globals.store.take(&session_id).await {
+        Ok(Some(data)) => data,
+        // The session was used by a concurrent request
+        Ok(None) => return missing_session(&session_id),
+        Err(err) => {
+            error!("Failed to fetch session {session_id}: {err}");
+            return NotaryServerError::from(err).into_response();
         }
     };
+    // Keep the session registered until the notarization finishes, or until the request fails
+    let registered_session = notary_globals.session_registry.claim(&session_id);
+    let max_transcript_size = session.max_transcript_size;
+    // Take the capacity reserved when the session was created, or acquire it if the session was
+    // created by another notary server replica sharing the same session store
+    let permit = match notary_globals.session_limiter.take_reservation(&session_id) {
+        Some(permit) => permit,
+        None => match notary_globals.session_limiter.acquire(None).await {
+            Ok(permit) => permit,
+            Err(err) => {
+                error!(
+                    ?session_id,
+                    "Failed to acquire capacity for notarization: {err}"
+                );
+                return NotaryServerError::from(err).into_response();
+            }
+        },
+    };
     // This completes the HTTP Upgrade request and returns a successful response to the client, meanwhile initiating the websocket or tcp connection
+    // The permit is held until the notarization finishes
     match protocol_upgrade {
-        ProtocolUpgrade::Ws(ws) => ws.on_upgrade(move |socket| {
-            websocket_notarize(socket, notary_globals, session_id, max_transcript_size)
+        ProtocolUpgrade::Ws(ws) => ws.on_upgrade(move |socket| async move {
+            websocket_notarize(
+                socket,
+                notary_globals,
+                session_id,
+                max_transcript_size,
+                remote_address,
+                registered_session,
+            )
+            .await;
+            drop(permit);
         }),
-        ProtocolUpgrade::Tcp(tcp) => tcp.on_upgrade(move |stream| {
-            tcp_notarize(stream, notary_globals, session_id, max_transcript_size)
+        ProtocolUpgrade::Tcp(tcp) => tcp.on_upgrade(move |stream| async move {
+            tcp_notarize(
+                stream,
+                notary_globals,
+                session_id,
+                max_transcript_size,
+                remote_address,
+                registered_session,
+            )
+            .await;
+            drop(permit);
         }),
     }
 }
 
+fn missing_session(session_id: &str) -> Response {
+    let err_msg = format!("Session id {} does not exist or has expired", session_id);
+    error!(err_msg);
+    NotaryServerError::BadProverRequest(err_msg).into_response()
+}
+
 /// Handler to initialize and configure notarization for both TCP and WebSocket clients
 #[debug_handler(state = NotaryGlobals)]
 pub async fn initialize(
     State(notary_globals): State<NotaryGlobals>,
+    headers: HeaderMap,
+    claims: Option<Extension<JwtClaims>>,
+    client_certificate: Option<Extension<ClientCertificate>>,
     payload: Result<Json<NotarizationSessionRequest>, JsonRejection>,
 ) -> impl IntoResponse {
+    let client_certificate_subject =
+        client_certificate.map(|Extension(certificate)| certificate.subject);
     info!(
         ?payload,
+        ?client_certificate_subject,
         "Received request for initializing a notarization session"
     );
 
@@ -128,16 +243,87 @@ pub async fn initialize(
         .into_response();
     }
 
+    // Ensure that the max_transcript_size submitted is not larger than the limit of the bearer token,
+    // a session which does not submit a size runs with the default size of the verifier
+    let claims = claims.map(|Extension(claims)| claims);
+    if let Some(limit) = claims
+        .as_ref()
+        .and_then(|claims| claims.max_transcript_size)
+    {
+        if payload
+            .max_transcript_size
+            .unwrap_or(DEFAULT_MAX_TRANSCRIPT_SIZE)
+            > limit
+        {
+            error!(
+                "Max transcript size requested {:?} exceeds the limit of the token {:?}",
+                payload.max_transcript_size, limit
+            );
+            return NotaryServerError::BadProverRequest(
+                "Max transcript size requested exceeds the limit of the token".to_string(),
+            )
+            .into_response();
+        }
+    }
+
+    // Acquire the capacity to run the session, API keys (or token subjects) are only limited when authorization is turned on
+    let api_key = match &claims {
+        Some(claims) => Some(claims.sub.as_str()),
+        None => notary_globals
+            .authorization_whitelist
+            .as_ref()
+            .and_then(|_| headers.get(header::AUTHORIZATION))
+            .and_then(|value| value.to_str().ok()),
+    };
+    let permit = match notary_globals.session_limiter.acquire(api_key).await {
+        Ok(permit) => permit,
+        Err(err) => {
+            error!("Failed to acquire capacity for a new session: {err}");
+            return NotaryServerError::from(err).into_response();
+        }
+    };
+
     let prover_session_id = Uuid::new_v4().to_string();
 
     // Store the configuration data in a temporary store
-    notary_globals.store.lock().await.insert(
-        prover_session_id.clone(),
-        SessionData {
-            max_transcript_size: payload.max_transcript_size,
-            created_at: Utc::now(),
-        },
-    );
+    if let Err(err) = notary_globals
+        .store
+        .insert(
+            prover_session_id.clone(),
+            SessionData {
+                max_transcript_size: payload.max_transcript_size,
+                created_at: Utc::now(),
+                subject: claims.as_ref().map(|claims| claims.sub.clone()),
+                client_certificate_subject,
+            },
+        )
+        .await
+    {
+        error!("Failed to store session {prover_session_id}: {err}");
+        return NotaryServerError::from(err).into_response();
+    }
+
+    // Enforce the quotas of the API key once the session is stored, so that a session which
+    // could not be created does not count towards the daily quota
+    if let (Some(whitelist), Some(api_key)) = (&notary_globals.authorization_whitelist, api_key) {
+        if let Err(err) = whitelist
+            .consume_session(api_key, payload.max_transcript_size)
+            .await
+        {
+            error!("API key quota check failed: {err}");
+            // The id of the session is not returned, so remove it instead of waiting for it to expire
+            if let Err(err) = notary_globals.store.take(&prover_session_id).await {
+                error!("Failed to remove session {prover_session_id}: {err}");
+            }
+            return NotaryServerError::from(err).into_response();
+        }
+    }
+
+    notary_globals
+        .session_limiter
+        .reserve(prover_session_id.clone(), permit);
+    // Register the session, so that the server keeps serving it when it drains on shutdown
+    notary_globals.session_registry.reserve(&prover_session_id);
 
     trace!("Latest store state: {:?}", notary_globals.store);
 
@@ -154,15 +340,32 @@ pub async fn initialize(
 /// Run the notarization
 pub async fn notary_service<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
     socket: T,
-    signing_key: &SigningKey,
+    signer: &dyn NotarySigner,
+    metrics: &NotaryMetrics,
     session_id: &str,
     max_transcript_size: Option<usize>,
+    timeouts: &NotarizationTimeoutProperties,
 ) -> Result<(), NotaryServerError> {
     debug!(?session_id, "Starting notarization...");
 
+    // Select the signing key when the session starts, so that a rotation during the
+    // notarization does not change the key id embedded in the session header
+    let signing_key = signer.signing_key(Utc::now())?;
+    debug!(
+        ?session_id,
+        key_id = signing_key.id(),
+        "Selected notary signing key"
+    );
+
     let mut config_builder = VerifierConfig::builder();
 
-    config_builder = config_builder.id(session_id);
+    // Bound the time a stalled prover can hold the resources of the session
+    config_builder = config_builder
+        .id(session_id)
+        .notary_key_id(signing_key.id())
+        .setup_timeout(Duration::from_secs(timeouts.setup_seconds))
+        .mpc_tls_timeout(Duration::from_secs(timeouts.mpc_tls_seconds))
+        .finalize_timeout(Duration::from_secs(timeouts.finalize_seconds));
 
     if let Some(max_transcript_size) = max_transcript_size {
         config_builder = config_builder.max_transcript_size(max_transcript_size);
@@ -170,9 +373,47 @@ pub async fn notary_service<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
 
     let config = config_builder.build()?;
 
-    Verifier::new(config)
-        .notarize::<_, Signature>(socket.compat(), signing_key)
-        .await?;
+    // Run each phase separately to record its duration
+    let verifier = run_phase(
+        metrics,
+        NotarizationPhase::Setup,
+        Verifier::new(config).setup(socket.compat()),
+    )
+    .await?;
+
+    let verifier = run_phase(metrics, NotarizationPhase::MpcTls, verifier.run()).await?;
+
+    run_phase(
+        metrics,
+        NotarizationPhase::Finalize,
+        verifier
+            .start_notarize()
+            .finalize::<Signature>(&signing_key),
+    )
+    .await?;
 
     Ok(())
 }
+
+/// Run a phase of the notarization, recording its duration or whether it timed out
+async fn run_phase<T, E>(
+    metrics: &NotaryMetrics,
+    phase: NotarizationPhase,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, NotaryServerError>
+where
+    NotaryServerError: From<E>,
+{
+    let start = Instant::now();
+    // The spans of the verifier during this phase are nested under the span of the phase
+    let span = info_span!("notarization_phase", phase = phase.as_str());
+    let output = future.instrument(span).await.map_err(|err| {
+        let err = NotaryServerError::from(err);
+        if err.is_timeout() {
+            metrics.phase_timed_out(phase);
+        }
+        err
+    })?;
+    metrics.phase_finished(phase, start);
+    Ok(output)
+}
@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::Utc;
+use tracing::{error, info, warn};
+
+use crate::{
+    domain::{
+        admin::{CancelSessionQuery, PendingSessionInfo, SessionsResponse},
+        notary::NotaryGlobals,
+    },
+    error::NotaryServerError,
+};
+
+/// Handler to list the sessions created via /session which have not been used yet, and the
+/// notarizations which are currently running
+pub async fn list_sessions(State(notary_globals): State<NotaryGlobals>) -> Response {
+    let mut pending: Vec<_> = match notary_globals.store.list(Utc::now()).await {
+        Ok(sessions) => sessions
+            .into_iter()
+            .map(|(session_id, data)| PendingSessionInfo {
+                session_id,
+                max_transcript_size: data.max_transcript_size,
+                created_at: data.created_at,
+                subject: data.subject,
+                client_certificate_subject: data.client_certificate_subject,
+            })
+            .collect(),
+        Err(err) => {
+            error!("Failed to list sessions: {err}");
+            return NotaryServerError::from(err).into_response();
+        }
+    };
+    pending.sort_by_key(|session| session.created_at);
+
+    (
+        StatusCode::OK,
+        Json(SessionsResponse {
+            pending,
+            running: notary_globals.session_registry.running_sessions(),
+        }),
+    )
+        .into_response()
+}
+
+/// Handler to cancel a running notarization by aborting its task, or to revoke a session which
+/// has not been used yet
+pub async fn cancel_session(
+    State(notary_globals): State<NotaryGlobals>,
+    Path(session_id): Path<String>,
+    Query(query): Query<CancelSessionQuery>,
+) -> Response {
+    let reason = query.reason.as_deref().unwrap_or("No reason given");
+    if notary_globals.session_registry.cancel(&session_id, reason) {
+        return StatusCode::NO_CONTENT.into_response();
+    }
+
+    match notary_globals.store.take(&session_id).await {
+        Ok(Some(_)) => {
+            // Release the capacity reserved for the session
+            notary_globals.session_limiter.take_reservation(&session_id);
+            notary_globals.session_registry.release(&session_id);
+            warn!(?session_id, reason, "Revoked pending notarization session");
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(None) => {
+            info!(?session_id, "Session to cancel does not exist");
+            (
+                StatusCode::NOT_FOUND,
+                format!("Session id {session_id} does not exist or has finished"),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            error!("Failed to revoke session {session_id}: {err}");
+            NotaryServerError::from(err).into_response()
+        }
+    }
+}
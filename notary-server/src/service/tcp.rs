@@ -5,11 +5,19 @@ use axum::{
     http::{header, request::Parts, HeaderValue, StatusCode},
     response::Response,
 };
+use futures_util::future::Abortable;
 use hyper::upgrade::{OnUpgrade, Upgraded};
-use std::future::Future;
+use std::{future::Future, sync::Arc};
 use tracing::{debug, error, info};
 
-use crate::{domain::notary::NotaryGlobals, service::notary_service, NotaryServerError};
+use crate::{
+    domain::notary::{ClientType, NotaryGlobals},
+    listener::ConnectionAddress,
+    metrics::{ByteCounter, CountingStream},
+    registry::RegisteredSession,
+    service::notary_service,
+    NotaryServerError,
+};
 
 /// Custom extractor used to extract underlying TCP connection for TCP client — using the same upgrade primitives used by
 /// the WebSocket implementation where the underlying TCP connection (wrapped in an Upgraded object) only gets polled as an OnUpgrade future
@@ -76,21 +84,46 @@ impl TcpUpgrade {
 }
 
 /// Perform notarization using the extracted tcp connection
+///
+/// Each notarization is the root span of its own trace, which carries the session id
+#[tracing::instrument(
+    name = "notarization",
+    parent = None,
+    skip_all,
+    fields(%session_id, client_type = "tcp", %remote_address)
+)]
 pub async fn tcp_notarize(
     stream: Upgraded,
     notary_globals: NotaryGlobals,
     session_id: String,
     max_transcript_size: Option<usize>,
+    remote_address: ConnectionAddress,
+    registered_session: RegisteredSession,
 ) {
     debug!(?session_id, "Upgraded to tcp connection");
-    match notary_service(
-        stream,
-        &notary_globals.notary_signing_key,
-        &session_id,
-        max_transcript_size,
+    let metrics = &notary_globals.metrics;
+    let _active_session = metrics.session_started(&ClientType::Tcp);
+    let byte_counter = Arc::new(ByteCounter::default());
+    // Mark the notarization as running so that it can be inspected and cancelled via the admin
+    // API, the session stays registered until the notarization finishes
+    let abort_registration =
+        registered_session.start(ClientType::Tcp, remote_address, byte_counter.clone());
+    let result = Abortable::new(
+        notary_service(
+            CountingStream::new(stream, byte_counter.clone()),
+            notary_globals.notary_signer.as_ref(),
+            metrics,
+            &session_id,
+            max_transcript_size,
+            &notary_globals.notarization_config.timeouts,
+        ),
+        abort_registration,
     )
     .await
-    {
+    .unwrap_or(Err(NotaryServerError::Cancelled));
+    metrics.session_traffic(&ClientType::Tcp, &byte_counter);
+    metrics.session_finished(&ClientType::Tcp, result.is_ok());
+    match result {
         Ok(_) => {
             info!(?session_id, "Successful notarization using tcp!");
         }
@@ -1,29 +1,60 @@
+use futures_util::future::Abortable;
+use std::sync::Arc;
 use tracing::{debug, error, info};
 use ws_stream_tungstenite::WsStream;
 
 use crate::{
-    domain::notary::NotaryGlobals,
+    domain::notary::{ClientType, NotaryGlobals},
+    error::NotaryServerError,
+    listener::ConnectionAddress,
+    metrics::{ByteCounter, CountingStream},
+    registry::RegisteredSession,
     service::{axum_websocket::WebSocket, notary_service},
 };
 
 /// Perform notarization using the established websocket connection
+///
+/// Each notarization is the root span of its own trace, which carries the session id
+#[tracing::instrument(
+    name = "notarization",
+    parent = None,
+    skip_all,
+    fields(%session_id, client_type = "websocket", %remote_address)
+)]
 pub async fn websocket_notarize(
     socket: WebSocket,
     notary_globals: NotaryGlobals,
     session_id: String,
     max_transcript_size: Option<usize>,
+    remote_address: ConnectionAddress,
+    registered_session: RegisteredSession,
 ) {
     debug!(?session_id, "Upgraded to websocket connection");
     // Wrap the websocket in WsStream so that we have AsyncRead and AsyncWrite implemented
     let stream = WsStream::new(socket.into_inner());
-    match notary_service(
-        stream,
-        &notary_globals.notary_signing_key,
-        &session_id,
-        max_transcript_size,
+    let metrics = &notary_globals.metrics;
+    let _active_session = metrics.session_started(&ClientType::Websocket);
+    let byte_counter = Arc::new(ByteCounter::default());
+    // Mark the notarization as running so that it can be inspected and cancelled via the admin
+    // API, the session stays registered until the notarization finishes
+    let abort_registration =
+        registered_session.start(ClientType::Websocket, remote_address, byte_counter.clone());
+    let result = Abortable::new(
+        notary_service(
+            CountingStream::new(stream, byte_counter.clone()),
+            notary_globals.notary_signer.as_ref(),
+            metrics,
+            &session_id,
+            max_transcript_size,
+            &notary_globals.notarization_config.timeouts,
+        ),
+        abort_registration,
     )
     .await
-    {
+    .unwrap_or(Err(NotaryServerError::Cancelled));
+    metrics.session_traffic(&ClientType::Websocket, &byte_counter);
+    metrics.session_finished(&ClientType::Websocket, result.is_ok());
+    match result {
         Ok(_) => {
             info!(?session_id, "Successful notarization using websocket!");
         }
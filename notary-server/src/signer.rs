@@ -0,0 +1,378 @@
+use chrono::{DateTime, Utc};
+use eyre::{ensure, eyre, Result};
+use p256::{
+    ecdsa::signature::Signer,
+    pkcs8::{DecodePrivateKey, EncodePublicKey, LineEnding},
+};
+use std::{collections::HashSet, fmt::Debug, sync::Arc};
+use tlsn_core::{Secp256k1EthSigner, Signature};
+use tracing::debug;
+
+use crate::{config::NotarySigningKeyProperties, domain::PublicKeyInfo};
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotarySignerError {
+    #[error("No notary signing key is active at {0}")]
+    NoActiveKey(DateTime<Utc>),
+}
+
+/// A key used to sign notarizations, together with its key id
+#[derive(Clone)]
+pub struct NotarySigningKey {
+    id: String,
+    key: Arc<dyn Signer<Signature> + Send + Sync>,
+}
+
+impl NotarySigningKey {
+    pub fn new(id: impl Into<String>, key: impl Signer<Signature> + Send + Sync + 'static) -> Self {
+        Self {
+            id: id.into(),
+            key: Arc::new(key),
+        }
+    }
+
+    /// Id of the key, which is embedded in the signed session header
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Debug for NotarySigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NotarySigningKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Signer<Signature> for NotarySigningKey {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, p256::ecdsa::Error> {
+        self.key.try_sign(msg)
+    }
+}
+
+/// Backend which provides the keys used to sign notarizations
+///
+/// All methods take the current time so that keys can be rotated on a schedule.
+pub trait NotarySigner: Debug + Send + Sync {
+    /// Returns the key which should sign a notarization started at `time`
+    fn signing_key(&self, time: DateTime<Utc>) -> Result<NotarySigningKey, NotarySignerError>;
+    /// Returns the public keys which are in use at `time`, the signing key included
+    fn active_keys(&self, time: DateTime<Utc>) -> Vec<PublicKeyInfo>;
+    /// Returns the public keys which are no longer used for signing at `time`, but are still
+    /// published so that earlier notarizations can be verified
+    fn retired_keys(&self, time: DateTime<Utc>) -> Vec<PublicKeyInfo>;
+}
+
+/// A private key of any of the signature schemes supported by the notary
+enum NotaryPrivateKey {
+    P256(p256::ecdsa::SigningKey),
+    Ed25519(ed25519_dalek::SigningKey),
+    Secp256k1(Secp256k1EthSigner),
+}
+
+impl NotaryPrivateKey {
+    /// Parse a PKCS#8 PEM private key, the scheme is determined by the algorithm of the key
+    fn from_pkcs8_pem(pem: &str) -> Result<Self> {
+        if let Ok(key) = p256::ecdsa::SigningKey::from_pkcs8_pem(pem) {
+            Ok(Self::P256(key))
+        } else if let Ok(key) = ed25519_dalek::SigningKey::from_pkcs8_pem(pem) {
+            Ok(Self::Ed25519(key))
+        } else if let Ok(key) = k256::ecdsa::SigningKey::from_pkcs8_pem(pem) {
+            Ok(Self::Secp256k1(Secp256k1EthSigner::new(key)))
+        } else {
+            Err(eyre!(
+                "key is not a PKCS#8 P-256, Ed25519 or secp256k1 private key"
+            ))
+        }
+    }
+
+    fn to_public_key_pem(&self) -> Result<String> {
+        match self {
+            Self::P256(key) => key.verifying_key().to_public_key_pem(LineEnding::LF),
+            Self::Ed25519(key) => key.verifying_key().to_public_key_pem(LineEnding::LF),
+            Self::Secp256k1(key) => key.verifying_key().to_public_key_pem(LineEnding::LF),
+        }
+        .map_err(|err| eyre!("Failed to encode notary public key: {err}"))
+    }
+}
+
+impl Signer<Signature> for NotaryPrivateKey {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, p256::ecdsa::Error> {
+        Ok(match self {
+            Self::P256(key) => Signer::<p256::ecdsa::Signature>::try_sign(key, msg)?.into(),
+            Self::Ed25519(key) => Signer::<ed25519_dalek::Signature>::try_sign(key, msg)?.into(),
+            Self::Secp256k1(key) => key.try_sign(msg)?.into(),
+        })
+    }
+}
+
+/// A signing key loaded from a PEM file
+#[derive(Debug)]
+struct FileKey {
+    key: NotarySigningKey,
+    public_key: String,
+    active_from: Option<DateTime<Utc>>,
+    retire_at: Option<DateTime<Utc>>,
+}
+
+impl FileKey {
+    fn is_active(&self, time: DateTime<Utc>) -> bool {
+        self.active_from
+            .map_or(true, |active_from| active_from <= time)
+            && !self.is_retired(time)
+    }
+
+    fn is_retired(&self, time: DateTime<Utc>) -> bool {
+        self.retire_at.map_or(false, |retire_at| retire_at <= time)
+    }
+
+    fn info(&self) -> PublicKeyInfo {
+        PublicKeyInfo {
+            key_id: self.key.id().to_string(),
+            public_key: self.public_key.clone(),
+        }
+    }
+}
+
+/// Notary signer which loads P-256, Ed25519 or secp256k1 PKCS#8 PEM keys from disk
+///
+/// Notarizations signed with a secp256k1 key carry an Ethereum-style recoverable signature.
+///
+/// Among the keys which are active, the one which was activated most recently signs new
+/// notarizations.
+#[derive(Debug)]
+pub struct FileNotarySigner {
+    keys: Vec<FileKey>,
+    retired_keys: Vec<PublicKeyInfo>,
+}
+
+impl FileNotarySigner {
+    /// Load the signing keys and retired public keys from the files in the config
+    pub fn load(config: &NotarySigningKeyProperties) -> Result<Self> {
+        debug!("Loading notary server's signing keys");
+
+        let mut ids = HashSet::new();
+        let mut keys = Vec::with_capacity(config.keys.len());
+        for key_config in &config.keys {
+            ensure!(
+                ids.insert(key_config.id.clone()),
+                "Duplicate notary key id: {}",
+                key_config.id
+            );
+
+            let signing_key = std::fs::read_to_string(&key_config.private_key_pem_path)
+                .map_err(|err| eyre!(err))
+                .and_then(|pem| NotaryPrivateKey::from_pkcs8_pem(&pem))
+                .map_err(|err| {
+                    eyre!("Failed to load notary signing key {}: {err}", key_config.id)
+                })?;
+            let public_key = signing_key.to_public_key_pem()?;
+
+            keys.push(FileKey {
+                key: NotarySigningKey::new(key_config.id.clone(), signing_key),
+                public_key,
+                active_from: key_config.active_from,
+                retire_at: key_config.retire_at,
+            });
+        }
+
+        let mut retired_keys = Vec::with_capacity(config.retired_keys.len());
+        for key_config in &config.retired_keys {
+            ensure!(
+                ids.insert(key_config.id.clone()),
+                "Duplicate notary key id: {}",
+                key_config.id
+            );
+
+            let public_key =
+                std::fs::read_to_string(&key_config.public_key_pem_path).map_err(|err| {
+                    eyre!(
+                        "Failed to load retired notary public key {}: {err}",
+                        key_config.id
+                    )
+                })?;
+
+            retired_keys.push(PublicKeyInfo {
+                key_id: key_config.id.clone(),
+                public_key,
+            });
+        }
+
+        ensure!(!keys.is_empty(), "No notary signing key is configured");
+
+        debug!("Successfully loaded notary server's signing keys!");
+        Ok(Self { keys, retired_keys })
+    }
+}
+
+impl NotarySigner for FileNotarySigner {
+    fn signing_key(&self, time: DateTime<Utc>) -> Result<NotarySigningKey, NotarySignerError> {
+        self.keys
+            .iter()
+            .filter(|key| key.is_active(time))
+            // Keys without an activation time are the oldest, later keys in the config win ties
+            .max_by_key(|key| key.active_from)
+            .map(|key| key.key.clone())
+            .ok_or(NotarySignerError::NoActiveKey(time))
+    }
+
+    fn active_keys(&self, time: DateTime<Utc>) -> Vec<PublicKeyInfo> {
+        self.keys
+            .iter()
+            .filter(|key| key.is_active(time))
+            .map(FileKey::info)
+            .collect()
+    }
+
+    fn retired_keys(&self, time: DateTime<Utc>) -> Vec<PublicKeyInfo> {
+        self.keys
+            .iter()
+            .filter(|key| key.is_retired(time))
+            .map(FileKey::info)
+            .chain(self.retired_keys.iter().cloned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::{NotaryKeyProperties, RetiredNotaryKeyProperties};
+    use chrono::TimeZone;
+
+    fn time(year: i32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn key(id: &str, active_from: Option<i32>, retire_at: Option<i32>) -> NotaryKeyProperties {
+        NotaryKeyProperties {
+            id: id.to_string(),
+            private_key_pem_path: "./fixture/notary/notary.key".to_string(),
+            active_from: active_from.map(time),
+            retire_at: retire_at.map(time),
+        }
+    }
+
+    fn load_key_file(name: &str) -> FileNotarySigner {
+        let config = NotarySigningKeyProperties {
+            keys: vec![NotaryKeyProperties {
+                private_key_pem_path: format!("./fixture/notary/{name}.key"),
+                ..key("0", None, None)
+            }],
+            retired_keys: vec![],
+        };
+        let signer = FileNotarySigner::load(&config).unwrap();
+
+        let expected_public_key =
+            std::fs::read_to_string(format!("./fixture/notary/{name}.pub")).unwrap();
+        assert_eq!(
+            signer.active_keys(Utc::now())[0].public_key.trim(),
+            expected_public_key.trim()
+        );
+
+        signer
+    }
+
+    fn ids(keys: Vec<PublicKeyInfo>) -> Vec<String> {
+        keys.into_iter().map(|key| key.key_id).collect()
+    }
+
+    #[test]
+    fn test_load_notary_signer() {
+        let config = NotarySigningKeyProperties {
+            keys: vec![key("0", None, None)],
+            retired_keys: vec![RetiredNotaryKeyProperties {
+                id: "old".to_string(),
+                public_key_pem_path: "./fixture/notary/notary.pub".to_string(),
+            }],
+        };
+        let signer = FileNotarySigner::load(&config).unwrap();
+
+        let expected_public_key = std::fs::read_to_string("./fixture/notary/notary.pub").unwrap();
+        let active_keys = signer.active_keys(Utc::now());
+        assert_eq!(active_keys.len(), 1);
+        assert_eq!(active_keys[0].public_key.trim(), expected_public_key.trim());
+        assert_eq!(ids(signer.retired_keys(Utc::now())), vec!["old"]);
+        assert_eq!(signer.signing_key(Utc::now()).unwrap().id(), "0");
+    }
+
+    #[test]
+    fn test_signature_schemes() {
+        let sign = |name: &str| {
+            load_key_file(name)
+                .signing_key(Utc::now())
+                .unwrap()
+                .sign(b"session header")
+        };
+
+        assert!(matches!(sign("notary"), Signature::P256(_)));
+        assert!(matches!(sign("notary-ed25519"), Signature::Ed25519(_)));
+        assert!(matches!(
+            sign("notary-secp256k1"),
+            Signature::Secp256k1Eth(_)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_key_file() {
+        let config = NotarySigningKeyProperties {
+            keys: vec![NotaryKeyProperties {
+                private_key_pem_path: "./fixture/notary/notary.pub".to_string(),
+                ..key("0", None, None)
+            }],
+            retired_keys: vec![],
+        };
+        assert!(FileNotarySigner::load(&config).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_key_id() {
+        let config = NotarySigningKeyProperties {
+            keys: vec![key("0", None, None), key("0", Some(2030), None)],
+            retired_keys: vec![],
+        };
+        assert!(FileNotarySigner::load(&config).is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let config = NotarySigningKeyProperties {
+            keys: vec![
+                key("0", None, Some(2025)),
+                key("1", Some(2024), None),
+                key("2", Some(2030), None),
+            ],
+            retired_keys: vec![],
+        };
+        let signer = FileNotarySigner::load(&config).unwrap();
+
+        assert_eq!(signer.signing_key(time(2023)).unwrap().id(), "0");
+        assert_eq!(ids(signer.active_keys(time(2023))), vec!["0"]);
+
+        // Both keys are published while they overlap, but the newer one signs
+        assert_eq!(signer.signing_key(time(2024)).unwrap().id(), "1");
+        assert_eq!(ids(signer.active_keys(time(2024))), vec!["0", "1"]);
+        assert!(signer.retired_keys(time(2024)).is_empty());
+
+        assert_eq!(signer.signing_key(time(2025)).unwrap().id(), "1");
+        assert_eq!(ids(signer.active_keys(time(2025))), vec!["1"]);
+        assert_eq!(ids(signer.retired_keys(time(2025))), vec!["0"]);
+
+        assert_eq!(signer.signing_key(time(2030)).unwrap().id(), "2");
+    }
+
+    #[test]
+    fn test_no_active_key() {
+        let config = NotarySigningKeyProperties {
+            keys: vec![key("0", Some(2030), None)],
+            retired_keys: vec![],
+        };
+        let signer = FileNotarySigner::load(&config).unwrap();
+
+        assert!(matches!(
+            signer.signing_key(time(2024)),
+            Err(NotarySignerError::NoActiveKey(_))
+        ));
+    }
+}
@@ -0,0 +1,444 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Row};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+use tokio::task::JoinHandle;
+use tracing::{debug, error};
+
+use crate::{
+    config::{SessionStoreBackend, SessionStoreProperties},
+    domain::notary::SessionData,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionStoreError {
+    #[error("Session store backend error: {0}")]
+    Backend(String),
+}
+
+impl From<rusqlite::Error> for SessionStoreError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Backend(error.to_string())
+    }
+}
+
+/// Storage of the session configuration submitted via /session until it is used by /notarize
+///
+/// Sessions expire after a TTL, so that sessions which are never used do not pile up.
+#[async_trait]
+pub trait SessionStore: Debug + Send + Sync {
+    /// Stores the configuration of a new session
+    async fn insert(&self, session_id: String, data: SessionData) -> Result<(), SessionStoreError>;
+    /// Returns the configuration of a session without removing it, or `None` if the session does
+    /// not exist or has expired
+    async fn get(&self, session_id: &str) -> Result<Option<SessionData>, SessionStoreError>;
+    /// Removes and returns the configuration of a session, or `None` if the session does not
+    /// exist or has expired
+    ///
+    /// Each session can only be taken once, even when the store is shared by multiple replicas.
+    async fn take(&self, session_id: &str) -> Result<Option<SessionData>, SessionStoreError>;
+    /// Returns the sessions which have neither been taken nor expired at `now`
+    async fn list(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, SessionData)>, SessionStoreError>;
+    /// Removes the sessions which have expired at `now`, returning the number of removed sessions
+    async fn evict_expired(&self, now: DateTime<Utc>) -> Result<usize, SessionStoreError>;
+}
+
+/// Build the session store configured in the config
+pub fn build_session_store(
+    config: &SessionStoreProperties,
+) -> Result<Arc<dyn SessionStore>, SessionStoreError> {
+    let ttl = Duration::seconds(config.ttl_seconds as i64);
+    Ok(match &config.backend {
+        SessionStoreBackend::Memory => Arc::new(InMemorySessionStore::new(ttl)),
+        SessionStoreBackend::Sqlite { path } => Arc::new(SqliteSessionStore::open(path, ttl)?),
+    })
+}
+
+/// Spawn a background task which periodically evicts expired sessions from the store
+pub fn spawn_eviction_task(
+    store: Arc<dyn SessionStore>,
+    interval: std::time::Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            match store.evict_expired(Utc::now()).await {
+                Ok(0) => {}
+                Ok(evicted) => debug!(evicted, "Evicted expired notarization sessions"),
+                Err(err) => error!("Failed to evict expired notarization sessions: {err}"),
+            }
+        }
+    })
+}
+
+/// Session store which keeps the sessions in memory, i.e. sessions are lost on restart and are
+/// not shared between replicas
+#[derive(Debug)]
+pub struct InMemorySessionStore {
+    sessions: Mutex<HashMap<String, SessionData>>,
+    ttl: Duration,
+}
+
+impl InMemorySessionStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            sessions: Default::default(),
+            ttl,
+        }
+    }
+
+    fn is_expired(&self, data: &SessionData, now: DateTime<Utc>) -> bool {
+        data.created_at + self.ttl <= now
+    }
+}
+
+#[async_trait]
+impl SessionStore for InMemorySessionStore {
+    async fn insert(&self, session_id: String, data: SessionData) -> Result<(), SessionStoreError> {
+        self.sessions.lock().unwrap().insert(session_id, data);
+        Ok(())
+    }
+
+    async fn get(&self, session_id: &str) -> Result<Option<SessionData>, SessionStoreError> {
+        let data = self.sessions.lock().unwrap().get(session_id).cloned();
+        Ok(data.filter(|data| !self.is_expired(data, Utc::now())))
+    }
+
+    async fn take(&self, session_id: &str) -> Result<Option<SessionData>, SessionStoreError> {
+        let data = self.sessions.lock().unwrap().remove(session_id);
+        Ok(data.filter(|data| !self.is_expired(data, Utc::now())))
+    }
+
+    async fn list(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, SessionData)>, SessionStoreError> {
+        Ok(self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, data)| !self.is_expired(data, now))
+            .map(|(session_id, data)| (session_id.clone(), data.clone()))
+            .collect())
+    }
+
+    async fn evict_expired(&self, now: DateTime<Utc>) -> Result<usize, SessionStoreError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let len = sessions.len();
+        sessions.retain(|_, data| !self.is_expired(data, now));
+        Ok(len - sessions.len())
+    }
+}
+
+/// Session store which persists the sessions in a SQLite database
+///
+/// The database file can be shared by multiple replicas on the same host. It must not be placed on
+/// a network filesystem, as the WAL mode of SQLite relies on memory shared between the processes.
+#[derive(Debug)]
+pub struct SqliteSessionStore {
+    connection: Arc<Mutex<Connection>>,
+    ttl: Duration,
+}
+
+impl SqliteSessionStore {
+    /// Open the database at `path`, creating it if it does not exist
+    pub fn open(path: &str, ttl: Duration) -> Result<Self, SessionStoreError> {
+        debug!(path, "Opening notarization session database");
+
+        let connection = Connection::open(path)?;
+        // Wait for the lock instead of failing when another replica is writing
+        connection.busy_timeout(std::time::Duration::from_secs(5))?;
+        connection.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS notarization_sessions (
+                session_id TEXT PRIMARY KEY,
+                max_transcript_size INTEGER,
+                created_at INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL,
+                subject TEXT,
+                client_certificate_subject TEXT
+            )",
+            [],
+        )?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            ttl,
+        })
+    }
+
+    /// Run a query on a blocking thread, as SQLite does blocking IO
+    async fn run<T, F>(&self, f: F) -> Result<T, SessionStoreError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, rusqlite::Error> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let connection = connection
+                .lock()
+                .map_err(|err| SessionStoreError::Backend(err.to_string()))?;
+            f(&connection).map_err(SessionStoreError::from)
+        })
+        .await
+        .map_err(|err| SessionStoreError::Backend(err.to_string()))?
+    }
+}
+
+/// Read a timestamp in milliseconds, failing instead of panicking if it is out of range
+fn timestamp(row: &Row, index: usize) -> Result<DateTime<Utc>, rusqlite::Error> {
+    let millis: i64 = row.get(index)?;
+    Utc.timestamp_millis_opt(millis).single().ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            index,
+            Type::Integer,
+            format!("Timestamp out of range: {millis}").into(),
+        )
+    })
+}
+
+#[async_trait]
+impl SessionStore for SqliteSessionStore {
+    async fn insert(&self, session_id: String, data: SessionData) -> Result<(), SessionStoreError> {
+        let expires_at = (data.created_at + self.ttl).timestamp_millis();
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT INTO notarization_sessions
+                    (session_id, max_transcript_size, created_at, expires_at, subject,
+                        client_certificate_subject)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    session_id,
+                    data.max_transcript_size,
+                    data.created_at.timestamp_millis(),
+                    expires_at,
+                    data.subject,
+                    data.client_certificate_subject
+                ],
+            )
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn get(&self, session_id: &str) -> Result<Option<SessionData>, SessionStoreError> {
+        let session_id = session_id.to_string();
+        let now = Utc::now().timestamp_millis();
+        self.run(move |connection| {
+            connection
+                .query_row(
+                    "SELECT max_transcript_size, created_at, subject, client_certificate_subject
+                        FROM notarization_sessions WHERE session_id = ?1 AND expires_at > ?2",
+                    params![session_id, now],
+                    |row| {
+                        Ok(SessionData {
+                            max_transcript_size: row.get(0)?,
+                            created_at: timestamp(row, 1)?,
+                            subject: row.get(2)?,
+                            client_certificate_subject: row.get(3)?,
+                        })
+                    },
+                )
+                .optional()
+        })
+        .await
+    }
+
+    async fn take(&self, session_id: &str) -> Result<Option<SessionData>, SessionStoreError> {
+        let session_id = session_id.to_string();
+        let now = Utc::now().timestamp_millis();
+        // Deleting and reading in one statement ensures that only one replica gets the session
+        let session = self
+            .run(move |connection| {
+                connection
+                    .query_row(
+                        "DELETE FROM notarization_sessions WHERE session_id = ?1
+                            RETURNING max_transcript_size, created_at, expires_at, subject,
+                                client_certificate_subject",
+                        params![session_id],
+                        |row| {
+                            Ok((
+                                row.get::<_, i64>(2)?,
+                                SessionData {
+                                    max_transcript_size: row.get(0)?,
+                                    created_at: timestamp(row, 1)?,
+                                    subject: row.get(3)?,
+                                    client_certificate_subject: row.get(4)?,
+                                },
+                            ))
+                        },
+                    )
+                    .optional()
+            })
+            .await?;
+
+        Ok(session.and_then(|(expires_at, data)| (expires_at > now).then_some(data)))
+    }
+
+    async fn list(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, SessionData)>, SessionStoreError> {
+        let now = now.timestamp_millis();
+        self.run(move |connection| {
+            connection
+                .prepare(
+                    "SELECT session_id, max_transcript_size, created_at, subject,
+                        client_certificate_subject FROM notarization_sessions WHERE expires_at > ?1",
+                )?
+                .query_map(params![now], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        SessionData {
+                            max_transcript_size: row.get(1)?,
+                            created_at: timestamp(row, 2)?,
+                            subject: row.get(3)?,
+                            client_certificate_subject: row.get(4)?,
+                        },
+                    ))
+                })?
+                .collect()
+        })
+        .await
+    }
+
+    async fn evict_expired(&self, now: DateTime<Utc>) -> Result<usize, SessionStoreError> {
+        let now = now.timestamp_millis();
+        self.run(move |connection| {
+            connection.execute(
+                "DELETE FROM notarization_sessions WHERE expires_at <= ?1",
+                params![now],
+            )
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn session(created_at: DateTime<Utc>) -> SessionData {
+        SessionData {
+            max_transcript_size: Some(1 << 14),
+            created_at,
+            subject: Some("prover-0".to_string()),
+            client_certificate_subject: Some("CN=prover-0".to_string()),
+        }
+    }
+
+    async fn test_take_once(store: &dyn SessionStore) {
+        store
+            .insert("0".to_string(), session(Utc::now()))
+            .await
+            .unwrap();
+
+        // Getting a session does not use it up
+        let data = store.get("0").await.unwrap().unwrap();
+        assert_eq!(data.subject.as_deref(), Some("prover-0"));
+        let data = store.take("0").await.unwrap().unwrap();
+        assert_eq!(data.max_transcript_size, Some(1 << 14));
+        assert_eq!(data.subject.as_deref(), Some("prover-0"));
+        assert_eq!(
+            data.client_certificate_subject.as_deref(),
+            Some("CN=prover-0")
+        );
+        assert!(store.get("0").await.unwrap().is_none());
+        assert!(store.take("0").await.unwrap().is_none());
+        assert!(store.take("1").await.unwrap().is_none());
+    }
+
+    async fn test_expiry(store: &dyn SessionStore) {
+        let now = Utc::now();
+        store
+            .insert("old".to_string(), session(now - Duration::hours(2)))
+            .await
+            .unwrap();
+        store
+            .insert("expired".to_string(), session(now - Duration::hours(2)))
+            .await
+            .unwrap();
+        store.insert("new".to_string(), session(now)).await.unwrap();
+
+        // Expired sessions can not be used even before they are evicted
+        let pending = store.list(now).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, "new");
+        assert!(store.get("expired").await.unwrap().is_none());
+        assert!(store.take("expired").await.unwrap().is_none());
+
+        assert_eq!(store.evict_expired(now).await.unwrap(), 1);
+        assert!(store.take("old").await.unwrap().is_none());
+        assert!(store.take("new").await.unwrap().is_some());
+    }
+
+    fn temp_db_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("notary-sessions-{}.db", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store() {
+        test_take_once(&InMemorySessionStore::new(Duration::hours(1))).await;
+        test_expiry(&InMemorySessionStore::new(Duration::hours(1))).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        test_take_once(&SqliteSessionStore::open(":memory:", Duration::hours(1)).unwrap()).await;
+        test_expiry(&SqliteSessionStore::open(":memory:", Duration::hours(1)).unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_invalid_timestamp() {
+        let path = temp_db_path();
+        let path_str = path.to_str().unwrap();
+        let store = SqliteSessionStore::open(path_str, Duration::hours(1)).unwrap();
+        Connection::open(path_str)
+            .unwrap()
+            .execute(
+                "INSERT INTO notarization_sessions (session_id, created_at, expires_at)
+                    VALUES ('0', ?1, ?1)",
+                params![i64::MAX],
+            )
+            .unwrap();
+
+        // A corrupt row is an error rather than a panic
+        assert!(store.list(Utc::now()).await.is_err());
+        assert!(store.get("0").await.is_err());
+        assert!(store.take("0").await.is_err());
+
+        drop(store);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{path_str}{suffix}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_is_shared() {
+        let path = temp_db_path();
+        let path_str = path.to_str().unwrap();
+        let replica_0 = SqliteSessionStore::open(path_str, Duration::hours(1)).unwrap();
+        let replica_1 = SqliteSessionStore::open(path_str, Duration::hours(1)).unwrap();
+
+        replica_0
+            .insert("0".to_string(), session(Utc::now()))
+            .await
+            .unwrap();
+        assert!(replica_1.take("0").await.unwrap().is_some());
+        assert!(replica_0.take("0").await.unwrap().is_none());
+
+        drop((replica_0, replica_1));
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{path_str}{suffix}"));
+        }
+    }
+}
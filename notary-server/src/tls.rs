@@ -0,0 +1,328 @@
+use eyre::{ensure, eyre, Result};
+use rustls::{
+    server::{
+        AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, ClientCertVerifier,
+        ClientHello, ResolvesServerCert,
+    },
+    sign::{any_supported_type, CertifiedKey},
+    Certificate, PrivateKey, RootCertStore,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+/// ALPN protocol of the TLS-ALPN-01 challenge of ACME (RFC 8737)
+pub const ACME_TLS_ALPN_PROTOCOL: &[u8] = b"acme-tls/1";
+
+/// Resolver of the TLS certificate of the notary server, whose certificate can be swapped while
+/// the server is running, e.g. when it is renewed
+#[derive(Default)]
+pub struct CertificateResolver {
+    certificate: RwLock<Option<Arc<CertifiedKey>>>,
+    /// Certificates of the pending ACME TLS-ALPN-01 challenges, by domain
+    challenges: RwLock<HashMap<String, Arc<CertifiedKey>>>,
+}
+
+impl CertificateResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the certificate presented to new connections
+    pub fn set_certificate(&self, certificate: CertifiedKey) {
+        *self.certificate.write().unwrap() = Some(Arc::new(certificate));
+    }
+
+    pub fn has_certificate(&self) -> bool {
+        self.certificate.read().unwrap().is_some()
+    }
+
+    /// Present `certificate` to the ACME server validating the TLS-ALPN-01 challenge of `domain`
+    pub fn set_challenge_certificate(&self, domain: &str, certificate: CertifiedKey) {
+        self.challenges
+            .write()
+            .unwrap()
+            .insert(domain.to_string(), Arc::new(certificate));
+    }
+
+    pub fn clear_challenge_certificates(&self) {
+        self.challenges.write().unwrap().clear();
+    }
+
+    fn select(&self, server_name: Option<&str>, acme_challenge: bool) -> Option<Arc<CertifiedKey>> {
+        if acme_challenge {
+            // Only challenge certificates are presented to the ACME server
+            let challenges = self.challenges.read().unwrap();
+            return server_name.and_then(|name| challenges.get(name).cloned());
+        }
+        self.certificate.read().unwrap().clone()
+    }
+}
+
+impl ResolvesServerCert for CertificateResolver {
+    fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        let acme_challenge = client_hello.alpn().is_some_and(|mut protocols| {
+            protocols.any(|protocol| protocol == ACME_TLS_ALPN_PROTOCOL)
+        });
+        self.select(client_hello.server_name(), acme_challenge)
+    }
+}
+
+/// Parse a PEM-formatted PKCS#8 private key and certificate chain into a certificate of the
+/// server
+pub fn parse_certified_key(private_key_pem: &[u8], certificate_pem: &[u8]) -> Result<CertifiedKey> {
+    let mut private_keys = rustls_pemfile::pkcs8_private_keys(&mut &*private_key_pem)?;
+    ensure!(
+        private_keys.len() == 1,
+        "Exactly 1 key must be in the tls private key pem file, found {}",
+        private_keys.len()
+    );
+    let private_key = any_supported_type(&PrivateKey(private_keys.remove(0)))
+        .map_err(|err| eyre!("Unsupported tls private key: {err}"))?;
+
+    let certificates: Vec<_> = rustls_pemfile::certs(&mut &*certificate_pem)?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+    ensure!(
+        !certificates.is_empty(),
+        "No certificate found in the tls certificate pem file"
+    );
+
+    Ok(CertifiedKey::new(certificates, private_key))
+}
+
+/// Certificate presented by the prover when mutual TLS is turned on, which is made available to
+/// the handlers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientCertificate {
+    /// Distinguished name of the subject, e.g. "CN=prover-0, O=Example"
+    pub subject: String,
+}
+
+impl ClientCertificate {
+    /// Parse the DER-encoded certificate, which has been verified during the handshake
+    pub fn from_der(certificate: &Certificate) -> Result<Self> {
+        let (_, certificate) = x509_parser::parse_x509_certificate(&certificate.0)
+            .map_err(|err| eyre!("Failed to parse client certificate: {err}"))?;
+        Ok(Self {
+            subject: certificate.subject().to_string(),
+        })
+    }
+}
+
+/// Load the CA bundle which client certificates must be issued by
+///
+/// Anonymous clients pass the handshake if `allow_anonymous` is set, e.g. for the ACME CA
+/// validating a challenge, so they must be rejected after the handshake.
+pub fn load_client_cert_verifier(
+    ca_pem_path: &str,
+    allow_anonymous: bool,
+) -> Result<Arc<dyn ClientCertVerifier>> {
+    let ca_pem = std::fs::read(ca_pem_path)
+        .map_err(|err| eyre!("Failed to read tls client CA bundle: {err}"))?;
+    let mut root_store = RootCertStore::empty();
+    for certificate in rustls_pemfile::certs(&mut &*ca_pem)? {
+        root_store
+            .add(&Certificate(certificate))
+            .map_err(|err| eyre!("Invalid certificate in tls client CA bundle: {err}"))?;
+    }
+    ensure!(
+        !root_store.is_empty(),
+        "No certificate found in the tls client CA bundle"
+    );
+
+    Ok(if allow_anonymous {
+        AllowAnyAnonymousOrAuthenticatedClient::new(root_store).boxed()
+    } else {
+        AllowAnyAuthenticatedClient::new(root_store).boxed()
+    })
+}
+
+/// TLS private key and certificate files of the server, which are loaded into a
+/// [`CertificateResolver`]
+pub struct CertificateFiles {
+    private_key_pem_path: String,
+    certificate_pem_path: String,
+    resolver: Arc<CertificateResolver>,
+    /// Content of the files which was last loaded
+    content: Mutex<Option<(Vec<u8>, Vec<u8>)>>,
+}
+
+impl CertificateFiles {
+    /// Load the files into `resolver`
+    pub fn load(
+        private_key_pem_path: &str,
+        certificate_pem_path: &str,
+        resolver: Arc<CertificateResolver>,
+    ) -> Result<Self> {
+        debug!("Loading notary server's tls private key and certificate");
+
+        let files = Self {
+            private_key_pem_path: private_key_pem_path.to_string(),
+            certificate_pem_path: certificate_pem_path.to_string(),
+            resolver,
+            content: Mutex::new(None),
+        };
+        files.reload()?;
+
+        debug!("Successfully loaded notary server's tls private key and certificate!");
+        Ok(files)
+    }
+
+    /// Reload the files if their content changed, returns whether they were reloaded
+    ///
+    /// The previous certificate is kept if the files are invalid, e.g. when only one of them has
+    /// been replaced so far.
+    pub fn reload(&self) -> Result<bool> {
+        let private_key_pem = std::fs::read(&self.private_key_pem_path)
+            .map_err(|err| eyre!("Failed to read tls private key: {err}"))?;
+        let certificate_pem = std::fs::read(&self.certificate_pem_path)
+            .map_err(|err| eyre!("Failed to read tls certificate: {err}"))?;
+        let content = (private_key_pem, certificate_pem);
+
+        let mut last_content = self.content.lock().unwrap();
+        if last_content.as_ref() == Some(&content) {
+            return Ok(false);
+        }
+
+        self.resolver
+            .set_certificate(parse_certified_key(&content.0, &content.1)?);
+        *last_content = Some(content);
+
+        Ok(true)
+    }
+}
+
+/// Spawn a background task which periodically reloads the TLS certificate if its files changed
+pub fn spawn_certificate_reload_task(
+    files: CertificateFiles,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            match files.reload() {
+                Ok(true) => info!("Reloaded tls private key and certificate"),
+                Ok(false) => {}
+                Err(err) => error!("Failed to reload tls private key and certificate: {err}"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PRIVATE_KEY_PEM_PATH: &str = "./fixture/tls/notary.key";
+    const CERTIFICATE_PEM_PATH: &str = "./fixture/tls/notary.crt";
+
+    fn generate_certificate(domain: &str) -> (String, String) {
+        let certificate = rcgen::generate_simple_self_signed(vec![domain.to_string()]).unwrap();
+        (
+            certificate.serialize_private_key_pem(),
+            certificate.serialize_pem().unwrap(),
+        )
+    }
+
+    fn leaf(resolver: &CertificateResolver) -> Certificate {
+        resolver.select(None, false).unwrap().cert[0].clone()
+    }
+
+    #[test]
+    fn test_load_notary_key_and_cert() {
+        let resolver = Arc::new(CertificateResolver::new());
+        let result =
+            CertificateFiles::load(PRIVATE_KEY_PEM_PATH, CERTIFICATE_PEM_PATH, resolver.clone());
+        assert!(result.is_ok(), "Could not load tls private key and cert");
+        assert!(resolver.has_certificate());
+    }
+
+    #[test]
+    fn test_reload_certificate() {
+        let dir = std::env::temp_dir().join(format!("notary-tls-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let private_key_pem_path = dir.join("notary.key").to_str().unwrap().to_string();
+        let certificate_pem_path = dir.join("notary.crt").to_str().unwrap().to_string();
+        std::fs::copy(PRIVATE_KEY_PEM_PATH, &private_key_pem_path).unwrap();
+        std::fs::copy(CERTIFICATE_PEM_PATH, &certificate_pem_path).unwrap();
+
+        let resolver = Arc::new(CertificateResolver::new());
+        let files = CertificateFiles::load(
+            &private_key_pem_path,
+            &certificate_pem_path,
+            resolver.clone(),
+        )
+        .unwrap();
+        let initial = leaf(&resolver);
+        assert!(!files.reload().unwrap());
+
+        // A key which does not parse keeps the previous certificate
+        std::fs::write(&private_key_pem_path, "invalid").unwrap();
+        assert!(files.reload().is_err());
+        assert_eq!(leaf(&resolver), initial);
+
+        let (private_key_pem, certificate_pem) = generate_certificate("notary.test");
+        std::fs::write(&private_key_pem_path, private_key_pem).unwrap();
+        std::fs::write(&certificate_pem_path, certificate_pem).unwrap();
+        assert!(files.reload().unwrap());
+        assert_ne!(leaf(&resolver), initial);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_client_certificate_subject() {
+        let mut params = rcgen::CertificateParams::new(vec!["prover.test".to_string()]);
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::OrganizationName, "Example");
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "prover-0");
+        let certificate = rcgen::Certificate::from_params(params).unwrap();
+
+        let client_certificate =
+            ClientCertificate::from_der(&Certificate(certificate.serialize_der().unwrap()))
+                .unwrap();
+        assert_eq!(client_certificate.subject, "O=Example, CN=prover-0");
+        assert!(ClientCertificate::from_der(&Certificate(vec![0; 8])).is_err());
+    }
+
+    #[test]
+    fn test_load_client_cert_verifier() {
+        assert!(load_client_cert_verifier("./fixture/tls/rootCA.crt", false).is_ok());
+        assert!(load_client_cert_verifier(PRIVATE_KEY_PEM_PATH, false).is_err());
+    }
+
+    #[test]
+    fn test_select_challenge_certificate() {
+        let resolver = CertificateResolver::new();
+        let (private_key_pem, certificate_pem) = generate_certificate("notary.test");
+        resolver.set_certificate(
+            parse_certified_key(private_key_pem.as_bytes(), certificate_pem.as_bytes()).unwrap(),
+        );
+        let (private_key_pem, certificate_pem) = generate_certificate("notary.test");
+        resolver.set_challenge_certificate(
+            "notary.test",
+            parse_certified_key(private_key_pem.as_bytes(), certificate_pem.as_bytes()).unwrap(),
+        );
+
+        let certificate = resolver.select(Some("notary.test"), false).unwrap();
+        let challenge = resolver.select(Some("notary.test"), true).unwrap();
+        assert_ne!(certificate.cert, challenge.cert);
+        assert!(resolver.select(Some("other.test"), true).is_none());
+        assert!(resolver.select(None, true).is_none());
+
+        resolver.clear_challenge_certificates();
+        assert!(resolver.select(Some("notary.test"), true).is_none());
+        assert!(resolver.select(Some("notary.test"), false).is_some());
+    }
+}
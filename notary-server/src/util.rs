@@ -1,13 +1,292 @@
-use eyre::Result;
-use serde::de::DeserializeOwned;
+use eyre::{bail, eyre, Result};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde_yaml::{mapping, Value};
+
+/// Prefix of the environment variables overriding the configuration
+pub const CONFIG_ENV_PREFIX: &str = "NOTARY_SERVER__";
 
 /// Parse a yaml configuration file into a struct
 pub fn parse_config_file<T: DeserializeOwned>(location: &str) -> Result<T> {
+    parse_config_file_with_overrides(location, &[])
+}
+
+/// Parse a yaml configuration file into a struct, after setting the properties in `overrides`
+///
+/// Each override is a pair of the dot-separated path of a property in the file, e.g.
+/// `notary-key.keys.0.private-key-pem-path`, and its value. The value is taken as is for a string
+/// property, and parsed as yaml for a property of another type, see [`TypedValue`]. The overrides
+/// are applied in order, so later ones take precedence.
+pub fn parse_config_file_with_overrides<T: DeserializeOwned>(
+    location: &str,
+    overrides: &[(String, String)],
+) -> Result<T> {
     let file = std::fs::File::open(location)?;
-    let config: T = serde_yaml::from_reader(file)?;
+    let mut config: Value = serde_yaml::from_reader(file)?;
+    for (path, value) in overrides {
+        let segments: Vec<&str> = path.split('.').collect();
+        set_yaml_value(&mut config, &segments, Value::String(value.clone()))
+            .map_err(|err| eyre!("Failed to override config property {path}: {err}"))?;
+    }
+    let config = T::deserialize(TypedValue(config))?;
     Ok(config)
 }
 
+/// Collect the config overrides from the environment variables, e.g.
+/// `NOTARY_SERVER__NOTARIZATION__MAX_TRANSCRIPT_SIZE` overrides `notarization.max-transcript-size`
+pub fn env_config_overrides(
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Vec<(String, String)> {
+    let mut overrides: Vec<_> = vars
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let path = key
+                .strip_prefix(CONFIG_ENV_PREFIX)?
+                .split("__")
+                .map(|segment| segment.to_lowercase().replace('_', "-"))
+                .collect::<Vec<_>>()
+                .join(".");
+            Some((path, value))
+        })
+        .collect();
+    // Sort so that the overrides are applied in the same order regardless of the environment
+    overrides.sort();
+    overrides
+}
+
+/// Parse a `path=value` config override from the command line
+pub fn parse_config_override(input: &str) -> Result<(String, String)> {
+    match input.split_once('=') {
+        Some((path, value)) if !path.is_empty() => Ok((path.to_string(), value.to_string())),
+        _ => bail!("Config override must be in the form of path=value, e.g. server.port=7047"),
+    }
+}
+
+/// A yaml document deserialized against the type of its target, so that the string values of the
+/// config overrides are only parsed as yaml where the target is not a string, e.g. `0123` stays
+/// `"0123"` for a string property while it is `123` for a numeric one
+struct TypedValue(Value);
+
+impl TypedValue {
+    /// Parse a string as yaml, as the target is not a string
+    fn parse(self) -> Result<Value, serde_yaml::Error> {
+        match self.0 {
+            Value::String(value) => serde_yaml::from_str(&value),
+            value => Ok(value),
+        }
+    }
+}
+
+/// Deserialize a value whose target is not a string, after parsing it as yaml
+macro_rules! deserialize_parsed {
+    ($($method:ident)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            self.parse()?.$method(visitor)
+        }
+    )*};
+}
+
+/// Deserialize a value whose target can be a string as is
+macro_rules! deserialize_as_is {
+    ($($method:ident)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            self.0.$method(visitor)
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for TypedValue {
+    type Error = serde_yaml::Error;
+
+    deserialize_parsed! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64
+    }
+
+    deserialize_as_is! {
+        deserialize_char deserialize_str deserialize_string deserialize_bytes
+        deserialize_byte_buf deserialize_unit deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Mapping(mapping) => visitor.visit_map(TypedMapAccess::new(mapping)),
+            Value::Sequence(sequence) => visitor.visit_seq(TypedSeqAccess(sequence.into_iter())),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(TypedValue(value)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.parse()? {
+            Value::Sequence(sequence) => visitor.visit_seq(TypedSeqAccess(sequence.into_iter())),
+            value => value.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.parse()? {
+            Value::Mapping(mapping) => visitor.visit_map(TypedMapAccess::new(mapping)),
+            value => value.deserialize_map(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+}
+
+/// Entries of a yaml mapping, whose values are deserialized as [`TypedValue`]s
+struct TypedMapAccess {
+    entries: mapping::IntoIter,
+    value: Option<Value>,
+}
+
+impl TypedMapAccess {
+    fn new(mapping: mapping::Mapping) -> Self {
+        Self {
+            entries: mapping.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for TypedMapAccess {
+    type Error = serde_yaml::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(value);
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value of the mapping entry is missing"))?;
+        seed.deserialize(TypedValue(value))
+    }
+}
+
+/// Items of a yaml sequence, which are deserialized as [`TypedValue`]s
+struct TypedSeqAccess(std::vec::IntoIter<Value>);
+
+impl<'de> SeqAccess<'de> for TypedSeqAccess {
+    type Error = serde_yaml::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.0
+            .next()
+            .map(|value| seed.deserialize(TypedValue(value)))
+            .transpose()
+    }
+}
+
+/// Set the value at `path` in a yaml document, creating the missing mappings along the way
+fn set_yaml_value(node: &mut Value, path: &[&str], value: Value) -> Result<()> {
+    let Some((key, rest)) = path.split_first() else {
+        *node = value;
+        return Ok(());
+    };
+    if node.is_null() {
+        *node = if key.parse::<usize>().is_ok() {
+            Value::Sequence(Default::default())
+        } else {
+            Value::Mapping(Default::default())
+        };
+    }
+
+    let child = match node {
+        Value::Mapping(mapping) => {
+            if !mapping.contains_key(*key) {
+                mapping.insert(Value::String(key.to_string()), Value::Null);
+            }
+            mapping.get_mut(*key).expect("key was just inserted")
+        }
+        Value::Sequence(sequence) => {
+            let index: usize = key
+                .parse()
+                .map_err(|_| eyre!("{key} is not an index of a list"))?;
+            // Allow appending to the list
+            if index == sequence.len() {
+                sequence.push(Value::Null);
+            }
+            sequence
+                .get_mut(index)
+                .ok_or_else(|| eyre!("index {key} is out of bounds"))?
+        }
+        _ => bail!("{key} is not a property of a mapping or a list"),
+    };
+    set_yaml_value(child, rest, value)
+}
+
 /// Parse a csv file into a vec of structs
 pub fn parse_csv_file<T: DeserializeOwned>(location: &str) -> Result<Vec<T>> {
     let file = std::fs::File::open(location)?;
@@ -24,11 +303,18 @@ pub fn parse_csv_file<T: DeserializeOwned>(location: &str) -> Result<Vec<T>> {
 mod test {
 
     use crate::{
-        config::NotaryServerProperties, domain::auth::AuthorizationWhitelistRecord,
+        config::{
+            ListenerAddress, NotaryServerProperties, NotarySigningKeyProperties,
+            SINGLE_NOTARY_KEY_ID,
+        },
+        domain::auth::AuthorizationWhitelistRecord,
         util::parse_csv_file,
     };
 
-    use super::{parse_config_file, Result};
+    use super::{
+        env_config_overrides, parse_config_file, parse_config_file_with_overrides,
+        parse_config_override, Result,
+    };
 
     #[test]
     fn test_parse_config_file() {
@@ -40,6 +326,103 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_config_is_valid() {
+        let config: NotaryServerProperties = parse_config_file("./config/config.yaml").unwrap();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_parse_config_file_with_overrides() {
+        let mut overrides = env_config_overrides([
+            (
+                "NOTARY_SERVER__SERVER__PORT".to_string(),
+                "8080".to_string(),
+            ),
+            (
+                "NOTARY_SERVER__NOTARY_KEY__KEYS__0__PRIVATE_KEY_PEM_PATH".to_string(),
+                "/keys/notary.key".to_string(),
+            ),
+            (
+                "NOTARY_SERVER__TLS__ENABLED".to_string(),
+                "false".to_string(),
+            ),
+            ("HOME".to_string(), "/root".to_string()),
+        ]);
+        assert_eq!(overrides.len(), 3);
+        // Command line overrides take precedence over the environment variables
+        overrides.push(parse_config_override("server.port=9090").unwrap());
+        overrides.push(parse_config_override("authorization.usage-db-path=usage.db").unwrap());
+        // Values are only parsed as yaml where the property is not a string
+        overrides.push(parse_config_override("server.name=0123").unwrap());
+        overrides.push(parse_config_override("server.host=::").unwrap());
+        overrides.push(
+            parse_config_override("server.additional-listeners.0=unix:/tmp/notary.sock").unwrap(),
+        );
+
+        let config: NotaryServerProperties =
+            parse_config_file_with_overrides("./config/config.yaml", &overrides).unwrap();
+        assert_eq!(config.server.port, 9090);
+        assert!(!config.tls.enabled);
+        assert_eq!(
+            config.notary_key.keys[0].private_key_pem_path,
+            "/keys/notary.key"
+        );
+        assert_eq!(
+            config.authorization.usage_db_path.as_deref(),
+            Some("usage.db")
+        );
+        assert_eq!(config.server.name, "0123");
+        assert_eq!(config.server.host, "::");
+        assert_eq!(
+            config.server.additional_listeners,
+            vec![ListenerAddress::Unix("/tmp/notary.sock".into())]
+        );
+
+        assert!(parse_config_override("server.port").is_err());
+        let overrides = vec![("server.port.number".to_string(), "1".to_string())];
+        assert!(parse_config_file_with_overrides::<NotaryServerProperties>(
+            "./config/config.yaml",
+            &overrides
+        )
+        .is_err());
+        let overrides = vec![("server.port".to_string(), "notary".to_string())];
+        assert!(parse_config_file_with_overrides::<NotaryServerProperties>(
+            "./config/config.yaml",
+            &overrides
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_single_notary_key_config() {
+        // The layout before key rotation was supported
+        let config: NotarySigningKeyProperties = serde_yaml::from_str(
+            "private-key-pem-path: ./fixture/notary/notary.key\n\
+             public-key-pem-path: ./fixture/notary/notary.pub\n",
+        )
+        .unwrap();
+        assert_eq!(config.keys.len(), 1);
+        assert_eq!(config.keys[0].id, SINGLE_NOTARY_KEY_ID);
+        assert_eq!(
+            config.keys[0].private_key_pem_path,
+            "./fixture/notary/notary.key"
+        );
+        assert!(config.keys[0].active_from.is_none());
+        assert!(config.retired_keys.is_empty());
+
+        let err = serde_yaml::from_str::<NotarySigningKeyProperties>(
+            "private-key-pem-path: ./fixture/notary/notary.key\n\
+             keys:\n  - id: notary-key-1\n    private-key-pem-path: ./fixture/notary/notary.key\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("notary-key.keys"));
+
+        let err =
+            serde_yaml::from_str::<NotarySigningKeyProperties>("retired-keys: []\n").unwrap_err();
+        assert!(err.to_string().contains("notary-key.keys is missing"));
+    }
+
     #[test]
     fn test_parse_csv_file() {
         let location = "./fixture/auth/whitelist.csv";
@@ -27,9 +27,12 @@ use tracing::debug;
 use ws_stream_tungstenite::WsStream;
 
 use notary_server::{
-    read_pem_file, run_server, AuthorizationProperties, NotarizationProperties,
-    NotarizationSessionRequest, NotarizationSessionResponse, NotaryServerProperties,
-    NotarySigningKeyProperties, ServerProperties, TLSProperties, TracingProperties,
+    read_pem_file, run_server, AdminProperties, AuthorizationMode, AuthorizationProperties,
+    ConcurrencyProperties, JwtAlgorithm, JwtClaims, JwtProperties, ListenerAddress, LogFormat,
+    NotarizationProperties, NotarizationSessionRequest, NotarizationSessionResponse,
+    NotarizationTimeoutProperties, NotaryKeyProperties, NotaryServerProperties,
+    NotarySigningKeyProperties, ServerProperties, SessionStoreProperties, SessionsResponse,
+    TLSProperties, TracingProperties,
 };
 
 const NOTARY_CA_CERT_PATH: &str = "./fixture/tls/rootCA.crt";
@@ -41,26 +44,46 @@ fn get_server_config(port: u16, tls_enabled: bool) -> NotaryServerProperties {
             name: "tlsnotaryserver.io".to_string(),
             host: "127.0.0.1".to_string(),
             port,
+            additional_listeners: vec![],
+            shutdown_timeout_seconds: 60,
         },
         notarization: NotarizationProperties {
             max_transcript_size: 1 << 14,
+            timeouts: NotarizationTimeoutProperties::default(),
         },
         tls: TLSProperties {
             enabled: tls_enabled,
             private_key_pem_path: "./fixture/tls/notary.key".to_string(),
             certificate_pem_path: "./fixture/tls/notary.crt".to_string(),
+            reload_interval_seconds: None,
+            acme: None,
+            client_ca_pem_path: None,
         },
         notary_key: NotarySigningKeyProperties {
-            private_key_pem_path: "./fixture/notary/notary.key".to_string(),
-            public_key_pem_path: "./fixture/notary/notary.pub".to_string(),
+            keys: vec![NotaryKeyProperties {
+                id: "notary-key-0".to_string(),
+                private_key_pem_path: "./fixture/notary/notary.key".to_string(),
+                active_from: None,
+                retire_at: None,
+            }],
+            retired_keys: vec![],
         },
         tracing: TracingProperties {
             default_level: "DEBUG".to_string(),
+            format: LogFormat::Compact,
+            otlp: None,
         },
         authorization: AuthorizationProperties {
             enabled: false,
+            mode: AuthorizationMode::Whitelist,
+            jwt: None,
             whitelist_csv_path: "./fixture/auth/whitelist.csv".to_string(),
+            whitelist_reload_interval_seconds: 10,
+            usage_db_path: None,
         },
+        session_store: SessionStoreProperties::default(),
+        concurrency: ConcurrencyProperties::default(),
+        admin: AdminProperties::default(),
     }
 }
 
@@ -69,8 +92,14 @@ async fn setup_config_and_server(
     port: u16,
     tls_enabled: bool,
 ) -> NotaryServerProperties {
-    let notary_config = get_server_config(port, tls_enabled);
+    start_server(get_server_config(port, tls_enabled), sleep_ms).await
+}
 
+/// Run the notary server with `notary_config`, and wait for `sleep_ms` for it to start listening
+async fn start_server(
+    notary_config: NotaryServerProperties,
+    sleep_ms: u64,
+) -> NotaryServerProperties {
     let _ = tracing_subscriber::fmt::try_init();
 
     let config = notary_config.clone();
@@ -86,6 +115,100 @@ async fn setup_config_and_server(
     notary_config
 }
 
+/// Send `request` to the notary server over plain HTTP, returns the status and body of the response
+async fn http_request(request: Request<Body>) -> (StatusCode, String) {
+    let response = Client::new().request(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body()).await.unwrap();
+    (status, String::from_utf8_lossy(&body).to_string())
+}
+
+/// Request to create a session on the notary server, authorized with `api_key` if set
+fn session_request(notary_config: &NotaryServerProperties, api_key: Option<&str>) -> Request<Body> {
+    let payload = serde_json::to_string(&NotarizationSessionRequest {
+        client_type: notary_server::ClientType::Tcp,
+        max_transcript_size: None,
+    })
+    .unwrap();
+    let mut request = Request::builder()
+        .uri(format!(
+            "http://{}:{}/session",
+            notary_config.server.host, notary_config.server.port
+        ))
+        .method("POST")
+        .header("Content-Type", "application/json");
+    if let Some(api_key) = api_key {
+        request = request.header("Authorization", api_key);
+    }
+    request.body(Body::from(payload)).unwrap()
+}
+
+/// Create a session on the notary server over plain HTTP, returns its id
+async fn create_session(notary_config: &NotaryServerProperties) -> String {
+    let (status, body) = http_request(session_request(notary_config, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    serde_json::from_str::<NotarizationSessionResponse>(&body)
+        .unwrap()
+        .session_id
+}
+
+/// Connect to /notarize over plain TCP, returns the socket of the notarization without running
+/// the prover
+async fn start_tcp_notarization(
+    notary_config: &NotaryServerProperties,
+    session_id: &str,
+) -> TcpStream {
+    let (mut request_sender, connection) =
+        hyper::client::conn::handshake(tcp_socket(notary_config.clone()).await)
+            .await
+            .unwrap();
+    let connection_task = tokio::spawn(connection.without_shutdown());
+    let request = Request::builder()
+        .uri(format!(
+            "http://{}:{}/notarize?sessionId={session_id}",
+            notary_config.server.host, notary_config.server.port
+        ))
+        .header("Host", notary_config.server.host.clone())
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "TCP")
+        .body(Body::empty())
+        .unwrap();
+    let response = request_sender.send_request(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+    connection_task.await.unwrap().unwrap().io
+}
+
+/// Send a TCP notarization request with an optional authorization header, returns the status of
+/// the response
+async fn notarize_status(
+    notary_config: &NotaryServerProperties,
+    session_id: &str,
+    authorization: Option<&str>,
+) -> StatusCode {
+    let (mut request_sender, connection) =
+        hyper::client::conn::handshake(tcp_socket(notary_config.clone()).await)
+            .await
+            .unwrap();
+    tokio::spawn(connection.without_shutdown());
+    let mut request = Request::builder()
+        .uri(format!("/notarize?sessionId={session_id}"))
+        .header("Host", notary_config.server.host.clone())
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "TCP");
+    if let Some(authorization) = authorization {
+        request = request.header("Authorization", authorization);
+    }
+    let request = request.body(Body::empty()).unwrap();
+    request_sender.send_request(request).await.unwrap().status()
+}
+
+/// Write `content` to a new file in the temporary directory, returns its path
+fn write_temp_file(name: &str, content: &str) -> String {
+    let path = std::env::temp_dir().join(format!("notary-{}-{name}", uuid::Uuid::new_v4()));
+    std::fs::write(&path, content).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
 async fn tcp_socket(notary_config: NotaryServerProperties) -> TcpStream {
     tokio::net::TcpStream::connect(SocketAddr::new(
         IpAddr::V4(notary_config.server.host.parse().unwrap()),
@@ -130,6 +253,112 @@ async fn tls_socket(notary_config: NotaryServerProperties) -> TlsStream<TcpStrea
         .unwrap()
 }
 
+/// Generate a CA which issues the client certificates of provers
+fn generate_client_ca(name: &str) -> rcgen::Certificate {
+    let mut params = rcgen::CertificateParams::new(vec![]);
+    params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, name);
+    rcgen::Certificate::from_params(params).unwrap()
+}
+
+/// Generate a client certificate of `subject` issued by `client_ca`, returns its chain and private
+/// key
+fn generate_client_certificate(
+    client_ca: &rcgen::Certificate,
+    subject: &str,
+) -> (Vec<Certificate>, rustls::PrivateKey) {
+    let mut params = rcgen::CertificateParams::new(vec!["prover.test".to_string()]);
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, subject);
+    params.extended_key_usages = vec![rcgen::ExtendedKeyUsagePurpose::ClientAuth];
+    let certificate = rcgen::Certificate::from_params(params).unwrap();
+    (
+        vec![Certificate(
+            certificate.serialize_der_with_signer(client_ca).unwrap(),
+        )],
+        rustls::PrivateKey(certificate.serialize_private_key_der()),
+    )
+}
+
+/// Send a request to the notary server over TLS with an optional client certificate, returns
+/// `None` if the handshake or the request failed
+async fn mtls_request(
+    notary_config: &NotaryServerProperties,
+    client_auth: Option<(Vec<Certificate>, rustls::PrivateKey)>,
+    request: Request<Body>,
+) -> Option<hyper::Response<Body>> {
+    let mut root_store = RootCertStore::empty();
+    for certificate in rustls_pemfile::certs(&mut &*NOTARY_CA_CERT_BYTES).unwrap() {
+        root_store.add(&Certificate(certificate)).unwrap();
+    }
+    let client_config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store);
+    let client_config = match client_auth {
+        Some((certificate_chain, private_key)) => client_config
+            .with_client_auth_cert(certificate_chain, private_key)
+            .unwrap(),
+        None => client_config.with_no_client_auth(),
+    };
+
+    let notary_tcp_socket = tcp_socket(notary_config.clone()).await;
+    // With TLS 1.3 the server may only reject the client certificate after the client
+    // considers the handshake done, so the request can fail as well
+    let notary_tls_socket = TlsConnector::from(Arc::new(client_config))
+        .connect(
+            notary_config.server.name.as_str().try_into().unwrap(),
+            notary_tcp_socket,
+        )
+        .await
+        .ok()?;
+    let (mut request_sender, connection) = hyper::client::conn::handshake(notary_tls_socket)
+        .await
+        .ok()?;
+    tokio::spawn(connection);
+
+    request_sender.send_request(request).await.ok()
+}
+
+/// Send a healthcheck request to the notary server over TLS with an optional client
+/// certificate, returns `None` if the handshake or the request failed
+async fn mtls_healthcheck(
+    notary_config: &NotaryServerProperties,
+    client_auth: Option<(Vec<Certificate>, rustls::PrivateKey)>,
+) -> Option<StatusCode> {
+    let request = Request::builder()
+        .uri("/healthcheck")
+        .header("Host", notary_config.server.name.as_str())
+        .body(Body::empty())
+        .unwrap();
+    mtls_request(notary_config, client_auth, request)
+        .await
+        .map(|response| response.status())
+}
+
+/// Send a TCP notarization request over TLS with an optional client certificate, returns the
+/// status of the response
+async fn mtls_notarize_status(
+    notary_config: &NotaryServerProperties,
+    session_id: &str,
+    client_auth: Option<(Vec<Certificate>, rustls::PrivateKey)>,
+) -> Option<StatusCode> {
+    let request = Request::builder()
+        .uri(format!("/notarize?sessionId={session_id}"))
+        .header("Host", notary_config.server.name.as_str())
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "TCP")
+        .body(Body::empty())
+        .unwrap();
+    mtls_request(notary_config, client_auth, request)
+        .await
+        .map(|response| response.status())
+}
+
 #[rstest]
 #[case::with_tls(
     setup_config_and_server(100, 7048, true),
@@ -479,3 +708,449 @@ async fn test_websocket_prover() {
 
     debug!("Done notarization!");
 }
+
+#[tokio::test]
+async fn test_session_at_capacity() {
+    let mut notary_config = get_server_config(7051, false);
+    notary_config.concurrency = ConcurrencyProperties {
+        max_sessions: 1,
+        max_sessions_per_api_key: None,
+        queue_timeout_seconds: 0,
+    };
+    let notary_config = start_server(notary_config, 100).await;
+
+    let (status, _) = http_request(session_request(&notary_config, None)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The only session is reserved until it is used or expires
+    let (status, body) = http_request(session_request(&notary_config, None)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(body.contains("at capacity"), "{body}");
+}
+
+#[tokio::test]
+async fn test_session_api_key_limits() {
+    let whitelist_csv_path = write_temp_file(
+        "whitelist.csv",
+        "\"Name\",\"ApiKey\",\"CreatedAt\",\"ExpiresAt\",\"MaxSessionsPerDay\",\"MaxTranscriptSize\"\n\
+         \"Quota\",\"test_api_key_quota\",\"2023-09-18T07:38:53Z\",,\"1\",\n\
+         \"Concurrency\",\"test_api_key_concurrency\",\"2023-09-18T07:38:53Z\",,,\n",
+    );
+    let mut notary_config = get_server_config(7052, false);
+    notary_config.authorization.enabled = true;
+    notary_config.authorization.whitelist_csv_path = whitelist_csv_path.clone();
+    notary_config.concurrency.max_sessions_per_api_key = Some(2);
+    let notary_config = start_server(notary_config, 100).await;
+
+    let (status, _) =
+        http_request(session_request(&notary_config, Some("test_api_key_quota"))).await;
+    assert_eq!(status, StatusCode::OK);
+    // The daily quota of the API key is used up
+    let (status, body) =
+        http_request(session_request(&notary_config, Some("test_api_key_quota"))).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(body.contains("quota of 1 sessions per day"), "{body}");
+
+    for _ in 0..2 {
+        let (status, _) = http_request(session_request(
+            &notary_config,
+            Some("test_api_key_concurrency"),
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    // The API key holds as many sessions as it may run concurrently
+    let (status, body) = http_request(session_request(
+        &notary_config,
+        Some("test_api_key_concurrency"),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(body.contains("limit of 2 concurrent sessions"), "{body}");
+
+    let (status, _) = http_request(session_request(&notary_config, None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    std::fs::remove_file(whitelist_csv_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    let notary_config = start_server(get_server_config(7053, false), 100).await;
+    let metrics_request = || {
+        Request::builder()
+            .uri(format!(
+                "http://{}:{}/metrics",
+                notary_config.server.host, notary_config.server.port
+            ))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = Client::new().request(metrics_request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/plain; version=0.0.4"
+    );
+    let metrics = to_bytes(response.into_body()).await.unwrap();
+    assert!(String::from_utf8_lossy(&metrics).contains("notary_active_sessions 0"));
+
+    let session_id = create_session(&notary_config).await;
+
+    // Start a notarization, and disconnect before the MPC setup so that it fails
+    drop(start_tcp_notarization(&notary_config, &session_id).await);
+
+    // The outcome is recorded once the notarization task notices the disconnection
+    let finished = |metrics: &str| {
+        metrics.contains(r#"notary_sessions_failed_total{client_type="tcp"} 1"#)
+            && metrics.contains("notary_active_sessions 0")
+    };
+    let mut metrics = String::new();
+    for _ in 0..50 {
+        let (status, body) = http_request(metrics_request()).await;
+        assert_eq!(status, StatusCode::OK);
+        metrics = body;
+        if finished(&metrics) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(finished(&metrics), "{metrics}");
+    assert!(metrics.contains(r#"notary_sessions_started_total{client_type="tcp"} 1"#));
+    assert!(metrics.contains(r#"notary_session_bytes_received_count{client_type="tcp"} 1"#));
+}
+
+#[tokio::test]
+async fn test_metrics_with_authorization() {
+    let mut notary_config = get_server_config(7061, false);
+    notary_config.authorization.enabled = true;
+    let notary_config = start_server(notary_config, 100).await;
+    let request = |path: &str| {
+        Request::builder()
+            .uri(format!(
+                "http://{}:{}/{path}",
+                notary_config.server.host, notary_config.server.port
+            ))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // Metrics scrapers do not hold an API key
+    let (status, body) = http_request(request("metrics")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("notary_active_sessions 0"), "{body}");
+
+    let (status, _) = http_request(request("info")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+/// Request to the admin API over plain HTTP, authorized with `token` if set
+fn admin_request(
+    notary_config: &NotaryServerProperties,
+    method: &str,
+    path: &str,
+    token: Option<&str>,
+) -> Request<Body> {
+    let mut request = Request::builder().method(method).uri(format!(
+        "http://{}:{}/admin/{path}",
+        notary_config.server.host, notary_config.server.port
+    ));
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("Bearer {token}"));
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+async fn list_sessions(notary_config: &NotaryServerProperties, token: &str) -> SessionsResponse {
+    let (status, body) =
+        http_request(admin_request(notary_config, "GET", "sessions", Some(token))).await;
+    assert_eq!(status, StatusCode::OK);
+    serde_json::from_str(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_admin_api() {
+    let token = "test_admin_token";
+    let token_path = write_temp_file("admin-token", &format!("{token}\n"));
+    let mut notary_config = get_server_config(7054, false);
+    notary_config.admin = AdminProperties {
+        enabled: true,
+        token_path: token_path.clone(),
+    };
+    let notary_config = start_server(notary_config, 100).await;
+
+    let (status, _) = http_request(admin_request(&notary_config, "GET", "sessions", None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = http_request(admin_request(
+        &notary_config,
+        "GET",
+        "sessions",
+        Some("wrong_token"),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // A pending session is listed until it is revoked
+    let pending_session_id = create_session(&notary_config).await;
+    let sessions = list_sessions(&notary_config, token).await;
+    assert_eq!(sessions.pending.len(), 1);
+    assert_eq!(sessions.pending[0].session_id, pending_session_id);
+    assert!(sessions.running.is_empty());
+
+    let cancel_path = format!("sessions/{pending_session_id}?reason=test");
+    let (status, _) = http_request(admin_request(
+        &notary_config,
+        "DELETE",
+        &cancel_path,
+        Some("wrong_token"),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = http_request(admin_request(
+        &notary_config,
+        "DELETE",
+        &cancel_path,
+        Some(token),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(list_sessions(&notary_config, token)
+        .await
+        .pending
+        .is_empty());
+    let (status, _) = http_request(admin_request(
+        &notary_config,
+        "DELETE",
+        &cancel_path,
+        Some(token),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A running notarization is listed until it is cancelled, the prover stalls in the MPC setup
+    let running_session_id = create_session(&notary_config).await;
+    let _notary_socket = start_tcp_notarization(&notary_config, &running_session_id).await;
+    // The notarization is registered once the connection is upgraded
+    let mut sessions = list_sessions(&notary_config, token).await;
+    for _ in 0..50 {
+        if !sessions.running.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        sessions = list_sessions(&notary_config, token).await;
+    }
+    assert!(sessions.pending.is_empty());
+    assert_eq!(sessions.running.len(), 1);
+    assert_eq!(sessions.running[0].session_id, running_session_id);
+    assert_eq!(
+        sessions.running[0].client_type,
+        notary_server::ClientType::Tcp
+    );
+
+    let cancel_path = format!("sessions/{running_session_id}?reason=test");
+    let (status, _) = http_request(admin_request(
+        &notary_config,
+        "DELETE",
+        &cancel_path,
+        Some(token),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    // The notarization is removed once its aborted task is dropped
+    let mut running = list_sessions(&notary_config, token).await.running;
+    for _ in 0..50 {
+        if running.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        running = list_sessions(&notary_config, token).await.running;
+    }
+    assert!(running.is_empty(), "{running:?}");
+
+    std::fs::remove_file(token_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_ipv6_and_unix_listeners() {
+    let socket_path = std::env::temp_dir().join(format!("notary-{}.sock", uuid::Uuid::new_v4()));
+    let mut notary_config = get_server_config(7055, false);
+    notary_config.server.host = "::1".to_string();
+    notary_config.server.additional_listeners = vec![ListenerAddress::Unix(socket_path.clone())];
+    start_server(notary_config, 100).await;
+
+    let (status, body) = http_request(
+        Request::builder()
+            .uri("http://[::1]:7055/healthcheck")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "Ok");
+
+    // Create a session and upgrade to the notarization over the Unix socket
+    let unix_socket = tokio::net::UnixStream::connect(&socket_path).await.unwrap();
+    let (mut request_sender, connection) =
+        hyper::client::conn::handshake(unix_socket).await.unwrap();
+    let connection_task = tokio::spawn(connection.without_shutdown());
+
+    let payload = serde_json::to_string(&NotarizationSessionRequest {
+        client_type: notary_server::ClientType::Tcp,
+        max_transcript_size: None,
+    })
+    .unwrap();
+    let request = Request::builder()
+        .uri("/session")
+        .method("POST")
+        .header("Host", "localhost")
+        .header("Content-Type", "application/json")
+        .body(Body::from(payload))
+        .unwrap();
+    let response = request_sender.send_request(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body()).await.unwrap();
+    let session_id = serde_json::from_slice::<NotarizationSessionResponse>(&body)
+        .unwrap()
+        .session_id;
+
+    let request = Request::builder()
+        .uri(format!("/notarize?sessionId={session_id}"))
+        .header("Host", "localhost")
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "TCP")
+        .body(Body::empty())
+        .unwrap();
+    let response = request_sender.send_request(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+    drop(connection_task.await.unwrap().unwrap().io);
+}
+
+#[tokio::test]
+async fn test_client_certificate_authentication() {
+    let client_ca = generate_client_ca("Prover CA");
+    let mut notary_config = get_server_config(7056, true);
+    notary_config.tls.client_ca_pem_path = Some(write_temp_file(
+        "client-ca.crt",
+        &client_ca.serialize_pem().unwrap(),
+    ));
+    let notary_config = start_server(notary_config, 100).await;
+
+    let client_auth = generate_client_certificate(&client_ca, "prover-0");
+    assert_eq!(
+        mtls_healthcheck(&notary_config, Some(client_auth)).await,
+        Some(StatusCode::OK)
+    );
+
+    // Clients without a certificate are rejected
+    assert_eq!(mtls_healthcheck(&notary_config, None).await, None);
+
+    // Clients with a certificate issued by another CA are rejected
+    let client_auth = generate_client_certificate(&generate_client_ca("Other CA"), "prover-0");
+    assert_eq!(
+        mtls_healthcheck(&notary_config, Some(client_auth)).await,
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_session_bound_to_token_subject() {
+    let mut notary_config = get_server_config(7059, false);
+    notary_config.authorization.enabled = true;
+    notary_config.authorization.mode = AuthorizationMode::Jwt;
+    notary_config.authorization.jwt = Some(JwtProperties {
+        algorithm: JwtAlgorithm::HS256,
+        key_path: write_temp_file("jwt-secret", "secret"),
+        issuer: None,
+        audience: None,
+    });
+    let notary_config = start_server(notary_config, 100).await;
+
+    let token = |subject: &str| {
+        let claims = JwtClaims {
+            sub: subject.to_string(),
+            exp: jsonwebtoken::get_current_timestamp() + 600,
+            max_transcript_size: None,
+        };
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+        format!("Bearer {token}")
+    };
+
+    let (status, body) =
+        http_request(session_request(&notary_config, Some(&token("prover-0")))).await;
+    assert_eq!(status, StatusCode::OK);
+    let session_id = serde_json::from_str::<NotarizationSessionResponse>(&body)
+        .unwrap()
+        .session_id;
+
+    // Requests without a token or with a token of another subject are rejected, and leave the
+    // session usable by its prover
+    assert_eq!(
+        notarize_status(&notary_config, &session_id, None).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        notarize_status(&notary_config, &session_id, Some(&token("prover-1"))).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        notarize_status(&notary_config, &session_id, Some(&token("prover-0"))).await,
+        StatusCode::SWITCHING_PROTOCOLS
+    );
+
+    // The session can only be used once
+    assert_eq!(
+        notarize_status(&notary_config, &session_id, Some(&token("prover-0"))).await,
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[tokio::test]
+async fn test_session_bound_to_client_certificate() {
+    let client_ca = generate_client_ca("Prover CA");
+    let mut notary_config = get_server_config(7060, true);
+    notary_config.tls.client_ca_pem_path = Some(write_temp_file(
+        "client-ca.crt",
+        &client_ca.serialize_pem().unwrap(),
+    ));
+    let notary_config = start_server(notary_config, 100).await;
+    let prover_0 = generate_client_certificate(&client_ca, "prover-0");
+    let prover_1 = generate_client_certificate(&client_ca, "prover-1");
+
+    let payload = serde_json::to_string(&NotarizationSessionRequest {
+        client_type: notary_server::ClientType::Tcp,
+        max_transcript_size: None,
+    })
+    .unwrap();
+    let request = Request::builder()
+        .uri("/session")
+        .method("POST")
+        .header("Host", notary_config.server.name.as_str())
+        .header("Content-Type", "application/json")
+        .body(Body::from(payload))
+        .unwrap();
+    let response = mtls_request(&notary_config, Some(prover_0.clone()), request)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body()).await.unwrap();
+    let session_id = serde_json::from_slice::<NotarizationSessionResponse>(&body)
+        .unwrap()
+        .session_id;
+
+    // A client with a certificate of another subject is rejected, and leaves the session usable
+    // by its prover
+    assert_eq!(
+        mtls_notarize_status(&notary_config, &session_id, Some(prover_1)).await,
+        Some(StatusCode::UNAUTHORIZED)
+    );
+    assert_eq!(
+        mtls_notarize_status(&notary_config, &session_id, Some(prover_0)).await,
+        Some(StatusCode::SWITCHING_PROTOCOLS)
+    );
+}
@@ -0,0 +1,140 @@
+use hyper::{
+    body::{to_bytes, Bytes},
+    service::{make_service_fn, service_fn},
+    Body, Client, HeaderMap, Request, Response, Server, StatusCode,
+};
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use notary_server::{
+    init_tracing, parse_config_file_with_overrides, run_server, NotarizationSessionRequest,
+    NotarizationSessionResponse, NotaryServerProperties, OtlpProperties,
+};
+
+/// Path of the gRPC method which the OTLP exporter calls to export spans
+const EXPORT_PATH: &str = "/opentelemetry.proto.collector.trace.v1.TraceService/Export";
+
+/// Start a stand-in for an OpenTelemetry collector, which accepts every export and records the
+/// encoded requests
+async fn start_collector(port: u16) -> Arc<Mutex<Vec<Bytes>>> {
+    let exports = Arc::new(Mutex::new(Vec::new()));
+    let recorded_exports = exports.clone();
+    let make_service = make_service_fn(move |_| {
+        let recorded_exports = recorded_exports.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                let recorded_exports = recorded_exports.clone();
+                async move {
+                    let is_export = request.uri().path() == EXPORT_PATH;
+                    let body = to_bytes(request.into_body()).await?;
+                    if is_export {
+                        recorded_exports.lock().unwrap().push(body);
+                    }
+
+                    // Reply with an empty ExportTraceServiceResponse and an OK status
+                    let (mut sender, response_body) = Body::channel();
+                    tokio::spawn(async move {
+                        sender.send_data(Bytes::from_static(&[0; 5])).await?;
+                        let mut trailers = HeaderMap::new();
+                        trailers.insert("grpc-status", "0".parse().unwrap());
+                        sender.send_trailers(trailers).await
+                    });
+                    Ok::<_, hyper::Error>(
+                        Response::builder()
+                            .header("Content-Type", "application/grpc")
+                            .body(response_body)
+                            .unwrap(),
+                    )
+                }
+            }))
+        }
+    });
+
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], port)))
+        .http2_only(true)
+        .serve(make_service);
+    tokio::spawn(server);
+    exports
+}
+
+// Runs in its own test binary, as the tracing subscriber is set up once per process
+#[tokio::test(flavor = "multi_thread")]
+async fn test_otlp_export() {
+    let exports = start_collector(7058).await;
+
+    let mut notary_config: NotaryServerProperties =
+        parse_config_file_with_overrides("./config/config.yaml", &[]).unwrap();
+    notary_config.server.host = "127.0.0.1".to_string();
+    notary_config.server.port = 7057;
+    notary_config.tls.enabled = false;
+    notary_config.tracing.otlp = Some(OtlpProperties {
+        endpoint: "http://127.0.0.1:7058".to_string(),
+        service_name: "notary-server-test".to_string(),
+    });
+    init_tracing(&notary_config).unwrap();
+
+    let config = notary_config.clone();
+    tokio::spawn(async move {
+        run_server(&config).await.unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let payload = serde_json::to_string(&NotarizationSessionRequest {
+        client_type: notary_server::ClientType::Tcp,
+        max_transcript_size: None,
+    })
+    .unwrap();
+    let request = Request::builder()
+        .uri("http://127.0.0.1:7057/session")
+        .method("POST")
+        .header("Content-Type", "application/json")
+        .body(Body::from(payload))
+        .unwrap();
+    let response = Client::new().request(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body()).await.unwrap();
+    let session_id = serde_json::from_slice::<NotarizationSessionResponse>(&body)
+        .unwrap()
+        .session_id;
+
+    // Start the notarization and disconnect, which ends its span
+    let (mut request_sender, connection) = hyper::client::conn::handshake(
+        tokio::net::TcpStream::connect("127.0.0.1:7057")
+            .await
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+    let connection_task = tokio::spawn(connection.without_shutdown());
+    let request = Request::builder()
+        .uri(format!("/notarize?sessionId={session_id}"))
+        .header("Host", "127.0.0.1")
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "TCP")
+        .body(Body::empty())
+        .unwrap();
+    let response = request_sender.send_request(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+    drop(connection_task.await.unwrap().unwrap().io);
+
+    // The batch exporter sends the ended spans every few seconds, the trace of the notarization
+    // carries its session id
+    let contains =
+        |export: &Bytes, needle: &[u8]| export.windows(needle.len()).any(|window| window == needle);
+    for _ in 0..60 {
+        let exported = exports.lock().unwrap().iter().any(|export| {
+            contains(export, b"notarization")
+                && contains(export, session_id.as_bytes())
+                && contains(export, b"notary-server-test")
+        });
+        if exported {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    panic!("The trace of the notarization was not exported to the collector");
+}
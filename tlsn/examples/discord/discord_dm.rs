@@ -157,16 +157,10 @@ async fn main() {
     debug!("Notarization complete!");
 
     // Dump the notarized session to a file
-    let mut file = tokio::fs::File::create("discord_dm_notarized_session.json")
+    let mut file = tokio::fs::File::create("discord_dm_notarized_session.bin")
         .await
         .unwrap();
-    file.write_all(
-        serde_json::to_string_pretty(&notarized_session)
-            .unwrap()
-            .as_bytes(),
-    )
-    .await
-    .unwrap();
+    file.write_all(&notarized_session.to_bytes()).await.unwrap();
 
     let session_proof = notarized_session.session_proof();
 
@@ -185,12 +179,10 @@ async fn main() {
     };
 
     // Dump the proof to a file.
-    let mut file = tokio::fs::File::create("discord_dm_proof.json")
-        .await
-        .unwrap();
-    file.write_all(serde_json::to_string_pretty(&proof).unwrap().as_bytes())
+    let mut file = tokio::fs::File::create("discord_dm_proof.bin")
         .await
         .unwrap();
+    file.write_all(&proof.to_bytes()).await.unwrap();
 }
 
 async fn setup_notary_connection() -> (tokio_rustls::client::TlsStream<TcpStream>, String) {
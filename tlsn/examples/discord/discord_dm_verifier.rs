@@ -4,12 +4,12 @@ use elliptic_curve::pkcs8::DecodePublicKey;
 
 use tlsn_core::proof::{SessionProof, TlsProof};
 
-/// A simple verifier which reads a proof generated by `discord_dm.rs` from "discord_dm_proof.json", verifies
+/// A simple verifier which reads a proof generated by `discord_dm.rs` from "discord_dm_proof.bin", verifies
 /// it and prints the verified data to the console.
 fn main() {
     // Deserialize the proof
-    let proof = std::fs::read_to_string("discord_dm_proof.json").unwrap();
-    let proof: TlsProof = serde_json::from_str(proof.as_str()).unwrap();
+    let proof = std::fs::read("discord_dm_proof.bin").unwrap();
+    let proof = TlsProof::from_bytes(&proof).unwrap();
 
     let TlsProof {
         // The session proof establishes the identity of the server and the commitments
@@ -5,7 +5,7 @@
 use futures::AsyncWriteExt;
 use hyper::{Body, Request, StatusCode};
 use std::ops::Range;
-use tlsn_core::proof::TlsProof;
+use tlsn_core::proof::Presentation;
 use tokio::io::{AsyncWriteExt as _, DuplexStream};
 use tokio_util::compat::{FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt};
 
@@ -106,13 +106,11 @@ async fn main() {
     };
 
     // Write the proof to a file
-    let mut file = tokio::fs::File::create("simple_proof.json").await.unwrap();
-    file.write_all(serde_json::to_string_pretty(&proof).unwrap().as_bytes())
-        .await
-        .unwrap();
+    let mut file = tokio::fs::File::create("simple_proof.bin").await.unwrap();
+    file.write_all(&proof.to_bytes()).await.unwrap();
 
     println!("Notarization completed successfully!");
-    println!("The proof has been written to `simple_proof.json`");
+    println!("The proof has been written to `simple_proof.bin`");
 }
 
 /// Find the ranges of the public and private parts of a sequence.
@@ -147,7 +145,7 @@ fn find_ranges(seq: &[u8], private_seq: &[&[u8]]) -> (Vec<Range<usize>>, Vec<Ran
     (public_ranges, private_ranges)
 }
 
-async fn build_proof_without_redactions(mut prover: Prover<Notarize>) -> TlsProof {
+async fn build_proof_without_redactions(mut prover: Prover<Notarize>) -> Presentation {
     let sent_len = prover.sent_transcript().data().len();
     let recv_len = prover.recv_transcript().data().len();
 
@@ -167,13 +165,10 @@ async fn build_proof_without_redactions(mut prover: Prover<Notarize>) -> TlsProo
 
     let substrings_proof = proof_builder.build().unwrap();
 
-    TlsProof {
-        session: notarized_session.session_proof(),
-        substrings: substrings_proof,
-    }
+    notarized_session.presentation(substrings_proof)
 }
 
-async fn build_proof_with_redactions(mut prover: Prover<Notarize>) -> TlsProof {
+async fn build_proof_with_redactions(mut prover: Prover<Notarize>) -> Presentation {
     // Identify the ranges in the outbound data which contain data which we want to disclose
     let (sent_public_ranges, _) = find_ranges(
         prover.sent_transcript().data(),
@@ -221,10 +216,7 @@ async fn build_proof_with_redactions(mut prover: Prover<Notarize>) -> TlsProof {
 
     let substrings_proof = proof_builder.build().unwrap();
 
-    TlsProof {
-        session: notarized_session.session_proof(),
-        substrings: substrings_proof,
-    }
+    notarized_session.presentation(substrings_proof)
 }
 
 async fn start_notary_thread(socket: DuplexStream) {
@@ -2,48 +2,30 @@ use std::{str, time::Duration};
 
 use elliptic_curve::pkcs8::DecodePublicKey;
 
-use tlsn_core::proof::{SessionProof, TlsProof};
+use tlsn_core::proof::Presentation;
 
-/// A simple verifier which reads a proof generated by `simple_prover.rs` from "proof.json", verifies
+/// A simple verifier which reads a proof generated by `simple_prover.rs` from "simple_proof.bin", verifies
 /// it and prints the verified data to the console.
 fn main() {
     // Deserialize the proof
-    let proof = std::fs::read_to_string("simple_proof.json").unwrap();
-    let proof: TlsProof = serde_json::from_str(proof.as_str()).unwrap();
+    let proof = std::fs::read("simple_proof.bin").unwrap();
+    let presentation = Presentation::from_bytes(&proof).unwrap();
 
-    let TlsProof {
-        // The session proof establishes the identity of the server and the commitments
-        // to the TLS transcript.
-        session,
-        // The substrings proof proves select portions of the transcript, while redacting
-        // anything the Prover chose not to disclose.
-        substrings,
-    } = proof;
-
-    // Verify the session proof against the Notary's public key
+    // Verify the presentation against the Notary's public key
     //
     // This verifies the identity of the server using a default certificate verifier which trusts
-    // the root certificates from the `webpki-roots` crate.
-    session
+    // the root certificates from the `webpki-roots` crate, and the revealed parts of the
+    // transcript against the session header that was signed by the Notary.
+    let verified = presentation
         .verify_with_default_cert_verifier(notary_pubkey())
         .unwrap();
 
-    let SessionProof {
-        // The session header that was signed by the Notary is a succinct commitment to the TLS transcript.
-        header,
-        // This is the session_info, which contains the server_name, that is checked against the
-        // certificate chain shared in the TLS handshake.
-        session_info,
-        ..
-    } = session;
-
     // The time at which the session was recorded
-    let time = chrono::DateTime::UNIX_EPOCH + Duration::from_secs(header.time());
+    let time = chrono::DateTime::UNIX_EPOCH + Duration::from_secs(verified.time());
+    let server_name = verified.server_name().clone();
 
-    // Verify the substrings proof against the session header.
-    //
-    // This returns the redacted transcripts
-    let (mut sent, mut recv) = substrings.verify(&header).unwrap();
+    // The redacted transcripts
+    let (mut sent, mut recv) = verified.into_transcripts();
 
     // Replace the bytes which the Prover chose not to disclose with 'X'
     sent.set_redacted(b'X');
@@ -52,7 +34,7 @@ fn main() {
     println!("-------------------------------------------------------------------");
     println!(
         "Successfully verified that the bytes below came from a session with {:?} at {}.",
-        session_info.server_name, time
+        server_name, time
     );
     println!("Note that the bytes which the Prover chose not to disclose are shown as X.");
     println!();
@@ -179,14 +179,8 @@ async fn main() {
     debug!("Notarization complete!");
 
     // Dump the notarized session to a file
-    let mut file = tokio::fs::File::create("twitter_dm.json").await.unwrap();
-    file.write_all(
-        serde_json::to_string_pretty(&notarized_session)
-            .unwrap()
-            .as_bytes(),
-    )
-    .await
-    .unwrap();
+    let mut file = tokio::fs::File::create("twitter_dm.bin").await.unwrap();
+    file.write_all(&notarized_session.to_bytes()).await.unwrap();
 
     let session_proof = notarized_session.session_proof();
 
@@ -202,12 +196,10 @@ async fn main() {
     };
 
     // Dump the proof to a file.
-    let mut file = tokio::fs::File::create("twitter_dm_proof.json")
-        .await
-        .unwrap();
-    file.write_all(serde_json::to_string_pretty(&proof).unwrap().as_bytes())
+    let mut file = tokio::fs::File::create("twitter_dm_proof.bin")
         .await
         .unwrap();
+    file.write_all(&proof.to_bytes()).await.unwrap();
 }
 
 async fn setup_notary_connection() -> (tokio_rustls::client::TlsStream<TcpStream>, String) {
@@ -3,26 +3,52 @@ use hyper::{body::to_bytes, Body, Request, StatusCode};
 use tlsn_prover::tls::{Prover, ProverConfig};
 use tlsn_server_fixture::{CA_CERT_DER, SERVER_DOMAIN};
 use tlsn_verifier::tls::{Verifier, VerifierConfig};
-use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, DuplexStream},
+    task::JoinHandle,
+};
 use tokio_util::compat::{FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt};
 use tracing::instrument;
 
 #[tokio::test]
 #[ignore]
 async fn notarize() {
-    tracing_subscriber::fmt::init();
+    let _ = tracing_subscriber::fmt::try_init();
 
     let (socket_0, socket_1) = tokio::io::duplex(2 << 23);
+    let (client_socket, server_socket) = tokio::io::duplex(2 << 16);
+
+    let server_task = tokio::spawn(tlsn_server_fixture::bind(server_socket.compat()));
 
-    tokio::join!(prover(socket_0), notary(socket_1));
+    tokio::join!(
+        prover(socket_0, client_socket, server_task),
+        notary(socket_1)
+    );
 }
 
-#[instrument(skip(notary_socket))]
-async fn prover<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(notary_socket: T) {
+#[tokio::test]
+#[ignore]
+async fn notarize_p384() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let (socket_0, socket_1) = tokio::io::duplex(2 << 23);
     let (client_socket, server_socket) = tokio::io::duplex(2 << 16);
 
-    let server_task = tokio::spawn(tlsn_server_fixture::bind(server_socket.compat()));
+    // The server only accepts P-384, which the prover must offer
+    let server_task = tokio::spawn(tlsn_server_fixture::bind_p384(server_socket.compat()));
+
+    tokio::join!(
+        prover(socket_0, client_socket, server_task),
+        notary(socket_1)
+    );
+}
 
+#[instrument(skip(notary_socket, client_socket, server_task))]
+async fn prover<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
+    notary_socket: T,
+    client_socket: DuplexStream,
+    server_task: JoinHandle<()>,
+) {
     let mut root_store = tls_core::anchors::RootCertStore::empty();
     root_store
         .add(&tls_core::key::Certificate(CA_CERT_DER.to_vec()))
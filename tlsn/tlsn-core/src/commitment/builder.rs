@@ -4,14 +4,16 @@ use bimap::BiMap;
 use mpz_core::hash::Hash;
 use utils::range::RangeSet;
 
+#[cfg(feature = "poseidon")]
+use crate::commitment::poseidon::PoseidonHashCommitment;
 use crate::{
     commitment::{
-        blake3::Blake3Commitment, Commitment, CommitmentId, CommitmentInfo, CommitmentKind,
-        TranscriptCommitments,
+        blake3::Blake3Commitment, plaintext::PlaintextHashCommitment, Commitment, CommitmentId,
+        CommitmentInfo, CommitmentKind, TranscriptCommitments,
     },
-    merkle::MerkleTree,
+    merkle::{HashAlgorithm, MerkleTree},
     transcript::get_value_ids,
-    Direction, EncodingProvider,
+    Direction, EncodingProvider, Transcript,
 };
 
 /// An error for [`TranscriptCommitmentBuilder`]
@@ -26,6 +28,9 @@ pub enum TranscriptCommitmentBuilderError {
     /// Failed to retrieve encodings for the provided transcript ranges.
     #[error("failed to retrieve encodings for the provided transcript ranges")]
     MissingEncodings,
+    /// The plaintext of the transcripts was not provided.
+    #[error("the plaintext of the transcripts is required to commit to plaintext hashes")]
+    MissingPlaintext,
     /// Duplicate commitment
     #[error("attempted to create a duplicate commitment, overwriting: {0:?}")]
     Duplicate(CommitmentId),
@@ -40,8 +45,12 @@ pub struct TranscriptCommitmentBuilder {
     /// Information about the above `commitments`.
     commitment_info: BiMap<CommitmentId, CommitmentInfo>,
     merkle_leaves: Vec<Hash>,
+    /// The hash algorithm of the Merkle tree.
+    merkle_hash: HashAlgorithm,
     /// A function that returns the encodings for the provided transcript byte ids.
     encoding_provider: EncodingProvider,
+    /// The sent and received transcripts, needed for plaintext hash commitments.
+    transcripts: Option<(Transcript, Transcript)>,
     sent_len: usize,
     recv_len: usize,
 }
@@ -60,12 +69,31 @@ impl TranscriptCommitmentBuilder {
             commitments: HashMap::default(),
             commitment_info: BiMap::default(),
             merkle_leaves: Vec::default(),
+            merkle_hash: HashAlgorithm::default(),
             encoding_provider,
+            transcripts: None,
             sent_len,
             recv_len,
         }
     }
 
+    /// Sets the sent and received transcripts, which are needed to commit to plaintext hashes.
+    #[doc(hidden)]
+    pub fn with_transcripts(
+        mut self,
+        transcript_tx: Transcript,
+        transcript_rx: Transcript,
+    ) -> Self {
+        self.transcripts = Some((transcript_tx, transcript_rx));
+        self
+    }
+
+    /// Sets the hash algorithm of the Merkle tree of the commitments, which defaults to SHA-256.
+    pub fn set_merkle_hash(&mut self, algorithm: HashAlgorithm) -> &mut Self {
+        self.merkle_hash = algorithm;
+        self
+    }
+
     /// Commits to the provided ranges of the `sent` transcript.
     pub fn commit_sent(
         &mut self,
@@ -82,6 +110,55 @@ impl TranscriptCommitmentBuilder {
         self.add_substrings_commitment(ranges.into(), Direction::Received)
     }
 
+    /// Commits to the salted SHA-256 hash of the plaintext in the provided ranges of the `sent`
+    /// transcript.
+    ///
+    /// The hash is checked by the Notary in MPC, so it is more costly to create than a BLAKE3
+    /// commitment, but its openings can be checked without the encodings of the transcript.
+    pub fn commit_sent_hash(
+        &mut self,
+        ranges: impl Into<RangeSet<usize>>,
+    ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
+        self.add_plaintext_hash_commitment(ranges.into(), Direction::Sent)
+    }
+
+    /// Commits to the salted SHA-256 hash of the plaintext in the provided ranges of the
+    /// `received` transcript.
+    ///
+    /// See [`commit_sent_hash`](Self::commit_sent_hash).
+    pub fn commit_recv_hash(
+        &mut self,
+        ranges: impl Into<RangeSet<usize>>,
+    ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
+        self.add_plaintext_hash_commitment(ranges.into(), Direction::Received)
+    }
+
+    /// Commits to the salted Poseidon hash of the plaintext in the provided ranges of the `sent`
+    /// transcript.
+    ///
+    /// The Notary computes the salted SHA-256 hash of the same plaintext in MPC, so notarization
+    /// additionally needs a proving key for the length of the committed data, see
+    /// [`poseidon`](crate::commitment::poseidon).
+    #[cfg(feature = "poseidon")]
+    pub fn commit_sent_poseidon(
+        &mut self,
+        ranges: impl Into<RangeSet<usize>>,
+    ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
+        self.add_poseidon_hash_commitment(ranges.into(), Direction::Sent)
+    }
+
+    /// Commits to the salted Poseidon hash of the plaintext in the provided ranges of the
+    /// `received` transcript.
+    ///
+    /// See [`commit_sent_poseidon`](Self::commit_sent_poseidon).
+    #[cfg(feature = "poseidon")]
+    pub fn commit_recv_poseidon(
+        &mut self,
+        ranges: impl Into<RangeSet<usize>>,
+    ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
+        self.add_poseidon_hash_commitment(ranges.into(), Direction::Received)
+    }
+
     /// Gets the commitment id for the provided commitment info.
     pub fn get_id(
         &self,
@@ -98,12 +175,12 @@ impl TranscriptCommitmentBuilder {
             .copied()
     }
 
-    /// Add a commitment to substrings of the transcript
-    fn add_substrings_commitment(
-        &mut self,
-        ranges: RangeSet<usize>,
+    /// Checks that the ranges are not empty and within the bounds of the transcript
+    fn check_ranges(
+        &self,
+        ranges: &RangeSet<usize>,
         direction: Direction,
-    ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
+    ) -> Result<(), TranscriptCommitmentBuilderError> {
         let max = ranges
             .max()
             .ok_or(TranscriptCommitmentBuilderError::EmptyRange)?;
@@ -116,6 +193,17 @@ impl TranscriptCommitmentBuilder {
             return Err(TranscriptCommitmentBuilderError::RangeOutOfBounds);
         }
 
+        Ok(())
+    }
+
+    /// Add a commitment to substrings of the transcript
+    fn add_substrings_commitment(
+        &mut self,
+        ranges: RangeSet<usize>,
+        direction: Direction,
+    ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
+        self.check_ranges(&ranges, direction)?;
+
         let ids: Vec<_> = get_value_ids(&ranges, direction).collect();
 
         let id_refs = ids.iter().map(|id| id.as_ref()).collect::<Vec<_>>();
@@ -123,13 +211,69 @@ impl TranscriptCommitmentBuilder {
         let encodings = (self.encoding_provider)(&id_refs)
             .ok_or(TranscriptCommitmentBuilderError::MissingEncodings)?;
 
-        // We only support BLAKE3 for now
         let commitment = Blake3Commitment::new(&encodings);
-        let hash = *commitment.hash();
 
-        let id = CommitmentId::new(self.merkle_leaves.len() as u32);
+        self.add_commitment(commitment.into(), ranges, direction)
+    }
 
-        let commitment: Commitment = commitment.into();
+    /// Returns the plaintext in the provided ranges of the transcript
+    fn get_plaintext(
+        &self,
+        ranges: &RangeSet<usize>,
+        direction: Direction,
+    ) -> Result<Vec<u8>, TranscriptCommitmentBuilderError> {
+        self.check_ranges(ranges, direction)?;
+
+        let (transcript_tx, transcript_rx) = self
+            .transcripts
+            .as_ref()
+            .ok_or(TranscriptCommitmentBuilderError::MissingPlaintext)?;
+
+        Ok(match direction {
+            Direction::Sent => transcript_tx,
+            Direction::Received => transcript_rx,
+        }
+        .get_bytes_in_ranges(ranges))
+    }
+
+    /// Add a commitment to the salted hash of the plaintext of substrings of the transcript
+    fn add_plaintext_hash_commitment(
+        &mut self,
+        ranges: RangeSet<usize>,
+        direction: Direction,
+    ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
+        let data = self.get_plaintext(&ranges, direction)?;
+
+        let commitment = PlaintextHashCommitment::new(&data, &ranges, direction);
+
+        self.add_commitment(commitment.into(), ranges, direction)
+    }
+
+    /// Add a commitment to the salted Poseidon hash of the plaintext of substrings of the
+    /// transcript
+    #[cfg(feature = "poseidon")]
+    fn add_poseidon_hash_commitment(
+        &mut self,
+        ranges: RangeSet<usize>,
+        direction: Direction,
+    ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
+        let data = self.get_plaintext(&ranges, direction)?;
+
+        let commitment = PoseidonHashCommitment::new(&data, &ranges, direction);
+
+        self.add_commitment(commitment.into(), ranges, direction)
+    }
+
+    /// Add a commitment and insert its hash into the merkle tree
+    fn add_commitment(
+        &mut self,
+        commitment: Commitment,
+        ranges: RangeSet<usize>,
+        direction: Direction,
+    ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
+        let hash = commitment.hash();
+
+        let id = CommitmentId::new(self.merkle_leaves.len() as u32);
 
         // Store commitment with its id
         self.commitment_info
@@ -156,10 +300,11 @@ impl TranscriptCommitmentBuilder {
             commitments,
             commitment_info,
             merkle_leaves,
+            merkle_hash,
             ..
         } = self;
 
-        let merkle_tree = MerkleTree::from_leaves(&merkle_leaves)
+        let merkle_tree = MerkleTree::from_leaves_with_hash(&merkle_leaves, merkle_hash)
             .map_err(|_| TranscriptCommitmentBuilderError::NoCommitments)?;
 
         Ok(TranscriptCommitments {
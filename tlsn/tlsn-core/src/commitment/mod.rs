@@ -3,18 +3,24 @@
 /// BLAKE3 commitments.
 pub mod blake3;
 mod builder;
+/// Plaintext hash commitments.
+pub mod plaintext;
+#[cfg(feature = "poseidon")]
+pub mod poseidon;
 
 use std::collections::HashMap;
 
 use bimap::BiMap;
+use mpz_circuits::types::ValueType;
 use mpz_core::hash::Hash;
-use mpz_garble_core::{encoding_state::Full, EncodedValue};
+use mpz_garble_core::Encoder;
 use serde::{Deserialize, Serialize};
 use utils::range::RangeSet;
 
 use crate::{
     merkle::{MerkleRoot, MerkleTree},
-    Direction,
+    transcript::get_value_ids,
+    Direction, EncodingId, SessionHeader,
 };
 
 pub use builder::{TranscriptCommitmentBuilder, TranscriptCommitmentBuilderError};
@@ -75,6 +81,11 @@ impl CommitmentInfo {
 pub enum Commitment {
     /// A BLAKE3 commitment to encodings of the transcript.
     Blake3(blake3::Blake3Commitment),
+    /// A SHA-256 commitment to the salted plaintext of the transcript.
+    Sha256(plaintext::PlaintextHashCommitment),
+    /// A Poseidon commitment to the salted plaintext of the transcript.
+    #[cfg(feature = "poseidon")]
+    Poseidon(poseidon::PoseidonHashCommitment),
 }
 
 impl Commitment {
@@ -82,6 +93,9 @@ impl Commitment {
     pub fn hash(&self) -> Hash {
         match self {
             Commitment::Blake3(commitment) => *commitment.hash(),
+            Commitment::Sha256(commitment) => *commitment.hash(),
+            #[cfg(feature = "poseidon")]
+            Commitment::Poseidon(commitment) => *commitment.hash(),
         }
     }
 
@@ -89,6 +103,9 @@ impl Commitment {
     pub fn kind(&self) -> CommitmentKind {
         match self {
             Commitment::Blake3(_) => CommitmentKind::Blake3,
+            Commitment::Sha256(_) => CommitmentKind::Sha256,
+            #[cfg(feature = "poseidon")]
+            Commitment::Poseidon(_) => CommitmentKind::Poseidon,
         }
     }
 }
@@ -99,6 +116,19 @@ impl Commitment {
 pub enum CommitmentKind {
     /// A BLAKE3 commitment to encodings of the transcript.
     Blake3,
+    /// A SHA-256 commitment to the salted plaintext of the transcript.
+    Sha256,
+    /// A Poseidon commitment to the salted plaintext of the transcript.
+    #[cfg(feature = "poseidon")]
+    Poseidon,
+}
+
+impl CommitmentKind {
+    /// Returns whether this is a commitment to the plaintext of the transcript, which the Notary
+    /// checks in MPC.
+    pub fn is_plaintext_hash(&self) -> bool {
+        !matches!(self, CommitmentKind::Blake3)
+    }
 }
 
 /// An opening to a commitment to the transcript.
@@ -107,6 +137,11 @@ pub enum CommitmentKind {
 pub enum CommitmentOpening {
     /// An opening to a BLAKE3 commitment
     Blake3(blake3::Blake3Opening),
+    /// An opening to a SHA-256 plaintext hash commitment
+    Sha256(plaintext::PlaintextHashOpening),
+    /// An opening to a Poseidon plaintext hash commitment
+    #[cfg(feature = "poseidon")]
+    Poseidon(poseidon::PoseidonHashOpening),
 }
 
 impl CommitmentOpening {
@@ -114,20 +149,46 @@ impl CommitmentOpening {
     pub fn kind(&self) -> CommitmentKind {
         match self {
             CommitmentOpening::Blake3(_) => CommitmentKind::Blake3,
+            CommitmentOpening::Sha256(_) => CommitmentKind::Sha256,
+            #[cfg(feature = "poseidon")]
+            CommitmentOpening::Poseidon(_) => CommitmentKind::Poseidon,
         }
     }
 
     /// Recovers the expected commitment from this opening.
     ///
-    /// # Panics
+    /// Only BLAKE3 openings need the encodings of the transcript, which are regenerated from the
+    /// encoder seed of the session header.
+    ///
+    /// # Arguments
+    ///
+    /// * `info` - The info of the commitment.
+    /// * `header` - The session header.
     ///
-    /// Implementations may panic if the following conditions are not met:
+    /// # Panics
     ///
-    /// - If the number of encodings does not match the number of bytes in the opening.
-    /// - If an encoding is not for a u8.
-    pub fn recover(&self, encodings: &[EncodedValue<Full>]) -> Commitment {
+    /// Implementations may panic if the number of bytes in the committed ranges does not match the
+    /// number of bytes in the opening.
+    pub fn recover(&self, info: &CommitmentInfo, header: &SessionHeader) -> Commitment {
         match self {
-            CommitmentOpening::Blake3(opening) => opening.recover(encodings).into(),
+            CommitmentOpening::Blake3(opening) => {
+                let encodings = get_value_ids(info.ranges(), *info.direction())
+                    .map(|id| {
+                        header
+                            .encoder()
+                            .encode_by_type(EncodingId::new(&id).to_inner(), &ValueType::U8)
+                    })
+                    .collect::<Vec<_>>();
+
+                opening.recover(&encodings).into()
+            }
+            CommitmentOpening::Sha256(opening) => {
+                opening.recover(info.ranges(), *info.direction()).into()
+            }
+            #[cfg(feature = "poseidon")]
+            CommitmentOpening::Poseidon(opening) => {
+                opening.recover(info.ranges(), *info.direction()).into()
+            }
         }
     }
 
@@ -135,6 +196,9 @@ impl CommitmentOpening {
     pub fn data(&self) -> &[u8] {
         match self {
             CommitmentOpening::Blake3(opening) => opening.data(),
+            CommitmentOpening::Sha256(opening) => opening.data(),
+            #[cfg(feature = "poseidon")]
+            CommitmentOpening::Poseidon(opening) => opening.data(),
         }
     }
 
@@ -142,6 +206,9 @@ impl CommitmentOpening {
     pub fn into_data(self) -> Vec<u8> {
         match self {
             CommitmentOpening::Blake3(opening) => opening.into_data(),
+            CommitmentOpening::Sha256(opening) => opening.into_data(),
+            #[cfg(feature = "poseidon")]
+            CommitmentOpening::Poseidon(opening) => opening.into_data(),
         }
     }
 }
@@ -151,8 +218,10 @@ impl CommitmentOpening {
 pub struct TranscriptCommitments {
     /// A Merkle tree of commitments. Each commitment's index in the tree matches its `CommitmentId`.
     merkle_tree: MerkleTree,
+    #[serde(serialize_with = "crate::format::serialize_sorted_map")]
     commitments: HashMap<CommitmentId, Commitment>,
     /// Information about the above `commitments`.
+    #[serde(serialize_with = "crate::format::serialize_sorted_bimap")]
     commitment_info: BiMap<CommitmentId, CommitmentInfo>,
 }
 
@@ -190,4 +259,41 @@ impl TranscriptCommitments {
     pub fn get_info(&self, id: &CommitmentId) -> Option<&CommitmentInfo> {
         self.commitment_info.get_by_left(id)
     }
+
+    /// Returns the plaintext hash commitments with their info, salt and salted SHA-256 digest,
+    /// sorted by id.
+    ///
+    /// The SHA-256 digest is the one which the Notary computes in MPC, which for a Poseidon
+    /// commitment is of the same salted plaintext as its Poseidon digest.
+    pub fn plaintext_hashes(
+        &self,
+    ) -> Vec<(
+        CommitmentId,
+        &CommitmentInfo,
+        &[u8; plaintext::PLAINTEXT_HASH_SALT_LEN],
+        &[u8; 32],
+    )> {
+        let mut hashes = self
+            .commitments
+            .iter()
+            .filter_map(|(id, commitment)| {
+                let (salt, digest) = match commitment {
+                    Commitment::Sha256(commitment) => (commitment.salt(), commitment.digest()),
+                    #[cfg(feature = "poseidon")]
+                    Commitment::Poseidon(commitment) => {
+                        (commitment.salt(), commitment.sha256_digest())
+                    }
+                    _ => return None,
+                };
+                Some((
+                    *id,
+                    self.get_info(id).expect("info exists if commitment exists"),
+                    salt,
+                    digest,
+                ))
+            })
+            .collect::<Vec<_>>();
+        hashes.sort_by_key(|(id, ..)| *id);
+        hashes
+    }
 }
@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use mpz_circuits::{circuits::sha256_trace, Circuit, CircuitBuilder, Tracer};
+use mpz_core::hash::Hash;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utils::range::RangeSet;
+
+use crate::{
+    commitment::{Commitment, CommitmentOpening},
+    Direction,
+};
+
+/// The length of the salt of a plaintext hash commitment.
+pub const PLAINTEXT_HASH_SALT_LEN: usize = 16;
+
+/// Domain separator of the Merkle leaves of plaintext hash commitments.
+const LEAF_DOMAIN: &[u8] = b"tlsn/plaintext-sha256";
+
+static SHA256_INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// A SHA-256 commitment to the salted plaintext of substrings of a [`Transcript`](crate::Transcript).
+///
+/// The digest is `SHA-256(data || salt)`, where `data` is the concatenation of the bytes in the
+/// committed ranges. Unlike a [`Blake3Commitment`](crate::commitment::blake3::Blake3Commitment),
+/// it can be opened without the encodings of the transcript, so it is cheap to check for any
+/// verifier, including ZK circuits and smart contracts.
+///
+/// The Prover proves to the Notary in MPC that the digest was computed over the authenticated
+/// plaintext, before the Notary signs the session header.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct PlaintextHashCommitment {
+    digest: [u8; 32],
+    salt: [u8; PLAINTEXT_HASH_SALT_LEN],
+    leaf: Hash,
+}
+
+opaque_debug::implement!(PlaintextHashCommitment);
+
+impl PlaintextHashCommitment {
+    /// Creates a new plaintext hash commitment with a random salt.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The bytes of the transcript in the committed ranges.
+    /// * `ranges` - The committed ranges of the transcript.
+    /// * `direction` - The direction of the transcript.
+    pub fn new(data: &[u8], ranges: &RangeSet<usize>, direction: Direction) -> Self {
+        Self::new_with_salt(data, rand::thread_rng().gen(), ranges, direction)
+    }
+
+    /// Creates a new plaintext hash commitment with the provided salt.
+    pub(crate) fn new_with_salt(
+        data: &[u8],
+        salt: [u8; PLAINTEXT_HASH_SALT_LEN],
+        ranges: &RangeSet<usize>,
+        direction: Direction,
+    ) -> Self {
+        let digest = plaintext_hash(data, &salt);
+
+        Self {
+            digest,
+            salt,
+            leaf: leaf_hash(&digest, ranges, direction),
+        }
+    }
+
+    /// Returns the hash of this commitment, which is its leaf in the Merkle tree of commitments.
+    pub fn hash(&self) -> &Hash {
+        &self.leaf
+    }
+
+    /// Returns the salted plaintext digest of this commitment.
+    pub fn digest(&self) -> &[u8; 32] {
+        &self.digest
+    }
+
+    /// Returns the salt of this commitment.
+    pub fn salt(&self) -> &[u8; PLAINTEXT_HASH_SALT_LEN] {
+        &self.salt
+    }
+
+    /// Opens this commitment
+    pub fn open(&self, data: Vec<u8>) -> PlaintextHashOpening {
+        PlaintextHashOpening::new(data, self.salt)
+    }
+}
+
+impl From<PlaintextHashCommitment> for Commitment {
+    fn from(value: PlaintextHashCommitment) -> Self {
+        Self::Sha256(value)
+    }
+}
+
+/// A substring opening using a plaintext hash.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlaintextHashOpening {
+    data: Vec<u8>,
+    salt: [u8; PLAINTEXT_HASH_SALT_LEN],
+}
+
+impl PlaintextHashOpening {
+    pub(crate) fn new(data: Vec<u8>, salt: [u8; PLAINTEXT_HASH_SALT_LEN]) -> Self {
+        Self { data, salt }
+    }
+
+    /// Recovers the expected commitment from this opening.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranges` - The committed ranges of the transcript.
+    /// * `direction` - The direction of the transcript.
+    pub fn recover(
+        &self,
+        ranges: &RangeSet<usize>,
+        direction: Direction,
+    ) -> PlaintextHashCommitment {
+        PlaintextHashCommitment::new_with_salt(&self.data, self.salt, ranges, direction)
+    }
+
+    /// Returns the salt of this opening
+    pub fn salt(&self) -> &[u8; PLAINTEXT_HASH_SALT_LEN] {
+        &self.salt
+    }
+
+    /// Returns the transcript data corresponding to this opening
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the transcript data corresponding to this opening
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl From<PlaintextHashOpening> for CommitmentOpening {
+    fn from(value: PlaintextHashOpening) -> Self {
+        Self::Sha256(value)
+    }
+}
+
+/// Computes the salted plaintext digest `SHA-256(data || salt)`.
+pub fn plaintext_hash(data: &[u8], salt: &[u8; PLAINTEXT_HASH_SALT_LEN]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+/// Computes the Merkle leaf of a plaintext hash commitment.
+///
+/// The leaf binds the digest to the committed ranges, so that an opening can not be presented
+/// for other bytes of the transcript. It is
+/// `SHA-256("tlsn/plaintext-sha256" || direction || (start || end)* || digest)`, where
+/// `direction` is 0 for sent and 1 for received data, and the range bounds are big-endian `u64`.
+pub fn leaf_hash(digest: &[u8; 32], ranges: &RangeSet<usize>, direction: Direction) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(LEAF_DOMAIN);
+    hasher.update([match direction {
+        Direction::Sent => 0u8,
+        Direction::Received => 1u8,
+    }]);
+    for range in ranges.iter_ranges() {
+        hasher.update((range.start as u64).to_be_bytes());
+        hasher.update((range.end as u64).to_be_bytes());
+    }
+    hasher.update(digest);
+
+    let leaf: [u8; 32] = hasher.finalize().into();
+    Hash::from(leaf)
+}
+
+/// Builds the circuit which computes the salted plaintext digest of `len` bytes.
+///
+/// The inputs of the circuit are the `len` bytes of the plaintext, each as a `u8`, followed by the
+/// salt as a `[u8; PLAINTEXT_HASH_SALT_LEN]`. The output is the digest as a `[u8; 32]`.
+pub fn build_plaintext_hash_circuit(len: usize) -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let data = (0..len)
+        .map(|_| builder.add_input::<u8>())
+        .collect::<Vec<_>>();
+    let salt = builder.add_array_input::<u8, PLAINTEXT_HASH_SALT_LEN>();
+
+    let initial_state = SHA256_INITIAL_STATE
+        .map(|v| Tracer::new(builder.state(), builder.get_constant(v).to_inner()));
+    let msg = data.into_iter().chain(salt).collect::<Vec<_>>();
+
+    let digest = sha256_trace(builder.state(), initial_state, 0, &msg);
+    builder.add_output(digest);
+
+    Arc::new(
+        builder
+            .build()
+            .expect("plaintext hash circuit should build"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::evaluate;
+
+    use super::*;
+
+    #[test]
+    fn test_plaintext_hash_circuit() {
+        let circ = build_plaintext_hash_circuit(3);
+        let salt = [42u8; PLAINTEXT_HASH_SALT_LEN];
+
+        let digest = evaluate!(circ, fn(1u8, 2u8, 3u8, salt) -> [u8; 32]).unwrap();
+
+        assert_eq!(digest, plaintext_hash(&[1, 2, 3], &salt));
+    }
+
+    #[test]
+    fn test_recover() {
+        let ranges = RangeSet::from([0..2, 4..5]);
+        let commitment = PlaintextHashCommitment::new(b"abc", &ranges, Direction::Sent);
+        let opening = commitment.open(b"abc".to_vec());
+
+        assert_eq!(
+            opening.recover(&ranges, Direction::Sent).hash(),
+            commitment.hash()
+        );
+        // The leaf is bound to the committed ranges
+        assert_ne!(
+            opening.recover(&ranges, Direction::Received).hash(),
+            commitment.hash()
+        );
+        assert_ne!(
+            opening
+                .recover(&RangeSet::from(0..3), Direction::Sent)
+                .hash(),
+            commitment.hash()
+        );
+    }
+}
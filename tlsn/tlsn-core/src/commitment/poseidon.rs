@@ -0,0 +1,535 @@
+//! A Poseidon commitment to the salted plaintext of substrings of a
+//! [`Transcript`](crate::Transcript).
+//!
+//! Poseidon is cheap to evaluate in arithmetic circuits, so these commitments are meant to be
+//! opened in ZK circuits and smart contracts, but it is impractical to evaluate in the boolean
+//! circuits of the MPC. The Notary instead computes the salted SHA-256 digest of the same
+//! plaintext in MPC, like for a
+//! [`PlaintextHashCommitment`](crate::commitment::plaintext::PlaintextHashCommitment), and the
+//! Prover proves with a Groth16 proof over BN254 that the Poseidon digest is of the same salted
+//! plaintext.
+//!
+//! # Setup
+//!
+//! Groth16 needs a setup for each circuit, which here is determined by the length of the committed
+//! data. The keys are created with [`setup_poseidon_keys`], and must be created by the Notary, or
+//! in a ceremony it trusts, as anyone knowing the randomness of the setup can forge proofs. The
+//! same keys can be used for any session.
+
+use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::{
+    crh::sha256::constraints::Sha256Gadget,
+    sponge::{constraints::CryptographicSpongeVar, poseidon::constraints::PoseidonSpongeVar},
+};
+use ark_ff::{PrimeField, ToConstraintField};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use mpz_core::hash::Hash;
+use rand::{CryptoRng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use utils::range::RangeSet;
+
+use crate::{
+    commitment::{
+        plaintext::{plaintext_hash, PLAINTEXT_HASH_SALT_LEN},
+        Commitment, CommitmentOpening,
+    },
+    format::{deserialize_ark, serialize_ark},
+    merkle::poseidon::{config, hash_elements},
+    Direction,
+};
+
+/// The first element hashed into a digest, distinct from the prefixes of the Merkle tree.
+const DIGEST_PREFIX: u64 = 2;
+/// The first element hashed into a Merkle leaf, distinct from the prefixes of the Merkle tree.
+const LEAF_PREFIX: u64 = 3;
+/// The number of bytes packed into a field element, which is less than the size of the field.
+const CHUNK_LEN: usize = 31;
+
+/// A Poseidon commitment to the salted plaintext of substrings of a
+/// [`Transcript`](crate::Transcript).
+///
+/// See the [module documentation](self) for how the Notary checks it.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct PoseidonHashCommitment {
+    digest: [u8; 32],
+    sha256_digest: [u8; 32],
+    salt: [u8; PLAINTEXT_HASH_SALT_LEN],
+    leaf: Hash,
+}
+
+opaque_debug::implement!(PoseidonHashCommitment);
+
+impl PoseidonHashCommitment {
+    /// Creates a new Poseidon commitment with a random salt.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The bytes of the transcript in the committed ranges.
+    /// * `ranges` - The committed ranges of the transcript.
+    /// * `direction` - The direction of the transcript.
+    pub fn new(data: &[u8], ranges: &RangeSet<usize>, direction: Direction) -> Self {
+        Self::new_with_salt(data, rand::thread_rng().gen(), ranges, direction)
+    }
+
+    /// Creates a new Poseidon commitment with the provided salt.
+    pub(crate) fn new_with_salt(
+        data: &[u8],
+        salt: [u8; PLAINTEXT_HASH_SALT_LEN],
+        ranges: &RangeSet<usize>,
+        direction: Direction,
+    ) -> Self {
+        let digest = poseidon_hash(data, &salt);
+
+        Self {
+            digest,
+            sha256_digest: plaintext_hash(data, &salt),
+            salt,
+            leaf: leaf_hash(&digest, ranges, direction),
+        }
+    }
+
+    /// Returns the hash of this commitment, which is its leaf in the Merkle tree of commitments.
+    pub fn hash(&self) -> &Hash {
+        &self.leaf
+    }
+
+    /// Returns the salted plaintext digest of this commitment.
+    pub fn digest(&self) -> &[u8; 32] {
+        &self.digest
+    }
+
+    /// Returns the salted SHA-256 digest of the same plaintext, which the Notary computes in MPC.
+    pub fn sha256_digest(&self) -> &[u8; 32] {
+        &self.sha256_digest
+    }
+
+    /// Returns the salt of this commitment.
+    pub fn salt(&self) -> &[u8; PLAINTEXT_HASH_SALT_LEN] {
+        &self.salt
+    }
+
+    /// Opens this commitment
+    pub fn open(&self, data: Vec<u8>) -> PoseidonHashOpening {
+        PoseidonHashOpening::new(data, self.salt)
+    }
+
+    /// Proves that the Poseidon and SHA-256 digests of this commitment are of the same salted
+    /// plaintext, returning the compressed proof.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The bytes of the transcript in the committed ranges.
+    /// * `keys` - The proving keys, one of which must be for the length of `data`.
+    /// * `rng` - The randomness of the proof.
+    pub(crate) fn prove_link<R: RngCore + CryptoRng>(
+        &self,
+        data: Vec<u8>,
+        keys: &[PoseidonProvingKey],
+        rng: &mut R,
+    ) -> Result<Vec<u8>, PoseidonLinkError> {
+        let key = keys
+            .iter()
+            .find(|key| key.len == data.len())
+            .ok_or(PoseidonLinkError::MissingKey(data.len()))?;
+
+        let circuit = LinkCircuit {
+            data,
+            salt: self.salt,
+            sha256_digest: self.sha256_digest,
+            digest: Fr::from_le_bytes_mod_order(&self.digest),
+        };
+
+        let proof = Groth16::<Bn254>::prove(&key.key, circuit, rng)
+            .map_err(|e| PoseidonLinkError::ProofError(e.to_string()))?;
+
+        let mut bytes = Vec::with_capacity(proof.compressed_size());
+        proof
+            .serialize_compressed(&mut bytes)
+            .map_err(|e| PoseidonLinkError::ProofError(e.to_string()))?;
+
+        Ok(bytes)
+    }
+}
+
+impl From<PoseidonHashCommitment> for Commitment {
+    fn from(value: PoseidonHashCommitment) -> Self {
+        Self::Poseidon(value)
+    }
+}
+
+/// A substring opening using a Poseidon plaintext hash.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PoseidonHashOpening {
+    data: Vec<u8>,
+    salt: [u8; PLAINTEXT_HASH_SALT_LEN],
+}
+
+impl PoseidonHashOpening {
+    pub(crate) fn new(data: Vec<u8>, salt: [u8; PLAINTEXT_HASH_SALT_LEN]) -> Self {
+        Self { data, salt }
+    }
+
+    /// Recovers the expected commitment from this opening.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranges` - The committed ranges of the transcript.
+    /// * `direction` - The direction of the transcript.
+    pub fn recover(
+        &self,
+        ranges: &RangeSet<usize>,
+        direction: Direction,
+    ) -> PoseidonHashCommitment {
+        PoseidonHashCommitment::new_with_salt(&self.data, self.salt, ranges, direction)
+    }
+
+    /// Returns the salt of this opening
+    pub fn salt(&self) -> &[u8; PLAINTEXT_HASH_SALT_LEN] {
+        &self.salt
+    }
+
+    /// Returns the transcript data corresponding to this opening
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the transcript data corresponding to this opening
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl From<PoseidonHashOpening> for CommitmentOpening {
+    fn from(value: PoseidonHashOpening) -> Self {
+        Self::Poseidon(value)
+    }
+}
+
+/// Returns the field elements hashed into the digest of `data`.
+///
+/// These are the prefix, the length of the data, the data in little-endian chunks of
+/// [`CHUNK_LEN`] bytes and the salt.
+fn digest_elements(data: &[u8], salt: &[u8; PLAINTEXT_HASH_SALT_LEN]) -> Vec<Fr> {
+    let mut elements = vec![Fr::from(DIGEST_PREFIX), Fr::from(data.len() as u64)];
+    elements.extend(
+        data.chunks(CHUNK_LEN)
+            .chain(std::iter::once(salt.as_slice()))
+            .map(Fr::from_le_bytes_mod_order),
+    );
+    elements
+}
+
+/// Computes the salted plaintext digest with the Poseidon sponge of the
+/// [Merkle tree](crate::merkle::HashAlgorithm::Poseidon).
+///
+/// The hashed field elements are `2 || len || chunks || salt`, where `len` is the length of
+/// `data`, `chunks` is `data` split into chunks of 31 bytes and `salt` is a single element. Each
+/// chunk is read as a little-endian number. The digest is encoded in little-endian.
+pub fn poseidon_hash(data: &[u8], salt: &[u8; PLAINTEXT_HASH_SALT_LEN]) -> [u8; 32] {
+    hash_elements(&digest_elements(data, salt))
+}
+
+/// Computes the Merkle leaf of a Poseidon commitment.
+///
+/// The leaf binds the digest to the committed ranges, so that an opening can not be presented
+/// for other bytes of the transcript. It is the Poseidon hash of
+/// `3 || direction || (start || end)* || digest`, where `direction` is 0 for sent and 1 for
+/// received data.
+pub fn leaf_hash(digest: &[u8; 32], ranges: &RangeSet<usize>, direction: Direction) -> Hash {
+    let mut elements = vec![
+        Fr::from(LEAF_PREFIX),
+        Fr::from(match direction {
+            Direction::Sent => 0u64,
+            Direction::Received => 1u64,
+        }),
+    ];
+    for range in ranges.iter_ranges() {
+        elements.push(Fr::from(range.start as u64));
+        elements.push(Fr::from(range.end as u64));
+    }
+    elements.push(Fr::from_le_bytes_mod_order(digest));
+
+    Hash::from(hash_elements(&elements))
+}
+
+/// The circuit of a link proof.
+///
+/// The public inputs are the salted SHA-256 digest followed by the Poseidon digest.
+struct LinkCircuit {
+    data: Vec<u8>,
+    salt: [u8; PLAINTEXT_HASH_SALT_LEN],
+    sha256_digest: [u8; 32],
+    digest: Fr,
+}
+
+impl ConstraintSynthesizer<Fr> for LinkCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let sha256_digest = UInt8::new_input_vec(cs.clone(), &self.sha256_digest)?;
+        let digest = FpVar::new_input(cs.clone(), || Ok(self.digest))?;
+        let data = UInt8::new_witness_vec(cs.clone(), self.data.as_slice())?;
+        let salt = UInt8::new_witness_vec(cs.clone(), self.salt.as_slice())?;
+
+        let salted_data = data.iter().chain(&salt).cloned().collect::<Vec<_>>();
+        Sha256Gadget::digest(&salted_data)?
+            .0
+            .as_slice()
+            .enforce_equal(sha256_digest.as_slice())?;
+
+        let mut elements = vec![
+            FpVar::constant(Fr::from(DIGEST_PREFIX)),
+            FpVar::constant(Fr::from(data.len() as u64)),
+        ];
+        for chunk in data
+            .chunks(CHUNK_LEN)
+            .chain(std::iter::once(salt.as_slice()))
+        {
+            let bits = chunk
+                .iter()
+                .map(|byte| byte.to_bits_le())
+                .collect::<Result<Vec<_>, _>>()?
+                .concat();
+            elements.push(Boolean::le_bits_to_fp_var(&bits)?);
+        }
+
+        let mut sponge = PoseidonSpongeVar::new(cs, config());
+        sponge.absorb(&elements)?;
+        sponge.squeeze_field_elements(1)?[0].enforce_equal(&digest)
+    }
+}
+
+/// A proving key for link proofs of Poseidon commitments.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PoseidonProvingKey {
+    len: usize,
+    #[serde(serialize_with = "serialize_ark", deserialize_with = "deserialize_ark")]
+    key: ProvingKey<Bn254>,
+}
+
+opaque_debug::implement!(PoseidonProvingKey);
+
+impl PoseidonProvingKey {
+    /// Returns the length of the committed data which this key proves links for
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether this key is for empty data, which is never the case
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// A verifying key for link proofs of Poseidon commitments.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PoseidonVerifyingKey {
+    len: usize,
+    #[serde(serialize_with = "serialize_ark", deserialize_with = "deserialize_ark")]
+    key: VerifyingKey<Bn254>,
+}
+
+opaque_debug::implement!(PoseidonVerifyingKey);
+
+impl PoseidonVerifyingKey {
+    /// Returns the length of the committed data which this key verifies links for
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether this key is for empty data, which is never the case
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Creates the keys to prove and verify links of Poseidon commitments to `len` bytes of data.
+///
+/// See the [module documentation](self) for who should run the setup.
+pub fn setup_poseidon_keys<R: RngCore + CryptoRng>(
+    len: usize,
+    rng: &mut R,
+) -> Result<(PoseidonProvingKey, PoseidonVerifyingKey), PoseidonLinkError> {
+    if len == 0 {
+        return Err(PoseidonLinkError::UnsupportedLength(len));
+    }
+
+    let circuit = LinkCircuit {
+        data: vec![0u8; len],
+        salt: [0u8; PLAINTEXT_HASH_SALT_LEN],
+        sha256_digest: [0u8; 32],
+        digest: Fr::from(0u64),
+    };
+
+    let (proving_key, verifying_key) = Groth16::<Bn254>::circuit_specific_setup(circuit, rng)
+        .map_err(|e| PoseidonLinkError::ProofError(e.to_string()))?;
+
+    Ok((
+        PoseidonProvingKey {
+            len,
+            key: proving_key,
+        },
+        PoseidonVerifyingKey {
+            len,
+            key: verifying_key,
+        },
+    ))
+}
+
+/// Verifies that a Poseidon digest is of the same salted plaintext as a SHA-256 digest.
+///
+/// # Arguments
+///
+/// * `len` - The length of the committed data.
+/// * `sha256_digest` - The salted SHA-256 digest, which the Notary computed in MPC.
+/// * `digest` - The Poseidon digest.
+/// * `proof` - The compressed link proof.
+/// * `keys` - The verifying keys, one of which must be for `len`.
+pub(crate) fn verify_link(
+    len: usize,
+    sha256_digest: &[u8; 32],
+    digest: &[u8; 32],
+    proof: &[u8],
+    keys: &[PoseidonVerifyingKey],
+) -> Result<(), PoseidonLinkError> {
+    let key = keys
+        .iter()
+        .find(|key| key.len == len)
+        .ok_or(PoseidonLinkError::MissingKey(len))?;
+
+    let proof = Proof::<Bn254>::deserialize_compressed(proof)
+        .map_err(|_| PoseidonLinkError::InvalidProof)?;
+
+    let mut inputs: Vec<Fr> = sha256_digest
+        .as_slice()
+        .to_field_elements()
+        .expect("bytes are convertible to field elements");
+    inputs.push(Fr::from_le_bytes_mod_order(digest));
+
+    match Groth16::<Bn254>::verify(&key.key, &inputs, &proof) {
+        Ok(true) => Ok(()),
+        _ => Err(PoseidonLinkError::InvalidProof),
+    }
+}
+
+/// An error for link proofs of Poseidon commitments
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PoseidonLinkError {
+    /// The length of the data is not supported.
+    #[error("Poseidon commitments over {0} bytes are not supported")]
+    UnsupportedLength(usize),
+    /// There is no key for the length of the data.
+    #[error("missing a key for {0} bytes")]
+    MissingKey(usize),
+    /// Failed to create the proof.
+    #[error("failed to create the proof: {0}")]
+    ProofError(String),
+    /// The proof is invalid.
+    #[error("invalid link proof")]
+    InvalidProof,
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use super::*;
+
+    fn is_satisfied(data: &[u8], sha256_digest: [u8; 32], digest: [u8; 32]) -> bool {
+        let circuit = LinkCircuit {
+            data: data.to_vec(),
+            salt: [7u8; PLAINTEXT_HASH_SALT_LEN],
+            sha256_digest,
+            digest: Fr::from_le_bytes_mod_order(&digest),
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_link_circuit() {
+        // Spans more than one chunk
+        let data = [42u8; 40];
+        let salt = [7u8; PLAINTEXT_HASH_SALT_LEN];
+        let sha256_digest = plaintext_hash(&data, &salt);
+        let digest = poseidon_hash(&data, &salt);
+
+        assert!(is_satisfied(&data, sha256_digest, digest));
+        assert!(!is_satisfied(&data, [0u8; 32], digest));
+        assert!(!is_satisfied(
+            &data,
+            sha256_digest,
+            poseidon_hash(&[1u8; 40], &salt)
+        ));
+    }
+
+    #[test]
+    fn test_recover() {
+        let ranges = RangeSet::from([0..2, 4..5]);
+        let commitment = PoseidonHashCommitment::new(b"abc", &ranges, Direction::Sent);
+        let opening = commitment.open(b"abc".to_vec());
+
+        assert_eq!(
+            opening.recover(&ranges, Direction::Sent).hash(),
+            commitment.hash()
+        );
+        // The leaf is bound to the committed ranges
+        assert_ne!(
+            opening.recover(&ranges, Direction::Received).hash(),
+            commitment.hash()
+        );
+        assert_ne!(
+            opening
+                .recover(&RangeSet::from(0..3), Direction::Sent)
+                .hash(),
+            commitment.hash()
+        );
+    }
+
+    #[test]
+    fn test_link_proof() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let (proving_key, verifying_key) = setup_poseidon_keys(3, &mut rng).unwrap();
+
+        let commitment =
+            PoseidonHashCommitment::new(b"abc", &RangeSet::from(0..3), Direction::Sent);
+        let proof = commitment
+            .prove_link(b"abc".to_vec(), &[proving_key.clone()], &mut rng)
+            .unwrap();
+
+        verify_link(
+            3,
+            commitment.sha256_digest(),
+            commitment.digest(),
+            &proof,
+            &[verifying_key.clone()],
+        )
+        .unwrap();
+
+        // The proof does not link other digests
+        let other = PoseidonHashCommitment::new(b"abd", &RangeSet::from(0..3), Direction::Sent);
+        assert!(matches!(
+            verify_link(
+                3,
+                commitment.sha256_digest(),
+                other.digest(),
+                &proof,
+                &[verifying_key.clone()],
+            ),
+            Err(PoseidonLinkError::InvalidProof)
+        ));
+
+        // There is no key for other lengths
+        assert!(matches!(
+            commitment.prove_link(b"abcd".to_vec(), &[proving_key], &mut rng),
+            Err(PoseidonLinkError::MissingKey(4))
+        ));
+    }
+}
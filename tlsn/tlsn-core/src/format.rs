@@ -0,0 +1,467 @@
+//! Versioned binary format of notarized sessions and proofs.
+//!
+//! [`NotarizedSession`](crate::NotarizedSession), [`SessionProof`](crate::proof::SessionProof),
+//! [`SubstringsProof`](crate::proof::SubstringsProof), [`TlsProof`](crate::proof::TlsProof) and
+//! [`Presentation`](crate::proof::Presentation) are stored and exchanged with their `to_bytes`
+//! and `from_bytes` methods, which use the following encoding:
+//!
+//! ```text
+//! magic (4 bytes, "TLSN")
+//! || format version (u16, big-endian)
+//! || object kind (u8)
+//! || signature algorithm (u8, 0 if there is no signature)
+//! || Merkle tree hash algorithm (u8, 0 if there is no Merkle tree or proof)
+//! || body
+//! ```
+//!
+//! The body is the [bincode](https://github.com/bincode-org/bincode) encoding of the object with
+//! fixed-size integers, in the layout of the format version. Maps are encoded ordered by their
+//! keys, so the encoding of an object is canonical.
+//!
+//! Objects are encoded with [`FORMAT_VERSION`]. A later version must keep decoding the objects of
+//! every earlier version.
+//!
+//! # Compatibility
+//!
+//! Version 1 is the first version of the format. Releases before it stored objects as their bare
+//! bincode encoding, which does not start with the magic prefix and is rejected with
+//! [`FormatError::InvalidMagic`]. Their [`SessionHeader`](crate::SessionHeader) also lacked the
+//! plaintext hash commitment ids, the Notary key id and the Merkle tree hash algorithm, which the
+//! Notary now signs, so their signatures do not verify against the current header layout. Their
+//! Merkle roots were also computed with `rs_merkle`, whose tree is not domain separated and is no
+//! longer implemented. Decoding them could therefore only yield objects which fail verification,
+//! so they are not supported and must be notarized again.
+
+use std::{collections::HashMap, hash::Hash};
+
+use bimap::BiMap;
+use bincode::Options;
+use serde::{de::DeserializeOwned, Serialize, Serializer};
+
+use crate::{merkle::HashAlgorithm, Signature};
+
+/// The magic prefix of encoded objects.
+pub const MAGIC: [u8; 4] = *b"TLSN";
+
+/// The current format version.
+pub const FORMAT_VERSION: u16 = 1;
+
+/// The length of the [`FormatHeader`].
+pub const HEADER_LEN: usize = 9;
+
+/// The kind of an encoded object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum ObjectKind {
+    /// A [`NotarizedSession`](crate::NotarizedSession).
+    NotarizedSession = 1,
+    /// A [`SessionProof`](crate::proof::SessionProof).
+    SessionProof = 2,
+    /// A [`SubstringsProof`](crate::proof::SubstringsProof).
+    SubstringsProof = 3,
+    /// A [`TlsProof`](crate::proof::TlsProof).
+    TlsProof = 4,
+    /// A [`Presentation`](crate::proof::Presentation).
+    Presentation = 5,
+}
+
+impl TryFrom<u8> for ObjectKind {
+    type Error = FormatError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            1 => Self::NotarizedSession,
+            2 => Self::SessionProof,
+            3 => Self::SubstringsProof,
+            4 => Self::TlsProof,
+            5 => Self::Presentation,
+            _ => return Err(FormatError::UnknownKind(value)),
+        })
+    }
+}
+
+/// The algorithm of the Notary signature of an encoded object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum SignatureAlgorithm {
+    /// ECDSA with NIST P-256.
+    P256 = 1,
+    /// Ed25519.
+    Ed25519 = 2,
+    /// Recoverable ECDSA with secp256k1 over the Keccak-256 digest, as used by Ethereum.
+    Secp256k1Eth = 3,
+}
+
+impl SignatureAlgorithm {
+    /// Returns the algorithm of `signature`.
+    pub fn of(signature: &Signature) -> Self {
+        match signature {
+            Signature::P256(_) => Self::P256,
+            Signature::Ed25519(_) => Self::Ed25519,
+            Signature::Secp256k1Eth(_) => Self::Secp256k1Eth,
+        }
+    }
+}
+
+/// The header of an encoded object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatHeader {
+    version: u16,
+    kind: ObjectKind,
+    signature_algorithm: Option<SignatureAlgorithm>,
+    merkle_hash_algorithm: Option<HashAlgorithm>,
+}
+
+impl FormatHeader {
+    /// Decodes the header at the start of an encoded object.
+    ///
+    /// This does not decode the body, so it can be used to inspect an object of any kind.
+    pub fn decode(bytes: &[u8]) -> Result<Self, FormatError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(FormatError::Truncated);
+        }
+
+        if bytes[..4] != MAGIC {
+            return Err(FormatError::InvalidMagic);
+        }
+
+        let version = u16::from_be_bytes([bytes[4], bytes[5]]);
+        if version == 0 || version > FORMAT_VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+
+        let kind = ObjectKind::try_from(bytes[6])?;
+
+        let signature_algorithm = match bytes[7] {
+            0 => None,
+            1 => Some(SignatureAlgorithm::P256),
+            2 => Some(SignatureAlgorithm::Ed25519),
+            3 => Some(SignatureAlgorithm::Secp256k1Eth),
+            id => return Err(FormatError::UnknownAlgorithm(id)),
+        };
+
+        let merkle_hash_algorithm = match bytes[8] {
+            0 => None,
+            id => Some(HashAlgorithm::try_from(id).map_err(|_| FormatError::UnknownAlgorithm(id))?),
+        };
+
+        Ok(Self {
+            version,
+            kind,
+            signature_algorithm,
+            merkle_hash_algorithm,
+        })
+    }
+
+    /// Returns the format version
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns the kind of the object
+    pub fn kind(&self) -> ObjectKind {
+        self.kind
+    }
+
+    /// Returns the algorithm of the Notary signature, if the object contains one
+    pub fn signature_algorithm(&self) -> Option<SignatureAlgorithm> {
+        self.signature_algorithm
+    }
+
+    /// Returns the hash algorithm of the Merkle tree of transcript commitments, if the object
+    /// contains a Merkle tree or proof
+    pub fn merkle_hash_algorithm(&self) -> Option<HashAlgorithm> {
+        self.merkle_hash_algorithm
+    }
+
+    fn encode(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4..6].copy_from_slice(&self.version.to_be_bytes());
+        bytes[6] = self.kind as u8;
+        bytes[7] = self.signature_algorithm.map_or(0, |alg| alg as u8);
+        bytes[8] = self.merkle_hash_algorithm.map_or(0, |alg| alg.id());
+        bytes
+    }
+}
+
+/// An error for decoding an encoded object
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum FormatError {
+    /// The input is shorter than the header.
+    #[error("input is too short")]
+    Truncated,
+    /// The input does not start with the magic prefix.
+    #[error("input does not start with the magic prefix")]
+    InvalidMagic,
+    /// The format version is not supported.
+    #[error("unsupported format version: {0}")]
+    UnsupportedVersion(u16),
+    /// The object kind is unknown.
+    #[error("unknown object kind: {0}")]
+    UnknownKind(u8),
+    /// The object is of another kind than expected.
+    #[error("expected {expected:?}, found {found:?}")]
+    UnexpectedKind {
+        /// The expected kind
+        expected: ObjectKind,
+        /// The kind in the header
+        found: ObjectKind,
+    },
+    /// An algorithm identifier is unknown.
+    #[error("unknown algorithm identifier: {0}")]
+    UnknownAlgorithm(u8),
+    /// The algorithm identifiers of the header do not match the object.
+    #[error("algorithm identifiers of the header do not match the object")]
+    AlgorithmMismatch,
+    /// The body could not be decoded.
+    #[error("invalid body: {0}")]
+    InvalidBody(String),
+}
+
+/// An object with a versioned binary encoding.
+pub(crate) trait Versioned: Serialize + DeserializeOwned {
+    /// The kind of the object.
+    const KIND: ObjectKind;
+
+    /// Returns the algorithm of the Notary signature contained in the object.
+    fn signature_algorithm(&self) -> Option<SignatureAlgorithm>;
+
+    /// Returns the hash algorithm of the Merkle tree or proof contained in the object.
+    fn merkle_hash_algorithm(&self) -> Option<HashAlgorithm>;
+}
+
+/// Returns the bincode options of the body.
+///
+/// These must never change for an existing format version.
+fn body_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+        .reject_trailing_bytes()
+}
+
+/// Encodes `value` with the current format version.
+pub(crate) fn encode<T: Versioned>(value: &T) -> Vec<u8> {
+    let header = FormatHeader {
+        version: FORMAT_VERSION,
+        kind: T::KIND,
+        signature_algorithm: value.signature_algorithm(),
+        merkle_hash_algorithm: value.merkle_hash_algorithm(),
+    };
+
+    let mut bytes = header.encode().to_vec();
+    body_options()
+        .serialize_into(&mut bytes, value)
+        .expect("object should be serializable");
+    bytes
+}
+
+/// Decodes an object encoded with any supported format version.
+pub(crate) fn decode<T: Versioned>(bytes: &[u8]) -> Result<T, FormatError> {
+    let header = FormatHeader::decode(bytes)?;
+
+    if header.kind != T::KIND {
+        return Err(FormatError::UnexpectedKind {
+            expected: T::KIND,
+            found: header.kind,
+        });
+    }
+
+    let body = &bytes[HEADER_LEN..];
+    let value: T = match header.version {
+        1 => body_options()
+            .deserialize(body)
+            .map_err(|e| FormatError::InvalidBody(e.to_string()))?,
+        version => return Err(FormatError::UnsupportedVersion(version)),
+    };
+
+    if header.signature_algorithm != value.signature_algorithm()
+        || header.merkle_hash_algorithm != value.merkle_hash_algorithm()
+    {
+        return Err(FormatError::AlgorithmMismatch);
+    }
+
+    Ok(value)
+}
+
+/// Serializes a map ordered by its keys, so that its encoding is canonical.
+pub(crate) fn serialize_sorted_map<S, K, V>(
+    map: &HashMap<K, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    K: Ord + Serialize,
+    V: Serialize,
+{
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    serializer.collect_map(entries)
+}
+
+/// Serializes a bidirectional map ordered by its left values, so that its encoding is canonical.
+pub(crate) fn serialize_sorted_bimap<S, L, R>(
+    map: &BiMap<L, R>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    L: Ord + Hash + Serialize,
+    R: Eq + Hash + Serialize,
+{
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    serializer.collect_map(entries)
+}
+
+/// Serializes an arkworks value with its compressed canonical encoding.
+#[cfg(any(feature = "predicate", feature = "poseidon"))]
+pub(crate) fn serialize_ark<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: ark_serialize::CanonicalSerialize,
+{
+    let mut bytes = Vec::with_capacity(value.compressed_size());
+    value
+        .serialize_compressed(&mut bytes)
+        .map_err(serde::ser::Error::custom)?;
+    serializer.serialize_bytes(&bytes)
+}
+
+/// Deserializes an arkworks value from its compressed canonical encoding.
+#[cfg(any(feature = "predicate", feature = "poseidon"))]
+pub(crate) fn deserialize_ark<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: ark_serialize::CanonicalDeserialize,
+{
+    let bytes = <Vec<u8> as serde::Deserialize>::deserialize(deserializer)?;
+    T::deserialize_compressed(bytes.as_slice()).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Object {
+        data: Vec<u8>,
+    }
+
+    impl Versioned for Object {
+        const KIND: ObjectKind = ObjectKind::SubstringsProof;
+
+        fn signature_algorithm(&self) -> Option<SignatureAlgorithm> {
+            None
+        }
+
+        fn merkle_hash_algorithm(&self) -> Option<HashAlgorithm> {
+            Some(HashAlgorithm::Blake3)
+        }
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let object = Object {
+            data: vec![1, 2, 3],
+        };
+        let bytes = encode(&object);
+
+        assert_eq!(&bytes[..HEADER_LEN], b"TLSN\x00\x01\x03\x00\x02");
+        assert_eq!(&bytes[HEADER_LEN..], &[3, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3][..]);
+        assert_eq!(decode::<Object>(&bytes).unwrap(), object);
+
+        let header = FormatHeader::decode(&bytes).unwrap();
+        assert_eq!(header.version(), FORMAT_VERSION);
+        assert_eq!(header.kind(), ObjectKind::SubstringsProof);
+        assert_eq!(header.signature_algorithm(), None);
+        assert_eq!(header.merkle_hash_algorithm(), Some(HashAlgorithm::Blake3));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let bytes = encode(&Object { data: vec![1] });
+
+        assert!(matches!(
+            decode::<Object>(&bytes[..4]),
+            Err(FormatError::Truncated)
+        ));
+
+        let mut invalid = bytes.clone();
+        invalid[0] = b'X';
+        assert!(matches!(
+            decode::<Object>(&invalid),
+            Err(FormatError::InvalidMagic)
+        ));
+
+        let mut invalid = bytes.clone();
+        invalid[5] = 2;
+        assert!(matches!(
+            decode::<Object>(&invalid),
+            Err(FormatError::UnsupportedVersion(2))
+        ));
+
+        let mut invalid = bytes.clone();
+        invalid[5] = 0;
+        assert!(matches!(
+            decode::<Object>(&invalid),
+            Err(FormatError::UnsupportedVersion(0))
+        ));
+
+        let mut invalid = bytes.clone();
+        invalid[6] = ObjectKind::SessionProof as u8;
+        assert!(matches!(
+            decode::<Object>(&invalid),
+            Err(FormatError::UnexpectedKind { .. })
+        ));
+
+        let mut invalid = bytes.clone();
+        invalid[7] = SignatureAlgorithm::P256 as u8;
+        assert!(matches!(
+            decode::<Object>(&invalid),
+            Err(FormatError::AlgorithmMismatch)
+        ));
+
+        let mut invalid = bytes.clone();
+        invalid[8] = HashAlgorithm::Sha256.id();
+        assert!(matches!(
+            decode::<Object>(&invalid),
+            Err(FormatError::AlgorithmMismatch)
+        ));
+
+        let mut invalid = bytes.clone();
+        invalid[8] = 0xfe;
+        assert!(matches!(
+            decode::<Object>(&invalid),
+            Err(FormatError::UnknownAlgorithm(0xfe))
+        ));
+
+        let mut invalid = bytes.clone();
+        invalid.push(0);
+        assert!(matches!(
+            decode::<Object>(&invalid),
+            Err(FormatError::InvalidBody(_))
+        ));
+    }
+
+    #[test]
+    fn test_sorted_map() {
+        let map = (0u32..64).map(|i| (i, i)).collect::<HashMap<_, _>>();
+
+        let mut bytes = Vec::new();
+        serialize_sorted_map(
+            &map,
+            &mut bincode::Serializer::new(&mut bytes, body_options()),
+        )
+        .unwrap();
+
+        let entries: Vec<(u32, u32)> = body_options().deserialize(&bytes).unwrap();
+        assert_eq!(entries, (0u32..64).map(|i| (i, i)).collect::<Vec<_>>());
+    }
+}
@@ -9,6 +9,7 @@
 pub mod commitment;
 #[cfg(any(test, feature = "fixtures"))]
 pub mod fixtures;
+pub mod format;
 pub mod merkle;
 pub mod msg;
 pub mod proof;
@@ -17,7 +18,9 @@ mod signature;
 pub mod transcript;
 
 pub use session::{HandshakeSummary, NotarizedSession, SessionData, SessionHeader};
-pub use signature::{NotaryPublicKey, Signature};
+pub use signature::{
+    NotaryPublicKey, Secp256k1EthSignature, Secp256k1EthSigner, Signature, SignatureVerifyError,
+};
 pub use transcript::{Direction, RedactedTranscript, Transcript, TranscriptSlice};
 
 use mpz_garble_core::{encoding_state, EncodedValue};
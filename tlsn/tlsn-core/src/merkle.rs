@@ -9,15 +9,58 @@
 //!
 //! Later, during selective disclosure to a `Verifier`, the `Prover` can open any subset of the commitments in the `MerkleTree`
 //! by providing a `MerkleProof` for the corresponding `MerkleRoot` which was signed by the Notary.
+//!
+//! # Construction
+//!
+//! The leaves of the tree are 32 byte commitment hashes. With the hash function `H` of the
+//! [`HashAlgorithm`], the nodes of the tree are
+//!
+//! ```text
+//! leaf node = H(0x00 || leaf)
+//! inner node = H(0x01 || left || right)
+//! ```
+//!
+//! Each level of the tree is built by hashing pairs of adjacent nodes of the level below. If a level has an odd
+//! number of nodes, its last node is moved up to the next level unchanged. The root is the single node of the
+//! top level, so the root of a tree with one leaf is its leaf node.
+//!
+//! For [`HashAlgorithm::Poseidon`] over the BN254 scalar field, each 32 byte input is split into two field elements
+//! from its first and last 16 bytes, read as little-endian integers, the prefix is absorbed as a field element before
+//! them, and the output is the little-endian encoding of the first squeezed field element.
+//!
+//! # Proof encoding
+//!
+//! A [`MerkleProof`] for a set of leaves is encoded as
+//!
+//! ```text
+//! hash algorithm (u8) || total number of leaves (u32, big-endian) || number of hashes (u32, big-endian) || hashes
+//! ```
+//!
+//! where each hash is 32 bytes. The indices and hashes of the proven leaves are provided by the verifier. A proof
+//! is verified by computing the root from the leaf nodes, level by level. Going through the known nodes of a level
+//! in ascending order of their index `i`:
+//!
+//! - if `i` is odd, its left sibling is the next hash of the proof,
+//! - else if `i` is the last node of the level, it is moved up unchanged,
+//! - else if the node `i + 1` is known, it is its right sibling,
+//! - else its right sibling is the next hash of the proof,
+//!
+//! and the parent is the node `i / 2` of the next level. The proof is valid if all of its hashes are used and the
+//! computed root is the expected root.
 
 use mpz_core::hash::Hash;
-use rs_merkle::{
-    algorithms::Sha256, proof_serializers, MerkleProof as MerkleProof_rs_merkle,
-    MerkleTree as MerkleTree_rs_merkle,
-};
 use serde::{ser::Serializer, Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
 use utils::iter::DuplicateCheck;
 
+/// The prefix of the hash of a leaf node.
+const LEAF_PREFIX: u8 = 0x00;
+/// The prefix of the hash of an inner node.
+const NODE_PREFIX: u8 = 0x01;
+
+/// The length of the header of an encoded [`MerkleProof`].
+const PROOF_HEADER_LEN: usize = 9;
+
 /// A Merkle root.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MerkleRoot([u8; 32]);
@@ -43,19 +86,153 @@ pub enum MerkleError {
     MerkleProofVerificationFailed,
     #[error("No leaves were provided when constructing a Merkle tree")]
     MerkleNoLeavesProvided,
+    #[error("Unknown hash algorithm: {0}")]
+    UnknownHashAlgorithm(u8),
+    #[error("Invalid Merkle proof encoding")]
+    InvalidProofEncoding,
+    #[error("Merkle proof is too large to encode")]
+    ProofTooLarge,
+}
+
+/// The hash algorithm of a Merkle tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(into = "u8", try_from = "u8")]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    /// SHA-256.
+    #[default]
+    Sha256 = 1,
+    /// BLAKE3.
+    Blake3 = 2,
+    /// Poseidon over the BN254 scalar field, with a width of 3, 8 full rounds, 57 partial rounds and an S-box of
+    /// `x^5`, and the round constants and MDS matrix of the Grain LFSR.
+    #[cfg(feature = "poseidon")]
+    Poseidon = 3,
+}
+
+impl HashAlgorithm {
+    /// Returns the identifier of this algorithm.
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    fn hash_leaf(&self, leaf: &[u8; 32]) -> [u8; 32] {
+        self.hash(LEAF_PREFIX, &[leaf])
+    }
+
+    fn hash_node(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        self.hash(NODE_PREFIX, &[left, right])
+    }
+
+    fn hash(&self, prefix: u8, inputs: &[&[u8; 32]]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update([prefix]);
+                inputs.iter().for_each(|input| hasher.update(input));
+                hasher.finalize().into()
+            }
+            HashAlgorithm::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                hasher.update(&[prefix]);
+                inputs.iter().for_each(|input| {
+                    hasher.update(input.as_slice());
+                });
+                hasher.finalize().into()
+            }
+            #[cfg(feature = "poseidon")]
+            HashAlgorithm::Poseidon => poseidon::hash(prefix, inputs),
+        }
+    }
+}
+
+impl From<HashAlgorithm> for u8 {
+    fn from(algorithm: HashAlgorithm) -> Self {
+        algorithm.id()
+    }
+}
+
+impl TryFrom<u8> for HashAlgorithm {
+    type Error = MerkleError;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        Ok(match id {
+            1 => Self::Sha256,
+            2 => Self::Blake3,
+            #[cfg(feature = "poseidon")]
+            3 => Self::Poseidon,
+            _ => return Err(MerkleError::UnknownHashAlgorithm(id)),
+        })
+    }
+}
+
+#[cfg(feature = "poseidon")]
+pub(crate) mod poseidon {
+    use std::sync::OnceLock;
+
+    use ark_bn254::Fr;
+    use ark_crypto_primitives::sponge::{
+        poseidon::{find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge},
+        CryptographicSponge,
+    };
+    use ark_ff::{BigInteger, PrimeField};
+
+    const RATE: usize = 2;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 57;
+    const ALPHA: u64 = 5;
+
+    /// Returns the configuration of the Poseidon sponge.
+    pub(crate) fn config() -> &'static PoseidonConfig<Fr> {
+        static CONFIG: OnceLock<PoseidonConfig<Fr>> = OnceLock::new();
+        CONFIG.get_or_init(|| {
+            let (ark, mds) = find_poseidon_ark_and_mds::<Fr>(
+                Fr::MODULUS_BIT_SIZE as u64,
+                RATE,
+                FULL_ROUNDS as u64,
+                PARTIAL_ROUNDS as u64,
+                0,
+            );
+            PoseidonConfig::new(FULL_ROUNDS, PARTIAL_ROUNDS, ALPHA, mds, ark, RATE, 1)
+        })
+    }
+
+    pub(super) fn hash(prefix: u8, inputs: &[&[u8; 32]]) -> [u8; 32] {
+        let mut elements = vec![Fr::from(prefix as u64)];
+        for input in inputs {
+            elements.push(Fr::from_le_bytes_mod_order(&input[..16]));
+            elements.push(Fr::from_le_bytes_mod_order(&input[16..]));
+        }
+
+        hash_elements(&elements)
+    }
+
+    /// Hashes field elements with the Poseidon sponge, returning the little-endian encoding of
+    /// the digest.
+    pub(crate) fn hash_elements(elements: &[Fr]) -> [u8; 32] {
+        let mut sponge = PoseidonSponge::new(config());
+        sponge.absorb(&elements);
+        let digest = sponge.squeeze_field_elements::<Fr>(1)[0];
+
+        digest
+            .into_bigint()
+            .to_bytes_le()
+            .try_into()
+            .expect("field element should be 32 bytes")
+    }
 }
 
 /// A Merkle proof.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone)]
 pub struct MerkleProof {
-    #[serde(
-        serialize_with = "merkle_proof_serialize",
-        deserialize_with = "merkle_proof_deserialize"
-    )]
-    proof: MerkleProof_rs_merkle<Sha256>,
+    algorithm: HashAlgorithm,
     total_leaves: usize,
+    hashes: Vec<[u8; 32]>,
 }
 
+opaque_debug::implement!(MerkleProof);
+
 impl MerkleProof {
     /// Checks if indices, hashes and leaves count are valid for the provided root
     ///
@@ -80,76 +257,173 @@ impl MerkleProof {
             leaf_indices
         );
 
-        // zip indices and hashes
-        let mut tuples: Vec<(usize, [u8; 32])> = leaf_indices
+        // zip indices and leaf nodes
+        let mut nodes: Vec<(usize, [u8; 32])> = leaf_indices
             .iter()
             .cloned()
-            .zip(leaf_hashes.iter().cloned().map(|h| *h.as_bytes()))
+            .zip(
+                leaf_hashes
+                    .iter()
+                    .map(|h| self.algorithm.hash_leaf(h.as_bytes())),
+            )
             .collect();
 
-        // sort by index and unzip
-        tuples.sort_by(|(a, _), (b, _)| a.cmp(b));
-        let (indices, hashes): (Vec<usize>, Vec<[u8; 32]>) = tuples.into_iter().unzip();
+        // sort by index
+        nodes.sort_by(|(a, _), (b, _)| a.cmp(b));
 
-        if !self
-            .proof
-            .verify(root.to_inner(), &indices, &hashes, self.total_leaves)
-        {
+        if nodes.iter().any(|(idx, _)| *idx >= self.total_leaves) {
             return Err(MerkleError::MerkleProofVerificationFailed);
         }
-        Ok(())
+
+        let mut hashes = self.hashes.iter();
+        let mut width = self.total_leaves;
+        while width > 1 {
+            let mut parents = Vec::with_capacity(nodes.len());
+            let mut i = 0;
+            while i < nodes.len() {
+                let (idx, node) = &nodes[i];
+                let parent = if idx % 2 == 1 {
+                    let left = hashes
+                        .next()
+                        .ok_or(MerkleError::MerkleProofVerificationFailed)?;
+                    self.algorithm.hash_node(left, node)
+                } else if idx + 1 == width {
+                    *node
+                } else if let Some((_, right)) = nodes.get(i + 1).filter(|(j, _)| *j == idx + 1) {
+                    i += 1;
+                    self.algorithm.hash_node(node, right)
+                } else {
+                    let right = hashes
+                        .next()
+                        .ok_or(MerkleError::MerkleProofVerificationFailed)?;
+                    self.algorithm.hash_node(node, right)
+                };
+                parents.push((idx / 2, parent));
+                i += 1;
+            }
+
+            nodes = parents;
+            width = width.div_ceil(2);
+        }
+
+        match nodes.as_slice() {
+            [(0, computed_root)] if hashes.next().is_none() && computed_root == &root.0 => Ok(()),
+            _ => Err(MerkleError::MerkleProofVerificationFailed),
+        }
     }
-}
 
-impl Clone for MerkleProof {
-    fn clone(&self) -> Self {
-        let bytes = self.proof.to_bytes();
-        Self {
-            proof: MerkleProof_rs_merkle::<Sha256>::from_bytes(&bytes).unwrap(),
-            total_leaves: self.total_leaves,
+    /// Returns the hash algorithm of the tree
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the [encoding](self#proof-encoding) of this proof
+    ///
+    /// Returns an error if the number of leaves or hashes does not fit in a `u32`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MerkleError> {
+        let total_leaves =
+            u32::try_from(self.total_leaves).map_err(|_| MerkleError::ProofTooLarge)?;
+        let hash_count =
+            u32::try_from(self.hashes.len()).map_err(|_| MerkleError::ProofTooLarge)?;
+
+        let mut bytes = Vec::with_capacity(PROOF_HEADER_LEN + self.hashes.len() * 32);
+        bytes.push(self.algorithm.id());
+        bytes.extend_from_slice(&total_leaves.to_be_bytes());
+        bytes.extend_from_slice(&hash_count.to_be_bytes());
+        for hash in &self.hashes {
+            bytes.extend_from_slice(hash);
+        }
+        Ok(bytes)
+    }
+
+    /// Decodes a proof from its [encoding](self#proof-encoding)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        if bytes.len() < PROOF_HEADER_LEN {
+            return Err(MerkleError::InvalidProofEncoding);
         }
+
+        let algorithm = HashAlgorithm::try_from(bytes[0])?;
+        let total_leaves = u32::from_be_bytes(bytes[1..5].try_into().unwrap()) as usize;
+        let hash_count = u32::from_be_bytes(bytes[5..9].try_into().unwrap()) as usize;
+
+        let hashes = &bytes[PROOF_HEADER_LEN..];
+        if hashes.len() != hash_count * 32 {
+            return Err(MerkleError::InvalidProofEncoding);
+        }
+
+        Ok(Self {
+            algorithm,
+            total_leaves,
+            hashes: hashes.chunks(32).map(|c| c.try_into().unwrap()).collect(),
+        })
     }
 }
 
-fn merkle_proof_serialize<S>(
-    proof: &MerkleProof_rs_merkle<Sha256>,
-    serializer: S,
-) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    let bytes = proof.serialize::<proof_serializers::DirectHashesOrder>();
-    serializer.serialize_bytes(&bytes)
+impl Serialize for MerkleProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes().map_err(serde::ser::Error::custom)?)
+    }
 }
 
-fn merkle_proof_deserialize<'de, D>(
-    deserializer: D,
-) -> Result<MerkleProof_rs_merkle<Sha256>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let bytes = Vec::deserialize(deserializer)?;
-    MerkleProof_rs_merkle::<Sha256>::from_bytes(bytes.as_slice()).map_err(serde::de::Error::custom)
+impl<'de> Deserialize<'de> for MerkleProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes: Vec<u8> = Vec::deserialize(deserializer)?;
+        Self::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
 }
 
 /// A Merkle tree.
-#[derive(Serialize, Deserialize, Default, Clone)]
-pub struct MerkleTree(
-    #[serde(
-        serialize_with = "merkle_tree_serialize",
-        deserialize_with = "merkle_tree_deserialize"
-    )]
-    pub MerkleTree_rs_merkle<Sha256>,
-);
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "MerkleTreeData", try_from = "MerkleTreeData")]
+pub struct MerkleTree {
+    algorithm: HashAlgorithm,
+    leaves: Vec<Hash>,
+    /// The nodes of each level of the tree, from the leaf nodes to the root.
+    levels: Vec<Vec<[u8; 32]>>,
+}
 
 impl MerkleTree {
-    /// Create a new Merkle tree from the given `leaves`
+    /// Create a new Merkle tree from the given `leaves` using SHA-256
     pub fn from_leaves(leaves: &[Hash]) -> Result<Self, MerkleError> {
+        Self::from_leaves_with_hash(leaves, HashAlgorithm::default())
+    }
+
+    /// Create a new Merkle tree from the given `leaves` using the given hash `algorithm`
+    pub fn from_leaves_with_hash(
+        leaves: &[Hash],
+        algorithm: HashAlgorithm,
+    ) -> Result<Self, MerkleError> {
         if leaves.is_empty() {
             return Err(MerkleError::MerkleNoLeavesProvided);
         }
-        let leaves: Vec<[u8; 32]> = leaves.iter().map(|h| *h.as_bytes()).collect();
-        Ok(Self(MerkleTree_rs_merkle::<Sha256>::from_leaves(&leaves)))
+
+        let mut levels = vec![leaves
+            .iter()
+            .map(|leaf| algorithm.hash_leaf(leaf.as_bytes()))
+            .collect::<Vec<_>>()];
+        while levels[levels.len() - 1].len() > 1 {
+            let parents = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => algorithm.hash_node(left, right),
+                    [node] => *node,
+                    _ => unreachable!("chunks have 1 or 2 nodes"),
+                })
+                .collect();
+            levels.push(parents);
+        }
+
+        Ok(Self {
+            algorithm,
+            leaves: leaves.to_vec(),
+            levels,
+        })
     }
 
     /// Creates an inclusion proof for the given `indices`
@@ -158,63 +432,84 @@ impl MerkleTree {
     ///
     /// - if `indices` is not sorted.
     /// - if `indices` contains duplicates
+    /// - if an index is out of bounds
     pub fn proof(&self, indices: &[usize]) -> MerkleProof {
         assert!(
             indices.windows(2).all(|w| w[0] < w[1]),
             "indices must be sorted"
         );
+        assert!(
+            indices.iter().all(|idx| *idx < self.leaves.len()),
+            "indices must be in bounds"
+        );
+
+        let mut hashes = Vec::new();
+        let mut known = indices.to_vec();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let mut parents = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let idx = known[i];
+                if idx % 2 == 1 {
+                    hashes.push(level[idx - 1]);
+                } else if idx + 1 == level.len() {
+                    // moved up unchanged
+                } else if known.get(i + 1) == Some(&(idx + 1)) {
+                    i += 1;
+                } else {
+                    hashes.push(level[idx + 1]);
+                }
+                parents.push(idx / 2);
+                i += 1;
+            }
+            known = parents;
+        }
 
-        let proof = self.0.proof(indices);
         MerkleProof {
-            proof,
-            total_leaves: self.0.leaves_len(),
+            algorithm: self.algorithm,
+            total_leaves: self.leaves.len(),
+            hashes,
         }
     }
 
     /// Returns the Merkle root for this MerkleTree
     pub fn root(&self) -> MerkleRoot {
-        self.0
-            .root()
-            .expect("Merkle root should be available")
-            .into()
+        MerkleRoot(self.levels[self.levels.len() - 1][0])
     }
+
+    /// Returns the hash algorithm of this MerkleTree
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the leaves of this MerkleTree
+    pub fn leaves(&self) -> &[Hash] {
+        &self.leaves
+    }
+}
+
+/// The serialized form of a [`MerkleTree`], which omits the inner nodes
+#[derive(Serialize, Deserialize)]
+struct MerkleTreeData {
+    algorithm: HashAlgorithm,
+    leaves: Vec<Hash>,
 }
 
-/// Serialize the rs_merkle's `MerkleTree` type
-fn merkle_tree_serialize<S>(
-    tree: &MerkleTree_rs_merkle<Sha256>,
-    serializer: S,
-) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    // all leaves are sha256 hashes
-    let hash_size = 32;
-    let mut bytes: Vec<u8> = Vec::with_capacity(tree.leaves_len() * hash_size);
-    if let Some(leaves) = tree.leaves() {
-        for leaf in leaves {
-            bytes.append(&mut leaf.to_vec());
+impl From<MerkleTree> for MerkleTreeData {
+    fn from(tree: MerkleTree) -> Self {
+        Self {
+            algorithm: tree.algorithm,
+            leaves: tree.leaves,
         }
     }
-
-    serializer.serialize_bytes(&bytes)
 }
 
-fn merkle_tree_deserialize<'de, D>(
-    deserializer: D,
-) -> Result<MerkleTree_rs_merkle<Sha256>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let bytes: Vec<u8> = Vec::deserialize(deserializer)?;
-    if bytes.len() % 32 != 0 {
-        return Err(serde::de::Error::custom("leaves must be 32 bytes"));
-    }
-    let leaves: Vec<[u8; 32]> = bytes.chunks(32).map(|c| c.try_into().unwrap()).collect();
-
-    Ok(MerkleTree_rs_merkle::<Sha256>::from_leaves(
-        leaves.as_slice(),
-    ))
+impl TryFrom<MerkleTreeData> for MerkleTree {
+    type Error = MerkleError;
+
+    fn try_from(data: MerkleTreeData) -> Result<Self, Self::Error> {
+        Self::from_leaves_with_hash(&data.leaves, data.algorithm)
+    }
 }
 
 #[cfg(test)]
@@ -382,4 +677,112 @@ mod test {
             .verify(&tree2.root(), &[2, 3, 4], &[leaf2, leaf3, leaf4])
             .is_ok());
     }
+
+    fn leaves(count: u8) -> Vec<Hash> {
+        (0..count).map(|i| Hash::from([i; 32])).collect()
+    }
+
+    fn algorithms() -> Vec<HashAlgorithm> {
+        vec![
+            HashAlgorithm::Sha256,
+            HashAlgorithm::Blake3,
+            #[cfg(feature = "poseidon")]
+            HashAlgorithm::Poseidon,
+        ]
+    }
+
+    // Expect proofs of any subset of up to 3 leaves to verify, for any number of leaves
+    #[test]
+    fn test_verify_all_algorithms() {
+        for algorithm in algorithms() {
+            for count in 1..=9u8 {
+                let leaves = leaves(count);
+                let tree = MerkleTree::from_leaves_with_hash(&leaves, algorithm).unwrap();
+                let count = count as usize;
+
+                let mut index_sets = vec![(0..count).collect::<Vec<_>>()];
+                for a in 0..count {
+                    index_sets.push(vec![a]);
+                    for b in a + 1..count {
+                        index_sets.push(vec![a, b]);
+                        for c in b + 1..count {
+                            index_sets.push(vec![a, b, c]);
+                        }
+                    }
+                }
+
+                for indices in index_sets {
+                    let hashes = indices.iter().map(|i| leaves[*i]).collect::<Vec<_>>();
+                    let proof =
+                        MerkleProof::from_bytes(&tree.proof(&indices).to_bytes().unwrap()).unwrap();
+
+                    assert_eq!(proof.algorithm(), algorithm);
+                    assert!(
+                        proof.verify(&tree.root(), &indices, &hashes).is_ok(),
+                        "{algorithm:?} {count} {indices:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let leaves = leaves(5);
+
+        let tree = MerkleTree::from_leaves(&leaves).unwrap();
+        assert_eq!(tree.algorithm(), HashAlgorithm::Sha256);
+
+        let single = MerkleTree::from_leaves(&leaves[..1]).unwrap();
+        let leaf_node: [u8; 32] = Sha256::new()
+            .chain_update([LEAF_PREFIX])
+            .chain_update(leaves[0].as_bytes())
+            .finalize()
+            .into();
+        assert_eq!(single.root(), MerkleRoot::from(leaf_node));
+
+        // an inner node can not be proven as a leaf
+        for algorithm in algorithms() {
+            let pair = MerkleTree::from_leaves_with_hash(&leaves[..2], algorithm).unwrap();
+            let inner = Hash::from(pair.root().to_inner());
+            let forged = MerkleTree::from_leaves_with_hash(&[inner, leaves[2]], algorithm).unwrap();
+            let tree = MerkleTree::from_leaves_with_hash(&leaves[..3], algorithm).unwrap();
+
+            assert_ne!(forged.root(), tree.root(), "{algorithm:?}");
+        }
+    }
+
+    #[test]
+    fn test_proof_encoding() {
+        let leaves = leaves(5);
+        let tree = MerkleTree::from_leaves_with_hash(&leaves, HashAlgorithm::Blake3).unwrap();
+        let bytes = tree.proof(&[2, 3]).to_bytes().unwrap();
+
+        // the left sibling of the parent of 2 and 3, and the right sibling of its parent
+        assert_eq!(&bytes[..PROOF_HEADER_LEN], &[2, 0, 0, 0, 5, 0, 0, 0, 2]);
+        assert_eq!(bytes.len(), PROOF_HEADER_LEN + 2 * 32);
+
+        assert_eq!(
+            MerkleProof::from_bytes(&bytes[..bytes.len() - 1]).err(),
+            Some(MerkleError::InvalidProofEncoding)
+        );
+        assert_eq!(
+            MerkleProof::from_bytes(&bytes[..4]).err(),
+            Some(MerkleError::InvalidProofEncoding)
+        );
+
+        let mut invalid = bytes.clone();
+        invalid[0] = 0;
+        assert_eq!(
+            MerkleProof::from_bytes(&invalid).err(),
+            Some(MerkleError::UnknownHashAlgorithm(0))
+        );
+
+        let too_large = MerkleProof {
+            algorithm: HashAlgorithm::Sha256,
+            total_leaves: u32::MAX as usize + 1,
+            hashes: Vec::new(),
+        };
+        assert_eq!(too_large.to_bytes().err(), Some(MerkleError::ProofTooLarge));
+    }
 }
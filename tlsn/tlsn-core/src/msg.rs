@@ -1,15 +1,28 @@
 //! Protocol message types.
 
+use std::collections::HashSet;
+
+use mpz_core::hash::Hash;
 use serde::{Deserialize, Serialize};
 use utils::range::RangeSet;
 
-use crate::{merkle::MerkleRoot, proof::SessionInfo, signature::Signature, SessionHeader};
+#[cfg(feature = "poseidon")]
+use crate::commitment::poseidon::{self, PoseidonLinkError, PoseidonVerifyingKey};
+use crate::{
+    commitment::{
+        plaintext::leaf_hash, CommitmentId, CommitmentInfo, CommitmentKind, TranscriptCommitments,
+    },
+    merkle::{HashAlgorithm, MerkleProof, MerkleRoot},
+    proof::SessionInfo,
+    signature::Signature,
+    Direction, SessionHeader,
+};
 
 /// Top-level enum for all messages
 #[derive(Debug, Serialize, Deserialize)]
 pub enum TlsnMessage {
     /// A Merkle root for the tree of commitments to the transcript.
-    TranscriptCommitmentRoot(MerkleRoot),
+    TranscriptCommitmentRoot(TranscriptCommitmentRoot),
     /// A session header signed by a notary.
     SignedSessionHeader(SignedSessionHeader),
     /// A session header.
@@ -18,6 +31,17 @@ pub enum TlsnMessage {
     SessionInfo(SessionInfo),
     /// Information about the values the prover wants to prove
     ProvingInfo(ProvingInfo),
+    /// The plaintext hash commitments which the prover proves to the notary
+    PlaintextHashes(PlaintextHashes),
+}
+
+/// The Merkle root of the tree of commitments to the transcript.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TranscriptCommitmentRoot {
+    /// The Merkle root
+    pub root: MerkleRoot,
+    /// The hash algorithm of the Merkle tree, which the notary records in the session header
+    pub algorithm: HashAlgorithm,
 }
 
 /// A signed session header.
@@ -39,3 +63,346 @@ pub struct ProvingInfo {
     /// Purported cleartext values
     pub cleartext: Vec<u8>,
 }
+
+/// An error for [`PlaintextHashes`]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PlaintextHashesError {
+    /// The commitments are not sorted by id or contain duplicates.
+    #[error("commitments are not sorted by id or contain duplicates")]
+    UnsortedCommitments,
+    /// A commitment is not a plaintext hash commitment.
+    #[error("commitment {0:?} is not a plaintext hash commitment")]
+    InvalidCommitmentKind(CommitmentId),
+    /// The ranges of a commitment are empty or out of bounds.
+    #[error("ranges of commitment {0:?} are empty or out of bounds")]
+    InvalidRanges(CommitmentId),
+    /// A commitment hashes the same ranges of the same transcript as a previous commitment.
+    #[error("commitment {0:?} duplicates a previous commitment")]
+    DuplicateCommitment(CommitmentId),
+    /// The commitments hash more data than the maximum allowed.
+    #[error("commitments hash more data than the maximum allowed: {0} > {1}")]
+    MaxDataExceeded(usize, usize),
+    /// The Poseidon links do not match the Poseidon commitments.
+    #[error("the Poseidon links do not match the Poseidon commitments")]
+    PoseidonLinkMismatch,
+    /// The link proof of a Poseidon commitment is invalid.
+    #[cfg(feature = "poseidon")]
+    #[error("invalid Poseidon link of commitment {0:?}: {1}")]
+    InvalidPoseidonLink(CommitmentId, PoseidonLinkError),
+    /// The number of digests does not match the number of commitments.
+    #[error("expected {0} digests, got {1}")]
+    DigestCountMismatch(usize, usize),
+    /// The inclusion proof is missing or invalid.
+    #[error("invalid inclusion proof")]
+    InvalidInclusionProof,
+}
+
+/// The Poseidon digest of a Poseidon commitment, with a proof that it is of the same salted
+/// plaintext as the SHA-256 digest which the notary computes in MPC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoseidonLink {
+    /// The id of the commitment
+    pub id: CommitmentId,
+    /// The Poseidon digest of the commitment
+    pub digest: [u8; 32],
+    /// The compressed Groth16 proof
+    pub proof: Vec<u8>,
+}
+
+/// The plaintext hash commitments which the prover proves to the notary
+///
+/// The notary computes the SHA-256 digests of the commitments in MPC, then checks that the
+/// corresponding leaves are included in the Merkle tree of commitments before signing the session
+/// header. The leaves of Poseidon commitments are computed from the digests of their links.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlaintextHashes {
+    /// The ids and info of the commitments, sorted by id
+    pub commitments: Vec<(CommitmentId, CommitmentInfo)>,
+    /// The links of the Poseidon commitments, sorted by id
+    pub poseidon_links: Vec<PoseidonLink>,
+    /// A proof that the commitments are included in the Merkle tree, if there are any
+    pub inclusion_proof: Option<MerkleProof>,
+}
+
+impl PlaintextHashes {
+    /// Creates the message for the plaintext hash commitments of `commitments`.
+    ///
+    /// The links of Poseidon commitments must be added to
+    /// [`poseidon_links`](Self::poseidon_links), which are built with
+    /// `SessionData::build_poseidon_links`.
+    pub fn new(commitments: &TranscriptCommitments) -> Self {
+        let hashes = commitments.plaintext_hashes();
+
+        let indices = hashes
+            .iter()
+            .map(|(id, ..)| id.to_inner() as usize)
+            .collect::<Vec<_>>();
+        let inclusion_proof =
+            (!indices.is_empty()).then(|| commitments.merkle_tree().proof(&indices));
+
+        Self {
+            commitments: hashes
+                .into_iter()
+                .map(|(id, info, ..)| (id, info.clone()))
+                .collect(),
+            poseidon_links: Vec::new(),
+            inclusion_proof,
+        }
+    }
+
+    /// Returns the ids of the commitments.
+    pub fn ids(&self) -> Vec<CommitmentId> {
+        self.commitments.iter().map(|(id, _)| *id).collect()
+    }
+
+    /// Checks that the commitments are well-formed plaintext hash commitments within the bounds
+    /// of the transcripts, and that each Poseidon commitment has a link.
+    ///
+    /// This must be checked before computing the digests of the commitments.
+    ///
+    /// # Arguments
+    ///
+    /// * `sent_len` - The length of the sent transcript.
+    /// * `recv_len` - The length of the received transcript.
+    /// * `max_len` - The maximum total number of bytes the commitments may hash.
+    pub fn validate(
+        &self,
+        sent_len: usize,
+        recv_len: usize,
+        max_len: usize,
+    ) -> Result<(), PlaintextHashesError> {
+        if !self.commitments.windows(2).all(|w| w[0].0 < w[1].0) {
+            return Err(PlaintextHashesError::UnsortedCommitments);
+        }
+
+        let mut seen = HashSet::with_capacity(self.commitments.len());
+        let mut total_len = 0usize;
+        for (id, info) in &self.commitments {
+            if !info.kind().is_plaintext_hash() {
+                return Err(PlaintextHashesError::InvalidCommitmentKind(*id));
+            }
+
+            let len = match info.direction() {
+                Direction::Sent => sent_len,
+                Direction::Received => recv_len,
+            };
+            match info.ranges().max() {
+                Some(max) if max <= len => {}
+                _ => return Err(PlaintextHashesError::InvalidRanges(*id)),
+            }
+
+            if !seen.insert((info.direction(), info.ranges())) {
+                return Err(PlaintextHashesError::DuplicateCommitment(*id));
+            }
+
+            // The ranges are within the bounds of the transcripts, so this does not overflow
+            total_len += info.ranges().len();
+            if total_len > max_len {
+                return Err(PlaintextHashesError::MaxDataExceeded(total_len, max_len));
+            }
+        }
+
+        // Each Poseidon commitment has exactly one link
+        let poseidon_ids = self
+            .commitments
+            .iter()
+            .filter(|(_, info)| info.kind() != CommitmentKind::Sha256)
+            .map(|(id, _)| *id);
+        if !poseidon_ids.eq(self.poseidon_links.iter().map(|link| link.id)) {
+            return Err(PlaintextHashesError::PoseidonLinkMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Verifies that the commitments with the provided digests are included in the Merkle tree.
+    ///
+    /// Poseidon commitments are rejected, as their links are verified with
+    /// [`verify_with_poseidon_keys`](Self::verify_with_poseidon_keys).
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The Merkle root of the commitments.
+    /// * `digests` - The salted SHA-256 digests of the commitments, which were computed in MPC.
+    pub fn verify(
+        &self,
+        root: &MerkleRoot,
+        digests: &[[u8; 32]],
+    ) -> Result<(), PlaintextHashesError> {
+        self.check_digest_count(digests)?;
+
+        let leaves = self
+            .commitments
+            .iter()
+            .zip(digests)
+            .map(|((id, info), digest)| match info.kind() {
+                CommitmentKind::Sha256 => Ok(leaf_hash(digest, info.ranges(), *info.direction())),
+                _ => Err(PlaintextHashesError::InvalidCommitmentKind(*id)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.verify_inclusion(root, &leaves)
+    }
+
+    /// Verifies the links of the Poseidon commitments, and that the commitments with the provided
+    /// digests are included in the Merkle tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The Merkle root of the commitments.
+    /// * `digests` - The salted SHA-256 digests of the commitments, which were computed in MPC.
+    /// * `keys` - The verifying keys of the links, for the lengths of the Poseidon commitments.
+    #[cfg(feature = "poseidon")]
+    pub fn verify_with_poseidon_keys(
+        &self,
+        root: &MerkleRoot,
+        digests: &[[u8; 32]],
+        keys: &[PoseidonVerifyingKey],
+    ) -> Result<(), PlaintextHashesError> {
+        self.check_digest_count(digests)?;
+
+        let mut links = self.poseidon_links.iter();
+        let leaves = self
+            .commitments
+            .iter()
+            .zip(digests)
+            .map(|((id, info), digest)| match info.kind() {
+                CommitmentKind::Sha256 => Ok(leaf_hash(digest, info.ranges(), *info.direction())),
+                CommitmentKind::Poseidon => {
+                    let link = links
+                        .next()
+                        .filter(|link| link.id == *id)
+                        .ok_or(PlaintextHashesError::PoseidonLinkMismatch)?;
+
+                    poseidon::verify_link(
+                        info.ranges().len(),
+                        digest,
+                        &link.digest,
+                        &link.proof,
+                        keys,
+                    )
+                    .map_err(|e| PlaintextHashesError::InvalidPoseidonLink(*id, e))?;
+
+                    Ok(poseidon::leaf_hash(
+                        &link.digest,
+                        info.ranges(),
+                        *info.direction(),
+                    ))
+                }
+                _ => Err(PlaintextHashesError::InvalidCommitmentKind(*id)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.verify_inclusion(root, &leaves)
+    }
+
+    /// Checks that there is a digest for each commitment.
+    fn check_digest_count(&self, digests: &[[u8; 32]]) -> Result<(), PlaintextHashesError> {
+        if digests.len() != self.commitments.len() {
+            return Err(PlaintextHashesError::DigestCountMismatch(
+                self.commitments.len(),
+                digests.len(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Verifies that the leaves of the commitments are included in the Merkle tree.
+    fn verify_inclusion(
+        &self,
+        root: &MerkleRoot,
+        leaves: &[Hash],
+    ) -> Result<(), PlaintextHashesError> {
+        if self.commitments.is_empty() {
+            return Ok(());
+        }
+
+        let inclusion_proof = self
+            .inclusion_proof
+            .as_ref()
+            .ok_or(PlaintextHashesError::InvalidInclusionProof)?;
+
+        let indices = self
+            .commitments
+            .iter()
+            .map(|(id, _)| id.to_inner() as usize)
+            .collect::<Vec<_>>();
+
+        inclusion_proof
+            .verify(root, &indices, leaves)
+            .map_err(|_| PlaintextHashesError::InvalidInclusionProof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plaintext_hashes(commitments: &[(Direction, std::ops::Range<usize>)]) -> PlaintextHashes {
+        PlaintextHashes {
+            commitments: commitments
+                .iter()
+                .enumerate()
+                .map(|(id, (direction, range))| {
+                    (
+                        CommitmentId::new(id as u32),
+                        CommitmentInfo::new(
+                            CommitmentKind::Sha256,
+                            range.clone().into(),
+                            *direction,
+                        ),
+                    )
+                })
+                .collect(),
+            poseidon_links: Vec::new(),
+            inclusion_proof: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_duplicate_commitments() {
+        let hashes = plaintext_hashes(&[
+            (Direction::Sent, 0..4),
+            (Direction::Received, 0..4),
+            (Direction::Sent, 0..4),
+        ]);
+
+        assert!(matches!(
+            hashes.validate(8, 8, 64),
+            Err(PlaintextHashesError::DuplicateCommitment(id)) if id == CommitmentId::new(2)
+        ));
+
+        plaintext_hashes(&[(Direction::Sent, 0..4), (Direction::Received, 0..4)])
+            .validate(8, 8, 64)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_validate_max_len() {
+        let hashes = plaintext_hashes(&[(Direction::Sent, 0..8), (Direction::Received, 0..8)]);
+
+        hashes.validate(8, 8, 16).unwrap();
+        assert!(matches!(
+            hashes.validate(8, 8, 15),
+            Err(PlaintextHashesError::MaxDataExceeded(16, 15))
+        ));
+    }
+
+    #[test]
+    fn test_validate_poseidon_links() {
+        let mut hashes = plaintext_hashes(&[(Direction::Sent, 0..4)]);
+        hashes.poseidon_links.push(PoseidonLink {
+            id: CommitmentId::new(0),
+            digest: [0u8; 32],
+            proof: Vec::new(),
+        });
+
+        // A SHA-256 commitment has no link
+        assert!(matches!(
+            hashes.validate(8, 8, 64),
+            Err(PlaintextHashesError::PoseidonLinkMismatch)
+        ));
+    }
+}
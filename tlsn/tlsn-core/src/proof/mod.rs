@@ -1,8 +1,17 @@
 //! Different types of proofs used in the TLSNotary protocol.
 
+#[cfg(feature = "predicate")]
+pub mod predicate;
+mod presentation;
 mod session;
 mod substrings;
 
+#[cfg(feature = "predicate")]
+pub use predicate::{
+    setup_predicate_keys, ComparisonOp, Predicate, PredicateKind, PredicateProof,
+    PredicateProofBuilderError, PredicateProofError, PredicateProvingKey, PredicateVerifyingKey,
+};
+pub use presentation::{Presentation, PresentationError, VerifiedPresentation};
 pub use session::{default_cert_verifier, SessionInfo, SessionProof, SessionProofError};
 pub use substrings::{
     SubstringsProof, SubstringsProofBuilder, SubstringsProofBuilderError, SubstringsProofError,
@@ -11,6 +20,11 @@ pub use substrings::{
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
+use crate::{
+    format::{self, FormatError, ObjectKind, SignatureAlgorithm, Versioned},
+    merkle::HashAlgorithm,
+};
+
 /// Proof that a transcript of communications took place between a Prover and Server.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TlsProof {
@@ -19,3 +33,27 @@ pub struct TlsProof {
     /// Proof regarding the contents of the transcript.
     pub substrings: SubstringsProof,
 }
+
+impl TlsProof {
+    /// Returns the encoding of this proof in the [versioned binary format](crate::format).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        format::encode(self)
+    }
+
+    /// Decodes a proof from the [versioned binary format](crate::format).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FormatError> {
+        format::decode(bytes)
+    }
+}
+
+impl Versioned for TlsProof {
+    const KIND: ObjectKind = ObjectKind::TlsProof;
+
+    fn signature_algorithm(&self) -> Option<SignatureAlgorithm> {
+        self.session.signature.as_ref().map(SignatureAlgorithm::of)
+    }
+
+    fn merkle_hash_algorithm(&self) -> Option<HashAlgorithm> {
+        self.substrings.merkle_hash_algorithm()
+    }
+}
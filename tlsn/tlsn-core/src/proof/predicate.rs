@@ -0,0 +1,656 @@
+//! Zero-knowledge proofs of predicates over committed transcript data.
+//!
+//! A [`PredicateProof`] proves a statement about the plaintext of a
+//! [plaintext hash commitment](crate::commitment::plaintext::PlaintextHashCommitment), e.g. that a
+//! balance is greater than 1000, without revealing the plaintext. The proof is a Groth16 proof over
+//! BN254, so it can also be checked by smart contracts.
+//!
+//! # Setup
+//!
+//! Groth16 needs a setup for each circuit, which here is determined by the length of the committed
+//! data and the [`PredicateKind`]. The keys are created with [`setup_predicate_keys`], and must be
+//! created by the verifier, or in a ceremony it trusts, as anyone knowing the randomness of the
+//! setup can forge proofs. The same keys can be used for any session and any constant of the
+//! predicate.
+
+use std::cmp::Ordering;
+
+use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
+use ark_ff::ToConstraintField;
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    commitment::{
+        plaintext::{leaf_hash, PLAINTEXT_HASH_SALT_LEN},
+        Commitment, CommitmentId, CommitmentInfo, CommitmentKind, TranscriptCommitments,
+    },
+    format::{deserialize_ark, serialize_ark},
+    merkle::{HashAlgorithm, MerkleProof},
+    Direction, SessionHeader, Transcript,
+};
+
+/// The maximum number of digits of a number in a numeric predicate.
+///
+/// Any number with this many digits fits into a `u64`.
+pub const MAX_PREDICATE_DIGITS: usize = 19;
+
+/// A comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ComparisonOp {
+    /// Less than
+    Lt,
+    /// Less than or equal
+    Le,
+    /// Greater than
+    Gt,
+    /// Greater than or equal
+    Ge,
+    /// Equal
+    Eq,
+    /// Not equal
+    Ne,
+}
+
+/// A predicate over the plaintext of a commitment.
+///
+/// Numeric predicates interpret the plaintext as an unsigned ASCII decimal number of at most
+/// [`MAX_PREDICATE_DIGITS`] digits, e.g. the value of a JSON field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Predicate {
+    /// The number compares to `value` with `op`, e.g. `number >= 18`.
+    Compare {
+        /// The comparison operator
+        op: ComparisonOp,
+        /// The constant to compare with
+        value: u64,
+    },
+    /// The number is in the inclusive range `min..=max`.
+    Range {
+        /// The lower bound
+        min: u64,
+        /// The upper bound
+        max: u64,
+    },
+    /// The SHA-256 hash of the plaintext equals `hash`.
+    EqualsHash {
+        /// The expected hash
+        hash: [u8; 32],
+    },
+}
+
+/// The kind of a [`Predicate`], which determines its circuit together with the length of the
+/// data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PredicateKind {
+    /// See [`Predicate::Compare`].
+    Compare(ComparisonOp),
+    /// See [`Predicate::Range`].
+    Range,
+    /// See [`Predicate::EqualsHash`].
+    EqualsHash,
+}
+
+impl Predicate {
+    /// Returns the kind of this predicate
+    pub fn kind(&self) -> PredicateKind {
+        match self {
+            Predicate::Compare { op, .. } => PredicateKind::Compare(*op),
+            Predicate::Range { .. } => PredicateKind::Range,
+            Predicate::EqualsHash { .. } => PredicateKind::EqualsHash,
+        }
+    }
+
+    /// Returns whether the predicate holds for `data`.
+    pub fn evaluate(&self, data: &[u8]) -> bool {
+        match self {
+            Predicate::Compare { op, value } => {
+                let Some(number) = parse_decimal(data) else {
+                    return false;
+                };
+                match op {
+                    ComparisonOp::Lt => number < *value,
+                    ComparisonOp::Le => number <= *value,
+                    ComparisonOp::Gt => number > *value,
+                    ComparisonOp::Ge => number >= *value,
+                    ComparisonOp::Eq => number == *value,
+                    ComparisonOp::Ne => number != *value,
+                }
+            }
+            Predicate::Range { min, max } => {
+                parse_decimal(data).is_some_and(|number| (*min..=*max).contains(&number))
+            }
+            Predicate::EqualsHash { hash } => Sha256::digest(data).as_slice() == hash,
+        }
+    }
+
+    /// Returns the public inputs of the circuit for the constants of this predicate.
+    fn public_inputs(&self) -> Vec<Fr> {
+        match self {
+            Predicate::Compare { value, .. } => vec![Fr::from(*value)],
+            Predicate::Range { min, max } => vec![Fr::from(*min), Fr::from(*max)],
+            Predicate::EqualsHash { hash } => hash
+                .as_slice()
+                .to_field_elements()
+                .expect("bytes are convertible to field elements"),
+        }
+    }
+
+    /// Returns a predicate of the same kind, used to synthesize the circuit during setup.
+    fn placeholder(kind: PredicateKind) -> Self {
+        match kind {
+            PredicateKind::Compare(op) => Predicate::Compare { op, value: 0 },
+            PredicateKind::Range => Predicate::Range { min: 0, max: 0 },
+            PredicateKind::EqualsHash => Predicate::EqualsHash { hash: [0u8; 32] },
+        }
+    }
+}
+
+/// Parses an unsigned ASCII decimal number.
+fn parse_decimal(data: &[u8]) -> Option<u64> {
+    if data.is_empty() || data.len() > MAX_PREDICATE_DIGITS || !data.iter().all(u8::is_ascii_digit)
+    {
+        return None;
+    }
+
+    Some(
+        data.iter()
+            .fold(0u64, |number, digit| number * 10 + (digit - b'0') as u64),
+    )
+}
+
+/// The circuit of a predicate proof.
+///
+/// The public inputs are the salted plaintext digest followed by the constants of the predicate.
+struct PredicateCircuit {
+    data: Vec<u8>,
+    salt: [u8; PLAINTEXT_HASH_SALT_LEN],
+    digest: [u8; 32],
+    predicate: Predicate,
+}
+
+impl ConstraintSynthesizer<Fr> for PredicateCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let digest = UInt8::new_input_vec(cs.clone(), &self.digest)?;
+        let data = UInt8::new_witness_vec(cs.clone(), self.data.as_slice())?;
+        let salt = UInt8::new_witness_vec(cs.clone(), self.salt.as_slice())?;
+
+        // The plaintext is the one of the commitment
+        let salted_data = data.iter().chain(&salt).cloned().collect::<Vec<_>>();
+        Sha256Gadget::digest(&salted_data)?
+            .0
+            .as_slice()
+            .enforce_equal(digest.as_slice())?;
+
+        match self.predicate {
+            Predicate::Compare { op, value } => {
+                let number = decimal_var(&data)?;
+                let value = FpVar::new_input(cs, || Ok(Fr::from(value)))?;
+                match op {
+                    ComparisonOp::Lt => number.enforce_cmp(&value, Ordering::Less, false)?,
+                    ComparisonOp::Le => number.enforce_cmp(&value, Ordering::Less, true)?,
+                    ComparisonOp::Gt => number.enforce_cmp(&value, Ordering::Greater, false)?,
+                    ComparisonOp::Ge => number.enforce_cmp(&value, Ordering::Greater, true)?,
+                    ComparisonOp::Eq => number.enforce_equal(&value)?,
+                    ComparisonOp::Ne => number.enforce_not_equal(&value)?,
+                }
+            }
+            Predicate::Range { min, max } => {
+                let number = decimal_var(&data)?;
+                let min = FpVar::new_input(cs.clone(), || Ok(Fr::from(min)))?;
+                let max = FpVar::new_input(cs, || Ok(Fr::from(max)))?;
+                number.enforce_cmp(&min, Ordering::Greater, true)?;
+                number.enforce_cmp(&max, Ordering::Less, true)?;
+            }
+            Predicate::EqualsHash { hash } => {
+                let hash = UInt8::new_input_vec(cs, &hash)?;
+                Sha256Gadget::digest(&data)?
+                    .0
+                    .as_slice()
+                    .enforce_equal(hash.as_slice())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the number encoded by ASCII decimal digits, enforcing that each byte is a digit.
+fn decimal_var(data: &[UInt8<Fr>]) -> Result<FpVar<Fr>, SynthesisError> {
+    let zero = FpVar::constant(Fr::from(b'0' as u64));
+    let nine = FpVar::constant(Fr::from(9u64));
+    let ten = FpVar::constant(Fr::from(10u64));
+
+    data.iter().try_fold(FpVar::zero(), |number, byte| {
+        let digit = Boolean::le_bits_to_fp_var(&byte.to_bits_le()?)? - &zero;
+        // A byte below '0' wraps around and is rejected as well
+        digit.enforce_cmp(&nine, Ordering::Less, true)?;
+        Ok(number * &ten + digit)
+    })
+}
+
+/// Returns the public inputs of a predicate proof.
+fn public_inputs(digest: &[u8; 32], predicate: &Predicate) -> Vec<Fr> {
+    let mut inputs: Vec<Fr> = digest
+        .as_slice()
+        .to_field_elements()
+        .expect("bytes are convertible to field elements");
+    inputs.extend(predicate.public_inputs());
+    inputs
+}
+
+/// A proving key for predicate proofs.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PredicateProvingKey {
+    len: usize,
+    kind: PredicateKind,
+    #[serde(serialize_with = "serialize_ark", deserialize_with = "deserialize_ark")]
+    key: ProvingKey<Bn254>,
+}
+
+opaque_debug::implement!(PredicateProvingKey);
+
+impl PredicateProvingKey {
+    /// Returns the length of the committed data which this key proves predicates for
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether this key is for empty data, which is never the case
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the kind of predicates this key proves
+    pub fn kind(&self) -> PredicateKind {
+        self.kind
+    }
+}
+
+/// A verifying key for predicate proofs.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PredicateVerifyingKey {
+    len: usize,
+    kind: PredicateKind,
+    #[serde(serialize_with = "serialize_ark", deserialize_with = "deserialize_ark")]
+    key: VerifyingKey<Bn254>,
+}
+
+opaque_debug::implement!(PredicateVerifyingKey);
+
+impl PredicateVerifyingKey {
+    /// Returns the length of the committed data which this key verifies predicates for
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether this key is for empty data, which is never the case
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the kind of predicates this key verifies
+    pub fn kind(&self) -> PredicateKind {
+        self.kind
+    }
+}
+
+/// Creates the keys to prove and verify predicates of `kind` over `len` bytes of committed data.
+///
+/// See the [module documentation](self) for who should run the setup.
+pub fn setup_predicate_keys<R: RngCore + CryptoRng>(
+    len: usize,
+    kind: PredicateKind,
+    rng: &mut R,
+) -> Result<(PredicateProvingKey, PredicateVerifyingKey), PredicateProofBuilderError> {
+    let numeric = !matches!(kind, PredicateKind::EqualsHash);
+    if len == 0 || (numeric && len > MAX_PREDICATE_DIGITS) {
+        return Err(PredicateProofBuilderError::UnsupportedLength(len));
+    }
+
+    let circuit = PredicateCircuit {
+        data: vec![b'0'; len],
+        salt: [0u8; PLAINTEXT_HASH_SALT_LEN],
+        digest: [0u8; 32],
+        predicate: Predicate::placeholder(kind),
+    };
+
+    let (proving_key, verifying_key) = Groth16::<Bn254>::circuit_specific_setup(circuit, rng)
+        .map_err(|e| PredicateProofBuilderError::ProofError(e.to_string()))?;
+
+    Ok((
+        PredicateProvingKey {
+            len,
+            kind,
+            key: proving_key,
+        },
+        PredicateVerifyingKey {
+            len,
+            kind,
+            key: verifying_key,
+        },
+    ))
+}
+
+/// An error for building a [`PredicateProof`]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PredicateProofBuilderError {
+    /// Invalid commitment id.
+    #[error("invalid commitment id: {0:?}")]
+    InvalidCommitmentId(CommitmentId),
+    /// Invalid commitment type.
+    #[error("commitment {0:?} is not a plaintext hash commitment")]
+    InvalidCommitmentType(CommitmentId),
+    /// The length of the data is not supported by the predicate.
+    #[error("predicates over {0} bytes are not supported")]
+    UnsupportedLength(usize),
+    /// The proving key is not for the length of the data or the kind of the predicate.
+    #[error("proving key is for {0:?} over {1} bytes")]
+    KeyMismatch(PredicateKind, usize),
+    /// The predicate does not hold for the committed data.
+    #[error("the predicate does not hold for the committed data")]
+    UnsatisfiedPredicate,
+    /// Failed to create the proof.
+    #[error("failed to create the proof: {0}")]
+    ProofError(String),
+}
+
+/// An error for [`PredicateProof`]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PredicateProofError {
+    /// The commitment is not a plaintext hash commitment which was proven to the Notary.
+    #[error("plaintext hash commitment {0:?} was not proven to the notary")]
+    UnprovenPlaintextHash(CommitmentId),
+    /// Range of the commitment is out of bounds.
+    #[error("range of commitment {0:?} is out of bounds")]
+    RangeOutOfBounds(CommitmentId),
+    /// The verifying key is not for the length of the data or the kind of the predicate.
+    #[error("verifying key is for {0:?} over {1} bytes")]
+    KeyMismatch(PredicateKind, usize),
+    /// The proof contains an invalid inclusion proof.
+    #[error("invalid inclusion proof: {0}")]
+    InvalidInclusionProof(String),
+    /// The inclusion proof uses another hash algorithm than the one signed in the session header.
+    #[error("inclusion proof uses {0:?}, but the session header specifies {1:?}")]
+    MerkleHashMismatch(HashAlgorithm, HashAlgorithm),
+    /// The zero-knowledge proof is invalid.
+    #[error("invalid predicate proof")]
+    InvalidProof,
+}
+
+/// A zero-knowledge proof that a predicate holds for the plaintext of a commitment
+///
+/// The proof contains the salted plaintext digest of the commitment and a proof that the
+/// commitment is present in the Merkle tree, which binds the predicate to the session header.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PredicateProof {
+    id: CommitmentId,
+    info: CommitmentInfo,
+    digest: [u8; 32],
+    predicate: Predicate,
+    inclusion_proof: MerkleProof,
+    #[serde(serialize_with = "serialize_ark", deserialize_with = "deserialize_ark")]
+    proof: Proof<Bn254>,
+}
+
+opaque_debug::implement!(PredicateProof);
+
+impl PredicateProof {
+    /// Creates a proof that `predicate` holds for the plaintext of the commitment `id`.
+    pub(crate) fn new<R: RngCore + CryptoRng>(
+        commitments: &TranscriptCommitments,
+        transcript_tx: &Transcript,
+        transcript_rx: &Transcript,
+        id: CommitmentId,
+        predicate: Predicate,
+        key: &PredicateProvingKey,
+        rng: &mut R,
+    ) -> Result<Self, PredicateProofBuilderError> {
+        let commitment = commitments
+            .get(&id)
+            .ok_or(PredicateProofBuilderError::InvalidCommitmentId(id))?;
+        let info = commitments
+            .get_info(&id)
+            .expect("info exists if commitment exists");
+
+        let Commitment::Sha256(commitment) = commitment else {
+            return Err(PredicateProofBuilderError::InvalidCommitmentType(id));
+        };
+
+        let len = info.ranges().len();
+        if key.len != len || key.kind != predicate.kind() {
+            return Err(PredicateProofBuilderError::KeyMismatch(key.kind, key.len));
+        }
+
+        let transcript = match info.direction() {
+            Direction::Sent => transcript_tx,
+            Direction::Received => transcript_rx,
+        };
+        let data = transcript.get_bytes_in_ranges(info.ranges());
+
+        // A proof of an unsatisfied circuit would simply be invalid, so check it beforehand
+        if !predicate.evaluate(&data) {
+            return Err(PredicateProofBuilderError::UnsatisfiedPredicate);
+        }
+
+        let circuit = PredicateCircuit {
+            data,
+            salt: *commitment.salt(),
+            digest: *commitment.digest(),
+            predicate,
+        };
+
+        let proof = Groth16::<Bn254>::prove(&key.key, circuit, rng)
+            .map_err(|e| PredicateProofBuilderError::ProofError(e.to_string()))?;
+
+        Ok(Self {
+            id,
+            info: info.clone(),
+            digest: *commitment.digest(),
+            predicate,
+            inclusion_proof: commitments.merkle_tree().proof(&[id.to_inner() as usize]),
+            proof,
+        })
+    }
+
+    /// Returns the info of the commitment, i.e. the ranges of the transcript the predicate is
+    /// about.
+    pub fn info(&self) -> &CommitmentInfo {
+        &self.info
+    }
+
+    /// Returns the predicate.
+    pub fn predicate(&self) -> &Predicate {
+        &self.predicate
+    }
+
+    /// Verifies this proof.
+    ///
+    /// The session header must be verified separately, e.g. with a
+    /// [`SessionProof`](crate::proof::SessionProof).
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The session header.
+    /// * `key` - The verifying key for the predicate.
+    pub fn verify(
+        &self,
+        header: &SessionHeader,
+        key: &PredicateVerifyingKey,
+    ) -> Result<(), PredicateProofError> {
+        // Make sure the Notary checked the hash of the plaintext, otherwise the Prover could
+        // have committed to any data.
+        if self.info.kind() != CommitmentKind::Sha256
+            || !header.plaintext_hash_ids().contains(&self.id)
+        {
+            return Err(PredicateProofError::UnprovenPlaintextHash(self.id));
+        }
+
+        let transcript_len = match self.info.direction() {
+            Direction::Sent => header.sent_len(),
+            Direction::Received => header.recv_len(),
+        };
+        match self.info.ranges().max() {
+            Some(max) if max <= transcript_len => {}
+            _ => return Err(PredicateProofError::RangeOutOfBounds(self.id)),
+        }
+
+        if key.len != self.info.ranges().len() || key.kind != self.predicate.kind() {
+            return Err(PredicateProofError::KeyMismatch(key.kind, key.len));
+        }
+
+        if self.inclusion_proof.algorithm() != header.merkle_hash() {
+            return Err(PredicateProofError::MerkleHashMismatch(
+                self.inclusion_proof.algorithm(),
+                header.merkle_hash(),
+            ));
+        }
+
+        let leaf = leaf_hash(&self.digest, self.info.ranges(), *self.info.direction());
+        self.inclusion_proof
+            .verify(
+                header.merkle_root(),
+                &[self.id.to_inner() as usize],
+                &[leaf],
+            )
+            .map_err(|e| PredicateProofError::InvalidInclusionProof(e.to_string()))?;
+
+        let inputs = public_inputs(&self.digest, &self.predicate);
+        match Groth16::<Bn254>::verify(&key.key, &inputs, &self.proof) {
+            Ok(true) => Ok(()),
+            _ => Err(PredicateProofError::InvalidProof),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commitment::TranscriptCommitmentBuilder, fixtures};
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+    use rstest::*;
+
+    fn is_satisfied(data: &[u8], predicate: Predicate) -> bool {
+        let salt = [7u8; PLAINTEXT_HASH_SALT_LEN];
+        let digest = crate::commitment::plaintext::plaintext_hash(data, &salt);
+        let circuit = PredicateCircuit {
+            data: data.to_vec(),
+            salt,
+            digest,
+            predicate,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[rstest]
+    #[case::gt(b"1500", Predicate::Compare { op: ComparisonOp::Gt, value: 1000 }, true)]
+    #[case::gt_equal(b"1000", Predicate::Compare { op: ComparisonOp::Gt, value: 1000 }, false)]
+    #[case::ge(b"18", Predicate::Compare { op: ComparisonOp::Ge, value: 18 }, true)]
+    #[case::lt(b"017", Predicate::Compare { op: ComparisonOp::Lt, value: 18 }, true)]
+    #[case::ne(b"5", Predicate::Compare { op: ComparisonOp::Ne, value: 5 }, false)]
+    #[case::range(b"42", Predicate::Range { min: 18, max: 65 }, true)]
+    #[case::out_of_range(b"99", Predicate::Range { min: 18, max: 65 }, false)]
+    #[case::not_a_number(b"4a", Predicate::Range { min: 0, max: 100 }, false)]
+    #[case::hash(b"secret", Predicate::EqualsHash { hash: Sha256::digest(b"secret").into() }, true)]
+    #[case::wrong_hash(b"secret", Predicate::EqualsHash { hash: [0u8; 32] }, false)]
+    fn test_predicate_circuit(
+        #[case] data: &[u8],
+        #[case] predicate: Predicate,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(predicate.evaluate(data), expected);
+        assert_eq!(is_satisfied(data, predicate), expected);
+    }
+
+    #[test]
+    fn test_predicate_proof() {
+        let sent = b"GET /balance";
+        let recv = b"{\"balance\":1500}";
+        let transcript_tx = Transcript::new(sent.to_vec());
+        let transcript_rx = Transcript::new(recv.to_vec());
+
+        let mut builder = TranscriptCommitmentBuilder::new(
+            fixtures::encoding_provider(sent, recv),
+            sent.len(),
+            recv.len(),
+        )
+        .with_transcripts(transcript_tx.clone(), transcript_rx.clone());
+        let id = builder.commit_recv_hash(11..15).unwrap();
+        let commitments = builder.build().unwrap();
+
+        let header = fixtures::session_header(commitments.merkle_root(), sent.len(), recv.len())
+            .with_plaintext_hash_ids(vec![id]);
+
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let kind = PredicateKind::Compare(ComparisonOp::Gt);
+        let (proving_key, verifying_key) = setup_predicate_keys(4, kind, &mut rng).unwrap();
+
+        let predicate = Predicate::Compare {
+            op: ComparisonOp::Gt,
+            value: 1000,
+        };
+        let proof = PredicateProof::new(
+            &commitments,
+            &transcript_tx,
+            &transcript_rx,
+            id,
+            predicate,
+            &proving_key,
+            &mut rng,
+        )
+        .unwrap();
+
+        let proof: PredicateProof =
+            bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+        proof.verify(&header, &verifying_key).unwrap();
+
+        // The proof does not hold for another constant
+        let mut forged = proof.clone();
+        forged.predicate = Predicate::Compare {
+            op: ComparisonOp::Gt,
+            value: 2000,
+        };
+        assert!(matches!(
+            forged.verify(&header, &verifying_key),
+            Err(PredicateProofError::InvalidProof)
+        ));
+
+        // The Notary did not check the plaintext hash
+        let header = fixtures::session_header(commitments.merkle_root(), sent.len(), recv.len());
+        assert!(matches!(
+            proof.verify(&header, &verifying_key),
+            Err(PredicateProofError::UnprovenPlaintextHash(_))
+        ));
+
+        // The predicate does not hold
+        let predicate = Predicate::Compare {
+            op: ComparisonOp::Gt,
+            value: 2000,
+        };
+        assert!(matches!(
+            PredicateProof::new(
+                &commitments,
+                &transcript_tx,
+                &transcript_rx,
+                id,
+                predicate,
+                &proving_key,
+                &mut rng,
+            ),
+            Err(PredicateProofBuilderError::UnsatisfiedPredicate)
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use tls_core::verify::ServerCertVerifier;
+
+use crate::{
+    format::{self, FormatError, ObjectKind, SignatureAlgorithm, Versioned},
+    merkle::HashAlgorithm,
+    proof::{
+        default_cert_verifier, SessionProof, SessionProofError, SubstringsProof,
+        SubstringsProofError,
+    },
+    NotaryPublicKey, RedactedTranscript, ServerName, SessionHeader,
+};
+
+/// An error that can occur while verifying a [`Presentation`].
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PresentationError {
+    /// The session proof is invalid.
+    #[error(transparent)]
+    Session(#[from] SessionProofError),
+    /// The substrings proof is invalid.
+    #[error(transparent)]
+    Substrings(#[from] SubstringsProofError),
+}
+
+/// A presentation of a notarized session to a Verifier
+///
+/// It contains a [`SessionProof`] and a [`SubstringsProof`], which are verified together with
+/// [`Presentation::verify`]. A presentation is created with
+/// [`NotarizedSession::presentation`](crate::NotarizedSession::presentation).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Presentation {
+    session: SessionProof,
+    substrings: SubstringsProof,
+}
+
+impl Presentation {
+    /// Creates a new presentation.
+    pub(crate) fn new(session: SessionProof, substrings: SubstringsProof) -> Self {
+        Self {
+            session,
+            substrings,
+        }
+    }
+
+    /// Returns the session proof
+    pub fn session(&self) -> &SessionProof {
+        &self.session
+    }
+
+    /// Returns the substrings proof
+    pub fn substrings(&self) -> &SubstringsProof {
+        &self.substrings
+    }
+
+    /// Verifies this presentation and, if successful, returns the verified data.
+    ///
+    /// # Arguments
+    ///
+    /// * `notary_public_key` - The public key of the notary.
+    /// * `cert_verifier` - The certificate verifier.
+    pub fn verify(
+        self,
+        notary_public_key: impl Into<NotaryPublicKey>,
+        cert_verifier: &impl ServerCertVerifier,
+    ) -> Result<VerifiedPresentation, PresentationError> {
+        let Self {
+            session,
+            substrings,
+        } = self;
+
+        session.verify(notary_public_key, cert_verifier)?;
+
+        let SessionProof {
+            header,
+            session_info,
+            ..
+        } = session;
+
+        let (sent, recv) = substrings.verify(&header)?;
+
+        Ok(VerifiedPresentation {
+            server_name: session_info.server_name,
+            time: header.time(),
+            sent,
+            recv,
+            header,
+        })
+    }
+
+    /// Verifies this presentation using a default certificate verifier which trusts the root
+    /// certificates from the `webpki-roots` crate.
+    ///
+    /// # Arguments
+    ///
+    /// * `notary_public_key` - The public key of the notary.
+    pub fn verify_with_default_cert_verifier(
+        self,
+        notary_public_key: impl Into<NotaryPublicKey>,
+    ) -> Result<VerifiedPresentation, PresentationError> {
+        self.verify(notary_public_key, &default_cert_verifier())
+    }
+
+    /// Returns the encoding of this presentation in the [versioned binary format](crate::format).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        format::encode(self)
+    }
+
+    /// Decodes a presentation from the [versioned binary format](crate::format).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FormatError> {
+        format::decode(bytes)
+    }
+}
+
+impl Versioned for Presentation {
+    const KIND: ObjectKind = ObjectKind::Presentation;
+
+    fn signature_algorithm(&self) -> Option<SignatureAlgorithm> {
+        self.session.signature.as_ref().map(SignatureAlgorithm::of)
+    }
+
+    fn merkle_hash_algorithm(&self) -> Option<HashAlgorithm> {
+        self.substrings.merkle_hash_algorithm()
+    }
+}
+
+/// The data of a verified [`Presentation`]
+#[derive(Debug)]
+pub struct VerifiedPresentation {
+    server_name: ServerName,
+    time: u64,
+    sent: RedactedTranscript,
+    recv: RedactedTranscript,
+    header: SessionHeader,
+}
+
+impl VerifiedPresentation {
+    /// Returns the name of the server
+    pub fn server_name(&self) -> &ServerName {
+        &self.server_name
+    }
+
+    /// Returns the time of the session, as seconds since the UNIX epoch
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Returns the redacted transcript of the data sent to the server
+    pub fn sent(&self) -> &RedactedTranscript {
+        &self.sent
+    }
+
+    /// Returns the redacted transcript of the data received from the server
+    pub fn recv(&self) -> &RedactedTranscript {
+        &self.recv
+    }
+
+    /// Returns the session header signed by the Notary
+    ///
+    /// It can be used to verify further proofs about the session.
+    pub fn header(&self) -> &SessionHeader {
+        &self.header
+    }
+
+    /// Returns the redacted sent and received transcripts
+    pub fn into_transcripts(self) -> (RedactedTranscript, RedactedTranscript) {
+        (self.sent, self.recv)
+    }
+}
@@ -11,6 +11,8 @@ use tls_core::{
 };
 
 use crate::{
+    format::{self, FormatError, ObjectKind, SignatureAlgorithm, Versioned},
+    merkle::HashAlgorithm,
     session::SessionHeader,
     signature::{Signature, SignatureVerifyError},
     HandshakeSummary, NotaryPublicKey, ServerName,
@@ -86,6 +88,28 @@ impl SessionProof {
     ) -> Result<(), SessionProofError> {
         self.verify(notary_public_key, &default_cert_verifier())
     }
+
+    /// Returns the encoding of this session proof in the [versioned binary format](crate::format).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        format::encode(self)
+    }
+
+    /// Decodes a session proof from the [versioned binary format](crate::format).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FormatError> {
+        format::decode(bytes)
+    }
+}
+
+impl Versioned for SessionProof {
+    const KIND: ObjectKind = ObjectKind::SessionProof;
+
+    fn signature_algorithm(&self) -> Option<SignatureAlgorithm> {
+        self.signature.as_ref().map(SignatureAlgorithm::of)
+    }
+
+    fn merkle_hash_algorithm(&self) -> Option<HashAlgorithm> {
+        None
+    }
 }
 
 /// Contains information about the session
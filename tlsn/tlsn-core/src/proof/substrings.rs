@@ -4,13 +4,11 @@ use crate::{
     commitment::{
         Commitment, CommitmentId, CommitmentInfo, CommitmentOpening, TranscriptCommitments,
     },
-    merkle::MerkleProof,
-    transcript::get_value_ids,
-    Direction, EncodingId, RedactedTranscript, SessionHeader, Transcript, TranscriptSlice,
+    format::{self, FormatError, ObjectKind, SignatureAlgorithm, Versioned},
+    merkle::{HashAlgorithm, MerkleProof},
+    Direction, RedactedTranscript, SessionHeader, Transcript, TranscriptSlice,
     MAX_TOTAL_COMMITTED_DATA,
 };
-use mpz_circuits::types::ValueType;
-use mpz_garble_core::Encoder;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use utils::range::{RangeDisjoint, RangeSet, RangeUnion};
@@ -72,12 +70,6 @@ impl<'a> SubstringsProofBuilder<'a> {
             .get_info(&id)
             .expect("info exists if commitment exists");
 
-        #[allow(irrefutable_let_patterns)]
-        let Commitment::Blake3(commitment) = commitment
-        else {
-            return Err(SubstringsProofBuilderError::InvalidCommitmentType(id));
-        };
-
         let transcript = match info.direction() {
             Direction::Sent => self.transcript_tx,
             Direction::Received => self.transcript_rx,
@@ -85,12 +77,15 @@ impl<'a> SubstringsProofBuilder<'a> {
 
         let data = transcript.get_bytes_in_ranges(info.ranges());
 
+        let opening: CommitmentOpening = match commitment {
+            Commitment::Blake3(commitment) => commitment.open(data).into(),
+            Commitment::Sha256(commitment) => commitment.open(data).into(),
+            #[cfg(feature = "poseidon")]
+            Commitment::Poseidon(commitment) => commitment.open(data).into(),
+        };
+
         // add commitment to openings and return an error if it is already present
-        if self
-            .openings
-            .insert(id, (info.clone(), commitment.open(data).into()))
-            .is_some()
-        {
+        if self.openings.insert(id, (info.clone(), opening)).is_some() {
             return Err(SubstringsProofBuilderError::DuplicateCommitmentId(id));
         }
 
@@ -139,9 +134,15 @@ pub enum SubstringsProofError {
     /// The proof contains an invalid commitment opening.
     #[error("invalid opening for commitment id: {0:?}")]
     InvalidOpening(CommitmentId),
+    /// The proof opens a plaintext hash commitment which was not proven to the Notary.
+    #[error("plaintext hash commitment {0:?} was not proven to the notary")]
+    UnprovenPlaintextHash(CommitmentId),
     /// The proof contains an invalid inclusion proof.
     #[error("invalid inclusion proof: {0}")]
     InvalidInclusionProof(String),
+    /// The inclusion proof uses another hash algorithm than the one signed in the session header.
+    #[error("inclusion proof uses {0:?}, but the session header specifies {1:?}")]
+    MerkleHashMismatch(HashAlgorithm, HashAlgorithm),
 }
 
 /// A substring proof using commitments
@@ -150,6 +151,7 @@ pub enum SubstringsProofError {
 /// that the corresponding commitments are present in the merkle tree.
 #[derive(Serialize, Deserialize)]
 pub struct SubstringsProof {
+    #[serde(serialize_with = "crate::format::serialize_sorted_map")]
     openings: HashMap<CommitmentId, (CommitmentInfo, CommitmentOpening)>,
     inclusion_proof: MerkleProof,
 }
@@ -171,6 +173,15 @@ impl SubstringsProof {
             inclusion_proof,
         } = self;
 
+        // Make sure the proof is verified with the hash algorithm the Notary signed, as the
+        // root of a tree could be the root of another tree with a weaker algorithm.
+        if inclusion_proof.algorithm() != header.merkle_hash() {
+            return Err(SubstringsProofError::MerkleHashMismatch(
+                inclusion_proof.algorithm(),
+                header.merkle_hash(),
+            ));
+        }
+
         let mut indices = Vec::with_capacity(openings.len());
         let mut expected_hashes = Vec::with_capacity(openings.len());
         let mut sent = vec![0u8; header.sent_len()];
@@ -179,9 +190,20 @@ impl SubstringsProof {
         let mut recv_ranges = RangeSet::default();
         let mut total_opened = 0u128;
         for (id, (info, opening)) in openings {
+            // Make sure the opening is of the kind of the commitment.
+            if opening.kind() != info.kind() {
+                return Err(SubstringsProofError::InvalidOpening(id));
+            }
+
+            // Make sure the Notary checked the hash of the plaintext, otherwise the Prover could
+            // have committed to any data.
+            if info.kind().is_plaintext_hash() && !header.plaintext_hash_ids().contains(&id) {
+                return Err(SubstringsProofError::UnprovenPlaintextHash(id));
+            }
+
             let CommitmentInfo {
                 ranges, direction, ..
-            } = info;
+            } = info.clone();
 
             let opened_len = ranges.len();
 
@@ -225,19 +247,10 @@ impl SubstringsProof {
                 return Err(SubstringsProofError::RangeOutOfBounds(id, max));
             }
 
-            // Generate the expected encodings for the purported data in the opening.
-            let encodings = get_value_ids(&ranges, direction)
-                .map(|id| {
-                    header
-                        .encoder()
-                        .encode_by_type(EncodingId::new(&id).to_inner(), &ValueType::U8)
-                })
-                .collect::<Vec<_>>();
-
             // Compute the expected hash of the commitment to make sure it is
             // present in the merkle tree.
             indices.push(id.to_inner() as usize);
-            expected_hashes.push(opening.recover(&encodings).hash());
+            expected_hashes.push(opening.recover(&info, header).hash());
 
             // Make sure the length of data from the opening matches the commitment.
             let mut data = opening.into_data();
@@ -283,4 +296,26 @@ impl SubstringsProof {
             RedactedTranscript::new(header.recv_len(), recv_slices),
         ))
     }
+
+    /// Returns the encoding of this substrings proof in the [versioned binary format](crate::format).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        format::encode(self)
+    }
+
+    /// Decodes a substrings proof from the [versioned binary format](crate::format).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FormatError> {
+        format::decode(bytes)
+    }
+}
+
+impl Versioned for SubstringsProof {
+    const KIND: ObjectKind = ObjectKind::SubstringsProof;
+
+    fn signature_algorithm(&self) -> Option<SignatureAlgorithm> {
+        None
+    }
+
+    fn merkle_hash_algorithm(&self) -> Option<HashAlgorithm> {
+        Some(self.inclusion_proof.algorithm())
+    }
 }
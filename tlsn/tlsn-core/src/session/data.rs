@@ -1,8 +1,22 @@
+#[cfg(feature = "predicate")]
+use crate::{
+    commitment::CommitmentId,
+    proof::{Predicate, PredicateProof, PredicateProofBuilderError, PredicateProvingKey},
+};
 use crate::{
     commitment::TranscriptCommitments,
     proof::{SessionInfo, SubstringsProofBuilder},
     ServerName, Transcript,
 };
+#[cfg(feature = "poseidon")]
+use crate::{
+    commitment::{
+        poseidon::{PoseidonLinkError, PoseidonProvingKey},
+        Commitment,
+    },
+    msg::PoseidonLink,
+    Direction,
+};
 use mpz_core::commit::Decommitment;
 use serde::{Deserialize, Serialize};
 use tls_core::handshake::HandshakeData;
@@ -72,6 +86,68 @@ impl SessionData {
     pub fn build_substrings_proof(&self) -> SubstringsProofBuilder {
         SubstringsProofBuilder::new(&self.commitments, &self.transcript_tx, &self.transcript_rx)
     }
+
+    /// Builds a zero-knowledge proof that `predicate` holds for the plaintext of a plaintext
+    /// hash commitment, without revealing the plaintext.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the plaintext hash commitment.
+    /// * `predicate` - The predicate to prove.
+    /// * `key` - The proving key for the length of the committed data and the predicate kind.
+    #[cfg(feature = "predicate")]
+    pub fn build_predicate_proof(
+        &self,
+        id: CommitmentId,
+        predicate: Predicate,
+        key: &PredicateProvingKey,
+    ) -> Result<PredicateProof, PredicateProofBuilderError> {
+        PredicateProof::new(
+            &self.commitments,
+            &self.transcript_tx,
+            &self.transcript_rx,
+            id,
+            predicate,
+            key,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// Builds the links of the Poseidon commitments, which prove to the Notary that their digests
+    /// are of the same salted plaintext as the SHA-256 digests it computes in MPC.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The proving keys for the lengths of the Poseidon commitments.
+    #[cfg(feature = "poseidon")]
+    pub fn build_poseidon_links(
+        &self,
+        keys: &[PoseidonProvingKey],
+    ) -> Result<Vec<PoseidonLink>, PoseidonLinkError> {
+        let mut rng = rand::thread_rng();
+
+        self.commitments
+            .plaintext_hashes()
+            .into_iter()
+            .filter_map(|(id, info, ..)| match self.commitments.get(&id) {
+                Some(Commitment::Poseidon(commitment)) => Some((id, info, commitment)),
+                _ => None,
+            })
+            .map(|(id, info, commitment)| {
+                let transcript = match info.direction() {
+                    Direction::Sent => &self.transcript_tx,
+                    Direction::Received => &self.transcript_rx,
+                };
+                let data = transcript.get_bytes_in_ranges(info.ranges());
+
+                Ok(PoseidonLink {
+                    id,
+                    digest: *commitment.digest(),
+                    proof: commitment.prove_link(data, keys, &mut rng)?,
+                })
+            })
+            .collect()
+    }
 }
 
 opaque_debug::implement!(SessionData);
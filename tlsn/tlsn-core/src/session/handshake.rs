@@ -1,6 +1,6 @@
 use mpz_core::{commit::Decommitment, hash::Hash};
 use serde::{Deserialize, Serialize};
-use tls_core::{handshake::HandshakeData, key::PublicKey, msgs::handshake::ServerECDHParams};
+use tls_core::{handshake::HandshakeData, key::PublicKey, msgs::enums::NamedGroup};
 
 /// An error that can occur while verifying a handshake summary
 #[derive(Debug, thiserror::Error)]
@@ -53,6 +53,11 @@ impl HandshakeSummary {
         &self.server_public_key
     }
 
+    /// Returns the group which was used for the key exchange
+    pub fn key_exchange_group(&self) -> NamedGroup {
+        self.server_public_key.group
+    }
+
     /// Returns commitment to the handshake data
     pub fn handshake_commitment(&self) -> &Hash {
         &self.handshake_commitment
@@ -64,13 +69,10 @@ impl HandshakeSummary {
         data.verify(&self.handshake_commitment)
             .map_err(|_| HandshakeVerifyError::Commitment)?;
 
-        let ecdh_params = tls_core::suites::tls12::decode_ecdh_params::<ServerECDHParams>(
-            data.data().server_kx_details().kx_params(),
-        )
-        .ok_or(HandshakeVerifyError::KxParams)?;
-
-        let server_public_key =
-            PublicKey::new(ecdh_params.curve_params.named_group, &ecdh_params.public.0);
+        let server_public_key = data
+            .data()
+            .server_public_key()
+            .ok_or(HandshakeVerifyError::KxParams)?;
 
         // Ephemeral pubkey must match the one which the Notary signed
         if server_public_key != self.server_public_key {
@@ -4,7 +4,11 @@ use serde::{Deserialize, Serialize};
 use mpz_garble_core::ChaChaEncoder;
 use tls_core::{handshake::HandshakeData, key::PublicKey};
 
-use crate::{merkle::MerkleRoot, HandshakeSummary};
+use crate::{
+    commitment::CommitmentId,
+    merkle::{HashAlgorithm, MerkleRoot},
+    HandshakeSummary,
+};
 
 /// An error that can occur while verifying a session header
 #[derive(Debug, thiserror::Error)]
@@ -16,6 +20,9 @@ pub enum SessionHeaderVerifyError {
 }
 
 /// An authentic session header from the Notary
+///
+/// The Notary signs the bincode encoding of all fields of the header. Headers signed by releases
+/// before [format version 1](crate::format#compatibility) have another layout and do not verify.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionHeader {
     /// A PRG seeds used to generate encodings for the plaintext
@@ -35,6 +42,16 @@ pub struct SessionHeader {
     recv_len: usize,
 
     handshake_summary: HandshakeSummary,
+
+    /// The ids of the plaintext hash commitments in the Merkle tree which the Prover proved to
+    /// the Notary in MPC
+    plaintext_hash_ids: Vec<CommitmentId>,
+
+    /// The ID of the Notary key used to sign this header, if the Notary publishes more than one key
+    notary_key_id: Option<String>,
+
+    /// The hash algorithm of the Merkle tree of the commitments, which inclusion proofs must use
+    merkle_hash: HashAlgorithm,
 }
 
 impl SessionHeader {
@@ -52,9 +69,30 @@ impl SessionHeader {
             sent_len,
             recv_len,
             handshake_summary,
+            plaintext_hash_ids: Vec::new(),
+            notary_key_id: None,
+            merkle_hash: HashAlgorithm::default(),
         }
     }
 
+    /// Sets the hash algorithm of the Merkle tree of the commitments, which defaults to SHA-256
+    pub fn with_merkle_hash(mut self, algorithm: HashAlgorithm) -> Self {
+        self.merkle_hash = algorithm;
+        self
+    }
+
+    /// Sets the ids of the plaintext hash commitments which the Prover proved to the Notary
+    pub fn with_plaintext_hash_ids(mut self, ids: Vec<CommitmentId>) -> Self {
+        self.plaintext_hash_ids = ids;
+        self
+    }
+
+    /// Sets the ID of the Notary key which signs this header
+    pub fn with_notary_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.notary_key_id = Some(key_id.into());
+        self
+    }
+
     /// Verify the data in the header is consistent with the Prover's view
     pub fn verify(
         &self,
@@ -117,4 +155,21 @@ impl SessionHeader {
     pub fn recv_len(&self) -> usize {
         self.recv_len
     }
+
+    /// Returns the ids of the plaintext hash commitments which the Prover proved to the Notary
+    pub fn plaintext_hash_ids(&self) -> &[CommitmentId] {
+        &self.plaintext_hash_ids
+    }
+
+    /// Returns the ID of the Notary key which signed this header, if set
+    ///
+    /// Verifiers can use this to select the Notary public key after the Notary rotated its keys.
+    pub fn notary_key_id(&self) -> Option<&str> {
+        self.notary_key_id.as_deref()
+    }
+
+    /// Returns the hash algorithm of the Merkle tree of the commitments
+    pub fn merkle_hash(&self) -> HashAlgorithm {
+        self.merkle_hash
+    }
 }
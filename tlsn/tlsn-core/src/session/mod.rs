@@ -11,7 +11,9 @@ pub use handshake::{HandshakeSummary, HandshakeVerifyError};
 pub use header::{SessionHeader, SessionHeaderVerifyError};
 
 use crate::{
-    proof::{SessionInfo, SessionProof},
+    format::{self, FormatError, ObjectKind, SignatureAlgorithm, Versioned},
+    merkle::HashAlgorithm,
+    proof::{Presentation, SessionInfo, SessionProof, SubstringsProof},
     signature::Signature,
 };
 
@@ -49,6 +51,13 @@ impl NotarizedSession {
         }
     }
 
+    /// Returns a presentation of the session which reveals the data opened by `substrings`
+    ///
+    /// The substrings proof is built with [`SessionData::build_substrings_proof`].
+    pub fn presentation(&self, substrings: SubstringsProof) -> Presentation {
+        Presentation::new(self.session_proof(), substrings)
+    }
+
     /// Returns the [SessionHeader]
     pub fn header(&self) -> &SessionHeader {
         &self.header
@@ -63,4 +72,26 @@ impl NotarizedSession {
     pub fn data(&self) -> &SessionData {
         &self.data
     }
+
+    /// Returns the encoding of this notarized session in the [versioned binary format](crate::format).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        format::encode(self)
+    }
+
+    /// Decodes a notarized session from the [versioned binary format](crate::format).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FormatError> {
+        format::decode(bytes)
+    }
+}
+
+impl Versioned for NotarizedSession {
+    const KIND: ObjectKind = ObjectKind::NotarizedSession;
+
+    fn signature_algorithm(&self) -> Option<SignatureAlgorithm> {
+        self.signature.as_ref().map(SignatureAlgorithm::of)
+    }
+
+    fn merkle_hash_algorithm(&self) -> Option<HashAlgorithm> {
+        Some(self.data.commitments().merkle_tree().algorithm())
+    }
 }
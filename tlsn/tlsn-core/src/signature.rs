@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use p256::ecdsa::{signature::Verifier, VerifyingKey};
+use sha3::{Digest, Keccak256};
 
 /// A Notary public key.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -8,6 +9,10 @@ use p256::ecdsa::{signature::Verifier, VerifyingKey};
 pub enum NotaryPublicKey {
     /// A NIST P-256 public key.
     P256(p256::PublicKey),
+    /// An Ed25519 public key.
+    Ed25519(ed25519_dalek::VerifyingKey),
+    /// A secp256k1 public key.
+    Secp256k1(k256::PublicKey),
 }
 
 impl From<p256::PublicKey> for NotaryPublicKey {
@@ -16,6 +21,18 @@ impl From<p256::PublicKey> for NotaryPublicKey {
     }
 }
 
+impl From<ed25519_dalek::VerifyingKey> for NotaryPublicKey {
+    fn from(key: ed25519_dalek::VerifyingKey) -> Self {
+        Self::Ed25519(key)
+    }
+}
+
+impl From<k256::PublicKey> for NotaryPublicKey {
+    fn from(key: k256::PublicKey) -> Self {
+        Self::Secp256k1(key)
+    }
+}
+
 /// An error occurred while verifying a signature.
 #[derive(Debug, thiserror::Error)]
 #[error("signature verification failed: {0}")]
@@ -27,6 +44,11 @@ pub struct SignatureVerifyError(String);
 pub enum Signature {
     /// A secp256r1 signature.
     P256(p256::ecdsa::Signature),
+    /// An Ed25519 signature.
+    Ed25519(ed25519_dalek::Signature),
+    /// A recoverable secp256k1 signature over the Keccak-256 digest of the message, as used by
+    /// Ethereum.
+    Secp256k1Eth(Secp256k1EthSignature),
 }
 
 impl From<p256::ecdsa::Signature> for Signature {
@@ -35,11 +57,27 @@ impl From<p256::ecdsa::Signature> for Signature {
     }
 }
 
+impl From<ed25519_dalek::Signature> for Signature {
+    fn from(sig: ed25519_dalek::Signature) -> Self {
+        Self::Ed25519(sig)
+    }
+}
+
+impl From<Secp256k1EthSignature> for Signature {
+    fn from(sig: Secp256k1EthSignature) -> Self {
+        Self::Secp256k1Eth(sig)
+    }
+}
+
 impl Signature {
     /// Returns the bytes of this signature.
+    ///
+    /// Ethereum signatures are encoded as `r || s || v`, with `v` being 27 or 28.
     pub fn to_bytes(&self) -> Vec<u8> {
         match self {
             Self::P256(sig) => sig.to_vec(),
+            Self::Ed25519(sig) => sig.to_vec(),
+            Self::Secp256k1Eth(sig) => sig.to_bytes().to_vec(),
         }
     }
 
@@ -58,6 +96,146 @@ impl Signature {
             (Self::P256(sig), NotaryPublicKey::P256(key)) => VerifyingKey::from(key)
                 .verify(msg, sig)
                 .map_err(|e| SignatureVerifyError(e.to_string())),
+            (Self::Ed25519(sig), NotaryPublicKey::Ed25519(key)) => key
+                .verify_strict(msg, sig)
+                .map_err(|e| SignatureVerifyError(e.to_string())),
+            (Self::Secp256k1Eth(sig), NotaryPublicKey::Secp256k1(key)) => {
+                let recovered_key = sig.recover(msg)?;
+
+                if recovered_key != k256::ecdsa::VerifyingKey::from(key) {
+                    return Err(SignatureVerifyError(
+                        "recovered key does not match the notary key".to_string(),
+                    ));
+                }
+
+                Ok(())
+            }
+            _ => Err(SignatureVerifyError(
+                "signature scheme does not match the notary key".to_string(),
+            )),
         }
     }
 }
+
+/// A recoverable secp256k1 signature over the Keccak-256 digest of a message.
+///
+/// The signature can be checked on-chain with `ecrecover(keccak256(msg), v, r, s)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Secp256k1EthSignature {
+    signature: k256::ecdsa::Signature,
+    recovery_id: u8,
+}
+
+impl Secp256k1EthSignature {
+    /// Signs the Keccak-256 digest of `msg`.
+    pub fn sign(key: &k256::ecdsa::SigningKey, msg: &[u8]) -> Self {
+        let (signature, recovery_id) = key
+            .sign_digest_recoverable(Keccak256::new_with_prefix(msg))
+            .expect("signing with a valid key does not fail");
+
+        Self {
+            signature,
+            recovery_id: recovery_id.to_byte(),
+        }
+    }
+
+    /// Returns the signature encoded as `r || s || v`, with `v` being 27 or 28.
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[..64].copy_from_slice(&self.signature.to_bytes());
+        bytes[64] = 27 + self.recovery_id;
+        bytes
+    }
+
+    /// Recovers the public key which signed `msg`.
+    pub fn recover(&self, msg: &[u8]) -> Result<k256::ecdsa::VerifyingKey, SignatureVerifyError> {
+        let recovery_id = k256::ecdsa::RecoveryId::from_byte(self.recovery_id)
+            .ok_or_else(|| SignatureVerifyError("invalid recovery id".to_string()))?;
+
+        k256::ecdsa::VerifyingKey::recover_from_digest(
+            Keccak256::new_with_prefix(msg),
+            &self.signature,
+            recovery_id,
+        )
+        .map_err(|e| SignatureVerifyError(e.to_string()))
+    }
+}
+
+/// A secp256k1 key which produces Ethereum-compatible signatures.
+///
+/// This can be passed to `Verifier::finalize` to sign the session header.
+pub struct Secp256k1EthSigner(k256::ecdsa::SigningKey);
+
+impl Secp256k1EthSigner {
+    /// Creates a new signer from a secp256k1 signing key.
+    pub fn new(key: k256::ecdsa::SigningKey) -> Self {
+        Self(key)
+    }
+
+    /// Returns the verifying key of the signer.
+    pub fn verifying_key(&self) -> &k256::ecdsa::VerifyingKey {
+        self.0.verifying_key()
+    }
+}
+
+opaque_debug::implement!(Secp256k1EthSigner);
+
+impl p256::ecdsa::signature::Signer<Secp256k1EthSignature> for Secp256k1EthSigner {
+    fn try_sign(&self, msg: &[u8]) -> Result<Secp256k1EthSignature, p256::ecdsa::Error> {
+        Ok(Secp256k1EthSignature::sign(&self.0, msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::signature::hazmat::PrehashVerifier;
+    use p256::ecdsa::signature::Signer;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    const MSG: &[u8] = b"session header";
+
+    #[test]
+    fn test_ed25519() {
+        let key = ed25519_dalek::SigningKey::generate(&mut ChaCha20Rng::seed_from_u64(0));
+        let sig: Signature = Signer::<ed25519_dalek::Signature>::sign(&key, MSG).into();
+
+        sig.verify(MSG, key.verifying_key()).unwrap();
+        assert!(sig.verify(b"other", key.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_secp256k1_eth() {
+        let key = k256::ecdsa::SigningKey::random(&mut ChaCha20Rng::seed_from_u64(0));
+        let signer = Secp256k1EthSigner::new(key.clone());
+        let public_key = k256::PublicKey::from(signer.verifying_key());
+        let sig: Signature = signer.sign(MSG).into();
+
+        sig.verify(MSG, public_key).unwrap();
+        assert!(sig.verify(b"other", public_key).is_err());
+
+        let bytes = sig.to_bytes();
+        assert_eq!(bytes.len(), 65);
+        assert!(bytes[64] == 27 || bytes[64] == 28);
+
+        // The signature is over the Keccak-256 digest, as expected by `ecrecover`
+        let Signature::Secp256k1Eth(eth_sig) = sig else {
+            panic!("signature is not a secp256k1 signature");
+        };
+        key.verifying_key()
+            .verify_prehash(&Keccak256::digest(MSG), &eth_sig.signature)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_scheme_mismatch() {
+        let key = ed25519_dalek::SigningKey::generate(&mut ChaCha20Rng::seed_from_u64(0));
+        let sig: Signature = Signer::<ed25519_dalek::Signature>::sign(&key, MSG).into();
+
+        let other_key = k256::ecdsa::SigningKey::random(&mut ChaCha20Rng::seed_from_u64(0));
+        assert!(sig
+            .verify(MSG, k256::PublicKey::from(other_key.verifying_key()))
+            .is_err());
+    }
+}
@@ -22,8 +22,8 @@ use mpz_core::{commit::HashCommit, serialize::CanonicalSerialize};
 use tlsn_core::{
     commitment::TranscriptCommitmentBuilder,
     fixtures,
-    msg::SignedSessionHeader,
-    proof::{SessionProof, SubstringsProof},
+    msg::{PlaintextHashes, SignedSessionHeader},
+    proof::{Presentation, SessionProof, SubstringsProof, SubstringsProofError},
     HandshakeSummary, NotarizedSession, ServerName, SessionData, SessionHeader, Signature,
     Transcript,
 };
@@ -126,7 +126,6 @@ fn test_api() {
     //---------------------------------------
 
     // Prover verifies the signature
-    #[allow(irrefutable_let_patterns)]
     if let Signature::P256(signature) = signature {
         notary_verifing_key
             .verify(&header.to_bytes(), &signature)
@@ -189,4 +188,204 @@ fn test_api() {
 
     assert_eq!(&sent.data()[range1], b"se".as_slice());
     assert_eq!(&recv.data()[range2], b"ec".as_slice());
+
+    // Alternatively, the Prover sends a presentation which the Verifier verifies in one call
+    let mut substrings_proof_builder = session.data().build_substrings_proof();
+    substrings_proof_builder.reveal(commitment_id_2).unwrap();
+    let presentation = session.presentation(substrings_proof_builder.build().unwrap());
+
+    let presentation = Presentation::from_bytes(&presentation.to_bytes()).unwrap();
+    let verified = presentation
+        .verify_with_default_cert_verifier(notary_pubkey)
+        .unwrap();
+
+    assert_eq!(verified.server_name().as_str(), testdata.dns_name.as_str());
+    assert_eq!(verified.time(), time + 60);
+    assert_eq!(verified.sent().authed().iter_ranges().count(), 0);
+    assert_eq!(&verified.recv().data()[1..3], b"ec".as_slice());
+}
+
+#[test]
+/// Tests that plaintext hash commitments are checked by the Notary and can be opened without the
+/// encodings of the transcript
+fn test_plaintext_hash_commitment() {
+    let data_sent = "sent data".as_bytes();
+    let data_recv = "received data".as_bytes();
+
+    let mut commitment_builder = TranscriptCommitmentBuilder::new(
+        fixtures::encoding_provider(data_sent, data_recv),
+        data_sent.len(),
+        data_recv.len(),
+    )
+    .with_transcripts(
+        Transcript::new(data_sent.to_vec()),
+        Transcript::new(data_recv.to_vec()),
+    );
+
+    let blake3_id = commitment_builder.commit_sent(0..4).unwrap();
+    let hash_id = commitment_builder.commit_recv_hash(0..8).unwrap();
+
+    let commitments = commitment_builder.build().unwrap();
+
+    // The Notary learns the digests of the plaintext hash commitments from the MPC and checks
+    // that they are included in the Merkle tree
+    let plaintext_hashes = PlaintextHashes::new(&commitments);
+    plaintext_hashes
+        .validate(
+            data_sent.len(),
+            data_recv.len(),
+            data_sent.len() + data_recv.len(),
+        )
+        .unwrap();
+    let digests = commitments
+        .plaintext_hashes()
+        .into_iter()
+        .map(|(.., digest)| *digest)
+        .collect::<Vec<_>>();
+    plaintext_hashes
+        .verify(&commitments.merkle_root(), &digests)
+        .unwrap();
+    assert!(plaintext_hashes
+        .verify(&commitments.merkle_root(), &[[0u8; 32]])
+        .is_err());
+    assert_eq!(plaintext_hashes.ids(), vec![hash_id]);
+
+    let session_data = SessionData::new(
+        ServerName::Dns("tlsnotary.org".to_string()),
+        fixtures::handshake_data().hash_commit().0,
+        Transcript::new(data_sent.to_vec()),
+        Transcript::new(data_recv.to_vec()),
+        commitments,
+    );
+
+    let header = fixtures::session_header(
+        session_data.commitments().merkle_root(),
+        data_sent.len(),
+        data_recv.len(),
+    );
+
+    let build_proof = || {
+        let mut builder = session_data.build_substrings_proof();
+        builder.reveal(blake3_id).unwrap().reveal(hash_id).unwrap();
+        builder.build().unwrap()
+    };
+
+    // The Verifier rejects plaintext hashes which the Notary did not check
+    assert!(matches!(
+        build_proof().verify(&header),
+        Err(SubstringsProofError::UnprovenPlaintextHash(id)) if id == hash_id
+    ));
+
+    let header = header.with_plaintext_hash_ids(plaintext_hashes.ids());
+    let (sent, recv) = build_proof().verify(&header).unwrap();
+
+    assert_eq!(&sent.data()[0..4], b"sent".as_slice());
+    assert_eq!(&recv.data()[0..8], b"received".as_slice());
+}
+
+#[cfg(feature = "poseidon")]
+#[test]
+/// Tests that Poseidon commitments are linked to the digests computed by the Notary and can be
+/// opened without the encodings of the transcript
+fn test_poseidon_commitment() {
+    use tlsn_core::{
+        commitment::poseidon::{setup_poseidon_keys, PoseidonLinkError},
+        msg::PlaintextHashesError,
+    };
+
+    let data_sent = "sent data".as_bytes();
+    let data_recv = "received data".as_bytes();
+
+    let mut commitment_builder = TranscriptCommitmentBuilder::new(
+        fixtures::encoding_provider(data_sent, data_recv),
+        data_sent.len(),
+        data_recv.len(),
+    )
+    .with_transcripts(
+        Transcript::new(data_sent.to_vec()),
+        Transcript::new(data_recv.to_vec()),
+    );
+
+    let hash_id = commitment_builder.commit_sent_hash(0..4).unwrap();
+    let poseidon_id = commitment_builder.commit_recv_poseidon(0..8).unwrap();
+
+    let session_data = SessionData::new(
+        ServerName::Dns("tlsnotary.org".to_string()),
+        fixtures::handshake_data().hash_commit().0,
+        Transcript::new(data_sent.to_vec()),
+        Transcript::new(data_recv.to_vec()),
+        commitment_builder.build().unwrap(),
+    );
+    let commitments = session_data.commitments();
+
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+    let (proving_key, verifying_key) = setup_poseidon_keys(8, &mut rng).unwrap();
+
+    // The links are required
+    let mut plaintext_hashes = PlaintextHashes::new(commitments);
+    assert!(matches!(
+        plaintext_hashes.validate(data_sent.len(), data_recv.len(), 64),
+        Err(PlaintextHashesError::PoseidonLinkMismatch)
+    ));
+
+    plaintext_hashes.poseidon_links = session_data.build_poseidon_links(&[proving_key]).unwrap();
+    plaintext_hashes
+        .validate(data_sent.len(), data_recv.len(), 64)
+        .unwrap();
+
+    // The Notary computes the SHA-256 digests of both commitments in MPC
+    let digests = commitments
+        .plaintext_hashes()
+        .into_iter()
+        .map(|(.., digest)| *digest)
+        .collect::<Vec<_>>();
+    plaintext_hashes
+        .verify_with_poseidon_keys(
+            &commitments.merkle_root(),
+            &digests,
+            &[verifying_key.clone()],
+        )
+        .unwrap();
+
+    // A link must be verified with a key
+    assert!(matches!(
+        plaintext_hashes.verify(&commitments.merkle_root(), &digests),
+        Err(PlaintextHashesError::InvalidCommitmentKind(id)) if id == poseidon_id
+    ));
+
+    // The link does not hold for another SHA-256 digest
+    let mut wrong_digests = digests.clone();
+    wrong_digests[1] = [0u8; 32];
+    assert!(matches!(
+        plaintext_hashes.verify_with_poseidon_keys(
+            &commitments.merkle_root(),
+            &wrong_digests,
+            &[verifying_key],
+        ),
+        Err(PlaintextHashesError::InvalidPoseidonLink(
+            id,
+            PoseidonLinkError::InvalidProof
+        )) if id == poseidon_id
+    ));
+    assert_eq!(plaintext_hashes.ids(), vec![hash_id, poseidon_id]);
+
+    let header =
+        fixtures::session_header(commitments.merkle_root(), data_sent.len(), data_recv.len());
+
+    let build_proof = || {
+        let mut builder = session_data.build_substrings_proof();
+        builder.reveal(poseidon_id).unwrap();
+        builder.build().unwrap()
+    };
+
+    // The Verifier rejects Poseidon commitments which the Notary did not check
+    assert!(matches!(
+        build_proof().verify(&header),
+        Err(SubstringsProofError::UnprovenPlaintextHash(id)) if id == poseidon_id
+    ));
+
+    let header = header.with_plaintext_hash_ids(plaintext_hashes.ids());
+    let (_, recv) = build_proof().verify(&header).unwrap();
+
+    assert_eq!(&recv.data()[0..8], b"received".as_slice());
 }
@@ -0,0 +1,309 @@
+//! Golden-file tests of the versioned binary format.
+//!
+//! The files in `tests/golden` were encoded by this crate and must keep decoding. Their names end
+//! with the format version they were encoded with, and a missing file fails the tests. The files
+//! of the current version are only written if the `TLSN_UPDATE_GOLDEN` environment variable is
+//! set, which must only be done together with a new format version. See `tests/golden/README.md`.
+
+use std::{fs, path::PathBuf};
+
+use p256::{
+    ecdsa::{signature::Signer, Signature as P256Signature},
+    PublicKey,
+};
+
+use mpz_core::commit::HashCommit;
+
+use tlsn_core::{
+    commitment::{CommitmentKind, TranscriptCommitmentBuilder},
+    fixtures,
+    format::{FormatError, FormatHeader, ObjectKind, SignatureAlgorithm, FORMAT_VERSION},
+    merkle::HashAlgorithm,
+    proof::{SessionProof, SubstringsProof, SubstringsProofError, TlsProof},
+    Direction, HandshakeSummary, NotarizedSession, ServerName, SessionData, SessionHeader,
+    Transcript,
+};
+
+const DATA_SENT: &[u8] = b"GET / HTTP/1.1\r\nHost: tlsnotary.org\r\n\r\n";
+const DATA_RECV: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nhi";
+
+fn notarized_session() -> NotarizedSession {
+    notarized_session_with_merkle_hash(HashAlgorithm::Sha256)
+}
+
+fn notarized_session_with_merkle_hash(merkle_hash: HashAlgorithm) -> NotarizedSession {
+    let testdata = fixtures::cert::tlsnotary();
+
+    let mut commitment_builder = TranscriptCommitmentBuilder::new(
+        fixtures::encoding_provider(DATA_SENT, DATA_RECV),
+        DATA_SENT.len(),
+        DATA_RECV.len(),
+    )
+    .with_transcripts(
+        Transcript::new(DATA_SENT.to_vec()),
+        Transcript::new(DATA_RECV.to_vec()),
+    );
+
+    commitment_builder.set_merkle_hash(merkle_hash);
+    commitment_builder.commit_sent(0..14).unwrap();
+    commitment_builder.commit_recv(0..15).unwrap();
+    let hash_id = commitment_builder
+        .commit_recv_hash(DATA_RECV.len() - 2..DATA_RECV.len())
+        .unwrap();
+    let commitments = commitment_builder.build().unwrap();
+
+    let (hs_decommitment, hs_commitment) = fixtures::handshake_data().hash_commit();
+
+    let header = SessionHeader::new(
+        fixtures::encoder_seed(),
+        commitments.merkle_root(),
+        DATA_SENT.len(),
+        DATA_RECV.len(),
+        HandshakeSummary::new(
+            testdata.time + 60,
+            fixtures::server_ephemeral_key(),
+            hs_commitment,
+        ),
+    )
+    .with_plaintext_hash_ids(vec![hash_id])
+    .with_merkle_hash(merkle_hash);
+
+    let signature: P256Signature = fixtures::notary_signing_key().sign(&header.to_bytes());
+
+    let data = SessionData::new(
+        ServerName::Dns(testdata.dns_name),
+        hs_decommitment,
+        Transcript::new(DATA_SENT.to_vec()),
+        Transcript::new(DATA_RECV.to_vec()),
+        commitments,
+    );
+
+    NotarizedSession::new(header, Some(signature.into()), data)
+}
+
+fn substrings_proof(session: &NotarizedSession) -> SubstringsProof {
+    let commitments = session.data().commitments();
+    let ids = [
+        (CommitmentKind::Blake3, 0..14, Direction::Sent),
+        (CommitmentKind::Blake3, 0..15, Direction::Received),
+        (
+            CommitmentKind::Sha256,
+            DATA_RECV.len() - 2..DATA_RECV.len(),
+            Direction::Received,
+        ),
+    ]
+    .map(|(kind, range, direction)| {
+        commitments
+            .get_id_by_info(kind, range.into(), direction)
+            .unwrap()
+    });
+
+    let mut builder = session.data().build_substrings_proof();
+    for id in ids {
+        builder.reveal(id).unwrap();
+    }
+    builder.build().unwrap()
+}
+
+fn notary_pubkey() -> PublicKey {
+    PublicKey::from(*fixtures::notary_signing_key().verifying_key())
+}
+
+/// Returns the golden file `name` of the current format version, writing it with `encode` if the
+/// golden files are being updated.
+fn golden(name: &str, encode: impl FnOnce() -> Vec<u8>) -> Vec<u8> {
+    let path = golden_path(name);
+
+    if std::env::var_os("TLSN_UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, encode()).unwrap();
+    }
+
+    fs::read(&path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read golden file {}: {e}, see tests/golden/README.md",
+            path.display()
+        )
+    })
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.bin"))
+}
+
+fn check_header(bytes: &[u8], kind: ObjectKind, signature_algorithm: Option<SignatureAlgorithm>) {
+    let header = FormatHeader::decode(bytes).unwrap();
+    assert!(header.version() <= FORMAT_VERSION);
+    assert_eq!(header.kind(), kind);
+    assert_eq!(header.signature_algorithm(), signature_algorithm);
+}
+
+fn check_notarized_session(bytes: &[u8]) -> NotarizedSession {
+    check_header(
+        bytes,
+        ObjectKind::NotarizedSession,
+        Some(SignatureAlgorithm::P256),
+    );
+
+    let session = NotarizedSession::from_bytes(bytes).unwrap();
+
+    assert_eq!(session.data().sent_transcript().data().as_ref(), DATA_SENT);
+    assert_eq!(session.data().recv_transcript().data().as_ref(), DATA_RECV);
+    session
+        .session_proof()
+        .verify_with_default_cert_verifier(notary_pubkey())
+        .unwrap();
+
+    let (sent, recv) = substrings_proof(&session).verify(session.header()).unwrap();
+    assert_eq!(&sent.data()[..14], &DATA_SENT[..14]);
+    assert_eq!(&recv.data()[..15], &DATA_RECV[..15]);
+    assert_eq!(&recv.data()[DATA_RECV.len() - 2..], b"hi");
+
+    session
+}
+
+fn check_session_proof(bytes: &[u8]) -> SessionProof {
+    check_header(
+        bytes,
+        ObjectKind::SessionProof,
+        Some(SignatureAlgorithm::P256),
+    );
+
+    let proof = SessionProof::from_bytes(bytes).unwrap();
+    proof
+        .verify_with_default_cert_verifier(notary_pubkey())
+        .unwrap();
+
+    proof
+}
+
+fn check_tls_proof(bytes: &[u8]) {
+    check_header(bytes, ObjectKind::TlsProof, Some(SignatureAlgorithm::P256));
+
+    let TlsProof {
+        session,
+        substrings,
+    } = TlsProof::from_bytes(bytes).unwrap();
+
+    session
+        .verify_with_default_cert_verifier(notary_pubkey())
+        .unwrap();
+
+    let substrings_bytes = substrings.to_bytes();
+    check_header(&substrings_bytes, ObjectKind::SubstringsProof, None);
+    let substrings = SubstringsProof::from_bytes(&substrings_bytes).unwrap();
+
+    let (sent, recv) = substrings.verify(&session.header).unwrap();
+    assert_eq!(&sent.data()[..14], &DATA_SENT[..14]);
+    assert_eq!(&recv.data()[..15], &DATA_RECV[..15]);
+    assert_eq!(&recv.data()[DATA_RECV.len() - 2..], b"hi");
+}
+
+#[test]
+fn test_golden_notarized_session() {
+    let bytes = golden("notarized_session_v1", || notarized_session().to_bytes());
+    assert_eq!(
+        FormatHeader::decode(&bytes)
+            .unwrap()
+            .merkle_hash_algorithm(),
+        Some(HashAlgorithm::Sha256)
+    );
+
+    let session = check_notarized_session(&bytes);
+    // The encoding is canonical
+    assert_eq!(session.to_bytes(), bytes);
+}
+
+#[test]
+fn test_golden_session_proof() {
+    let bytes = golden("session_proof_v1", || {
+        notarized_session().session_proof().to_bytes()
+    });
+    assert_eq!(
+        FormatHeader::decode(&bytes)
+            .unwrap()
+            .merkle_hash_algorithm(),
+        None
+    );
+
+    let proof = check_session_proof(&bytes);
+    assert_eq!(proof.to_bytes(), bytes);
+}
+
+#[test]
+fn test_golden_tls_proof() {
+    let bytes = golden("tls_proof_v1", || {
+        let session = notarized_session();
+        TlsProof {
+            session: session.session_proof(),
+            substrings: substrings_proof(&session),
+        }
+        .to_bytes()
+    });
+
+    check_tls_proof(&bytes);
+}
+
+#[test]
+fn test_merkle_hash() {
+    let session = notarized_session_with_merkle_hash(HashAlgorithm::Blake3);
+    let bytes = session.to_bytes();
+    assert_eq!(
+        FormatHeader::decode(&bytes)
+            .unwrap()
+            .merkle_hash_algorithm(),
+        Some(HashAlgorithm::Blake3)
+    );
+
+    let session = check_notarized_session(&bytes);
+    let proof_bytes = substrings_proof(&session).to_bytes();
+    assert_eq!(
+        FormatHeader::decode(&proof_bytes)
+            .unwrap()
+            .merkle_hash_algorithm(),
+        Some(HashAlgorithm::Blake3)
+    );
+}
+
+#[test]
+fn test_merkle_hash_mismatch() {
+    let session = notarized_session_with_merkle_hash(HashAlgorithm::Blake3);
+    let header = session
+        .header()
+        .clone()
+        .with_merkle_hash(HashAlgorithm::Sha256);
+
+    assert!(matches!(
+        substrings_proof(&session).verify(&header),
+        Err(SubstringsProofError::MerkleHashMismatch(
+            HashAlgorithm::Blake3,
+            HashAlgorithm::Sha256
+        ))
+    ));
+}
+
+#[test]
+fn test_decode_wrong_kind() {
+    let bytes = notarized_session().session_proof().to_bytes();
+
+    assert!(matches!(
+        NotarizedSession::from_bytes(&bytes),
+        Err(FormatError::UnexpectedKind {
+            expected: ObjectKind::NotarizedSession,
+            found: ObjectKind::SessionProof,
+        })
+    ));
+}
+
+#[test]
+fn test_decode_pre_format_encoding() {
+    // Releases before the format stored objects as their bare bincode encoding
+    let bytes = bincode::serialize(&notarized_session()).unwrap();
+
+    assert!(matches!(
+        NotarizedSession::from_bytes(&bytes),
+        Err(FormatError::InvalidMagic)
+    ));
+}
@@ -2,6 +2,8 @@ use mpz_ot::{chou_orlandi, kos};
 use mpz_share_conversion::{ReceiverConfig, SenderConfig};
 use tls_client::RootCertStore;
 use tls_mpc::{MpcTlsCommonConfig, MpcTlsLeaderConfig};
+#[cfg(feature = "poseidon")]
+use tlsn_core::commitment::poseidon::PoseidonProvingKey;
 
 const DEFAULT_MAX_TRANSCRIPT_SIZE: usize = 1 << 14; // 16Kb
 
@@ -22,6 +24,12 @@ pub struct ProverConfig {
     /// This includes the number of bytes sent and received to the server.
     #[builder(default = "DEFAULT_MAX_TRANSCRIPT_SIZE")]
     max_transcript_size: usize,
+    /// Proving keys for the links of Poseidon commitments
+    ///
+    /// A key is needed for the length of each Poseidon commitment.
+    #[cfg(feature = "poseidon")]
+    #[builder(default)]
+    poseidon_proving_keys: Vec<PoseidonProvingKey>,
 }
 
 impl ProverConfig {
@@ -40,6 +48,12 @@ impl ProverConfig {
         &self.server_dns
     }
 
+    /// Returns the proving keys for the links of Poseidon commitments.
+    #[cfg(feature = "poseidon")]
+    pub fn poseidon_proving_keys(&self) -> &[PoseidonProvingKey] {
+        &self.poseidon_proving_keys
+    }
+
     pub(crate) fn build_mpc_tls_config(&self) -> MpcTlsLeaderConfig {
         MpcTlsLeaderConfig::builder()
             .common(
@@ -88,9 +102,33 @@ impl ProverConfig {
         ReceiverConfig::builder().id("p256/1").build().unwrap()
     }
 
+    pub(crate) fn build_p384_sender_config(&self) -> SenderConfig {
+        SenderConfig::builder().id("p384/0").build().unwrap()
+    }
+
+    pub(crate) fn build_p384_receiver_config(&self) -> ReceiverConfig {
+        ReceiverConfig::builder().id("p384/1").build().unwrap()
+    }
+
+    pub(crate) fn build_x25519_sender_config(&self) -> SenderConfig {
+        SenderConfig::builder().id("x25519/0").build().unwrap()
+    }
+
+    pub(crate) fn build_x25519_receiver_config(&self) -> ReceiverConfig {
+        ReceiverConfig::builder().id("x25519/1").build().unwrap()
+    }
+
     pub(crate) fn build_gf2_config(&self) -> SenderConfig {
         SenderConfig::builder().id("gf2").record().build().unwrap()
     }
+
+    pub(crate) fn build_p1305_config(&self) -> SenderConfig {
+        SenderConfig::builder()
+            .id("p1305")
+            .record()
+            .build()
+            .unwrap()
+    }
 }
 
 /// Default root store using mozilla certs.
@@ -54,6 +54,18 @@ impl From<mpz_garble::MemoryError> for ProverError {
     }
 }
 
+impl From<mpz_garble::ExecutionError> for ProverError {
+    fn from(e: mpz_garble::ExecutionError) -> Self {
+        Self::MpcError(Box::new(e))
+    }
+}
+
+impl From<mpz_garble::DecodeError> for ProverError {
+    fn from(e: mpz_garble::DecodeError) -> Self {
+        Self::MpcError(Box::new(e))
+    }
+}
+
 impl From<mpz_garble::ProveError> for ProverError {
     fn from(e: mpz_garble::ProveError) -> Self {
         Self::MpcError(Box::new(e))
@@ -29,13 +29,20 @@ use mpz_ot::{
     chou_orlandi, kos,
 };
 use mpz_share_conversion as ff;
+use point_addition as pa;
 use rand::Rng;
 use state::{Notarize, Prove};
 use std::sync::Arc;
-use tls_client::{ClientConnection, ServerName as TlsServerName};
+use tls_client::{
+    kx_group::{SECP256R1, SECP384R1, X25519},
+    ClientConnection, ServerName as TlsServerName,
+};
 use tls_client_async::{bind_client, ClosedConnection, TlsConnection};
-use tls_mpc::{setup_components, LeaderCtrl, MpcTlsLeader, TlsRole};
+use tls_mpc::{
+    setup_components, LeaderCtrl, MpcTlsLeader, ShareConversions, TlsRole, MPC_KX_GROUPS,
+};
 use tlsn_core::transcript::Transcript;
+use tlsn_universal_hash::poly1305::P1305;
 use utils_aio::mux::MuxChannel;
 
 #[cfg(feature = "formats")]
@@ -83,7 +90,7 @@ impl Prover<state::Initialized> {
         };
 
         let mpc_setup_fut = setup_mpc_backend(&self.config, mux_ctrl.clone());
-        let (mpc_tls, vm, _, gf2, ot_fut) = futures::select! {
+        let (mpc_tls, vm, _, gf2, p1305, ot_fut) = futures::select! {
             res = mpc_setup_fut.fuse() => res?,
             _ = (&mut mux_fut).fuse() => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?,
         };
@@ -97,6 +104,7 @@ impl Prover<state::Initialized> {
                 vm,
                 ot_fut,
                 gf2,
+                p1305,
             },
         })
     }
@@ -126,13 +134,27 @@ impl Prover<state::Setup> {
             vm,
             mut ot_fut,
             gf2,
+            p1305,
         } = self.state;
 
+        // Only offer the cipher suites and key exchange groups which both parties can run in MPC.
+        // P-384 is only supported in TLS 1.2, the leader rejects it if TLS 1.3 is negotiated.
+        let cipher_suites = mpc_tls.cipher_suites().to_vec();
+        let kx_groups: Vec<_> = MPC_KX_GROUPS
+            .iter()
+            .filter_map(|group| {
+                [&X25519, &SECP256R1, &SECP384R1]
+                    .into_iter()
+                    .find(|kx_group| kx_group.name == *group)
+            })
+            .collect();
         let (mpc_ctrl, mpc_fut) = mpc_tls.run();
 
         let server_name = TlsServerName::try_from(self.config.server_dns())?;
         let config = tls_client::ClientConfig::builder()
-            .with_safe_defaults()
+            .with_cipher_suites(&cipher_suites)
+            .with_kx_groups(&kx_groups)
+            .with_safe_default_protocol_versions()?
             .with_root_certificates(self.config.root_cert_store.clone())
             .with_no_client_auth();
         let client =
@@ -169,6 +191,7 @@ impl Prover<state::Setup> {
                         vm,
                         ot_fut,
                         gf2,
+                        p1305,
                         start_time,
                         handshake_decommitment: mpc_tls_data
                             .handshake_decommitment
@@ -246,6 +269,7 @@ async fn setup_mpc_backend(
         DEAPVm<SharedSender, SharedReceiver>,
         SharedReceiver,
         ff::ConverterSender<ff::Gf2_128, SharedSender>,
+        ff::ConverterSender<P1305, SharedSender>,
         OTFuture,
     ),
     ProverError,
@@ -323,34 +347,74 @@ async fn setup_mpc_backend(
     let p256_recv =
         ff::ConverterReceiver::<ff::P256, _>::new(p256_receiver_config, ot_recv.clone(), channel);
 
+    let p384_sender_config = config.build_p384_sender_config();
+    let channel = mux.get_channel(p384_sender_config.id()).await?;
+    let p384_send =
+        ff::ConverterSender::<pa::P384, _>::new(p384_sender_config, ot_send.clone(), channel);
+
+    let p384_receiver_config = config.build_p384_receiver_config();
+    let channel = mux.get_channel(p384_receiver_config.id()).await?;
+    let p384_recv =
+        ff::ConverterReceiver::<pa::P384, _>::new(p384_receiver_config, ot_recv.clone(), channel);
+
+    let x25519_sender_config = config.build_x25519_sender_config();
+    let channel = mux.get_channel(x25519_sender_config.id()).await?;
+    let x25519_send = ff::ConverterSender::<pa::Curve25519, _>::new(
+        x25519_sender_config,
+        ot_send.clone(),
+        channel,
+    );
+
+    let x25519_receiver_config = config.build_x25519_receiver_config();
+    let channel = mux.get_channel(x25519_receiver_config.id()).await?;
+    let x25519_recv = ff::ConverterReceiver::<pa::Curve25519, _>::new(
+        x25519_receiver_config,
+        ot_recv.clone(),
+        channel,
+    );
+
     let gf2_config = config.build_gf2_config();
     let channel = mux.get_channel(gf2_config.id()).await?;
     let gf2 = ff::ConverterSender::<ff::Gf2_128, _>::new(gf2_config, ot_send.clone(), channel);
 
+    let p1305_config = config.build_p1305_config();
+    let channel = mux.get_channel(p1305_config.id()).await?;
+    let p1305 = ff::ConverterSender::<P1305, _>::new(p1305_config, ot_send.clone(), channel);
+
     let mpc_tls_config = config.build_mpc_tls_config();
 
-    let (ke, prf, encrypter, decrypter) = setup_components(
+    let components = setup_components(
         mpc_tls_config.common(),
         TlsRole::Leader,
         &mut mux,
         &mut vm,
-        p256_send,
-        p256_recv,
-        gf2.handle()
-            .map_err(|e| ProverError::MpcError(Box::new(e)))?,
+        ShareConversions {
+            p256_send,
+            p256_recv,
+            p384_send,
+            p384_recv,
+            x25519_send,
+            x25519_recv,
+            gf2: gf2
+                .handle()
+                .map_err(|e| ProverError::MpcError(Box::new(e)))?,
+            p1305: p1305
+                .handle()
+                .map_err(|e| ProverError::MpcError(Box::new(e)))?,
+        },
     )
     .await
     .map_err(|e| ProverError::MpcError(Box::new(e)))?;
 
     let channel = mux.get_channel(mpc_tls_config.common().id()).await?;
-    let mut mpc_tls = MpcTlsLeader::new(mpc_tls_config, channel, ke, prf, encrypter, decrypter);
+    let mut mpc_tls = MpcTlsLeader::new(mpc_tls_config, channel, components);
 
     mpc_tls.setup().await?;
 
     #[cfg(feature = "tracing")]
     debug!("MPC backend setup complete");
 
-    Ok((mpc_tls, vm, ot_recv, gf2, ot_fut))
+    Ok((mpc_tls, vm, ot_recv, gf2, p1305, ot_fut))
 }
 
 /// A controller for the prover.
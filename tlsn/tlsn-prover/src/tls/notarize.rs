@@ -6,10 +6,14 @@ use crate::tls::error::OTShutdownError;
 
 use super::{ff::ShareConversionReveal, state::Notarize, Prover, ProverError};
 use futures::{FutureExt, SinkExt, StreamExt};
+use mpz_garble::{Decode, Execute, Memory, Vm};
 use tlsn_core::{
-    commitment::TranscriptCommitmentBuilder,
-    msg::{SignedSessionHeader, TlsnMessage},
-    transcript::Transcript,
+    commitment::{
+        plaintext::{build_plaintext_hash_circuit, PLAINTEXT_HASH_SALT_LEN},
+        TranscriptCommitmentBuilder,
+    },
+    msg::{PlaintextHashes, SignedSessionHeader, TlsnMessage, TranscriptCommitmentRoot},
+    transcript::{get_value_ids, Transcript},
     NotarizedSession, ServerName, SessionData,
 };
 #[cfg(feature = "tracing")]
@@ -41,6 +45,7 @@ impl Prover<Notarize> {
             mut vm,
             mut ot_fut,
             mut gf2,
+            mut p1305,
             start_time,
             handshake_decommitment,
             server_public_key,
@@ -60,14 +65,82 @@ impl Prover<Notarize> {
         );
 
         let merkle_root = session_data.commitments().merkle_root();
+        let merkle_hash = session_data.commitments().merkle_tree().algorithm();
+
+        #[allow(unused_mut)]
+        let mut plaintext_hashes = PlaintextHashes::new(session_data.commitments());
+        #[cfg(feature = "poseidon")]
+        {
+            plaintext_hashes.poseidon_links = session_data
+                .build_poseidon_links(self.config.poseidon_proving_keys())
+                .map_err(|e| ProverError::NotarizationError(e.to_string()))?;
+        }
+        let plaintext_hash_ids = plaintext_hashes.ids();
+        let hash_commitments = session_data
+            .commitments()
+            .plaintext_hashes()
+            .into_iter()
+            .map(|(_, info, salt, digest)| (info.clone(), *salt, *digest))
+            .collect::<Vec<_>>();
 
         let mut notarize_fut = Box::pin(async move {
             let mut channel = mux_ctrl.get_channel("notarize").await?;
 
             channel
-                .send(TlsnMessage::TranscriptCommitmentRoot(merkle_root))
+                .send(TlsnMessage::TranscriptCommitmentRoot(
+                    TranscriptCommitmentRoot {
+                        root: merkle_root,
+                        algorithm: merkle_hash,
+                    },
+                ))
                 .await?;
 
+            channel
+                .send(TlsnMessage::PlaintextHashes(plaintext_hashes))
+                .await?;
+
+            // Prove to the notary that the plaintext hashes were computed over the authenticated
+            // plaintext, the salts remain private
+            if !hash_commitments.is_empty() {
+                let mut thread = vm.new_thread("plaintext-hash").await?;
+
+                for (idx, (info, salt, digest)) in hash_commitments.into_iter().enumerate() {
+                    let mut inputs = get_value_ids(info.ranges(), *info.direction())
+                        .map(|id| {
+                            thread
+                                .get_value(id.as_str())
+                                .expect("Byte should be in VM memory")
+                        })
+                        .collect::<Vec<_>>();
+                    let circ = build_plaintext_hash_circuit(inputs.len());
+
+                    let salt_ref = thread.new_private_input::<[u8; PLAINTEXT_HASH_SALT_LEN]>(
+                        &format!("plaintext-hash/{idx}/salt"),
+                    )?;
+                    let digest_ref =
+                        thread.new_output::<[u8; 32]>(&format!("plaintext-hash/{idx}/digest"))?;
+
+                    thread.assign(&salt_ref, salt)?;
+                    inputs.push(salt_ref);
+
+                    thread.execute(circ, &inputs, &[digest_ref.clone()]).await?;
+
+                    let mut outputs = thread.decode(&[digest_ref]).await?;
+                    let decoded: [u8; 32] = outputs
+                        .pop()
+                        .expect("digest should be present")
+                        .try_into()
+                        .expect("digest should be 32 bytes");
+
+                    if decoded != digest {
+                        return Err(ProverError::NotarizationError(
+                            "plaintext hash computed in MPC does not match the commitment"
+                                .to_string(),
+                        ));
+                    }
+                }
+            }
+
             let notary_encoder_seed = vm
                 .finalize()
                 .await
@@ -80,6 +153,10 @@ impl Prover<Notarize> {
             gf2.reveal()
                 .await
                 .map_err(|e| ProverError::MpcError(Box::new(e)))?;
+            p1305
+                .reveal()
+                .await
+                .map_err(|e| ProverError::MpcError(Box::new(e)))?;
 
             let signed_header = expect_msg_or_err!(channel, TlsnMessage::SignedSessionHeader)?;
 
@@ -110,6 +187,18 @@ impl Prover<Notarize> {
                 )
             })?;
 
+        if header.merkle_hash() != merkle_hash {
+            return Err(ProverError::NotarizationError(
+                "notary signed another Merkle tree hash algorithm".to_string(),
+            ));
+        }
+
+        if header.plaintext_hash_ids() != plaintext_hash_ids.as_slice() {
+            return Err(ProverError::NotarizationError(
+                "notary did not sign the proven plaintext hashes".to_string(),
+            ));
+        }
+
         Ok(NotarizedSession::new(header, Some(signature), session_data))
     }
 }
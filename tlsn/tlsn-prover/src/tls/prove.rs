@@ -159,6 +159,7 @@ impl Prover<ProveState> {
             mut vm,
             mut ot_fut,
             mut gf2,
+            mut p1305,
             handshake_decommitment,
             ..
         } = self.state;
@@ -184,6 +185,10 @@ impl Prover<ProveState> {
             gf2.reveal()
                 .await
                 .map_err(|e| ProverError::MpcError(Box::new(e)))?;
+            p1305
+                .reveal()
+                .await
+                .map_err(|e| ProverError::MpcError(Box::new(e)))?;
 
             // Send session_info to the verifier
             channel.send(TlsnMessage::SessionInfo(session_info)).await?;
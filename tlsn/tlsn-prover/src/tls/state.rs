@@ -15,6 +15,7 @@ use tlsn_core::{
     msg::{ProvingInfo, TlsnMessage},
     Transcript,
 };
+use tlsn_universal_hash::poly1305::P1305;
 use utils_aio::duplex::Duplex;
 
 /// Entry state
@@ -32,6 +33,7 @@ pub struct Setup {
     pub(crate) vm: DEAPVm<SharedSender, SharedReceiver>,
     pub(crate) ot_fut: OTFuture,
     pub(crate) gf2: ConverterSender<Gf2_128, SharedSender>,
+    pub(crate) p1305: ConverterSender<P1305, SharedSender>,
 }
 
 opaque_debug::implement!(Setup);
@@ -44,6 +46,7 @@ pub struct Closed {
     pub(crate) vm: DEAPVm<SharedSender, SharedReceiver>,
     pub(crate) ot_fut: OTFuture,
     pub(crate) gf2: ConverterSender<Gf2_128, SharedSender>,
+    pub(crate) p1305: ConverterSender<P1305, SharedSender>,
 
     pub(crate) start_time: u64,
     pub(crate) handshake_decommitment: Decommitment<HandshakeData>,
@@ -64,6 +67,7 @@ pub struct Notarize {
     pub(crate) vm: DEAPVm<SharedSender, SharedReceiver>,
     pub(crate) ot_fut: OTFuture,
     pub(crate) gf2: ConverterSender<Gf2_128, SharedSender>,
+    pub(crate) p1305: ConverterSender<P1305, SharedSender>,
 
     pub(crate) start_time: u64,
     pub(crate) handshake_decommitment: Decommitment<HandshakeData>,
@@ -89,7 +93,8 @@ impl From<Closed> for Notarize {
             encoding_provider,
             state.transcript_tx.data().len(),
             state.transcript_rx.data().len(),
-        );
+        )
+        .with_transcripts(state.transcript_tx.clone(), state.transcript_rx.clone());
 
         Self {
             mux_ctrl: state.mux_ctrl,
@@ -97,6 +102,7 @@ impl From<Closed> for Notarize {
             vm: state.vm,
             ot_fut: state.ot_fut,
             gf2: state.gf2,
+            p1305: state.p1305,
             start_time: state.start_time,
             handshake_decommitment: state.handshake_decommitment,
             server_public_key: state.server_public_key,
@@ -115,6 +121,7 @@ pub struct Prove {
     pub(crate) vm: DEAPVm<SharedSender, SharedReceiver>,
     pub(crate) ot_fut: OTFuture,
     pub(crate) gf2: ConverterSender<Gf2_128, SharedSender>,
+    pub(crate) p1305: ConverterSender<P1305, SharedSender>,
 
     pub(crate) handshake_decommitment: Decommitment<HandshakeData>,
 
@@ -134,6 +141,7 @@ impl From<Closed> for Prove {
             vm: state.vm,
             ot_fut: state.ot_fut,
             gf2: state.gf2,
+            p1305: state.p1305,
             handshake_decommitment: state.handshake_decommitment,
             transcript_tx: state.transcript_tx,
             transcript_rx: state.transcript_rx,
@@ -31,15 +31,42 @@ fn app() -> Router {
 
 /// Bind the server to the given socket.
 pub async fn bind<T: AsyncRead + AsyncWrite + Send + Unpin + 'static>(socket: T) {
-    let key = PrivateKey(SERVER_KEY_DER.to_vec());
-    let cert = Certificate(SERVER_CERT_DER.to_vec());
-
     let config = ServerConfig::builder()
         .with_safe_defaults()
         .with_no_client_auth()
-        .with_single_cert(vec![cert], key)
+        .with_single_cert(vec![cert()], key())
+        .unwrap();
+
+    serve(socket, config).await
+}
+
+/// Bind a server which only accepts the P-384 key exchange group, over TLS 1.2, to the given
+/// socket.
+pub async fn bind_p384<T: AsyncRead + AsyncWrite + Send + Unpin + 'static>(socket: T) {
+    let config = ServerConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_kx_groups(&[&rustls::kx_group::SECP384R1])
+        .with_protocol_versions(&[&rustls::version::TLS12])
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert()], key())
         .unwrap();
 
+    serve(socket, config).await
+}
+
+fn cert() -> Certificate {
+    Certificate(SERVER_CERT_DER.to_vec())
+}
+
+fn key() -> PrivateKey {
+    PrivateKey(SERVER_KEY_DER.to_vec())
+}
+
+async fn serve<T: AsyncRead + AsyncWrite + Send + Unpin + 'static>(
+    socket: T,
+    config: ServerConfig,
+) {
     let acceptor = TlsAcceptor::from(Arc::new(config));
 
     let conn = acceptor.accept(socket).await.unwrap();
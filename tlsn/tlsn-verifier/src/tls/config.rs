@@ -1,11 +1,21 @@
 use mpz_ot::{chou_orlandi, kos};
 use mpz_share_conversion::{ReceiverConfig, SenderConfig};
-use std::fmt::{Debug, Formatter, Result};
+use std::{
+    fmt::{Debug, Formatter, Result},
+    time::Duration,
+};
 use tls_core::verify::{ServerCertVerifier, WebPkiVerifier};
 use tls_mpc::{MpcTlsCommonConfig, MpcTlsFollowerConfig};
+#[cfg(feature = "poseidon")]
+use tlsn_core::commitment::poseidon::PoseidonVerifyingKey;
 use tlsn_core::proof::default_cert_verifier;
 
-const DEFAULT_MAX_TRANSCRIPT_SIZE: usize = 1 << 14; // 16Kb
+/// The maximum transcript size in bytes used if none is configured.
+pub const DEFAULT_MAX_TRANSCRIPT_SIZE: usize = 1 << 14; // 16Kb
+
+/// The maximum number of bytes hashed by the plaintext hash commitments of a session, as a
+/// multiple of the maximum transcript size.
+const PLAINTEXT_HASH_SIZE_FACTOR: usize = 2;
 
 /// Configuration for the [`Verifier`](crate::tls::Verifier)
 #[allow(missing_docs)]
@@ -26,6 +36,37 @@ pub struct VerifierConfig {
         default = "Some(default_cert_verifier())"
     )]
     cert_verifier: Option<WebPkiVerifier>,
+    /// ID of the key used to sign the session header
+    ///
+    /// If set, the ID is included in the signed session header.
+    #[builder(setter(into, strip_option), default)]
+    notary_key_id: Option<String>,
+    /// Time limit for the MPC setup with the prover
+    ///
+    /// If set, [`Verifier::setup`](crate::tls::Verifier::setup) fails with
+    /// [`VerifierError::SetupTimeout`](crate::tls::VerifierError::SetupTimeout) once it is exceeded.
+    #[builder(setter(strip_option), default)]
+    setup_timeout: Option<Duration>,
+    /// Time limit for the MPC-TLS connection
+    ///
+    /// If set, [`Verifier::run`](crate::tls::Verifier::run) fails with
+    /// [`VerifierError::MpcTlsTimeout`](crate::tls::VerifierError::MpcTlsTimeout) once it is
+    /// exceeded.
+    #[builder(setter(strip_option), default)]
+    mpc_tls_timeout: Option<Duration>,
+    /// Time limit for the finalization of the notarization
+    ///
+    /// If set, [`Verifier::finalize`](crate::tls::Verifier::finalize) fails with
+    /// [`VerifierError::FinalizeTimeout`](crate::tls::VerifierError::FinalizeTimeout) once it is
+    /// exceeded.
+    #[builder(setter(strip_option), default)]
+    finalize_timeout: Option<Duration>,
+    /// Verifying keys for the links of Poseidon commitments
+    ///
+    /// Poseidon commitments are rejected if there is no key for their length.
+    #[cfg(feature = "poseidon")]
+    #[builder(default)]
+    poseidon_verifying_keys: Vec<PoseidonVerifyingKey>,
 }
 
 impl Debug for VerifierConfig {
@@ -34,6 +75,10 @@ impl Debug for VerifierConfig {
             .field("id", &self.id)
             .field("max_transcript_size", &self.max_transcript_size)
             .field("cert_verifier", &"_")
+            .field("notary_key_id", &self.notary_key_id)
+            .field("setup_timeout", &self.setup_timeout)
+            .field("mpc_tls_timeout", &self.mpc_tls_timeout)
+            .field("finalize_timeout", &self.finalize_timeout)
             .finish()
     }
 }
@@ -54,6 +99,40 @@ impl VerifierConfig {
         self.max_transcript_size
     }
 
+    /// Get the maximum number of bytes which the plaintext hash commitments may hash in total.
+    ///
+    /// Each plaintext hash is computed in MPC, this bounds the work a prover can cause with
+    /// overlapping commitments.
+    pub fn max_plaintext_hash_size(&self) -> usize {
+        self.max_transcript_size * PLAINTEXT_HASH_SIZE_FACTOR
+    }
+
+    /// Returns the ID of the key used to sign the session header, if set.
+    pub fn notary_key_id(&self) -> Option<&str> {
+        self.notary_key_id.as_deref()
+    }
+
+    /// Returns the time limit for the MPC setup, if set.
+    pub fn setup_timeout(&self) -> Option<Duration> {
+        self.setup_timeout
+    }
+
+    /// Returns the time limit for the MPC-TLS connection, if set.
+    pub fn mpc_tls_timeout(&self) -> Option<Duration> {
+        self.mpc_tls_timeout
+    }
+
+    /// Returns the time limit for the finalization of the notarization, if set.
+    pub fn finalize_timeout(&self) -> Option<Duration> {
+        self.finalize_timeout
+    }
+
+    /// Returns the verifying keys for the links of Poseidon commitments.
+    #[cfg(feature = "poseidon")]
+    pub fn poseidon_verifying_keys(&self) -> &[PoseidonVerifyingKey] {
+        &self.poseidon_verifying_keys
+    }
+
     /// Get the certificate verifier.
     pub fn cert_verifier(&self) -> &impl ServerCertVerifier {
         self.cert_verifier
@@ -109,6 +188,22 @@ impl VerifierConfig {
         ReceiverConfig::builder().id("p256/0").build().unwrap()
     }
 
+    pub(crate) fn build_p384_sender_config(&self) -> SenderConfig {
+        SenderConfig::builder().id("p384/1").build().unwrap()
+    }
+
+    pub(crate) fn build_p384_receiver_config(&self) -> ReceiverConfig {
+        ReceiverConfig::builder().id("p384/0").build().unwrap()
+    }
+
+    pub(crate) fn build_x25519_sender_config(&self) -> SenderConfig {
+        SenderConfig::builder().id("x25519/1").build().unwrap()
+    }
+
+    pub(crate) fn build_x25519_receiver_config(&self) -> ReceiverConfig {
+        ReceiverConfig::builder().id("x25519/0").build().unwrap()
+    }
+
     pub(crate) fn build_gf2_config(&self) -> ReceiverConfig {
         ReceiverConfig::builder()
             .id("gf2")
@@ -116,4 +211,12 @@ impl VerifierConfig {
             .build()
             .unwrap()
     }
+
+    pub(crate) fn build_p1305_config(&self) -> ReceiverConfig {
+        ReceiverConfig::builder()
+            .id("p1305")
+            .record()
+            .build()
+            .unwrap()
+    }
 }
@@ -1,4 +1,4 @@
-use std::error::Error;
+use std::{error::Error, time::Duration};
 use tls_mpc::MpcTlsError;
 
 /// An error that can occur during TLS verification.
@@ -13,6 +13,14 @@ pub enum VerifierError {
     MpcError(Box<dyn Error + Send + 'static>),
     #[error("Range exceeds transcript length")]
     InvalidRange,
+    #[error(transparent)]
+    PlaintextHashError(#[from] tlsn_core::msg::PlaintextHashesError),
+    #[error("MPC setup did not complete within {0:?}")]
+    SetupTimeout(Duration),
+    #[error("MPC-TLS connection did not finish within {0:?}")]
+    MpcTlsTimeout(Duration),
+    #[error("Notarization did not complete within {0:?}")]
+    FinalizeTimeout(Duration),
 }
 
 impl From<MpcTlsError> for VerifierError {
@@ -51,6 +59,18 @@ impl From<mpz_garble::MemoryError> for VerifierError {
     }
 }
 
+impl From<mpz_garble::ExecutionError> for VerifierError {
+    fn from(e: mpz_garble::ExecutionError) -> Self {
+        Self::MpcError(Box::new(e))
+    }
+}
+
+impl From<mpz_garble::DecodeError> for VerifierError {
+    fn from(e: mpz_garble::DecodeError) -> Self {
+        Self::MpcError(Box::new(e))
+    }
+}
+
 impl From<tlsn_core::proof::SessionProofError> for VerifierError {
     fn from(e: tlsn_core::proof::SessionProofError) -> Self {
         Self::MpcError(Box::new(e))
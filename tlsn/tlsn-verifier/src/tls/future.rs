@@ -2,7 +2,8 @@
 
 use super::{OTSenderActor, VerifierError};
 use futures::{future::FusedFuture, Future};
-use std::pin::Pin;
+use futures_timer::Delay;
+use std::{pin::Pin, time::Duration};
 
 /// A future which must be polled for the muxer to make progress.
 pub(crate) struct MuxFuture {
@@ -48,3 +49,48 @@ impl FusedFuture for OTFuture {
         self.fut.is_terminated()
     }
 }
+
+/// A future which completes with the time limit of a step once it has elapsed, or never if the
+/// step has no time limit.
+pub(crate) struct TimeoutFuture {
+    delay: Option<(Duration, Delay)>,
+    terminated: bool,
+}
+
+impl TimeoutFuture {
+    pub(crate) fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            delay: timeout.map(|timeout| (timeout, Delay::new(timeout))),
+            terminated: false,
+        }
+    }
+}
+
+impl Future for TimeoutFuture {
+    type Output = Duration;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let Some((timeout, delay)) = self.delay.as_mut() else {
+            return std::task::Poll::Pending;
+        };
+        let timeout = *timeout;
+        let poll = Pin::new(delay).poll(cx);
+
+        match poll {
+            std::task::Poll::Ready(()) => {
+                self.terminated = true;
+                std::task::Poll::Ready(timeout)
+            }
+            std::task::Poll::Pending => std::task::Poll::Pending,
+        }
+    }
+}
+
+impl FusedFuture for TimeoutFuture {
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
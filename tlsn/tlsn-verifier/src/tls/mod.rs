@@ -7,13 +7,15 @@ mod notarize;
 pub mod state;
 mod verify;
 
-pub use config::{VerifierConfig, VerifierConfigBuilder, VerifierConfigBuilderError};
+pub use config::{
+    VerifierConfig, VerifierConfigBuilder, VerifierConfigBuilderError, DEFAULT_MAX_TRANSCRIPT_SIZE,
+};
 pub use error::VerifierError;
 
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::tls::future::OTFuture;
-use future::MuxFuture;
+use future::{MuxFuture, TimeoutFuture};
 use futures::{
     stream::{SplitSink, SplitStream},
     AsyncRead, AsyncWrite, FutureExt, StreamExt, TryFutureExt,
@@ -26,15 +28,17 @@ use mpz_ot::{
     chou_orlandi, kos,
 };
 use mpz_share_conversion as ff;
+use point_addition as pa;
 use rand::Rng;
 use signature::Signer;
 use state::{Notarize, Verify};
-use tls_mpc::{setup_components, MpcTlsFollower, MpcTlsFollowerData, TlsRole};
+use tls_mpc::{setup_components, MpcTlsFollower, MpcTlsFollowerData, ShareConversions, TlsRole};
 use tlsn_common::{
     mux::{attach_mux, MuxControl},
     Role,
 };
 use tlsn_core::{proof::SessionInfo, RedactedTranscript, SessionHeader, Signature};
+use tlsn_universal_hash::poly1305::P1305;
 use utils_aio::{duplex::Duplex, mux::MuxChannel};
 
 #[cfg(feature = "tracing")]
@@ -66,7 +70,8 @@ impl Verifier<state::Initialized> {
 
     /// Set up the verifier.
     ///
-    /// This performs all MPC setup.
+    /// This performs all MPC setup, which fails with [`VerifierError::SetupTimeout`] if it exceeds
+    /// the configured [setup timeout](VerifierConfig::setup_timeout).
     ///
     /// # Arguments
     ///
@@ -83,9 +88,11 @@ impl Verifier<state::Initialized> {
 
         let encoder_seed: [u8; 32] = rand::rngs::OsRng.gen();
         let mpc_setup_fut = setup_mpc_backend(&self.config, mux_ctrl.clone(), encoder_seed);
-        let (mpc_tls, vm, ot_send, ot_recv, gf2, ot_fut) = futures::select! {
+        let mut timeout_fut = TimeoutFuture::new(self.config.setup_timeout());
+        let (mpc_tls, vm, ot_send, ot_recv, gf2, p1305, ot_fut) = futures::select! {
             res = mpc_setup_fut.fuse() => res?,
             _ = &mut mux_fut => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?,
+            timeout = &mut timeout_fut => return Err(VerifierError::SetupTimeout(timeout)),
         };
 
         Ok(Verifier {
@@ -99,6 +106,7 @@ impl Verifier<state::Initialized> {
                 ot_recv,
                 ot_fut,
                 gf2,
+                p1305,
                 encoder_seed,
             },
         })
@@ -141,6 +149,9 @@ impl Verifier<state::Initialized> {
 
 impl Verifier<state::Setup> {
     /// Runs the verifier until the TLS connection is closed.
+    ///
+    /// Fails with [`VerifierError::MpcTlsTimeout`] if the connection lasts longer than the
+    /// configured [MPC-TLS timeout](VerifierConfig::mpc_tls_timeout).
     pub async fn run(self) -> Result<Verifier<state::Closed>, VerifierError> {
         let state::Setup {
             mux_ctrl,
@@ -151,6 +162,7 @@ impl Verifier<state::Setup> {
             ot_recv,
             mut ot_fut,
             gf2,
+            p1305,
             encoder_seed,
         } = self.state;
 
@@ -160,6 +172,7 @@ impl Verifier<state::Setup> {
             .as_secs();
 
         let (_, mpc_fut) = mpc_tls.run();
+        let mut timeout_fut = TimeoutFuture::new(self.config.mpc_tls_timeout());
 
         let MpcTlsFollowerData {
             handshake_commitment,
@@ -169,7 +182,8 @@ impl Verifier<state::Setup> {
         } = futures::select! {
             res = mpc_fut.fuse() => res?,
             _ = &mut mux_fut => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?,
-            res = ot_fut => return Err(res.map(|_| ()).expect_err("future will not return Ok here"))
+            res = ot_fut => return Err(res.map(|_| ()).expect_err("future will not return Ok here")),
+            timeout = &mut timeout_fut => return Err(VerifierError::MpcTlsTimeout(timeout)),
         };
 
         #[cfg(feature = "tracing")]
@@ -188,6 +202,7 @@ impl Verifier<state::Setup> {
                 ot_recv,
                 ot_fut,
                 gf2,
+                p1305,
                 encoder_seed,
                 start_time,
                 server_ephemeral_key,
@@ -237,6 +252,7 @@ async fn setup_mpc_backend(
         SharedSender,
         SharedReceiver,
         ff::ConverterReceiver<ff::Gf2_128, SharedReceiver>,
+        ff::ConverterReceiver<P1305, SharedReceiver>,
         OTFuture,
     ),
     VerifierError,
@@ -314,32 +330,72 @@ async fn setup_mpc_backend(
     let p256_recv =
         ff::ConverterReceiver::<ff::P256, _>::new(p256_receiver_config, ot_recv.clone(), channel);
 
+    let p384_sender_config = config.build_p384_sender_config();
+    let channel = mux_ctrl.get_channel(p384_sender_config.id()).await?;
+    let p384_send =
+        ff::ConverterSender::<pa::P384, _>::new(p384_sender_config, ot_send.clone(), channel);
+
+    let p384_receiver_config = config.build_p384_receiver_config();
+    let channel = mux_ctrl.get_channel(p384_receiver_config.id()).await?;
+    let p384_recv =
+        ff::ConverterReceiver::<pa::P384, _>::new(p384_receiver_config, ot_recv.clone(), channel);
+
+    let x25519_sender_config = config.build_x25519_sender_config();
+    let channel = mux_ctrl.get_channel(x25519_sender_config.id()).await?;
+    let x25519_send = ff::ConverterSender::<pa::Curve25519, _>::new(
+        x25519_sender_config,
+        ot_send.clone(),
+        channel,
+    );
+
+    let x25519_receiver_config = config.build_x25519_receiver_config();
+    let channel = mux_ctrl.get_channel(x25519_receiver_config.id()).await?;
+    let x25519_recv = ff::ConverterReceiver::<pa::Curve25519, _>::new(
+        x25519_receiver_config,
+        ot_recv.clone(),
+        channel,
+    );
+
     let gf2_config = config.build_gf2_config();
     let channel = mux_ctrl.get_channel(gf2_config.id()).await?;
     let gf2 = ff::ConverterReceiver::<ff::Gf2_128, _>::new(gf2_config, ot_recv.clone(), channel);
 
+    let p1305_config = config.build_p1305_config();
+    let channel = mux_ctrl.get_channel(p1305_config.id()).await?;
+    let p1305 = ff::ConverterReceiver::<P1305, _>::new(p1305_config, ot_recv.clone(), channel);
+
     let mpc_tls_config = config.build_mpc_tls_config();
 
-    let (ke, prf, encrypter, decrypter) = setup_components(
+    let components = setup_components(
         mpc_tls_config.common(),
         TlsRole::Follower,
         &mut mux_ctrl,
         &mut vm,
-        p256_send,
-        p256_recv,
-        gf2.handle()
-            .map_err(|e| VerifierError::MpcError(Box::new(e)))?,
+        ShareConversions {
+            p256_send,
+            p256_recv,
+            p384_send,
+            p384_recv,
+            x25519_send,
+            x25519_recv,
+            gf2: gf2
+                .handle()
+                .map_err(|e| VerifierError::MpcError(Box::new(e)))?,
+            p1305: p1305
+                .handle()
+                .map_err(|e| VerifierError::MpcError(Box::new(e)))?,
+        },
     )
     .await
     .map_err(|e| VerifierError::MpcError(Box::new(e)))?;
 
     let channel = mux_ctrl.get_channel(mpc_tls_config.common().id()).await?;
-    let mut mpc_tls = MpcTlsFollower::new(mpc_tls_config, channel, ke, prf, encrypter, decrypter);
+    let mut mpc_tls = MpcTlsFollower::new(mpc_tls_config, channel, components);
 
     mpc_tls.setup().await?;
 
     #[cfg(feature = "tracing")]
     debug!("MPC backend setup complete");
 
-    Ok((mpc_tls, vm, ot_send, ot_recv, gf2, ot_fut))
+    Ok((mpc_tls, vm, ot_send, ot_recv, gf2, p1305, ot_fut))
 }
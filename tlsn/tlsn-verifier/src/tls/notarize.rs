@@ -2,13 +2,16 @@
 //!
 //! The TLS verifier is only a notary.
 
-use super::{state::Notarize, Verifier, VerifierError};
+use super::{future::TimeoutFuture, state::Notarize, Verifier, VerifierError};
 use futures::{FutureExt, SinkExt, StreamExt, TryFutureExt};
 use mpz_core::serialize::CanonicalSerialize;
+use mpz_garble::{Decode, Execute, Memory, Vm};
 use mpz_share_conversion::ShareConversionVerify;
 use signature::Signer;
 use tlsn_core::{
-    msg::{SignedSessionHeader, TlsnMessage},
+    commitment::plaintext::{build_plaintext_hash_circuit, PLAINTEXT_HASH_SALT_LEN},
+    msg::{PlaintextHashesError, SignedSessionHeader, TlsnMessage, TranscriptCommitmentRoot},
+    transcript::get_value_ids,
     HandshakeSummary, SessionHeader, Signature,
 };
 use utils_aio::{expect_msg_or_err, mux::MuxChannel};
@@ -18,6 +21,13 @@ use tracing::info;
 
 impl Verifier<Notarize> {
     /// Notarizes the TLS session.
+    ///
+    /// The session header can be signed with any of the schemes supported by [`Signature`], e.g. a
+    /// `p256::ecdsa::SigningKey`, an `ed25519_dalek::SigningKey` or a
+    /// [`Secp256k1EthSigner`](tlsn_core::Secp256k1EthSigner).
+    ///
+    /// Fails with [`VerifierError::FinalizeTimeout`] if the notarization takes longer than the
+    /// configured [finalize timeout](crate::tls::VerifierConfig::finalize_timeout).
     pub async fn finalize<T>(self, signer: &impl Signer<T>) -> Result<SessionHeader, VerifierError>
     where
         T: Into<Signature>,
@@ -30,6 +40,7 @@ impl Verifier<Notarize> {
             ot_recv,
             ot_fut,
             mut gf2,
+            mut p1305,
             encoder_seed,
             start_time,
             server_ephemeral_key,
@@ -38,11 +49,79 @@ impl Verifier<Notarize> {
             recv_len,
         } = self.state;
 
+        let mut timeout_fut = TimeoutFuture::new(self.config.finalize_timeout());
+
         let notarize_fut = async {
             let mut notarize_channel = mux_ctrl.get_channel("notarize").await?;
 
-            let merkle_root =
-                expect_msg_or_err!(notarize_channel, TlsnMessage::TranscriptCommitmentRoot)?;
+            let TranscriptCommitmentRoot {
+                root: merkle_root,
+                algorithm: merkle_hash,
+            } = expect_msg_or_err!(notarize_channel, TlsnMessage::TranscriptCommitmentRoot)?;
+
+            let plaintext_hashes =
+                expect_msg_or_err!(notarize_channel, TlsnMessage::PlaintextHashes)?;
+            plaintext_hashes.validate(sent_len, recv_len, self.config.max_plaintext_hash_size())?;
+
+            // The inclusion proof must use the hash algorithm which is signed in the header
+            if plaintext_hashes
+                .inclusion_proof
+                .as_ref()
+                .is_some_and(|proof| proof.algorithm() != merkle_hash)
+            {
+                return Err(PlaintextHashesError::InvalidInclusionProof.into());
+            }
+
+            // Compute the plaintext hashes in MPC, blind to the salts of the prover, and check
+            // that they are included in the merkle tree of commitments
+            if !plaintext_hashes.commitments.is_empty() {
+                let mut thread = vm.new_thread("plaintext-hash").await?;
+
+                let mut digests = Vec::with_capacity(plaintext_hashes.commitments.len());
+                for (idx, (_, info)) in plaintext_hashes.commitments.iter().enumerate() {
+                    let mut inputs = get_value_ids(info.ranges(), *info.direction())
+                        .map(|id| {
+                            thread
+                                .get_value(id.as_str())
+                                .ok_or(VerifierError::InvalidRange)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let circ = build_plaintext_hash_circuit(inputs.len());
+
+                    let salt_ref = thread.new_blind_input::<[u8; PLAINTEXT_HASH_SALT_LEN]>(
+                        &format!("plaintext-hash/{idx}/salt"),
+                    )?;
+                    let digest_ref =
+                        thread.new_output::<[u8; 32]>(&format!("plaintext-hash/{idx}/digest"))?;
+
+                    inputs.push(salt_ref);
+
+                    thread.execute(circ, &inputs, &[digest_ref.clone()]).await?;
+
+                    let mut outputs = thread.decode(&[digest_ref]).await?;
+                    let digest: [u8; 32] = outputs
+                        .pop()
+                        .expect("digest should be present")
+                        .try_into()
+                        .expect("digest should be 32 bytes");
+                    digests.push(digest);
+                }
+
+                #[cfg(feature = "poseidon")]
+                plaintext_hashes.verify_with_poseidon_keys(
+                    &merkle_root,
+                    &digests,
+                    self.config.poseidon_verifying_keys(),
+                )?;
+                #[cfg(not(feature = "poseidon"))]
+                plaintext_hashes.verify(&merkle_root, &digests)?;
+
+                #[cfg(feature = "tracing")]
+                info!(
+                    "Verified {} plaintext hash commitments",
+                    plaintext_hashes.commitments.len()
+                );
+            }
 
             // Finalize all MPC before signing the session header
             let (mut ot_sender_actor, _, _) = futures::try_join!(
@@ -60,6 +139,10 @@ impl Verifier<Notarize> {
             gf2.verify()
                 .await
                 .map_err(|e| VerifierError::MpcError(Box::new(e)))?;
+            p1305
+                .verify()
+                .await
+                .map_err(|e| VerifierError::MpcError(Box::new(e)))?;
 
             #[cfg(feature = "tracing")]
             info!("Finalized all MPC");
@@ -67,13 +150,19 @@ impl Verifier<Notarize> {
             let handshake_summary =
                 HandshakeSummary::new(start_time, server_ephemeral_key, handshake_commitment);
 
-            let session_header = SessionHeader::new(
+            let mut session_header = SessionHeader::new(
                 encoder_seed,
                 merkle_root,
                 sent_len,
                 recv_len,
                 handshake_summary,
-            );
+            )
+            .with_plaintext_hash_ids(plaintext_hashes.ids())
+            .with_merkle_hash(merkle_hash);
+
+            if let Some(key_id) = self.config.notary_key_id() {
+                session_header = session_header.with_notary_key_id(key_id);
+            }
 
             let signature = signer.sign(&session_header.to_bytes());
 
@@ -96,11 +185,17 @@ impl Verifier<Notarize> {
         let session_header = futures::select! {
             res = notarize_fut.fuse() => res?,
             _ = &mut mux_fut => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?,
+            timeout = &mut timeout_fut => Err(VerifierError::FinalizeTimeout(timeout))?,
         };
 
         let mut mux_ctrl = mux_ctrl.into_inner();
 
-        futures::try_join!(mux_ctrl.close().map_err(VerifierError::from), mux_fut)?;
+        let close_fut =
+            futures::future::try_join(mux_ctrl.close().map_err(VerifierError::from), mux_fut);
+        futures::select! {
+            res = close_fut.fuse() => res?,
+            timeout = timeout_fut => Err(VerifierError::FinalizeTimeout(timeout))?,
+        };
 
         Ok(session_header)
     }
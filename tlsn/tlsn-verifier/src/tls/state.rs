@@ -8,6 +8,7 @@ use tls_core::key::PublicKey;
 use tls_mpc::MpcTlsFollower;
 use tlsn_common::mux::MuxControl;
 use tlsn_core::msg::TlsnMessage;
+use tlsn_universal_hash::poly1305::P1305;
 use utils_aio::duplex::Duplex;
 
 use crate::tls::future::{MuxFuture, OTFuture};
@@ -31,6 +32,7 @@ pub struct Setup {
     pub(crate) ot_recv: SharedReceiver,
     pub(crate) ot_fut: OTFuture,
     pub(crate) gf2: ConverterReceiver<Gf2_128, SharedReceiver>,
+    pub(crate) p1305: ConverterReceiver<P1305, SharedReceiver>,
 
     pub(crate) encoder_seed: [u8; 32],
 }
@@ -45,6 +47,7 @@ pub struct Closed {
     pub(crate) ot_recv: SharedReceiver,
     pub(crate) ot_fut: OTFuture,
     pub(crate) gf2: ConverterReceiver<Gf2_128, SharedReceiver>,
+    pub(crate) p1305: ConverterReceiver<P1305, SharedReceiver>,
 
     pub(crate) encoder_seed: [u8; 32],
     pub(crate) start_time: u64,
@@ -66,6 +69,7 @@ pub struct Notarize {
     pub(crate) ot_recv: SharedReceiver,
     pub(crate) ot_fut: OTFuture,
     pub(crate) gf2: ConverterReceiver<Gf2_128, SharedReceiver>,
+    pub(crate) p1305: ConverterReceiver<P1305, SharedReceiver>,
 
     pub(crate) encoder_seed: [u8; 32],
     pub(crate) start_time: u64,
@@ -87,6 +91,7 @@ impl From<Closed> for Notarize {
             ot_recv: value.ot_recv,
             ot_fut: value.ot_fut,
             gf2: value.gf2,
+            p1305: value.p1305,
             encoder_seed: value.encoder_seed,
             start_time: value.start_time,
             server_ephemeral_key: value.server_ephemeral_key,
@@ -107,6 +112,7 @@ pub struct Verify {
     pub(crate) ot_recv: SharedReceiver,
     pub(crate) ot_fut: OTFuture,
     pub(crate) gf2: ConverterReceiver<Gf2_128, SharedReceiver>,
+    pub(crate) p1305: ConverterReceiver<P1305, SharedReceiver>,
 
     pub(crate) start_time: u64,
     pub(crate) server_ephemeral_key: PublicKey,
@@ -130,6 +136,7 @@ impl From<Closed> for Verify {
             ot_recv: value.ot_recv,
             ot_fut: value.ot_fut,
             gf2: value.gf2,
+            p1305: value.p1305,
             start_time: value.start_time,
             server_ephemeral_key: value.server_ephemeral_key,
             handshake_commitment: value.handshake_commitment,
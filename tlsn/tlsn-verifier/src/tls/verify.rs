@@ -140,6 +140,7 @@ impl Verifier<VerifyState> {
             ot_recv,
             ot_fut,
             mut gf2,
+            mut p1305,
             start_time,
             server_ephemeral_key,
             handshake_commitment,
@@ -165,6 +166,10 @@ impl Verifier<VerifyState> {
             gf2.verify()
                 .await
                 .map_err(|e| VerifierError::MpcError(Box::new(e)))?;
+            p1305
+                .verify()
+                .await
+                .map_err(|e| VerifierError::MpcError(Box::new(e)))?;
 
             let session_info = expect_msg_or_err!(channel, TlsnMessage::SessionInfo)?;
 